@@ -2,8 +2,11 @@ use std::collections::{HashMap, HashSet};
 use std::time::Instant;
 
 use chrono::{NaiveDateTime, Timelike};
+use segment::data_types::composite_index::CompositeIndexType;
+use segment::data_types::numeric_index::NumericIndexType;
+use segment::data_types::sparse_vector::SparseVector;
 use segment::data_types::text_index::TextIndexType;
-use segment::data_types::vectors::VectorElementType;
+use segment::spaces::custom::CustomDistance;
 use segment::types::{PayloadSelector, WithPayloadInterface};
 use tonic::Status;
 use uuid::Uuid;
@@ -12,18 +15,22 @@ use crate::grpc::models::{CollectionsResponse, VersionInfo};
 use crate::grpc::qdrant::condition::ConditionOneOf;
 use crate::grpc::qdrant::payload_index_params::IndexParams;
 use crate::grpc::qdrant::point_id::PointIdOptions;
+use crate::grpc::qdrant::quantization_config::Quantization;
 use crate::grpc::qdrant::r#match::MatchValue;
 use crate::grpc::qdrant::value::Kind;
 use crate::grpc::qdrant::vectors::VectorsOptions;
 use crate::grpc::qdrant::with_payload_selector::SelectorOptions;
 use crate::grpc::qdrant::{
-    with_vectors_selector, CollectionDescription, CollectionOperationResponse, Condition, Distance,
-    FieldCondition, Filter, GeoBoundingBox, GeoPoint, GeoRadius, HasIdCondition, HealthCheckReply,
-    HnswConfigDiff, IsEmptyCondition, ListCollectionsResponse, ListValue, Match, NamedVectors,
-    PayloadExcludeSelector, PayloadIncludeSelector, PayloadIndexParams, PayloadSchemaInfo,
-    PayloadSchemaType, PointId, Range, ScoredPoint, SearchParams, Struct, TextIndexParams,
-    TokenizerType, Value, ValuesCount, Vector, Vectors, VectorsSelector, WithPayloadSelector,
-    WithVectorsSelector,
+    with_vectors_selector, BinaryQuantization, CollectionDescription, CollectionOperationResponse,
+    CompositeIndexParams, Condition, Distance, FieldCondition, Filter, FloatIndexParams,
+    GeoBoundingBox, GeoLineString, GeoPoint, GeoPolygon, GeoRadius, HasIdCondition,
+    HealthCheckReply, HnswConfigDiff, IntegerIndexParams, IsEmptyCondition, IsNullCondition,
+    IvfConfig, ListCollectionsResponse, ListValue, Match, MultiVectorComparator, MultiVectorConfig,
+    NamedVectors, PayloadExcludeSelector, PayloadIncludeSelector, PayloadIndexParams,
+    PayloadIndexStatus, PayloadSchemaInfo, PayloadSchemaType, PointId, QuantizationConfig,
+    QuantizationSearchParams, QuantizationType, Range, ScalarQuantization, ScoredPoint,
+    SearchParams, SparseIndices, StopwordsParams, Struct, TextIndexParams, TokenizerType, Value,
+    ValuesCount, Vector, Vectors, VectorsSelector, WithPayloadSelector, WithVectorsSelector,
 };
 
 pub fn payload_to_proto(payload: segment::types::Payload) -> HashMap<String, Value> {
@@ -137,6 +144,9 @@ impl From<segment::data_types::text_index::TokenizerType> for TokenizerType {
             segment::data_types::text_index::TokenizerType::Prefix => TokenizerType::Prefix,
             segment::data_types::text_index::TokenizerType::Whitespace => TokenizerType::Whitespace,
             segment::data_types::text_index::TokenizerType::Word => TokenizerType::Word,
+            segment::data_types::text_index::TokenizerType::Multilingual => {
+                TokenizerType::Multilingual
+            }
         }
     }
 }
@@ -150,6 +160,39 @@ impl From<segment::data_types::text_index::TextIndexParams> for PayloadIndexPara
                 lowercase: params.lowercase,
                 min_token_len: params.min_token_len.map(|x| x as u64),
                 max_token_len: params.max_token_len.map(|x| x as u64),
+                stemmer: params.stemmer.map(|language| language.to_string()),
+                stopwords: params.stopwords.map(|stopwords| StopwordsParams {
+                    language: stopwords.language.map(|language| language.to_string()),
+                    custom: stopwords.custom,
+                }),
+            })),
+        }
+    }
+}
+
+impl From<segment::data_types::numeric_index::NumericIndexParams> for PayloadIndexParams {
+    fn from(params: segment::data_types::numeric_index::NumericIndexParams) -> Self {
+        let index_params = match params.r#type {
+            NumericIndexType::Integer => IndexParams::IntegerIndexParams(IntegerIndexParams {
+                on_disk: params.on_disk,
+                is_principal: params.is_principal,
+            }),
+            NumericIndexType::Float => IndexParams::FloatIndexParams(FloatIndexParams {
+                on_disk: params.on_disk,
+                is_principal: params.is_principal,
+            }),
+        };
+        PayloadIndexParams {
+            index_params: Some(index_params),
+        }
+    }
+}
+
+impl From<segment::data_types::composite_index::CompositeIndexParams> for PayloadIndexParams {
+    fn from(params: segment::data_types::composite_index::CompositeIndexParams) -> Self {
+        PayloadIndexParams {
+            index_params: Some(IndexParams::CompositeIndexParams(CompositeIndexParams {
+                keys: params.keys,
             })),
         }
     }
@@ -164,13 +207,31 @@ impl From<segment::types::PayloadIndexInfo> for PayloadSchemaInfo {
                 segment::types::PayloadSchemaType::Float => PayloadSchemaType::Float,
                 segment::types::PayloadSchemaType::Geo => PayloadSchemaType::Geo,
                 segment::types::PayloadSchemaType::Text => PayloadSchemaType::Text,
+                segment::types::PayloadSchemaType::Composite => PayloadSchemaType::Composite,
             }
             .into(),
             params: schema.params.map(|params| match params {
                 segment::types::PayloadSchemaParams::Text(text_index_params) => {
                     text_index_params.into()
                 }
+                segment::types::PayloadSchemaParams::Numeric(numeric_index_params) => {
+                    numeric_index_params.into()
+                }
+                segment::types::PayloadSchemaParams::Composite(composite_index_params) => {
+                    composite_index_params.into()
+                }
             }),
+            status: Some(
+                match schema.status {
+                    segment::types::PayloadIndexStatus::Ready => {
+                        PayloadIndexStatus::PayloadIndexReady
+                    }
+                    segment::types::PayloadIndexStatus::Building => {
+                        PayloadIndexStatus::PayloadIndexBuilding
+                    }
+                }
+                .into(),
+            ),
         }
     }
 }
@@ -185,6 +246,9 @@ impl TryFrom<TokenizerType> for segment::data_types::text_index::TokenizerType {
                 Ok(segment::data_types::text_index::TokenizerType::Whitespace)
             }
             TokenizerType::Word => Ok(segment::data_types::text_index::TokenizerType::Word),
+            TokenizerType::Multilingual => {
+                Ok(segment::data_types::text_index::TokenizerType::Multilingual)
+            }
         }
     }
 }
@@ -200,6 +264,24 @@ impl TryFrom<TextIndexParams> for segment::data_types::text_index::TextIndexPara
             lowercase: params.lowercase,
             min_token_len: params.min_token_len.map(|x| x as usize),
             max_token_len: params.max_token_len.map(|x| x as usize),
+            stemmer: params
+                .stemmer
+                .map(|language| language.parse())
+                .transpose()
+                .map_err(|err: String| Status::invalid_argument(err))?,
+            stopwords: params
+                .stopwords
+                .map(|stopwords| {
+                    Ok::<_, Status>(segment::data_types::text_index::StopwordsParams {
+                        language: stopwords
+                            .language
+                            .map(|language| language.parse())
+                            .transpose()
+                            .map_err(|err: String| Status::invalid_argument(err))?,
+                        custom: stopwords.custom,
+                    })
+                })
+                .transpose()?,
         })
     }
 }
@@ -212,6 +294,13 @@ impl TryFrom<PayloadIndexParams> for segment::data_types::text_index::TextIndexP
             Some(IndexParams::TextIndexParams(text_index_params)) => {
                 Ok(text_index_params.try_into()?)
             }
+            Some(
+                IndexParams::IntegerIndexParams(_)
+                | IndexParams::FloatIndexParams(_)
+                | IndexParams::CompositeIndexParams(_),
+            ) => Err(Status::invalid_argument(
+                "expected parameters of text index",
+            )),
         }
     }
 }
@@ -224,6 +313,34 @@ impl TryFrom<IndexParams> for segment::types::PayloadSchemaParams {
             IndexParams::TextIndexParams(text_index_params) => Ok(
                 segment::types::PayloadSchemaParams::Text(text_index_params.try_into()?),
             ),
+            IndexParams::IntegerIndexParams(IntegerIndexParams {
+                on_disk,
+                is_principal,
+            }) => Ok(segment::types::PayloadSchemaParams::Numeric(
+                segment::data_types::numeric_index::NumericIndexParams {
+                    r#type: NumericIndexType::Integer,
+                    on_disk,
+                    is_principal,
+                },
+            )),
+            IndexParams::FloatIndexParams(FloatIndexParams {
+                on_disk,
+                is_principal,
+            }) => Ok(segment::types::PayloadSchemaParams::Numeric(
+                segment::data_types::numeric_index::NumericIndexParams {
+                    r#type: NumericIndexType::Float,
+                    on_disk,
+                    is_principal,
+                },
+            )),
+            IndexParams::CompositeIndexParams(CompositeIndexParams { keys }) => {
+                Ok(segment::types::PayloadSchemaParams::Composite(
+                    segment::data_types::composite_index::CompositeIndexParams {
+                        r#type: CompositeIndexType::Composite,
+                        keys,
+                    },
+                ))
+            }
         }
     }
 }
@@ -244,6 +361,7 @@ impl TryFrom<PayloadSchemaInfo> for segment::types::PayloadIndexInfo {
                 PayloadSchemaType::Float => segment::types::PayloadSchemaType::Float,
                 PayloadSchemaType::Geo => segment::types::PayloadSchemaType::Geo,
                 PayloadSchemaType::Text => segment::types::PayloadSchemaType::Text,
+                PayloadSchemaType::Composite => segment::types::PayloadSchemaType::Composite,
                 PayloadSchemaType::UnknownType => {
                     return Err(Status::invalid_argument(
                         "Malformed payload schema".to_string(),
@@ -259,7 +377,19 @@ impl TryFrom<PayloadSchemaInfo> for segment::types::PayloadIndexInfo {
             }) => Some(index_params.try_into()?),
         };
 
-        Ok(segment::types::PayloadIndexInfo { data_type, params })
+        // Index status is not reported by older versions, which don't track it
+        let status = match schema.status.and_then(PayloadIndexStatus::from_i32) {
+            Some(PayloadIndexStatus::PayloadIndexBuilding) => {
+                segment::types::PayloadIndexStatus::Building
+            }
+            _ => segment::types::PayloadIndexStatus::Ready,
+        };
+
+        Ok(segment::types::PayloadIndexInfo {
+            data_type,
+            params,
+            status,
+        })
     }
 }
 
@@ -327,6 +457,8 @@ impl From<SearchParams> for segment::types::SearchParams {
     fn from(params: SearchParams) -> Self {
         Self {
             hnsw_ef: params.hnsw_ef.map(|x| x as usize),
+            quantization: params.quantization.map(|quantization| quantization.into()),
+            indexed_only: params.indexed_only.unwrap_or_default(),
         }
     }
 }
@@ -335,10 +467,101 @@ impl From<segment::types::SearchParams> for SearchParams {
     fn from(params: segment::types::SearchParams) -> Self {
         Self {
             hnsw_ef: params.hnsw_ef.map(|x| x as u64),
+            quantization: params.quantization.map(|quantization| quantization.into()),
+            indexed_only: Some(params.indexed_only),
+        }
+    }
+}
+
+impl From<QuantizationSearchParams> for segment::types::QuantizationSearchParams {
+    fn from(params: QuantizationSearchParams) -> Self {
+        Self {
+            ignore: params.ignore.unwrap_or_default(),
+            rescore: params.rescore.unwrap_or_default(),
+            oversampling: params.oversampling,
         }
     }
 }
 
+impl From<segment::types::QuantizationSearchParams> for QuantizationSearchParams {
+    fn from(params: segment::types::QuantizationSearchParams) -> Self {
+        Self {
+            ignore: Some(params.ignore),
+            rescore: Some(params.rescore),
+            oversampling: params.oversampling,
+        }
+    }
+}
+
+impl From<segment::types::QuantizationConfig> for QuantizationConfig {
+    fn from(config: segment::types::QuantizationConfig) -> Self {
+        match config {
+            segment::types::QuantizationConfig::Scalar(scalar) => Self {
+                quantization: Some(Quantization::Scalar(ScalarQuantization {
+                    r#type: match scalar.r#type {
+                        segment::types::ScalarType::Int8 => QuantizationType::Int8,
+                    }
+                    .into(),
+                    quantile: scalar.quantile,
+                })),
+            },
+            segment::types::QuantizationConfig::Binary(_) => Self {
+                quantization: Some(Quantization::Binary(BinaryQuantization {})),
+            },
+        }
+    }
+}
+
+impl TryFrom<QuantizationConfig> for segment::types::QuantizationConfig {
+    type Error = Status;
+
+    fn try_from(config: QuantizationConfig) -> Result<Self, Self::Error> {
+        match config.quantization {
+            Some(Quantization::Scalar(scalar)) => {
+                let r#type = match QuantizationType::from_i32(scalar.r#type) {
+                    Some(QuantizationType::Int8) => segment::types::ScalarType::Int8,
+                    _ => return Err(Status::invalid_argument("Unknown quantization type")),
+                };
+                Ok(segment::types::QuantizationConfig::Scalar(
+                    segment::types::ScalarQuantizationConfig {
+                        r#type,
+                        quantile: scalar.quantile,
+                    },
+                ))
+            }
+            Some(Quantization::Binary(_)) => Ok(segment::types::QuantizationConfig::Binary(
+                segment::types::BinaryQuantizationConfig {},
+            )),
+            None => Err(Status::invalid_argument(
+                "Malformed QuantizationConfig type",
+            )),
+        }
+    }
+}
+
+impl From<segment::types::MultiVectorConfig> for MultiVectorConfig {
+    fn from(config: segment::types::MultiVectorConfig) -> Self {
+        Self {
+            comparator: match config.comparator {
+                segment::types::MultiVectorComparator::MaxSim => MultiVectorComparator::MaxSim,
+            }
+            .into(),
+        }
+    }
+}
+
+impl TryFrom<MultiVectorConfig> for segment::types::MultiVectorConfig {
+    type Error = Status;
+
+    fn try_from(config: MultiVectorConfig) -> Result<Self, Self::Error> {
+        let comparator = match MultiVectorComparator::from_i32(config.comparator) {
+            Some(MultiVectorComparator::MaxSim) => segment::types::MultiVectorComparator::MaxSim,
+            None => return Err(Status::invalid_argument("Unknown multivector comparator")),
+        };
+        Ok(segment::types::MultiVectorConfig { comparator })
+    }
+}
+
 impl From<segment::types::PointIdType> for PointId {
     fn from(point_id: segment::types::PointIdType) -> Self {
         PointId {
@@ -352,12 +575,44 @@ impl From<segment::types::PointIdType> for PointId {
 
 impl From<segment::data_types::vectors::VectorType> for Vector {
     fn from(vector: segment::data_types::vectors::VectorType) -> Self {
-        Self { data: vector }
+        Self {
+            data: vector,
+            indices: None,
+        }
+    }
+}
+
+impl From<segment::data_types::vectors::Vector> for Vector {
+    fn from(vector: segment::data_types::vectors::Vector) -> Self {
+        match vector {
+            segment::data_types::vectors::Vector::Dense(vector) => vector.into(),
+            segment::data_types::vectors::Vector::Sparse(vector) => Self {
+                data: vector.values,
+                indices: Some(SparseIndices {
+                    data: vector.indices,
+                }),
+            },
+        }
     }
 }
 
-impl From<HashMap<String, Vec<VectorElementType>>> for NamedVectors {
-    fn from(vectors: HashMap<String, Vec<VectorElementType>>) -> Self {
+impl TryFrom<Vector> for segment::data_types::vectors::Vector {
+    type Error = Status;
+
+    fn try_from(vector: Vector) -> Result<Self, Self::Error> {
+        match vector.indices {
+            None => Ok(segment::data_types::vectors::Vector::Dense(vector.data)),
+            Some(indices) => {
+                let sparse_vector = SparseVector::new(indices.data, vector.data)
+                    .map_err(Status::invalid_argument)?;
+                Ok(segment::data_types::vectors::Vector::Sparse(sparse_vector))
+            }
+        }
+    }
+}
+
+impl From<HashMap<String, segment::data_types::vectors::Vector>> for NamedVectors {
+    fn from(vectors: HashMap<String, segment::data_types::vectors::Vector>) -> Self {
         Self {
             vectors: vectors
                 .into_iter()
@@ -388,16 +643,19 @@ impl From<segment::types::ScoredPoint> for ScoredPoint {
             score: point.score,
             version: point.version,
             vectors: point.vector.map(|v| v.into()),
+            payload_truncated: point.payload_truncated,
         }
     }
 }
 
-impl From<NamedVectors> for HashMap<String, Vec<VectorElementType>> {
-    fn from(vectors: NamedVectors) -> Self {
+impl TryFrom<NamedVectors> for HashMap<String, segment::data_types::vectors::Vector> {
+    type Error = Status;
+
+    fn try_from(vectors: NamedVectors) -> Result<Self, Self::Error> {
         vectors
             .vectors
             .into_iter()
-            .map(|(name, vector)| (name, vector.data))
+            .map(|(name, vector)| Ok((name, vector.try_into()?)))
             .collect()
     }
 }
@@ -409,10 +667,15 @@ impl TryFrom<Vectors> for segment::data_types::vectors::VectorStruct {
         match vectors.vectors_options {
             Some(vectors_options) => Ok(match vectors_options {
                 VectorsOptions::Vector(vector) => {
+                    if vector.indices.is_some() {
+                        return Err(Status::invalid_argument(
+                            "Sparse vector must be named, use multiple vectors mode",
+                        ));
+                    }
                     segment::data_types::vectors::VectorStruct::Single(vector.data)
                 }
                 VectorsOptions::Vectors(vectors) => {
-                    segment::data_types::vectors::VectorStruct::Multi(vectors.into())
+                    segment::data_types::vectors::VectorStruct::Multi(vectors.try_into()?)
                 }
             }),
             None => Err(Status::invalid_argument("No Provided")),
@@ -438,6 +701,7 @@ impl TryFrom<ScoredPoint> for segment::types::ScoredPoint {
             score: point.score,
             vector,
             version: point.version,
+            payload_truncated: point.payload_truncated,
         })
     }
 }
@@ -555,6 +819,9 @@ impl TryFrom<Condition> for segment::types::Condition {
                 ConditionOneOf::IsEmpty(is_empty) => {
                     Ok(segment::types::Condition::IsEmpty(is_empty.into()))
                 }
+                ConditionOneOf::IsNull(is_null) => {
+                    Ok(segment::types::Condition::IsNull(is_null.into()))
+                }
             };
         }
         Err(Status::invalid_argument("Malformed Condition type"))
@@ -568,6 +835,7 @@ impl From<segment::types::Condition> for Condition {
             segment::types::Condition::IsEmpty(is_empty) => {
                 ConditionOneOf::IsEmpty(is_empty.into())
             }
+            segment::types::Condition::IsNull(is_null) => ConditionOneOf::IsNull(is_null.into()),
             segment::types::Condition::HasId(has_id) => ConditionOneOf::HasId(has_id.into()),
             segment::types::Condition::Filter(filter) => ConditionOneOf::Filter(filter.into()),
         };
@@ -594,6 +862,22 @@ impl From<segment::types::IsEmptyCondition> for IsEmptyCondition {
     }
 }
 
+impl From<IsNullCondition> for segment::types::IsNullCondition {
+    fn from(value: IsNullCondition) -> Self {
+        segment::types::IsNullCondition {
+            is_null: segment::types::PayloadField { key: value.key },
+        }
+    }
+}
+
+impl From<segment::types::IsNullCondition> for IsNullCondition {
+    fn from(value: segment::types::IsNullCondition) -> Self {
+        Self {
+            key: value.is_null.key,
+        }
+    }
+}
+
 impl TryFrom<HasIdCondition> for segment::types::HasIdCondition {
     type Error = Status;
 
@@ -625,17 +909,20 @@ impl TryFrom<FieldCondition> for segment::types::FieldCondition {
             geo_bounding_box,
             geo_radius,
             values_count,
+            geo_polygon,
         } = value;
 
         let geo_bounding_box =
             geo_bounding_box.map_or_else(|| Ok(None), |g| g.try_into().map(Some))?;
         let geo_radius = geo_radius.map_or_else(|| Ok(None), |g| g.try_into().map(Some))?;
+        let geo_polygon = geo_polygon.map_or_else(|| Ok(None), |g| g.try_into().map(Some))?;
         Ok(Self {
             key,
             r#match: r#match.map_or_else(|| Ok(None), |m| m.try_into().map(Some))?,
             range: range.map(|r| r.into()),
             geo_bounding_box,
             geo_radius,
+            geo_polygon,
             values_count: values_count.map(|r| r.into()),
         })
     }
@@ -649,11 +936,13 @@ impl From<segment::types::FieldCondition> for FieldCondition {
             range,
             geo_bounding_box,
             geo_radius,
+            geo_polygon,
             values_count,
         } = value;
 
         let geo_bounding_box = geo_bounding_box.map(|g| g.into());
         let geo_radius = geo_radius.map(|g| g.into());
+        let geo_polygon = geo_polygon.map(|g| g.into());
         Self {
             key,
             r#match: r#match.map(|m| m.into()),
@@ -661,6 +950,7 @@ impl From<segment::types::FieldCondition> for FieldCondition {
             geo_bounding_box,
             geo_radius,
             values_count: values_count.map(|r| r.into()),
+            geo_polygon,
         }
     }
 }
@@ -717,6 +1007,57 @@ impl From<segment::types::GeoRadius> for GeoRadius {
     }
 }
 
+impl From<GeoLineString> for segment::types::GeoLineString {
+    fn from(value: GeoLineString) -> Self {
+        Self {
+            points: value.points.into_iter().map(|p| p.into()).collect(),
+        }
+    }
+}
+
+impl From<segment::types::GeoLineString> for GeoLineString {
+    fn from(value: segment::types::GeoLineString) -> Self {
+        Self {
+            points: value.points.into_iter().map(|p| p.into()).collect(),
+        }
+    }
+}
+
+impl TryFrom<GeoPolygon> for segment::types::GeoPolygon {
+    type Error = Status;
+
+    fn try_from(value: GeoPolygon) -> Result<Self, Self::Error> {
+        match value {
+            GeoPolygon {
+                exterior: Some(e),
+                interiors,
+            } => Ok(Self {
+                exterior: e.into(),
+                interiors: if interiors.is_empty() {
+                    None
+                } else {
+                    Some(interiors.into_iter().map(|i| i.into()).collect())
+                },
+            }),
+            _ => Err(Status::invalid_argument("Malformed GeoPolygon type")),
+        }
+    }
+}
+
+impl From<segment::types::GeoPolygon> for GeoPolygon {
+    fn from(value: segment::types::GeoPolygon) -> Self {
+        Self {
+            exterior: Some(value.exterior.into()),
+            interiors: value
+                .interiors
+                .into_iter()
+                .flatten()
+                .map(|i| i.into())
+                .collect(),
+        }
+    }
+}
+
 impl From<GeoPoint> for segment::types::GeoPoint {
     fn from(value: GeoPoint) -> Self {
         Self {
@@ -780,6 +1121,7 @@ impl TryFrom<Match> for segment::types::Match {
                 MatchValue::Integer(int) => int.into(),
                 MatchValue::Boolean(flag) => flag.into(),
                 MatchValue::Text(text) => segment::types::Match::Text(text.into()),
+                MatchValue::Phrase(phrase) => segment::types::Match::Phrase(phrase.into()),
             }),
             _ => Err(Status::invalid_argument("Malformed Match condition")),
         }
@@ -797,6 +1139,9 @@ impl From<segment::types::Match> for Match {
             segment::types::Match::Text(segment::types::MatchText { text }) => {
                 MatchValue::Text(text)
             }
+            segment::types::Match::Phrase(segment::types::MatchPhrase { phrase }) => {
+                MatchValue::Phrase(phrase)
+            }
         };
         Self {
             match_value: Some(match_value),
@@ -811,6 +1156,30 @@ impl From<HnswConfigDiff> for segment::types::HnswConfig {
             ef_construct: hnsw_config.ef_construct.unwrap_or_default() as usize,
             full_scan_threshold: hnsw_config.full_scan_threshold.unwrap_or_default() as usize,
             max_indexing_threads: hnsw_config.max_indexing_threads.unwrap_or_default() as usize,
+            on_disk: hnsw_config.on_disk,
+            payload_m: hnsw_config.payload_m.map(|v| v as usize),
+            payload_block_threshold: hnsw_config.payload_block_threshold.map(|v| v as usize),
+        }
+    }
+}
+
+impl From<IvfConfig> for segment::types::IvfConfig {
+    fn from(ivf_config: IvfConfig) -> Self {
+        Self {
+            num_lists: ivf_config.num_lists.unwrap_or_default() as usize,
+            num_probes: ivf_config
+                .num_probes
+                .map(|v| v as usize)
+                .unwrap_or(segment::types::IvfConfig::default().num_probes),
+        }
+    }
+}
+
+impl From<segment::types::IvfConfig> for IvfConfig {
+    fn from(ivf_config: segment::types::IvfConfig) -> Self {
+        Self {
+            num_lists: Some(ivf_config.num_lists as u64),
+            num_probes: Some(ivf_config.num_probes as u64),
         }
     }
 }
@@ -835,15 +1204,33 @@ impl TryFrom<Distance> for segment::types::Distance {
             Distance::Cosine => segment::types::Distance::Cosine,
             Distance::Euclid => segment::types::Distance::Euclid,
             Distance::Dot => segment::types::Distance::Dot,
+            Distance::Custom => {
+                return Err(Status::invalid_argument(
+                    "Malformed distance parameter: Custom distance requires a name",
+                ))
+            }
         })
     }
 }
 
-pub fn from_grpc_dist(dist: i32) -> Result<segment::types::Distance, Status> {
+pub fn from_grpc_dist(
+    dist: i32,
+    custom_distance: Option<&str>,
+) -> Result<segment::types::Distance, Status> {
     match Distance::from_i32(dist) {
         None => Err(Status::invalid_argument(format!(
             "Malformed distance parameter, unexpected value: {dist}"
         ))),
+        Some(Distance::Custom) => {
+            let name = custom_distance.ok_or_else(|| {
+                Status::invalid_argument("custom_distance is required for Custom distance")
+            })?;
+            CustomDistance::get(name)
+                .map(segment::types::Distance::Custom)
+                .ok_or_else(|| {
+                    Status::invalid_argument(format!("Custom distance {name} is not registered"))
+                })
+        }
         Some(grpc_distance) => Ok(grpc_distance.try_into()?),
     }
 }
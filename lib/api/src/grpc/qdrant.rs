@@ -1,4 +1,31 @@
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScalarQuantization {
+    /// Type of quantized vector elements
+    #[prost(enumeration="QuantizationType", tag="1")]
+    pub r#type: i32,
+    /// Quantile of vector element values, which defines the quantization range
+    #[prost(float, optional, tag="2")]
+    pub quantile: ::core::option::Option<f32>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BinaryQuantization {
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QuantizationConfig {
+    #[prost(oneof="quantization_config::Quantization", tags="1, 2")]
+    pub quantization: ::core::option::Option<quantization_config::Quantization>,
+}
+/// Nested message and enum types in `QuantizationConfig`.
+pub mod quantization_config {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Quantization {
+        #[prost(message, tag="1")]
+        Scalar(super::ScalarQuantization),
+        #[prost(message, tag="2")]
+        Binary(super::BinaryQuantization),
+    }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct VectorParams {
     /// Size of the vectors
     #[prost(uint64, tag="1")]
@@ -6,6 +33,30 @@ pub struct VectorParams {
     /// Distance function used for comparing vectors
     #[prost(enumeration="Distance", tag="2")]
     pub distance: i32,
+    /// Quantization of vectors in indexed segments
+    #[prost(message, optional, tag="3")]
+    pub quantization_config: ::core::option::Option<QuantizationConfig>,
+    /// If true, vectors are stored on disk in optimized segments
+    #[prost(bool, optional, tag="4")]
+    pub on_disk: ::core::option::Option<bool>,
+    /// If set, points may store several sub-vectors, concatenated into a single vector
+    #[prost(message, optional, tag="5")]
+    pub multivector_config: ::core::option::Option<MultiVectorConfig>,
+    /// Custom params for HNSW index of this vector. If none - values from collection configuration are used
+    #[prost(message, optional, tag="6")]
+    pub hnsw_config: ::core::option::Option<HnswConfigDiff>,
+    /// If false, vectors with Cosine distance are stored without normalization. Default: true
+    #[prost(bool, optional, tag="7")]
+    pub normalize: ::core::option::Option<bool>,
+    /// Name of the distance function, registered on the server. Required for Custom distance
+    #[prost(string, optional, tag="8")]
+    pub custom_distance: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MultiVectorConfig {
+    /// How to compare multivectors
+    #[prost(enumeration="MultiVectorComparator", tag="1")]
+    pub comparator: i32,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct VectorParamsMap {
@@ -28,6 +79,14 @@ pub mod vectors_config {
     }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SparseVectorParams {
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SparseVectorConfig {
+    #[prost(map="string, message", tag="1")]
+    pub map: ::std::collections::HashMap<::prost::alloc::string::String, SparseVectorParams>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetCollectionInfoRequest {
     /// Name of the collection
     #[prost(string, tag="1")]
@@ -86,6 +145,45 @@ pub struct HnswConfigDiff {
     ///Number of parallel threads used for background index building. If 0 - auto selection.
     #[prost(uint64, optional, tag="4")]
     pub max_indexing_threads: ::core::option::Option<u64>,
+    ///
+    ///Store HNSW graph links in a memory-mapped file instead of RAM. Default: false
+    #[prost(bool, optional, tag="5")]
+    pub on_disk: ::core::option::Option<bool>,
+    ///
+    ///Number of edges per node in additional payload-aware graph links, used by filtered search.
+    ///If not set - `m` is used. If 0 - additional payload-aware links are not built.
+    #[prost(uint64, optional, tag="6")]
+    pub payload_m: ::core::option::Option<u64>,
+    ///
+    ///Minimal size (in KiloBytes) of vectors with the same payload value to build additional links for.
+    ///If not set - `full_scan_threshold` is used.
+    ///Note: 1Kb = 1 vector of size 256
+    #[prost(uint64, optional, tag="7")]
+    pub payload_block_threshold: ::core::option::Option<u64>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct IvfConfig {
+    ///
+    ///Number of lists (k-means centroids) to split vectors into.
+    ///If 0 - selected automatically as a square root of the number of vectors.
+    #[prost(uint64, optional, tag="1")]
+    pub num_lists: ::core::option::Option<u64>,
+    ///
+    ///Number of lists closest to the query to scan during search.
+    ///Larger the value - more accurate the search, more time required. Default: 8
+    #[prost(uint64, optional, tag="2")]
+    pub num_probes: ::core::option::Option<u64>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PlacementConstraints {
+    ///
+    ///Failure domain labels of peers, e.g. availability zone or rack.
+    ///Replicas of the same shard are never placed on peers with the same label.
+    #[prost(map="uint64, string", tag="1")]
+    pub failure_domains: ::std::collections::HashMap<u64, ::prost::alloc::string::String>,
+    /// Peers, which never host shards of the collection
+    #[prost(uint64, repeated, tag="2")]
+    pub excluded_peers: ::prost::alloc::vec::Vec<u64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WalConfigDiff {
@@ -97,6 +195,18 @@ pub struct WalConfigDiff {
     pub wal_segments_ahead: ::core::option::Option<u64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CollectionParamsDiff {
+    /// Number of replicas for each shard
+    #[prost(uint32, optional, tag="1")]
+    pub replication_factor: ::core::option::Option<u32>,
+    /// Number of active remote replicas, read in parallel if the local replica can't answer
+    #[prost(uint32, optional, tag="2")]
+    pub read_fan_out_factor: ::core::option::Option<u32>,
+    /// Number of active replicas, which have to answer a read
+    #[prost(enumeration="ReadConsistency", optional, tag="3")]
+    pub read_consistency: ::core::option::Option<i32>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OptimizersConfigDiff {
     ///
     ///The minimal fraction of deleted vectors in a segment, required to perform segment optimization
@@ -137,6 +247,7 @@ pub struct OptimizersConfigDiff {
     ///
     ///Maximum size (in KiloBytes) of vectors allowed for plain index.
     ///Default value based on <https://github.com/google-research/google-research/blob/master/scann/docs/algorithms.md>
+    ///To disable vector indexing and always use exact search, set to `0`.
     ///Note: 1Kb = 1 vector of size 256
     #[prost(uint64, optional, tag="6")]
     pub indexing_threshold: ::core::option::Option<u64>,
@@ -148,6 +259,15 @@ pub struct OptimizersConfigDiff {
     ///Max number of threads, which can be used for optimization. If 0 - `NUM_CPU - 1` will be used
     #[prost(uint64, optional, tag="8")]
     pub max_optimization_threads: ::core::option::Option<u64>,
+    ///
+    ///Do not merge segments, if the merged segment would take more than this size on disk (in KiloBytes).
+    ///Unlike `max_segment_size`, accounts for payload, indexes and all other data of the segments.
+    #[prost(uint64, optional, tag="9")]
+    pub max_segment_disk_size: ::core::option::Option<u64>,
+    ///
+    ///Strategy of selecting segments to merge, once there are more segments than `default_segment_number`
+    #[prost(enumeration="MergePolicy", optional, tag="10")]
+    pub merge_policy: ::core::option::Option<i32>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CreateCollection {
@@ -175,6 +295,33 @@ pub struct CreateCollection {
     /// Configuration for vectors
     #[prost(message, optional, tag="10")]
     pub vectors_config: ::core::option::Option<VectorsConfig>,
+    /// Limit total size of payloads in read responses
+    #[prost(uint64, optional, tag="11")]
+    pub max_response_payload_bytes: ::core::option::Option<u64>,
+    /// Transformations applied to payloads of upserted points and set payloads
+    #[prost(message, repeated, tag="12")]
+    pub payload_transformers: ::prost::alloc::vec::Vec<PayloadTransformer>,
+    /// Configuration for sparse vectors
+    #[prost(message, optional, tag="13")]
+    pub sparse_vectors_config: ::core::option::Option<SparseVectorConfig>,
+    /// Use IVF index instead of HNSW for this collection
+    #[prost(message, optional, tag="14")]
+    pub ivf_config: ::core::option::Option<IvfConfig>,
+    /// Constraints for placing shard replicas on peers
+    #[prost(message, optional, tag="15")]
+    pub placement: ::core::option::Option<PlacementConstraints>,
+    /// Number of active remote replicas, read in parallel if the local replica can't answer
+    #[prost(uint32, optional, tag="16")]
+    pub read_fan_out_factor: ::core::option::Option<u32>,
+    /// Scheme of distributing points across shards
+    #[prost(enumeration="HashRingType", optional, tag="17")]
+    pub hash_ring: ::core::option::Option<i32>,
+    /// Number of active replicas, which have to acknowledge an update
+    #[prost(enumeration="WriteConsistency", optional, tag="18")]
+    pub write_consistency: ::core::option::Option<i32>,
+    /// Number of active replicas, which have to answer a read
+    #[prost(enumeration="ReadConsistency", optional, tag="19")]
+    pub read_consistency: ::core::option::Option<i32>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateCollection {
@@ -187,6 +334,12 @@ pub struct UpdateCollection {
     /// Wait timeout for operation commit in seconds, if not specified - default value will be supplied
     #[prost(uint64, optional, tag="3")]
     pub timeout: ::core::option::Option<u64>,
+    /// New parameters of the collection
+    #[prost(message, optional, tag="4")]
+    pub params: ::core::option::Option<CollectionParamsDiff>,
+    /// If set - the update is rejected, if the config version differs
+    #[prost(uint64, optional, tag="5")]
+    pub expected_version: ::core::option::Option<u64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeleteCollection {
@@ -217,6 +370,42 @@ pub struct CollectionParams {
     /// Configuration for vectors
     #[prost(message, optional, tag="5")]
     pub vectors_config: ::core::option::Option<VectorsConfig>,
+    /// Limit total size of payloads in read responses
+    #[prost(uint64, optional, tag="6")]
+    pub max_response_payload_bytes: ::core::option::Option<u64>,
+    /// Transformations applied to payloads of upserted points and set payloads
+    #[prost(message, repeated, tag="7")]
+    pub payload_transformers: ::prost::alloc::vec::Vec<PayloadTransformer>,
+    /// Configuration for sparse vectors
+    #[prost(message, optional, tag="8")]
+    pub sparse_vectors_config: ::core::option::Option<SparseVectorConfig>,
+    /// If set - IVF index is used instead of HNSW
+    #[prost(message, optional, tag="9")]
+    pub ivf_config: ::core::option::Option<IvfConfig>,
+    /// Constraints for placing shard replicas on peers
+    #[prost(message, optional, tag="10")]
+    pub placement: ::core::option::Option<PlacementConstraints>,
+    /// Number of active remote replicas, read in parallel if the local replica can't answer
+    #[prost(uint32, optional, tag="11")]
+    pub read_fan_out_factor: ::core::option::Option<u32>,
+    /// Scheme of distributing points across shards
+    #[prost(enumeration="HashRingType", optional, tag="12")]
+    pub hash_ring: ::core::option::Option<i32>,
+    /// Number of active replicas, which have to acknowledge an update
+    #[prost(enumeration="WriteConsistency", optional, tag="13")]
+    pub write_consistency: ::core::option::Option<i32>,
+    /// Number of active replicas, which have to answer a read
+    #[prost(enumeration="ReadConsistency", optional, tag="14")]
+    pub read_consistency: ::core::option::Option<i32>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PayloadTransformer {
+    /// Payload field to transform
+    #[prost(string, tag="1")]
+    pub key: ::prost::alloc::string::String,
+    /// Transformation to apply
+    #[prost(enumeration="PayloadTransformerType", tag="2")]
+    pub transform: i32,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CollectionConfig {
@@ -232,6 +421,9 @@ pub struct CollectionConfig {
     /// Configuration of the Write-Ahead-Log
     #[prost(message, optional, tag="4")]
     pub wal_config: ::core::option::Option<WalConfigDiff>,
+    /// Incremented on every update of the config
+    #[prost(uint64, tag="5")]
+    pub version: u64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TextIndexParams {
@@ -247,10 +439,49 @@ pub struct TextIndexParams {
     /// Maximal token length
     #[prost(uint64, optional, tag="4")]
     pub max_token_len: ::core::option::Option<u64>,
+    /// Language of the stemmer, e.g. `english`
+    #[prost(string, optional, tag="5")]
+    pub stemmer: ::core::option::Option<::prost::alloc::string::String>,
+    /// Stopwords to exclude from tokens
+    #[prost(message, optional, tag="6")]
+    pub stopwords: ::core::option::Option<StopwordsParams>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StopwordsParams {
+    /// Language of the built-in stopword list
+    #[prost(string, optional, tag="1")]
+    pub language: ::core::option::Option<::prost::alloc::string::String>,
+    /// Additional stopwords
+    #[prost(string, repeated, tag="2")]
+    pub custom: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct IntegerIndexParams {
+    /// If true - store the range index on disk, the match index of integers is kept in RAM
+    #[prost(bool, optional, tag="1")]
+    pub on_disk: ::core::option::Option<bool>,
+    /// If true - use the field to keep segments compact in terms of its values
+    #[prost(bool, optional, tag="2")]
+    pub is_principal: ::core::option::Option<bool>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FloatIndexParams {
+    /// If true - store the range index on disk
+    #[prost(bool, optional, tag="1")]
+    pub on_disk: ::core::option::Option<bool>,
+    /// If true - use the field to keep segments compact in terms of its values
+    #[prost(bool, optional, tag="2")]
+    pub is_principal: ::core::option::Option<bool>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CompositeIndexParams {
+    /// Payload keys, combined by the index
+    #[prost(string, repeated, tag="1")]
+    pub keys: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PayloadIndexParams {
-    #[prost(oneof="payload_index_params::IndexParams", tags="1")]
+    #[prost(oneof="payload_index_params::IndexParams", tags="1, 2, 3, 4")]
     pub index_params: ::core::option::Option<payload_index_params::IndexParams>,
 }
 /// Nested message and enum types in `PayloadIndexParams`.
@@ -260,6 +491,15 @@ pub mod payload_index_params {
         /// Parameters for text index
         #[prost(message, tag="1")]
         TextIndexParams(super::TextIndexParams),
+        /// Parameters for integer index
+        #[prost(message, tag="2")]
+        IntegerIndexParams(super::IntegerIndexParams),
+        /// Parameters for float index
+        #[prost(message, tag="3")]
+        FloatIndexParams(super::FloatIndexParams),
+        /// Parameters for index over multiple payload keys
+        #[prost(message, tag="4")]
+        CompositeIndexParams(super::CompositeIndexParams),
     }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -270,6 +510,9 @@ pub struct PayloadSchemaInfo {
     /// Field index parameters
     #[prost(message, optional, tag="2")]
     pub params: ::core::option::Option<PayloadIndexParams>,
+    /// Build status of the index
+    #[prost(enumeration="PayloadIndexStatus", optional, tag="3")]
+    pub status: ::core::option::Option<i32>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CollectionInfo {
@@ -355,6 +598,7 @@ pub enum Distance {
     Cosine = 1,
     Euclid = 2,
     Dot = 3,
+    Custom = 4,
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -376,6 +620,27 @@ pub enum PayloadSchemaType {
     Float = 3,
     Geo = 4,
     Text = 5,
+    Composite = 6,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum PayloadIndexStatus {
+    UnknownPayloadIndexStatus = 0,
+    /// Index is built for all points
+    PayloadIndexReady = 1,
+    /// Index is not built in some segments yet
+    PayloadIndexBuilding = 2,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum PayloadTransformerType {
+    UnknownTransform = 0,
+    /// Convert string values to lowercase
+    Lowercase = 1,
+    /// Convert date strings into unix timestamps in seconds
+    ParseDatetime = 2,
+    /// Remove HTML tags from string values
+    StripHtml = 3,
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -384,6 +649,56 @@ pub enum TokenizerType {
     Prefix = 1,
     Whitespace = 2,
     Word = 3,
+    /// Unicode word segmentation, CJK texts are split into character bigrams
+    Multilingual = 4,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum QuantizationType {
+    UnknownQuantization = 0,
+    Int8 = 1,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum MultiVectorComparator {
+    /// Sum of maximal similarities of query sub-vectors
+    MaxSim = 0,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum MergePolicy {
+    /// Merge segments, neighbouring by principal payload values, if there are any. Otherwise merge the smallest segments
+    Auto = 0,
+    /// Always merge the smallest segments
+    Smallest = 1,
+    /// Merge segments of similar size, so that large segments are not rewritten by every merge
+    Tiered = 2,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum HashRingType {
+    /// Ring with a fixed number of virtual nodes per shard
+    Fair = 0,
+    /// Jump consistent hash, only moves points to new shards when shards are added
+    Jump = 1,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum WriteConsistency {
+    /// Update fails if any of the active replicas fails to apply it
+    AllReplicas = 0,
+    /// Update succeeds once the majority of active replicas applied it
+    MajorityReplicas = 1,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ReadConsistency {
+    /// Read from a single replica, the local one if possible
+    SingleReplica = 0,
+    /// Read from the majority of active replicas and reconcile the results
+    QuorumReplicas = 1,
+    /// Read from all active replicas and reconcile the results
+    AllActiveReplicas = 2,
 }
 /// Generated client implementations.
 pub mod collections_client {
@@ -1338,9 +1653,17 @@ pub mod point_id {
     }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SparseIndices {
+    #[prost(uint32, repeated, tag="1")]
+    pub data: ::prost::alloc::vec::Vec<u32>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Vector {
     #[prost(float, repeated, tag="1")]
     pub data: ::prost::alloc::vec::Vec<f32>,
+    /// If set - vector is sparse, `data` holds values of the dimensions with these indices
+    #[prost(message, optional, tag="2")]
+    pub indices: ::core::option::Option<SparseIndices>,
 }
 // ---------------------------------------------
 // ---------------- RPC Requests ---------------
@@ -1356,6 +1679,9 @@ pub struct UpsertPoints {
     pub wait: ::core::option::Option<bool>,
     #[prost(message, repeated, tag="3")]
     pub points: ::prost::alloc::vec::Vec<PointStruct>,
+    /// Restrict the operation to the selected shards
+    #[prost(message, optional, tag="4")]
+    pub shard_selector: ::core::option::Option<ShardSelector>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeletePoints {
@@ -1368,6 +1694,9 @@ pub struct DeletePoints {
     /// Affected points
     #[prost(message, optional, tag="3")]
     pub points: ::core::option::Option<PointsSelector>,
+    /// Restrict the operation to the selected shards
+    #[prost(message, optional, tag="4")]
+    pub shard_selector: ::core::option::Option<ShardSelector>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetPoints {
@@ -1383,6 +1712,9 @@ pub struct GetPoints {
     /// Options for specifying which vectors to include into response
     #[prost(message, optional, tag="5")]
     pub with_vectors: ::core::option::Option<WithVectorsSelector>,
+    /// Limit total size of payloads in the response
+    #[prost(uint64, optional, tag="6")]
+    pub max_payload_bytes: ::core::option::Option<u64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SetPayloadPoints {
@@ -1398,6 +1730,9 @@ pub struct SetPayloadPoints {
     /// List of point to modify
     #[prost(message, repeated, tag="4")]
     pub points: ::prost::alloc::vec::Vec<PointId>,
+    /// Restrict the operation to the selected shards
+    #[prost(message, optional, tag="5")]
+    pub shard_selector: ::core::option::Option<ShardSelector>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeletePayloadPoints {
@@ -1413,6 +1748,9 @@ pub struct DeletePayloadPoints {
     /// Affected points
     #[prost(message, repeated, tag="4")]
     pub points: ::prost::alloc::vec::Vec<PointId>,
+    /// Restrict the operation to the selected shards
+    #[prost(message, optional, tag="5")]
+    pub shard_selector: ::core::option::Option<ShardSelector>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ClearPayloadPoints {
@@ -1425,6 +1763,9 @@ pub struct ClearPayloadPoints {
     /// Affected points
     #[prost(message, optional, tag="3")]
     pub points: ::core::option::Option<PointsSelector>,
+    /// Restrict the operation to the selected shards
+    #[prost(message, optional, tag="4")]
+    pub shard_selector: ::core::option::Option<ShardSelector>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CreateFieldIndexCollection {
@@ -1443,6 +1784,9 @@ pub struct CreateFieldIndexCollection {
     /// Payload index params.
     #[prost(message, optional, tag="5")]
     pub field_index_params: ::core::option::Option<PayloadIndexParams>,
+    /// Drop and build the index again, if it already exists
+    #[prost(bool, optional, tag="6")]
+    pub rebuild: ::core::option::Option<bool>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeleteFieldIndexCollection {
@@ -1536,6 +1880,27 @@ pub struct SearchParams {
     ///Larger the value - more accurate the result, more time required for search.
     #[prost(uint64, optional, tag="1")]
     pub hnsw_ef: ::core::option::Option<u64>,
+    ///
+    ///Params relevant to quantized vectors
+    #[prost(message, optional, tag="2")]
+    pub quantization: ::core::option::Option<QuantizationSearchParams>,
+    ///
+    ///If true - search only in segments with built vector index, skip not yet indexed ones.
+    ///Gives predictable latency during massive upload, but recently inserted points may be missing.
+    #[prost(bool, optional, tag="3")]
+    pub indexed_only: ::core::option::Option<bool>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QuantizationSearchParams {
+    /// If true, quantized vectors are not used and search is performed on original vectors
+    #[prost(bool, optional, tag="1")]
+    pub ignore: ::core::option::Option<bool>,
+    /// If true, candidates found with quantized vectors are re-scored with original vectors
+    #[prost(bool, optional, tag="2")]
+    pub rescore: ::core::option::Option<bool>,
+    /// Number of candidates to re-score, relative to the limit. If not set, all `ef` candidates are re-scored
+    #[prost(double, optional, tag="3")]
+    pub oversampling: ::core::option::Option<f64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SearchPoints {
@@ -1569,6 +1934,15 @@ pub struct SearchPoints {
     /// Options for specifying which vectors to include into response
     #[prost(message, optional, tag="11")]
     pub with_vectors: ::core::option::Option<WithVectorsSelector>,
+    /// If set - search by sparse vector, `vector` holds values of the dimensions with these indices
+    #[prost(message, optional, tag="12")]
+    pub sparse_indices: ::core::option::Option<SparseIndices>,
+    /// Restrict the operation to the selected shards
+    #[prost(message, optional, tag="13")]
+    pub shard_selector: ::core::option::Option<ShardSelector>,
+    /// Limit total size of payloads in the response
+    #[prost(uint64, optional, tag="14")]
+    pub max_payload_bytes: ::core::option::Option<u64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SearchBatchPoints {
@@ -1577,6 +1951,9 @@ pub struct SearchBatchPoints {
     pub collection_name: ::prost::alloc::string::String,
     #[prost(message, repeated, tag="2")]
     pub search_points: ::prost::alloc::vec::Vec<SearchPoints>,
+    /// Restrict the operation to the selected shards
+    #[prost(message, optional, tag="3")]
+    pub shard_selector: ::core::option::Option<ShardSelector>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ScrollPoints {
@@ -1597,6 +1974,12 @@ pub struct ScrollPoints {
     /// Options for specifying which vectors to include into response
     #[prost(message, optional, tag="7")]
     pub with_vectors: ::core::option::Option<WithVectorsSelector>,
+    /// Limit total size of payloads in the response
+    #[prost(uint64, optional, tag="8")]
+    pub max_payload_bytes: ::core::option::Option<u64>,
+    /// Restrict the operation to the selected shards
+    #[prost(message, optional, tag="11")]
+    pub shard_selector: ::core::option::Option<ShardSelector>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RecommendPoints {
@@ -1633,6 +2016,9 @@ pub struct RecommendPoints {
     /// Options for specifying which vectors to include into response
     #[prost(message, optional, tag="12")]
     pub with_vectors: ::core::option::Option<WithVectorsSelector>,
+    /// Limit total size of payloads in the response
+    #[prost(uint64, optional, tag="13")]
+    pub max_payload_bytes: ::core::option::Option<u64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RecommendBatchPoints {
@@ -1653,6 +2039,27 @@ pub struct CountPoints {
     /// If `true` - return exact count, if `false` - return approximate count
     #[prost(bool, optional, tag="3")]
     pub exact: ::core::option::Option<bool>,
+    /// Restrict the operation to the selected shards
+    #[prost(message, optional, tag="4")]
+    pub shard_selector: ::core::option::Option<ShardSelector>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MovePoints {
+    /// Name of the collection to take points from
+    #[prost(string, tag="1")]
+    pub collection_name: ::prost::alloc::string::String,
+    /// Name of the collection to store points into, must have the same vectors configuration
+    #[prost(string, tag="2")]
+    pub target_collection: ::prost::alloc::string::String,
+    /// Transfer only points which satisfy this conditions. If not provided - all points
+    #[prost(message, optional, tag="3")]
+    pub filter: ::core::option::Option<Filter>,
+    /// Whether points are deleted from the source collection. Default: MovePoints
+    #[prost(enumeration="MovePointsMode", optional, tag="4")]
+    pub mode: ::core::option::Option<i32>,
+    /// Number of points transferred at once. Default: 100
+    #[prost(uint64, optional, tag="5")]
+    pub batch_size: ::core::option::Option<u64>,
 }
 // ---------------------------------------------
 // ---------------- RPC Response ---------------
@@ -1692,6 +2099,9 @@ pub struct ScoredPoint {
     /// Vectors to search
     #[prost(message, optional, tag="6")]
     pub vectors: ::core::option::Option<Vectors>,
+    /// True if some payload fields were omitted to fit the response size limit
+    #[prost(bool, tag="7")]
+    pub payload_truncated: bool,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SearchResponse {
@@ -1739,6 +2149,20 @@ pub struct CountResult {
     pub count: u64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MovePointsResponse {
+    #[prost(message, optional, tag="1")]
+    pub result: ::core::option::Option<MovePointsResult>,
+    /// Time spent to process
+    #[prost(double, tag="2")]
+    pub time: f64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MovePointsResult {
+    /// Number of points stored in the target collection
+    #[prost(uint64, tag="1")]
+    pub points_count: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RetrievedPoint {
     #[prost(message, optional, tag="1")]
     pub id: ::core::option::Option<PointId>,
@@ -1746,6 +2170,9 @@ pub struct RetrievedPoint {
     pub payload: ::std::collections::HashMap<::prost::alloc::string::String, Value>,
     #[prost(message, optional, tag="4")]
     pub vectors: ::core::option::Option<Vectors>,
+    /// True if some payload fields were omitted to fit the response size limit
+    #[prost(bool, tag="5")]
+    pub payload_truncated: bool,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetResponse {
@@ -1789,7 +2216,7 @@ pub struct Filter {
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Condition {
-    #[prost(oneof="condition::ConditionOneOf", tags="1, 2, 3, 4")]
+    #[prost(oneof="condition::ConditionOneOf", tags="1, 2, 3, 4, 5")]
     pub condition_one_of: ::core::option::Option<condition::ConditionOneOf>,
 }
 /// Nested message and enum types in `Condition`.
@@ -1804,6 +2231,8 @@ pub mod condition {
         HasId(super::HasIdCondition),
         #[prost(message, tag="4")]
         Filter(super::Filter),
+        #[prost(message, tag="5")]
+        IsNull(super::IsNullCondition),
     }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -1812,6 +2241,11 @@ pub struct IsEmptyCondition {
     pub key: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct IsNullCondition {
+    #[prost(string, tag="1")]
+    pub key: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct HasIdCondition {
     #[prost(message, repeated, tag="1")]
     pub has_id: ::prost::alloc::vec::Vec<PointId>,
@@ -1835,10 +2269,13 @@ pub struct FieldCondition {
     /// Check number of values for a specific field
     #[prost(message, optional, tag="6")]
     pub values_count: ::core::option::Option<ValuesCount>,
+    /// Check if geo point is within a given polygon
+    #[prost(message, optional, tag="7")]
+    pub geo_polygon: ::core::option::Option<GeoPolygon>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Match {
-    #[prost(oneof="r#match::MatchValue", tags="1, 2, 3, 4")]
+    #[prost(oneof="r#match::MatchValue", tags="1, 2, 3, 4, 5")]
     pub match_value: ::core::option::Option<r#match::MatchValue>,
 }
 /// Nested message and enum types in `Match`.
@@ -1857,6 +2294,9 @@ pub mod r#match {
         /// Match text
         #[prost(string, tag="4")]
         Text(::prost::alloc::string::String),
+        /// Match tokens of the phrase in a row
+        #[prost(string, tag="5")]
+        Phrase(::prost::alloc::string::String),
     }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -1889,6 +2329,21 @@ pub struct GeoRadius {
     pub radius: f32,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GeoLineString {
+    /// Ordered sequence of GeoPoints representing the line
+    #[prost(message, repeated, tag="1")]
+    pub points: ::prost::alloc::vec::Vec<GeoPoint>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GeoPolygon {
+    /// The exterior line bounds the surface
+    #[prost(message, optional, tag="1")]
+    pub exterior: ::core::option::Option<GeoLineString>,
+    /// Interior lines (if present) bound holes within the surface
+    #[prost(message, repeated, tag="2")]
+    pub interiors: ::prost::alloc::vec::Vec<GeoLineString>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ValuesCount {
     #[prost(uint64, optional, tag="1")]
     pub lt: ::core::option::Option<u64>,
@@ -1923,6 +2378,27 @@ pub struct PointsIdsList {
     #[prost(message, repeated, tag="1")]
     pub ids: ::prost::alloc::vec::Vec<PointId>,
 }
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShardSelector {
+    /// Select shards, which the keys are mapped to
+    #[prost(string, repeated, tag="1")]
+    pub shard_keys: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Select shards by ids, used if no shard keys are given
+    #[prost(uint32, repeated, tag="2")]
+    pub shard_ids: ::prost::alloc::vec::Vec<u32>,
+    /// Read from the replica of a shard on a peer, e.g. from a listener. Not allowed for updates
+    #[prost(message, optional, tag="3")]
+    pub replica: ::core::option::Option<ShardReplicaSelector>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShardReplicaSelector {
+    /// Id of the shard
+    #[prost(uint32, tag="1")]
+    pub shard_id: u32,
+    /// Id of the peer, which holds the replica
+    #[prost(uint64, tag="2")]
+    pub peer_id: u64,
+}
 // ---------------------------------------------
 // ------------------- Point -------------------
 // ---------------------------------------------
@@ -1951,6 +2427,15 @@ pub enum FieldType {
     Float = 2,
     Geo = 3,
     Text = 4,
+    Composite = 5,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum MovePointsMode {
+    /// Points are deleted from the source collection once all points of their shard are stored in the target collection
+    MovePoints = 0,
+    /// Points are kept in the source collection
+    CopyPoints = 1,
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -2303,6 +2788,27 @@ pub mod points_client {
             let path = http::uri::PathAndQuery::from_static("/qdrant.Points/Count");
             self.inner.unary(request.into_request(), path, codec).await
         }
+        ///
+        ///Move or copy points, which match given filtering conditions, into another collection
+        pub async fn move_points(
+            &mut self,
+            request: impl tonic::IntoRequest<super::MovePoints>,
+        ) -> Result<tonic::Response<super::MovePointsResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/qdrant.Points/MovePoints",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -2396,6 +2902,12 @@ pub mod points_server {
             &self,
             request: tonic::Request<super::CountPoints>,
         ) -> Result<tonic::Response<super::CountResponse>, tonic::Status>;
+        ///
+        ///Move or copy points, which match given filtering conditions, into another collection
+        async fn move_points(
+            &self,
+            request: tonic::Request<super::MovePoints>,
+        ) -> Result<tonic::Response<super::MovePointsResponse>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct PointsServer<T: Points> {
@@ -2970,6 +3482,42 @@ pub mod points_server {
                     };
                     Box::pin(fut)
                 }
+                "/qdrant.Points/MovePoints" => {
+                    #[allow(non_camel_case_types)]
+                    struct MovePointsSvc<T: Points>(pub Arc<T>);
+                    impl<T: Points> tonic::server::UnaryService<super::MovePoints>
+                    for MovePointsSvc<T> {
+                        type Response = super::MovePointsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::MovePoints>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).move_points(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = MovePointsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -3126,6 +3674,25 @@ pub struct CountPointsInternal {
     #[prost(uint32, tag="2")]
     pub shard_id: u32,
 }
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShardChecksumInternal {
+    #[prost(string, tag="1")]
+    pub collection_name: ::prost::alloc::string::String,
+    #[prost(uint32, tag="2")]
+    pub shard_id: u32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShardChecksumResponse {
+    /// Number of points in the shard
+    #[prost(uint64, tag="1")]
+    pub points_count: u64,
+    /// Order independent hash of ids, vectors and payloads of the points
+    #[prost(uint64, tag="2")]
+    pub checksum: u64,
+    /// Time spent to process
+    #[prost(double, tag="3")]
+    pub time: f64,
+}
 /// Generated client implementations.
 pub mod points_internal_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -3456,6 +4023,25 @@ pub mod points_internal_client {
             );
             self.inner.unary(request.into_request(), path, codec).await
         }
+        pub async fn checksum(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ShardChecksumInternal>,
+        ) -> Result<tonic::Response<super::ShardChecksumResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/qdrant.PointsInternal/Checksum",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -3521,6 +4107,10 @@ pub mod points_internal_server {
             &self,
             request: tonic::Request<super::GetPointsInternal>,
         ) -> Result<tonic::Response<super::GetResponse>, tonic::Status>;
+        async fn checksum(
+            &self,
+            request: tonic::Request<super::ShardChecksumInternal>,
+        ) -> Result<tonic::Response<super::ShardChecksumResponse>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct PointsInternalServer<T: PointsInternal> {
@@ -4117,6 +4707,44 @@ pub mod points_internal_server {
                     };
                     Box::pin(fut)
                 }
+                "/qdrant.PointsInternal/Checksum" => {
+                    #[allow(non_camel_case_types)]
+                    struct ChecksumSvc<T: PointsInternal>(pub Arc<T>);
+                    impl<
+                        T: PointsInternal,
+                    > tonic::server::UnaryService<super::ShardChecksumInternal>
+                    for ChecksumSvc<T> {
+                        type Response = super::ShardChecksumResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ShardChecksumInternal>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).checksum(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ChecksumSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -4183,6 +4811,9 @@ pub struct AddPeerToKnownMessage {
     pub port: ::core::option::Option<u32>,
     #[prost(uint64, tag="3")]
     pub id: u64,
+    /// Shared secret of the cluster, required if the bootstrap peer has one configured
+    #[prost(string, optional, tag="4")]
+    pub join_token: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PeerId {
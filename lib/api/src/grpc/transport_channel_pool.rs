@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::future::Future;
 use std::num::NonZeroUsize;
+use std::sync::Mutex;
 use std::time::{Duration, Instant};
 use std::vec::Vec;
 
@@ -24,6 +25,7 @@ struct ChannelPool {
     /// Channel for fast connectivity test
     fast_channel: Channel,
     init_at: Instant,
+    last_used_at: Mutex<Instant>,
 }
 
 impl ChannelPool {
@@ -32,26 +34,38 @@ impl ChannelPool {
         pool_size: NonZeroUsize,
         grpc_timeout: Duration,
         connection_timeout: Duration,
+        max_concurrent_streams: Option<NonZeroUsize>,
     ) -> Result<Self, TonicError> {
         let mut channels = Vec::with_capacity(pool_size.into());
         for _ in 0..pool_size.into() {
-            let channel =
-                TransportChannelPool::make_channel(grpc_timeout, connection_timeout, uri.clone())
-                    .await?;
+            let channel = TransportChannelPool::make_limited_channel(
+                grpc_timeout,
+                connection_timeout,
+                max_concurrent_streams,
+                uri.clone(),
+            )
+            .await?;
             channels.push(channel);
         }
         let fast_channel =
             TransportChannelPool::make_channel(SMART_CONNECT_TIMEOUT, connection_timeout, uri)
                 .await?;
 
+        let now = Instant::now();
         Ok(Self {
             channels,
             fast_channel,
-            init_at: Instant::now(),
+            init_at: now,
+            last_used_at: Mutex::new(now),
         })
     }
 
+    fn is_idle(&self, idle_timeout: Duration) -> bool {
+        self.last_used_at.lock().unwrap().elapsed() > idle_timeout
+    }
+
     fn choose(&self) -> Channel {
+        *self.last_used_at.lock().unwrap() = Instant::now();
         self.channels
             .choose(&mut rand::thread_rng())
             .expect("Pool size can not be zero")
@@ -75,6 +89,10 @@ pub struct TransportChannelPool {
     pool_size: NonZeroUsize,
     grpc_timeout: Duration,
     connection_timeout: Duration,
+    /// Pools, which were not used for longer, are re-established on the next request
+    idle_timeout: Option<Duration>,
+    /// Max number of concurrent requests (HTTP/2 streams) per channel
+    max_concurrent_streams: Option<NonZeroUsize>,
 }
 
 impl Default for TransportChannelPool {
@@ -84,6 +102,8 @@ impl Default for TransportChannelPool {
             pool_size: NonZeroUsize::new(DEFAULT_POOL_SIZE).unwrap(),
             grpc_timeout: DEFAULT_GRPC_TIMEOUT,
             connection_timeout: DEFAULT_CONNECT_TIMEOUT,
+            idle_timeout: None,
+            max_concurrent_streams: None,
         }
     }
 }
@@ -95,18 +115,46 @@ impl TransportChannelPool {
             grpc_timeout: p2p_grpc_timeout,
             connection_timeout,
             pool_size: NonZeroUsize::new(pool_size).unwrap(),
+            idle_timeout: None,
+            max_concurrent_streams: None,
         }
     }
 
+    /// Re-establish connections to a peer, which were not used for longer than `idle_timeout`.
+    /// Idle connections might be silently closed by proxies and load balancers in between.
+    pub fn with_idle_timeout(mut self, idle_timeout: Option<Duration>) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Limit number of concurrent requests on each connection, further requests wait for a slot.
+    /// Zero means no limit.
+    pub fn with_max_concurrent_streams(mut self, max_concurrent_streams: Option<usize>) -> Self {
+        self.max_concurrent_streams = max_concurrent_streams.and_then(NonZeroUsize::new);
+        self
+    }
+
     pub async fn make_channel(
         grpc_timeout: Duration,
         connection_timeout: Duration,
         uri: Uri,
     ) -> Result<Channel, TonicError> {
-        let endpoint = Channel::builder(uri)
+        Self::make_limited_channel(grpc_timeout, connection_timeout, None, uri).await
+    }
+
+    async fn make_limited_channel(
+        grpc_timeout: Duration,
+        connection_timeout: Duration,
+        max_concurrent_streams: Option<NonZeroUsize>,
+        uri: Uri,
+    ) -> Result<Channel, TonicError> {
+        let mut endpoint = Channel::builder(uri)
             .timeout(grpc_timeout)
             .connect_timeout(connection_timeout)
             .keep_alive_while_idle(true);
+        if let Some(limit) = max_concurrent_streams {
+            endpoint = endpoint.concurrency_limit(limit.get());
+        }
         // `connect` is using the `Reconnect` network service internally to handle dropped connections
         endpoint.connect().await
     }
@@ -122,6 +170,7 @@ impl TransportChannelPool {
                     self.pool_size,
                     self.grpc_timeout,
                     self.connection_timeout,
+                    self.max_concurrent_streams,
                 )
                 .await?;
                 let channel = channels.choose();
@@ -139,7 +188,15 @@ impl TransportChannelPool {
 
     async fn get_pooled_channel(&self, uri: &Uri) -> Option<Channel> {
         let guard = self.uri_to_pool.read().await;
-        guard.get(uri).map(|channels| channels.choose())
+        let channels = guard.get(uri)?;
+        if let Some(idle_timeout) = self.idle_timeout {
+            if channels.is_idle(idle_timeout) {
+                drop(guard);
+                self.drop_pool(uri).await;
+                return None;
+            }
+        }
+        Some(channels.choose())
     }
 
     async fn get_fast_pooled_channel(&self, uri: &Uri) -> Option<Channel> {
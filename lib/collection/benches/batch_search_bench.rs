@@ -9,7 +9,7 @@ use collection::config::{CollectionConfig, CollectionParams, VectorParams, WalCo
 use collection::operations::point_ops::{PointInsertOperations, PointOperations, PointStruct};
 use collection::operations::types::{SearchRequest, SearchRequestBatch};
 use collection::operations::CollectionUpdateOperations;
-use collection::optimizers_builder::OptimizersConfig;
+use collection::optimizers_builder::{MergePolicy, OptimizersConfig};
 use collection::shard::local_shard::LocalShard;
 use collection::shard::ShardOperation;
 use criterion::{criterion_group, criterion_main, Criterion};
@@ -61,11 +61,25 @@ fn batch_search_bench(c: &mut Criterion) {
         vectors: VectorParams {
             size: NonZeroU64::new(100).unwrap(),
             distance: Distance::Dot,
+            quantization_config: None,
+            on_disk: None,
+            multivector_config: None,
+            hnsw_config: None,
+            normalize: None,
         }
         .into(),
+        sparse_vectors: None,
         shard_number: NonZeroU32::new(1).expect("Shard number can not be zero"),
         replication_factor: NonZeroU32::new(1).unwrap(),
+        read_fan_out_factor: None,
+        write_consistency: Default::default(),
+        read_consistency: Default::default(),
+        hash_ring: Default::default(),
         on_disk_payload: false,
+        max_response_payload_bytes: None,
+        payload_transformers: vec![],
+        ivf_config: None,
+        placement: Default::default(),
     };
 
     let collection_config = CollectionConfig {
@@ -79,9 +93,12 @@ fn batch_search_bench(c: &mut Criterion) {
             indexing_threshold: 50_000,
             flush_interval_sec: 30,
             max_optimization_threads: 2,
+            max_segment_disk_size: None,
+            merge_policy: MergePolicy::Auto,
         },
         wal_config,
         hnsw_config: Default::default(),
+        version: 0,
     };
 
     let shared_config = Arc::new(RwLock::new(collection_config));
@@ -92,6 +109,7 @@ fn batch_search_bench(c: &mut Criterion) {
             "test_collection".to_string(),
             storage_dir.path(),
             shared_config,
+            Default::default(),
         ))
         .unwrap();
 
@@ -137,6 +155,7 @@ fn batch_search_bench(c: &mut Criterion) {
                             with_payload: None,
                             with_vector: None,
                             score_threshold: None,
+                            max_payload_bytes: None,
                         };
                         let result = shard
                             .search(
@@ -169,6 +188,7 @@ fn batch_search_bench(c: &mut Criterion) {
                             with_payload: None,
                             with_vector: None,
                             score_threshold: None,
+                            max_payload_bytes: None,
                         };
                         searches.push(search_query);
                     }
@@ -9,11 +9,13 @@ fn hash_ring_bench(c: &mut Criterion) {
 
     let mut ring_raw = HashRing::raw();
     let mut ring_fair = HashRing::fair(100);
+    let mut ring_jump = HashRing::jump();
 
     // add 10 shards to ring
     for i in 0..10 {
         ring_raw.add(i);
         ring_fair.add(i);
+        ring_jump.add(i);
     }
 
     let mut rnd = rand::thread_rng();
@@ -25,6 +27,13 @@ fn hash_ring_bench(c: &mut Criterion) {
         })
     });
 
+    group.bench_function("hash-ring-jump", |b| {
+        b.iter(|| {
+            let point = rnd.gen_range(0..100000);
+            let _shard = ring_jump.get(&point);
+        })
+    });
+
     group.bench_function("hash-ring-raw", |b| {
         b.iter(|| {
             let point = rnd.gen_range(0..100000);
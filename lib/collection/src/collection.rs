@@ -1,47 +1,63 @@
 use std::cmp::max;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::future::Future;
 use std::num::NonZeroU32;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
+use api::grpc::api_crate_version;
 use futures::future::{join_all, try_join_all};
 use itertools::Itertools;
 use segment::common::version::StorageVersion;
 use segment::data_types::vectors::{NamedVector, VectorElementType, DEFAULT_VECTOR_NAME};
+use segment::index::field_index::resource_estimation::{FieldValuesStats, IndexResourceEstimation};
 use segment::spaces::tools::{peek_top_largest_iterable, peek_top_smallest_iterable};
 use segment::types::{
-    Condition, ExtendedPointId, Filter, HasIdCondition, Order, ScoredPoint, WithPayload,
+    Condition, ExtendedPointId, Filter, HasIdCondition, Order, PayloadIndexInfo,
+    PayloadIndexStatus, PayloadSelector, ScoredPoint, SeqNumberType, WithPayload,
     WithPayloadInterface, WithVector,
 };
 use semver::Version;
-use tar::Builder as TarBuilder;
-use tokio::fs::{copy, create_dir_all, remove_dir_all, remove_file, rename};
+use tokio::fs::{copy, create_dir_all, remove_dir_all, rename};
 use tokio::runtime::Handle;
 use tokio::sync::{Mutex, RwLock};
 
 use crate::collection_state::{ShardInfo, State};
-use crate::config::CollectionConfig;
+use crate::common::payload_size_limit::limit_payload_size;
+use crate::config::{
+    colocated_replicas, CollectionConfig, HashRingType, PeerMetadataById, COLLECTION_CONFIG_FILE,
+};
 use crate::hash_ring::HashRing;
 use crate::operations::config_diff::{CollectionParamsDiff, DiffConfig, OptimizersConfigDiff};
+use crate::operations::point_ops::PointOperations;
+use crate::operations::shard_selector::ShardSelectorInternal;
 use crate::operations::snapshot_ops::{
-    get_snapshot_description, list_snapshots_in_directory, SnapshotDescription,
+    check_free_space, get_snapshot_description, list_snapshots_in_directory, SnapshotCompression,
+    SnapshotDescription, SnapshotMetadata,
 };
+use crate::operations::snapshot_storage_ops::{archive_snapshot, SnapshotData, SnapshotStorage};
 use crate::operations::types::{
-    CollectionClusterInfo, CollectionError, CollectionInfo, CollectionResult, CountRequest,
-    CountResult, LocalShardInfo, PointRequest, RecommendRequest, RecommendRequestBatch, Record,
-    RemoteShardInfo, ScrollRequest, ScrollResult, SearchRequest, SearchRequestBatch,
-    ShardTransferInfo, UpdateResult, UsingVector,
+    merge_index_info, CollectionClusterInfo, CollectionError, CollectionInfo, CollectionResult,
+    CountRequest, CountResult, FieldIndexEstimation, FieldIndexEstimationRequest, LocalShardInfo,
+    PointRequest, RecommendRequest, RecommendRequestBatch, Record, RemoteShardInfo, ScrollRequest,
+    ScrollResult, SearchRequest, SearchRequestBatch, ShardCleanupProgress, ShardMovementEstimate,
+    ShardTransferInfo, ShardUpdateResult, UpdateResult, UsingVector, WalInfo,
+    DEFAULT_INDEX_ESTIMATION_SAMPLE_SIZE,
 };
 use crate::operations::{CollectionUpdateOperations, Validate};
 use crate::optimizers_builder::OptimizersConfig;
+use crate::resource_watchdog::ResourceThrottle;
+use crate::shard::checksum::ShardChecksum;
 use crate::shard::collection_shard_distribution::CollectionShardDistribution;
-use crate::shard::local_shard::LocalShard;
+use crate::shard::local_shard::{drop_and_delete_from_disk, LocalShard};
 use crate::shard::remote_shard::RemoteShard;
-use crate::shard::replica_set::ReplicaSet;
+use crate::shard::replica_set::{ReplicaSet, ReplicaState};
 use crate::shard::shard_config::{ShardConfig, ShardType};
 use crate::shard::shard_holder::{LockedShardHolder, ShardHolder};
-use crate::shard::shard_versioning::versioned_shard_path;
+use crate::shard::shard_versioning::{
+    drop_old_shards, suggest_next_version_path, versioned_shard_path,
+};
 use crate::shard::transfer::shard_transfer::{
     change_remote_shard_route, drop_temporary_shard, promote_proxy_to_remote_shard,
     promote_temporary_shard_to_local, revert_proxy_shard_to_local, spawn_transfer_task,
@@ -53,6 +69,56 @@ use crate::shard::{
 };
 use crate::telemetry::CollectionTelemetry;
 
+/// How often to poll segments while waiting for a payload index to be built
+const INDEX_STATUS_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Directory inside of the collection snapshots, which holds exported segments
+pub const SEGMENT_EXPORTS_DIR: &str = "segments";
+
+/// Directory inside of the collection snapshots, which holds snapshots of individual shards
+pub const SHARD_SNAPSHOTS_DIR: &str = "shards";
+
+/// Minimal number of points, read from a shard at once by the ID-ordered scroll
+const MIN_SCROLL_PAGE_SIZE: usize = 16;
+
+/// Points of a shard, read by the ID-ordered scroll, which are not merged yet
+struct ShardScrollCursor {
+    points: VecDeque<Record>,
+    /// Offset of the next page, if there is any
+    next_offset: Option<ExtendedPointId>,
+}
+
+impl ShardScrollCursor {
+    async fn read(
+        shard: &(dyn ShardOperation + Sync + Send),
+        offset: Option<ExtendedPointId>,
+        page_size: usize,
+        with_payload_interface: &WithPayloadInterface,
+        with_vector: &WithVector,
+        filter: Option<&Filter>,
+    ) -> CollectionResult<Self> {
+        // One extra point is requested to get the offset of the next page
+        let mut points = shard
+            .scroll_by(
+                offset,
+                page_size + 1,
+                with_payload_interface,
+                with_vector,
+                filter,
+            )
+            .await?;
+        let next_offset = if points.len() > page_size {
+            points.pop().map(|point| point.id)
+        } else {
+            None
+        };
+        Ok(Self {
+            points: points.into(),
+            next_offset,
+        })
+    }
+}
+
 struct CollectionVersion;
 
 impl StorageVersion for CollectionVersion {
@@ -64,15 +130,20 @@ impl StorageVersion for CollectionVersion {
 /// Collection's data is split into several shards.
 pub struct Collection {
     pub(crate) id: CollectionId,
+    this_peer_id: PeerId,
     pub(crate) shards_holder: Arc<LockedShardHolder>,
     pub(crate) config: Arc<RwLock<CollectionConfig>>,
     /// Tracks whether `before_drop` fn has been called.
     before_drop_called: bool,
     path: PathBuf,
     snapshots_path: PathBuf,
+    snapshot_storage: SnapshotStorage,
     telemetry: CollectionTelemetry,
     channel_service: ChannelService,
     transfer_tasks: Mutex<TransferTasksPool>,
+    on_replica_failure: replica_set::OnPeerFailure,
+    /// Throttle of the node, which pauses optimizations and shard transfers
+    resource_throttle: ResourceThrottle,
 }
 
 impl Collection {
@@ -80,26 +151,36 @@ impl Collection {
         self.id.clone()
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         id: CollectionId,
+        this_peer_id: PeerId,
         path: &Path,
         snapshots_path: &Path,
+        snapshot_storage: SnapshotStorage,
         config: &CollectionConfig,
         shard_distribution: CollectionShardDistribution,
         channel_service: ChannelService,
         on_replica_failure: replica_set::OnPeerFailure,
+        resource_throttle: ResourceThrottle,
     ) -> Result<Self, CollectionError> {
         let start_time = std::time::Instant::now();
 
-        let mut shard_holder = ShardHolder::new(path, HashRing::fair(HASH_RING_SHARD_SCALE))?;
+        let mut shard_holder = ShardHolder::new(path, build_hash_ring(config.params.hash_ring))?;
 
         let shared_config = Arc::new(RwLock::new(config.clone()));
         for shard_id in shard_distribution.local {
             let shard_path = create_shard_dir(path, shard_id).await;
             let shard = match shard_path {
                 Ok(shard_path) => {
-                    LocalShard::build(shard_id, id.clone(), &shard_path, shared_config.clone())
-                        .await
+                    LocalShard::build(
+                        shard_id,
+                        id.clone(),
+                        &shard_path,
+                        shared_config.clone(),
+                        resource_throttle.clone(),
+                    )
+                    .await
                 }
                 Err(e) => Err(e),
             };
@@ -135,39 +216,34 @@ impl Collection {
             };
             shard_holder.add_shard(shard_id, Shard::Remote(shard));
         }
-        // This is a stub to check that types and hidden lifetimes fit
-        // It might be worth leaving it here for now until we have an actual impl
-        // TODO: Create ReplicaSet shards
-        if false {
-            let shard = ReplicaSet::new(
-                1,
-                1,
-                Default::default(),
-                Default::default(),
-                Default::default(),
-                0.0,
-                on_replica_failure,
-            );
-            shard_holder.add_shard(0, Shard::ReplicaSet(shard))
-        }
 
         let locked_shard_holder = Arc::new(LockedShardHolder::new(shard_holder));
 
-        // Once the config is persisted - the collection is considered to be successfully created.
-        CollectionVersion::save(path)?;
-        config.save(path)?;
-
-        Ok(Self {
+        let mut collection = Self {
             id: id.clone(),
+            this_peer_id,
             shards_holder: locked_shard_holder,
             config: shared_config,
             before_drop_called: false,
             path: path.to_owned(),
             snapshots_path: snapshots_path.to_owned(),
+            snapshot_storage,
             telemetry: CollectionTelemetry::new(id, config.clone(), start_time.elapsed()),
             channel_service,
             transfer_tasks: Default::default(),
-        })
+            on_replica_failure,
+            resource_throttle,
+        };
+        if let Err(err) = collection.upgrade_to_replica_sets().await {
+            collection.before_drop().await;
+            return Err(err);
+        }
+
+        // Once the config is persisted - the collection is considered to be successfully created.
+        CollectionVersion::save(path)?;
+        config.save(path)?;
+
+        Ok(collection)
     }
 
     /// Check if stored version have consequent version.
@@ -193,11 +269,16 @@ impl Collection {
         true
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn load(
         collection_id: CollectionId,
+        this_peer_id: PeerId,
         path: &Path,
         snapshots_path: &Path,
+        snapshot_storage: SnapshotStorage,
         channel_service: ChannelService,
+        on_replica_failure: replica_set::OnPeerFailure,
+        resource_throttle: ResourceThrottle,
     ) -> Self {
         let start_time = std::time::Instant::now();
         let stored_version = CollectionVersion::load(path)
@@ -232,7 +313,7 @@ impl Collection {
             )
         });
 
-        let ring = HashRing::fair(HASH_RING_SHARD_SCALE);
+        let ring = build_hash_ring(config.params.hash_ring);
         let mut shard_holder = ShardHolder::new(path, ring).expect("Can not create shard holder");
 
         let shared_config = Arc::new(RwLock::new(config.clone()));
@@ -241,23 +322,134 @@ impl Collection {
             .load_shards(
                 path,
                 &collection_id,
+                this_peer_id,
                 shared_config.clone(),
                 channel_service.clone(),
+                on_replica_failure.clone(),
+                resource_throttle.clone(),
             )
             .await;
 
         let locked_shard_holder = Arc::new(LockedShardHolder::new(shard_holder));
 
-        Self {
+        let collection = Self {
             id: collection_id.clone(),
+            this_peer_id,
             shards_holder: locked_shard_holder,
             config: shared_config,
             before_drop_called: false,
             path: path.to_owned(),
             snapshots_path: snapshots_path.to_owned(),
+            snapshot_storage,
             telemetry: CollectionTelemetry::new(collection_id, config, start_time.elapsed()),
             channel_service,
             transfer_tasks: Mutex::new(TransferTasksPool::default()),
+            on_replica_failure,
+            resource_throttle,
+        };
+        // Replicated collections might have been stored before their shards were upgraded
+        collection
+            .upgrade_to_replica_sets()
+            .await
+            .unwrap_or_else(|err| panic!("Can't upgrade shards to replica sets: {err}"));
+        collection
+    }
+
+    /// Turn local and remote shards into replica sets, once the collection is replicated.
+    /// Each shard becomes a replica set with its only replica, which is active. New replicas are
+    /// synced by transfers, see `suggest_shard_replica_changes`.
+    /// Shards with ongoing transfers are upgraded once those are finished.
+    pub async fn upgrade_to_replica_sets(&self) -> CollectionResult<()> {
+        let (read_fan_out_factor, read_consistency, write_consistency) = {
+            let config = self.config.read().await;
+            if config.params.replication_factor.get() < 2 {
+                return Ok(());
+            }
+            (
+                config.params.read_fan_out_factor,
+                config.params.read_consistency,
+                config.params.write_consistency,
+            )
+        };
+        let mut shards_holder = self.shards_holder.write().await;
+        let shard_ids: Vec<ShardId> = shards_holder
+            .get_shards()
+            .filter(|(shard_id, shard)| {
+                matches!(shard, Shard::Local(_) | Shard::Remote(_))
+                    && !shards_holder.is_shard_transferred(**shard_id)
+            })
+            .map(|(shard_id, _)| *shard_id)
+            .collect();
+        for shard_id in shard_ids {
+            let (shard_path, peer_id) = match shards_holder.get_shard(&shard_id) {
+                Some(Shard::Local(local)) => (local.shard_path(), self.this_peer_id),
+                // Remote shards keep no data, so the replica set gets a new directory
+                Some(Shard::Remote(remote)) => (
+                    create_shard_dir(&self.path, shard_id).await?,
+                    remote.peer_id,
+                ),
+                _ => continue,
+            };
+            let remotes = if peer_id == self.this_peer_id {
+                vec![]
+            } else {
+                vec![RemoteShard::new(
+                    shard_id,
+                    self.id.clone(),
+                    peer_id,
+                    self.channel_service.clone(),
+                )]
+            };
+            let mut replica_set = ReplicaSet::new(
+                shard_id,
+                self.id.clone(),
+                self.this_peer_id,
+                &shard_path,
+                None,
+                remotes,
+                HashMap::from([(peer_id, ReplicaState::Active)]),
+                read_fan_out_factor,
+                read_consistency,
+                write_consistency,
+                self.channel_service.clone(),
+                self.on_replica_failure.clone(),
+            )?;
+            let res = match shards_holder.take_shard(shard_id) {
+                Some(Shard::Local(local)) => replica_set.set_local(local, ReplicaState::Active),
+                _ => ShardConfig::new_replica_set().save(&shard_path),
+            };
+            shards_holder.replace_shard(shard_id, Shard::ReplicaSet(replica_set));
+            res?;
+            if peer_id != self.this_peer_id {
+                drop_old_shards(&self.path, shard_id).await?;
+            }
+            log::debug!(
+                "Shard {shard_id} of collection {} is upgraded to a replica set",
+                self.id
+            );
+        }
+        Ok(())
+    }
+
+    /// Check, that a user can switch the replica of `shard_id` on `peer_id` to `state`,
+    /// see `ReplicaSet::validate_listener_change`
+    pub async fn validate_replica_state_change(
+        &self,
+        shard_id: ShardId,
+        peer_id: PeerId,
+        state: ReplicaState,
+    ) -> CollectionResult<()> {
+        let shards_holder = self.shards_holder.read().await;
+        match shards_holder.get_shard(&shard_id) {
+            Some(Shard::ReplicaSet(replica_set)) => {
+                replica_set.validate_listener_change(peer_id, state)
+            }
+            Some(_) => Err(CollectionError::BadRequest {
+                description: format!("Shard {shard_id} is not replicated"),
+            }),
+            None => Err(CollectionError::NotFound {
+                what: format!("Shard {shard_id}"),
+            }),
         }
     }
 
@@ -265,10 +457,242 @@ impl Collection {
         &self,
         shard_id: ShardId,
         peer_id: PeerId,
-        active: bool,
+        state: ReplicaState,
     ) -> CollectionResult<()> {
-        let mut shard_holder = self.shards_holder.write().await;
-        shard_holder.set_shard_replica_state(shard_id, peer_id, active)
+        let shard_holder = self.shards_holder.read().await;
+        shard_holder.set_shard_replica_state(shard_id, peer_id, state)
+    }
+
+    /// Mark local replicas, which can't be trusted after restart, as `Partial`, so they don't
+    /// answer reads, and return transfers to recover them from active replicas on other peers.
+    pub async fn start_local_replicas_recovery(&self) -> CollectionResult<Vec<ShardTransfer>> {
+        let shards_holder = self.shards_holder.read().await;
+        let shard_transfers = shards_holder.shard_transfers();
+        let transfers: Vec<_> = shards_holder
+            .get_shards()
+            .filter_map(|(shard_id, shard)| match shard {
+                Shard::ReplicaSet(replica_set) => {
+                    replica_set
+                        .local_recovery_source()
+                        .map(|from| ShardTransfer {
+                            shard_id: *shard_id,
+                            from,
+                            to: replica_set.this_peer_id(),
+                        })
+                }
+                _ => None,
+            })
+            // Recovery might be in progress already
+            .filter(|transfer| {
+                !shard_transfers
+                    .iter()
+                    .any(|t| t.shard_id == transfer.shard_id && t.to == transfer.to)
+            })
+            .collect();
+
+        for transfer in &transfers {
+            if let Some(Shard::ReplicaSet(replica_set)) =
+                shards_holder.get_shard(&transfer.shard_id)
+            {
+                replica_set.set_replica_state(&transfer.to, ReplicaState::Partial)?;
+            }
+        }
+        Ok(transfers)
+    }
+
+    /// Transfers to sync the missing replicas of shards, e.g. after the replication factor was
+    /// increased. Each new replica is placed on one of `known_peers`, allowed by the placement
+    /// constraints, preferring zones and racks without replicas of the shard, then the least loaded
+    /// peer. It is synced from the least loaded active replica of the shard.
+    /// Shards with ongoing transfers are skipped until those are finished.
+    ///
+    /// Only the collection state, shared through consensus, is considered, so that every peer
+    /// suggests the same transfers.
+    pub async fn suggest_shard_replica_changes(
+        &self,
+        known_peers: &[PeerId],
+        peer_metadata: &PeerMetadataById,
+    ) -> Vec<ShardTransfer> {
+        let State {
+            config,
+            shards,
+            transfers: shard_transfers,
+        } = self.state(self.this_peer_id).await;
+        let replication_factor = config.params.replication_factor.get() as usize;
+        let placement = &config.params.placement;
+        let shard_replicas: Vec<(ShardId, HashMap<PeerId, ReplicaState>)> = shards
+            .into_iter()
+            .map(|(shard_id, shard_info)| {
+                let replicas = match shard_info {
+                    ShardInfo::ReplicaSet { replicas } => replicas,
+                    // The only replica of a shard is always active
+                    ShardInfo::Single(peer_id) => HashMap::from([(peer_id, ReplicaState::Active)]),
+                };
+                (shard_id, replicas)
+            })
+            .sorted_by_key(|(shard_id, _)| *shard_id)
+            .collect();
+
+        let mut peer_load: HashMap<PeerId, usize> =
+            known_peers.iter().map(|peer_id| (*peer_id, 0)).collect();
+        for (_shard_id, replicas) in &shard_replicas {
+            for peer_id in replicas.keys() {
+                *peer_load.entry(*peer_id).or_default() += 1;
+            }
+        }
+        for transfer in &shard_transfers {
+            *peer_load.entry(transfer.from).or_default() += 1;
+            *peer_load.entry(transfer.to).or_default() += 1;
+        }
+
+        let mut transfers = vec![];
+        for (shard_id, replicas) in &shard_replicas {
+            if shard_transfers
+                .iter()
+                .any(|transfer| transfer.shard_id == *shard_id)
+            {
+                continue;
+            }
+            let mut shard_peers: Vec<PeerId> = replicas.keys().copied().sorted().collect();
+            while shard_peers.len() < replication_factor {
+                let from = match replica_set::replication_source(replicas, &peer_load) {
+                    Some(from) => from,
+                    None => {
+                        log::warn!(
+                            "No active replica of shard {shard_id} to sync new replicas from"
+                        );
+                        break;
+                    }
+                };
+                let to = known_peers
+                    .iter()
+                    .copied()
+                    .filter(|peer_id| {
+                        !shard_peers.contains(peer_id) && placement.allows(*peer_id, &shard_peers)
+                    })
+                    .min_by_key(|peer_id| {
+                        (
+                            colocated_replicas(peer_metadata, *peer_id, &shard_peers),
+                            peer_load[peer_id],
+                            *peer_id,
+                        )
+                    });
+                let to = match to {
+                    Some(to) => to,
+                    None => {
+                        log::warn!(
+                            "No peer to place a new replica of shard {shard_id} on, {} of {replication_factor} replicas exist",
+                            shard_peers.len()
+                        );
+                        break;
+                    }
+                };
+                *peer_load.entry(from).or_default() += 1;
+                *peer_load.entry(to).or_default() += 1;
+                shard_peers.push(to);
+                transfers.push(ShardTransfer {
+                    shard_id: *shard_id,
+                    from,
+                    to,
+                });
+            }
+        }
+        transfers
+    }
+
+    /// Transfers to move local shards away from this peer, which is being drained.
+    /// Each shard is moved to the least loaded of `target_peers`, allowed by the placement
+    /// constraints. Shards with ongoing transfers are skipped until those are finished.
+    pub async fn suggest_drain_transfers(
+        &self,
+        target_peers: &[PeerId],
+        this_peer_id: PeerId,
+    ) -> Vec<ShardTransfer> {
+        let config = self.config.read().await;
+        let placement = &config.params.placement;
+        let shard_holder = self.shards_holder.read().await;
+
+        let mut peer_load: HashMap<PeerId, usize> =
+            target_peers.iter().map(|peer_id| (*peer_id, 0)).collect();
+        for (_shard_id, shard) in shard_holder.get_shards() {
+            for peer_id in shard.peer_ids(this_peer_id) {
+                *peer_load.entry(peer_id).or_default() += 1;
+            }
+        }
+        for transfer in shard_holder.shard_transfers() {
+            *peer_load.entry(transfer.to).or_default() += 1;
+        }
+
+        let mut transfers = vec![];
+        for (shard_id, shard) in shard_holder
+            .get_shards()
+            .sorted_by_key(|(shard_id, _)| **shard_id)
+        {
+            if !matches!(shard, Shard::Local(_)) {
+                continue;
+            }
+            if shard_holder.is_shard_transferred(*shard_id) {
+                continue;
+            }
+            let to = target_peers
+                .iter()
+                .copied()
+                .filter(|peer_id| *peer_id != this_peer_id && placement.allows(*peer_id, &[]))
+                .min_by_key(|peer_id| (peer_load[peer_id], *peer_id));
+            let to = match to {
+                Some(to) => to,
+                None => {
+                    log::warn!(
+                        "No peer to move shard {shard_id} of collection {} to",
+                        self.id
+                    );
+                    continue;
+                }
+            };
+            *peer_load.entry(to).or_default() += 1;
+            transfers.push(ShardTransfer {
+                shard_id: *shard_id,
+                from: this_peer_id,
+                to,
+            });
+        }
+        transfers
+    }
+
+    /// Checksum of the points of the shard, stored on this peer
+    pub async fn shard_checksum(&self, shard_id: ShardId) -> CollectionResult<ShardChecksum> {
+        let shard_holder = self.shards_holder.read().await;
+        match shard_holder.get_shard(&shard_id) {
+            Some(shard) => shard.get().checksum().await,
+            None => Err(CollectionError::bad_shard_selection(format!(
+                "Shard {shard_id} does not exist"
+            ))),
+        }
+    }
+
+    /// Transfers to repair replicas, which diverged from other replicas of the same shard.
+    /// Shards are verified one by one, to not block changes of other shards for the whole time.
+    /// Shards with ongoing transfers are skipped, as their replicas are not expected to be equal.
+    pub async fn suggest_replica_repairs(&self) -> Vec<ShardTransfer> {
+        let shard_ids: Vec<ShardId> = self
+            .shards_holder
+            .read()
+            .await
+            .get_shards()
+            .map(|(shard_id, _)| *shard_id)
+            .sorted()
+            .collect();
+        let mut transfers = vec![];
+        for shard_id in shard_ids {
+            let shard_holder = self.shards_holder.read().await;
+            if shard_holder.is_shard_transferred(shard_id) {
+                continue;
+            }
+            if let Some(Shard::ReplicaSet(replica_set)) = shard_holder.get_shard(&shard_id) {
+                transfers.extend(replica_set.suggest_repairs().await);
+            }
+        }
+        transfers
     }
 
     pub async fn contains_shard(&self, shard_id: &ShardId) -> bool {
@@ -276,6 +700,38 @@ impl Collection {
         shard_holder_read.contains_shard(shard_id)
     }
 
+    /// Check that the placement constraints of the collection allow a replica of the shard
+    /// on `peer_id`. The replica on `replaced_peer_id`, if any, is not taken into account.
+    pub async fn validate_replica_placement(
+        &self,
+        shard_id: ShardId,
+        peer_id: PeerId,
+        replaced_peer_id: Option<PeerId>,
+        this_peer_id: PeerId,
+    ) -> CollectionResult<()> {
+        let placement = self.config.read().await.params.placement.clone();
+        let shard_holder = self.shards_holder.read().await;
+        let shard = shard_holder
+            .get_shard(&shard_id)
+            .ok_or_else(|| CollectionError::NotFound {
+                what: format!("Shard {}", shard_id),
+            })?;
+        let shard_peers: Vec<PeerId> = shard
+            .peer_ids(this_peer_id)
+            .into_iter()
+            .filter(|&shard_peer| Some(shard_peer) != replaced_peer_id)
+            .collect();
+        if !placement.allows(peer_id, &shard_peers) {
+            return Err(CollectionError::BadRequest {
+                description: format!(
+                    "Placement constraints of the collection do not allow a replica of shard {} on peer {}",
+                    shard_id, peer_id
+                ),
+            });
+        }
+        Ok(())
+    }
+
     /// Returns true if shard it explicitly local, false otherwise.
     pub async fn is_shard_local(&self, shard_id: &ShardId) -> Option<bool> {
         let shard_holder_read = self.shards_holder.read().await;
@@ -286,21 +742,25 @@ impl Collection {
 
     pub async fn check_transfer_exists(&self, transfer: &ShardTransfer) -> bool {
         let shard_holder_read = self.shards_holder.read().await;
-        shard_holder_read.shard_transfers.contains(transfer)
+        shard_holder_read.shard_transfers().contains(transfer)
     }
 
     pub async fn get_outgoing_transfers(&self, current_peer_id: &PeerId) -> Vec<ShardTransfer> {
         let shard_holder = self.shards_holder.read().await;
         shard_holder
-            .shard_transfers
-            .iter()
+            .shard_transfers()
+            .into_iter()
             .filter(|transfer| transfer.from == *current_peer_id)
-            .cloned()
             .collect()
     }
 
-    async fn send_shard<OF, OE>(&self, transfer: ShardTransfer, on_finish: OF, on_error: OE)
-    where
+    async fn send_shard<OF, OE>(
+        &self,
+        transfer: ShardTransfer,
+        transfer_runtime: &Handle,
+        on_finish: OF,
+        on_error: OE,
+    ) where
         OF: Future<Output = ()> + Send + 'static,
         OE: Future<Output = ()> + Send + 'static,
     {
@@ -314,10 +774,12 @@ impl Collection {
         let channel_service = self.channel_service.clone();
 
         let transfer_task = spawn_transfer_task(
+            transfer_runtime,
             shard_holder,
             transfer.clone(),
             collection_id,
             channel_service,
+            self.resource_throttle.clone(),
             on_finish,
             on_error,
         );
@@ -325,9 +787,11 @@ impl Collection {
         active_transfer_tasks.add_task(&transfer, transfer_task);
     }
 
+    /// Register the transfer and, if this peer is the source, send the shard on `transfer_runtime`
     pub async fn start_shard_transfer<T, F>(
         &self,
         shard_transfer: ShardTransfer,
+        transfer_runtime: &Handle,
         on_finish: T,
         on_error: F,
     ) -> CollectionResult<bool>
@@ -336,10 +800,41 @@ impl Collection {
         F: Future<Output = ()> + Send + 'static,
     {
         let shard_id = shard_transfer.shard_id;
-        let do_transfer = {
+        let is_new_replica = |shard: Option<&Shard>| match shard {
+            Some(Shard::ReplicaSet(replica_set)) => {
+                replica_set.peer_state(&shard_transfer.to).is_none()
+                    && replica_set.this_peer_id() != shard_transfer.to
+            }
+            _ => false,
+        };
+        // Target of a replication transfer is a new replica of the shard.
+        // The receiving peer gets the data into its local replica, see `init_local_replica`.
+        // Adding a replica changes the shard, so only this step needs an exclusive lock.
+        if is_new_replica(self.shards_holder.read().await.get_shard(&shard_id)) {
             let mut shards_holder = self.shards_holder.write().await;
+            if is_new_replica(shards_holder.get_shard(&shard_id)) {
+                if let Some(Shard::ReplicaSet(replica_set)) = shards_holder.get_mut_shard(&shard_id)
+                {
+                    replica_set.add_remote(
+                        RemoteShard::new(
+                            shard_id,
+                            self.id.clone(),
+                            shard_transfer.to,
+                            self.channel_service.clone(),
+                        ),
+                        ReplicaState::Partial,
+                    )?;
+                }
+            }
+        }
+        if shard_transfer.to == self.this_peer_id {
+            self.init_local_replica(shard_id).await?;
+        }
+        let do_transfer = {
+            let shards_holder = self.shards_holder.read().await;
             let was_not_transferred =
                 shards_holder.register_start_shard_transfer(shard_transfer.clone())?;
+            shards_holder.set_transfer_target_state(&shard_transfer, ReplicaState::Partial)?;
             let shard = shards_holder.get_shard(&shard_id);
 
             // Check if current node owns the shard which should be transferred
@@ -363,16 +858,58 @@ impl Collection {
                         debug_assert!(!was_not_transferred);
                         false // Shard if already in transferring state
                     }
-                    Shard::ReplicaSet(_) => todo!(),
+                    // Only the peer with the local replica is able to send it
+                    Shard::ReplicaSet(replica_set) => {
+                        replica_set.has_local_shard()
+                            && replica_set.this_peer_id() == shard_transfer.from
+                    }
                 },
             }
         };
         if do_transfer {
-            self.send_shard(shard_transfer, on_finish, on_error).await;
+            self.send_shard(shard_transfer, transfer_runtime, on_finish, on_error)
+                .await;
         }
         Ok(do_transfer)
     }
 
+    /// Create an empty local replica of the shard, if it is a replica set without one.
+    /// The replica receives updates right away, so it misses none of them while it is transferred.
+    async fn init_local_replica(&self, shard_id: ShardId) -> CollectionResult<()> {
+        let needs_local = |shard: Option<&Shard>| match shard {
+            Some(Shard::ReplicaSet(replica_set)) => !replica_set.has_local_shard(),
+            _ => false,
+        };
+        if !needs_local(self.shards_holder.read().await.get_shard(&shard_id)) {
+            return Ok(());
+        }
+        // do not lock shards while creating the shard on disk
+        let shard_path = create_shard_dir(&self.path, shard_id).await?;
+        // Shard is only loaded as a temporary one, until it is registered as the local replica
+        let mut local_shard = LocalShard::build_temp(
+            shard_id,
+            self.id.clone(),
+            &shard_path,
+            self.config.clone(),
+            self.resource_throttle.clone(),
+        )
+        .await?;
+
+        let mut shards_holder = self.shards_holder.write().await;
+        if !needs_local(shards_holder.get_shard(&shard_id)) {
+            drop(shards_holder);
+            local_shard.before_drop().await;
+            drop_and_delete_from_disk(local_shard).await?;
+            return Ok(());
+        }
+        if let Some(Shard::ReplicaSet(replica_set)) = shards_holder.get_mut_shard(&shard_id) {
+            replica_set.set_local(local_shard, ReplicaState::Partial)?;
+        }
+        drop(shards_holder);
+
+        drop_old_shards(&self.path, shard_id).await
+    }
+
     /// Handles finishing of the shard transfer.
     ///
     /// 1. Removes transfer state from list of active transfers.
@@ -380,14 +917,22 @@ impl Collection {
     /// 3. Converts proxy shard -> remote shard
     /// 4. Promotes temporary shard to local shard.
     /// 5. Point remote shard to new location
+    /// 6. Marks receiving replica as active
     ///
     /// Returns true if state was changed, false otherwise.
     pub async fn finish_shard_transfer(&self, transfer: ShardTransfer) -> CollectionResult<bool> {
-        let finish_was_registered = self
-            .shards_holder
-            .write()
-            .await
-            .register_finish_transfer(&transfer)?;
+        let (finish_was_registered, is_replica_set) = {
+            let shards_holder = self.shards_holder.read().await;
+            shards_holder.set_transfer_target_state(&transfer, ReplicaState::Active)?;
+            let is_replica_set = matches!(
+                shards_holder.get_shard(&transfer.shard_id),
+                Some(Shard::ReplicaSet(_))
+            );
+            (
+                shards_holder.register_finish_transfer(&transfer)?,
+                is_replica_set,
+            )
+        };
         let transfer_finished = self
             .transfer_tasks
             .lock()
@@ -421,16 +966,19 @@ impl Collection {
         )
         .await?;
 
-        // Transfer task and proxy should exist on the same node
-        debug_assert_eq!(transfer_finished, proxy_promoted);
+        // Transfer task and proxy should exist on the same node, replica sets are sent without proxy
+        debug_assert!(is_replica_set || transfer_finished == proxy_promoted);
         log::debug!("finish_was_registered: {}", finish_was_registered);
         log::debug!("transfer_finished: {}", transfer_finished);
         log::debug!("proxy_promoted: {}", proxy_promoted);
         log::debug!("shard_promoted: {}", shard_promoted);
         log::debug!("remote_shard_rerouted: {}", remote_shard_rerouted);
 
-        let something_changed =
-            finish_was_registered && (proxy_promoted || shard_promoted || remote_shard_rerouted);
+        let something_changed = finish_was_registered
+            && (is_replica_set || proxy_promoted || shard_promoted || remote_shard_rerouted);
+
+        // Shards are only upgraded to replica sets after their transfers
+        self.upgrade_to_replica_sets().await?;
 
         Ok(something_changed)
     }
@@ -441,12 +989,13 @@ impl Collection {
     /// 2. Stop transfer task
     /// 3. Unwrap the proxy
     /// 4. Remove temp shard
+    /// 5. Marks receiving replica as dead
     pub async fn abort_shard_transfer(&self, transfer: ShardTransfer) -> CollectionResult<bool> {
-        let finish_was_registered = self
-            .shards_holder
-            .write()
-            .await
-            .register_finish_transfer(&transfer)?;
+        let finish_was_registered = {
+            let shards_holder = self.shards_holder.read().await;
+            shards_holder.set_transfer_target_state(&transfer, ReplicaState::Dead)?;
+            shards_holder.register_finish_transfer(&transfer)?
+        };
         let transfer_finished = self
             .transfer_tasks
             .lock()
@@ -464,6 +1013,9 @@ impl Collection {
         let changed_something =
             finish_was_registered && (transfer_finished || proxy_unwrapped || temp_shard_removed);
 
+        // Shards are only upgraded to replica sets after their transfers
+        self.upgrade_to_replica_sets().await?;
+
         Ok(changed_something)
     }
 
@@ -502,7 +1054,9 @@ impl Collection {
                 Shard::ForwardProxy(_) => {
                     debug_assert!(false, "Proxy shard should not be temporary");
                 }
-                Shard::ReplicaSet(_) => todo!(),
+                Shard::ReplicaSet(_) => {
+                    debug_assert!(false, "Replica set should not be temporary");
+                }
             }
         }
 
@@ -515,6 +1069,7 @@ impl Collection {
             self.id.clone(),
             &temporary_shard_path,
             self.config.clone(),
+            self.resource_throttle.clone(),
         )
         .await?;
 
@@ -535,10 +1090,17 @@ impl Collection {
     ) -> CollectionResult<UpdateResult> {
         let shard_holder_guard = self.shards_holder.read().await;
 
-        let target_shards = shard_holder_guard.target_shards(Some(shard_selection))?;
+        let target_shards =
+            shard_holder_guard.target_shards(&ShardSelectorInternal::ShardId(shard_selection))?;
         let mut res = None;
         for target_shard in target_shards {
-            res = Some(target_shard.get().update(operation.clone(), wait).await?);
+            res = match target_shard {
+                // Peers update their own replica, so the update is not sent to other replicas again
+                Shard::ReplicaSet(replica_set) => {
+                    replica_set.update_local(operation.clone(), wait).await?
+                }
+                shard => Some(shard.get().update(operation.clone(), wait).await?),
+            };
         }
         if let Some(res) = res {
             Ok(res)
@@ -550,54 +1112,107 @@ impl Collection {
         }
     }
 
+    /// Handle collection updates from clients.
+    ///
+    /// Operation is split between the shards by the hash ring, unless the client selected shards.
+    /// Selected shards receive the whole operation, so points can only be upserted into a single shard.
     pub async fn update_from_client(
         &self,
         operation: CollectionUpdateOperations,
+        shard_selection: &ShardSelectorInternal,
         wait: bool,
     ) -> CollectionResult<UpdateResult> {
         operation.validate()?;
+        if shard_selection.selected_replica().is_some() {
+            return Err(CollectionError::bad_shard_selection(
+                "Updates are applied to all replicas of a shard, a single replica can't be selected"
+                    .to_string(),
+            ));
+        }
 
         let mut results = {
             let shards_holder = self.shards_holder.read().await;
-            let shard_to_op = shards_holder.split_by_shard(operation);
+            let shard_to_op = match shard_selection {
+                ShardSelectorInternal::Selected(selector)
+                | ShardSelectorInternal::Session {
+                    selector: Some(selector),
+                    ..
+                } => {
+                    let shard_ids = shards_holder.select_shards(selector)?;
+                    if shard_ids.len() > 1 && operation.is_insert_operation() {
+                        return Err(CollectionError::bad_shard_selection(
+                            "Points can only be upserted into a single shard".to_string(),
+                        ));
+                    }
+                    shard_ids
+                        .iter()
+                        .filter_map(|shard_id| {
+                            let shard = shards_holder.get_shard(shard_id)?;
+                            Some((*shard_id, shard, operation.clone()))
+                        })
+                        .collect()
+                }
+                _ => shards_holder.split_by_shard(operation),
+            };
 
-            let shard_requests = shard_to_op
-                .into_iter()
-                .map(move |(shard, operation)| shard.get().update(operation, wait));
+            let shard_requests =
+                shard_to_op
+                    .into_iter()
+                    .map(move |(shard_id, shard, operation)| async move {
+                        (shard_id, shard.get().update(operation, wait).await)
+                    });
             join_all(shard_requests).await
         };
 
         let with_error = results
             .iter()
-            .filter(|result| matches!(result, Err(_)))
+            .filter(|(_, result)| matches!(result, Err(_)))
             .count();
 
         // one request per shard
         let result_len = results.len();
 
         if with_error > 0 {
+            let shard_results = results
+                .iter()
+                .map(|(shard_id, result)| ShardUpdateResult {
+                    shard_id: *shard_id,
+                    status: result.as_ref().ok().map(|res| res.status),
+                    error: result.as_ref().err().map(|err| err.to_string()),
+                })
+                .collect();
             let first_err = results
                 .into_iter()
-                .find(|result| matches!(result, Err(_)))
+                .find_map(|(_, result)| result.err())
                 .unwrap();
             // inconsistent if only a subset of the requests fail - one request per shard.
             if with_error < result_len {
-                first_err.map_err(|err| {
-                    // compute final status code based on the first error
-                    // e.g. a partially successful batch update failing because of bad input is a client error
-                    CollectionError::InconsistentShardFailure {
-                        shards_total: result_len as u32, // report only the number of shards that took part in the update
-                        shards_failed: with_error as u32,
-                        first_err: Box::new(err),
-                    }
+                // compute final status code based on the first error
+                // e.g. a partially successful batch update failing because of bad input is a client error
+                Err(CollectionError::InconsistentShardFailure {
+                    shards_total: result_len as u32, // report only the number of shards that took part in the update
+                    shards_failed: with_error as u32,
+                    first_err: Box::new(first_err),
+                    shard_results,
                 })
             } else {
                 // all requests per shard failed - propagate first error (assume there are all the same)
-                first_err
+                Err(first_err)
             }
         } else {
             // At least one result is always present.
-            results.pop().unwrap()
+            // Session tokens are combined, as the parts of the update are sent to different shards.
+            let mut result = results.pop().unwrap().1?;
+            let tokens = results
+                .into_iter()
+                .filter_map(|(_, other)| other.ok()?.session_token);
+            for token in tokens {
+                result
+                    .session_token
+                    .get_or_insert_with(Default::default)
+                    .merge(token);
+            }
+            Ok(result)
         }
     }
 
@@ -605,7 +1220,7 @@ impl Collection {
         &self,
         request: RecommendRequest,
         search_runtime_handle: &Handle,
-        shard_selection: Option<ShardId>,
+        shard_selection: &ShardSelectorInternal,
     ) -> CollectionResult<Vec<ScoredPoint>> {
         if request.limit == 0 {
             return Ok(vec![]);
@@ -624,7 +1239,7 @@ impl Collection {
         &self,
         request_batch: RecommendRequestBatch,
         search_runtime_handle: &Handle,
-        shard_selection: Option<ShardId>,
+        shard_selection: &ShardSelectorInternal,
     ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
         // shortcuts batch if all requests with limit=0
         if request_batch.searches.iter().all(|s| s.limit == 0) {
@@ -650,6 +1265,7 @@ impl Collection {
                     ids: all_reference_vectors_ids.into_iter().collect(),
                     with_payload: Some(WithPayloadInterface::Bool(true)),
                     with_vector: true.into(),
+                    max_payload_bytes: None,
                 },
                 shard_selection,
             )
@@ -663,6 +1279,22 @@ impl Collection {
                 Some(UsingVector::Name(name)) => name,
             };
 
+            // Sub-vectors of multivectors can't be averaged position-wise
+            let is_multivector = self
+                .config
+                .read()
+                .await
+                .params
+                .get_vector_params(&vector_name)
+                .map_or(false, |params| params.multivector_config.is_some());
+            if is_multivector {
+                return Err(CollectionError::BadRequest {
+                    description: format!(
+                        "Recommendation is not supported for multivector {vector_name}"
+                    ),
+                });
+            }
+
             //let rec_vectors = rec.get
             let mut all_vectors_map = HashMap::new();
 
@@ -743,6 +1375,7 @@ impl Collection {
                 limit: request.limit,
                 score_threshold: request.score_threshold,
                 offset: request.offset,
+                max_payload_bytes: request.max_payload_bytes,
             };
             searches.push(search_request)
         }
@@ -757,7 +1390,7 @@ impl Collection {
         &self,
         request: SearchRequestBatch,
         search_runtime_handle: &Handle,
-        shard_selection: Option<ShardId>,
+        shard_selection: &ShardSelectorInternal,
     ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
         // shortcuts batch if all requests with limit=0
         if request.searches.iter().all(|s| s.limit == 0) {
@@ -781,6 +1414,11 @@ impl Collection {
         });
 
         let metadata_required = is_payload_required || with_vectors;
+        let request_limits: Vec<Option<usize>> = request
+            .searches
+            .iter()
+            .map(|search| search.max_payload_bytes)
+            .collect();
 
         let sum_limits: usize = request.searches.iter().map(|s| s.limit).sum();
         let sum_offsets: usize = request.searches.iter().map(|s| s.offset).sum();
@@ -793,7 +1431,7 @@ impl Collection {
         let is_required_transfer_large_enough =
             require_transfers > used_transfers * PAYLOAD_TRANSFERS_FACTOR_THRESHOLD;
 
-        if metadata_required && is_required_transfer_large_enough {
+        let mut results = if metadata_required && is_required_transfer_large_enough {
             // If there is a significant offset, we need to retrieve the whole result
             // set without payload first and then retrieve the payload.
             // It is required to do this because the payload might be too large to send over the
@@ -826,20 +1464,27 @@ impl Collection {
                         shard_selection,
                     )
                 });
-            try_join_all(filled_results).await
+            try_join_all(filled_results).await?
         } else {
-            let result = self
-                ._search_batch(request, search_runtime_handle, shard_selection)
-                .await?;
-            Ok(result)
+            self._search_batch(request, search_runtime_handle, shard_selection)
+                .await?
+        };
+
+        if shard_selection.is_from_client() {
+            for (result, request_limit) in results.iter_mut().zip(request_limits) {
+                if let Some(max_bytes) = self.max_payload_bytes(request_limit).await {
+                    limit_scored_points_payload(result, max_bytes);
+                }
+            }
         }
+        Ok(results)
     }
 
     pub async fn _search_batch(
         &self,
         request: SearchRequestBatch,
         search_runtime_handle: &Handle,
-        shard_selection: Option<ShardId>,
+        shard_selection: &ShardSelectorInternal,
     ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
         let batch_size = request.searches.len();
         let request = Arc::new(request);
@@ -847,10 +1492,10 @@ impl Collection {
         // query all shards concurrently
         let mut all_searches_res = {
             let shard_holder = self.shards_holder.read().await;
-            let target_shards = shard_holder.target_shards(shard_selection)?;
+            let target_shards = shard_holder.read_targets(shard_selection).await?;
             let all_searches = target_shards
                 .iter()
-                .map(|shard| shard.get().search(request.clone(), search_runtime_handle));
+                .map(|shard| shard.search(request.clone(), search_runtime_handle));
             try_join_all(all_searches).await?
         };
 
@@ -866,9 +1511,7 @@ impl Collection {
             .into_iter()
             .zip(request.searches.iter())
             .map(|(res, request)| {
-                let distance = collection_params
-                    .get_vector_params(request.vector.get_name())?
-                    .distance;
+                let distance = collection_params.get_search_distance(&request.vector)?;
                 let mut top_res = match distance.distance_order() {
                     Order::LargeBetter => {
                         peek_top_largest_iterable(res, request.limit + request.offset)
@@ -879,7 +1522,7 @@ impl Collection {
                 };
                 // Remove `offset` from top result only for client requests
                 // to avoid applying `offset` twice in distributed mode.
-                if shard_selection.is_none() && request.offset > 0 {
+                if shard_selection.is_from_client() && request.offset > 0 {
                     if top_res.len() >= request.offset {
                         // Panics if the end point > length of the vector.
                         top_res.drain(..request.offset);
@@ -899,12 +1542,13 @@ impl Collection {
         search_result: Vec<ScoredPoint>,
         with_payload: Option<WithPayloadInterface>,
         with_vector: WithVector,
-        shard_selection: Option<ShardId>,
+        shard_selection: &ShardSelectorInternal,
     ) -> CollectionResult<Vec<ScoredPoint>> {
         let retrieve_request = PointRequest {
             ids: search_result.iter().map(|x| x.id).collect(),
             with_payload,
             with_vector,
+            max_payload_bytes: None,
         };
         let retrieved_records = self.retrieve(retrieve_request, shard_selection).await?;
         let mut records_map: HashMap<ExtendedPointId, Record> = retrieved_records
@@ -920,6 +1564,7 @@ impl Collection {
                 records_map.remove(&scored_point.id).map(|record| {
                     scored_point.payload = record.payload;
                     scored_point.vector = record.vector;
+                    scored_point.payload_truncated = record.payload_truncated;
                     scored_point
                 })
             })
@@ -931,11 +1576,12 @@ impl Collection {
         &self,
         request: SearchRequest,
         search_runtime_handle: &Handle,
-        shard_selection: Option<ShardId>,
+        shard_selection: &ShardSelectorInternal,
     ) -> CollectionResult<Vec<ScoredPoint>> {
         if request.limit == 0 {
             return Ok(vec![]);
         }
+        let request_limit = request.max_payload_bytes;
         // search is a special case of search_batch with a single batch
         let request_batch = SearchRequestBatch {
             searches: vec![request],
@@ -943,13 +1589,19 @@ impl Collection {
         let results = self
             ._search_batch(request_batch, search_runtime_handle, shard_selection)
             .await?;
-        Ok(results.into_iter().next().unwrap())
+        let mut result = results.into_iter().next().unwrap();
+        if shard_selection.is_from_client() {
+            if let Some(max_bytes) = self.max_payload_bytes(request_limit).await {
+                limit_scored_points_payload(&mut result, max_bytes);
+            }
+        }
+        Ok(result)
     }
 
     pub async fn scroll_by(
         &self,
         request: ScrollRequest,
-        shard_selection: Option<ShardId>,
+        shard_selection: &ShardSelectorInternal,
     ) -> CollectionResult<ScrollResult> {
         let default_request = ScrollRequest::default();
 
@@ -971,27 +1623,16 @@ impl Collection {
 
         // Needed to return next page offset.
         let limit = limit + 1;
-        let retrieved_points: Vec<_> = {
-            let shards_holder = self.shards_holder.read().await;
-            let target_shards = shards_holder.target_shards(shard_selection)?;
-            let scroll_futures = target_shards.into_iter().map(|shard| {
-                shard.get().scroll_by(
-                    offset,
-                    limit,
-                    &with_payload_interface,
-                    &with_vector,
-                    request.filter.as_ref(),
-                )
-            });
-
-            try_join_all(scroll_futures).await?
-        };
-        let mut points: Vec<_> = retrieved_points
-            .into_iter()
-            .flatten()
-            .sorted_by_key(|point| point.id)
-            .take(limit)
-            .collect();
+        let mut points = self
+            .scroll_by_id_merge(
+                offset,
+                limit,
+                &with_payload_interface,
+                &with_vector,
+                request.filter.as_ref(),
+                shard_selection,
+            )
+            .await?;
 
         let next_page_offset = if points.len() < limit {
             // This was the last page
@@ -1000,25 +1641,100 @@ impl Collection {
             // remove extra point, it would be a first point of the next page
             Some(points.pop().unwrap().id)
         };
+
+        self.limit_records_payload(&mut points, request.max_payload_bytes, shard_selection)
+            .await;
+
         Ok(ScrollResult {
             points,
             next_page_offset,
         })
     }
 
-    pub async fn count(
+    /// Merges points of the shards in the order of IDs.
+    ///
+    /// Shards are read lazily, page by page, so only a fraction of `limit` points is requested
+    /// from each shard up front, and more pages are only read from shards which come first in order.
+    async fn scroll_by_id_merge(
         &self,
-        request: CountRequest,
-        shard_selection: Option<ShardId>,
-    ) -> CollectionResult<CountResult> {
-        let request = Arc::new(request);
+        offset: Option<ExtendedPointId>,
+        limit: usize,
+        with_payload_interface: &WithPayloadInterface,
+        with_vector: &WithVector,
+        filter: Option<&Filter>,
+        shard_selection: &ShardSelectorInternal,
+    ) -> CollectionResult<Vec<Record>> {
+        let shards_holder = self.shards_holder.read().await;
+        let target_shards = shards_holder.read_targets(shard_selection).await?;
+        if target_shards.is_empty() {
+            return Ok(vec![]);
+        }
+        let page_size = max(
+            (limit + target_shards.len() - 1) / target_shards.len(),
+            MIN_SCROLL_PAGE_SIZE,
+        )
+        .min(limit);
+
+        let read_page = |shard, offset| {
+            ShardScrollCursor::read(
+                shard,
+                offset,
+                page_size,
+                with_payload_interface,
+                with_vector,
+                filter,
+            )
+        };
 
-        let counts: Vec<_> = {
-            let shards_holder = self.shards_holder.read().await;
-            let target_shards = shards_holder.target_shards(shard_selection)?;
+        let mut cursors =
+            try_join_all(target_shards.iter().map(|&shard| read_page(shard, offset))).await?;
+
+        let mut points = Vec::with_capacity(limit);
+        while points.len() < limit {
+            // Shard cursors run dry one by one, as the merge consumes their points
+            let exhausted: Vec<_> = cursors
+                .iter()
+                .enumerate()
+                .filter(|(_, cursor)| cursor.points.is_empty() && cursor.next_offset.is_some())
+                .map(|(idx, _)| idx)
+                .collect();
+            if !exhausted.is_empty() {
+                let pages = try_join_all(
+                    exhausted
+                        .iter()
+                        .map(|&idx| read_page(target_shards[idx], cursors[idx].next_offset)),
+                )
+                .await?;
+                for (idx, page) in exhausted.into_iter().zip(pages) {
+                    cursors[idx] = page;
+                }
+            }
+
+            let next_cursor = cursors
+                .iter_mut()
+                .filter(|cursor| !cursor.points.is_empty())
+                .min_by_key(|cursor| cursor.points[0].id);
+            match next_cursor {
+                Some(cursor) => points.extend(cursor.points.pop_front()),
+                None => break,
+            }
+        }
+        Ok(points)
+    }
+
+    pub async fn count(
+        &self,
+        request: CountRequest,
+        shard_selection: &ShardSelectorInternal,
+    ) -> CollectionResult<CountResult> {
+        let request = Arc::new(request);
+
+        let counts: Vec<_> = {
+            let shards_holder = self.shards_holder.read().await;
+            let target_shards = shards_holder.read_targets(shard_selection).await?;
             let count_futures = target_shards
                 .into_iter()
-                .map(|shard| shard.get().count(request.clone()));
+                .map(|shard| shard.count(request.clone()));
             try_join_all(count_futures).await?.into_iter().collect()
         };
 
@@ -1027,10 +1743,87 @@ impl Collection {
         Ok(aggregated_count)
     }
 
+    /// Estimate resources of the payload index by the values of the first points of the collection
+    pub async fn estimate_field_index(
+        &self,
+        request: FieldIndexEstimationRequest,
+        shard_selection: &ShardSelectorInternal,
+    ) -> CollectionResult<FieldIndexEstimation> {
+        request
+            .field_schema
+            .validate()
+            .map_err(|description| CollectionError::BadRequest { description })?;
+        let data_type = PayloadIndexInfo::from(request.field_schema.clone()).data_type;
+        let sample_size = request
+            .sample_size
+            .unwrap_or(DEFAULT_INDEX_ESTIMATION_SAMPLE_SIZE);
+
+        let points_count = self
+            .count(
+                CountRequest {
+                    filter: None,
+                    exact: false,
+                },
+                shard_selection,
+            )
+            .await?
+            .count;
+        // Payload selector only supports top-level keys
+        let payload_key = request
+            .field_name
+            .split('.')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let sample = self
+            .scroll_by(
+                ScrollRequest {
+                    limit: Some(sample_size),
+                    with_payload: Some(WithPayloadInterface::Selector(
+                        PayloadSelector::new_include(vec![payload_key]),
+                    )),
+                    max_payload_bytes: Some(usize::MAX),
+                    ..Default::default()
+                },
+                shard_selection,
+            )
+            .await?
+            .points;
+
+        let sample_values = sample
+            .iter()
+            .map(|point| {
+                point
+                    .payload
+                    .as_ref()
+                    .and_then(|payload| payload.get_path_value(&request.field_name))
+            })
+            .collect_vec();
+        let sample_stats = FieldValuesStats::collect(
+            data_type,
+            sample_values.iter().map(|value| value.as_deref()),
+        );
+        let stats = sample_stats.extrapolate(points_count);
+        let resources = IndexResourceEstimation::estimate(data_type, &stats).ok_or_else(|| {
+            CollectionError::BadRequest {
+                description: format!("Estimation of {:?} index is not supported", data_type),
+            }
+        })?;
+        Ok(FieldIndexEstimation {
+            points_count,
+            sampled_points_count: sample_stats.points_count,
+            indexed_points_count: stats.points_with_values,
+            values_count: stats.values_count,
+            distinct_values_count: stats.distinct_values_count,
+            ram_usage_bytes: resources.ram_usage_bytes,
+            disk_usage_bytes: resources.disk_usage_bytes,
+        })
+    }
+
     pub async fn retrieve(
         &self,
         request: PointRequest,
-        shard_selection: Option<ShardId>,
+        shard_selection: &ShardSelectorInternal,
     ) -> CollectionResult<Vec<Record>> {
         let with_payload_interface = request
             .with_payload
@@ -1040,68 +1833,99 @@ impl Collection {
         let request = Arc::new(request);
         let all_shard_collection_results = {
             let shard_holder = self.shards_holder.read().await;
-            let target_shards = shard_holder.target_shards(shard_selection)?;
-            let retrieve_futures = target_shards.into_iter().map(|shard| {
-                shard
-                    .get()
-                    .retrieve(request.clone(), &with_payload, &request.with_vector)
-            });
+            let target_shards = shard_holder.read_targets(shard_selection).await?;
+            let retrieve_futures = target_shards
+                .into_iter()
+                .map(|shard| shard.retrieve(request.clone(), &with_payload, &request.with_vector));
             try_join_all(retrieve_futures).await?
         };
-        let points = all_shard_collection_results.into_iter().flatten().collect();
+        let mut points: Vec<_> = all_shard_collection_results.into_iter().flatten().collect();
+
+        self.limit_records_payload(&mut points, request.max_payload_bytes, shard_selection)
+            .await;
         Ok(points)
     }
 
-    pub async fn update_params_from_diff(
-        &self,
-        params_diff: CollectionParamsDiff,
-    ) -> CollectionResult<()> {
-        let mut config = self.config.write().await;
-        let old_repl_factor = config.params.replication_factor;
-        config.params = params_diff.update(&config.params)?;
-        self.handle_repl_factor_change(old_repl_factor, config.params.replication_factor);
-        Ok(())
+    /// Size limit of the payloads in a response.
+    /// The smallest of the request and collection limits is used.
+    async fn max_payload_bytes(&self, request_limit: Option<usize>) -> Option<usize> {
+        let collection_limit = self.config.read().await.params.max_response_payload_bytes;
+        match (request_limit, collection_limit) {
+            (Some(request_limit), Some(collection_limit)) => {
+                Some(request_limit.min(collection_limit))
+            }
+            (request_limit, collection_limit) => request_limit.or(collection_limit),
+        }
     }
 
-    pub fn handle_repl_factor_change(&self, old: NonZeroU32, new: NonZeroU32) {
-        if old != new {
-            // TODO: remove or add replicas. In case of replica addition:
-            // 1. Create and mark them as inactive
-            // 2. Copy data
-            // 3. Mark them as active
+    /// Limits the size of payloads in the response to a client request.
+    /// Internal requests are not limited, the limit is applied by the peer which received the request.
+    async fn limit_records_payload(
+        &self,
+        records: &mut [Record],
+        request_limit: Option<usize>,
+        shard_selection: &ShardSelectorInternal,
+    ) {
+        if !shard_selection.is_from_client() {
+            return;
+        }
+        if let Some(max_bytes) = self.max_payload_bytes(request_limit).await {
+            limit_payload_size(
+                records
+                    .iter_mut()
+                    .map(|record| (&mut record.payload, &mut record.payload_truncated)),
+                max_bytes,
+            );
         }
     }
 
-    /// Updates shard optimization params:
-    /// - Saves new params on disk
-    /// - Stops existing optimization loop
-    /// - Runs new optimizers with new params
-    pub async fn update_optimizer_params_from_diff(
+    /// Applies the diff of the config and increments its version under the same lock.
+    /// If `expected_version` is set, fails if the current version differs,
+    /// so that concurrent updates do not overwrite each other.
+    /// The config is left unchanged, if the version does not match or the diff is invalid.
+    pub async fn update_config_from_diff(
         &self,
-        optimizer_config_diff: OptimizersConfigDiff,
-    ) -> CollectionResult<()> {
-        {
+        expected_version: Option<u64>,
+        optimizers_config_diff: Option<OptimizersConfigDiff>,
+        params_diff: Option<CollectionParamsDiff>,
+    ) -> CollectionResult<u64> {
+        let updates_optimizers = optimizers_config_diff.is_some();
+        let version = {
             let mut config = self.config.write().await;
-            config.optimizer_config =
-                DiffConfig::update(optimizer_config_diff, &config.optimizer_config)?;
-        }
-        {
-            let shard_holder = self.shards_holder.read().await;
-            for shard in shard_holder
-                .all_shards()
-                .chain(shard_holder.all_temporary_shards())
+            config.check_version(expected_version)?;
+            let optimizer_config = match optimizers_config_diff {
+                None => config.optimizer_config.clone(),
+                Some(diff) => DiffConfig::update(diff, &config.optimizer_config)?,
+            };
+            let params = match params_diff {
+                None => config.params.clone(),
+                Some(diff) => diff.update(&config.params)?,
+            };
+
+            let old_repl_factor = config.params.replication_factor;
+            config.optimizer_config = optimizer_config;
+            config.params = params;
+            config.version += 1;
+            self.handle_repl_factor_change(old_repl_factor, config.params.replication_factor);
             {
-                match shard {
-                    Shard::Local(shard) => shard.on_optimizer_config_update().await?,
-                    Shard::Proxy(shard) => shard.on_optimizer_config_update().await?,
-                    Shard::ForwardProxy(shard) => shard.on_optimizer_config_update().await?,
-                    Shard::Remote(_) => {} // Do nothing for remote shards
-                    Shard::ReplicaSet(_) => todo!(),
-                }
+                let shards_holder = self.shards_holder.read().await;
+                shards_holder.set_read_fan_out_factor(config.params.read_fan_out_factor);
+                shards_holder.set_read_consistency(config.params.read_consistency);
             }
+            config.save(&self.path)?;
+            config.version
+        };
+        if updates_optimizers {
+            self.restart_optimizers().await?;
+        }
+        Ok(version)
+    }
+
+    pub fn handle_repl_factor_change(&self, old: NonZeroU32, new: NonZeroU32) {
+        // New replicas are synced by transfers, see `suggest_shard_replica_changes`
+        if old > new {
+            // TODO: remove replicas
         }
-        self.config.read().await.save(&self.path)?;
-        Ok(())
     }
 
     /// Updates shard optimization params:
@@ -1116,30 +1940,37 @@ impl Collection {
             let mut config = self.config.write().await;
             config.optimizer_config = optimizer_config;
         }
+        self.restart_optimizers().await?;
+        self.config.read().await.save(&self.path)?;
+        Ok(())
+    }
+
+    /// Stops existing optimization loops of the shards and runs new optimizers with the current config
+    async fn restart_optimizers(&self) -> CollectionResult<()> {
+        let shard_holder = self.shards_holder.read().await;
+        for shard in shard_holder
+            .all_shards()
+            .chain(shard_holder.all_temporary_shards())
         {
-            let shard_holder = self.shards_holder.read().await;
-            for shard in shard_holder
-                .all_shards()
-                .chain(shard_holder.all_temporary_shards())
-            {
-                match shard {
-                    Shard::Local(shard) => shard.on_optimizer_config_update().await?,
-                    Shard::Remote(_) => {} // Do nothing for remote shards
-                    Shard::Proxy(proxy) => proxy.on_optimizer_config_update().await?,
-                    Shard::ForwardProxy(proxy) => proxy.on_optimizer_config_update().await?,
-                    Shard::ReplicaSet(_) => todo!(),
-                }
+            match shard {
+                Shard::Local(shard) => shard.on_optimizer_config_update().await?,
+                Shard::Remote(_) => {} // Do nothing for remote shards
+                Shard::Proxy(proxy) => proxy.on_optimizer_config_update().await?,
+                Shard::ForwardProxy(proxy) => proxy.on_optimizer_config_update().await?,
+                Shard::ReplicaSet(replica_set) => replica_set.on_optimizer_config_update().await?,
             }
         }
-        self.config.read().await.save(&self.path)?;
         Ok(())
     }
 
-    pub async fn info(&self, shard_selection: Option<ShardId>) -> CollectionResult<CollectionInfo> {
+    pub async fn info(
+        &self,
+        shard_selection: &ShardSelectorInternal,
+    ) -> CollectionResult<CollectionInfo> {
         let (all_shard_collection_results, mut info) = {
             let shards_holder = self.shards_holder.read().await;
 
-            let target_shards = shards_holder.target_shards(shard_selection)?;
+            let target_shards = shards_holder.read_targets(shard_selection).await?;
 
             let first_shard =
                 *target_shards
@@ -1148,11 +1979,8 @@ impl Collection {
                         error: "There are no shards for selected collection".to_string(),
                     })?;
 
-            let info = first_shard.get().info().await?;
-            let info_futures = target_shards
-                .into_iter()
-                .skip(1)
-                .map(|shard| shard.get().info());
+            let info = first_shard.info().await?;
+            let info_futures = target_shards.into_iter().skip(1).map(|shard| shard.info());
 
             (try_join_all(info_futures).await?, info)
         };
@@ -1167,12 +1995,31 @@ impl Collection {
                 info.indexed_vectors_count += shard_info.indexed_vectors_count;
                 info.points_count += shard_info.points_count;
                 info.segments_count += shard_info.segments_count;
-                info.payload_schema
-                    .extend(shard_info.payload_schema.drain());
+                for (key, val) in shard_info.payload_schema.drain() {
+                    merge_index_info(&mut info.payload_schema, key, val);
+                }
             });
         Ok(info)
     }
 
+    /// Wait until the payload index on `field_name` is built in all segments of the selected shards.
+    /// Returns immediately if the field is not indexed.
+    pub async fn wait_field_index_ready(
+        &self,
+        field_name: &str,
+        shard_selection: &ShardSelectorInternal,
+    ) -> CollectionResult<()> {
+        loop {
+            let info = self.info(shard_selection).await?;
+            match info.payload_schema.get(field_name) {
+                Some(index_info) if index_info.status == PayloadIndexStatus::Building => {
+                    tokio::time::sleep(INDEX_STATUS_CHECK_INTERVAL).await;
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
     pub async fn cluster_info(&self, peer_id: PeerId) -> CollectionResult<CollectionClusterInfo> {
         let shards_holder = self.shards_holder.read().await;
         let shard_count = shards_holder.len();
@@ -1193,11 +2040,13 @@ impl Collection {
                     local_shards.push(LocalShardInfo {
                         shard_id,
                         points_count,
+                        replication_lag: None,
                     })
                 }
                 Shard::Remote(rs) => remote_shards.push(RemoteShardInfo {
                     shard_id,
                     peer_id: rs.peer_id,
+                    replication_lag: None,
                 }),
                 Shard::Proxy(ls) => {
                     let count_result = ls.count(count_request.clone()).await?;
@@ -1205,6 +2054,7 @@ impl Collection {
                     local_shards.push(LocalShardInfo {
                         shard_id,
                         points_count,
+                        replication_lag: None,
                     })
                 }
                 Shard::ForwardProxy(ls) => {
@@ -1213,13 +2063,35 @@ impl Collection {
                     local_shards.push(LocalShardInfo {
                         shard_id,
                         points_count,
+                        replication_lag: None,
                     })
                 }
-                Shard::ReplicaSet(_) => todo!(),
+                Shard::ReplicaSet(replicas) => {
+                    let mut peer_ids = replicas.peer_ids();
+                    peer_ids.sort_unstable();
+                    for peer_id in peer_ids {
+                        let replication_lag = Some(replicas.replication_lag(&peer_id));
+                        if peer_id != replicas.this_peer_id() {
+                            remote_shards.push(RemoteShardInfo {
+                                shard_id,
+                                peer_id,
+                                replication_lag,
+                            });
+                        } else if let Some(count_result) =
+                            replicas.count_local(count_request.clone()).await?
+                        {
+                            local_shards.push(LocalShardInfo {
+                                shard_id,
+                                points_count: count_result.count,
+                                replication_lag,
+                            });
+                        }
+                    }
+                }
             }
         }
         // extract shard transfers info
-        for shard_transfer in shards_holder.get_shard_transfers() {
+        for shard_transfer in shards_holder.shard_transfers() {
             let shard_id = shard_transfer.shard_id;
             let to = shard_transfer.to;
             let from = shard_transfer.from;
@@ -1241,6 +2113,51 @@ impl Collection {
         Ok(info)
     }
 
+    /// Estimate which fraction of points would move between shards,
+    /// if the collection had `target_shard_count` shards.
+    /// New shards get ids after the largest existing one, removed shards are the ones with the largest ids.
+    pub async fn estimate_shard_movement(
+        &self,
+        target_shard_count: usize,
+    ) -> CollectionResult<ShardMovementEstimate> {
+        if target_shard_count == 0 {
+            return Err(CollectionError::BadInput {
+                description: "Number of shards must be positive".to_string(),
+            });
+        }
+        let hash_ring = self.config.read().await.params.hash_ring;
+
+        let mut shard_ids: Vec<ShardId> = {
+            let shards_holder = self.shards_holder.read().await;
+            shards_holder
+                .get_shards()
+                .map(|(shard_id, _)| *shard_id)
+                .collect()
+        };
+        shard_ids.sort_unstable();
+        let shard_count = shard_ids.len();
+
+        let mut current_ring = build_hash_ring(hash_ring);
+        for shard_id in &shard_ids {
+            current_ring.add(*shard_id);
+        }
+
+        let next_shard_id = shard_ids.last().map_or(0, |max_id| max_id + 1);
+        let mut target_ring = build_hash_ring(hash_ring);
+        shard_ids
+            .into_iter()
+            .chain(next_shard_id..)
+            .take(target_shard_count)
+            .for_each(|shard_id| target_ring.add(shard_id));
+
+        Ok(ShardMovementEstimate {
+            hash_ring,
+            shard_count,
+            target_shard_count,
+            moved_points_fraction: current_ring.movement_fraction(&target_ring),
+        })
+    }
+
     pub async fn before_drop(&mut self) {
         self.shards_holder.write().await.before_drop().await;
         self.before_drop_called = true
@@ -1255,7 +2172,7 @@ impl Collection {
                 .map(|(shard_id, shard)| {
                     let shard_info = match shard {
                         Shard::ReplicaSet(replicas) => ShardInfo::ReplicaSet {
-                            replicas: replicas.replica_state.clone(),
+                            replicas: replicas.replica_states(),
                         },
                         shard => ShardInfo::Single(
                             *shard
@@ -1267,7 +2184,7 @@ impl Collection {
                     (*shard_id, shard_info)
                 })
                 .collect(),
-            transfers: (*shards_holder.shard_transfers).clone(),
+            transfers: shards_holder.shard_transfers(),
         }
     }
 
@@ -1291,20 +2208,23 @@ impl Collection {
     }
 
     pub async fn list_snapshots(&self) -> CollectionResult<Vec<SnapshotDescription>> {
-        list_snapshots_in_directory(&self.snapshots_path).await
+        self.snapshot_storage
+            .list_snapshots(&self.snapshots_path)
+            .await
     }
 
-    pub async fn get_snapshot_path(&self, snapshot_name: &str) -> CollectionResult<PathBuf> {
-        let snapshot_path = self.snapshots_path.join(snapshot_name);
-        if !snapshot_path.exists() {
-            return Err(CollectionError::NotFound {
-                what: format!("Snapshot {}", snapshot_name),
-            });
-        }
-        Ok(snapshot_path)
+    pub async fn get_snapshot(&self, snapshot_name: &str) -> CollectionResult<SnapshotData> {
+        self.snapshot_storage
+            .get_snapshot(&self.snapshots_path.join(snapshot_name))
+            .await
     }
 
-    pub async fn create_snapshot(&self, temp_dir: &Path) -> CollectionResult<SnapshotDescription> {
+    /// Create a snapshot of the collection, archiving is done on the `io_runtime`
+    pub async fn create_snapshot(
+        &self,
+        temp_dir: &Path,
+        io_runtime: &Handle,
+    ) -> CollectionResult<SnapshotDescription> {
         let snapshot_name = format!(
             "{}-{}.snapshot",
             self.name(),
@@ -1312,38 +2232,38 @@ impl Collection {
         );
         let snapshot_path = self.snapshots_path.join(&snapshot_name);
 
-        let snapshot_path_tmp = snapshot_path.with_extension("tmp");
-
         let snapshot_path_with_tmp_extension = temp_dir.join(&snapshot_name).with_extension("tmp");
         let snapshot_path_with_arc_extension = temp_dir.join(snapshot_name).with_extension("arc");
 
+        // Fail fast, instead of filling the staging volume halfway through archiving
+        let estimated_size = self.local_data_size().await?;
+        check_free_space(
+            temp_dir,
+            self.snapshot_storage.staging_space(estimated_size),
+        )?;
+
         create_dir_all(&snapshot_path_with_tmp_extension).await?;
 
-        {
+        // Snapshots are prepared under the lock, and the data is copied once it is released,
+        // so that changes of the shards, such as transfers, are not blocked by a long snapshot
+        let (snapshot_tasks, points_counts): (Vec<_>, Vec<_>) = {
             let shards_holder = self.shards_holder.read().await;
-            // Create snapshot of each shard
-            for (shard_id, shard) in shards_holder.get_shards() {
+            let prepared_snapshots = shards_holder.get_shards().map(|(shard_id, shard)| {
                 let shard_snapshot_path =
                     versioned_shard_path(&snapshot_path_with_tmp_extension, *shard_id, 0);
-                create_dir_all(&shard_snapshot_path).await?;
-                match shard {
-                    Shard::Local(local_shard) => {
-                        local_shard.create_snapshot(&shard_snapshot_path).await?;
-                    }
-                    Shard::Proxy(proxy_shard) => {
-                        proxy_shard.create_snapshot(&shard_snapshot_path).await?;
-                    }
-                    Shard::ForwardProxy(proxy_shard) => {
-                        proxy_shard.create_snapshot(&shard_snapshot_path).await?;
-                    }
-                    Shard::Remote(remote_shard) => {
-                        // copy shard directory to snapshot directory
-                        remote_shard.create_snapshot(&shard_snapshot_path).await?;
-                    }
-                    Shard::ReplicaSet(_) => todo!(),
+                async move {
+                    create_dir_all(&shard_snapshot_path).await?;
+                    let snapshot_task = shard.snapshot_task(&shard_snapshot_path).await?;
+                    let points_count = Self::local_points_count(shard).await?;
+                    CollectionResult::Ok((snapshot_task, points_count))
                 }
-            }
-        }
+            });
+            try_join_all(prepared_snapshots).await?.into_iter().unzip()
+        };
+        // Shards are snapshotted concurrently, updates of local shards continue meanwhile
+        try_join_all(snapshot_tasks).await?;
+        let shards_count = points_counts.len();
+        let points_count = points_counts.into_iter().sum();
 
         CollectionVersion::save(&snapshot_path_with_tmp_extension)?;
         self.config
@@ -1351,24 +2271,543 @@ impl Collection {
             .await
             .save(&snapshot_path_with_tmp_extension)?;
 
-        // have to use std here, cause TarBuilder is not async
-        let file = std::fs::File::create(&snapshot_path_with_arc_extension)?;
-        let mut builder = TarBuilder::new(file);
-        // archive recursively collection directory `snapshot_path_with_arc_extension` into `snapshot_path`
-        builder.append_dir_all(".", &snapshot_path_with_tmp_extension)?;
-        builder.finish()?;
+        let metadata = self.snapshot_metadata(shards_count, points_count).await;
+        self.snapshot_storage
+            .store_snapshot(
+                &snapshot_path_with_tmp_extension,
+                &snapshot_path_with_arc_extension,
+                &snapshot_path,
+                &metadata,
+                io_runtime,
+            )
+            .await
+    }
+
+    /// Size of the collection data on this peer, an estimation of the size of its snapshot
+    async fn local_data_size(&self) -> CollectionResult<u64> {
+        let path = self.path.clone();
+        tokio::task::spawn_blocking(move || fs_extra::dir::get_size(path))
+            .await?
+            .map_err(|err| {
+                CollectionError::service_error(format!(
+                    "Failed to estimate size of the collection: {err}"
+                ))
+            })
+    }
 
-        // remove temporary snapshot directory
-        remove_dir_all(&snapshot_path_with_tmp_extension).await?;
+    async fn snapshot_metadata(
+        &self,
+        shards_count: usize,
+        points_count: usize,
+    ) -> SnapshotMetadata {
+        SnapshotMetadata {
+            version: api_crate_version().to_string(),
+            compression: SnapshotCompression::None,
+            collection_params: self.config.read().await.params.clone(),
+            shards_count,
+            points_count,
+        }
+    }
 
-        // move snapshot to permanent location
-        // We can't move right away, because snapshot folder can be on another mounting point.
-        // We can't copy to the target location directly, cause copy is not atomic.
-        copy(&snapshot_path_with_arc_extension, &snapshot_path_tmp).await?;
-        rename(&snapshot_path_tmp, &snapshot_path).await?;
-        remove_file(snapshot_path_with_arc_extension).await?;
+    /// Number of points in the data of the shard on this peer
+    async fn local_points_count(shard: &Shard) -> CollectionResult<usize> {
+        match shard {
+            Shard::Local(_) | Shard::Proxy(_) | Shard::ForwardProxy(_) => {
+                Ok(shard.get().info().await?.points_count)
+            }
+            Shard::ReplicaSet(replica_set) => {
+                let request = Arc::new(CountRequest {
+                    filter: None,
+                    exact: false,
+                });
+                let count = replica_set.count_local(request).await?;
+                Ok(count.map_or(0, |count| count.count))
+            }
+            Shard::Remote(_) => Ok(0),
+        }
+    }
+
+    pub fn shard_snapshots_path(&self, shard_id: ShardId) -> PathBuf {
+        self.snapshots_path
+            .join(SHARD_SNAPSHOTS_DIR)
+            .join(shard_id.to_string())
+    }
+
+    pub async fn list_shard_snapshots(
+        &self,
+        shard_id: ShardId,
+    ) -> CollectionResult<Vec<SnapshotDescription>> {
+        let snapshots_path = self.shard_snapshots_path(shard_id);
+        if !snapshots_path.exists() {
+            return Ok(vec![]);
+        }
+        list_snapshots_in_directory(&snapshots_path).await
+    }
 
-        get_snapshot_description(&snapshot_path).await
+    pub async fn get_shard_snapshot_path(
+        &self,
+        shard_id: ShardId,
+        snapshot_name: &str,
+    ) -> CollectionResult<PathBuf> {
+        let not_found = || CollectionError::NotFound {
+            what: format!("Snapshot {snapshot_name} of shard {shard_id}"),
+        };
+        // Do not allow to refer files outside of the shard snapshots directory
+        if Path::new(snapshot_name).file_name() != Some(std::ffi::OsStr::new(snapshot_name)) {
+            return Err(not_found());
+        }
+        let snapshot_path = self.shard_snapshots_path(shard_id).join(snapshot_name);
+        if !snapshot_path.is_file() {
+            return Err(not_found());
+        }
+        Ok(snapshot_path)
+    }
+
+    /// Create a snapshot of the local shard `shard_id`, archiving is done on the `io_runtime`
+    pub async fn create_shard_snapshot(
+        &self,
+        shard_id: ShardId,
+        temp_dir: &Path,
+        io_runtime: &Handle,
+    ) -> CollectionResult<SnapshotDescription> {
+        let snapshot_name = format!(
+            "{}-shard-{}-{}.snapshot",
+            self.name(),
+            shard_id,
+            chrono::Utc::now().format("%Y-%m-%d-%H-%M-%S")
+        );
+        let snapshots_path = self.shard_snapshots_path(shard_id);
+        create_dir_all(&snapshots_path).await?;
+        let snapshot_path = snapshots_path.join(&snapshot_name);
+
+        let snapshot_path_with_tmp_extension = temp_dir.join(&snapshot_name).with_extension("tmp");
+        let snapshot_path_with_arc_extension = temp_dir.join(snapshot_name).with_extension("arc");
+
+        let (snapshot_task, points_count) = {
+            let shards_holder = self.shards_holder.read().await;
+            let local_shard = Self::local_shard(&shards_holder, shard_id)?;
+            create_dir_all(&snapshot_path_with_tmp_extension).await?;
+            let snapshot_task = local_shard
+                .snapshot_task(&snapshot_path_with_tmp_extension)
+                .await?;
+            (snapshot_task, local_shard.info().await?.points_count)
+        };
+        snapshot_task.await?;
+
+        let metadata = self.snapshot_metadata(1, points_count).await;
+        archive_snapshot(
+            &snapshot_path_with_tmp_extension,
+            &snapshot_path_with_arc_extension,
+            &snapshot_path,
+            &metadata,
+            io_runtime,
+        )
+        .await
+    }
+
+    /// Replace data of the local shard `shard_id` with its snapshot, made by `create_shard_snapshot`.
+    ///
+    /// Shard is restored on this peer only, other replicas of the shard are not affected.
+    /// The snapshot should be made from a collection with the same vectors configuration.
+    pub async fn restore_shard_snapshot(
+        &self,
+        shard_id: ShardId,
+        snapshot_name: &str,
+        io_runtime: &Handle,
+    ) -> CollectionResult<()> {
+        let snapshot_path = self
+            .get_shard_snapshot_path(shard_id, snapshot_name)
+            .await?;
+        {
+            let shards_holder = self.shards_holder.read().await;
+            Self::local_shard(&shards_holder, shard_id)?;
+        }
+
+        // Unpack into a temporary directory first, so that a partially unpacked snapshot is never loaded
+        let unpack_path = self
+            .path
+            .join(format!("{shard_id}.restore-{}", uuid::Uuid::new_v4()));
+        let unpack_target = unpack_path.clone();
+        let snapshot_name_owned = snapshot_name.to_string();
+        let unpacked = io_runtime
+            .spawn_blocking(move || -> CollectionResult<()> {
+                let archive_file = std::fs::File::open(&snapshot_path)?;
+                tar::Archive::new(archive_file).unpack(&unpack_target)?;
+                match ShardConfig::load(&unpack_target)? {
+                    Some(ShardConfig {
+                        r#type: ShardType::Local,
+                    }) => LocalShard::restore_snapshot(&unpack_target),
+                    _ => Err(CollectionError::BadInput {
+                        description: format!(
+                            "{snapshot_name_owned} is not a snapshot of a local shard"
+                        ),
+                    }),
+                }
+            })
+            .await?;
+
+        if let Err(err) = unpacked {
+            if unpack_path.exists() {
+                remove_dir_all(&unpack_path).await?;
+            }
+            return Err(err);
+        }
+        self.replace_local_shard(shard_id, &unpack_path).await?;
+        log::info!(
+            "Restored shard {shard_id} of collection {} from snapshot {snapshot_name}",
+            self.name()
+        );
+        Ok(())
+    }
+
+    /// Replace data of all local shards with the data of the same shards in the collection
+    /// snapshot at `snapshot_path`. Shards, which are not local in the snapshot, are not changed.
+    ///
+    /// The snapshot should be made from a collection with the same shard number and vectors configuration.
+    /// Returns ids of the restored shards.
+    pub async fn restore_local_shards(
+        &self,
+        snapshot_path: &Path,
+        io_runtime: &Handle,
+    ) -> CollectionResult<Vec<ShardId>> {
+        let unpack_path = self.path.join(format!("restore-{}", uuid::Uuid::new_v4()));
+        let unpack_target = unpack_path.clone();
+        let archive_path = snapshot_path.to_owned();
+        let unpacked = io_runtime
+            .spawn_blocking(move || -> CollectionResult<CollectionConfig> {
+                Self::restore_snapshot(&archive_path, &unpack_target)?;
+                CollectionConfig::load(&unpack_target)
+            })
+            .await?;
+        let result = match unpacked {
+            Ok(snapshot_config) => {
+                self.restore_local_shards_from(&unpack_path, &snapshot_config)
+                    .await
+            }
+            Err(err) => Err(err),
+        };
+        if unpack_path.exists() {
+            remove_dir_all(&unpack_path).await?;
+        }
+        result
+    }
+
+    async fn restore_local_shards_from(
+        &self,
+        unpack_path: &Path,
+        snapshot_config: &CollectionConfig,
+    ) -> CollectionResult<Vec<ShardId>> {
+        {
+            let config = self.config.read().await;
+            if config.params.shard_number != snapshot_config.params.shard_number
+                || config.params.vectors != snapshot_config.params.vectors
+            {
+                return Err(CollectionError::BadInput {
+                    description: format!(
+                        "Snapshot of collection with different shards or vectors configuration can't be restored into {}",
+                        self.name()
+                    ),
+                });
+            }
+        }
+
+        let local_shard_ids: Vec<ShardId> = {
+            let shards_holder = self.shards_holder.read().await;
+            (0..snapshot_config.params.shard_number.get())
+                .filter(|shard_id| Self::local_shard(&shards_holder, *shard_id).is_ok())
+                .collect()
+        };
+        let mut restored = vec![];
+        for shard_id in local_shard_ids {
+            let shard_path = versioned_shard_path(unpack_path, shard_id, 0);
+            match ShardConfig::load(&shard_path)? {
+                Some(ShardConfig {
+                    r#type: ShardType::Local,
+                }) => {
+                    self.replace_local_shard(shard_id, &shard_path).await?;
+                    restored.push(shard_id);
+                }
+                _ => log::warn!(
+                    "Shard {shard_id} is not local in the snapshot, not restoring it into collection {}",
+                    self.name()
+                ),
+            }
+        }
+        if restored.is_empty() {
+            return Err(CollectionError::BadInput {
+                description: format!(
+                    "Snapshot contains no shards, which are local in collection {} on this peer",
+                    self.name()
+                ),
+            });
+        }
+        log::info!(
+            "Restored shards {restored:?} of collection {} from snapshot",
+            self.name()
+        );
+        Ok(restored)
+    }
+
+    /// Replace the local shard `shard_id` with the restored shard data at `restored_path`.
+    /// Restored data is moved into the collection directory, or removed if the shard can't be replaced.
+    async fn replace_local_shard(
+        &self,
+        shard_id: ShardId,
+        restored_path: &Path,
+    ) -> CollectionResult<()> {
+        let mut shards_holder = self.shards_holder.write().await;
+        // Shard might be transferred away, while the snapshot was unpacked
+        if let Err(err) = Self::local_shard(&shards_holder, shard_id) {
+            remove_dir_all(restored_path).await?;
+            return Err(err);
+        }
+
+        // Newer version of the shard replaces the current one on load, even if the old one is not removed
+        let shard_path = suggest_next_version_path(&self.path, shard_id).await?;
+        rename(restored_path, &shard_path).await?;
+        let restored_shard = LocalShard::load(
+            shard_id,
+            self.id.clone(),
+            &shard_path,
+            self.config.clone(),
+            self.resource_throttle.clone(),
+        )
+        .await;
+        if let Some(mut replaced_shard) =
+            shards_holder.replace_shard(shard_id, Shard::Local(restored_shard))
+        {
+            replaced_shard.before_drop().await;
+        }
+        drop_old_shards(&self.path, shard_id).await?;
+        Ok(())
+    }
+
+    fn segment_exports_path(&self) -> PathBuf {
+        self.snapshots_path.join(SEGMENT_EXPORTS_DIR)
+    }
+
+    /// Delete points of the local shard `shard_id`, which belong to other shards according to
+    /// the hash ring, e.g. left behind by shard transfers or changes of the ring.
+    ///
+    /// Points are checked in batches of `batch_size`, `on_progress` is called after each batch.
+    /// Shards are only locked while a batch is processed.
+    pub async fn cleanup_shard(
+        &self,
+        shard_id: ShardId,
+        batch_size: usize,
+        mut on_progress: impl FnMut(&ShardCleanupProgress),
+    ) -> CollectionResult<ShardCleanupProgress> {
+        if batch_size == 0 {
+            return Err(CollectionError::BadInput {
+                description: "Cleanup batch size must be positive".to_string(),
+            });
+        }
+
+        let mut progress = ShardCleanupProgress::default();
+        let mut offset = None;
+        loop {
+            let shards_holder = self.shards_holder.read().await;
+            let local_shard = match shards_holder.get_shard(&shard_id) {
+                Some(Shard::Local(local_shard)) => local_shard,
+                Some(_) => {
+                    return Err(CollectionError::bad_shard_selection(format!(
+                        "Shard {shard_id} is not local on peer"
+                    )))
+                }
+                None => {
+                    return Err(CollectionError::bad_shard_selection(format!(
+                        "Shard {shard_id} does not exist"
+                    )))
+                }
+            };
+
+            // Needed to find the offset of the next batch
+            let mut points = local_shard
+                .scroll_by(
+                    offset,
+                    batch_size + 1,
+                    &WithPayloadInterface::Bool(false),
+                    &WithVector::Bool(false),
+                    None,
+                )
+                .await?;
+            offset = if points.len() > batch_size {
+                points.pop().map(|point| point.id)
+            } else {
+                None
+            };
+
+            let misplaced_ids: Vec<_> = points
+                .iter()
+                .map(|point| point.id)
+                .filter(|point_id| {
+                    matches!(shards_holder.point_shard(point_id), Some(target) if target != shard_id)
+                })
+                .collect();
+
+            progress.checked_points += points.len();
+            progress.deleted_points += misplaced_ids.len();
+            if !misplaced_ids.is_empty() {
+                local_shard
+                    .update(
+                        CollectionUpdateOperations::PointOperation(PointOperations::DeletePoints {
+                            ids: misplaced_ids,
+                        }),
+                        true,
+                    )
+                    .await?;
+            }
+
+            if offset.is_none() {
+                break;
+            }
+            on_progress(&progress);
+        }
+
+        progress.finished = true;
+        on_progress(&progress);
+        Ok(progress)
+    }
+
+    fn local_shard(
+        shards_holder: &ShardHolder,
+        shard_id: ShardId,
+    ) -> CollectionResult<&LocalShard> {
+        match shards_holder.get_shard(&shard_id) {
+            Some(Shard::Local(local_shard)) => Ok(local_shard),
+            Some(_) => Err(CollectionError::bad_shard_selection(format!(
+                "Shard {shard_id} is not local on peer"
+            ))),
+            None => Err(CollectionError::bad_shard_selection(format!(
+                "Shard {shard_id} does not exist"
+            ))),
+        }
+    }
+
+    /// List operations of the WAL of the local shard `shard_id`, see `LocalShard::wal_operations`
+    pub async fn wal_operations(
+        &self,
+        shard_id: ShardId,
+        offset: Option<SeqNumberType>,
+        limit: usize,
+    ) -> CollectionResult<WalInfo> {
+        let shards_holder = self.shards_holder.read().await;
+        Ok(Self::local_shard(&shards_holder, shard_id)?.wal_operations(offset, limit))
+    }
+
+    /// Re-apply operations of the WAL of the local shard `shard_id`, see `LocalShard::replay_wal`
+    pub async fn replay_wal(
+        &self,
+        shard_id: ShardId,
+        from_op_num: SeqNumberType,
+    ) -> CollectionResult<usize> {
+        let shards_holder = self.shards_holder.read().await;
+        Self::local_shard(&shards_holder, shard_id)?
+            .replay_wal(from_op_num)
+            .await
+    }
+
+    /// Remove operations of the WAL of the local shard `shard_id`, see `LocalShard::truncate_wal`
+    pub async fn truncate_wal(
+        &self,
+        shard_id: ShardId,
+        from_op_num: SeqNumberType,
+    ) -> CollectionResult<()> {
+        let shards_holder = self.shards_holder.read().await;
+        Self::local_shard(&shards_holder, shard_id)?.truncate_wal(from_op_num)
+    }
+
+    /// Export all segments of the local shard `shard_id` with built indexes.
+    ///
+    /// Archives are placed into the segment exports directory of the collection snapshots,
+    /// from where they can be downloaded or imported into a shard of another collection.
+    pub async fn export_segments(
+        &self,
+        shard_id: ShardId,
+        temp_dir: &Path,
+    ) -> CollectionResult<Vec<SnapshotDescription>> {
+        let export_path_tmp = temp_dir.join(format!(
+            "{}-{}-{}.segments.tmp",
+            self.name(),
+            shard_id,
+            uuid::Uuid::new_v4()
+        ));
+
+        {
+            let shards_holder = self.shards_holder.read().await;
+            match shards_holder.get_shard(&shard_id) {
+                Some(Shard::Local(local_shard)) => {
+                    local_shard.export_segments(&export_path_tmp).await?
+                }
+                Some(_) => {
+                    return Err(CollectionError::bad_shard_selection(format!(
+                        "Shard {} is not local on peer",
+                        shard_id
+                    )))
+                }
+                None => {
+                    return Err(CollectionError::bad_shard_selection(format!(
+                        "Shard {} does not exist",
+                        shard_id
+                    )))
+                }
+            }
+        }
+
+        // Same as for snapshots: export directory can be on another mounting point,
+        // so archives are copied under temporary name and renamed afterwards
+        let exports_path = self.segment_exports_path();
+        create_dir_all(&exports_path).await?;
+        let mut exported = Vec::new();
+        let mut entries = tokio::fs::read_dir(&export_path_tmp).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let export_path = exports_path.join(entry.file_name());
+            let export_path_with_tmp_extension = export_path.with_extension("tmp");
+            copy(entry.path(), &export_path_with_tmp_extension).await?;
+            rename(&export_path_with_tmp_extension, &export_path).await?;
+            exported.push(get_snapshot_description(&export_path).await?);
+        }
+        remove_dir_all(&export_path_tmp).await?;
+
+        Ok(exported)
+    }
+
+    pub async fn get_segment_export_path(&self, export_name: &str) -> CollectionResult<PathBuf> {
+        let not_found = || CollectionError::NotFound {
+            what: format!("Segment export {}", export_name),
+        };
+        // Do not allow to refer files outside of the exports directory
+        if Path::new(export_name).file_name() != Some(std::ffi::OsStr::new(export_name)) {
+            return Err(not_found());
+        }
+        let export_path = self.segment_exports_path().join(export_name);
+        if !export_path.is_file() {
+            return Err(not_found());
+        }
+        Ok(export_path)
+    }
+
+    /// Import segment, exported with `export_segments`, into the local shard `shard_id`.
+    ///
+    /// Segment is added on this peer only and is not replicated to other peers.
+    pub async fn import_segment(
+        &self,
+        shard_id: ShardId,
+        export_name: &str,
+    ) -> CollectionResult<()> {
+        let archive_path = self.get_segment_export_path(export_name).await?;
+        let shards_holder = self.shards_holder.read().await;
+        match shards_holder.get_shard(&shard_id) {
+            Some(Shard::Local(local_shard)) => local_shard.import_segment(&archive_path).await,
+            Some(_) => Err(CollectionError::bad_shard_selection(format!(
+                "Shard {} is not local on peer",
+                shard_id
+            ))),
+            None => Err(CollectionError::bad_shard_selection(format!(
+                "Shard {} does not exist",
+                shard_id
+            ))),
+        }
     }
 
     pub fn restore_snapshot(snapshot_path: &Path, target_dir: &Path) -> CollectionResult<()> {
@@ -1388,7 +2827,15 @@ impl Collection {
                     ShardType::Local => LocalShard::restore_snapshot(&shard_path)?,
                     ShardType::Remote { .. } => RemoteShard::restore_snapshot(&shard_path),
                     ShardType::Temporary => {}
+                    ShardType::ReplicaSet => {
+                        return Err(CollectionError::service_error(format!(
+                            "Replica set is not expected in snapshot at {}",
+                            shard_path.display()
+                        )))
+                    }
                 }
+                // Replica state is only present in snapshots of replica sets taken by older versions
+                ReplicaSet::restore_snapshot(&shard_path)?;
             } else {
                 return Err(CollectionError::service_error(format!(
                     "Can't read shard config at {}",
@@ -1400,6 +2847,29 @@ impl Collection {
         Ok(())
     }
 
+    /// Read collection config from the snapshot archive without unpacking it
+    pub fn snapshot_config(snapshot_path: &Path) -> CollectionResult<CollectionConfig> {
+        let archive_file = std::fs::File::open(snapshot_path)?;
+        let mut ar = tar::Archive::new(archive_file);
+        for entry in ar.entries()? {
+            let entry = entry?;
+            let path = entry.path()?.into_owned();
+            if path.strip_prefix(".").unwrap_or(&path) == Path::new(COLLECTION_CONFIG_FILE) {
+                return Ok(serde_json::from_reader(entry)?);
+            }
+        }
+        Err(CollectionError::BadInput {
+            description: format!(
+                "Snapshot {} does not contain collection config",
+                snapshot_path.display()
+            ),
+        })
+    }
+
+    pub async fn config(&self) -> CollectionConfig {
+        self.config.read().await.clone()
+    }
+
     pub async fn shards_distribution(&self, local_peer_id: PeerId) -> Vec<(ShardId, PeerId)> {
         let shard_holder = self.shards_holder.read().await;
         shard_holder
@@ -1409,7 +2879,19 @@ impl Collection {
                 Shard::Proxy(_proxy_shard) => (*shard_id, local_peer_id),
                 Shard::ForwardProxy(_proxy_shard) => (*shard_id, local_peer_id),
                 Shard::Remote(remote_shard) => (*shard_id, remote_shard.peer_id),
-                Shard::ReplicaSet(_) => todo!(),
+                // Replica on this peer is preferred, then the first active one
+                Shard::ReplicaSet(replica_set) => {
+                    let peer_id = if replica_set.has_local_shard() {
+                        local_peer_id
+                    } else {
+                        replica_set
+                            .peer_ids()
+                            .into_iter()
+                            .min_by_key(|peer_id| (!replica_set.peer_is_active(peer_id), *peer_id))
+                            .unwrap_or(local_peer_id)
+                    };
+                    (*shard_id, peer_id)
+                }
             })
             .collect()
     }
@@ -1429,6 +2911,13 @@ impl Drop for Collection {
     }
 }
 
+fn build_hash_ring(hash_ring: HashRingType) -> HashRing<ShardId> {
+    match hash_ring {
+        HashRingType::Fair => HashRing::fair(HASH_RING_SHARD_SCALE),
+        HashRingType::Jump => HashRing::jump(),
+    }
+}
+
 fn avg_vectors<'a>(
     vectors: impl Iterator<Item = &'a Vec<VectorElementType>>,
 ) -> Vec<VectorElementType> {
@@ -1451,3 +2940,12 @@ fn avg_vectors<'a>(
 
     avg_vector
 }
+
+fn limit_scored_points_payload(points: &mut [ScoredPoint], max_bytes: usize) {
+    limit_payload_size(
+        points
+            .iter_mut()
+            .map(|point| (&mut point.payload, &mut point.payload_truncated)),
+        max_bytes,
+    );
+}
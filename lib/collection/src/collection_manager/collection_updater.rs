@@ -3,6 +3,7 @@ use segment::types::SeqNumberType;
 
 use crate::collection_manager::holders::segment_holder::SegmentHolder;
 use crate::collection_manager::segments_updater::*;
+use crate::operations::payload_transformers::PayloadTransformer;
 use crate::operations::types::{CollectionError, CollectionResult};
 use crate::operations::CollectionUpdateOperations;
 
@@ -47,15 +48,16 @@ impl CollectionUpdater {
         segments: &RwLock<SegmentHolder>,
         op_num: SeqNumberType,
         operation: CollectionUpdateOperations,
+        payload_transformers: &[PayloadTransformer],
     ) -> CollectionResult<usize> {
         // Allow only one update at a time, ensure no data races between segments.
         // let _lock = self.update_lock.lock().unwrap();
         let operation_result = match operation {
             CollectionUpdateOperations::PointOperation(point_operation) => {
-                process_point_operation(segments, op_num, point_operation)
+                process_point_operation(segments, op_num, point_operation, payload_transformers)
             }
             CollectionUpdateOperations::PayloadOperation(payload_operation) => {
-                process_payload_operation(segments, op_num, payload_operation)
+                process_payload_operation(segments, op_num, payload_operation, payload_transformers)
             }
             CollectionUpdateOperations::FieldIndexOperation(index_operation) => {
                 process_field_index_operation(segments, op_num, &index_operation)
@@ -80,6 +82,7 @@ mod tests {
     use crate::collection_manager::segments_searcher::SegmentsSearcher;
     use crate::collection_manager::segments_updater::upsert_points;
     use crate::operations::payload_ops::{DeletePayload, PayloadOps, SetPayload};
+    use crate::operations::payload_transformers::PayloadTransformerType;
     use crate::operations::point_ops::{PointOperations, PointStruct};
 
     #[test]
@@ -121,7 +124,7 @@ mod tests {
         ];
 
         let (num_deleted, num_new, num_updated) =
-            sync_points(&segments.read(), 100, Some(10.into()), None, &points).unwrap();
+            sync_points(&segments.read(), 100, Some(10.into()), None, &points, &[]).unwrap();
 
         assert_eq!(num_deleted, 1); // delete point 15
         assert_eq!(num_new, 1); // insert point 500
@@ -147,7 +150,7 @@ mod tests {
             },
         ];
 
-        let res = upsert_points(&segments.read(), 100, &points);
+        let res = upsert_points(&segments.read(), 100, &points, &[]);
         assert!(matches!(res, Ok(1)));
 
         let records = SegmentsSearcher::retrieve(
@@ -178,6 +181,7 @@ mod tests {
             PointOperations::DeletePoints {
                 ids: vec![500.into()],
             },
+            &[],
         )
         .unwrap();
 
@@ -212,6 +216,7 @@ mod tests {
                 payload,
                 points: points.clone(),
             }),
+            &[],
         )
         .unwrap();
 
@@ -240,6 +245,7 @@ mod tests {
                 points: vec![3.into()],
                 keys: vec!["color".to_string(), "empty".to_string()],
             }),
+            &[],
         )
         .unwrap();
 
@@ -273,6 +279,7 @@ mod tests {
             PayloadOps::ClearPayload {
                 points: vec![2.into()],
             },
+            &[],
         )
         .unwrap();
         let res = SegmentsSearcher::retrieve(
@@ -286,4 +293,39 @@ mod tests {
         assert_eq!(res.len(), 1);
         assert!(!res[0].payload.as_ref().unwrap().contains_key("color"));
     }
+
+    #[tokio::test]
+    async fn test_set_payload_transformed() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let segments = build_test_holder(dir.path());
+
+        let transformers = vec![PayloadTransformer {
+            key: "color".to_string(),
+            transform: PayloadTransformerType::Lowercase,
+        }];
+        let payload: Payload = json!({ "color": "RED", "size": "XL" }).into();
+
+        process_payload_operation(
+            &segments,
+            100,
+            PayloadOps::SetPayload(SetPayload {
+                payload,
+                points: vec![1.into()],
+            }),
+            &transformers,
+        )
+        .unwrap();
+
+        let res = SegmentsSearcher::retrieve(
+            &segments,
+            &[1.into()],
+            &WithPayload::from(true),
+            &false.into(),
+        )
+        .await
+        .unwrap();
+        let payload = res[0].payload.as_ref().unwrap();
+        assert_eq!(payload.get_value("color"), Some(&json!("red")));
+        assert_eq!(payload.get_value("size"), Some(&json!("XL")));
+    }
 }
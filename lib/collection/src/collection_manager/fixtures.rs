@@ -18,6 +18,7 @@ use crate::collection_manager::optimizers::indexing_optimizer::IndexingOptimizer
 use crate::collection_manager::optimizers::merge_optimizer::MergeOptimizer;
 use crate::collection_manager::optimizers::segment_optimizer::OptimizerThresholds;
 use crate::config::{CollectionParams, VectorParams, VectorsConfig};
+use crate::optimizers_builder::MergePolicy;
 
 pub fn empty_segment(path: &Path) -> Segment {
     build_simple_segment(path, 4, Distance::Dot).unwrap()
@@ -164,6 +165,8 @@ pub(crate) fn get_merge_optimizer(
 ) -> MergeOptimizer {
     MergeOptimizer::new(
         5,
+        MergePolicy::Auto,
+        None,
         OptimizerThresholds {
             max_segment_size: 100_000,
             memmap_threshold: 1000000,
@@ -175,10 +178,24 @@ pub(crate) fn get_merge_optimizer(
             vectors: VectorsConfig::Single(VectorParams {
                 size: NonZeroU64::new(dim as u64).unwrap(),
                 distance: Distance::Dot,
+                quantization_config: None,
+                on_disk: None,
+                multivector_config: None,
+                hnsw_config: None,
+                normalize: None,
             }),
+            sparse_vectors: None,
             shard_number: NonZeroU32::new(1).unwrap(),
             on_disk_payload: false,
+            max_response_payload_bytes: None,
+            payload_transformers: vec![],
             replication_factor: NonZeroU32::new(1).unwrap(),
+            read_fan_out_factor: None,
+            write_consistency: Default::default(),
+            read_consistency: Default::default(),
+            hash_ring: Default::default(),
+            ivf_config: None,
+            placement: Default::default(),
         },
         Default::default(),
     )
@@ -201,10 +218,24 @@ pub(crate) fn get_indexing_optimizer(
             vectors: VectorsConfig::Single(VectorParams {
                 size: NonZeroU64::new(dim as u64).unwrap(),
                 distance: Distance::Dot,
+                quantization_config: None,
+                on_disk: None,
+                multivector_config: None,
+                hnsw_config: None,
+                normalize: None,
             }),
+            sparse_vectors: None,
             shard_number: NonZeroU32::new(1).unwrap(),
             on_disk_payload: false,
+            max_response_payload_bytes: None,
+            payload_transformers: vec![],
             replication_factor: NonZeroU32::new(1).unwrap(),
+            read_fan_out_factor: None,
+            write_consistency: Default::default(),
+            read_consistency: Default::default(),
+            hash_ring: Default::default(),
+            ivf_config: None,
+            placement: Default::default(),
         },
         Default::default(),
     )
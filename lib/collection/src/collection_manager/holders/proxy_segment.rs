@@ -6,6 +6,7 @@ use std::sync::Arc;
 
 use parking_lot::{RwLock, RwLockUpgradableReadGuard};
 use segment::data_types::named_vectors::NamedVectors;
+use segment::data_types::sparse_vector::SparseVector;
 use segment::data_types::vectors::VectorElementType;
 use segment::entry::entry_point::{
     OperationError, OperationResult, SegmentEntry, SegmentFailedState,
@@ -14,9 +15,9 @@ use segment::index::field_index::CardinalityEstimation;
 use segment::segment_constructor::load_segment;
 use segment::telemetry::SegmentTelemetry;
 use segment::types::{
-    Condition, Filter, Payload, PayloadFieldSchema, PayloadKeyType, PayloadKeyTypeRef, PointIdType,
-    ScoredPoint, SearchParams, SegmentConfig, SegmentInfo, SegmentType, SeqNumberType, WithPayload,
-    WithVector,
+    Condition, Filter, Payload, PayloadFieldSchema, PayloadIndexInfo, PayloadIndexStatus,
+    PayloadKeyType, PayloadKeyTypeRef, PointIdType, ScoredPoint, SearchParams, SegmentConfig,
+    SegmentInfo, SegmentType, SeqNumberType, WithPayload, WithVector,
 };
 use uuid::Uuid;
 
@@ -196,6 +197,54 @@ impl SegmentEntry for ProxySegment {
         Ok(wrapped_result)
     }
 
+    fn search_sparse(
+        &self,
+        vector_name: &str,
+        vector: &SparseVector,
+        with_payload: &WithPayload,
+        with_vector: &WithVector,
+        filter: Option<&Filter>,
+        top: usize,
+    ) -> OperationResult<Vec<ScoredPoint>> {
+        let deleted_points = self.deleted_points.read();
+
+        // Same as for dense search: exclude points, moved to the write segment
+        let mut wrapped_result = if !deleted_points.is_empty() {
+            let wrapped_filter =
+                self.add_deleted_points_condition_to_filter(filter, &deleted_points);
+
+            self.wrapped_segment.get().read().search_sparse(
+                vector_name,
+                vector,
+                with_payload,
+                with_vector,
+                Some(&wrapped_filter),
+                top,
+            )?
+        } else {
+            self.wrapped_segment.get().read().search_sparse(
+                vector_name,
+                vector,
+                with_payload,
+                with_vector,
+                filter,
+                top,
+            )?
+        };
+
+        let mut write_result = self.write_segment.get().read().search_sparse(
+            vector_name,
+            vector,
+            with_payload,
+            with_vector,
+            filter,
+            top,
+        )?;
+
+        wrapped_result.append(&mut write_result);
+        Ok(wrapped_result)
+    }
+
     fn search_batch(
         &self,
         vector_name: &str,
@@ -516,6 +565,20 @@ impl SegmentEntry for ProxySegment {
         let write_info = self.write_segment.get().read().info();
         let num_vectors = self.wrapped_segment.get().read().config().vector_data.len();
 
+        // Indexes created during the proxy lifetime are only built for the write segment,
+        // the wrapped segment gets them once optimization is finished
+        let deleted_indexes = self.deleted_indexes.read();
+        let mut index_schema: HashMap<_, _> = wrapped_info
+            .index_schema
+            .into_iter()
+            .filter(|(key, _)| !deleted_indexes.contains(key))
+            .collect();
+        for (key, schema) in self.created_indexes.read().iter() {
+            let mut index_info = PayloadIndexInfo::from(schema.clone());
+            index_info.status = PayloadIndexStatus::Building;
+            index_schema.insert(key.clone(), index_info);
+        }
+
         SegmentInfo {
             segment_type: SegmentType::Special,
             num_vectors: self.points_count() * num_vectors, // ToDo: account number of vector storages
@@ -524,7 +587,7 @@ impl SegmentEntry for ProxySegment {
             ram_usage_bytes: wrapped_info.ram_usage_bytes + write_info.ram_usage_bytes,
             disk_usage_bytes: wrapped_info.disk_usage_bytes + write_info.disk_usage_bytes,
             is_appendable: false,
-            index_schema: wrapped_info.index_schema,
+            index_schema,
         }
     }
 
@@ -612,6 +675,38 @@ impl SegmentEntry for ProxySegment {
         Ok(true)
     }
 
+    fn rebuild_field_index(
+        &mut self,
+        op_num: u64,
+        key: PayloadKeyTypeRef,
+        field_schema: Option<&PayloadFieldSchema>,
+    ) -> OperationResult<bool> {
+        if self.version() > op_num {
+            return Ok(false);
+        }
+
+        // Optimized segment is built from scratch, so only the schema has to be propagated
+        let payload_schema = match field_schema {
+            Some(schema) => schema.to_owned(),
+            None => match self.get_indexed_fields().remove(key) {
+                Some(schema) => schema,
+                None => return Ok(false),
+            },
+        };
+
+        self.write_segment
+            .get()
+            .write()
+            .rebuild_field_index(op_num, key, Some(&payload_schema))?;
+
+        self.created_indexes
+            .write()
+            .insert(key.into(), payload_schema);
+        self.deleted_indexes.write().remove(key);
+
+        Ok(true)
+    }
+
     fn get_indexed_fields(&self) -> HashMap<PayloadKeyType, PayloadFieldSchema> {
         let indexed_fields = self.wrapped_segment.get().read().get_indexed_fields();
         indexed_fields
@@ -626,6 +721,20 @@ impl SegmentEntry for ProxySegment {
             .collect()
     }
 
+    fn principal_range(&self) -> Option<(PayloadKeyType, (f64, f64))> {
+        let wrapped_range = self.wrapped_segment.get().read().principal_range();
+        let write_range = self.write_segment.get().read().principal_range();
+        match (wrapped_range, write_range) {
+            (Some((field, (min, max))), Some((write_field, (write_min, write_max))))
+                if field == write_field =>
+            {
+                Some((field, (min.min(write_min), max.max(write_max))))
+            }
+            (Some(wrapped_range), _) => Some(wrapped_range),
+            (None, write_range) => write_range,
+        }
+    }
+
     fn check_error(&self) -> Option<SegmentFailedState> {
         self.write_segment.get().read().check_error()
     }
@@ -736,7 +845,7 @@ mod tests {
     use std::fs::read_dir;
 
     use segment::data_types::vectors::{only_default_vector, DEFAULT_VECTOR_NAME};
-    use segment::types::FieldCondition;
+    use segment::types::{FieldCondition, PayloadSchemaType};
     use serde_json::json;
     use tempfile::{Builder, TempDir};
 
@@ -1128,4 +1237,40 @@ mod tests {
             assert_eq!(archive_extension, "tar");
         }
     }
+
+    #[test]
+    fn test_index_status_of_created_index() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let original_segment = LockedSegment::new(build_segment_1(dir.path()));
+        let write_segment = LockedSegment::new(empty_segment(dir.path()));
+        let deleted_points = Arc::new(RwLock::new(HashSet::<PointIdType>::new()));
+
+        let deleted_indexes = Arc::new(RwLock::new(HashSet::<PayloadKeyType>::new()));
+        let created_indexes = Arc::new(RwLock::new(
+            HashMap::<PayloadKeyType, PayloadFieldSchema>::new(),
+        ));
+
+        let mut proxy_segment = ProxySegment::new(
+            original_segment,
+            write_segment,
+            deleted_points,
+            created_indexes,
+            deleted_indexes,
+        );
+
+        proxy_segment
+            .create_field_index(
+                100,
+                "color",
+                Some(&PayloadFieldSchema::FieldType(PayloadSchemaType::Keyword)),
+            )
+            .unwrap();
+
+        // Wrapped segment doesn't have the index until optimization is finished
+        let index_schema = proxy_segment.info().index_schema;
+        assert_eq!(
+            index_schema.get("color").unwrap().status,
+            PayloadIndexStatus::Building
+        );
+    }
 }
@@ -1,6 +1,6 @@
 use std::cmp::{max, min};
 use std::collections::{BTreeSet, HashMap, HashSet};
-use std::ops::Mul;
+use std::ops::{DerefMut, Mul};
 use std::path::Path;
 use std::sync::Arc;
 use std::thread::sleep;
@@ -11,7 +11,7 @@ use rand::seq::SliceRandom;
 use rand::{thread_rng, Rng};
 use segment::entry::entry_point::{OperationError, OperationResult, SegmentEntry};
 use segment::segment::Segment;
-use segment::types::{PointIdType, SeqNumberType};
+use segment::types::{PayloadFieldSchema, PayloadKeyType, PointIdType, SeqNumberType};
 
 use crate::collection_manager::holders::proxy_segment::ProxySegment;
 use crate::operations::types::CollectionError;
@@ -421,6 +421,117 @@ impl<'s> SegmentHolder {
         Ok(())
     }
 
+    /// Take a snapshot of all segments into `snapshot_dir_path` without blocking updates.
+    ///
+    /// Segments are wrapped into proxies for the time of the snapshot, so updates go into `temp_segment`
+    /// while the wrapped segments are archived. Afterwards, changes collected by the proxies are applied
+    /// to the wrapped segments and the proxies are removed. `temp_segment` is kept, if it received any points.
+    pub fn snapshot_all_segments_with_proxies(
+        segments: &LockedSegmentHolder,
+        temp_segment: LockedSegment,
+        snapshot_dir_path: &Path,
+    ) -> OperationResult<()> {
+        let deleted_points = Arc::new(RwLock::new(HashSet::<PointIdType>::new()));
+        let deleted_indexes = Arc::new(RwLock::new(HashSet::<PayloadKeyType>::new()));
+        let created_indexes = Arc::new(RwLock::new(
+            HashMap::<PayloadKeyType, PayloadFieldSchema>::new(),
+        ));
+
+        let mut proxy_ids = vec![];
+        let mut snapshot_segments = vec![];
+        {
+            let mut segments_lock = segments.write();
+            let segment_ids: Vec<_> = segments_lock.segments.keys().copied().collect();
+            for segment_id in segment_ids {
+                let segment = segments_lock.segments[&segment_id].clone();
+                match segment {
+                    LockedSegment::Original(_) => {
+                        let proxy = ProxySegment::new(
+                            segment.clone(),
+                            temp_segment.clone(),
+                            deleted_points.clone(),
+                            created_indexes.clone(),
+                            deleted_indexes.clone(),
+                        );
+                        proxy_ids.push(segments_lock.swap(proxy, &[segment_id]).0);
+                    }
+                    // Segment is under optimization, its proxy already keeps the changes apart
+                    LockedSegment::Proxy(_) => {}
+                }
+                snapshot_segments.push(segment);
+            }
+        }
+
+        let snapshot_result = snapshot_segments.iter().try_for_each(|segment| {
+            let segment_lock = segment.get();
+            let read_segment = segment_lock.read();
+            read_segment.take_snapshot(snapshot_dir_path)
+        });
+        drop(snapshot_segments);
+
+        // Proxies are removed and the temporary segment is kept regardless of errors,
+        // so that no proxy stays in the holder and no acknowledged update is lost
+        let mut unproxy_result = Ok(());
+        let mut segments_lock = segments.write();
+        for proxy_id in proxy_ids {
+            let proxy = match segments_lock.get(proxy_id) {
+                Some(LockedSegment::Proxy(proxy)) => proxy.clone(),
+                _ => {
+                    log::warn!("Snapshot proxy segment {proxy_id} is missing");
+                    continue;
+                }
+            };
+            let wrapped_segment = proxy.read().wrapped_segment.clone();
+            let applied = Self::apply_proxy_changes(
+                wrapped_segment.get().write().deref_mut(),
+                &deleted_points.read(),
+                &deleted_indexes.read(),
+                &created_indexes.read(),
+            );
+            if let Err(err) = applied {
+                log::error!(
+                    "Failed to apply changes of snapshot proxy to segment {proxy_id}: {err}"
+                );
+                if unproxy_result.is_ok() {
+                    unproxy_result = Err(err);
+                }
+            }
+            drop(proxy);
+            segments_lock.swap(wrapped_segment, &[proxy_id]);
+        }
+
+        let temp_segment_result = if temp_segment.get().read().points_count() > 0 {
+            segments_lock.add_locked(temp_segment);
+            Ok(())
+        } else {
+            drop(segments_lock);
+            temp_segment.drop_data()
+        };
+        snapshot_result.and(unproxy_result).and(temp_segment_result)
+    }
+
+    /// Apply changes, which a proxy kept apart from the wrapped `segment`
+    fn apply_proxy_changes(
+        segment: &mut dyn SegmentEntry,
+        deleted_points: &HashSet<PointIdType>,
+        deleted_indexes: &HashSet<PayloadKeyType>,
+        created_indexes: &HashMap<PayloadKeyType, PayloadFieldSchema>,
+    ) -> OperationResult<()> {
+        let version = segment.version();
+        for &point_id in deleted_points {
+            if segment.has_point(point_id) {
+                segment.delete_point(version, point_id)?;
+            }
+        }
+        for field_name in deleted_indexes {
+            segment.delete_field_index(version, field_name)?;
+        }
+        for (field_name, schema) in created_indexes {
+            segment.create_field_index(version, field_name, Some(schema))?;
+        }
+        Ok(())
+    }
+
     pub fn report_optimizer_error<E: Into<CollectionError>>(&mut self, error: E) {
         if self.optimizer_errors.is_none() {
             self.optimizer_errors = Some(error.into());
@@ -438,7 +549,7 @@ mod tests {
     use tempfile::Builder;
 
     use super::*;
-    use crate::collection_manager::fixtures::{build_segment_1, build_segment_2};
+    use crate::collection_manager::fixtures::{build_segment_1, build_segment_2, empty_segment};
 
     #[test]
     fn test_add_and_swap() {
@@ -555,4 +666,35 @@ mod tests {
         // one archive produced per concrete segment in the SegmentHolder
         assert_eq!(archive_count, 2);
     }
+
+    #[test]
+    fn test_snapshot_all_with_proxies() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let segment1 = build_segment_1(dir.path());
+        let segment2 = build_segment_2(dir.path());
+
+        let mut holder = SegmentHolder::default();
+        holder.add(segment1);
+        holder.add(segment2);
+        let holder: LockedSegmentHolder = Arc::new(RwLock::new(holder));
+
+        let snapshot_dir = Builder::new().prefix("snapshot_dir").tempdir().unwrap();
+        let temp_segment = LockedSegment::new(empty_segment(dir.path()));
+        SegmentHolder::snapshot_all_segments_with_proxies(
+            &holder,
+            temp_segment,
+            snapshot_dir.path(),
+        )
+        .unwrap();
+
+        let archive_count = read_dir(&snapshot_dir).unwrap().into_iter().count();
+        assert_eq!(archive_count, 2);
+
+        // Proxies are removed, empty temp segment is not added
+        let holder = holder.read();
+        assert_eq!(holder.len(), 2);
+        assert!(holder
+            .iter()
+            .all(|(_, segment)| matches!(segment, LockedSegment::Original(_))));
+    }
 }
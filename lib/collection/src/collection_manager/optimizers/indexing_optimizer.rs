@@ -81,11 +81,11 @@ impl IndexingOptimizer {
 
                 let is_vector_indexed = match segment_config.index {
                     Indexes::Plain { .. } => false,
-                    Indexes::Hnsw(_) => true,
+                    Indexes::Hnsw(_) | Indexes::Ivf(_) => true,
                 };
 
                 let is_memmaped = match segment_config.storage_type {
-                    StorageType::InMemory => false,
+                    StorageType::InMemory => segment_config.is_any_vector_on_disk(),
                     StorageType::Mmap => true,
                 };
 
@@ -134,7 +134,7 @@ impl IndexingOptimizer {
                 // Apply indexing to plain segments which have grown too big
                 let is_vector_indexed = match segment_config.index {
                     Indexes::Plain { .. } => false,
-                    Indexes::Hnsw(_) => true,
+                    Indexes::Hnsw(_) | Indexes::Ivf(_) => true,
                 };
 
                 let is_memmaped = match segment_config.storage_type {
@@ -147,11 +147,13 @@ impl IndexingOptimizer {
                         .thresholds_config
                         .memmap_threshold
                         .saturating_mul(BYTES_IN_KB);
-                let big_for_index = vector_size
-                    >= self
-                        .thresholds_config
-                        .indexing_threshold
-                        .saturating_mul(BYTES_IN_KB);
+                // Vector index is disabled for the collection with zero threshold
+                let big_for_index = self.thresholds_config.indexing_threshold > 0
+                    && vector_size
+                        >= self
+                            .thresholds_config
+                            .indexing_threshold
+                            .saturating_mul(BYTES_IN_KB);
 
                 let require_indexing =
                     (big_for_mmap && !is_memmaped) || (big_for_index && !is_vector_indexed);
@@ -309,6 +311,11 @@ mod tests {
                     VectorParams {
                         size: NonZeroU64::new(params.size as u64).unwrap(),
                         distance: params.distance,
+                        quantization_config: None,
+                        on_disk: None,
+                        multivector_config: None,
+                        hnsw_config: None,
+                        normalize: None,
                     },
                 )
             })
@@ -324,9 +331,18 @@ mod tests {
             segments_temp_dir.path().to_owned(),
             CollectionParams {
                 vectors: VectorsConfig::Multi(vectors_config),
+                sparse_vectors: None,
                 shard_number: NonZeroU32::new(1).unwrap(),
                 replication_factor: NonZeroU32::new(1).unwrap(),
+                read_fan_out_factor: None,
+                write_consistency: Default::default(),
+                read_consistency: Default::default(),
+                hash_ring: Default::default(),
                 on_disk_payload: false,
+                max_response_payload_bytes: None,
+                payload_transformers: vec![],
+                ivf_config: None,
+                placement: Default::default(),
             },
             Default::default(),
         );
@@ -422,10 +438,24 @@ mod tests {
                     )
                     .unwrap(),
                     distance: segment_config.vector_data[DEFAULT_VECTOR_NAME].distance,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
                 }),
+                sparse_vectors: None,
                 shard_number: NonZeroU32::new(1).unwrap(),
                 replication_factor: NonZeroU32::new(1).unwrap(),
+                read_fan_out_factor: None,
+                write_consistency: Default::default(),
+                read_consistency: Default::default(),
+                hash_ring: Default::default(),
                 on_disk_payload: false,
+                max_response_payload_bytes: None,
+                payload_transformers: vec![],
+                ivf_config: None,
+                placement: Default::default(),
             },
             Default::default(),
         );
@@ -579,6 +609,7 @@ mod tests {
             locked_holder.deref(),
             opnum.next().unwrap(),
             insert_point_ops,
+            &[],
         )
         .unwrap();
 
@@ -642,7 +673,95 @@ mod tests {
             locked_holder.deref(),
             opnum.next().unwrap(),
             insert_point_ops,
+            &[],
         )
         .unwrap();
     }
+
+    #[test]
+    fn test_indexing_disabled() {
+        init();
+        let mut holder = SegmentHolder::default();
+
+        let stopped = AtomicBool::new(false);
+        let dim = 256;
+
+        let segments_dir = Builder::new().prefix("segments_dir").tempdir().unwrap();
+        let segments_temp_dir = Builder::new()
+            .prefix("segments_temp_dir")
+            .tempdir()
+            .unwrap();
+        let mut opnum = 101..1000000;
+
+        let large_segment = random_segment(segments_dir.path(), opnum.next().unwrap(), 200, dim);
+        let segment_config = large_segment.segment_config.clone();
+        let large_segment_id = holder.add(large_segment);
+
+        let mut index_optimizer = IndexingOptimizer::new(
+            OptimizerThresholds {
+                max_segment_size: 300,
+                memmap_threshold: 1000,
+                indexing_threshold: 0,
+            },
+            segments_dir.path().to_owned(),
+            segments_temp_dir.path().to_owned(),
+            CollectionParams {
+                vectors: VectorsConfig::Single(VectorParams {
+                    size: NonZeroU64::new(
+                        segment_config.vector_data[DEFAULT_VECTOR_NAME].size as u64,
+                    )
+                    .unwrap(),
+                    distance: segment_config.vector_data[DEFAULT_VECTOR_NAME].distance,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
+                }),
+                sparse_vectors: None,
+                shard_number: NonZeroU32::new(1).unwrap(),
+                replication_factor: NonZeroU32::new(1).unwrap(),
+                read_fan_out_factor: None,
+                write_consistency: Default::default(),
+                read_consistency: Default::default(),
+                hash_ring: Default::default(),
+                on_disk_payload: false,
+                max_response_payload_bytes: None,
+                payload_transformers: vec![],
+                ivf_config: None,
+                placement: Default::default(),
+            },
+            Default::default(),
+        );
+
+        let locked_holder: Arc<RwLock<_, _>> = Arc::new(RwLock::new(holder));
+        let excluded_ids = Default::default();
+
+        // Segment of any size does not require indexing
+        let suggested_to_optimize =
+            index_optimizer.check_condition(locked_holder.clone(), &excluded_ids);
+        assert!(suggested_to_optimize.is_empty());
+
+        // Segment is still converted into mmap, but without vector index
+        index_optimizer.thresholds_config.memmap_threshold = 50;
+        let suggested_to_optimize =
+            index_optimizer.check_condition(locked_holder.clone(), &excluded_ids);
+        assert_eq!(suggested_to_optimize, vec![large_segment_id]);
+
+        index_optimizer
+            .optimize(locked_holder.clone(), suggested_to_optimize, &stopped)
+            .unwrap();
+
+        let configs = locked_holder
+            .read()
+            .iter()
+            .map(|(_sid, segment)| segment.get().read().config())
+            .collect_vec();
+        assert!(configs
+            .iter()
+            .any(|config| config.storage_type == StorageType::Mmap));
+        assert!(configs
+            .iter()
+            .all(|config| matches!(config.index, Indexes::Plain {})));
+    }
 }
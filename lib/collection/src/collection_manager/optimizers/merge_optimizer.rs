@@ -1,4 +1,5 @@
-use std::collections::HashSet;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -14,16 +15,20 @@ use crate::collection_manager::optimizers::segment_optimizer::{
     OptimizerThresholds, SegmentOptimizer,
 };
 use crate::config::CollectionParams;
+use crate::optimizers_builder::MergePolicy;
 use crate::telemetry::OptimizerTelemetry;
 
 const BYTES_IN_KB: usize = 1024;
 
 /// Optimizer that tries to reduce number of segments until it fits configured value.
-/// It merges 3 smallest segments into a single large segment.
+/// It merges at least 3 segments, selected according to the `MergePolicy`, into a single large segment.
 /// Merging 3 segments instead of 2 guarantees that after the optimization the number of segments
 /// will be less than before.
 pub struct MergeOptimizer {
     max_segments: usize,
+    merge_policy: MergePolicy,
+    /// Max size of the merged segment on disk, in KiloBytes
+    max_segment_disk_size: Option<usize>,
     thresholds_config: OptimizerThresholds,
     segments_path: PathBuf,
     collection_temp_dir: PathBuf,
@@ -36,6 +41,8 @@ impl MergeOptimizer {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         max_segments: usize,
+        merge_policy: MergePolicy,
+        max_segment_disk_size: Option<usize>,
         thresholds_config: OptimizerThresholds,
         segments_path: PathBuf,
         collection_temp_dir: PathBuf,
@@ -44,6 +51,8 @@ impl MergeOptimizer {
     ) -> Self {
         MergeOptimizer {
             max_segments,
+            merge_policy,
+            max_segment_disk_size,
             thresholds_config,
             segments_path,
             collection_temp_dir,
@@ -54,6 +63,101 @@ impl MergeOptimizer {
     }
 }
 
+/// Selects segments which are neighbours in terms of principal field values,
+/// so the merged segment covers a compact range of values and range filters can skip it entirely.
+/// Prefers the longest sequence of neighbours, and the smallest one among equally long sequences.
+///
+/// # Arguments
+///
+/// * `segments` - ids of the segments with their sizes and smallest principal values
+fn principal_merge_candidates(
+    mut segments: Vec<(SegmentId, usize, Option<f64>)>,
+    max_size: usize,
+    max_candidates: usize,
+) -> Vec<SegmentId> {
+    // Segments without principal values go first, they can be merged with any neighbour
+    segments
+        .sort_by(|(_, _, left), (_, _, right)| left.partial_cmp(right).unwrap_or(Ordering::Equal));
+
+    let mut best_candidates = vec![];
+    let mut best_size = 0;
+    for start in 0..segments.len() {
+        let mut candidates = vec![];
+        let mut candidates_size = 0;
+        for (sid, size, _) in &segments[start..] {
+            if candidates.len() >= max_candidates || candidates_size + size >= max_size {
+                break;
+            }
+            candidates_size += size;
+            candidates.push(*sid);
+        }
+        if candidates.len() > best_candidates.len()
+            || (candidates.len() == best_candidates.len() && candidates_size < best_size)
+        {
+            best_candidates = candidates;
+            best_size = candidates_size;
+        }
+    }
+    best_candidates
+}
+
+/// Selects the longest sequence of segments of similar size, so that large segments are merged
+/// with other large segments only.
+/// Among equally long sequences prefers the one with the smallest ratio of the largest segment
+/// to the smallest one.
+///
+/// # Arguments
+///
+/// * `segments` - ids of the segments with their sizes
+fn tiered_merge_candidates(
+    mut segments: Vec<(SegmentId, usize)>,
+    max_size: usize,
+    max_candidates: usize,
+) -> Vec<SegmentId> {
+    segments.sort_by_key(|(_, size)| *size);
+
+    let mut best_candidates = vec![];
+    let mut best_ratio = f64::INFINITY;
+    for start in 0..segments.len() {
+        let mut candidates = vec![];
+        let mut candidates_size = 0;
+        for (sid, size) in &segments[start..] {
+            if candidates.len() >= max_candidates || candidates_size + size >= max_size {
+                break;
+            }
+            candidates_size += size;
+            candidates.push(*sid);
+        }
+        let (_, smallest) = segments[start];
+        let (_, largest) = segments[start + candidates.len().saturating_sub(1)];
+        let ratio = (largest + 1) as f64 / (smallest + 1) as f64;
+        if candidates.len() > best_candidates.len()
+            || (candidates.len() == best_candidates.len() && ratio < best_ratio)
+        {
+            best_candidates = candidates;
+            best_ratio = ratio;
+        }
+    }
+    best_candidates
+}
+
+/// Longest prefix of the candidates, which fits into `max_disk_size` bytes on disk
+fn limit_disk_size(
+    candidates: Vec<SegmentId>,
+    disk_sizes: &HashMap<SegmentId, usize>,
+    max_disk_size: usize,
+) -> Vec<SegmentId> {
+    candidates
+        .into_iter()
+        .scan(0, |disk_size_sum, sid| {
+            *disk_size_sum += disk_sizes.get(&sid).copied().unwrap_or(0);
+            Some((sid, *disk_size_sum))
+        })
+        .take_while(|(_, disk_size)| *disk_size < max_disk_size)
+        .map(|(sid, _)| sid)
+        .collect()
+}
+
 impl SegmentOptimizer for MergeOptimizer {
     fn collection_path(&self) -> &Path {
         self.segments_path.as_path()
@@ -97,7 +201,12 @@ impl SegmentOptimizer for MergeOptimizer {
         // Find at least top-3 smallest segments to join.
         // We need 3 segments because in this case we can guarantee that total segments number will be less
 
-        let candidates: Vec<_> = raw_segments
+        let max_size = self
+            .thresholds_config
+            .max_segment_size
+            .saturating_mul(BYTES_IN_KB);
+
+        let segment_sizes: Vec<_> = raw_segments
             .iter()
             .cloned()
             .filter_map(|(idx, segment)| {
@@ -114,26 +223,70 @@ impl SegmentOptimizer for MergeOptimizer {
                                 .copied()
                                 .unwrap_or(0)
                             * VECTOR_ELEMENT_SIZE,
+                        read_segment
+                            .principal_range()
+                            .map(|(_field, (min, _max))| min),
                     )),
                     false => None,
                 }
             })
-            .sorted_by_key(|(_, size)| *size)
-            .scan(0, |size_sum, (sid, size)| {
-                *size_sum += size; // produce a cumulative sum of segment sizes starting from smallest
-                Some((sid, *size_sum))
-            })
-            .take_while(|(_, size)| {
-                *size
-                    < self
-                        .thresholds_config
-                        .max_segment_size
-                        .saturating_mul(BYTES_IN_KB)
-            })
-            .take(max_candidates)
-            .map(|x| x.0)
             .collect();
 
+        // Size on disk is only measured if limited, as it requires to traverse segment files
+        let disk_sizes: HashMap<_, _> = match self.max_segment_disk_size {
+            Some(_) => segment_sizes
+                .iter()
+                .filter_map(|(sid, _, _)| {
+                    let data_path = read_segments.get(*sid)?.get().read().data_path();
+                    let disk_size = fs_extra::dir::get_size(data_path).unwrap_or_default();
+                    Some((*sid, disk_size as usize))
+                })
+                .collect(),
+            None => HashMap::new(),
+        };
+
+        let use_principal = match self.merge_policy {
+            MergePolicy::Auto => segment_sizes
+                .iter()
+                .any(|(_, _, principal_min)| principal_min.is_some()),
+            MergePolicy::Smallest | MergePolicy::Tiered => false,
+        };
+
+        let candidates: Vec<_> = if use_principal {
+            principal_merge_candidates(segment_sizes, max_size, max_candidates)
+        } else if self.merge_policy == MergePolicy::Tiered {
+            tiered_merge_candidates(
+                segment_sizes
+                    .into_iter()
+                    .map(|(sid, size, _)| (sid, size))
+                    .collect(),
+                max_size,
+                max_candidates,
+            )
+        } else {
+            segment_sizes
+                .into_iter()
+                .map(|(sid, size, _)| (sid, size))
+                .sorted_by_key(|(_, size)| *size)
+                .scan(0, |size_sum, (sid, size)| {
+                    *size_sum += size; // produce a cumulative sum of segment sizes starting from smallest
+                    Some((sid, *size_sum))
+                })
+                .take_while(|(_, size)| *size < max_size)
+                .take(max_candidates)
+                .map(|x| x.0)
+                .collect()
+        };
+
+        let candidates = match self.max_segment_disk_size {
+            Some(max_disk_size) => limit_disk_size(
+                candidates,
+                &disk_sizes,
+                max_disk_size.saturating_mul(BYTES_IN_KB),
+            ),
+            None => candidates,
+        };
+
         if candidates.len() < 3 {
             return vec![];
         }
@@ -164,6 +317,87 @@ mod tests {
     use crate::collection_manager::fixtures::{get_merge_optimizer, random_segment};
     use crate::collection_manager::holders::segment_holder::{LockedSegment, SegmentHolder};
 
+    #[test]
+    fn test_principal_merge_candidates() {
+        let segments = vec![
+            (0, 10, Some(300.0)),
+            (1, 10, Some(100.0)),
+            (2, 30, Some(200.0)),
+            (3, 10, None),
+            (4, 10, Some(400.0)),
+        ];
+
+        // The smallest segments are not neighbours, so the largest one is merged in between
+        let candidates = principal_merge_candidates(segments.clone(), 1000, 3);
+        assert_eq!(candidates, vec![3, 1, 2]);
+
+        let candidates = principal_merge_candidates(segments.clone(), 1000, 2);
+        assert_eq!(candidates, vec![3, 1]);
+
+        // Size limit breaks the sequence at the largest segment
+        let candidates = principal_merge_candidates(segments, 35, 5);
+        assert_eq!(candidates, vec![3, 1]);
+    }
+
+    #[test]
+    fn test_tiered_merge_candidates() {
+        let segments = vec![(0, 2000), (1, 10), (2, 900), (3, 12), (4, 1400), (5, 11)];
+
+        // Small segments are merged together, not with the large ones
+        let candidates = tiered_merge_candidates(segments.clone(), 10_000, 3);
+        assert_eq!(candidates, vec![1, 5, 3]);
+
+        // Longer sequence is preferred
+        let candidates = tiered_merge_candidates(segments.clone(), 10_000, 4);
+        assert_eq!(candidates, vec![1, 5, 3, 2]);
+
+        // Large segments don't fit, so only the small ones are left
+        let candidates = tiered_merge_candidates(segments, 500, 4);
+        assert_eq!(candidates, vec![1, 5, 3]);
+    }
+
+    #[test]
+    fn test_limit_disk_size() {
+        let disk_sizes = HashMap::from([(0, 100), (1, 200), (2, 300)]);
+
+        assert_eq!(
+            limit_disk_size(vec![0, 1, 2], &disk_sizes, 1000),
+            vec![0, 1, 2]
+        );
+        assert_eq!(limit_disk_size(vec![0, 1, 2], &disk_sizes, 500), vec![0, 1]);
+        assert_eq!(
+            limit_disk_size(vec![2, 1, 0], &disk_sizes, 200),
+            Vec::<SegmentId>::new()
+        );
+    }
+
+    #[test]
+    fn test_max_merge_disk_size() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let temp_dir = Builder::new().prefix("segment_temp_dir").tempdir().unwrap();
+
+        let mut holder = SegmentHolder::default();
+        let dim = 256;
+
+        holder.add(random_segment(dir.path(), 100, 40, dim));
+        holder.add(random_segment(dir.path(), 100, 50, dim));
+        holder.add(random_segment(dir.path(), 100, 60, dim));
+
+        let mut merge_optimizer = get_merge_optimizer(dir.path(), temp_dir.path(), dim);
+        merge_optimizer.max_segments = 1;
+
+        let locked_holder = Arc::new(RwLock::new(holder));
+
+        merge_optimizer.max_segment_disk_size = Some(1);
+        let check_result_empty =
+            merge_optimizer.check_condition(locked_holder.clone(), &Default::default());
+        assert!(check_result_empty.is_empty());
+
+        merge_optimizer.max_segment_disk_size = Some(1_000_000);
+        let check_result = merge_optimizer.check_condition(locked_holder, &Default::default());
+        assert_eq!(check_result.len(), 3);
+    }
+
     #[test]
     fn test_max_merge_size() {
         let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
@@ -71,7 +71,8 @@ pub trait SegmentOptimizer {
     fn temp_segment(&self) -> CollectionResult<LockedSegment> {
         let collection_params = self.collection_params();
         let config = SegmentConfig {
-            vector_data: collection_params.get_all_vector_params()?,
+            vector_data: collection_params.get_appendable_vector_params()?,
+            sparse_vector_data: collection_params.get_all_sparse_vector_params(),
             index: Indexes::Plain {},
             storage_type: StorageType::InMemory,
             payload_storage_type: match collection_params.on_disk_payload {
@@ -109,18 +110,20 @@ pub trait SegmentOptimizer {
         let thresholds = self.threshold_config();
         let collection_params = self.collection_params();
 
-        let is_indexed =
-            total_vectors_size >= thresholds.indexing_threshold.saturating_mul(BYTES_IN_KB);
+        // Zero threshold disables vector index, segments are searched with exact (plain) search
+        let is_indexed = thresholds.indexing_threshold > 0
+            && total_vectors_size >= thresholds.indexing_threshold.saturating_mul(BYTES_IN_KB);
 
         let is_on_disk =
             total_vectors_size >= thresholds.memmap_threshold.saturating_mul(BYTES_IN_KB);
 
         let optimized_config = SegmentConfig {
-            vector_data: collection_params.get_all_vector_params()?,
-            index: if is_indexed {
-                Indexes::Hnsw(self.hnsw_config())
-            } else {
-                Indexes::Plain {}
+            vector_data: collection_params.get_optimized_vector_params(&self.hnsw_config())?,
+            sparse_vector_data: collection_params.get_all_sparse_vector_params(),
+            index: match collection_params.ivf_config {
+                _ if !is_indexed => Indexes::Plain {},
+                Some(ivf_config) => Indexes::Ivf(ivf_config),
+                None => Indexes::Hnsw(self.hnsw_config()),
             },
             storage_type: if is_on_disk {
                 StorageType::Mmap
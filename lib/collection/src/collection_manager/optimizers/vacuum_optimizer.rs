@@ -220,10 +220,24 @@ mod tests {
                 vectors: VectorsConfig::Single(VectorParams {
                     size: NonZeroU64::new(4).unwrap(),
                     distance: Distance::Dot,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
                 }),
+                sparse_vectors: None,
                 shard_number: NonZeroU32::new(1).unwrap(),
                 on_disk_payload: false,
+                max_response_payload_bytes: None,
+                payload_transformers: vec![],
                 replication_factor: NonZeroU32::new(1).unwrap(),
+                read_fan_out_factor: None,
+                write_consistency: Default::default(),
+                read_consistency: Default::default(),
+                hash_ring: Default::default(),
+                ivf_config: None,
+                placement: Default::default(),
             },
             Default::default(),
         );
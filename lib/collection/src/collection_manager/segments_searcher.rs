@@ -5,7 +5,7 @@ use futures::future::try_join_all;
 use itertools::Itertools;
 use parking_lot::RwLock;
 use segment::data_types::named_vectors::NamedVectors;
-use segment::data_types::vectors::VectorElementType;
+use segment::data_types::vectors::{NamedVectorStruct, VectorElementType};
 use segment::entry::entry_point::OperationError;
 use segment::spaces::tools::peek_top_largest_iterable;
 use segment::types::{
@@ -123,8 +123,19 @@ impl SegmentsSearcher {
                             WithVector::Bool(true) => Some(segment.all_vectors(id)?.into()),
                             WithVector::Bool(false) => None,
                             WithVector::Selector(vector_names) => {
+                                let sparse_vector_names = segment.config().sparse_vector_data;
                                 let mut selected_vectors = NamedVectors::default();
                                 for vector_name in vector_names {
+                                    if sparse_vector_names.contains_key(vector_name) {
+                                        // Sparse vectors are optional, there might be none
+                                        if let Some(vector) =
+                                            segment.all_vectors(id)?.get_sparse(vector_name)
+                                        {
+                                            selected_vectors
+                                                .insert_sparse(vector_name.clone(), vector.clone());
+                                        }
+                                        continue;
+                                    }
                                     selected_vectors.insert(
                                         vector_name.clone(),
                                         segment.vector(vector_name, id)?,
@@ -133,6 +144,7 @@ impl SegmentsSearcher {
                                 Some(selected_vectors.into())
                             }
                         },
+                        payload_truncated: false,
                     },
                 );
                 point_version.insert(id, version);
@@ -153,6 +165,22 @@ struct BatchSearchParams<'a> {
     pub params: Option<&'a SearchParams>,
 }
 
+fn search_batch_in_segment(
+    segment: &LockedSegment,
+    params: &BatchSearchParams,
+    vectors_batch: &[&[VectorElementType]],
+) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
+    Ok(segment.get().read().search_batch(
+        params.vector_name,
+        vectors_batch,
+        &params.with_payload,
+        &params.with_vector,
+        params.filter,
+        params.top,
+        params.params,
+    )?)
+}
+
 /// Process sequentially contiguous batches
 async fn search_in_segment(
     segment: LockedSegment,
@@ -177,43 +205,50 @@ async fn search_in_segment(
             params: search_query.params.as_ref(),
         };
 
+        let vector = match &search_query.vector {
+            NamedVectorStruct::Default(vector) => vector.as_slice(),
+            NamedVectorStruct::Named(named_vector) => named_vector.vector.as_slice(),
+            NamedVectorStruct::Sparse(named_vector) => {
+                // Sparse search is not batched, execute what has been batched so far
+                if !vectors_batch.is_empty() {
+                    let mut res = search_batch_in_segment(&segment, &prev_params, &vectors_batch)?;
+                    result.append(&mut res);
+                    vectors_batch.clear();
+                }
+                let res = segment.get().read().search_sparse(
+                    params.vector_name,
+                    &named_vector.vector,
+                    &params.with_payload,
+                    &params.with_vector,
+                    params.filter,
+                    params.top,
+                )?;
+                result.push(res);
+                continue;
+            }
+        };
+
         // same params enables batching
         if params == prev_params {
-            vectors_batch.push(search_query.vector.get_vector().as_slice());
+            vectors_batch.push(vector);
         } else {
             // different params means different batches
             // execute what has been batched so far
             if !vectors_batch.is_empty() {
-                let mut res = segment.get().read().search_batch(
-                    prev_params.vector_name,
-                    &vectors_batch,
-                    &prev_params.with_payload,
-                    &prev_params.with_vector,
-                    prev_params.filter,
-                    prev_params.top,
-                    prev_params.params,
-                )?;
+                let mut res = search_batch_in_segment(&segment, &prev_params, &vectors_batch)?;
                 result.append(&mut res);
                 // clear current batch
                 vectors_batch.clear();
             }
             // start new batch for current search query
-            vectors_batch.push(search_query.vector.get_vector().as_slice());
+            vectors_batch.push(vector);
             prev_params = params;
         }
     }
 
     // run last batch if any
     if !vectors_batch.is_empty() {
-        let mut res = segment.get().read().search_batch(
-            prev_params.vector_name,
-            &vectors_batch,
-            &prev_params.with_payload,
-            &prev_params.with_vector,
-            prev_params.filter,
-            prev_params.top,
-            prev_params.params,
-        )?;
+        let mut res = search_batch_in_segment(&segment, &prev_params, &vectors_batch)?;
         result.append(&mut res);
     }
 
@@ -245,6 +280,7 @@ mod tests {
             limit: 5,
             score_threshold: None,
             offset: 0,
+            max_payload_bytes: None,
         };
 
         let batch_request = SearchRequestBatch {
@@ -11,6 +11,7 @@ use segment::types::{
 
 use crate::collection_manager::holders::segment_holder::SegmentHolder;
 use crate::operations::payload_ops::PayloadOps;
+use crate::operations::payload_transformers::{transform_payload, PayloadTransformer};
 use crate::operations::point_ops::{PointInsertOperations, PointOperations, PointStruct};
 use crate::operations::types::{CollectionError, CollectionResult};
 use crate::operations::FieldIndexOperations;
@@ -134,6 +135,20 @@ pub(crate) fn create_field_index(
     Ok(res)
 }
 
+/// Rebuild the field index segment by segment.
+/// Only the segment which is currently rebuilt is locked, so the rest of them stay available.
+pub(crate) fn rebuild_field_index(
+    segments: &SegmentHolder,
+    op_num: SeqNumberType,
+    field_name: PayloadKeyTypeRef,
+    field_schema: Option<&PayloadFieldSchema>,
+) -> CollectionResult<usize> {
+    let res = segments.apply_segments(|write_segment| {
+        write_segment.rebuild_field_index(op_num, field_name, field_schema)
+    })?;
+    Ok(res)
+}
+
 pub(crate) fn delete_field_index(
     segments: &SegmentHolder,
     op_num: SeqNumberType,
@@ -179,7 +194,25 @@ pub(crate) fn sync_points(
     from_id: Option<PointIdType>,
     to_id: Option<PointIdType>,
     points: &[PointStruct],
+    payload_transformers: &[PayloadTransformer],
 ) -> CollectionResult<(usize, usize, usize)> {
+    // Compare stored points with the transformed ones, as they are stored after transformation
+    let transformed_points;
+    let points = if payload_transformers.is_empty() {
+        points
+    } else {
+        transformed_points = points
+            .iter()
+            .cloned()
+            .map(|mut point| {
+                if let Some(payload) = &mut point.payload {
+                    transform_payload(payload, payload_transformers);
+                }
+                point
+            })
+            .collect_vec();
+        transformed_points.as_slice()
+    };
     let id_to_point = points
         .iter()
         .map(|p| (p.id, p))
@@ -233,7 +266,7 @@ pub(crate) fn sync_points(
         });
 
     // 5. Upsert points which differ from the stored ones
-    let num_replaced = upsert_points(segments, op_num, points_to_update)?;
+    let num_replaced = upsert_points(segments, op_num, points_to_update, &[])?;
     debug_assert_eq!(num_replaced, num_updated);
 
     Ok((deleted, num_new, num_updated))
@@ -246,6 +279,7 @@ pub(crate) fn upsert_points<'a, T>(
     segments: &SegmentHolder,
     op_num: SeqNumberType,
     points: T,
+    payload_transformers: &[PayloadTransformer],
 ) -> CollectionResult<usize>
 where
     T: IntoIterator<Item = &'a PointStruct>,
@@ -257,6 +291,18 @@ where
         points_map.insert(p.id, p);
     });
 
+    // Normalize payloads before they are stored and indexed
+    let mut transformed_payloads: HashMap<PointIdType, Payload> = Default::default();
+    if !payload_transformers.is_empty() {
+        for (id, point) in &points_map {
+            if let Some(payload) = &point.payload {
+                let mut payload = payload.clone();
+                if transform_payload(&mut payload, payload_transformers) {
+                    transformed_payloads.insert(*id, payload);
+                }
+            }
+        }
+    }
     // Update points in writable segments
     let updated_points =
         segments.apply_points_to_appendable(op_num, &ids, |id, write_segment| {
@@ -266,7 +312,7 @@ where
                 op_num,
                 id,
                 &point.get_vectors(),
-                point.payload.as_ref(),
+                transformed_payloads.get(&id).or(point.payload.as_ref()),
             )
         })?;
 
@@ -294,7 +340,9 @@ where
                 op_num,
                 point_id,
                 &point.get_vectors(),
-                point.payload.as_ref(),
+                transformed_payloads
+                    .get(&point_id)
+                    .or(point.payload.as_ref()),
             )? as usize;
         }
         RwLockWriteGuard::unlock_fair(write_segment);
@@ -307,6 +355,7 @@ pub(crate) fn process_point_operation(
     segments: &RwLock<SegmentHolder>,
     op_num: SeqNumberType,
     point_operation: PointOperations,
+    payload_transformers: &[PayloadTransformer],
 ) -> CollectionResult<usize> {
     match point_operation {
         PointOperations::DeletePoints { ids, .. } => delete_points(&segments.read(), op_num, &ids),
@@ -335,7 +384,12 @@ pub(crate) fn process_point_operation(
                 }
                 PointInsertOperations::PointsList(points) => points,
             };
-            let res = upsert_points(&segments.read(), op_num, points.iter())?;
+            let res = upsert_points(
+                &segments.read(),
+                op_num,
+                points.iter(),
+                payload_transformers,
+            )?;
             Ok(res)
         }
         PointOperations::DeletePointsByFilter(filter) => {
@@ -348,6 +402,7 @@ pub(crate) fn process_point_operation(
                 operation.from_id,
                 operation.to_id,
                 &operation.points,
+                payload_transformers,
             )?;
             Ok(deleted + new + updated)
         }
@@ -358,10 +413,13 @@ pub(crate) fn process_payload_operation(
     segments: &RwLock<SegmentHolder>,
     op_num: SeqNumberType,
     payload_operation: PayloadOps,
+    payload_transformers: &[PayloadTransformer],
 ) -> CollectionResult<usize> {
     match payload_operation {
         PayloadOps::SetPayload(sp) => {
-            let payload: Payload = sp.payload;
+            let mut payload: Payload = sp.payload;
+            // Only the fields which are set are transformed, the rest are stored transformed already
+            transform_payload(&mut payload, payload_transformers);
             set_payload(&segments.read(), op_num, &payload, &sp.points)
         }
         PayloadOps::DeletePayload(dp) => {
@@ -391,6 +449,12 @@ pub(crate) fn process_field_index_operation(
         FieldIndexOperations::DeleteIndex(field_name) => {
             delete_field_index(&segments.read(), op_num, field_name)
         }
+        FieldIndexOperations::RebuildIndex(index_data) => rebuild_field_index(
+            &segments.read(),
+            op_num,
+            &index_data.field_name,
+            index_data.field_schema.as_ref(),
+        ),
     }
 }
 
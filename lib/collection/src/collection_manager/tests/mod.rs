@@ -76,7 +76,7 @@ fn test_update_proxy_segments() {
                 payload: None,
             },
         ];
-        upsert_points(&segments.read(), 1000 + i, &points).unwrap();
+        upsert_points(&segments.read(), 1000 + i, &points, &[]).unwrap();
     }
 
     let all_ids = segments
@@ -121,7 +121,7 @@ fn test_move_points_to_copy_on_write() {
         },
     ];
 
-    upsert_points(&segments.read(), 1001, &points).unwrap();
+    upsert_points(&segments.read(), 1001, &points, &[]).unwrap();
 
     let points = vec![
         PointStruct {
@@ -136,7 +136,7 @@ fn test_move_points_to_copy_on_write() {
         },
     ];
 
-    upsert_points(&segments.read(), 1002, &points).unwrap();
+    upsert_points(&segments.read(), 1002, &points, &[]).unwrap();
 
     let segments_write = segments.write();
 
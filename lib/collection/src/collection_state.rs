@@ -5,12 +5,14 @@ use serde::{Deserialize, Serialize};
 use crate::collection::Collection;
 use crate::config::CollectionConfig;
 use crate::operations::types::{CollectionError, CollectionResult};
-use crate::shard::replica_set::IsActive;
+use crate::shard::replica_set::ReplicaState;
 use crate::shard::{PeerId, Shard, ShardId, ShardTransfer};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub enum ShardInfo {
-    ReplicaSet { replicas: HashMap<PeerId, IsActive> },
+    ReplicaSet {
+        replicas: HashMap<PeerId, ReplicaState>,
+    },
     Single(PeerId),
 }
 
@@ -42,12 +44,7 @@ impl State {
         this_peer_id: PeerId,
         mut abort_transfer: impl FnMut(ShardTransfer),
     ) -> CollectionResult<()> {
-        let old_transfers = collection
-            .shards_holder
-            .read()
-            .await
-            .shard_transfers
-            .clone();
+        let old_transfers = collection.shards_holder.read().await.shard_transfers();
         for transfer in shard_transfers.difference(&old_transfers) {
             if transfer.from == this_peer_id {
                 // Abort transfer as sender should not learn about the transfer from snapshot
@@ -57,10 +54,9 @@ impl State {
         }
         collection
             .shards_holder
-            .write()
+            .read()
             .await
-            .shard_transfers
-            .write(|transfers| *transfers = shard_transfers)?;
+            .set_shard_transfers(shard_transfers)?;
         Ok(())
     }
 
@@ -69,6 +65,8 @@ impl State {
         collection: &Collection,
     ) -> CollectionResult<()> {
         log::warn!("Applying only optimizers config snapshot. Other config updates are not yet implemented.");
+        // Saved together with the optimizers config
+        collection.config.write().await.version = new_config.version;
         collection
             .update_optimizer_params(new_config.optimizer_config)
             .await?;
@@ -77,6 +75,11 @@ impl State {
         let old_repl_factor = config.params.replication_factor;
         config.params.replication_factor = new_config.params.replication_factor;
         collection.handle_repl_factor_change(old_repl_factor, config.params.replication_factor);
+        config.params.read_fan_out_factor = new_config.params.read_fan_out_factor;
+        config.params.read_consistency = new_config.params.read_consistency;
+        let shards_holder = collection.shards_holder.read().await;
+        shards_holder.set_read_fan_out_factor(config.params.read_fan_out_factor);
+        shards_holder.set_read_consistency(config.params.read_consistency);
         Ok(())
     }
 
@@ -107,8 +110,16 @@ impl State {
                 (Some(shard), ShardInfo::ReplicaSet { replicas }) => {
                     if let Shard::ReplicaSet(replica_set) = shard {
                         replica_set.apply_state(replicas).await?;
-                    } else {
-                        todo!("check if replication factor was increased and upgrade shard to replica set")
+                        continue;
+                    }
+                    // Replication factor was increased, shard is upgraded to a replica set first
+                    drop(shards_holder);
+                    collection.upgrade_to_replica_sets().await?;
+                    let mut shards_holder = collection.shards_holder.write().await;
+                    if let Some(Shard::ReplicaSet(replica_set)) =
+                        shards_holder.get_mut_shard(&shard_id)
+                    {
+                        replica_set.apply_state(replicas).await?;
                     }
                 }
                 (None, _) => {
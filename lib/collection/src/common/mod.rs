@@ -1,2 +1,3 @@
+pub mod payload_size_limit;
 pub mod stoppable_task;
 pub mod stoppable_task_async;
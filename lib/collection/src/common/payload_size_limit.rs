@@ -0,0 +1,112 @@
+use segment::types::Payload;
+
+/// Size of a serialized JSON value, in bytes
+fn json_size<T: serde::Serialize + ?Sized>(value: &T) -> usize {
+    serde_json::to_vec(value)
+        .map(|bytes| bytes.len())
+        .unwrap_or(0)
+}
+
+/// Approximate size of the payload in the response, in bytes
+fn payload_size(payload: &Payload) -> usize {
+    json_size(&payload.0)
+}
+
+/// Removes the largest fields of the payload until it fits into `max_bytes`.
+/// Each field is measured once, returns the size of the remaining payload.
+fn shrink_payload(payload: &mut Payload, max_bytes: usize) -> usize {
+    // Serialized field is `"key":value`, fields are separated by commas and wrapped in braces
+    let mut fields_by_size: Vec<(String, usize)> = payload
+        .0
+        .iter()
+        .map(|(key, value)| (key.clone(), json_size(key) + 1 + json_size(value)))
+        .collect();
+    let separators = fields_by_size.len().saturating_sub(1);
+    let mut size = 2 + separators + fields_by_size.iter().map(|(_, size)| size).sum::<usize>();
+
+    fields_by_size.sort_unstable_by(|(_, size_a), (_, size_b)| size_b.cmp(size_a));
+    for (key, field_size) in fields_by_size {
+        if size <= max_bytes {
+            break;
+        }
+        payload.0.remove(&key);
+        // The last remaining field has no separator
+        size -= field_size + usize::from(!payload.0.is_empty());
+    }
+    size
+}
+
+/// Limits the total size of payloads in a response.
+///
+/// Points are processed in the response order. Once the budget is exhausted, the largest fields
+/// of the following payloads are omitted. Affected points are marked with `payload_truncated`.
+pub fn limit_payload_size<'a>(
+    points: impl IntoIterator<Item = (&'a mut Option<Payload>, &'a mut bool)>,
+    max_bytes: usize,
+) {
+    let mut remaining = max_bytes;
+    for (payload, truncated) in points {
+        let payload = match payload {
+            None => continue,
+            Some(payload) => payload,
+        };
+        let size = payload_size(payload);
+        if size <= remaining {
+            remaining -= size;
+            continue;
+        }
+        let shrunk_size = shrink_payload(payload, remaining);
+        *truncated = true;
+        remaining = remaining.saturating_sub(shrunk_size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_limit_payload_size() {
+        let payload: Payload = json!({
+            "small": 1,
+            "large": "a very long string value which does not fit into the budget",
+        })
+        .into();
+        let full_size = payload_size(&payload);
+
+        let mut points = vec![(Some(payload.clone()), false), (Some(payload), false)];
+        limit_payload_size(
+            points.iter_mut().map(|(payload, flag)| (payload, flag)),
+            full_size + 20,
+        );
+
+        // First point fits completely
+        assert!(!points[0].1);
+        assert_eq!(points[0].0.as_ref().unwrap().len(), 2);
+
+        // Second point lost its largest field
+        assert!(points[1].1);
+        let truncated = points[1].0.as_ref().unwrap();
+        assert!(truncated.0.contains_key("small"));
+        assert!(!truncated.0.contains_key("large"));
+    }
+
+    #[test]
+    fn test_shrink_payload_size() {
+        let mut payload: Payload = json!({
+            "a": 1,
+            "b": "text",
+            "c": [1, 2, 3],
+            "d\"escaped": {"nested": true},
+        })
+        .into();
+        let full_size = shrink_payload(&mut payload.clone(), usize::MAX);
+        assert_eq!(full_size, payload_size(&payload));
+
+        let shrunk_size = shrink_payload(&mut payload, 10);
+        assert_eq!(shrunk_size, payload_size(&payload));
+        assert!(shrunk_size <= 10);
+    }
+}
@@ -2,6 +2,7 @@ use std::future::Future;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Weak};
 
+use tokio::runtime::Handle;
 use tokio::task::JoinHandle;
 
 pub struct StoppableAsyncTaskHandle<T> {
@@ -27,7 +28,7 @@ impl<T> StoppableAsyncTaskHandle<T> {
     }
 }
 
-pub fn spawn_async_stoppable<F, T>(f: F) -> StoppableAsyncTaskHandle<T::Output>
+pub fn spawn_async_stoppable<F, T>(runtime: &Handle, f: F) -> StoppableAsyncTaskHandle<T::Output>
 where
     F: FnOnce(Arc<AtomicBool>) -> T,
     F: Send + 'static,
@@ -43,7 +44,7 @@ where
     let stopped_w = Arc::downgrade(&stopped);
 
     StoppableAsyncTaskHandle {
-        join_handle: tokio::task::spawn(async move {
+        join_handle: runtime.spawn(async move {
             let res = f(stopped).await;
             // We use `Release` ordering to ensure that `f` won't be moved after the `store`
             // by the compiler
@@ -79,7 +80,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_task_stop() {
-        let handle = spawn_async_stoppable(long_task);
+        let handle = spawn_async_stoppable(&Handle::current(), long_task);
 
         sleep(Duration::from_millis(STEP_MILLIS * 5)).await;
         handle.ask_to_stop();
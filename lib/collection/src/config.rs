@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs::File;
 use std::hash::Hash;
 use std::io::{Read, Write};
@@ -8,13 +8,21 @@ use std::path::Path;
 use atomicwrites::AtomicFile;
 use atomicwrites::OverwriteBehavior::AllowOverwrite;
 use schemars::JsonSchema;
-use segment::data_types::vectors::DEFAULT_VECTOR_NAME;
-use segment::types::{Distance, HnswConfig, VectorDataConfig};
+use segment::data_types::vectors::{NamedVectorStruct, DEFAULT_VECTOR_NAME};
+use segment::types::{
+    Distance, HnswConfig, IvfConfig, MultiVectorConfig, QuantizationConfig, SparseVectorDataConfig,
+    VectorDataConfig,
+};
+use semver::Version;
 use serde::{Deserialize, Serialize};
 use wal::WalOptions;
 
+use crate::operations::config_diff::{DiffConfig, HnswConfigDiff};
+use crate::operations::payload_transformers::PayloadTransformer;
 use crate::operations::types::{CollectionError, CollectionResult};
 use crate::optimizers_builder::OptimizersConfig;
+use crate::shard::replica_set::{ReadConsistency, WriteConsistency};
+use crate::shard::PeerId;
 
 pub const COLLECTION_CONFIG_FILE: &str = "config.json";
 
@@ -49,20 +57,185 @@ impl Default for WalConfig {
 pub struct CollectionParams {
     /// Configuration of the vector storage
     pub vectors: VectorsConfig,
+    /// Configuration of the sparse vector storages by name.
+    /// Sparse vectors are optional for points and searched by dot product
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sparse_vectors: Option<BTreeMap<String, SparseVectorParams>>,
     /// Number of shards the collection has
     #[serde(default = "default_shard_number")]
     pub shard_number: NonZeroU32,
+    /// Method to distribute points between the shards by their ids.
+    /// Can't be changed after the collection is created, as points would belong to other shards.
+    #[serde(default)]
+    pub hash_ring: HashRingType,
     /// Number of replicas for each shard
     // TODO: do not skip in v1.0 (when replication ships)
     #[serde(skip)]
     #[serde(default = "default_replication_factor")]
     pub replication_factor: NonZeroU32,
+    /// Number of active remote replicas, which are read in parallel, if the local replica can't answer.
+    /// Fewer replicas reduce the load of the cluster, more replicas reduce the latency of reads.
+    /// If not set - all active remote replicas are read.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_fan_out_factor: Option<u32>,
+    /// Number of active replicas, which have to answer a read. Results of several replicas are
+    /// reconciled, so a replica, which missed updates, doesn't return stale data.
+    #[serde(default)]
+    pub read_consistency: ReadConsistency,
+    /// Number of active replicas, which have to acknowledge an update for it to succeed.
+    /// With `majority`, updates of slower replicas continue in background.
+    #[serde(default)]
+    pub write_consistency: WriteConsistency,
     /// If true - point's payload will not be stored in memory.
     /// It will be read from the disk every time it is requested.
     /// This setting saves RAM by (slightly) increasing the response time.
     /// Note: those payload values that are involved in filtering and are indexed - remain in RAM.
     #[serde(default = "default_on_disk_payload")]
     pub on_disk_payload: bool,
+    /// Maximum total size of payloads in a single read response, in bytes.
+    /// Largest payload fields are omitted once the limit is reached. If not set - no limit.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_response_payload_bytes: Option<usize>,
+    /// Transformations applied in order to payloads of upserted points and set payloads, before they are stored and indexed
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub payload_transformers: Vec<PayloadTransformer>,
+    /// If set, optimized segments are indexed with IVF index instead of HNSW
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ivf_config: Option<IvfConfig>,
+    /// Constraints for placing shard replicas on peers
+    #[serde(default)]
+    #[serde(skip_serializing_if = "PlacementConstraints::is_empty")]
+    pub placement: PlacementConstraints,
+}
+
+/// Method to distribute points between the shards of a collection
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum HashRingType {
+    /// Consistent hashing with many virtual nodes of each shard on the ring.
+    /// Adding a shard moves points to it from all the other shards, distribution is slightly uneven.
+    Fair,
+    /// Jump consistent hashing. Points are distributed evenly,
+    /// and adding a shard only moves the points, which belong to the new shard.
+    Jump,
+}
+
+impl Default for HashRingType {
+    fn default() -> Self {
+        HashRingType::Fair
+    }
+}
+
+/// Constraints for placing shards of the collection on peers.
+/// Applied when shards are distributed on collection creation and when replicas are added.
+#[derive(Debug, Default, Hash, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct PlacementConstraints {
+    /// Failure domain labels of peers, e.g. availability zone or rack.
+    /// Replicas of the same shard are never placed on peers with the same label.
+    /// Peers without a label are not restricted.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub failure_domains: BTreeMap<PeerId, String>,
+    /// Peers, which never host shards of the collection
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub excluded_peers: BTreeSet<PeerId>,
+}
+
+impl PlacementConstraints {
+    pub fn is_empty(&self) -> bool {
+        self.failure_domains.is_empty() && self.excluded_peers.is_empty()
+    }
+
+    /// Check if a replica of a shard can be placed on `peer_id`,
+    /// given the peers already hosting replicas of the same shard
+    pub fn allows(&self, peer_id: PeerId, shard_peers: &[PeerId]) -> bool {
+        if self.excluded_peers.contains(&peer_id) {
+            return false;
+        }
+        match self.failure_domains.get(&peer_id) {
+            None => true,
+            Some(domain) => shard_peers
+                .iter()
+                .filter(|&&shard_peer| shard_peer != peer_id)
+                .all(|shard_peer| self.failure_domains.get(shard_peer) != Some(domain)),
+        }
+    }
+}
+
+/// Location labels and version of a peer, shared across the cluster through consensus.
+/// Replicas of the same shard are spread across zones and racks whenever possible.
+#[derive(Debug, Default, Hash, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct PeerMetadata {
+    /// Availability zone of the peer
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+    /// Rack of the peer inside of its zone
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rack: Option<String>,
+    /// Version of Qdrant, which the peer runs. Reported by the peer itself on start.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Witness peer only votes in consensus and never hosts shards.
+    /// It allows to break ties between two data centers with a small third peer.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub witness: bool,
+}
+
+impl PeerMetadata {
+    /// Whether the peer runs a version of Qdrant, compatible with `version`.
+    /// Internal API and storage formats are only compatible within the same major and minor version.
+    /// Returns `None` if the version of the peer is unknown.
+    pub fn is_compatible_with(&self, version: &str) -> Option<bool> {
+        let peer_version: Version = self.version.as_ref()?.parse().ok()?;
+        let version: Version = version.parse().ok()?;
+        Some(peer_version.major == version.major && peer_version.minor == version.minor)
+    }
+}
+
+pub type PeerMetadataById = HashMap<PeerId, PeerMetadata>;
+
+/// Count replicas in `shard_peers`, which share the zone, and the zone and rack, with `peer_id`.
+/// Candidates with lower counts are preferred for new replicas.
+/// Peers without labels never share a location.
+pub fn colocated_replicas(
+    peer_metadata: &PeerMetadataById,
+    peer_id: PeerId,
+    shard_peers: &[PeerId],
+) -> (usize, usize) {
+    let metadata = match peer_metadata.get(&peer_id) {
+        Some(metadata) => metadata,
+        None => return (0, 0),
+    };
+    let zone = match &metadata.zone {
+        Some(zone) => zone,
+        None => return (0, 0),
+    };
+    let same_zone: Vec<_> = shard_peers
+        .iter()
+        .filter(|&&shard_peer| shard_peer != peer_id)
+        .filter_map(|shard_peer| peer_metadata.get(shard_peer))
+        .filter(|other| other.zone.as_ref() == Some(zone))
+        .collect();
+    let same_rack = match &metadata.rack {
+        Some(rack) => same_zone
+            .iter()
+            .filter(|other| other.rack.as_ref() == Some(rack))
+            .count(),
+        None => 0,
+    };
+    (same_zone.len(), same_rack)
 }
 
 /// Params of single vector data storage
@@ -73,8 +246,36 @@ pub struct VectorParams {
     pub size: NonZeroU64,
     /// Type of distance function used for measuring distance between vectors
     pub distance: Distance,
+    /// If set, quantized copies of vectors are used for fast approximate search in indexed segments
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantization_config: Option<QuantizationConfig>,
+    /// If true, vectors are stored on disk (memmap) in optimized segments, regardless of the `memmap_threshold`.
+    /// Useful for rarely searched vectors, which should not occupy RAM. Default: false
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_disk: Option<bool>,
+    /// If set, each point may store several sub-vectors of `size` under this name, passed as
+    /// a single concatenated vector. Used for late-interaction models, like ColBERT
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multivector_config: Option<MultiVectorConfig>,
+    /// Custom params for HNSW index of this vector. If none - values from collection configuration are used.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hnsw_config: Option<HnswConfigDiff>,
+    /// If false, vectors with Cosine distance are stored as is, without normalization.
+    /// Norms of the vectors are cached to score them. Default: true
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalize: Option<bool>,
 }
 
+/// Params of single sparse vector data storage
+#[derive(Debug, Default, Hash, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct SparseVectorParams {}
+
 /// Vector params separator for single and multiple vector modes
 /// Single mode:
 ///
@@ -115,6 +316,61 @@ impl VectorsConfig {
             VectorsConfig::Multi(params) => params.get(name),
         }
     }
+
+    /// Check that vector params are consistent, e.g. quantization settings are in valid range
+    pub fn validate(&self) -> CollectionResult<()> {
+        let params: Vec<(&str, &VectorParams)> = match self {
+            VectorsConfig::Single(params) => vec![(DEFAULT_VECTOR_NAME, params)],
+            VectorsConfig::Multi(params) => params
+                .iter()
+                .map(|(name, params)| (name.as_str(), params))
+                .collect(),
+        };
+        for (name, params) in params {
+            if let Some(quantization_config) = &params.quantization_config {
+                quantization_config
+                    .validate()
+                    .map_err(|err| CollectionError::BadInput {
+                        description: format!("Invalid quantization config of vector {name}: {err}"),
+                    })?;
+            }
+            if params.multivector_config.is_some() {
+                if params.quantization_config.is_some() {
+                    return Err(CollectionError::BadInput {
+                        description: format!("Multivector {name} can't be quantized"),
+                    });
+                }
+                if params.on_disk.unwrap_or(false) {
+                    return Err(CollectionError::BadInput {
+                        description: format!("Multivector {name} can't be stored on disk"),
+                    });
+                }
+                if !params.normalize.unwrap_or(true) {
+                    return Err(CollectionError::BadInput {
+                        description: format!("Multivector {name} is always normalized"),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that sparse vector names do not clash with names of dense vectors
+    pub fn check_sparse_vector_names(
+        &self,
+        sparse_vectors: Option<&BTreeMap<String, SparseVectorParams>>,
+    ) -> CollectionResult<()> {
+        for vector_name in sparse_vectors.into_iter().flat_map(|params| params.keys()) {
+            if self.get_params(vector_name).is_some() {
+                return Err(CollectionError::BadInput {
+                    description: format!(
+                        "Sparse vector {vector_name} has the same name as a dense vector"
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
 }
 
 fn default_shard_number() -> NonZeroU32 {
@@ -135,6 +391,9 @@ pub struct CollectionConfig {
     pub hnsw_config: HnswConfig,
     pub optimizer_config: OptimizersConfig,
     pub wal_config: WalConfig,
+    /// Incremented on every update of the config, used to detect concurrent updates
+    #[serde(default)]
+    pub version: u64,
 }
 
 impl CollectionConfig {
@@ -162,6 +421,21 @@ impl CollectionConfig {
         let config_path = path.join(COLLECTION_CONFIG_FILE);
         config_path.exists()
     }
+
+    /// Check that the config was not updated since `expected_version`, if it is set
+    pub fn check_version(&self, expected_version: Option<u64>) -> CollectionResult<()> {
+        match expected_version {
+            Some(expected_version) if self.version != expected_version => {
+                Err(CollectionError::BadRequest {
+                    description: format!(
+                        "Collection config was updated concurrently: expected version {expected_version}, current version {}",
+                        self.version
+                    ),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 impl CollectionParams {
@@ -185,6 +459,38 @@ impl CollectionParams {
         }
     }
 
+    /// Distance, used to order results of the search by the given vector.
+    /// Sparse vectors are always compared by dot product
+    pub fn get_search_distance(&self, vector: &NamedVectorStruct) -> CollectionResult<Distance> {
+        match vector {
+            NamedVectorStruct::Sparse(sparse_vector) => {
+                let vector_name = &sparse_vector.name;
+                if self
+                    .sparse_vectors
+                    .as_ref()
+                    .map_or(false, |params| params.contains_key(vector_name))
+                {
+                    Ok(Distance::Dot)
+                } else {
+                    Err(CollectionError::BadInput {
+                        description: format!(
+                            "sparse vector params for {vector_name} are not specified in config"
+                        ),
+                    })
+                }
+            }
+            _ => Ok(self.get_vector_params(vector.get_name())?.distance),
+        }
+    }
+
+    pub fn get_all_sparse_vector_params(&self) -> HashMap<String, SparseVectorDataConfig> {
+        self.sparse_vectors
+            .iter()
+            .flat_map(|params| params.keys())
+            .map(|vector_name| (vector_name.clone(), SparseVectorDataConfig {}))
+            .collect()
+    }
+
     pub fn get_all_vector_params(&self) -> CollectionResult<HashMap<String, VectorDataConfig>> {
         let vector_config = match &self.vectors {
             VectorsConfig::Single(params) => {
@@ -194,6 +500,11 @@ impl CollectionParams {
                     VectorDataConfig {
                         size: params.size.get() as usize,
                         distance: params.distance,
+                        quantization_config: params.quantization_config,
+                        on_disk: params.on_disk,
+                        multivector_config: params.multivector_config,
+                        hnsw_config: None,
+                        normalize: params.normalize,
                     },
                 );
                 map
@@ -206,6 +517,11 @@ impl CollectionParams {
                         VectorDataConfig {
                             size: params.size.get() as usize,
                             distance: params.distance,
+                            quantization_config: params.quantization_config,
+                            on_disk: params.on_disk,
+                            multivector_config: params.multivector_config,
+                            hnsw_config: None,
+                            normalize: params.normalize,
                         },
                     )
                 })
@@ -213,4 +529,50 @@ impl CollectionParams {
         };
         Ok(vector_config)
     }
+
+    /// Vector params for optimized segments.
+    /// HNSW config of each vector is resolved against the collection-wide `hnsw_config`
+    pub fn get_optimized_vector_params(
+        &self,
+        hnsw_config: &HnswConfig,
+    ) -> CollectionResult<HashMap<String, VectorDataConfig>> {
+        self.get_all_vector_params()?
+            .into_iter()
+            .map(|(name, config)| {
+                let vector_hnsw_config = self
+                    .get_vector_params(&name)?
+                    .hnsw_config
+                    .map(|diff| diff.update(hnsw_config))
+                    .transpose()?;
+                Ok((
+                    name,
+                    VectorDataConfig {
+                        hnsw_config: vector_hnsw_config,
+                        ..config
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    /// Vector params for appendable segments.
+    /// Vectors can't be appended to memmap storage, so `on_disk` vectors are kept in memory
+    /// until optimizers move them into optimized segments.
+    pub fn get_appendable_vector_params(
+        &self,
+    ) -> CollectionResult<HashMap<String, VectorDataConfig>> {
+        Ok(self
+            .get_all_vector_params()?
+            .into_iter()
+            .map(|(name, config)| {
+                (
+                    name,
+                    VectorDataConfig {
+                        on_disk: None,
+                        ..config
+                    },
+                )
+            })
+            .collect())
+    }
 }
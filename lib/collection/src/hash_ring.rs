@@ -1,14 +1,21 @@
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
 
-pub enum HashRing<T: Hash + Copy> {
+use siphasher::sip::SipHasher;
+
+/// Number of keys, sampled to estimate the fraction of points, which move between the rings
+const MOVEMENT_SAMPLE_SIZE: u64 = 10_000;
+
+pub enum HashRing<T: Hash + Copy + Ord> {
     Raw(hashring::HashRing<T>),
     Fair {
         ring: hashring::HashRing<(T, u32)>,
         scale: u32,
     },
+    /// Shards, sorted by id, to pick from with the jump consistent hash
+    Jump(Vec<T>),
 }
 
-impl<T: Hash + Copy> HashRing<T> {
+impl<T: Hash + Copy + Ord> HashRing<T> {
     pub fn raw() -> Self {
         Self::Raw(hashring::HashRing::new())
     }
@@ -23,6 +30,14 @@ impl<T: Hash + Copy> HashRing<T> {
         }
     }
 
+    /// Constructs a HashRing, which uses jump consistent hash: <https://arxiv.org/abs/1406.2294>.
+    /// Points are distributed between the shards evenly, and adding a shard with the largest id
+    /// only moves the points, which go to the new shard.
+    /// Removing any other shard than the one with the largest id moves most of the points.
+    pub fn jump() -> Self {
+        Self::Jump(Vec::new())
+    }
+
     pub fn add(&mut self, shard: T) {
         match self {
             HashRing::Raw(ring) => ring.add(shard),
@@ -31,6 +46,11 @@ impl<T: Hash + Copy> HashRing<T> {
                     ring.add((shard, i))
                 }
             }
+            HashRing::Jump(shards) => {
+                if let Err(pos) = shards.binary_search(&shard) {
+                    shards.insert(pos, shard)
+                }
+            }
         }
     }
 
@@ -46,6 +66,13 @@ impl<T: Hash + Copy> HashRing<T> {
                 }
                 removed
             }
+            HashRing::Jump(shards) => match shards.binary_search(shard) {
+                Ok(pos) => {
+                    shards.remove(pos);
+                    true
+                }
+                Err(_) => false,
+            },
         }
     }
 
@@ -53,6 +80,78 @@ impl<T: Hash + Copy> HashRing<T> {
         match self {
             HashRing::Raw(ring) => ring.get(key),
             HashRing::Fair { ring, .. } => ring.get(key).map(|(shard, _)| shard),
+            HashRing::Jump(shards) => {
+                if shards.is_empty() {
+                    return None;
+                }
+                // Same hasher as the other rings use, so the mapping is stable between versions
+                let mut hasher = SipHasher::new();
+                key.hash(&mut hasher);
+                shards.get(jump_consistent_hash(hasher.finish(), shards.len()))
+            }
+        }
+    }
+
+    /// Expected fraction of points, which belong to a different shard in the `target` ring.
+    /// Estimated by sampling keys.
+    pub fn movement_fraction(&self, target: &Self) -> f64 {
+        let moved = (0..MOVEMENT_SAMPLE_SIZE)
+            .filter(|key| self.get(key) != target.get(key))
+            .count();
+        moved as f64 / MOVEMENT_SAMPLE_SIZE as f64
+    }
+}
+
+/// Bucket of the `key` out of `buckets`.
+/// With one more bucket, a key either stays in its bucket or moves to the new one.
+fn jump_consistent_hash(mut key: u64, buckets: usize) -> usize {
+    let mut bucket: i64 = -1;
+    let mut next: i64 = 0;
+    while next < buckets as i64 {
+        bucket = next;
+        key = key.wrapping_mul(2862933555777941757).wrapping_add(1);
+        next = ((bucket + 1) as f64 * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as i64;
+    }
+    bucket as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(mut ring: HashRing<u32>, shards: u32) -> HashRing<u32> {
+        for shard in 0..shards {
+            ring.add(shard);
+        }
+        ring
+    }
+
+    #[test]
+    fn test_jump_ring_moves_points_to_new_shard_only() {
+        let before = ring(HashRing::jump(), 4);
+        let after = ring(HashRing::jump(), 5);
+        for key in 0..MOVEMENT_SAMPLE_SIZE {
+            let (old_shard, new_shard) = (before.get(&key).unwrap(), after.get(&key).unwrap());
+            assert!(old_shard == new_shard || *new_shard == 4);
+        }
+        // A fifth of the points move to the new shard
+        let moved = before.movement_fraction(&after);
+        assert!((moved - 0.2).abs() < 0.02, "{moved}");
+    }
+
+    #[test]
+    fn test_jump_ring_is_balanced() {
+        let ring = ring(HashRing::jump(), 8);
+        let mut counts = [0; 8];
+        for key in 0..MOVEMENT_SAMPLE_SIZE {
+            counts[*ring.get(&key).unwrap() as usize] += 1;
+        }
+        let expected = MOVEMENT_SAMPLE_SIZE as f64 / 8.0;
+        for count in counts {
+            assert!(
+                (count as f64 - expected).abs() < expected * 0.1,
+                "{counts:?}"
+            );
         }
     }
 }
@@ -1,6 +1,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::shard::replica_set::ReplicaState;
 use crate::shard::{PeerId, ShardId};
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
@@ -11,6 +12,8 @@ pub enum ClusterOperations {
     MoveShard(MoveShardOperation),
     /// Abort currently running shard moving operation
     AbortTransfer(AbortTransferOperation),
+    /// Turn an active replica into a listener or a listener into an active replica
+    SetReplicaState(SetReplicaStateOperation),
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
@@ -25,6 +28,12 @@ pub struct AbortTransferOperation {
     pub abort_transfer: MoveShard,
 }
 
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SetReplicaStateOperation {
+    pub set_replica_state: SetReplicaState,
+}
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct MoveShard {
@@ -32,3 +41,14 @@ pub struct MoveShard {
     pub to_peer_id: PeerId,
     pub from_peer_id: PeerId,
 }
+
+/// Listeners receive updates, but only answer reads, which select them explicitly,
+/// e.g. to run analytics queries without slowing down the other replicas
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SetReplicaState {
+    pub shard_id: ShardId,
+    pub peer_id: PeerId,
+    /// `listener` or `active`
+    pub state: ReplicaState,
+}
@@ -8,7 +8,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::config::{CollectionParams, WalConfig};
 use crate::operations::types::CollectionResult;
-use crate::optimizers_builder::OptimizersConfig;
+use crate::optimizers_builder::{MergePolicy, OptimizersConfig};
+use crate::shard::replica_set::ReadConsistency;
 
 // Structures for partial update of collection params
 // ToDo: Make auto-generated somehow...
@@ -38,6 +39,22 @@ pub struct HnswConfigDiff {
     /// Note: 1Kb = 1 vector of size 256
     #[serde(alias = "full_scan_threshold_kb")]
     pub full_scan_threshold: Option<usize>,
+    /// Number of parallel threads used for background index building. If 0 - auto selection.
+    /// Limit it to leave spare CPU for search while large segments are being indexed.
+    #[serde(default)]
+    pub max_indexing_threads: Option<usize>,
+    /// Store HNSW graph links in a memory-mapped file instead of RAM. Default: false
+    #[serde(default)]
+    pub on_disk: Option<bool>,
+    /// Number of edges per node in additional payload-aware graph links, used by filtered search.
+    /// If not set - `m` is used. If 0 - additional payload-aware links are not built.
+    #[serde(default)]
+    pub payload_m: Option<usize>,
+    /// Minimal size (in KiloBytes) of vectors with the same payload value to build additional links for.
+    /// If not set - `full_scan_threshold` is used.
+    /// Note: 1Kb = 1 vector of size 256
+    #[serde(default)]
+    pub payload_block_threshold: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Merge, PartialEq, Eq, Hash)]
@@ -52,6 +69,10 @@ pub struct WalConfigDiff {
 pub struct CollectionParamsDiff {
     /// Number of replicas for each shard
     pub replication_factor: Option<NonZeroU32>,
+    /// Number of active remote replicas, which are read in parallel, if the local replica can't answer
+    pub read_fan_out_factor: Option<u32>,
+    /// Number of active replicas, which have to answer a read
+    pub read_consistency: Option<ReadConsistency>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Merge)]
@@ -86,6 +107,7 @@ pub struct OptimizersConfigDiff {
     pub memmap_threshold: Option<usize>,
     /// Maximum size (in KiloBytes) of vectors allowed for plain index.
     /// Default value based on <https://github.com/google-research/google-research/blob/master/scann/docs/algorithms.md>
+    /// To disable vector indexing and always use exact search, set to `0`.
     /// Note: 1Kb = 1 vector of size 256
     #[serde(alias = "indexing_threshold_kb")]
     pub indexing_threshold: Option<usize>,
@@ -93,6 +115,13 @@ pub struct OptimizersConfigDiff {
     pub flush_interval_sec: Option<u64>,
     /// Maximum available threads for optimization workers
     pub max_optimization_threads: Option<usize>,
+    /// Do not merge segments, if the merged segment would take more than this size on disk (in KiloBytes).
+    /// Unlike `max_segment_size`, accounts for payload, indexes and all other data of the segments.
+    #[serde(default)]
+    pub max_segment_disk_size: Option<usize>,
+    /// Strategy of selecting segments to merge, once there are more segments than `default_segment_number`
+    #[serde(default)]
+    pub merge_policy: Option<MergePolicy>,
 }
 
 impl std::hash::Hash for OptimizersConfigDiff {
@@ -105,6 +134,8 @@ impl std::hash::Hash for OptimizersConfigDiff {
         self.indexing_threshold.hash(state);
         self.flush_interval_sec.hash(state);
         self.max_optimization_threads.hash(state);
+        self.max_segment_disk_size.hash(state);
+        self.merge_policy.hash(state);
     }
 }
 
@@ -119,6 +150,8 @@ impl PartialEq for OptimizersConfigDiff {
             && self.indexing_threshold == other.indexing_threshold
             && self.flush_interval_sec == other.flush_interval_sec
             && self.max_optimization_threads == other.max_optimization_threads
+            && self.max_segment_disk_size == other.max_segment_disk_size
+            && self.merge_policy == other.merge_policy
     }
 }
 
@@ -147,6 +180,12 @@ pub fn update_config<T: DeserializeOwned + Serialize, Y: DeserializeOwned + Seri
     Ok(res)
 }
 
+/// Diff, which sets every parameter to its value in `config`
+pub fn config_to_diff<T: Serialize, Y: DeserializeOwned>(config: &T) -> CollectionResult<Y> {
+    let serialized = serde_json::to_vec(config)?;
+    Ok(serde_json::from_slice(&serialized)?)
+}
+
 #[cfg(test)]
 mod tests {
     use segment::types::HnswConfig;
@@ -154,6 +193,17 @@ mod tests {
     use super::*;
     use crate::optimizers_builder::OptimizersConfig;
 
+    #[test]
+    fn test_config_to_diff() {
+        let config = HnswConfig {
+            m: 32,
+            ..HnswConfig::default()
+        };
+        let diff: HnswConfigDiff = config_to_diff(&config).unwrap();
+        assert_eq!(diff.m, Some(32));
+        assert_eq!(diff.update(&HnswConfig::default()).unwrap(), config);
+    }
+
     #[test]
     fn test_hnsw_update() {
         let base_config = HnswConfig::default();
@@ -162,6 +212,23 @@ mod tests {
         assert_eq!(new_config.m, 32)
     }
 
+    #[test]
+    fn test_hnsw_update_max_indexing_threads() {
+        let base_config = HnswConfig {
+            max_indexing_threads: 4,
+            ..HnswConfig::default()
+        };
+        let update: HnswConfigDiff = serde_json::from_str(r#"{ "m": 32 }"#).unwrap();
+        let new_config = update.update(&base_config).unwrap();
+        assert_eq!(new_config.max_indexing_threads, 4);
+
+        let update: HnswConfigDiff =
+            serde_json::from_str(r#"{ "max_indexing_threads": 2 }"#).unwrap();
+        let new_config = update.update(&base_config).unwrap();
+        assert_eq!(new_config.max_indexing_threads, 2);
+        assert_eq!(new_config.m, base_config.m);
+    }
+
     #[test]
     fn test_optimizer_update() {
         let base_config = OptimizersConfig {
@@ -173,6 +240,8 @@ mod tests {
             indexing_threshold: 50_000,
             flush_interval_sec: 30,
             max_optimization_threads: 1,
+            max_segment_disk_size: None,
+            merge_policy: MergePolicy::Auto,
         };
         let update: OptimizersConfigDiff =
             serde_json::from_str(r#"{ "indexing_threshold": 10000 }"#).unwrap();
@@ -180,6 +249,32 @@ mod tests {
         assert_eq!(new_config.indexing_threshold, 10000)
     }
 
+    #[test]
+    fn test_merge_policy_update() {
+        let base_config: OptimizersConfig = serde_json::from_str(
+            r#"{
+                "deleted_threshold": 0.2,
+                "vacuum_min_vector_number": 1000,
+                "default_segment_number": 0,
+                "indexing_threshold": 20000,
+                "flush_interval_sec": 5,
+                "max_optimization_threads": 1
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(base_config.merge_policy, MergePolicy::Auto);
+        assert_eq!(base_config.max_segment_disk_size, None);
+
+        let update: OptimizersConfigDiff = serde_json::from_str(
+            r#"{ "merge_policy": "tiered", "max_segment_disk_size": 1000000 }"#,
+        )
+        .unwrap();
+        let new_config = update.update(&base_config).unwrap();
+        assert_eq!(new_config.merge_policy, MergePolicy::Tiered);
+        assert_eq!(new_config.max_segment_disk_size, Some(1_000_000));
+        assert_eq!(new_config.indexing_threshold, 20000);
+    }
+
     #[test]
     fn test_wal_config() {
         let base_config = WalConfig::default();
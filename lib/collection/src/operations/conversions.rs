@@ -3,25 +3,33 @@ use std::num::{NonZeroU32, NonZeroU64};
 
 use api::grpc::conversions::{from_grpc_dist, payload_to_proto, proto_to_payloads};
 use itertools::Itertools;
-use segment::data_types::vectors::{NamedVector, VectorStruct, DEFAULT_VECTOR_NAME};
+use segment::data_types::sparse_vector::SparseVector;
+use segment::data_types::vectors::{
+    NamedSparseVector, NamedVector, VectorStruct, DEFAULT_VECTOR_NAME,
+};
 use segment::types::Distance;
 use tonic::Status;
 
 use crate::config::{
-    default_replication_factor, CollectionConfig, CollectionParams, VectorParams, VectorsConfig,
-    WalConfig,
+    default_replication_factor, CollectionConfig, CollectionParams, HashRingType,
+    PlacementConstraints, SparseVectorParams, VectorParams, VectorsConfig, WalConfig,
+};
+use crate::operations::config_diff::{
+    CollectionParamsDiff, HnswConfigDiff, OptimizersConfigDiff, WalConfigDiff,
 };
-use crate::operations::config_diff::{HnswConfigDiff, OptimizersConfigDiff, WalConfigDiff};
+use crate::operations::payload_transformers::{PayloadTransformer, PayloadTransformerType};
 use crate::operations::point_ops::PointsSelector::PointIdsSelector;
 use crate::operations::point_ops::{
     Batch, FilterSelector, PointIdsList, PointStruct, PointsSelector,
 };
+use crate::operations::shard_selector::ShardSelector;
 use crate::operations::types::{
-    CollectionInfo, CollectionStatus, CountResult, OptimizersStatus, RecommendRequest, Record,
-    SearchRequest, UpdateResult, UpdateStatus,
+    CollectionInfo, CollectionStatus, CountResult, MovePointsMode, MovePointsResult,
+    OptimizersStatus, RecommendRequest, Record, SearchRequest, UpdateResult, UpdateStatus,
 };
-use crate::optimizers_builder::OptimizersConfig;
+use crate::optimizers_builder::{MergePolicy, OptimizersConfig};
 use crate::shard::remote_shard::CollectionSearchRequest;
+use crate::shard::replica_set::{ReadConsistency, WriteConsistency};
 
 impl From<api::grpc::qdrant::HnswConfigDiff> for HnswConfigDiff {
     fn from(value: api::grpc::qdrant::HnswConfigDiff) -> Self {
@@ -29,10 +37,76 @@ impl From<api::grpc::qdrant::HnswConfigDiff> for HnswConfigDiff {
             m: value.m.map(|v| v as usize),
             ef_construct: value.ef_construct.map(|v| v as usize),
             full_scan_threshold: value.full_scan_threshold.map(|v| v as usize),
+            max_indexing_threads: value.max_indexing_threads.map(|v| v as usize),
+            on_disk: value.on_disk,
+            payload_m: value.payload_m.map(|v| v as usize),
+            payload_block_threshold: value.payload_block_threshold.map(|v| v as usize),
+        }
+    }
+}
+
+impl From<HnswConfigDiff> for api::grpc::qdrant::HnswConfigDiff {
+    fn from(value: HnswConfigDiff) -> Self {
+        Self {
+            m: value.m.map(|v| v as u64),
+            ef_construct: value.ef_construct.map(|v| v as u64),
+            full_scan_threshold: value.full_scan_threshold.map(|v| v as u64),
+            max_indexing_threads: value.max_indexing_threads.map(|v| v as u64),
+            on_disk: value.on_disk,
+            payload_m: value.payload_m.map(|v| v as u64),
+            payload_block_threshold: value.payload_block_threshold.map(|v| v as u64),
+        }
+    }
+}
+
+impl From<PayloadTransformer> for api::grpc::qdrant::PayloadTransformer {
+    fn from(value: PayloadTransformer) -> Self {
+        let transform = match value.transform {
+            PayloadTransformerType::Lowercase => {
+                api::grpc::qdrant::PayloadTransformerType::Lowercase
+            }
+            PayloadTransformerType::ParseDatetime => {
+                api::grpc::qdrant::PayloadTransformerType::ParseDatetime
+            }
+            PayloadTransformerType::StripHtml => {
+                api::grpc::qdrant::PayloadTransformerType::StripHtml
+            }
+        };
+        Self {
+            key: value.key,
+            transform: transform as i32,
         }
     }
 }
 
+impl TryFrom<api::grpc::qdrant::PayloadTransformer> for PayloadTransformer {
+    type Error = Status;
+
+    fn try_from(value: api::grpc::qdrant::PayloadTransformer) -> Result<Self, Self::Error> {
+        let transform = match api::grpc::qdrant::PayloadTransformerType::from_i32(value.transform) {
+            Some(api::grpc::qdrant::PayloadTransformerType::Lowercase) => {
+                PayloadTransformerType::Lowercase
+            }
+            Some(api::grpc::qdrant::PayloadTransformerType::ParseDatetime) => {
+                PayloadTransformerType::ParseDatetime
+            }
+            Some(api::grpc::qdrant::PayloadTransformerType::StripHtml) => {
+                PayloadTransformerType::StripHtml
+            }
+            Some(api::grpc::qdrant::PayloadTransformerType::UnknownTransform) | None => {
+                return Err(Status::invalid_argument(format!(
+                    "Unknown payload transformer type for key `{}`",
+                    value.key
+                )))
+            }
+        };
+        Ok(Self {
+            key: value.key,
+            transform,
+        })
+    }
+}
+
 impl From<api::grpc::qdrant::WalConfigDiff> for WalConfigDiff {
     fn from(value: api::grpc::qdrant::WalConfigDiff) -> Self {
         Self {
@@ -42,6 +116,28 @@ impl From<api::grpc::qdrant::WalConfigDiff> for WalConfigDiff {
     }
 }
 
+impl TryFrom<api::grpc::qdrant::CollectionParamsDiff> for CollectionParamsDiff {
+    type Error = Status;
+
+    fn try_from(value: api::grpc::qdrant::CollectionParamsDiff) -> Result<Self, Self::Error> {
+        Ok(Self {
+            replication_factor: value
+                .replication_factor
+                .map(|factor| {
+                    NonZeroU32::new(factor).ok_or_else(|| {
+                        Status::invalid_argument("`replication_factor` cannot be zero")
+                    })
+                })
+                .transpose()?,
+            read_fan_out_factor: value.read_fan_out_factor,
+            read_consistency: value
+                .read_consistency
+                .and_then(api::grpc::qdrant::ReadConsistency::from_i32)
+                .map(|read_consistency| read_consistency.into()),
+        })
+    }
+}
+
 impl From<api::grpc::qdrant::OptimizersConfigDiff> for OptimizersConfigDiff {
     fn from(value: api::grpc::qdrant::OptimizersConfigDiff) -> Self {
         Self {
@@ -53,6 +149,87 @@ impl From<api::grpc::qdrant::OptimizersConfigDiff> for OptimizersConfigDiff {
             indexing_threshold: value.indexing_threshold.map(|v| v as usize),
             flush_interval_sec: value.flush_interval_sec,
             max_optimization_threads: value.max_optimization_threads.map(|v| v as usize),
+            max_segment_disk_size: value.max_segment_disk_size.map(|v| v as usize),
+            merge_policy: value
+                .merge_policy
+                .and_then(api::grpc::qdrant::MergePolicy::from_i32)
+                .map(|policy| policy.into()),
+        }
+    }
+}
+
+impl From<api::grpc::qdrant::MergePolicy> for MergePolicy {
+    fn from(value: api::grpc::qdrant::MergePolicy) -> Self {
+        match value {
+            api::grpc::qdrant::MergePolicy::Auto => MergePolicy::Auto,
+            api::grpc::qdrant::MergePolicy::Smallest => MergePolicy::Smallest,
+            api::grpc::qdrant::MergePolicy::Tiered => MergePolicy::Tiered,
+        }
+    }
+}
+
+impl From<MergePolicy> for api::grpc::qdrant::MergePolicy {
+    fn from(value: MergePolicy) -> Self {
+        match value {
+            MergePolicy::Auto => api::grpc::qdrant::MergePolicy::Auto,
+            MergePolicy::Smallest => api::grpc::qdrant::MergePolicy::Smallest,
+            MergePolicy::Tiered => api::grpc::qdrant::MergePolicy::Tiered,
+        }
+    }
+}
+
+impl From<api::grpc::qdrant::HashRingType> for HashRingType {
+    fn from(value: api::grpc::qdrant::HashRingType) -> Self {
+        match value {
+            api::grpc::qdrant::HashRingType::Fair => HashRingType::Fair,
+            api::grpc::qdrant::HashRingType::Jump => HashRingType::Jump,
+        }
+    }
+}
+
+impl From<api::grpc::qdrant::WriteConsistency> for WriteConsistency {
+    fn from(value: api::grpc::qdrant::WriteConsistency) -> Self {
+        match value {
+            api::grpc::qdrant::WriteConsistency::AllReplicas => WriteConsistency::All,
+            api::grpc::qdrant::WriteConsistency::MajorityReplicas => WriteConsistency::Majority,
+        }
+    }
+}
+
+impl From<WriteConsistency> for api::grpc::qdrant::WriteConsistency {
+    fn from(value: WriteConsistency) -> Self {
+        match value {
+            WriteConsistency::All => api::grpc::qdrant::WriteConsistency::AllReplicas,
+            WriteConsistency::Majority => api::grpc::qdrant::WriteConsistency::MajorityReplicas,
+        }
+    }
+}
+
+impl From<api::grpc::qdrant::ReadConsistency> for ReadConsistency {
+    fn from(value: api::grpc::qdrant::ReadConsistency) -> Self {
+        match value {
+            api::grpc::qdrant::ReadConsistency::SingleReplica => ReadConsistency::Single,
+            api::grpc::qdrant::ReadConsistency::QuorumReplicas => ReadConsistency::Quorum,
+            api::grpc::qdrant::ReadConsistency::AllActiveReplicas => ReadConsistency::All,
+        }
+    }
+}
+
+impl From<ReadConsistency> for api::grpc::qdrant::ReadConsistency {
+    fn from(value: ReadConsistency) -> Self {
+        match value {
+            ReadConsistency::Single => api::grpc::qdrant::ReadConsistency::SingleReplica,
+            ReadConsistency::Quorum => api::grpc::qdrant::ReadConsistency::QuorumReplicas,
+            ReadConsistency::All => api::grpc::qdrant::ReadConsistency::AllActiveReplicas,
+        }
+    }
+}
+
+impl From<HashRingType> for api::grpc::qdrant::HashRingType {
+    fn from(value: HashRingType) -> Self {
+        match value {
+            HashRingType::Fair => api::grpc::qdrant::HashRingType::Fair,
+            HashRingType::Jump => api::grpc::qdrant::HashRingType::Jump,
         }
     }
 }
@@ -114,14 +291,51 @@ impl From<CollectionInfo> for api::grpc::qdrant::CollectionInfo {
                         };
                         Some(api::grpc::qdrant::VectorsConfig { config })
                     },
+                    sparse_vectors_config: config
+                        .params
+                        .sparse_vectors
+                        .as_ref()
+                        .map(sparse_vectors_config_to_grpc),
                     shard_number: config.params.shard_number.get(),
                     on_disk_payload: config.params.on_disk_payload,
+                    ivf_config: config.params.ivf_config.map(|config| config.into()),
+                    placement: if config.params.placement.is_empty() {
+                        None
+                    } else {
+                        Some(config.params.placement.into())
+                    },
+                    read_fan_out_factor: config.params.read_fan_out_factor,
+                    write_consistency: Some(api::grpc::qdrant::WriteConsistency::from(
+                        config.params.write_consistency,
+                    ) as i32),
+                    read_consistency: Some(api::grpc::qdrant::ReadConsistency::from(
+                        config.params.read_consistency,
+                    ) as i32),
+                    hash_ring: Some(
+                        api::grpc::qdrant::HashRingType::from(config.params.hash_ring) as i32,
+                    ),
+                    max_response_payload_bytes: config
+                        .params
+                        .max_response_payload_bytes
+                        .map(|bytes| bytes as u64),
+                    payload_transformers: config
+                        .params
+                        .payload_transformers
+                        .into_iter()
+                        .map(|transformer| transformer.into())
+                        .collect(),
                 }),
                 hnsw_config: Some(api::grpc::qdrant::HnswConfigDiff {
                     m: Some(config.hnsw_config.m as u64),
                     ef_construct: Some(config.hnsw_config.ef_construct as u64),
                     full_scan_threshold: Some(config.hnsw_config.full_scan_threshold as u64),
                     max_indexing_threads: Some(config.hnsw_config.max_indexing_threads as u64),
+                    on_disk: config.hnsw_config.on_disk,
+                    payload_m: config.hnsw_config.payload_m.map(|v| v as u64),
+                    payload_block_threshold: config
+                        .hnsw_config
+                        .payload_block_threshold
+                        .map(|v| v as u64),
                 }),
                 optimizer_config: Some(api::grpc::qdrant::OptimizersConfigDiff {
                     deleted_threshold: Some(config.optimizer_config.deleted_threshold),
@@ -138,11 +352,20 @@ impl From<CollectionInfo> for api::grpc::qdrant::CollectionInfo {
                     max_optimization_threads: Some(
                         config.optimizer_config.max_optimization_threads as u64,
                     ),
+                    max_segment_disk_size: config
+                        .optimizer_config
+                        .max_segment_disk_size
+                        .map(|x| x as u64),
+                    merge_policy: Some(
+                        api::grpc::qdrant::MergePolicy::from(config.optimizer_config.merge_policy)
+                            .into(),
+                    ),
                 }),
                 wal_config: Some(api::grpc::qdrant::WalConfigDiff {
                     wal_capacity_mb: Some(config.wal_config.wal_capacity_mb as u64),
                     wal_segments_ahead: Some(config.wal_config.wal_segments_ahead as u64),
                 }),
+                version: config.version,
             }),
             payload_schema: payload_schema
                 .into_iter()
@@ -160,6 +383,7 @@ impl From<Record> for api::grpc::qdrant::RetrievedPoint {
             id: Some(record.id.into()),
             payload: record.payload.map(payload_to_proto).unwrap_or_default(),
             vectors,
+            payload_truncated: record.payload_truncated,
         }
     }
 }
@@ -177,6 +401,7 @@ impl TryFrom<api::grpc::qdrant::RetrievedPoint> for Record {
             id: retrieved_point.id.unwrap().try_into()?,
             payload: Some(proto_to_payloads(retrieved_point.payload)?),
             vector: vectors,
+            payload_truncated: retrieved_point.payload_truncated,
         })
     }
 }
@@ -210,6 +435,12 @@ impl From<api::grpc::qdrant::OptimizersConfigDiff> for OptimizersConfig {
             max_optimization_threads: optimizer_config
                 .max_optimization_threads
                 .unwrap_or_default() as usize,
+            max_segment_disk_size: optimizer_config.max_segment_disk_size.map(|x| x as usize),
+            merge_policy: optimizer_config
+                .merge_policy
+                .and_then(api::grpc::qdrant::MergePolicy::from_i32)
+                .map(|policy| policy.into())
+                .unwrap_or_default(),
         }
     }
 }
@@ -223,6 +454,57 @@ impl From<api::grpc::qdrant::WalConfigDiff> for WalConfig {
     }
 }
 
+impl From<api::grpc::qdrant::SparseVectorParams> for SparseVectorParams {
+    fn from(_value: api::grpc::qdrant::SparseVectorParams) -> Self {
+        SparseVectorParams {}
+    }
+}
+
+impl From<SparseVectorParams> for api::grpc::qdrant::SparseVectorParams {
+    fn from(_value: SparseVectorParams) -> Self {
+        api::grpc::qdrant::SparseVectorParams {}
+    }
+}
+
+impl From<api::grpc::qdrant::PlacementConstraints> for PlacementConstraints {
+    fn from(placement: api::grpc::qdrant::PlacementConstraints) -> Self {
+        Self {
+            failure_domains: placement.failure_domains.into_iter().collect(),
+            excluded_peers: placement.excluded_peers.into_iter().collect(),
+        }
+    }
+}
+
+impl From<PlacementConstraints> for api::grpc::qdrant::PlacementConstraints {
+    fn from(placement: PlacementConstraints) -> Self {
+        Self {
+            failure_domains: placement.failure_domains.into_iter().collect(),
+            excluded_peers: placement.excluded_peers.into_iter().collect(),
+        }
+    }
+}
+
+fn sparse_vectors_config_from_grpc(
+    config: api::grpc::qdrant::SparseVectorConfig,
+) -> BTreeMap<String, SparseVectorParams> {
+    config
+        .map
+        .into_iter()
+        .map(|(name, params)| (name, params.into()))
+        .collect()
+}
+
+fn sparse_vectors_config_to_grpc(
+    params: &BTreeMap<String, SparseVectorParams>,
+) -> api::grpc::qdrant::SparseVectorConfig {
+    api::grpc::qdrant::SparseVectorConfig {
+        map: params
+            .iter()
+            .map(|(name, params)| (name.clone(), params.clone().into()))
+            .collect(),
+    }
+}
+
 impl TryFrom<api::grpc::qdrant::VectorParams> for VectorParams {
     type Error = Status;
 
@@ -231,7 +513,21 @@ impl TryFrom<api::grpc::qdrant::VectorParams> for VectorParams {
             size: NonZeroU64::new(vector_params.size).ok_or_else(|| {
                 Status::invalid_argument("VectorParams size must be greater than zero")
             })?,
-            distance: from_grpc_dist(vector_params.distance)?,
+            distance: from_grpc_dist(
+                vector_params.distance,
+                vector_params.custom_distance.as_deref(),
+            )?,
+            quantization_config: vector_params
+                .quantization_config
+                .map(|config| config.try_into())
+                .transpose()?,
+            on_disk: vector_params.on_disk,
+            multivector_config: vector_params
+                .multivector_config
+                .map(|config| config.try_into())
+                .transpose()?,
+            hnsw_config: vector_params.hnsw_config.map(|config| config.into()),
+            normalize: vector_params.normalize,
         })
     }
 }
@@ -272,12 +568,41 @@ impl TryFrom<api::grpc::qdrant::CollectionConfig> for CollectionConfig {
                                 ),
                             },
                         },
+                        sparse_vectors: params
+                            .sparse_vectors_config
+                            .map(sparse_vectors_config_from_grpc),
                         shard_number: NonZeroU32::new(params.shard_number).ok_or_else(|| {
                             Status::invalid_argument("`shard_number` cannot be zero")
                         })?,
                         on_disk_payload: params.on_disk_payload,
+                        max_response_payload_bytes: params
+                            .max_response_payload_bytes
+                            .map(|bytes| bytes as usize),
+                        payload_transformers: params
+                            .payload_transformers
+                            .into_iter()
+                            .map(|transformer| transformer.try_into())
+                            .collect::<Result<_, Status>>()?,
+                        ivf_config: params.ivf_config.map(|config| config.into()),
+                        placement: params.placement.map(|v| v.into()).unwrap_or_default(),
                         // TODO: use `repliction_factor` from `config`
                         replication_factor: default_replication_factor(),
+                        read_fan_out_factor: params.read_fan_out_factor,
+                        write_consistency: params
+                            .write_consistency
+                            .and_then(api::grpc::qdrant::WriteConsistency::from_i32)
+                            .map(|write_consistency| write_consistency.into())
+                            .unwrap_or_default(),
+                        read_consistency: params
+                            .read_consistency
+                            .and_then(api::grpc::qdrant::ReadConsistency::from_i32)
+                            .map(|read_consistency| read_consistency.into())
+                            .unwrap_or_default(),
+                        hash_ring: params
+                            .hash_ring
+                            .and_then(api::grpc::qdrant::HashRingType::from_i32)
+                            .map(|hash_ring| hash_ring.into())
+                            .unwrap_or_default(),
                     }
                 }
             },
@@ -293,6 +618,7 @@ impl TryFrom<api::grpc::qdrant::CollectionConfig> for CollectionConfig {
                 None => return Err(Status::invalid_argument("Malformed WalConfig type")),
                 Some(wal_config) => wal_config.into(),
             },
+            version: config.version,
         })
     }
 }
@@ -441,6 +767,30 @@ impl TryFrom<api::grpc::qdrant::PointsSelector> for PointsSelector {
     }
 }
 
+impl TryFrom<api::grpc::qdrant::ShardSelector> for ShardSelector {
+    type Error = Status;
+
+    fn try_from(value: api::grpc::qdrant::ShardSelector) -> Result<Self, Self::Error> {
+        let api::grpc::qdrant::ShardSelector {
+            shard_keys,
+            shard_ids,
+            replica,
+        } = value;
+        match (shard_keys.is_empty(), shard_ids.is_empty(), replica) {
+            (false, true, None) => Ok(ShardSelector::ShardKeys { shard_keys }),
+            (true, false, None) => Ok(ShardSelector::ShardIds { shard_ids }),
+            (true, true, Some(replica)) => Ok(ShardSelector::ShardReplica {
+                shard_id: replica.shard_id,
+                peer_id: replica.peer_id,
+            }),
+            (true, true, None) => Err(Status::invalid_argument("ShardSelector is empty")),
+            _ => Err(Status::invalid_argument(
+                "ShardSelector must contain either shard keys, shard ids or a replica",
+            )),
+        }
+    }
+}
+
 impl From<UpdateResult> for api::grpc::qdrant::UpdateResult {
     fn from(value: UpdateResult) -> Self {
         Self {
@@ -468,6 +818,7 @@ impl TryFrom<api::grpc::qdrant::UpdateResult> for UpdateResult {
                 }
                 _ => return Err(Status::invalid_argument("Malformed UpdateStatus type")),
             },
+            session_token: None,
         })
     }
 }
@@ -488,14 +839,44 @@ impl From<CountResult> for api::grpc::qdrant::CountResult {
     }
 }
 
+impl From<api::grpc::qdrant::MovePointsMode> for MovePointsMode {
+    fn from(value: api::grpc::qdrant::MovePointsMode) -> Self {
+        match value {
+            api::grpc::qdrant::MovePointsMode::MovePoints => MovePointsMode::Move,
+            api::grpc::qdrant::MovePointsMode::CopyPoints => MovePointsMode::Copy,
+        }
+    }
+}
+
+impl From<MovePointsResult> for api::grpc::qdrant::MovePointsResult {
+    fn from(value: MovePointsResult) -> Self {
+        Self {
+            points_count: value.points_count as u64,
+        }
+    }
+}
+
 // Use wrapper type to bundle CollectionId & SearchRequest
 impl<'a> From<CollectionSearchRequest<'a>> for api::grpc::qdrant::SearchPoints {
     fn from(value: CollectionSearchRequest<'a>) -> Self {
         let (collection_id, request) = value.0;
 
+        let (vector, sparse_indices) = match request.vector.get_sparse_vector() {
+            Some(sparse_vector) => (
+                sparse_vector.values.clone(),
+                Some(api::grpc::qdrant::SparseIndices {
+                    data: sparse_vector.indices.clone(),
+                }),
+            ),
+            None => (
+                request.vector.get_vector().cloned().unwrap_or_default(),
+                None,
+            ),
+        };
+
         api::grpc::qdrant::SearchPoints {
             collection_name: collection_id,
-            vector: request.vector.get_vector().clone(),
+            vector,
             filter: request.filter.clone().map(|f| f.into()),
             limit: request.limit as u64,
             with_vectors: request.with_vector.clone().map(|wv| wv.into()),
@@ -507,6 +888,10 @@ impl<'a> From<CollectionSearchRequest<'a>> for api::grpc::qdrant::SearchPoints {
                 DEFAULT_VECTOR_NAME => None,
                 vector_name => Some(vector_name.to_string()),
             },
+            sparse_indices,
+            shard_selector: None,
+            // Payload size is limited by the peer, which received the client request
+            max_payload_bytes: None,
         }
     }
 }
@@ -516,13 +901,19 @@ impl TryFrom<api::grpc::qdrant::SearchPoints> for SearchRequest {
 
     fn try_from(value: api::grpc::qdrant::SearchPoints) -> Result<Self, Self::Error> {
         Ok(SearchRequest {
-            vector: match value.vector_name {
-                Some(vector_name) => NamedVector {
+            vector: match (value.sparse_indices, value.vector_name) {
+                (Some(indices), vector_name) => NamedSparseVector {
+                    name: vector_name.unwrap_or_else(|| DEFAULT_VECTOR_NAME.to_owned()),
+                    vector: SparseVector::new(indices.data, value.vector)
+                        .map_err(Status::invalid_argument)?,
+                }
+                .into(),
+                (None, Some(vector_name)) => NamedVector {
                     name: vector_name,
                     vector: value.vector,
                 }
                 .into(),
-                None => value.vector.into(),
+                (None, None) => value.vector.into(),
             },
             filter: value.filter.map(|f| f.try_into()).transpose()?,
             params: value.params.map(|p| p.into()),
@@ -536,6 +927,7 @@ impl TryFrom<api::grpc::qdrant::SearchPoints> for SearchRequest {
                     .unwrap_or_default(),
             ),
             score_threshold: value.score_threshold,
+            max_payload_bytes: value.max_payload_bytes.map(|bytes| bytes as usize),
         })
     }
 }
@@ -568,6 +960,7 @@ impl TryFrom<api::grpc::qdrant::RecommendPoints> for RecommendRequest {
             ),
             score_threshold: value.score_threshold,
             using: value.using.map(|name| name.into()),
+            max_payload_bytes: value.max_payload_bytes.map(|bytes| bytes as usize),
         })
     }
 }
@@ -580,8 +973,18 @@ impl From<VectorParams> for api::grpc::qdrant::VectorParams {
                 Distance::Cosine => api::grpc::qdrant::Distance::Cosine,
                 Distance::Euclid => api::grpc::qdrant::Distance::Euclid,
                 Distance::Dot => api::grpc::qdrant::Distance::Dot,
+                Distance::Custom(_) => api::grpc::qdrant::Distance::Custom,
             }
             .into(),
+            quantization_config: value.quantization_config.map(|config| config.into()),
+            on_disk: value.on_disk,
+            multivector_config: value.multivector_config.map(|config| config.into()),
+            hnsw_config: value.hnsw_config.map(|config| config.into()),
+            normalize: value.normalize,
+            custom_distance: match value.distance {
+                Distance::Custom(custom) => Some(custom.name().to_string()),
+                _ => None,
+            },
         }
     }
 }
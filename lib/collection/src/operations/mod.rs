@@ -3,8 +3,11 @@ pub mod config_diff;
 mod conversions;
 pub mod operation_effect;
 pub mod payload_ops;
+pub mod payload_transformers;
 pub mod point_ops;
+pub mod shard_selector;
 pub mod snapshot_ops;
+pub mod snapshot_storage_ops;
 pub mod types;
 
 use std::collections::HashMap;
@@ -30,6 +33,8 @@ pub enum FieldIndexOperations {
     CreateIndex(CreateIndex),
     /// Delete index for the field
     DeleteIndex(String),
+    /// Drop and build again index for payload field, optionally with the new schema
+    RebuildIndex(CreateIndex),
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -41,6 +46,16 @@ pub enum CollectionUpdateOperations {
     FieldIndexOperation(FieldIndexOperations),
 }
 
+impl CollectionUpdateOperations {
+    /// Operation creates new points, which have to be stored in a single shard
+    pub fn is_insert_operation(&self) -> bool {
+        matches!(
+            self,
+            CollectionUpdateOperations::PointOperation(point_ops::PointOperations::UpsertPoints(_))
+        )
+    }
+}
+
 /// A mapping of operation to shard.
 /// Is a result of splitting one operation into several shards by corresponding PointIds
 pub enum OperationToShard<O> {
@@ -0,0 +1,156 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use schemars::JsonSchema;
+use segment::types::{Payload, PayloadKeyType};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const DATETIME_FORMATS: [&str; 2] = ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S"];
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Built-in normalization of payload values
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadTransformerType {
+    /// Convert string values to lowercase
+    Lowercase,
+    /// Convert RFC 3339 or `YYYY-MM-DD[ HH:MM:SS]` strings into unix timestamps in seconds.
+    /// Values which can't be parsed are kept as is.
+    ParseDatetime,
+    /// Remove HTML tags from string values and decode basic HTML entities
+    StripHtml,
+}
+
+/// Transformation of a single payload field, applied to points before they are stored and indexed
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct PayloadTransformer {
+    /// Payload field to transform, nested fields are separated by `.`
+    pub key: PayloadKeyType,
+    /// Transformation to apply
+    pub transform: PayloadTransformerType,
+}
+
+/// Applies transformers to the payload in order of declaration.
+/// Returns `true` if the payload was changed.
+pub fn transform_payload(payload: &mut Payload, transformers: &[PayloadTransformer]) -> bool {
+    let mut changed = false;
+    for transformer in transformers {
+        if let Some(value) = payload.get_value_mut(&transformer.key) {
+            changed |= transform_value(value, transformer.transform);
+        }
+    }
+    changed
+}
+
+fn transform_value(value: &mut Value, transform: PayloadTransformerType) -> bool {
+    match value {
+        Value::Array(values) => values.iter_mut().fold(false, |changed, value| {
+            transform_value(value, transform) || changed
+        }),
+        Value::String(string) => {
+            let transformed = match transform {
+                PayloadTransformerType::Lowercase => Value::String(string.to_lowercase()),
+                PayloadTransformerType::ParseDatetime => match parse_timestamp(string) {
+                    Some(timestamp) => Value::from(timestamp),
+                    None => return false,
+                },
+                PayloadTransformerType::StripHtml => Value::String(strip_html(string)),
+            };
+            if *value == transformed {
+                return false;
+            }
+            *value = transformed;
+            true
+        }
+        _ => false,
+    }
+}
+
+fn parse_timestamp(string: &str) -> Option<i64> {
+    let string = string.trim();
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(string) {
+        return Some(datetime.timestamp());
+    }
+    for format in DATETIME_FORMATS {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(string, format) {
+            return Some(datetime.timestamp());
+        }
+    }
+    NaiveDate::parse_from_str(string, DATE_FORMAT)
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|datetime| datetime.timestamp())
+}
+
+fn strip_html(string: &str) -> String {
+    let mut text = String::with_capacity(string.len());
+    let mut in_tag = false;
+    for char in string.chars() {
+        match char {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(char),
+            _ => {}
+        }
+    }
+    // `&amp;` goes last, so escaped entities like `&amp;lt;` are not decoded twice
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn transformer(key: &str, transform: PayloadTransformerType) -> PayloadTransformer {
+        PayloadTransformer {
+            key: key.to_string(),
+            transform,
+        }
+    }
+
+    #[test]
+    fn test_transform_payload() {
+        let mut payload: Payload = json!({
+            "tags": ["Rust", "QDRANT"],
+            "meta": {
+                "created": "2022-10-01T12:00:00Z",
+                "day": "2022-10-01",
+                "broken": "yesterday",
+            },
+            "body": "<p>Fish &amp; <b>Chips</b></p>",
+            "count": 10,
+        })
+        .into();
+
+        let transformers = vec![
+            transformer("tags", PayloadTransformerType::Lowercase),
+            transformer("meta.created", PayloadTransformerType::ParseDatetime),
+            transformer("meta.day", PayloadTransformerType::ParseDatetime),
+            transformer("meta.broken", PayloadTransformerType::ParseDatetime),
+            transformer("body", PayloadTransformerType::StripHtml),
+            transformer("count", PayloadTransformerType::Lowercase),
+            transformer("missing", PayloadTransformerType::Lowercase),
+        ];
+
+        assert!(transform_payload(&mut payload, &transformers));
+        let expected: Payload = json!({
+            "tags": ["rust", "qdrant"],
+            "meta": {
+                "created": 1664625600,
+                "day": 1664582400,
+                "broken": "yesterday",
+            },
+            "body": "Fish & Chips",
+            "count": 10,
+        })
+        .into();
+        assert_eq!(payload, expected);
+    }
+}
@@ -37,6 +37,7 @@ impl TryFrom<Record> for PointStruct {
             id,
             payload,
             vector,
+            payload_truncated: _,
         } = record;
 
         if vector.is_none() {
@@ -0,0 +1,98 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::operations::types::SessionToken;
+use crate::shard::{PeerId, ShardId};
+
+/// Key, which is mapped to a shard in the same way as point ids.
+/// Points upserted with a shard key are stored in the shard of the key, instead of the shard of their ids.
+pub type ShardKey = String;
+
+/// Restricts an operation to a subset of the collection shards,
+/// e.g. to the shards holding the data of a single tenant
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[serde(untagged)]
+pub enum ShardSelector {
+    /// Shards, which the given keys are mapped to
+    ShardKeys { shard_keys: Vec<ShardKey> },
+    /// Shards with the given ids
+    ShardIds { shard_ids: Vec<ShardId> },
+    /// Replica of the shard on the given peer, e.g. a listener, which doesn't answer other reads.
+    /// Can't be used for updates, as they are applied to all replicas.
+    ShardReplica { shard_id: ShardId, peer_id: PeerId },
+}
+
+/// Client request with an optional shard selector next to the request fields
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct WithShardSelector<T> {
+    #[serde(flatten)]
+    pub request: T,
+    /// If set - execute the request only on the selected shards
+    #[serde(default)]
+    pub shard_selector: Option<ShardSelector>,
+    /// If set - read only from the replicas, which have applied the update of the token.
+    /// Ignored by updates.
+    #[serde(default)]
+    pub session_token: Option<SessionToken>,
+}
+
+/// Shards, an operation is executed on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShardSelectorInternal {
+    /// All shards of the collection, requested by a client
+    All,
+    /// Subset of shards, requested by a client
+    Selected(ShardSelector),
+    /// Single shard, requested by another peer, which merges results of the shards itself
+    ShardId(ShardId),
+    /// All or selected shards, requested by a client,
+    /// which reads from the replicas, consistent with its previous update
+    Session {
+        selector: Option<ShardSelector>,
+        token: SessionToken,
+    },
+}
+
+impl ShardSelectorInternal {
+    /// Operation is requested by a client, so results of the shards are merged on this peer
+    pub fn is_from_client(&self) -> bool {
+        !matches!(self, ShardSelectorInternal::ShardId(_))
+    }
+
+    /// Selection of a client, reading with the session token, if any
+    pub fn with_session(selector: Option<ShardSelector>, token: Option<SessionToken>) -> Self {
+        match token {
+            None => selector.into(),
+            Some(token) => ShardSelectorInternal::Session { selector, token },
+        }
+    }
+
+    pub fn session_token(&self) -> Option<&SessionToken> {
+        match self {
+            ShardSelectorInternal::Session { token, .. } => Some(token),
+            _ => None,
+        }
+    }
+
+    /// Shard and peer of the replica, selected by a client to read from
+    pub fn selected_replica(&self) -> Option<(ShardId, PeerId)> {
+        match self {
+            ShardSelectorInternal::Selected(ShardSelector::ShardReplica { shard_id, peer_id })
+            | ShardSelectorInternal::Session {
+                selector: Some(ShardSelector::ShardReplica { shard_id, peer_id }),
+                ..
+            } => Some((*shard_id, *peer_id)),
+            _ => None,
+        }
+    }
+}
+
+impl From<Option<ShardSelector>> for ShardSelectorInternal {
+    fn from(selector: Option<ShardSelector>) -> Self {
+        match selector {
+            None => ShardSelectorInternal::All,
+            Some(selector) => ShardSelectorInternal::Selected(selector),
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use std::io::{Read, Write};
 use std::path::Path;
 use std::time::SystemTime;
 
@@ -5,14 +6,82 @@ use api::grpc::conversions::date_time_to_proto;
 use chrono::NaiveDateTime;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use tar::Builder as TarBuilder;
 
-use crate::operations::types::CollectionResult;
+use crate::config::CollectionParams;
+use crate::operations::types::{CollectionError, CollectionResult};
+
+/// File with `SnapshotMetadata`, the first entry of the snapshot archive
+pub const SNAPSHOT_METADATA_FILE: &str = "snapshot_metadata.json";
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 pub struct SnapshotDescription {
     pub name: String,
     pub creation_time: Option<NaiveDateTime>,
     pub size: u64,
+    /// Content of the snapshot. None for full snapshots and snapshots made by older versions
+    #[serde(default)]
+    pub metadata: Option<SnapshotMetadata>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotCompression {
+    /// Plain tar archive
+    None,
+}
+
+/// Information about the content of the snapshot, available without unpacking it
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+pub struct SnapshotMetadata {
+    /// Version of Qdrant, which made the snapshot
+    pub version: String,
+    pub compression: SnapshotCompression,
+    /// Parameters of the collection the snapshot was made of
+    pub collection_params: CollectionParams,
+    /// Number of shards in the snapshot
+    pub shards_count: usize,
+    /// Number of points in the shards, which have data on the peer.
+    /// Points, updated while the snapshot is made, might be counted imprecisely
+    pub points_count: usize,
+}
+
+/// Add `metadata` as the first entry of the snapshot archive
+pub fn append_snapshot_metadata<W: Write>(
+    builder: &mut TarBuilder<W>,
+    metadata: &SnapshotMetadata,
+) -> CollectionResult<()> {
+    let data = serde_json::to_vec(metadata)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    builder.append_data(&mut header, SNAPSHOT_METADATA_FILE, data.as_slice())?;
+    Ok(())
+}
+
+/// Read metadata from the beginning of the snapshot archive.
+/// None if the archive has no metadata, e.g. it is made by an older version
+pub fn read_snapshot_metadata(archive: impl Read) -> Option<SnapshotMetadata> {
+    let mut archive = tar::Archive::new(archive);
+    let entry = archive.entries().ok()?.next()?.ok()?;
+    if entry.path().ok()?.as_ref() != Path::new(SNAPSHOT_METADATA_FILE) {
+        return None;
+    }
+    serde_json::from_reader(entry).ok()
+}
+
+/// Fail if there is less than `required_bytes` of free space on the volume of `dir`
+pub fn check_free_space(dir: &Path, required_bytes: u64) -> CollectionResult<()> {
+    let available_bytes = fs2::available_space(dir)?;
+    if available_bytes < required_bytes {
+        return Err(CollectionError::service_error(format!(
+            "Not enough free space in {} to create snapshot: {} MB available, {} MB required",
+            dir.display(),
+            available_bytes / 1024 / 1024,
+            required_bytes / 1024 / 1024,
+        )));
+    }
+    Ok(())
 }
 
 impl From<SnapshotDescription> for api::grpc::qdrant::SnapshotDescription {
@@ -35,10 +104,18 @@ pub async fn get_snapshot_description(path: &Path) -> CollectionResult<SnapshotD
             .map(|duration| NaiveDateTime::from_timestamp(duration.as_secs() as i64, 0))
     });
     let size = file_meta.len();
+    let archive_path = path.to_owned();
+    let metadata = tokio::task::spawn_blocking(move || {
+        std::fs::File::open(archive_path)
+            .ok()
+            .and_then(read_snapshot_metadata)
+    })
+    .await?;
     Ok(SnapshotDescription {
         name: name.to_string(),
         creation_time,
         size,
+        metadata,
     })
 }
 
@@ -0,0 +1,453 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use itertools::Itertools;
+use object_store::aws::AmazonS3Builder;
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectMeta, ObjectStore};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tar::Builder as TarBuilder;
+use tokio::fs::{copy, remove_dir_all, remove_file, rename};
+use tokio::io::AsyncWriteExt;
+use tokio::runtime::Handle;
+use tokio_util::io::SyncIoBridge;
+
+use crate::operations::snapshot_ops::{
+    append_snapshot_metadata, get_snapshot_description, list_snapshots_in_directory,
+    read_snapshot_metadata, SnapshotDescription, SnapshotMetadata,
+};
+use crate::operations::types::{CollectionError, CollectionResult};
+
+/// Kind of storage, where snapshots of collections are kept
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotsStorageType {
+    /// Local snapshots directory
+    Local,
+    /// Bucket of S3-compatible object store
+    S3,
+}
+
+impl Default for SnapshotsStorageType {
+    fn default() -> Self {
+        SnapshotsStorageType::Local
+    }
+}
+
+/// Connection to S3-compatible object store.
+/// Credentials, which are not set, are taken from the standard AWS environment variables.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct S3Config {
+    pub bucket: String,
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub access_key: Option<String>,
+    #[serde(default)]
+    pub secret_key: Option<String>,
+    /// Endpoint of the S3-compatible store, e.g. `http://localhost:9000` for MinIO
+    #[serde(default)]
+    pub endpoint_url: Option<String>,
+    /// Prefix of the keys of all snapshots, e.g. to keep snapshots of different peers apart
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct SnapshotsConfig {
+    #[serde(default)]
+    pub snapshots_storage: SnapshotsStorageType,
+    /// Required if `snapshots_storage` is `s3`
+    #[serde(default)]
+    pub s3_config: Option<S3Config>,
+}
+
+/// Size of the beginning of the snapshot object, which is fetched to read the snapshot metadata
+const METADATA_RANGE_BYTES: usize = 64 * 1024;
+
+/// Size of the chunks, in which snapshots are read from the object store
+const OBJECT_CHUNK_BYTES: usize = 16 * 1024 * 1024;
+
+/// Content of the snapshot, ready to be sent to the client
+pub enum SnapshotData {
+    File(PathBuf),
+    Object(SnapshotObject),
+}
+
+/// Snapshot in the object store, which can be read by byte ranges
+pub struct SnapshotObject {
+    store: Arc<dyn ObjectStore>,
+    key: ObjectPath,
+    pub size: u64,
+}
+
+impl SnapshotObject {
+    /// Stream `range` of bytes of the snapshot, e.g. to continue an interrupted download.
+    /// Object is read in chunks, so that a large range is never kept in memory.
+    pub fn stream(&self, range: Range<u64>) -> BoxStream<'static, std::io::Result<Bytes>> {
+        let store = self.store.clone();
+        let key = self.key.clone();
+        let end = range.end;
+        futures::stream::iter(range.step_by(OBJECT_CHUNK_BYTES))
+            .then(move |start| {
+                let store = store.clone();
+                let key = key.clone();
+                let chunk = start as usize..end.min(start + OBJECT_CHUNK_BYTES as u64) as usize;
+                async move {
+                    store
+                        .get_range(&key, chunk)
+                        .await
+                        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))
+                }
+            })
+            .boxed()
+    }
+}
+
+/// Storage of collection snapshots.
+///
+/// Snapshots are always addressed by their paths in the local snapshots directory.
+/// Object store keeps each snapshot under the key of its path, relative to the snapshots directory.
+#[derive(Debug, Clone)]
+pub enum SnapshotStorage {
+    LocalFs,
+    ObjectStore {
+        store: Arc<dyn ObjectStore>,
+        /// Local snapshots directory, keys are relative to it
+        root: PathBuf,
+        prefix: Option<String>,
+    },
+}
+
+impl Default for SnapshotStorage {
+    fn default() -> Self {
+        SnapshotStorage::LocalFs
+    }
+}
+
+impl SnapshotStorage {
+    pub fn from_config(config: &SnapshotsConfig, snapshots_path: &Path) -> CollectionResult<Self> {
+        match config.snapshots_storage {
+            SnapshotsStorageType::Local => Ok(SnapshotStorage::LocalFs),
+            SnapshotsStorageType::S3 => {
+                let s3_config =
+                    config
+                        .s3_config
+                        .as_ref()
+                        .ok_or_else(|| CollectionError::BadInput {
+                            description: "`s3_config` is required to store snapshots in S3"
+                                .to_string(),
+                        })?;
+                let mut builder = AmazonS3Builder::from_env().with_bucket_name(&s3_config.bucket);
+                if let Some(region) = &s3_config.region {
+                    builder = builder.with_region(region);
+                }
+                if let Some(access_key) = &s3_config.access_key {
+                    builder = builder.with_access_key_id(access_key);
+                }
+                if let Some(secret_key) = &s3_config.secret_key {
+                    builder = builder.with_secret_access_key(secret_key);
+                }
+                if let Some(endpoint_url) = &s3_config.endpoint_url {
+                    builder = builder
+                        .with_endpoint(endpoint_url)
+                        .with_allow_http(endpoint_url.starts_with("http://"));
+                }
+                let store = builder.build()?;
+                Ok(SnapshotStorage::ObjectStore {
+                    store: Arc::new(store),
+                    root: snapshots_path.to_owned(),
+                    prefix: s3_config.prefix.clone(),
+                })
+            }
+        }
+    }
+
+    /// Free space required to stage a snapshot of `data_size` bytes before it is stored.
+    /// Local archive is written next to the snapshot directory, archive is streamed directly into the object store.
+    pub fn staging_space(&self, data_size: u64) -> u64 {
+        match self {
+            SnapshotStorage::LocalFs => data_size.saturating_mul(2),
+            SnapshotStorage::ObjectStore { .. } => data_size,
+        }
+    }
+
+    pub async fn list_snapshots(
+        &self,
+        directory: &Path,
+    ) -> CollectionResult<Vec<SnapshotDescription>> {
+        match self {
+            SnapshotStorage::LocalFs => list_snapshots_in_directory(directory).await,
+            SnapshotStorage::ObjectStore {
+                store,
+                root,
+                prefix,
+            } => {
+                let directory_key = object_key(root, prefix, directory)?;
+                let listing = store.list_with_delimiter(Some(&directory_key)).await?;
+                let descriptions = listing
+                    .objects
+                    .iter()
+                    .filter(|object| {
+                        object
+                            .location
+                            .extension()
+                            .map_or(false, |extension| extension == "snapshot")
+                    })
+                    .map(|object| object_description(store.as_ref(), object));
+                Ok(futures::future::join_all(descriptions).await)
+            }
+        }
+    }
+
+    /// Archive the snapshot directory `source_path` and store the archive as `snapshot_path`.
+    /// The source directory is removed.
+    ///
+    /// Local archive is written into `archive_path` first, archive is streamed directly into the object store.
+    pub async fn store_snapshot(
+        &self,
+        source_path: &Path,
+        archive_path: &Path,
+        snapshot_path: &Path,
+        metadata: &SnapshotMetadata,
+        io_runtime: &Handle,
+    ) -> CollectionResult<SnapshotDescription> {
+        match self {
+            SnapshotStorage::LocalFs => {
+                archive_snapshot(
+                    source_path,
+                    archive_path,
+                    snapshot_path,
+                    metadata,
+                    io_runtime,
+                )
+                .await
+            }
+            SnapshotStorage::ObjectStore {
+                store,
+                root,
+                prefix,
+            } => {
+                let key = object_key(root, prefix, snapshot_path)?;
+                let (multipart_id, writer) = store.put_multipart(&key).await?;
+                // Writes of the bridge are driven by the current runtime, while the archive is built on `io_runtime`
+                let mut bridge = SyncIoBridge::new(writer);
+                let archive_source = source_path.to_owned();
+                let archive_metadata = metadata.clone();
+                let uploaded = io_runtime
+                    .spawn_blocking(move || -> CollectionResult<()> {
+                        let mut builder = TarBuilder::new(&mut bridge);
+                        append_snapshot_metadata(&mut builder, &archive_metadata)?;
+                        builder.append_dir_all(".", &archive_source)?;
+                        builder.finish()?;
+                        drop(builder);
+                        bridge.shutdown()?;
+                        Ok(())
+                    })
+                    .await
+                    .map_err(CollectionError::from)
+                    .and_then(|result| result);
+
+                remove_dir_all(source_path).await?;
+                if let Err(err) = uploaded {
+                    if let Err(abort_err) = store.abort_multipart(&key, &multipart_id).await {
+                        log::warn!("Failed to abort upload of snapshot {key}: {abort_err}");
+                    }
+                    return Err(err);
+                }
+                let object = store.head(&key).await?;
+                Ok(object_description(store.as_ref(), &object).await)
+            }
+        }
+    }
+
+    pub async fn get_snapshot(&self, snapshot_path: &Path) -> CollectionResult<SnapshotData> {
+        match self {
+            SnapshotStorage::LocalFs => {
+                check_local_snapshot(snapshot_path)?;
+                Ok(SnapshotData::File(snapshot_path.to_owned()))
+            }
+            SnapshotStorage::ObjectStore {
+                store,
+                root,
+                prefix,
+            } => {
+                let key = object_key(root, prefix, snapshot_path)?;
+                let object = store.head(&key).await?;
+                Ok(SnapshotData::Object(SnapshotObject {
+                    store: store.clone(),
+                    key,
+                    size: object.size as u64,
+                }))
+            }
+        }
+    }
+
+    /// Make the snapshot available as a local file.
+    /// Snapshot from the object store is downloaded into `temp_dir`, caller should remove it after use.
+    pub async fn fetch_snapshot(
+        &self,
+        snapshot_path: &Path,
+        temp_dir: &Path,
+    ) -> CollectionResult<PathBuf> {
+        match self.get_snapshot(snapshot_path).await? {
+            SnapshotData::File(path) => Ok(path),
+            SnapshotData::Object(object) => {
+                let file_name = snapshot_path.file_name().ok_or_else(|| {
+                    CollectionError::service_error(format!(
+                        "Invalid snapshot path {}",
+                        snapshot_path.display()
+                    ))
+                })?;
+                let local_path = temp_dir.join(file_name);
+                let mut file = tokio::fs::File::create(&local_path).await?;
+                let mut stream = object.stream(0..object.size);
+                while let Some(chunk) = stream.next().await {
+                    file.write_all(&chunk?).await?;
+                }
+                file.sync_all().await?;
+                Ok(local_path)
+            }
+        }
+    }
+
+    pub async fn delete_snapshot(&self, snapshot_path: &Path) -> CollectionResult<()> {
+        match self {
+            SnapshotStorage::LocalFs => {
+                remove_file(snapshot_path).await?;
+            }
+            SnapshotStorage::ObjectStore {
+                store,
+                root,
+                prefix,
+            } => {
+                store
+                    .delete(&object_key(root, prefix, snapshot_path)?)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Archive the snapshot directory `source_path` into `archive_path` on the `io_runtime`
+/// and move the archive to `snapshot_path`. The source directory is removed.
+pub(crate) async fn archive_snapshot(
+    source_path: &Path,
+    archive_path: &Path,
+    snapshot_path: &Path,
+    metadata: &SnapshotMetadata,
+    io_runtime: &Handle,
+) -> CollectionResult<SnapshotDescription> {
+    // have to use std here, cause TarBuilder is not async
+    let archive_source = source_path.to_owned();
+    let archive_target = archive_path.to_owned();
+    let archive_metadata = metadata.clone();
+    io_runtime
+        .spawn_blocking(move || -> CollectionResult<()> {
+            let file = std::fs::File::create(&archive_target)?;
+            let mut builder = TarBuilder::new(file);
+            append_snapshot_metadata(&mut builder, &archive_metadata)?;
+            // archive recursively snapshot directory `archive_source` into `archive_target`
+            builder.append_dir_all(".", &archive_source)?;
+            builder.finish()?;
+            Ok(())
+        })
+        .await??;
+
+    // remove temporary snapshot directory
+    remove_dir_all(source_path).await?;
+
+    // move snapshot to permanent location
+    // We can't move right away, because snapshot folder can be on another mounting point.
+    // We can't copy to the target location directly, cause copy is not atomic.
+    let snapshot_path_tmp = snapshot_path.with_extension("tmp");
+    copy(archive_path, &snapshot_path_tmp).await?;
+    rename(&snapshot_path_tmp, snapshot_path).await?;
+    remove_file(archive_path).await?;
+
+    get_snapshot_description(snapshot_path).await
+}
+
+fn check_local_snapshot(snapshot_path: &Path) -> CollectionResult<()> {
+    if !snapshot_path.exists() {
+        return Err(CollectionError::NotFound {
+            what: format!(
+                "Snapshot {}",
+                snapshot_path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+            ),
+        });
+    }
+    Ok(())
+}
+
+fn object_key(root: &Path, prefix: &Option<String>, path: &Path) -> CollectionResult<ObjectPath> {
+    let relative = path.strip_prefix(root).map_err(|_| {
+        CollectionError::service_error(format!(
+            "Snapshot path {} is outside of the snapshots directory",
+            path.display()
+        ))
+    })?;
+    let relative = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .join("/");
+    let key = match prefix {
+        Some(prefix) => format!("{}/{}", prefix.trim_matches('/'), relative),
+        None => relative,
+    };
+    ObjectPath::parse(&key)
+        .map_err(|err| CollectionError::service_error(format!("Invalid snapshot key {key}: {err}")))
+}
+
+async fn object_description(store: &dyn ObjectStore, object: &ObjectMeta) -> SnapshotDescription {
+    let metadata_range = 0..object.size.min(METADATA_RANGE_BYTES);
+    let metadata = match store.get_range(&object.location, metadata_range).await {
+        Ok(head) => read_snapshot_metadata(head.as_ref()),
+        Err(err) => {
+            log::warn!(
+                "Failed to read metadata of snapshot {}: {err}",
+                object.location
+            );
+            None
+        }
+    };
+    SnapshotDescription {
+        name: object.location.filename().unwrap_or_default().to_string(),
+        creation_time: Some(object.last_modified.naive_utc()),
+        size: object.size as u64,
+        metadata,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn object_keys_are_relative_to_snapshots_directory() {
+        let root = Path::new("./snapshots");
+        let path = root.join("test").join("test-2022.snapshot");
+        assert_eq!(
+            object_key(root, &None, &path).unwrap().as_ref(),
+            "test/test-2022.snapshot"
+        );
+        assert_eq!(
+            object_key(root, &Some("/peer-1/".to_string()), &path)
+                .unwrap()
+                .as_ref(),
+            "peer-1/test/test-2022.snapshot"
+        );
+        assert!(object_key(root, &None, Path::new("/tmp/test.snapshot")).is_err());
+    }
+}
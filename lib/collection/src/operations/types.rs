@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use std::cmp::max;
+use std::collections::{BTreeMap, HashMap};
 use std::result;
 use std::time::SystemTimeError;
 
@@ -7,12 +8,12 @@ use futures::io;
 use schemars::JsonSchema;
 use segment::common::file_operations::FileStorageError;
 use segment::data_types::vectors::{
-    NamedVectorStruct, VectorStruct, VectorType, DEFAULT_VECTOR_NAME,
+    NamedVectorStruct, Vector, VectorStruct, VectorType, DEFAULT_VECTOR_NAME,
 };
 use segment::entry::entry_point::OperationError;
 use segment::types::{
-    Filter, Payload, PayloadIndexInfo, PayloadKeyType, PointIdType, ScoreType, SearchParams,
-    SeqNumberType, WithPayloadInterface, WithVector,
+    Filter, Payload, PayloadFieldSchema, PayloadIndexInfo, PayloadKeyType, PointIdType, ScoreType,
+    SearchParams, SeqNumberType, WithPayloadInterface, WithVector,
 };
 use serde;
 use serde::{Deserialize, Serialize};
@@ -23,7 +24,8 @@ use tokio::sync::oneshot::error::RecvError as OneshotRecvError;
 use tokio::task::JoinError;
 use tonic::codegen::http::uri::InvalidUri;
 
-use crate::config::CollectionConfig;
+use crate::config::{CollectionConfig, HashRingType};
+use crate::operations::CollectionUpdateOperations;
 use crate::save_on_disk;
 use crate::shard::{PeerId, ShardId};
 use crate::wal::WalError;
@@ -54,7 +56,7 @@ pub enum OptimizersStatus {
 }
 
 /// Point data
-#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct Record {
     /// Id of the point
@@ -63,6 +65,10 @@ pub struct Record {
     pub payload: Option<Payload>,
     /// Vector of the point
     pub vector: Option<VectorStruct>,
+    /// True if some payload fields were omitted to fit the response size limit
+    #[serde(default)]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub payload_truncated: bool,
 }
 
 /// Current statistics and configuration of the collection
@@ -86,6 +92,20 @@ pub struct CollectionInfo {
     pub payload_schema: HashMap<PayloadKeyType, PayloadIndexInfo>,
 }
 
+/// Add index info of a single segment or shard into the combined payload schema.
+/// Index is considered `Building` if it is not ready in any of the parts.
+pub fn merge_index_info(
+    schema: &mut HashMap<PayloadKeyType, PayloadIndexInfo>,
+    key: PayloadKeyType,
+    info: PayloadIndexInfo,
+) {
+    let status = match schema.get(&key) {
+        Some(existing) => max(existing.status, info.status),
+        None => info.status,
+    };
+    schema.insert(key, PayloadIndexInfo { status, ..info });
+}
+
 /// Current clustering distribution for the collection
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct CollectionClusterInfo {
@@ -101,6 +121,19 @@ pub struct CollectionClusterInfo {
     pub shard_transfers: Vec<ShardTransferInfo>,
 }
 
+/// Expected point movement, if the collection is resharded to another number of shards
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ShardMovementEstimate {
+    /// Scheme of distributing points across shards, used by the collection
+    pub hash_ring: HashRingType,
+    /// Current number of shards
+    pub shard_count: usize,
+    /// Number of shards after resharding
+    pub target_shard_count: usize,
+    /// Expected fraction of points, which move to another shard
+    pub moved_points_fraction: f64,
+}
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct ShardTransferInfo {
     pub shard_id: ShardId,
@@ -115,6 +148,9 @@ pub struct LocalShardInfo {
     pub shard_id: ShardId,
     /// Number of points in the shard
     pub points_count: usize,
+    /// Replication lag of the local replica, if the shard is replicated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replication_lag: Option<ReplicationLag>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
@@ -124,9 +160,25 @@ pub struct RemoteShardInfo {
     pub shard_id: ShardId,
     /// Remote peer id
     pub peer_id: PeerId,
+    /// Replication lag of the remote replica, if the shard is replicated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replication_lag: Option<ReplicationLag>,
 }
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+/// Updates of a shard replica, which it has not acknowledged yet.
+/// Tracked by each peer for the updates it sends, so the lag may differ between peers.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct ReplicationLag {
+    /// Number of updates sent to the replica since the last one it acknowledged
+    pub unacked_operations: usize,
+    /// Milliseconds since the oldest not acknowledged update was sent,
+    /// i.e. for how long the replica is behind
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lag_ms: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq, Clone, Copy)]
 #[serde(rename_all = "snake_case")]
 pub enum UpdateStatus {
     /// Request is saved to WAL and will be process in a queue
@@ -142,6 +194,51 @@ pub struct UpdateResult {
     pub operation_id: SeqNumberType,
     /// Update status
     pub status: UpdateStatus,
+    /// Attach to the subsequent reads to see the result of this update
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_token: Option<SessionToken>,
+}
+
+/// Outcome of an update in a single shard
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ShardUpdateResult {
+    pub shard_id: ShardId,
+    /// Update status, if the shard applied the update
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<UpdateStatus>,
+    /// Error, if the shard failed to apply the update
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Replicas, which acknowledged an update, by shard.
+/// Reads with the token only use replicas, which have applied the update,
+/// so a client reads its own writes even with replicated shards.
+/// Tokens of sequential updates are not merged, the token of the latest update is enough,
+/// as replicas, which miss an update, are marked dead and stop answering reads until synced.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct SessionToken {
+    pub shards: BTreeMap<ShardId, ShardSession>,
+}
+
+impl SessionToken {
+    /// Combine tokens of the parts of the same update, sent to different shards
+    pub fn merge(&mut self, other: SessionToken) {
+        self.shards.extend(other.shards)
+    }
+}
+
+/// Replicas of a single shard, which acknowledged an update
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct ShardSession {
+    /// Sequential number of the operation on each replica, which acknowledged the update
+    pub replicas: BTreeMap<PeerId, SeqNumberType>,
+    /// Whether the replicas applied the update before acknowledging it, i.e. `wait=true` was used.
+    /// Otherwise only the local replica of the reading peer can be made to apply it before the read.
+    pub applied: bool,
 }
 
 /// Scroll request - paginate over all points which matches given condition
@@ -159,6 +256,11 @@ pub struct ScrollRequest {
     /// Whether to return the point vector with the result?
     #[serde(default)]
     pub with_vector: WithVector,
+    /// Limit total size of payloads in the response (in bytes).
+    /// Largest payload fields are omitted once the limit is reached.
+    /// If not set - collection-level limit is used.
+    #[serde(default)]
+    pub max_payload_bytes: Option<usize>,
 }
 
 impl Default for ScrollRequest {
@@ -169,6 +271,7 @@ impl Default for ScrollRequest {
             filter: None,
             with_payload: Some(WithPayloadInterface::Bool(true)),
             with_vector: WithVector::Bool(false),
+            max_payload_bytes: None,
         }
     }
 }
@@ -183,6 +286,65 @@ pub struct ScrollResult {
     pub next_page_offset: Option<PointIdType>,
 }
 
+/// Export points, which match the filter, as a stream of JSON lines: one point per line
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ExportRequest {
+    /// Export only points which satisfy this conditions. If not provided - all points.
+    pub filter: Option<Filter>,
+    /// Select which payload to export with the points. Default: All
+    pub with_payload: Option<WithPayloadInterface>,
+    /// Select which vectors to export with the points. Default: All
+    #[serde(default = "default_export_with_vector")]
+    pub with_vector: WithVector,
+}
+
+fn default_export_with_vector() -> WithVector {
+    WithVector::Bool(true)
+}
+
+/// How points are treated in the source collection by the move operation
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MovePointsMode {
+    /// Points are deleted from the source collection once all points of their shard are stored
+    /// in the target collection. Points updated in the source collection during the move are
+    /// deleted as well, the target collection keeps the copied version.
+    Move,
+    /// Points are kept in the source collection
+    Copy,
+}
+
+impl Default for MovePointsMode {
+    fn default() -> Self {
+        MovePointsMode::Move
+    }
+}
+
+/// Move request - transfer points which match given condition into another collection
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct MovePointsRequest {
+    /// Name of the collection to store points into.
+    /// Must have the same vectors configuration as the source collection.
+    pub target_collection: String,
+    /// Look only for points which satisfies this conditions. If not provided - all points.
+    pub filter: Option<Filter>,
+    /// Whether points should be deleted from the source collection. Default: move
+    #[serde(default)]
+    pub mode: MovePointsMode,
+    /// Number of points transferred at once. Default: 100
+    pub batch_size: Option<usize>,
+}
+
+/// Result of the points move request
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MovePointsResult {
+    /// Number of points stored in the target collection
+    pub points_count: usize,
+}
+
 /// Search request.
 /// Holds all conditions and parameters for the search of most similar points by vector similarity
 /// given the filtering restrictions.
@@ -213,6 +375,11 @@ pub struct SearchRequest {
     /// Score of the returned result might be higher or smaller than the threshold depending on the
     /// Distance function used. E.g. for cosine similarity only higher scores will be returned.
     pub score_threshold: Option<ScoreType>,
+    /// Limit total size of payloads in the response (in bytes).
+    /// Largest payload fields are omitted once the limit is reached.
+    /// If not set - collection-level limit is used.
+    #[serde(default)]
+    pub max_payload_bytes: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
@@ -231,6 +398,11 @@ pub struct PointRequest {
     /// Whether to return the point vector with the result?
     #[serde(default)]
     pub with_vector: WithVector,
+    /// Limit total size of payloads in the response (in bytes).
+    /// Largest payload fields are omitted once the limit is reached.
+    /// If not set - collection-level limit is used.
+    #[serde(default)]
+    pub max_payload_bytes: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
@@ -285,6 +457,11 @@ pub struct RecommendRequest {
     /// Define which vector to use for recommendation, if not specified - try to use default vector
     #[serde(default)]
     pub using: Option<UsingVector>,
+    /// Limit total size of payloads in the response (in bytes).
+    /// Largest payload fields are omitted once the limit is reached.
+    /// If not set - collection-level limit is used.
+    #[serde(default)]
+    pub max_payload_bytes: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
@@ -311,13 +488,81 @@ pub fn default_exact_count() -> bool {
     true
 }
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub struct CountResult {
     /// Number of points which satisfy the conditions
     pub count: usize,
 }
 
+pub const DEFAULT_INDEX_ESTIMATION_SAMPLE_SIZE: usize = 1000;
+
+/// Estimate resources required by a payload index, without creating it.
+/// The estimation is based on the values of a sample of points.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct FieldIndexEstimationRequest {
+    pub field_name: PayloadKeyType,
+    #[serde(alias = "field_type")]
+    pub field_schema: PayloadFieldSchema,
+    /// Amount of points to analyze. Default: 1000
+    pub sample_size: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct FieldIndexEstimation {
+    /// Approximate number of points in the collection
+    pub points_count: usize,
+    /// Number of points, which values were analyzed
+    pub sampled_points_count: usize,
+    /// Estimated number of points with values of the field
+    pub indexed_points_count: usize,
+    /// Estimated number of indexed values, or tokens for the text index
+    pub values_count: usize,
+    /// Estimated number of distinct values, or tokens for the text index
+    pub distinct_values_count: usize,
+    /// Estimated RAM required by the index on all shards
+    pub ram_usage_bytes: usize,
+    /// Estimated disk space required by the index on all shards
+    pub disk_usage_bytes: usize,
+}
+
+pub const DEFAULT_WAL_OPERATIONS_LIMIT: usize = 100;
+
+/// Operation, stored in the WAL of a shard
+#[derive(Debug, Serialize)]
+pub struct WalOperation {
+    pub op_num: SeqNumberType,
+    pub operation: CollectionUpdateOperations,
+}
+
+/// Operations in the WAL of a shard. Operations are removed from the WAL once they are
+/// acknowledged, i.e. persisted in segments, so these might be missing in segments after a crash.
+#[derive(Debug, Serialize)]
+pub struct WalInfo {
+    /// Number of the oldest operation in the WAL
+    pub first_op_num: SeqNumberType,
+    /// Number of operations in the WAL
+    pub operations_count: u64,
+    /// Requested page of operations
+    pub operations: Vec<WalOperation>,
+}
+
+pub const DEFAULT_CLEANUP_BATCH_SIZE: usize = 1000;
+
+/// Progress of deleting points, which don't belong to the shard according to the hash ring
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct ShardCleanupProgress {
+    /// Number of points of the shard, checked so far
+    pub checked_points: usize,
+    /// Number of points, deleted because they belong to other shards
+    pub deleted_points: usize,
+    /// True, once all points of the shard are checked
+    pub finished: bool,
+}
+
 #[derive(Error, Debug, Clone)]
 #[error("{0}")]
 pub enum CollectionError {
@@ -342,6 +587,8 @@ pub enum CollectionError {
         shards_total: u32,
         shards_failed: u32,
         first_err: Box<CollectionError>,
+        /// Outcome of the update in each shard, so that only the failed shards can be retried
+        shard_results: Vec<ShardUpdateResult>,
     },
 }
 
@@ -381,6 +628,9 @@ impl From<OperationError> for CollectionError {
             OperationError::MissedVectorName { .. } => Self::BadInput {
                 description: format!("{}", err),
             },
+            OperationError::WrongSparseVector { .. } => Self::BadInput {
+                description: format!("{}", err),
+            },
             OperationError::PointIdError { missed_point_id } => {
                 Self::PointNotFound { missed_point_id }
             }
@@ -406,6 +656,17 @@ impl From<OneshotRecvError> for CollectionError {
     }
 }
 
+impl From<object_store::Error> for CollectionError {
+    fn from(err: object_store::Error) -> Self {
+        match err {
+            object_store::Error::NotFound { path, .. } => Self::NotFound { what: path },
+            _ => Self::ServiceError {
+                error: format!("Object store error: {}", err),
+            },
+        }
+    }
+}
+
 impl From<JoinError> for CollectionError {
     fn from(err: JoinError) -> Self {
         Self::ServiceError {
@@ -551,7 +812,7 @@ impl Record {
                     None
                 }
             }
-            Some(VectorStruct::Multi(vectors)) => vectors.get(name),
+            Some(VectorStruct::Multi(vectors)) => vectors.get(name).and_then(Vector::as_dense),
             None => None,
         }
     }
@@ -51,6 +51,7 @@ pub struct OptimizersConfig {
     pub memmap_threshold: Option<usize>,
     /// Maximum size (in KiloBytes) of vectors allowed for plain index.
     /// Default value based on <https://github.com/google-research/google-research/blob/master/scann/docs/algorithms.md>
+    /// To disable vector indexing and always use exact search, set to `0`.
     /// Note: 1Kb = 1 vector of size 256
     #[serde(alias = "indexing_threshold_kb")]
     pub indexing_threshold: usize,
@@ -58,6 +59,33 @@ pub struct OptimizersConfig {
     pub flush_interval_sec: u64,
     /// Maximum available threads for optimization workers
     pub max_optimization_threads: usize,
+    /// Do not merge segments, if the merged segment would take more than this size on disk (in KiloBytes).
+    /// Unlike `max_segment_size`, accounts for payload, indexes and all other data of the segments.
+    /// If not set, size on disk is not limited.
+    #[serde(default)]
+    pub max_segment_disk_size: Option<usize>,
+    /// Strategy of selecting segments to merge, once there are more segments than `default_segment_number`
+    #[serde(default)]
+    pub merge_policy: MergePolicy,
+}
+
+/// Strategy of selecting segments to merge
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum MergePolicy {
+    /// Merge segments, neighbouring by principal payload values, if there are any.
+    /// Otherwise merge the smallest segments
+    Auto,
+    /// Always merge the smallest segments
+    Smallest,
+    /// Merge segments of similar size, so that large segments are not rewritten by every merge
+    Tiered,
+}
+
+impl Default for MergePolicy {
+    fn default() -> Self {
+        MergePolicy::Auto
+    }
 }
 
 impl OptimizersConfig {
@@ -100,6 +128,8 @@ pub fn build_optimizers(
     Arc::new(vec![
         Arc::new(MergeOptimizer::new(
             optimizers_config.get_number_segments(),
+            optimizers_config.merge_policy,
+            optimizers_config.max_segment_disk_size,
             threshold_config.clone(),
             segments_path.clone(),
             temp_segments_path.clone(),
@@ -0,0 +1,346 @@
+//! Node-level resource watchdog.
+//!
+//! Periodically samples process RSS, page-cache pressure and disk latency and decides whether
+//! background work (optimizations and shard transfers) should be throttled.
+//! Resources are shared by all collections of the node, so all of them get the same throttle.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use schemars::JsonSchema;
+use segment::telemetry::Anonymize;
+use serde::{Deserialize, Serialize};
+
+const DISK_PROBE_FILE: &str = ".watchdog_probe";
+
+/// Interval between throttle checks for the tasks waiting for resources
+const THROTTLE_WAIT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Throttle state of the node, set by the watchdog and checked by background tasks.
+/// Clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct ResourceThrottle(Arc<AtomicBool>);
+
+impl ResourceThrottle {
+    /// Returns true if background tasks should currently be paused
+    pub fn is_throttled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Waits until the watchdog releases the throttle
+    pub async fn wait_until_unthrottled(&self) {
+        while self.is_throttled() {
+            tokio::time::sleep(THROTTLE_WAIT_INTERVAL).await;
+        }
+    }
+
+    /// Sets the throttle state, returns the previous one
+    fn set_throttled(&self, throttled: bool) -> bool {
+        self.0.swap(throttled, Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+pub struct ResourceWatchdogConfig {
+    /// If false - watchdog is not started and background tasks are never throttled
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// How often resources are sampled
+    #[serde(default = "default_check_interval_ms")]
+    pub check_interval_ms: u64,
+    /// Throttle if process RSS exceeds this fraction of total RAM
+    #[serde(default = "default_max_rss_ratio")]
+    pub max_rss_ratio: f64,
+    /// Throttle if available memory (free + reclaimable page cache) drops below this fraction of total RAM
+    #[serde(default = "default_min_available_memory_ratio")]
+    pub min_available_memory_ratio: f64,
+    /// Throttle if a small synced write into the storage directory takes longer than this
+    #[serde(default = "default_max_disk_latency_ms")]
+    pub max_disk_latency_ms: u64,
+}
+
+impl Default for ResourceWatchdogConfig {
+    fn default() -> Self {
+        ResourceWatchdogConfig {
+            enabled: default_enabled(),
+            check_interval_ms: default_check_interval_ms(),
+            max_rss_ratio: default_max_rss_ratio(),
+            min_available_memory_ratio: default_min_available_memory_ratio(),
+            max_disk_latency_ms: default_max_disk_latency_ms(),
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_check_interval_ms() -> u64 {
+    1000
+}
+
+fn default_max_rss_ratio() -> f64 {
+    0.9
+}
+
+fn default_min_available_memory_ratio() -> f64 {
+    0.05
+}
+
+fn default_max_disk_latency_ms() -> u64 {
+    500
+}
+
+/// Single measurement of the node resources
+#[derive(Serialize, Deserialize, Clone, Debug, Default, JsonSchema)]
+pub struct ResourceSample {
+    pub rss_bytes: Option<u64>,
+    pub total_memory_bytes: Option<u64>,
+    pub available_memory_bytes: Option<u64>,
+    pub disk_latency_ms: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, JsonSchema)]
+pub struct ResourceWatchdogTelemetry {
+    pub enabled: bool,
+    pub throttled: bool,
+    /// Number of times background tasks were throttled since start
+    pub throttle_count: usize,
+    /// Reason of the latest throttle decision
+    pub last_throttle_reason: Option<String>,
+    pub last_sample: Option<ResourceSample>,
+}
+
+impl Anonymize for ResourceWatchdogTelemetry {
+    fn anonymize(&self) -> Self {
+        self.clone()
+    }
+}
+
+pub struct ResourceWatchdog {
+    config: ResourceWatchdogConfig,
+    probe_path: PathBuf,
+    throttle: ResourceThrottle,
+    telemetry: Arc<Mutex<ResourceWatchdogTelemetry>>,
+}
+
+impl ResourceWatchdog {
+    pub fn new(
+        config: ResourceWatchdogConfig,
+        storage_path: &Path,
+        throttle: ResourceThrottle,
+    ) -> Self {
+        let telemetry = ResourceWatchdogTelemetry {
+            enabled: config.enabled,
+            ..Default::default()
+        };
+        Self {
+            config,
+            probe_path: storage_path.join(DISK_PROBE_FILE),
+            throttle,
+            telemetry: Arc::new(Mutex::new(telemetry)),
+        }
+    }
+
+    pub fn telemetry(&self) -> Arc<Mutex<ResourceWatchdogTelemetry>> {
+        self.telemetry.clone()
+    }
+
+    /// Starts the watchdog in a separate thread. Returns `None` if watchdog is disabled.
+    pub fn run(self) -> Option<JoinHandle<()>> {
+        if !self.config.enabled {
+            return None;
+        }
+        let handle = thread::Builder::new()
+            .name("resource_watchdog".to_string())
+            .spawn(move || loop {
+                thread::sleep(Duration::from_millis(self.config.check_interval_ms));
+                let sample = self.sample();
+                self.apply(sample);
+            })
+            .expect("Can't spawn resource watchdog thread");
+        Some(handle)
+    }
+
+    fn sample(&self) -> ResourceSample {
+        let (total_memory_bytes, available_memory_bytes) = read_meminfo().unwrap_or((None, None));
+        ResourceSample {
+            rss_bytes: read_rss(),
+            total_memory_bytes,
+            available_memory_bytes,
+            disk_latency_ms: self.probe_disk_latency(),
+        }
+    }
+
+    /// Measures the time of a small synced write into the storage directory
+    fn probe_disk_latency(&self) -> Option<u64> {
+        let start = Instant::now();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.probe_path)
+            .ok()?;
+        file.write_all(&[0u8; 512]).ok()?;
+        file.sync_data().ok()?;
+        Some(start.elapsed().as_millis() as u64)
+    }
+
+    /// Returns the reason to throttle, if any of the limits is exceeded
+    fn check_limits(&self, sample: &ResourceSample) -> Option<String> {
+        if let (Some(rss), Some(total)) = (sample.rss_bytes, sample.total_memory_bytes) {
+            if total > 0 && rss as f64 > total as f64 * self.config.max_rss_ratio {
+                return Some(format!(
+                    "RSS {} MB exceeds {:.0}% of total memory {} MB",
+                    rss / 1024 / 1024,
+                    self.config.max_rss_ratio * 100.0,
+                    total / 1024 / 1024,
+                ));
+            }
+        }
+        if let (Some(available), Some(total)) =
+            (sample.available_memory_bytes, sample.total_memory_bytes)
+        {
+            if total > 0
+                && (available as f64) < total as f64 * self.config.min_available_memory_ratio
+            {
+                return Some(format!(
+                    "available memory {} MB is below {:.0}% of total memory {} MB",
+                    available / 1024 / 1024,
+                    self.config.min_available_memory_ratio * 100.0,
+                    total / 1024 / 1024,
+                ));
+            }
+        }
+        if let Some(latency) = sample.disk_latency_ms {
+            if latency > self.config.max_disk_latency_ms {
+                return Some(format!(
+                    "disk latency {} ms exceeds {} ms",
+                    latency, self.config.max_disk_latency_ms
+                ));
+            }
+        }
+        None
+    }
+
+    fn apply(&self, sample: ResourceSample) {
+        let reason = self.check_limits(&sample);
+        let was_throttled = self.throttle.set_throttled(reason.is_some());
+
+        let mut telemetry = self.telemetry.lock();
+        match (&reason, was_throttled) {
+            (Some(reason), false) => {
+                log::warn!("Throttling optimizers and shard transfers: {}", reason);
+                telemetry.throttle_count += 1;
+                telemetry.last_throttle_reason = Some(reason.clone());
+            }
+            (None, true) => {
+                log::info!("Resources are back to normal, resuming optimizers and shard transfers");
+            }
+            _ => {}
+        }
+        telemetry.throttled = reason.is_some();
+        telemetry.last_sample = Some(sample);
+    }
+}
+
+fn read_rss() -> Option<u64> {
+    // Second field of statm is the resident set size in pages
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let rss_pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    Some(rss_pages * page_size())
+}
+
+#[cfg(unix)]
+fn page_size() -> u64 {
+    // SAFETY: `sysconf` has no preconditions and only reads a system constant
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if page_size > 0 {
+        page_size as u64
+    } else {
+        4096
+    }
+}
+
+#[cfg(not(unix))]
+fn page_size() -> u64 {
+    4096
+}
+
+/// Returns total and available memory in bytes
+fn read_meminfo() -> Option<(Option<u64>, Option<u64>)> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let mut total = None;
+    let mut available = None;
+    for line in meminfo.lines() {
+        let mut parts = line.split_whitespace();
+        let key = parts.next();
+        let value_kb = parts.next().and_then(|value| value.parse::<u64>().ok());
+        match key {
+            Some("MemTotal:") => total = value_kb.map(|kb| kb * 1024),
+            Some("MemAvailable:") => available = value_kb.map(|kb| kb * 1024),
+            _ => {}
+        }
+    }
+    Some((total, available))
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+
+    #[test]
+    fn test_check_limits() {
+        let dir = Builder::new().prefix("watchdog").tempdir().unwrap();
+        let throttle = ResourceThrottle::default();
+        let watchdog = ResourceWatchdog::new(
+            ResourceWatchdogConfig::default(),
+            dir.path(),
+            throttle.clone(),
+        );
+
+        let healthy = ResourceSample {
+            rss_bytes: Some(100),
+            total_memory_bytes: Some(1000),
+            available_memory_bytes: Some(500),
+            disk_latency_ms: Some(1),
+        };
+        assert!(watchdog.check_limits(&healthy).is_none());
+
+        let high_rss = ResourceSample {
+            rss_bytes: Some(950),
+            ..healthy.clone()
+        };
+        assert!(watchdog.check_limits(&high_rss).is_some());
+
+        let low_memory = ResourceSample {
+            available_memory_bytes: Some(10),
+            ..healthy.clone()
+        };
+        assert!(watchdog.check_limits(&low_memory).is_some());
+
+        let slow_disk = ResourceSample {
+            disk_latency_ms: Some(10_000),
+            ..healthy.clone()
+        };
+        assert!(watchdog.check_limits(&slow_disk).is_some());
+
+        assert!(watchdog.probe_disk_latency().is_some());
+
+        // Clones of the handle share the state, other handles are not affected
+        watchdog.apply(slow_disk);
+        assert!(throttle.is_throttled());
+        assert!(!ResourceThrottle::default().is_throttled());
+        watchdog.apply(healthy);
+        assert!(!throttle.is_throttled());
+    }
+}
@@ -0,0 +1,231 @@
+use segment::data_types::vectors::{Vector, VectorStruct};
+use segment::types::{ExtendedPointId, WithPayloadInterface, WithVector};
+use serde_json::Value;
+
+use crate::operations::types::{CollectionResult, Record};
+use crate::shard::ShardOperation;
+
+/// Number of points read at once to compute the checksum of a shard
+const CHECKSUM_BATCH_SIZE: usize = 1000;
+
+/// Summary of the points stored in a shard.
+/// Replicas of the shard, which are in sync, have equal checksums.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShardChecksum {
+    pub points_count: usize,
+    /// Order independent combination of hashes of ids, vectors and payloads of the points
+    pub checksum: u64,
+}
+
+impl ShardChecksum {
+    pub fn add_record(&mut self, record: &Record) {
+        self.points_count += 1;
+        self.checksum = self.checksum.wrapping_add(record_hash(record));
+    }
+}
+
+/// Compute checksum of the shard, reading all of its points in batches
+pub async fn compute_checksum(
+    shard: &(dyn ShardOperation + Send + Sync),
+) -> CollectionResult<ShardChecksum> {
+    let with_payload = WithPayloadInterface::Bool(true);
+    let with_vector = WithVector::Bool(true);
+    let mut checksum = ShardChecksum::default();
+    let mut offset = None;
+    loop {
+        // Read one more point to get the offset of the next batch
+        let mut records = shard
+            .scroll_by(
+                offset,
+                CHECKSUM_BATCH_SIZE + 1,
+                &with_payload,
+                &with_vector,
+                None,
+            )
+            .await?;
+        offset = if records.len() > CHECKSUM_BATCH_SIZE {
+            records.pop().map(|record| record.id)
+        } else {
+            None
+        };
+        for record in &records {
+            checksum.add_record(record);
+        }
+        if offset.is_none() {
+            return Ok(checksum);
+        }
+    }
+}
+
+/// FNV-1a hash.
+/// Unlike the std hasher, it is stable, so checksums of different builds are comparable.
+struct StableHasher(u64);
+
+impl StableHasher {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_str(&mut self, value: &str) {
+        self.write_u64(value.len() as u64);
+        self.write(value.as_bytes());
+    }
+
+    fn write_floats(&mut self, values: &[f32]) {
+        self.write_u64(values.len() as u64);
+        for value in values {
+            self.write(&value.to_bits().to_le_bytes());
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+fn record_hash(record: &Record) -> u64 {
+    let mut hasher = StableHasher::new();
+    match record.id {
+        ExtendedPointId::NumId(id) => {
+            hasher.write_u64(0);
+            hasher.write_u64(id);
+        }
+        ExtendedPointId::Uuid(id) => {
+            hasher.write_u64(1);
+            hasher.write(id.as_bytes());
+        }
+    }
+    match &record.vector {
+        None => hasher.write_u64(0),
+        Some(VectorStruct::Single(vector)) => {
+            hasher.write_u64(1);
+            hasher.write_floats(vector);
+        }
+        Some(VectorStruct::Multi(vectors)) => {
+            hasher.write_u64(2);
+            let mut names: Vec<_> = vectors.keys().collect();
+            names.sort_unstable();
+            for name in names {
+                hasher.write_str(name);
+                match &vectors[name] {
+                    Vector::Dense(vector) => {
+                        hasher.write_u64(0);
+                        hasher.write_floats(vector);
+                    }
+                    Vector::Sparse(vector) => {
+                        hasher.write_u64(1);
+                        hasher.write_u64(vector.indices.len() as u64);
+                        for index in &vector.indices {
+                            hasher.write_u64(*index as u64);
+                        }
+                        hasher.write_floats(&vector.values);
+                    }
+                }
+            }
+        }
+    }
+    match &record.payload {
+        None => hasher.write_u64(0),
+        Some(payload) => {
+            hasher.write_u64(1);
+            hash_object(&payload.0, &mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+/// Hash keys in sorted order, so that the hash does not depend on the order of insertion
+fn hash_object(object: &serde_json::Map<String, Value>, hasher: &mut StableHasher) {
+    let mut keys: Vec<_> = object.keys().collect();
+    keys.sort_unstable();
+    hasher.write_u64(keys.len() as u64);
+    for key in keys {
+        hasher.write_str(key);
+        hash_value(&object[key], hasher);
+    }
+}
+
+fn hash_value(value: &Value, hasher: &mut StableHasher) {
+    match value {
+        Value::Null => hasher.write_u64(0),
+        Value::Bool(value) => {
+            hasher.write_u64(1);
+            hasher.write_u64(*value as u64);
+        }
+        Value::Number(value) => {
+            hasher.write_u64(2);
+            hasher.write_str(&value.to_string());
+        }
+        Value::String(value) => {
+            hasher.write_u64(3);
+            hasher.write_str(value);
+        }
+        Value::Array(values) => {
+            hasher.write_u64(4);
+            hasher.write_u64(values.len() as u64);
+            for value in values {
+                hash_value(value, hasher);
+            }
+        }
+        Value::Object(object) => {
+            hasher.write_u64(5);
+            hash_object(object, hasher);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use segment::types::Payload;
+    use serde_json::json;
+
+    use super::*;
+
+    fn record(id: u64, payload: Value) -> Record {
+        Record {
+            id: id.into(),
+            payload: Some(Payload(payload.as_object().unwrap().clone())),
+            vector: Some(vec![id as f32, 1.0].into()),
+            payload_truncated: false,
+        }
+    }
+
+    #[test]
+    fn test_checksum_is_order_independent() {
+        let records = vec![
+            record(1, json!({"a": 1, "b": [true, null]})),
+            record(2, json!({"b": "x", "a": {"c": 1.5, "d": 2}})),
+        ];
+        let mut forward = ShardChecksum::default();
+        records.iter().for_each(|record| forward.add_record(record));
+        let mut backward = ShardChecksum::default();
+        records
+            .iter()
+            .rev()
+            .for_each(|record| backward.add_record(record));
+        assert_eq!(forward, backward);
+        assert_eq!(forward.points_count, 2);
+
+        // same payload with keys inserted in a different order
+        let mut reordered = ShardChecksum::default();
+        reordered.add_record(&record(1, json!({"b": [true, null], "a": 1})));
+        reordered.add_record(&record(2, json!({"a": {"d": 2, "c": 1.5}, "b": "x"})));
+        assert_eq!(forward, reordered);
+
+        let mut changed = ShardChecksum::default();
+        changed.add_record(&record(1, json!({"a": 1, "b": [true, null]})));
+        changed.add_record(&record(2, json!({"b": "x", "a": {"c": 1.5, "d": 3}})));
+        assert_ne!(forward, changed);
+    }
+}
@@ -7,6 +7,7 @@ use api::grpc::qdrant::{
     DeletePoints, DeletePointsInternal, PointsIdsList, PointsSelector, SetPayloadPoints,
     SetPayloadPointsInternal, SyncPoints, SyncPointsInternal, UpsertPoints, UpsertPointsInternal,
 };
+use segment::data_types::numeric_index::NumericIndexType;
 use segment::types::{Filter, PayloadFieldSchema, PayloadSchemaParams, PointIdType};
 use tonic::Status;
 
@@ -54,6 +55,7 @@ pub fn internal_upsert_points(
                     .map(|id| id.try_into())
                     .collect::<Result<Vec<_>, Status>>()?,
             },
+            shard_selector: None,
         }),
     })
 }
@@ -73,6 +75,7 @@ pub fn internal_delete_points(
                     ids: ids.into_iter().map(|id| id.into()).collect(),
                 })),
             }),
+            shard_selector: None,
         }),
     }
 }
@@ -90,6 +93,7 @@ pub fn internal_delete_points_by_filter(
             points: Some(PointsSelector {
                 points_selector_one_of: Some(PointsSelectorOneOf::Filter(filter.into())),
             }),
+            shard_selector: None,
         }),
     }
 }
@@ -106,6 +110,7 @@ pub fn internal_set_payload(
             wait: Some(wait),
             payload: payload_to_proto(set_payload.payload),
             points: set_payload.points.into_iter().map(|id| id.into()).collect(),
+            shard_selector: None,
         }),
     }
 }
@@ -126,6 +131,7 @@ pub fn internal_delete_payload(
                 .into_iter()
                 .map(|id| id.into())
                 .collect(),
+            shard_selector: None,
         }),
     }
 }
@@ -145,6 +151,7 @@ pub fn internal_clear_payload(
                     ids: points.into_iter().map(|id| id.into()).collect(),
                 })),
             }),
+            shard_selector: None,
         }),
     }
 }
@@ -162,12 +169,14 @@ pub fn internal_clear_payload_by_filter(
             points: Some(PointsSelector {
                 points_selector_one_of: Some(PointsSelectorOneOf::Filter(filter.into())),
             }),
+            shard_selector: None,
         }),
     }
 }
 
 pub fn internal_create_index(
     create_index: CreateIndex,
+    rebuild: bool,
     shard: &RemoteShard,
     wait: bool,
 ) -> CreateFieldIndexCollectionInternal {
@@ -191,6 +200,9 @@ pub fn internal_create_index(
                     segment::types::PayloadSchemaType::Text => {
                         api::grpc::qdrant::FieldType::Text as i32
                     }
+                    segment::types::PayloadSchemaType::Composite => {
+                        api::grpc::qdrant::FieldType::Composite as i32
+                    }
                 },
                 None,
             ),
@@ -199,6 +211,17 @@ pub fn internal_create_index(
                     api::grpc::qdrant::FieldType::Text as i32,
                     Some(text_index_params.into()),
                 ),
+                PayloadSchemaParams::Numeric(numeric_index_params) => (
+                    match numeric_index_params.r#type {
+                        NumericIndexType::Integer => api::grpc::qdrant::FieldType::Integer as i32,
+                        NumericIndexType::Float => api::grpc::qdrant::FieldType::Float as i32,
+                    },
+                    Some(numeric_index_params.into()),
+                ),
+                PayloadSchemaParams::Composite(composite_index_params) => (
+                    api::grpc::qdrant::FieldType::Composite as i32,
+                    Some(composite_index_params.into()),
+                ),
             },
         })
         .map(|(field_type, field_params)| (Some(field_type), field_params))
@@ -212,6 +235,7 @@ pub fn internal_create_index(
             field_name: create_index.field_name,
             field_type,
             field_index_params,
+            rebuild: Some(rebuild),
         }),
     }
 }
@@ -1,4 +1,5 @@
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -7,19 +8,181 @@ use segment::types::{
     WithVector,
 };
 use tokio::runtime::Handle;
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, oneshot, Mutex};
 
 use crate::operations::point_ops::{PointInsertOperations, PointOperations, PointStruct};
 use crate::operations::types::{
-    CollectionInfo, CollectionResult, CountRequest, CountResult, PointRequest, Record,
-    SearchRequestBatch, UpdateResult,
+    CollectionError, CollectionInfo, CollectionResult, CountRequest, CountResult, PointRequest,
+    Record, SearchRequestBatch, UpdateResult,
 };
 use crate::operations::{CollectionUpdateOperations, CreateIndex, FieldIndexOperations};
+use crate::shard::checksum::ShardChecksum;
 use crate::shard::local_shard::LocalShard;
 use crate::shard::remote_shard::RemoteShard;
-use crate::shard::ShardOperation;
+use crate::shard::{ShardOperation, SnapshotTask};
 use crate::telemetry::ShardTelemetry;
 
+const DEFAULT_FORWARD_WINDOW: usize = 64;
+
+/// Max number of updates, queued for forwarding to the remote shard during a transfer
+static FORWARD_WINDOW: AtomicUsize = AtomicUsize::new(DEFAULT_FORWARD_WINDOW);
+
+/// Set max number of updates, which are queued for forwarding to the remote shard.
+/// If 0 - every update waits until it is forwarded.
+pub fn set_forward_window(forward_window: usize) {
+    FORWARD_WINDOW.store(forward_window, Ordering::Relaxed);
+}
+
+pub fn get_forward_window() -> usize {
+    FORWARD_WINDOW.load(Ordering::Relaxed)
+}
+
+enum ForwardMessage {
+    Update(CollectionUpdateOperations),
+    /// Report the result of forwarding, once all previously queued updates are sent
+    Flush(oneshot::Sender<CollectionResult<()>>),
+}
+
+/// Merge upserts of the point lists into a single operation.
+/// Returns operations back, if they can't be merged.
+fn merge_upserts(
+    operation: CollectionUpdateOperations,
+    following: CollectionUpdateOperations,
+) -> Result<CollectionUpdateOperations, (CollectionUpdateOperations, CollectionUpdateOperations)> {
+    match (operation, following) {
+        (
+            CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+                PointInsertOperations::PointsList(mut points),
+            )),
+            CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+                PointInsertOperations::PointsList(following_points),
+            )),
+        ) => {
+            points.extend(following_points);
+            Ok(CollectionUpdateOperations::PointOperation(
+                PointOperations::UpsertPoints(PointInsertOperations::PointsList(points)),
+            ))
+        }
+        (operation, following) => Err((operation, following)),
+    }
+}
+
+/// Send queued updates to the remote shard in the order they were applied locally.
+/// Updates, which are queued at the same time, are sent in a single request where possible.
+/// Once forwarding fails, following updates are dropped, as the remote shard is out of sync anyway.
+async fn forward_updates(remote_shard: RemoteShard, mut receiver: mpsc::Receiver<ForwardMessage>) {
+    let mut result = Ok(());
+    let mut next = receiver.recv().await;
+    while let Some(message) = next.take() {
+        match message {
+            ForwardMessage::Flush(sender) => {
+                // Flush might be cancelled by the caller
+                let _ = sender.send(result.clone());
+            }
+            ForwardMessage::Update(mut operation) => {
+                while let Ok(message) = receiver.try_recv() {
+                    match message {
+                        ForwardMessage::Update(following) => {
+                            match merge_upserts(operation, following) {
+                                Ok(merged) => operation = merged,
+                                Err((unmerged, following)) => {
+                                    operation = unmerged;
+                                    next = Some(ForwardMessage::Update(following));
+                                    break;
+                                }
+                            }
+                        }
+                        flush => {
+                            next = Some(flush);
+                            break;
+                        }
+                    }
+                }
+                if result.is_ok() {
+                    if let Err(err) = remote_shard.update(operation, false).await {
+                        log::warn!(
+                            "Failed to forward update to shard {} on peer {}: {}",
+                            remote_shard.id,
+                            remote_shard.peer_id,
+                            err
+                        );
+                        result = Err(err);
+                    }
+                }
+            }
+        }
+        if next.is_none() {
+            next = receiver.recv().await;
+        }
+    }
+}
+
+/// Create payload indexes in the remote shard same as in the local shard.
+pub(crate) async fn transfer_indexes(
+    local_shard: &LocalShard,
+    remote_shard: &RemoteShard,
+) -> CollectionResult<()> {
+    for (index_key, index_type) in local_shard.info().await?.payload_schema {
+        remote_shard
+            .update(
+                CollectionUpdateOperations::FieldIndexOperation(FieldIndexOperations::CreateIndex(
+                    CreateIndex {
+                        field_name: index_key,
+                        field_schema: Some(index_type.try_into()?),
+                    },
+                )),
+                false,
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Copy batch of points of the local shard to the remote shard.
+/// Returns an offset of the next batch to be transferred.
+pub(crate) async fn transfer_batch(
+    local_shard: &LocalShard,
+    remote_shard: &RemoteShard,
+    offset: Option<PointIdType>,
+    batch_size: usize,
+) -> CollectionResult<Option<PointIdType>> {
+    debug_assert!(batch_size > 0);
+    let limit = batch_size + 1;
+    let mut batch = local_shard
+        .scroll_by(
+            offset,
+            limit,
+            &WithPayloadInterface::Bool(true),
+            &true.into(),
+            None,
+        )
+        .await?;
+    let next_page_offset = if batch.len() < limit {
+        // This was the last page
+        None
+    } else {
+        // remove extra point, it would be a first point of the next page
+        Some(batch.pop().unwrap().id)
+    };
+
+    if batch.is_empty() {
+        return Ok(next_page_offset);
+    }
+
+    let points: Result<Vec<PointStruct>, String> =
+        batch.into_iter().map(|point| point.try_into()).collect();
+
+    let insert_points_operation = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(PointInsertOperations::PointsList(points?)),
+    );
+
+    // We only need to wait for the last batch.
+    let wait = next_page_offset.is_none();
+    remote_shard.update(insert_points_operation, wait).await?;
+
+    Ok(next_page_offset)
+}
+
 /// ForwardProxyShard
 ///
 /// ForwardProxyShard is a wrapper type for a LocalShard.
@@ -32,34 +195,51 @@ pub struct ForwardProxyShard {
     /// Lock required to protect transfer-in-progress updates.
     /// It should block data updating operations while the batch it being transferred.
     update_lock: Mutex<()>,
+    /// Queue of updates to forward in background, if the forward window is not 0
+    forward_queue: Option<mpsc::Sender<ForwardMessage>>,
 }
 
 impl ForwardProxyShard {
     pub fn new(wrapped_shard: LocalShard, remote_shard: RemoteShard) -> Self {
+        let forward_window = get_forward_window();
+        let forward_queue = if forward_window > 0 {
+            let (sender, receiver) = mpsc::channel(forward_window);
+            tokio::spawn(forward_updates(remote_shard.clone(), receiver));
+            Some(sender)
+        } else {
+            None
+        };
         Self {
             wrapped_shard,
             remote_shard,
             update_lock: Mutex::new(()),
+            forward_queue,
+        }
+    }
+
+    /// Wait until all queued updates are forwarded to the remote shard.
+    /// Fails if any of them was not forwarded.
+    pub async fn flush_forwarded_updates(&self) -> CollectionResult<()> {
+        match &self.forward_queue {
+            Some(forward_queue) => {
+                let (sender, receiver) = oneshot::channel();
+                forward_queue
+                    .send(ForwardMessage::Flush(sender))
+                    .await
+                    .map_err(|_| {
+                        CollectionError::service_error("Update forwarding stopped".to_string())
+                    })?;
+                receiver.await?
+            }
+            None => Ok(()),
         }
     }
 
     /// Create payload indexes in the remote shard same as in the wrapped shard.
     pub async fn transfer_indexes(&self) -> CollectionResult<()> {
         let _update_lock = self.update_lock.lock().await;
-        for (index_key, index_type) in self.wrapped_shard.info().await?.payload_schema {
-            self.remote_shard
-                .update(
-                    CollectionUpdateOperations::FieldIndexOperation(
-                        FieldIndexOperations::CreateIndex(CreateIndex {
-                            field_name: index_key,
-                            field_schema: Some(index_type.try_into()?),
-                        }),
-                    ),
-                    false,
-                )
-                .await?;
-        }
-        Ok(())
+        self.flush_forwarded_updates().await?;
+        transfer_indexes(&self.wrapped_shard, &self.remote_shard).await
     }
 
     /// Move batch of points to the remote shard.
@@ -69,45 +249,10 @@ impl ForwardProxyShard {
         offset: Option<PointIdType>,
         batch_size: usize,
     ) -> CollectionResult<Option<PointIdType>> {
-        debug_assert!(batch_size > 0);
-        let limit = batch_size + 1;
         let _update_lock = self.update_lock.lock().await;
-        let mut batch = self
-            .wrapped_shard
-            .scroll_by(
-                offset,
-                limit,
-                &WithPayloadInterface::Bool(true),
-                &true.into(),
-                None,
-            )
-            .await?;
-        let next_page_offset = if batch.len() < limit {
-            // This was the last page
-            None
-        } else {
-            // remove extra point, it would be a first point of the next page
-            Some(batch.pop().unwrap().id)
-        };
-
-        if batch.is_empty() {
-            return Ok(next_page_offset);
-        }
-
-        let points: Result<Vec<PointStruct>, String> =
-            batch.into_iter().map(|point| point.try_into()).collect();
-
-        let insert_points_operation = CollectionUpdateOperations::PointOperation(
-            PointOperations::UpsertPoints(PointInsertOperations::PointsList(points?)),
-        );
-
-        // We only need to wait for the last batch.
-        let wait = next_page_offset.is_none();
-        self.remote_shard
-            .update(insert_points_operation, wait)
-            .await?;
-
-        Ok(next_page_offset)
+        // Batch has to be sent after forwarded updates, so the last one completes the transfer
+        self.flush_forwarded_updates().await?;
+        transfer_batch(&self.wrapped_shard, &self.remote_shard, offset, batch_size).await
     }
 
     pub fn deconstruct(self) -> (LocalShard, RemoteShard) {
@@ -119,6 +264,11 @@ impl ForwardProxyShard {
         self.wrapped_shard.create_snapshot(target_path).await
     }
 
+    /// Forward `snapshot_task` to `wrapped_shard`
+    pub async fn snapshot_task(&self, target_path: &Path) -> CollectionResult<SnapshotTask> {
+        self.wrapped_shard.snapshot_task(target_path).await
+    }
+
     pub async fn on_optimizer_config_update(&self) -> CollectionResult<()> {
         self.wrapped_shard.on_optimizer_config_update().await
     }
@@ -145,9 +295,21 @@ impl ShardOperation for ForwardProxyShard {
         let local_shard = &self.wrapped_shard;
         // Shard update is within a write lock scope, because we need a way to block the shard updates
         // during the transfer restart and finalization.
-        local_shard.update(operation.clone(), wait).await?;
+        let result = local_shard.update(operation.clone(), wait).await?;
 
-        self.remote_shard.update(operation, false).await
+        match &self.forward_queue {
+            // Queue is bounded by the forward window, so it also applies back-pressure to updates
+            Some(forward_queue) => {
+                forward_queue
+                    .send(ForwardMessage::Update(operation))
+                    .await
+                    .map_err(|_| {
+                        CollectionError::service_error("Update forwarding stopped".to_string())
+                    })?;
+                Ok(result)
+            }
+            None => self.remote_shard.update(operation, false).await,
+        }
     }
 
     /// Forward read-only `scroll_by` to `wrapped_shard`
@@ -195,4 +357,54 @@ impl ShardOperation for ForwardProxyShard {
             .retrieve(request, with_payload, with_vector)
             .await
     }
+
+    async fn checksum(&self) -> CollectionResult<ShardChecksum> {
+        let local_shard = &self.wrapped_shard;
+        local_shard.checksum().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upsert(ids: &[u64]) -> CollectionUpdateOperations {
+        let points = ids
+            .iter()
+            .map(|&id| PointStruct {
+                id: id.into(),
+                vector: vec![1.0, 0.0].into(),
+                payload: None,
+            })
+            .collect();
+        CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+            PointInsertOperations::PointsList(points),
+        ))
+    }
+
+    fn point_ids(operation: &CollectionUpdateOperations) -> Vec<PointIdType> {
+        match operation {
+            CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+                PointInsertOperations::PointsList(points),
+            )) => points.iter().map(|point| point.id).collect(),
+            _ => panic!("not a list of upserted points"),
+        }
+    }
+
+    #[test]
+    fn test_merge_upserts() {
+        let merged = merge_upserts(upsert(&[1, 2]), upsert(&[2, 3])).unwrap();
+        assert_eq!(point_ids(&merged), point_ids(&upsert(&[1, 2, 2, 3])));
+
+        // Other operations are kept in order
+        let delete = CollectionUpdateOperations::PointOperation(PointOperations::DeletePoints {
+            ids: vec![1.into()],
+        });
+        let (operation, following) = merge_upserts(upsert(&[1]), delete).unwrap_err();
+        assert_eq!(point_ids(&operation), point_ids(&upsert(&[1])));
+        assert!(matches!(
+            following,
+            CollectionUpdateOperations::PointOperation(PointOperations::DeletePoints { .. })
+        ));
+    }
 }
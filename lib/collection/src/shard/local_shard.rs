@@ -7,26 +7,29 @@ use std::sync::Arc;
 use std::thread;
 
 use arc_swap::ArcSwap;
+use futures::FutureExt;
 use indicatif::{ProgressBar, ProgressStyle};
 use itertools::Itertools;
 use parking_lot::{Mutex as ParkingMutex, RwLock};
+use segment::entry::entry_point::SegmentEntry;
 use segment::index::field_index::CardinalityEstimation;
 use segment::segment::Segment;
-use segment::segment_constructor::{build_segment, load_segment};
-use segment::types::{Filter, PayloadStorageType, PointIdType, SegmentConfig};
+use segment::segment_constructor::{build_segment, import_segment, load_segment};
+use segment::types::{Filter, PayloadStorageType, PointIdType, SegmentConfig, SeqNumberType};
 use tokio::fs::{copy, create_dir_all, remove_dir_all};
 use tokio::runtime::{self, Runtime};
 use tokio::sync::mpsc::Sender;
-use tokio::sync::{mpsc, Mutex, RwLock as TokioRwLock};
+use tokio::sync::{mpsc, oneshot, Mutex, RwLock as TokioRwLock};
 
 use crate::collection_manager::collection_updater::CollectionUpdater;
-use crate::collection_manager::holders::segment_holder::SegmentHolder;
-use crate::config::CollectionConfig;
-use crate::operations::types::{CollectionError, CollectionResult};
+use crate::collection_manager::holders::segment_holder::{LockedSegment, SegmentHolder};
+use crate::config::{CollectionConfig, CollectionParams};
+use crate::operations::types::{CollectionError, CollectionResult, WalInfo, WalOperation};
 use crate::operations::CollectionUpdateOperations;
 use crate::optimizers_builder::build_optimizers;
+use crate::resource_watchdog::ResourceThrottle;
 use crate::shard::shard_config::{ShardConfig, SHARD_CONFIG_FILE};
-use crate::shard::{CollectionId, ShardId};
+use crate::shard::{CollectionId, ShardId, SnapshotTask};
 use crate::telemetry::ShardTelemetry;
 use crate::update_handler::{Optimizer, UpdateHandler, UpdateSignal, UPDATE_QUEUE_SIZE};
 use crate::wal::SerdeWal;
@@ -59,6 +62,7 @@ impl LocalShard {
         wal: SerdeWal<CollectionUpdateOperations>,
         optimizers: Arc<Vec<Arc<Optimizer>>>,
         collection_path: &Path,
+        resource_throttle: ResourceThrottle,
     ) -> Self {
         let segment_holder = Arc::new(RwLock::new(segment_holder));
         let config = shared_config.read().await;
@@ -90,6 +94,8 @@ impl LocalShard {
             locked_wal.clone(),
             config.optimizer_config.flush_interval_sec,
             config.optimizer_config.max_optimization_threads,
+            config.params.payload_transformers.clone(),
+            resource_throttle,
         );
 
         let (update_sender, update_receiver) = mpsc::channel(UPDATE_QUEUE_SIZE);
@@ -119,6 +125,7 @@ impl LocalShard {
         collection_id: CollectionId,
         shard_path: &Path,
         shared_config: Arc<TokioRwLock<CollectionConfig>>,
+        resource_throttle: ResourceThrottle,
     ) -> LocalShard {
         let collection_config = shared_config.read().await;
 
@@ -188,6 +195,7 @@ impl LocalShard {
             wal,
             optimizers,
             shard_path,
+            resource_throttle,
         )
         .await;
 
@@ -213,6 +221,7 @@ impl LocalShard {
         collection_id: CollectionId,
         shard_path: &Path,
         shared_config: Arc<TokioRwLock<CollectionConfig>>,
+        resource_throttle: ResourceThrottle,
     ) -> CollectionResult<LocalShard> {
         // initialize temporary shard config file
         let temp_shard_config = ShardConfig::new_temp();
@@ -222,6 +231,7 @@ impl LocalShard {
             shard_path,
             shared_config,
             temp_shard_config,
+            resource_throttle,
         )
         .await
     }
@@ -231,6 +241,7 @@ impl LocalShard {
         collection_id: CollectionId,
         shard_path: &Path,
         shared_config: Arc<TokioRwLock<CollectionConfig>>,
+        resource_throttle: ResourceThrottle,
     ) -> CollectionResult<LocalShard> {
         // initialize local shard config file
         let local_shard_config = ShardConfig::new_local();
@@ -240,6 +251,7 @@ impl LocalShard {
             shard_path,
             shared_config,
             local_shard_config,
+            resource_throttle,
         )
         .await
     }
@@ -251,6 +263,7 @@ impl LocalShard {
         shard_path: &Path,
         shared_config: Arc<TokioRwLock<CollectionConfig>>,
         config: ShardConfig,
+        resource_throttle: ResourceThrottle,
     ) -> CollectionResult<LocalShard> {
         config.save(shard_path)?;
 
@@ -275,13 +288,14 @@ impl LocalShard {
         let mut segment_holder = SegmentHolder::default();
         let mut build_handlers = vec![];
 
-        let vector_params = config.params.get_all_vector_params()?;
+        let vector_params = config.params.get_appendable_vector_params()?;
         let segment_number = config.optimizer_config.get_number_segments();
 
         for _sid in 0..segment_number {
             let path_clone = segments_path.clone();
             let segment_config = SegmentConfig {
                 vector_data: vector_params.clone(),
+                sparse_vector_data: config.params.get_all_sparse_vector_params(),
                 index: Default::default(),
                 storage_type: Default::default(),
                 payload_storage_type: match config.params.on_disk_payload {
@@ -332,6 +346,7 @@ impl LocalShard {
             wal,
             optimizers,
             shard_path,
+            resource_throttle,
         )
         .await;
 
@@ -350,6 +365,7 @@ impl LocalShard {
 
     /// Loads latest collection operations from WAL
     pub async fn load_from_wal(&self, collection_id: CollectionId) {
+        let payload_transformers = self.config.read().await.params.payload_transformers.clone();
         let wal = self.wal.lock();
         let bar = ProgressBar::new(wal.len());
 
@@ -364,7 +380,7 @@ impl LocalShard {
         for (op_num, update) in wal.read_all() {
             // Panic only in case of internal error. If wrong formatting - skip
             if let Err(CollectionError::ServiceError { error }) =
-                CollectionUpdater::update(segments, op_num, update)
+                CollectionUpdater::update(segments, op_num, update, &payload_transformers)
             {
                 panic!("Can't apply WAL operation: {}", error)
             }
@@ -375,6 +391,80 @@ impl LocalShard {
         bar.finish();
     }
 
+    /// Up to `limit` operations of the WAL, starting from `offset` or from the oldest one
+    pub fn wal_operations(&self, offset: Option<SeqNumberType>, limit: usize) -> WalInfo {
+        let wal = self.wal.lock();
+        let first_op_num = wal.first_index();
+        let start_from = offset.map_or(first_op_num, |offset| offset.max(first_op_num));
+        let operations = wal
+            .read(start_from)
+            .take(limit)
+            .map(|(op_num, operation)| WalOperation { op_num, operation })
+            .collect();
+        WalInfo {
+            first_op_num,
+            operations_count: wal.len(),
+            operations,
+        }
+    }
+
+    /// Apply operations of the WAL, starting from `from_op_num`, to segments once again.
+    /// Segments skip operations, which they already contain, so only missing changes are applied.
+    ///
+    /// Returns number of replayed operations.
+    pub async fn replay_wal(&self, from_op_num: SeqNumberType) -> CollectionResult<usize> {
+        let payload_transformers = self.config.read().await.params.payload_transformers.clone();
+        let wal = self.wal.lock();
+        let segments = self.segments();
+        let mut replayed = 0;
+        for (op_num, update) in wal.read(from_op_num.max(wal.first_index())) {
+            // Operations with wrong input are skipped, same as on loading
+            if let Err(err @ CollectionError::ServiceError { .. }) =
+                CollectionUpdater::update(segments, op_num, update, &payload_transformers)
+            {
+                return Err(err);
+            }
+            replayed += 1;
+        }
+        segments.read().flush_all(true)?;
+        Ok(replayed)
+    }
+
+    /// Remove operations of the WAL, starting from `from_op_num`, e.g. the ones which can't be applied.
+    ///
+    /// Numbers of removed operations are given to the new ones, so only operations,
+    /// which are not applied to any segment yet, can be removed.
+    pub fn truncate_wal(&self, from_op_num: SeqNumberType) -> CollectionResult<()> {
+        let max_applied_op_num = self
+            .segments
+            .read()
+            .iter()
+            .map(|(_id, segment)| segment.get().read().version())
+            .max()
+            .unwrap_or(0);
+        if from_op_num <= max_applied_op_num {
+            return Err(CollectionError::BadInput {
+                description: format!(
+                    "Operation {from_op_num} is already applied to segments, \
+                     only operations after {max_applied_op_num} can be removed"
+                ),
+            });
+        }
+        self.wal.lock().truncate(from_op_num)?;
+        Ok(())
+    }
+
+    /// Wait until the updates, which are already in the update queue, are applied
+    pub async fn wait_update_queue(&self) -> CollectionResult<()> {
+        let (tx, rx) = oneshot::channel();
+        self.update_sender
+            .load()
+            .send(UpdateSignal::Plunger(tx))
+            .await?;
+        rx.await?;
+        Ok(())
+    }
+
     pub async fn on_optimizer_config_update(&self) -> CollectionResult<()> {
         let config = self.config.read().await;
         let mut update_handler = self.update_handler.lock().await;
@@ -457,22 +547,152 @@ impl LocalShard {
 
     /// create snapshot for local shard into `target_path`
     pub async fn create_snapshot(&self, target_path: &Path) -> CollectionResult<()> {
-        let snapshot_shard_path = target_path;
+        self.snapshot_task(target_path).await?.await
+    }
 
-        // snapshot all shard's segment
+    /// Prepare snapshot of the shard into `target_path`.
+    ///
+    /// Returned task copies segments and WAL of the shard. It does not borrow the shard,
+    /// so it may be awaited after the lock, which the shard is accessed through, is released.
+    pub async fn snapshot_task(&self, target_path: &Path) -> CollectionResult<SnapshotTask> {
+        let snapshot_shard_path = target_path.to_path_buf();
         let snapshot_segments_shard_path = snapshot_shard_path.join("segments");
         create_dir_all(&snapshot_segments_shard_path).await?;
-        self.segments
-            .read()
-            .snapshot_all_segments(&snapshot_segments_shard_path)?;
-
-        // snapshot all shard's WAL
-        self.snapshot_wal(snapshot_shard_path).await?;
 
         // copy shard's config
         let shard_config_path = ShardConfig::get_config_path(&self.path);
         let target_shard_config_path = snapshot_shard_path.join(SHARD_CONFIG_FILE);
         copy(&shard_config_path, &target_shard_config_path).await?;
+
+        // Temporary segment is only built once the task runs, so a dropped task leaves nothing behind
+        let temp_segment_config = self.temp_segment_config().await?;
+        let segments_path = Self::segments_path(&self.path);
+        let segments = self.segments.clone();
+        let wal = self.wal.clone();
+        let source_wal_path = self.path.join("wal");
+        Ok(async move {
+            // snapshot all shard's segment
+            tokio::task::spawn_blocking(move || {
+                let temp_segment =
+                    LockedSegment::new(build_segment(&segments_path, &temp_segment_config)?);
+                SegmentHolder::snapshot_all_segments_with_proxies(
+                    &segments,
+                    temp_segment,
+                    &snapshot_segments_shard_path,
+                )
+            })
+            .await??;
+
+            // snapshot all shard's WAL
+            tokio::task::spawn_blocking(move || {
+                Self::copy_wal(&wal, &source_wal_path, &snapshot_shard_path)
+            })
+            .await?
+        }
+        .boxed())
+    }
+
+    /// Config of the appendable segment, which receives updates while the shard is snapshotted
+    async fn temp_segment_config(&self) -> CollectionResult<SegmentConfig> {
+        let config = self.config.read().await;
+        let segment_config = SegmentConfig {
+            vector_data: config.params.get_appendable_vector_params()?,
+            sparse_vector_data: config.params.get_all_sparse_vector_params(),
+            index: Default::default(),
+            storage_type: Default::default(),
+            payload_storage_type: match config.params.on_disk_payload {
+                true => PayloadStorageType::OnDisk,
+                false => PayloadStorageType::InMemory,
+            },
+        };
+        Ok(segment_config)
+    }
+
+    /// Export every segment of the shard into `target_path` as a `<segment id>.tar` archive.
+    ///
+    /// Exported segments keep their vector storages and built indexes,
+    /// so they can be imported into another shard with `import_segment` without re-indexing.
+    pub async fn export_segments(&self, target_path: &Path) -> CollectionResult<()> {
+        create_dir_all(target_path).await?;
+        self.segments.read().snapshot_all_segments(target_path)?;
+        Ok(())
+    }
+
+    /// Add segment from the archive, made by `export_segments`, to this shard.
+    ///
+    /// Vectors of the segment must match the collection config, and none of its points
+    /// may already exist in the shard. Imported segment is placed at the current position
+    /// of the WAL: operations, which are already in the WAL, are not applied to it.
+    pub async fn import_segment(&self, archive_path: &Path) -> CollectionResult<()> {
+        let collection_params = self.config.read().await.params.clone();
+        let segments_path = Self::segments_path(&self.path);
+        let mut segment = import_segment(archive_path, &segments_path)?;
+        let version = self.wal.lock().last_index();
+
+        let mut segments = self.segments.write();
+        let imported = match Self::check_imported_segment(&collection_params, &segments, &segment) {
+            Ok(()) => segment.set_version(version).map_err(CollectionError::from),
+            Err(err) => Err(err),
+        };
+        if let Err(err) = imported {
+            let segment_path = segment.current_path.clone();
+            drop(segment);
+            std::fs::remove_dir_all(segment_path)?;
+            return Err(err);
+        }
+
+        log::info!(
+            "Imported segment {} with {} points",
+            segment.current_path.display(),
+            segment.points_count()
+        );
+        segments.add(segment);
+        Ok(())
+    }
+
+    fn check_imported_segment(
+        collection_params: &CollectionParams,
+        segments: &SegmentHolder,
+        segment: &Segment,
+    ) -> CollectionResult<()> {
+        let segment_config = segment.config();
+
+        let vector_params = collection_params.get_all_vector_params()?;
+        let vectors_match = vector_params.len() == segment_config.vector_data.len()
+            && vector_params.iter().all(|(name, params)| {
+                segment_config
+                    .vector_data
+                    .get(name)
+                    .map_or(false, |config| {
+                        config.size == params.size && config.distance == params.distance
+                    })
+            });
+        let sparse_vector_params = collection_params.get_all_sparse_vector_params();
+        let sparse_vectors_match = sparse_vector_params.len()
+            == segment_config.sparse_vector_data.len()
+            && sparse_vector_params
+                .keys()
+                .all(|name| segment_config.sparse_vector_data.contains_key(name));
+        if !vectors_match || !sparse_vectors_match {
+            return Err(CollectionError::BadInput {
+                description: "Vectors of the imported segment do not match the collection"
+                    .to_string(),
+            });
+        }
+
+        let existing_point = segment.iter_points().find(|point_id| {
+            segments
+                .iter()
+                .any(|(_id, existing)| existing.get().read().has_point(*point_id))
+        });
+        if let Some(point_id) = existing_point {
+            return Err(CollectionError::BadInput {
+                description: format!(
+                    "Point {} of the imported segment already exists in the shard",
+                    point_id
+                ),
+            });
+        }
         Ok(())
     }
 
@@ -480,11 +700,18 @@ impl LocalShard {
     ///
     /// copies all WAL files into `snapshot_shard_path/wal`
     pub async fn snapshot_wal(&self, snapshot_shard_path: &Path) -> CollectionResult<()> {
+        Self::copy_wal(&self.wal, &self.path.join("wal"), snapshot_shard_path)
+    }
+
+    fn copy_wal(
+        wal: &ParkingMutex<SerdeWal<CollectionUpdateOperations>>,
+        source_wal_path: &Path,
+        snapshot_shard_path: &Path,
+    ) -> CollectionResult<()> {
         // lock wal during snapshot
-        let _wal_guard = self.wal.lock();
-        let source_wal_path = self.path.join("wal");
+        let _wal_guard = wal.lock();
         let options = fs_extra::dir::CopyOptions::new();
-        fs_extra::dir::copy(&source_wal_path, snapshot_shard_path, &options).map_err(|err| {
+        fs_extra::dir::copy(source_wal_path, snapshot_shard_path, &options).map_err(|err| {
             CollectionError::service_error(format!(
                 "Error while copy WAL {:?} {}",
                 snapshot_shard_path, err
@@ -14,10 +14,12 @@ use tokio::sync::oneshot;
 use crate::collection_manager::holders::segment_holder::LockedSegment;
 use crate::collection_manager::segments_searcher::SegmentsSearcher;
 use crate::operations::types::{
-    CollectionInfo, CollectionResult, CollectionStatus, CountRequest, CountResult,
-    OptimizersStatus, PointRequest, Record, SearchRequestBatch, UpdateResult, UpdateStatus,
+    merge_index_info, CollectionInfo, CollectionResult, CollectionStatus, CountRequest,
+    CountResult, OptimizersStatus, PointRequest, Record, SearchRequestBatch, UpdateResult,
+    UpdateStatus,
 };
 use crate::operations::CollectionUpdateOperations;
+use crate::shard::checksum::{compute_checksum, ShardChecksum};
 use crate::shard::local_shard::LocalShard;
 use crate::shard::ShardOperation;
 use crate::update_handler::{OperationData, UpdateSignal};
@@ -57,11 +59,13 @@ impl ShardOperation for LocalShard {
             Ok(UpdateResult {
                 operation_id,
                 status: UpdateStatus::Completed,
+                session_token: None,
             })
         } else {
             Ok(UpdateResult {
                 operation_id,
                 status: UpdateStatus::Acknowledged,
+                session_token: None,
             })
         }
     }
@@ -137,7 +141,7 @@ impl ShardOperation for LocalShard {
             vectors_count += segment_info.num_vectors;
             points_count += segment_info.num_points;
             for (key, val) in segment_info.index_schema {
-                schema.insert(key, val);
+                merge_index_info(&mut schema, key, val);
             }
         }
         if !segments.failed_operation.is_empty() || segments.optimizer_errors.is_some() {
@@ -169,7 +173,7 @@ impl ShardOperation for LocalShard {
         let collection_params = self.config.read().await.params.clone();
         // check vector names existing
         for req in &request.searches {
-            collection_params.get_vector_params(req.vector.get_name())?;
+            collection_params.get_search_distance(&req.vector)?;
         }
         let res = SegmentsSearcher::search(self.segments(), request.clone(), search_runtime_handle)
             .await?;
@@ -177,11 +181,7 @@ impl ShardOperation for LocalShard {
             .into_iter()
             .zip(request.searches.iter())
             .map(|(vector_res, req)| {
-                let vector_name = req.vector.get_name();
-                let distance = collection_params
-                    .get_vector_params(vector_name)
-                    .unwrap()
-                    .distance;
+                let distance = collection_params.get_search_distance(&req.vector).unwrap();
                 let processed_res = vector_res.into_iter().map(|mut scored_point| {
                     scored_point.score = distance.postprocess_score(scored_point.score);
                     scored_point
@@ -221,4 +221,8 @@ impl ShardOperation for LocalShard {
     ) -> CollectionResult<Vec<Record>> {
         SegmentsSearcher::retrieve(self.segments(), &request.ids, with_payload, with_vector).await
     }
+
+    async fn checksum(&self) -> CollectionResult<ShardChecksum> {
+        compute_checksum(self).await
+    }
 }
@@ -1,3 +1,4 @@
+pub mod checksum;
 pub mod collection_shard_distribution;
 mod conversions;
 pub mod forward_proxy_shard;
@@ -7,6 +8,7 @@ pub mod proxy_shard;
 pub mod remote_shard;
 #[allow(dead_code)]
 pub mod replica_set;
+pub mod retry_policy;
 pub mod shard_config;
 pub mod shard_holder;
 pub mod shard_versioning;
@@ -18,6 +20,8 @@ use std::sync::Arc;
 
 use api::grpc::transport_channel_pool::TransportChannelPool;
 use async_trait::async_trait;
+use futures::future::{self, BoxFuture};
+use futures::FutureExt;
 use schemars::JsonSchema;
 use segment::types::{
     ExtendedPointId, Filter, ScoredPoint, WithPayload, WithPayloadInterface, WithVector,
@@ -26,7 +30,10 @@ use serde::{Deserialize, Serialize};
 use tokio::runtime::Handle;
 use tonic::transport::Uri;
 
+use self::checksum::ShardChecksum;
 use self::replica_set::ReplicaSet;
+use self::retry_policy::RetryPolicy;
+use crate::operations::shard_selector::ShardSelectorInternal;
 use crate::operations::types::{
     CollectionError, CollectionInfo, CollectionResult, CountRequest, CountResult, PointRequest,
     Record, SearchRequestBatch, UpdateResult,
@@ -41,6 +48,9 @@ use crate::telemetry::ShardTelemetry;
 
 pub type ShardId = u32;
 
+/// Deferred part of a shard snapshot, which copies the shard data without borrowing the shard
+pub type SnapshotTask = BoxFuture<'static, CollectionResult<()>>;
+
 /// Shard
 ///
 /// Contains a part of the collection's points
@@ -65,13 +75,52 @@ impl Shard {
         }
     }
 
+    /// Shard to read from.
+    /// Replica sets only read from the replica, selected by the client, if any,
+    /// or from the replicas, which have applied the update of the session.
+    pub async fn get_for_read(
+        &self,
+        shard_selection: &ShardSelectorInternal,
+    ) -> CollectionResult<&(dyn ShardOperation + Sync + Send + '_)> {
+        if let Some((shard_id, peer_id)) = shard_selection.selected_replica() {
+            return match self {
+                Shard::ReplicaSet(replica_set) => replica_set.peer_replica(peer_id),
+                _ => Err(CollectionError::bad_shard_selection(format!(
+                    "Shard {shard_id} is not replicated, its replicas can't be selected"
+                ))),
+            };
+        }
+        if let (Shard::ReplicaSet(replica_set), Some(session)) =
+            (self, shard_selection.session_token())
+        {
+            if let Some(shard_session) = session.shards.get(&replica_set.shard_id()) {
+                return replica_set.session_replica(shard_session).await;
+            }
+        }
+        Ok(self.get())
+    }
+
     pub async fn before_drop(&mut self) {
         match self {
             Shard::Local(local_shard) => local_shard.before_drop().await,
             Shard::Remote(_) => (),
             Shard::Proxy(proxy_shard) => proxy_shard.before_drop().await,
             Shard::ForwardProxy(proxy_shard) => proxy_shard.before_drop().await,
-            Shard::ReplicaSet(_) => todo!(),
+            Shard::ReplicaSet(replica_set) => replica_set.before_drop().await,
+        }
+    }
+
+    /// Prepare snapshot of the shard into `target_path`, see `LocalShard::snapshot_task`
+    pub async fn snapshot_task(&self, target_path: &Path) -> CollectionResult<SnapshotTask> {
+        match self {
+            Shard::Local(local_shard) => local_shard.snapshot_task(target_path).await,
+            Shard::Remote(remote_shard) => {
+                remote_shard.create_snapshot(target_path).await?;
+                Ok(future::ready(Ok(())).boxed())
+            }
+            Shard::Proxy(proxy_shard) => proxy_shard.snapshot_task(target_path).await,
+            Shard::ForwardProxy(proxy_shard) => proxy_shard.snapshot_task(target_path).await,
+            Shard::ReplicaSet(replica_set) => replica_set.snapshot_task(target_path).await,
         }
     }
 
@@ -91,7 +140,7 @@ impl Shard {
             Shard::Remote(remote_shard) => remote_shard.get_telemetry_data(),
             Shard::Proxy(proxy_shard) => proxy_shard.get_telemetry_data(),
             Shard::ForwardProxy(proxy_shard) => proxy_shard.get_telemetry_data(),
-            Shard::ReplicaSet(_) => todo!(),
+            Shard::ReplicaSet(replica_set) => replica_set.get_telemetry_data(),
         }
     }
 }
@@ -130,6 +179,9 @@ pub trait ShardOperation {
         with_payload: &WithPayload,
         with_vector: &WithVector,
     ) -> CollectionResult<Vec<Record>>;
+
+    /// Checksum of all points of the shard, used to verify that replicas are in sync
+    async fn checksum(&self) -> CollectionResult<ShardChecksum>;
 }
 
 pub const HASH_RING_SHARD_SCALE: u32 = 100;
@@ -144,6 +196,8 @@ pub type PeerId = u64;
 pub struct ChannelService {
     pub id_to_address: Arc<parking_lot::RwLock<HashMap<PeerId, Uri>>>,
     pub channel_pool: Arc<TransportChannelPool>,
+    /// Retries of requests to remote shards on transient errors
+    pub retry_policy: RetryPolicy,
 }
 
 impl ChannelService {
@@ -154,6 +208,7 @@ impl ChannelService {
         Self {
             id_to_address,
             channel_pool,
+            retry_policy: Default::default(),
         }
     }
 
@@ -170,6 +225,7 @@ impl Default for ChannelService {
         Self {
             id_to_address: Arc::new(Default::default()),
             channel_pool: Arc::new(Default::default()),
+            retry_policy: Default::default(),
         }
     }
 }
@@ -21,8 +21,9 @@ use crate::operations::types::{
     Record, SearchRequestBatch, UpdateResult,
 };
 use crate::operations::CollectionUpdateOperations;
+use crate::shard::checksum::ShardChecksum;
 use crate::shard::local_shard::LocalShard;
-use crate::shard::ShardOperation;
+use crate::shard::{ShardOperation, SnapshotTask};
 use crate::telemetry::ShardTelemetry;
 use crate::update_handler::UpdateSignal;
 
@@ -66,6 +67,11 @@ impl ProxyShard {
         self.wrapped_shard.create_snapshot(target_path).await
     }
 
+    /// Forward `snapshot_task` to `wrapped_shard`
+    pub async fn snapshot_task(&self, target_path: &Path) -> CollectionResult<SnapshotTask> {
+        self.wrapped_shard.snapshot_task(target_path).await
+    }
+
     pub async fn on_optimizer_config_update(&self) -> CollectionResult<()> {
         self.wrapped_shard.on_optimizer_config_update().await
     }
@@ -214,4 +220,10 @@ impl ShardOperation for ProxyShard {
             .retrieve(request, with_payload, with_vector)
             .await
     }
+
+    /// Forward read-only `checksum` to `wrapped_shard`
+    async fn checksum(&self) -> CollectionResult<ShardChecksum> {
+        let local_shard = &self.wrapped_shard;
+        local_shard.checksum().await
+    }
 }
@@ -7,7 +7,7 @@ use api::grpc::qdrant::points_internal_client::PointsInternalClient;
 use api::grpc::qdrant::{
     CollectionOperationResponse, CountPoints, CountPointsInternal, GetCollectionInfoRequest,
     GetCollectionInfoRequestInternal, GetPoints, GetPointsInternal, InitiateShardTransferRequest,
-    ScrollPoints, ScrollPointsInternal, SearchBatchPointsInternal,
+    ScrollPoints, ScrollPointsInternal, SearchBatchPointsInternal, ShardChecksumInternal,
 };
 use async_trait::async_trait;
 use parking_lot::Mutex;
@@ -26,6 +26,7 @@ use crate::operations::types::{
     Record, SearchRequest, SearchRequestBatch, UpdateResult,
 };
 use crate::operations::{CollectionUpdateOperations, FieldIndexOperations};
+use crate::shard::checksum::ShardChecksum;
 use crate::shard::conversions::{
     internal_clear_payload, internal_clear_payload_by_filter, internal_create_index,
     internal_delete_index, internal_delete_payload, internal_delete_points,
@@ -39,6 +40,7 @@ use crate::telemetry::ShardTelemetry;
 /// RemoteShard
 ///
 /// Remote Shard is a representation of a shard that is located on a remote peer.
+#[derive(Clone)]
 pub struct RemoteShard {
     pub(crate) id: ShardId,
     pub(crate) collection_id: CollectionId,
@@ -111,11 +113,15 @@ impl RemoteShard {
         &self,
         f: impl Fn(PointsInternalClient<Channel>) -> O,
     ) -> CollectionResult<T> {
-        let current_address = self.current_address()?;
+        let current_address = &self.current_address()?;
+        let channel_pool = &self.channel_service.channel_pool;
+        let f = &f;
         self.channel_service
-            .channel_pool
-            .with_channel(&current_address, |channel| {
-                f(PointsInternalClient::new(channel))
+            .retry_policy
+            .retry(move || {
+                channel_pool.with_channel(current_address, move |channel| {
+                    f(PointsInternalClient::new(channel))
+                })
             })
             .await
             .map_err(|err| err.into())
@@ -125,11 +131,15 @@ impl RemoteShard {
         &self,
         f: impl Fn(CollectionsInternalClient<Channel>) -> O,
     ) -> CollectionResult<T> {
-        let current_address = self.current_address()?;
+        let current_address = &self.current_address()?;
+        let channel_pool = &self.channel_service.channel_pool;
+        let f = &f;
         self.channel_service
-            .channel_pool
-            .with_channel(&current_address, |channel| {
-                f(CollectionsInternalClient::new(channel))
+            .retry_policy
+            .retry(move || {
+                channel_pool.with_channel(current_address, move |channel| {
+                    f(CollectionsInternalClient::new(channel))
+                })
             })
             .await
             .map_err(|err| err.into())
@@ -253,7 +263,17 @@ impl ShardOperation for RemoteShard {
             CollectionUpdateOperations::FieldIndexOperation(field_index_op) => match field_index_op
             {
                 FieldIndexOperations::CreateIndex(create_index) => {
-                    let request = &internal_create_index(create_index, self, wait);
+                    let request = &internal_create_index(create_index, false, self, wait);
+                    self.with_points_client(|mut client| async move {
+                        client
+                            .create_field_index(tonic::Request::new(request.clone()))
+                            .await
+                    })
+                    .await?
+                    .into_inner()
+                }
+                FieldIndexOperations::RebuildIndex(rebuild_index) => {
+                    let request = &internal_create_index(rebuild_index, true, self, wait);
                     self.with_points_client(|mut client| async move {
                         client
                             .create_field_index(tonic::Request::new(request.clone()))
@@ -298,6 +318,8 @@ impl ShardOperation for RemoteShard {
             limit: Some(limit as u32),
             with_payload: Some(with_payload_interface.clone().into()),
             with_vectors: Some(with_vector.clone().into()),
+            max_payload_bytes: None,
+            shard_selector: None,
         };
         let request = &ScrollPointsInternal {
             scroll_points: Some(scroll_points),
@@ -383,6 +405,7 @@ impl ShardOperation for RemoteShard {
             collection_name: self.collection_id.clone(),
             filter: request.filter.clone().map(|f| f.into()),
             exact: Some(request.exact),
+            shard_selector: None,
         };
 
         let request = &CountPointsInternal {
@@ -416,6 +439,7 @@ impl ShardOperation for RemoteShard {
             ids: request.ids.iter().copied().map(|v| v.into()).collect(),
             with_payload: request.with_payload.clone().map(|wp| wp.into()),
             with_vectors: Some(with_vector.clone().into()),
+            max_payload_bytes: None,
         };
         let request = &GetPointsInternal {
             get_points: Some(get_points),
@@ -436,4 +460,21 @@ impl ShardOperation for RemoteShard {
             .collect();
         result.map_err(|e| e.into())
     }
+
+    async fn checksum(&self) -> CollectionResult<ShardChecksum> {
+        let request = &ShardChecksumInternal {
+            collection_name: self.collection_id.clone(),
+            shard_id: self.id,
+        };
+        let checksum_response = self
+            .with_points_client(|mut client| async move {
+                client.checksum(tonic::Request::new(request.clone())).await
+            })
+            .await?
+            .into_inner();
+        Ok(ShardChecksum {
+            points_count: checksum_response.points_count as usize,
+            checksum: checksum_response.checksum,
+        })
+    }
 }
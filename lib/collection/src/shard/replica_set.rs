@@ -1,87 +1,703 @@
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::future::Future;
 use std::ops::Deref;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Instant;
 
-use futures::future::{try_join, try_join_all};
+use futures::future::{self, join, join_all, BoxFuture};
 use futures::stream::FuturesUnordered;
-use futures::StreamExt;
+use futures::{FutureExt, StreamExt};
+use parking_lot::{Mutex, RwLock};
+use schemars::JsonSchema;
 use segment::types::{
-    ExtendedPointId, Filter, ScoredPoint, WithPayload, WithPayloadInterface, WithVector,
+    ExtendedPointId, Filter, ScoredPoint, SeqNumberType, WithPayload, WithPayloadInterface,
+    WithVector,
 };
+use serde::{Deserialize, Serialize};
 use tokio::runtime::Handle;
+use tokio::sync::{OwnedRwLockReadGuard, RwLock as TokioRwLock};
+use tokio::task::JoinHandle;
 
+use super::checksum::ShardChecksum;
+use super::forward_proxy_shard;
 use super::local_shard::{drop_and_delete_from_disk, LocalShard};
 use super::remote_shard::RemoteShard;
-use super::{PeerId, ShardId, ShardOperation};
+use super::shard_config::ShardConfig;
+use super::{
+    create_shard_dir, ChannelService, CollectionId, PeerId, ShardId, ShardOperation, ShardTransfer,
+    SnapshotTask,
+};
+use crate::config::CollectionConfig;
 use crate::operations::types::{
     CollectionError, CollectionInfo, CollectionResult, CountRequest, CountResult, PointRequest,
-    Record, SearchRequestBatch, UpdateResult,
+    Record, ReplicationLag, SearchRequestBatch, SessionToken, ShardSession, UpdateResult,
+    UpdateStatus,
 };
 use crate::operations::CollectionUpdateOperations;
+use crate::resource_watchdog::ResourceThrottle;
+use crate::save_on_disk::SaveOnDisk;
+use crate::telemetry::ShardTelemetry;
+
+const REPLICA_STATE_FILE: &str = "replica_state.json";
+
+/// State of a single shard replica
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplicaState {
+    /// Replica is up to date, receives updates and answers read requests
+    Active,
+    /// Replica failed to apply an update or is unreachable
+    Dead,
+    /// Replica is receiving data from another replica with a shard transfer,
+    /// receives updates, but doesn't answer read requests yet
+    Partial,
+    /// Replica is being created together with the collection
+    Initializing,
+    /// Replica is being recovered, e.g. from a snapshot, and doesn't receive updates yet
+    Recovery,
+    /// Replica receives updates, but only answers read requests, which select it explicitly
+    Listener,
+}
+
+impl ReplicaState {
+    /// Whether the replica can answer read requests
+    pub fn is_active(self) -> bool {
+        self == ReplicaState::Active
+    }
+
+    /// Whether updates should be sent to the replica
+    pub fn accepts_updates(self) -> bool {
+        match self {
+            ReplicaState::Active
+            | ReplicaState::Partial
+            | ReplicaState::Initializing
+            | ReplicaState::Listener => true,
+            ReplicaState::Dead | ReplicaState::Recovery => false,
+        }
+    }
+}
+
+/// Updates, sent to a replica by this peer, which the replica has not acknowledged yet
+#[derive(Debug, Default, Clone, Copy)]
+struct LagTracker {
+    unacked_operations: usize,
+    /// When the oldest not acknowledged update was sent
+    oldest_unacked: Option<Instant>,
+}
 
-pub type IsActive = bool;
 pub type OnPeerFailure =
-    Box<dyn Fn(PeerId, ShardId) -> Box<dyn Future<Output = ()> + Send> + Send + Sync>;
+    Arc<dyn Fn(PeerId, ShardId) -> Box<dyn Future<Output = ()> + Send> + Send + Sync>;
+
+/// Number of replicas, which have to answer a read operation
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadConsistency {
+    /// Result of the first replica, which answered successfully
+    Single,
+    /// Results of the majority of active replicas, reconciled into a single one
+    Quorum,
+    /// Results of all active replicas, reconciled into a single one
+    All,
+}
+
+impl Default for ReadConsistency {
+    fn default() -> Self {
+        ReadConsistency::Single
+    }
+}
+
+/// Number of replicas, which have to acknowledge an update operation
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum WriteConsistency {
+    /// Update fails if any of the active replicas fails to apply it
+    All,
+    /// Update succeeds once the majority of active replicas applied it.
+    /// Updates of the remaining replicas continue in background,
+    /// replicas, which fail or time out, are reported as failed to catch up later.
+    Majority,
+}
+
+impl Default for WriteConsistency {
+    fn default() -> Self {
+        WriteConsistency::All
+    }
+}
+
+impl WriteConsistency {
+    /// Number of acknowledgements required out of `replicas_count` replicas
+    pub fn required_acks(&self, replicas_count: usize) -> usize {
+        match self {
+            WriteConsistency::All => replicas_count,
+            WriteConsistency::Majority => replicas_count / 2 + 1,
+        }
+    }
+}
+
+/// Reconciles results of the same read operation, received from different replicas
+pub trait Resolve: Sized {
+    /// `results` are never empty
+    fn resolve(results: Vec<Self>) -> Self;
+}
+
+/// Most frequent of the results, the earliest one wins ties
+fn majority<T: PartialEq>(mut results: Vec<T>) -> T {
+    let best = (0..results.len())
+        .max_by_key(|&i| {
+            let votes = results.iter().filter(|res| **res == results[i]).count();
+            (votes, Reverse(i))
+        })
+        .expect("results are not empty");
+    results.swap_remove(best)
+}
+
+fn max_version(points: &[ScoredPoint]) -> Option<SeqNumberType> {
+    points.iter().map(|point| point.version).max()
+}
+
+impl Resolve for Vec<Record> {
+    fn resolve(results: Vec<Self>) -> Self {
+        majority(results)
+    }
+}
+
+impl Resolve for CountResult {
+    fn resolve(results: Vec<Self>) -> Self {
+        majority(results)
+    }
+}
+
+impl Resolve for CollectionInfo {
+    /// Info describes a particular replica, so there is nothing to reconcile
+    fn resolve(results: Vec<Self>) -> Self {
+        results.into_iter().next().expect("results are not empty")
+    }
+}
+
+impl Resolve for Vec<Vec<ScoredPoint>> {
+    /// For each search request takes the result of the replica,
+    /// which has seen the latest version of the found points
+    fn resolve(results: Vec<Self>) -> Self {
+        let mut results = results.into_iter();
+        let mut resolved = results.next().expect("results are not empty");
+        for result in results {
+            for (resolved_batch, batch) in resolved.iter_mut().zip(result) {
+                if max_version(&batch) > max_version(resolved_batch) {
+                    *resolved_batch = batch;
+                }
+            }
+        }
+        resolved
+    }
+}
 
 /// A set of shard replicas.
 /// Handles operations so that the state is consistent across all the replicas of the shard.
-/// Prefers local shard for read-only operations.
-/// Perform updates on all replicas and report error if there is at least one failure.
+/// Prefers local shard for read-only operations, unless `read_consistency` requires more replicas.
+/// Perform updates on all replicas and report error if less replicas than `write_consistency`
+/// requires acknowledged the update.
 pub struct ReplicaSet {
     shard_id: ShardId,
+    collection_id: CollectionId,
     this_peer_id: PeerId,
+    /// Directory, which holds the replica state and the data of the local replica, if any
+    shard_path: PathBuf,
     local: Option<LocalShard>,
     remotes: Vec<RemoteShard>,
-    pub(crate) replica_state: HashMap<PeerId, IsActive>,
-    read_fan_out_ratio: f32,
+    /// Locked separately from the shard holder, so bookkeeping of replicas doesn't block reads
+    replica_state: RwLock<SaveOnDisk<HashMap<PeerId, ReplicaState>>>,
+    /// Number of active remote replicas to read in parallel, all of them if `None`
+    read_fan_out_factor: RwLock<Option<u32>>,
+    read_consistency: RwLock<ReadConsistency>,
+    write_consistency: WriteConsistency,
     notify_peer_failure_cb: OnPeerFailure,
+    /// Shared with the update tasks of remote replicas
+    replication_lag: Arc<Mutex<HashMap<PeerId, LagTracker>>>,
+    channel_service: ChannelService,
+    /// Updates hold it shared until all replicas got them, batches of a transfer hold it exclusively,
+    /// so a batch never overwrites a newer version of a point on the receiving replica.
+    transfer_lock: Arc<TokioRwLock<()>>,
 }
 
 impl ReplicaSet {
+    /// Create replica set with the state of replicas, persisted in `shard_path`.
+    /// `replica_state` is only used if there is no persisted state yet.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         shard_id: ShardId,
+        collection_id: CollectionId,
         this_peer_id: PeerId,
+        shard_path: &Path,
         local: Option<LocalShard>,
         remotes: Vec<RemoteShard>,
-        replica_state: HashMap<PeerId, IsActive>,
-        read_fan_out_ratio: f32,
+        replica_state: HashMap<PeerId, ReplicaState>,
+        read_fan_out_factor: Option<u32>,
+        read_consistency: ReadConsistency,
+        write_consistency: WriteConsistency,
+        channel_service: ChannelService,
         on_peer_failure: OnPeerFailure,
-    ) -> Self {
-        Self {
+    ) -> CollectionResult<Self> {
+        let mut stored_replica_state: SaveOnDisk<HashMap<PeerId, ReplicaState>> =
+            SaveOnDisk::load_or_init(shard_path.join(REPLICA_STATE_FILE))?;
+        if stored_replica_state.is_empty() {
+            stored_replica_state.write(|state| *state = replica_state)?;
+        }
+        Ok(Self {
             shard_id,
+            collection_id,
             this_peer_id,
+            shard_path: shard_path.to_owned(),
             local,
             remotes,
-            replica_state,
-            read_fan_out_ratio,
+            replica_state: RwLock::new(stored_replica_state),
+            read_fan_out_factor: RwLock::new(read_fan_out_factor),
+            read_consistency: RwLock::new(read_consistency),
+            write_consistency,
             notify_peer_failure_cb: on_peer_failure,
+            replication_lag: Default::default(),
+            channel_service,
+            transfer_lock: Default::default(),
+        })
+    }
+
+    /// Load replica set, persisted in `shard_path`.
+    /// Local replica is only loaded if this peer is one of the replicas and its data exists.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn load(
+        shard_id: ShardId,
+        collection_id: CollectionId,
+        this_peer_id: PeerId,
+        shard_path: &Path,
+        shared_config: Arc<TokioRwLock<CollectionConfig>>,
+        channel_service: ChannelService,
+        on_peer_failure: OnPeerFailure,
+        resource_throttle: ResourceThrottle,
+    ) -> CollectionResult<Self> {
+        let (read_fan_out_factor, read_consistency, write_consistency) = {
+            let config = shared_config.read().await;
+            (
+                config.params.read_fan_out_factor,
+                config.params.read_consistency,
+                config.params.write_consistency,
+            )
+        };
+        let mut replica_set = Self::new(
+            shard_id,
+            collection_id.clone(),
+            this_peer_id,
+            shard_path,
+            None,
+            vec![],
+            HashMap::new(),
+            read_fan_out_factor,
+            read_consistency,
+            write_consistency,
+            channel_service,
+            on_peer_failure,
+        )?;
+        replica_set.remotes = replica_set
+            .peer_ids()
+            .into_iter()
+            .filter(|peer_id| *peer_id != this_peer_id)
+            .map(|peer_id| replica_set.new_remote(peer_id))
+            .collect();
+        if replica_set.peer_state(&this_peer_id).is_some()
+            && LocalShard::segments_path(shard_path).exists()
+        {
+            let local = LocalShard::load(
+                shard_id,
+                collection_id,
+                shard_path,
+                shared_config,
+                resource_throttle,
+            )
+            .await;
+            replica_set.local = Some(local);
         }
+        Ok(replica_set)
+    }
+
+    fn new_remote(&self, peer_id: PeerId) -> RemoteShard {
+        RemoteShard::new(
+            self.shard_id,
+            self.collection_id.clone(),
+            peer_id,
+            self.channel_service.clone(),
+        )
+    }
+
+    /// Persist the replica state and the shard config of the replica set in `shard_path`,
+    /// which holds the replica set from now on.
+    fn move_state(&mut self, shard_path: &Path) -> CollectionResult<()> {
+        let replicas = self.replica_states();
+        let mut replica_state = SaveOnDisk::load_or_init(shard_path.join(REPLICA_STATE_FILE))?;
+        replica_state.write(|state| *state = replicas)?;
+        ShardConfig::new_replica_set().save(shard_path)?;
+        *self.replica_state.get_mut() = replica_state;
+        self.shard_path = shard_path.to_owned();
+        Ok(())
+    }
+
+    /// Set the local replica of a replica set, which has none yet, in the given state.
+    /// The replica set is persisted next to the data of the local replica from now on.
+    /// The local replica is kept even if persisting fails, so it is dropped with the replica set.
+    pub fn set_local(&mut self, local: LocalShard, state: ReplicaState) -> CollectionResult<()> {
+        debug_assert!(self.local.is_none(), "local replica is replaced");
+        let shard_path = local.shard_path();
+        self.local = Some(local);
+        self.move_state(&shard_path)?;
+        let this_peer_id = self.this_peer_id;
+        self.replica_state.get_mut().write(|replicas| {
+            replicas.insert(this_peer_id, state);
+        })?;
+        Ok(())
     }
     pub async fn notify_peer_failure(&self, peer_id: PeerId) {
         Box::into_pin(self.notify_peer_failure_cb.deref()(peer_id, self.shard_id)).await
     }
 
     pub fn peer_ids(&self) -> Vec<PeerId> {
-        todo!()
+        self.replica_state.read().keys().copied().collect()
     }
 
-    pub fn set_active(&mut self, peer_id: &PeerId, active: bool) -> CollectionResult<()> {
-        *self
-            .replica_state
-            .get_mut(peer_id)
+    /// Copy of the state of all replicas
+    pub fn replica_states(&self) -> HashMap<PeerId, ReplicaState> {
+        (**self.replica_state.read()).clone()
+    }
+
+    pub fn set_read_fan_out_factor(&self, read_fan_out_factor: Option<u32>) {
+        *self.read_fan_out_factor.write() = read_fan_out_factor;
+    }
+
+    pub fn set_read_consistency(&self, read_consistency: ReadConsistency) {
+        *self.read_consistency.write() = read_consistency;
+    }
+
+    pub fn shard_id(&self) -> ShardId {
+        self.shard_id
+    }
+
+    pub fn this_peer_id(&self) -> PeerId {
+        self.this_peer_id
+    }
+
+    pub fn has_local_shard(&self) -> bool {
+        self.local.is_some()
+    }
+
+    /// Local replica to serve requests of other peers, which are never forwarded to other replicas
+    pub fn local_replica(&self) -> CollectionResult<&(dyn ShardOperation + Sync + Send)> {
+        match &self.local {
+            Some(local) => Ok(local),
+            None => Err(CollectionError::bad_shard_selection(format!(
+                "Shard {} has no replica on peer {}",
+                self.shard_id, self.this_peer_id
+            ))),
+        }
+    }
+
+    /// Update only the local replica, e.g. with an update, sent by another replica.
+    /// Returns `None` if there is no local replica.
+    pub async fn update_local(
+        &self,
+        operation: CollectionUpdateOperations,
+        wait: bool,
+    ) -> CollectionResult<Option<UpdateResult>> {
+        match &self.local {
+            Some(local) => Ok(Some(local.update(operation, wait).await?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn on_optimizer_config_update(&self) -> CollectionResult<()> {
+        match &self.local {
+            Some(local) => local.on_optimizer_config_update().await,
+            None => Ok(()),
+        }
+    }
+
+    pub async fn before_drop(&mut self) {
+        if let Some(local) = &mut self.local {
+            local.before_drop().await
+        }
+    }
+
+    pub fn get_telemetry_data(&self) -> ShardTelemetry {
+        ShardTelemetry::ReplicaSet {
+            shard_id: self.shard_id,
+            local: self
+                .local
+                .as_ref()
+                .map(|local| Box::new(local.get_telemetry_data())),
+            remote: self
+                .remotes
+                .iter()
+                .map(|remote| remote.get_telemetry_data())
+                .collect(),
+        }
+    }
+
+    /// Count points of the local replica regardless of its state, `None` if there is no local replica
+    pub async fn count_local(
+        &self,
+        request: Arc<CountRequest>,
+    ) -> CollectionResult<Option<CountResult>> {
+        match &self.local {
+            Some(local) => Ok(Some(local.count(request).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Peer of an active replica to recover the local replica from, if the local replica can't be
+    /// trusted after restart: it was not active, e.g. missed updates being dead, or has no data.
+    /// Listeners are not recovered, as they don't answer reads unless selected explicitly.
+    pub fn local_recovery_source(&self) -> Option<PeerId> {
+        let needs_recovery = match self.peer_state(&self.this_peer_id)? {
+            ReplicaState::Active => self.local.is_none(),
+            ReplicaState::Listener => false,
+            ReplicaState::Dead
+            | ReplicaState::Partial
+            | ReplicaState::Initializing
+            | ReplicaState::Recovery => true,
+        };
+        if !needs_recovery {
+            return None;
+        }
+        self.remotes
+            .iter()
+            .map(|rs| rs.peer_id)
+            .find(|peer_id| self.peer_is_active(peer_id))
+    }
+
+    /// Peer of an active replica to sync a new replica of the shard from, see `replication_source`
+    pub fn replication_source(&self, peer_load: &HashMap<PeerId, usize>) -> Option<PeerId> {
+        replication_source(&self.replica_state.read(), peer_load)
+    }
+
+    /// Register a new remote replica, e.g. the target of a replication transfer
+    pub fn add_remote(&mut self, remote: RemoteShard, state: ReplicaState) -> CollectionResult<()> {
+        let peer_id = remote.peer_id;
+        self.replica_state.get_mut().write(|replicas| {
+            replicas.insert(peer_id, state);
+        })?;
+        self.remotes.push(remote);
+        Ok(())
+    }
+
+    fn transfer_target(&self, peer_id: PeerId) -> CollectionResult<(&LocalShard, &RemoteShard)> {
+        let local = self.local.as_ref().ok_or_else(|| {
+            CollectionError::service_error(format!(
+                "Shard {} has no replica on peer {} to transfer",
+                self.shard_id, self.this_peer_id
+            ))
+        })?;
+        let remote = self
+            .remotes
+            .iter()
+            .find(|rs| rs.peer_id == peer_id)
             .ok_or_else(|| CollectionError::NotFound {
                 what: format!("Shard {} replica on peer {peer_id}", self.shard_id),
-            })? = active;
+            })?;
+        Ok((local, remote))
+    }
+
+    /// Create payload indexes of the local replica in the replica on `peer_id`
+    pub async fn transfer_indexes(&self, peer_id: PeerId) -> CollectionResult<()> {
+        let (local, remote) = self.transfer_target(peer_id)?;
+        let _transfer_lock = self.transfer_lock.write().await;
+        forward_proxy_shard::transfer_indexes(local, remote).await
+    }
+
+    /// Copy batch of points of the local replica to the replica on `peer_id`.
+    /// Returns an offset of the next batch to be transferred.
+    pub async fn transfer_batch(
+        &self,
+        peer_id: PeerId,
+        offset: Option<ExtendedPointId>,
+        batch_size: usize,
+    ) -> CollectionResult<Option<ExtendedPointId>> {
+        let (local, remote) = self.transfer_target(peer_id)?;
+        let _transfer_lock = self.transfer_lock.write().await;
+        forward_proxy_shard::transfer_batch(local, remote, offset, batch_size).await
+    }
+
+    /// Info of the local replica and of the replica on `peer_id`, to compare indexing progress
+    pub async fn transfer_info(
+        &self,
+        peer_id: PeerId,
+    ) -> CollectionResult<(CollectionInfo, CollectionInfo)> {
+        let (local, remote) = self.transfer_target(peer_id)?;
+        Ok((local.info().await?, remote.info().await?))
+    }
+
+    /// Replication lag of the replica on `peer_id`, as seen by the updates sent from this peer
+    pub fn replication_lag(&self, peer_id: &PeerId) -> ReplicationLag {
+        let tracker = self
+            .replication_lag
+            .lock()
+            .get(peer_id)
+            .copied()
+            .unwrap_or_default();
+        ReplicationLag {
+            unacked_operations: tracker.unacked_operations,
+            lag_ms: tracker
+                .oldest_unacked
+                .map(|sent| sent.elapsed().as_millis() as u64),
+        }
+    }
+
+    fn track_sent_update(&self, peer_ids: impl IntoIterator<Item = PeerId>) {
+        let now = Instant::now();
+        let mut lag = self.replication_lag.lock();
+        for peer_id in peer_ids {
+            let tracker = lag.entry(peer_id).or_default();
+            tracker.unacked_operations += 1;
+            tracker.oldest_unacked.get_or_insert(now);
+        }
+    }
+
+    /// Update of a single replica.
+    /// Remote replicas are updated in separate tasks and report their own failures, so replicas,
+    /// which didn't answer by the time the outcome of the update is known, still get it.
+    /// The local update is always awaited, so it is never cancelled midway.
+    fn update_replica(
+        &self,
+        peer_id: PeerId,
+        operation: CollectionUpdateOperations,
+        wait: bool,
+        transfer_guard: Arc<OwnedRwLockReadGuard<()>>,
+    ) -> BoxFuture<'_, CollectionResult<UpdateResult>> {
+        if let Some(remote) = self.remotes.iter().find(|rs| rs.peer_id == peer_id) {
+            let handle = self.spawn_remote_update(remote, operation, wait, transfer_guard);
+            return async move { handle.await? }.boxed();
+        }
+        async move {
+            let local = self.local.as_ref().ok_or_else(|| {
+                CollectionError::service_error(format!(
+                    "No replica of shard {} on peer {peer_id}",
+                    self.shard_id
+                ))
+            })?;
+            let res = local.update(operation, wait).await;
+            if res.is_ok() {
+                self.track_acked_update(peer_id);
+            }
+            res
+        }
+        .boxed()
+    }
+
+    /// Update the remote replica in a separate task, which keeps running if the caller stops waiting
+    /// for it. The task reports the replica as failed if the update fails or times out.
+    fn spawn_remote_update(
+        &self,
+        remote: &RemoteShard,
+        operation: CollectionUpdateOperations,
+        wait: bool,
+        transfer_guard: Arc<OwnedRwLockReadGuard<()>>,
+    ) -> JoinHandle<CollectionResult<UpdateResult>> {
+        let remote = remote.clone();
+        let shard_id = self.shard_id;
+        let replication_lag = self.replication_lag.clone();
+        let notify_peer_failure_cb = self.notify_peer_failure_cb.clone();
+        tokio::spawn(async move {
+            let peer_id = remote.peer_id;
+            let res = remote.update(operation, wait).await;
+            drop(transfer_guard);
+            match &res {
+                Ok(_) => {
+                    replication_lag.lock().remove(&peer_id);
+                }
+                Err(err) => {
+                    log::warn!(
+                        "Failed to update replica of shard {shard_id} on peer {peer_id}: {err}"
+                    );
+                    Box::into_pin(notify_peer_failure_cb(peer_id, shard_id)).await;
+                }
+            }
+            res
+        })
+    }
+
+    /// Replica applied an update, so it is considered to be caught up with the previous ones.
+    /// Replicas, which missed updates, are marked dead and synced with a transfer anyway.
+    fn track_acked_update(&self, peer_id: PeerId) {
+        self.replication_lag.lock().remove(&peer_id);
+    }
+
+    pub fn peer_state(&self, peer_id: &PeerId) -> Option<ReplicaState> {
+        self.replica_state.read().get(peer_id).copied()
+    }
+
+    /// Check, that a user can switch the replica on `peer_id` to `state`.
+    /// Users can only turn active replicas into listeners and back, other states are managed by
+    /// the cluster. The last active replica can't become a listener, as the shard can't be read then.
+    pub fn validate_listener_change(
+        &self,
+        peer_id: PeerId,
+        state: ReplicaState,
+    ) -> CollectionResult<()> {
+        let replica_state = self.replica_state.read();
+        let current_state = match replica_state.get(&peer_id) {
+            Some(current_state) => *current_state,
+            None => {
+                return Err(CollectionError::NotFound {
+                    what: format!("Shard {} replica on peer {peer_id}", self.shard_id),
+                })
+            }
+        };
+        match (current_state, state) {
+            (ReplicaState::Active, ReplicaState::Listener) => {
+                let active_replicas = replica_state
+                    .values()
+                    .filter(|state| state.is_active())
+                    .count();
+                if active_replicas < 2 {
+                    return Err(CollectionError::BadRequest {
+                        description: format!(
+                            "Shard {} replica on peer {peer_id} is the last active one, \
+                             it can't become a listener",
+                            self.shard_id
+                        ),
+                    });
+                }
+                Ok(())
+            }
+            (ReplicaState::Listener, ReplicaState::Active) => Ok(()),
+            (current_state, state) if current_state == state => Ok(()),
+            (current_state, state) => Err(CollectionError::BadRequest {
+                description: format!(
+                    "Shard {} replica on peer {peer_id} can't be switched from {current_state:?} \
+                     to {state:?}, only active replicas and listeners can be switched",
+                    self.shard_id
+                ),
+            }),
+        }
+    }
+
+    /// Only takes a shared reference, so the state can be changed without locking the shard holder
+    pub fn set_replica_state(&self, peer_id: &PeerId, state: ReplicaState) -> CollectionResult<()> {
+        let mut replica_state = self.replica_state.write();
+        if !replica_state.contains_key(peer_id) {
+            return Err(CollectionError::NotFound {
+                what: format!("Shard {} replica on peer {peer_id}", self.shard_id),
+            });
+        }
+        replica_state.write(|replicas| {
+            replicas.insert(*peer_id, state);
+        })?;
         Ok(())
     }
 
     pub async fn apply_state(
         &mut self,
-        replicas: HashMap<PeerId, IsActive>,
+        replicas: HashMap<PeerId, ReplicaState>,
     ) -> CollectionResult<()> {
         let removed_peers = self
             .replica_state
+            .get_mut()
             .keys()
             .filter(|peer_id| !replicas.contains_key(peer_id))
             .copied()
@@ -89,42 +705,308 @@ impl ReplicaSet {
         for peer_id in removed_peers {
             if peer_id == self.this_peer_id {
                 if let Some(mut shard) = self.local.take() {
+                    // Replica state is persisted next to the data of the local replica
+                    let collection_path = self.shard_path.parent().ok_or_else(|| {
+                        CollectionError::service_error(format!(
+                            "Shard path {} has no parent",
+                            self.shard_path.display()
+                        ))
+                    })?;
+                    let shard_path = create_shard_dir(collection_path, self.shard_id).await?;
+                    self.move_state(&shard_path)?;
                     shard.before_drop().await;
                     drop_and_delete_from_disk(shard).await?;
-                } else {
-                    debug_assert!(false, "inconsistent `replica_set` map with actual shards")
                 }
-            } else if let Some(_remote_shard) =
-                &mut self.remotes.iter().find(|rs| rs.peer_id == peer_id)
-            {
-                todo!("remote_shard.remove_peer(peer_id)")
+            } else {
+                self.remotes.retain(|rs| rs.peer_id != peer_id);
             }
-            self.replica_state.remove(&peer_id);
+            self.replica_state
+                .get_mut()
+                .write(|state| state.remove(&peer_id))?;
+            self.replication_lag.lock().remove(&peer_id);
         }
-        for (peer_id, is_active) in replicas {
-            if let Some(state) = self.replica_state.get_mut(&peer_id) {
-                *state = is_active;
+        for (peer_id, state) in replicas {
+            if self.replica_state.get_mut().contains_key(&peer_id) {
+                self.replica_state
+                    .get_mut()
+                    .write(|replicas| replicas.insert(peer_id, state))?;
             } else if peer_id == self.this_peer_id {
-                todo!("clone replica from another peer or log error that it should be cloned with normal operation")
+                // Data of a local replica is only received with a shard transfer
+                log::warn!(
+                    "Shard {} is expected to have a replica on this peer {peer_id}, which has to be recovered with a transfer",
+                    self.shard_id
+                );
+                self.replica_state
+                    .get_mut()
+                    .write(|replicas| replicas.insert(peer_id, state))?;
             } else {
-                todo!("Add remote replica")
+                let remote = self.new_remote(peer_id);
+                self.add_remote(remote, state)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Create snapshot of the replica set into `target_path`.
+    ///
+    /// Data of the local replica is snapshotted the same way as a local shard.
+    /// Without a local replica, the shard is recorded as a remote one, located on an active replica.
+    pub async fn create_snapshot(&self, target_path: &Path) -> CollectionResult<()> {
+        self.snapshot_task(target_path).await?.await
+    }
+
+    /// Prepare snapshot of the replica set into `target_path`, see `LocalShard::snapshot_task`.
+    /// Snapshot the replica set as a plain shard: the local replica if there is one,
+    /// otherwise a remote shard pointing to an active replica.
+    /// Replica states are not part of the snapshot, they are restored through consensus.
+    pub async fn snapshot_task(&self, target_path: &Path) -> CollectionResult<SnapshotTask> {
+        match &self.local {
+            Some(local) => {
+                let task = local.snapshot_task(target_path).await?;
+                // Local replica is restored as a plain local shard
+                ShardConfig::new_local().save(target_path)?;
+                Ok(task)
+            }
+            None => {
+                let active_peer_id = self
+                    .remotes
+                    .iter()
+                    .map(|rs| rs.peer_id)
+                    .find(|peer_id| self.peer_is_active(peer_id))
+                    .ok_or_else(|| {
+                        CollectionError::service_error(format!(
+                            "The replica set for shard {} on peer {} has no active replica",
+                            self.shard_id, self.this_peer_id
+                        ))
+                    })?;
+                ShardConfig::new_remote(active_peer_id).save(target_path)?;
+                Ok(future::ready(Ok(())).boxed())
             }
         }
+    }
+
+    /// Replica set is restored as a plain local or remote shard, see `snapshot_task`.
+    /// Remove replica state left by snapshots which still recorded it, so the restored shard
+    /// does not pick up stale replicas once it is upgraded to a replica set again.
+    pub fn restore_snapshot(snapshot_path: &Path) -> CollectionResult<()> {
+        let replica_state_path = snapshot_path.join(REPLICA_STATE_FILE);
+        if replica_state_path.exists() {
+            std::fs::remove_file(replica_state_path)?;
+        }
         Ok(())
     }
 
     /// Check whether a peer is registered as `active`.
     /// Unknown peers are not active.
     pub fn peer_is_active(&self, peer_id: &PeerId) -> bool {
-        self.replica_state.get(peer_id) == Some(&true)
+        self.peer_state(peer_id)
+            .map_or(false, ReplicaState::is_active)
+    }
+
+    /// Check whether a peer should receive updates.
+    /// Unknown peers don't receive updates.
+    pub fn peer_accepts_updates(&self, peer_id: &PeerId) -> bool {
+        self.peer_state(peer_id)
+            .map_or(false, ReplicaState::accepts_updates)
+    }
+
+    /// Compare checksums of active replicas to find the ones, which silently diverged, e.g. missed
+    /// updates. Only the peer with the lowest id among active replicas compares them, so that each
+    /// shard is verified once. Returns transfers to repair replicas, which differ from the majority.
+    pub async fn suggest_repairs(&self) -> Vec<ShardTransfer> {
+        let mut active_peers: Vec<PeerId> = self
+            .replica_state
+            .read()
+            .iter()
+            .filter(|(_, state)| state.is_active())
+            .map(|(peer_id, _)| *peer_id)
+            .collect();
+        active_peers.sort_unstable();
+        if active_peers.len() < 2 || active_peers[0] != self.this_peer_id {
+            return vec![];
+        }
+        let checksums = join_all(active_peers.iter().map(|&peer_id| async move {
+            let checksum = match self.peer_replica(peer_id) {
+                Ok(replica) => replica.checksum().await,
+                Err(err) => Err(err),
+            };
+            (peer_id, checksum)
+        }))
+        .await;
+        // Unreachable replicas are handled as failed, not as diverged ones
+        let checksums: Vec<_> = checksums
+            .into_iter()
+            .filter_map(|(peer_id, checksum)| match checksum {
+                Ok(checksum) => Some((peer_id, checksum)),
+                Err(err) => {
+                    log::debug!(
+                        "Can't get checksum of shard {} replica on peer {peer_id}: {err}",
+                        self.shard_id
+                    );
+                    None
+                }
+            })
+            .collect();
+        diverged_replicas(&checksums)
+            .into_iter()
+            .map(|(to, from)| ShardTransfer {
+                shard_id: self.shard_id,
+                from,
+                to,
+            })
+            .collect()
+    }
+
+    /// Replica on `peer_id` to read from, e.g. to run analytics queries on a listener.
+    /// Listeners are never selected for reads otherwise.
+    pub fn peer_replica(
+        &self,
+        peer_id: PeerId,
+    ) -> CollectionResult<&(dyn ShardOperation + Send + Sync)> {
+        let replica_not_found = || CollectionError::NotFound {
+            what: format!("Shard {} replica on peer {peer_id}", self.shard_id),
+        };
+        match self.peer_state(&peer_id) {
+            Some(ReplicaState::Active | ReplicaState::Listener) => {}
+            Some(state) => {
+                return Err(CollectionError::bad_shard_selection(format!(
+                    "Shard {} replica on peer {peer_id} can't be read in {state:?} state",
+                    self.shard_id
+                )))
+            }
+            None => return Err(replica_not_found()),
+        }
+
+        if peer_id == self.this_peer_id {
+            match &self.local {
+                Some(local) => Ok(local),
+                None => Err(replica_not_found()),
+            }
+        } else {
+            match self.remotes.iter().find(|rs| rs.peer_id == peer_id) {
+                Some(remote) => Ok(remote),
+                None => Err(replica_not_found()),
+            }
+        }
+    }
+
+    /// Replica to read from, which has applied the update of the `session`.
+    /// Bypasses the configured `read_consistency`, as the other replicas might return stale results.
+    /// Local replica is preferred, as it can be made to apply an update, which it only acknowledged.
+    pub async fn session_replica(
+        &self,
+        session: &ShardSession,
+    ) -> CollectionResult<&(dyn ShardOperation + Send + Sync)> {
+        let has_update = |peer_id: &PeerId| {
+            self.peer_is_active(peer_id) && session.replicas.contains_key(peer_id)
+        };
+        if let Some(local) = &self.local {
+            if has_update(&self.this_peer_id) {
+                if !session.applied {
+                    local.wait_update_queue().await?;
+                }
+                return Ok(local);
+            }
+        }
+        if session.applied {
+            if let Some(remote) = self.remotes.iter().find(|rs| has_update(&rs.peer_id)) {
+                return Ok(remote);
+            }
+        }
+        Err(CollectionError::service_error(format!(
+            "No active replica of shard {} on peer {} is known to have applied the update of the session",
+            self.shard_id, self.this_peer_id
+        )))
+    }
+
+    /// Execute read operation on replica set with the configured `read_consistency`.
+    /// It does not report failing peer_ids to the consensus.
+    pub async fn execute_read_operation<'a, F, Fut, Res>(&'a self, read: F) -> CollectionResult<Res>
+    where
+        F: Fn(&'a (dyn ShardOperation + Send + Sync)) -> Fut,
+        Fut: Future<Output = CollectionResult<Res>>,
+        Res: Resolve,
+    {
+        let read_consistency = *self.read_consistency.read();
+        match read_consistency {
+            ReadConsistency::Single => self.execute_read_single(read).await,
+            ReadConsistency::Quorum | ReadConsistency::All => {
+                self.execute_read_resolved(read, read_consistency).await
+            }
+        }
+    }
+
+    /// Execute read operation on all active replicas in parallel,
+    /// until the number of successful results required by `read_consistency` is collected.
+    /// Collected results are reconciled with `Resolve`.
+    async fn execute_read_resolved<'a, F, Fut, Res>(
+        &'a self,
+        read: F,
+        read_consistency: ReadConsistency,
+    ) -> CollectionResult<Res>
+    where
+        F: Fn(&'a (dyn ShardOperation + Send + Sync)) -> Fut,
+        Fut: Future<Output = CollectionResult<Res>>,
+        Res: Resolve,
+    {
+        let mut active_shards: Vec<&'a (dyn ShardOperation + Send + Sync)> = self
+            .remotes
+            .iter()
+            .filter(|rs| self.peer_is_active(&rs.peer_id))
+            .map(|rs| rs as &(dyn ShardOperation + Send + Sync))
+            .collect();
+        if let Some(local) = &self.local {
+            if self.peer_is_active(&self.this_peer_id) {
+                active_shards.push(local);
+            }
+        }
+
+        if active_shards.is_empty() {
+            return Err(CollectionError::service_error(format!(
+                "The replica set for shard {} on peer {} has no active replica",
+                self.shard_id, self.this_peer_id
+            )));
+        }
+
+        let active_count = active_shards.len();
+        let required = match read_consistency {
+            ReadConsistency::All => active_count,
+            ReadConsistency::Single | ReadConsistency::Quorum => active_count / 2 + 1,
+        };
+
+        let mut futures: FuturesUnordered<_> =
+            active_shards.into_iter().map(|shard| read(shard)).collect();
+
+        let mut results = Vec::with_capacity(required);
+        let mut captured_error = None;
+        while let Some(result) = futures.next().await {
+            match result {
+                Ok(res) => {
+                    results.push(res);
+                    if results.len() >= required {
+                        return Ok(Res::resolve(results));
+                    }
+                }
+                Err(err) => captured_error = Some(err), // capture error for possible error reporting
+            }
+        }
+
+        let err =
+            captured_error.expect("at this point `captured_error` must be defined by construction");
+        Err(CollectionError::service_error(format!(
+            "{:?} read consistency requires {required} of {active_count} replicas of shard {}, \
+             but only {} answered successfully, last error: {err}",
+            read_consistency,
+            self.shard_id,
+            results.len(),
+        )))
     }
 
     /// Execute read operation on replica set:
     /// 1 - Prefer local replica
-    /// 2 - Otherwise uses `read_fan_out_ratio` to compute list of active remote shards.
+    /// 2 - Otherwise reads `read_fan_out_factor` of active remote shards in parallel.
     /// 3 - Fallbacks to all remaining shards if the optimisations fails.
-    /// It does not report failing peer_ids to the consensus.
-    pub async fn execute_read_operation<'a, F, Fut, Res>(&'a self, read: F) -> CollectionResult<Res>
+    async fn execute_read_single<'a, F, Fut, Res>(&'a self, read: F) -> CollectionResult<Res>
     where
         F: Fn(&'a (dyn ShardOperation + Send + Sync)) -> Fut,
         Fut: Future<Output = CollectionResult<Res>>,
@@ -152,8 +1034,10 @@ impl ReplicaSet {
             )));
         }
 
-        let fan_out_selection =
-            (self.read_fan_out_ratio * active_remote_shards.len() as f32).ceil() as usize;
+        let fan_out_selection = match *self.read_fan_out_factor.read() {
+            Some(factor) => (factor as usize).clamp(1, active_remote_shards.len()),
+            None => active_remote_shards.len(),
+        };
 
         let mut futures = FuturesUnordered::new();
         for remote in &active_remote_shards[0..fan_out_selection] {
@@ -191,6 +1075,50 @@ impl ReplicaSet {
     }
 }
 
+/// Peer of an active replica to sync a new replica of the shard from.
+/// The least loaded one is preferred, ties are broken by the lowest peer id.
+pub fn replication_source(
+    replicas: &HashMap<PeerId, ReplicaState>,
+    peer_load: &HashMap<PeerId, usize>,
+) -> Option<PeerId> {
+    replicas
+        .iter()
+        .filter(|(_, state)| state.is_active())
+        .map(|(peer_id, _)| *peer_id)
+        .min_by_key(|peer_id| {
+            (
+                peer_load.get(peer_id).copied().unwrap_or_default(),
+                *peer_id,
+            )
+        })
+}
+
+/// Pairs of diverged replica and the replica to repair it from.
+/// The majority of replicas with equal checksums is considered to be correct. Ties are resolved in
+/// favor of more points, as missed inserts are more likely than missed deletes, then of lower ids.
+fn diverged_replicas(checksums: &[(PeerId, ShardChecksum)]) -> Vec<(PeerId, PeerId)> {
+    let mut groups: HashMap<ShardChecksum, Vec<PeerId>> = HashMap::new();
+    for (peer_id, checksum) in checksums {
+        groups.entry(*checksum).or_default().push(*peer_id);
+    }
+    let majority = groups.iter().max_by_key(|(checksum, peers)| {
+        (
+            peers.len(),
+            checksum.points_count,
+            Reverse(peers.iter().min().copied()),
+        )
+    });
+    let (majority_checksum, source) = match majority {
+        Some((checksum, peers)) => (*checksum, *peers.iter().min().unwrap()),
+        None => return vec![],
+    };
+    checksums
+        .iter()
+        .filter(|(_, checksum)| *checksum != majority_checksum)
+        .map(|(peer_id, _)| (*peer_id, source))
+        .collect()
+}
+
 #[async_trait::async_trait]
 impl ShardOperation for ReplicaSet {
     async fn update(
@@ -198,69 +1126,124 @@ impl ShardOperation for ReplicaSet {
         operation: CollectionUpdateOperations,
         wait: bool,
     ) -> CollectionResult<UpdateResult> {
-        // target all remote peers that are active
-        let active_remote_shards: Vec<_> = self
+        // target all replicas that accept updates, including the local one
+        let mut target_peers: Vec<PeerId> = self
             .remotes
             .iter()
-            .filter(|rs| self.peer_is_active(&rs.peer_id))
+            .map(|rs| rs.peer_id)
+            .filter(|peer_id| self.peer_accepts_updates(peer_id))
             .collect();
+        if self.local.is_some() && self.peer_accepts_updates(&self.this_peer_id) {
+            target_peers.push(self.this_peer_id);
+        }
 
-        // local is defined AND the peer itself is active
-        let local_is_active = self.local.is_some() && self.peer_is_active(&self.this_peer_id);
+        // listeners receive updates, but are not counted for write acknowledgement
+        let (listener_peers, voting_peers): (Vec<_>, Vec<_>) = target_peers
+            .into_iter()
+            .partition(|peer_id| self.peer_state(peer_id) == Some(ReplicaState::Listener));
 
-        if active_remote_shards.is_empty() && !local_is_active {
+        if voting_peers.is_empty() {
             return Err(CollectionError::service_error(format!(
                 "The replica set for shard {} on peer {} has no active replica",
                 self.shard_id, self.this_peer_id
             )));
         }
 
-        let mut remote_futures = Vec::new();
-        for remote in active_remote_shards {
-            let op = operation.clone();
-            remote_futures.push(async move {
-                remote
-                    .update(op, wait)
-                    .await
-                    .map_err(|err| (remote.peer_id, err))
-            });
-        }
+        // Batches of a running transfer wait until all replicas got the update
+        let transfer_guard = Arc::new(self.transfer_lock.clone().read_owned().await);
+        self.track_sent_update(voting_peers.iter().chain(&listener_peers).copied());
+        let required_acks = self.write_consistency.required_acks(voting_peers.len());
+        let tolerated_failures = voting_peers.len() - required_acks;
 
-        let all_res = match &self.local {
-            Some(local) if self.peer_is_active(&self.this_peer_id) => {
-                let local_update = async move {
-                    local
-                        .update(operation.clone(), wait)
-                        .await
-                        .map_err(|err| (self.this_peer_id, err))
+        let voting_updates = async {
+            let mut pending: FuturesUnordered<_> = voting_peers
+                .iter()
+                .map(|&peer_id| {
+                    self.update_replica(peer_id, operation.clone(), wait, transfer_guard.clone())
+                        .map(move |res| (peer_id, res))
+                })
+                .collect();
+            let mut local_pending = voting_peers.contains(&self.this_peer_id);
+            let mut acked = Vec::new();
+            let mut failed = Vec::new();
+            // stop waiting for the remote replicas as soon as the outcome is known
+            while local_pending
+                || (acked.len() < required_acks && failed.len() <= tolerated_failures)
+            {
+                let (peer_id, res) = match pending.next().await {
+                    Some(result) => result,
+                    None => break,
                 };
-                let remote_updates = try_join_all(remote_futures);
-
-                // run local and remote shards read concurrently
-                try_join(remote_updates, local_update)
-                    .await
-                    .map(|(remote_res, _local_res)| remote_res)
+                if peer_id == self.this_peer_id {
+                    local_pending = false;
+                }
+                match res {
+                    Ok(res) => acked.push((peer_id, res)),
+                    Err(err) => failed.push((peer_id, err)),
+                }
             }
-            _ => try_join_all(remote_futures).await,
+            (acked, failed)
         };
+        let listener_updates = join_all(listener_peers.iter().map(|&peer_id| {
+            self.update_replica(peer_id, operation.clone(), wait, transfer_guard.clone())
+                .map(move |res| res.map_err(|err| (peer_id, err)))
+        }));
 
-        match all_res {
-            Ok(results) => {
-                // return first result
-                match results.into_iter().next() {
-                    None => Err(CollectionError::service_error(format!(
-                        "None of the replicas replied for Replica set {} on peer {}",
-                        self.shard_id, self.this_peer_id
-                    ))),
-                    Some(res) => Ok(res),
-                }
-            }
-            Err((peer_id, err)) => {
-                // report failing `peer_id`
+        // run updates of all replicas concurrently
+        let ((acked, failed), listener_res) = join(voting_updates, listener_updates).await;
+
+        // failures of remote replicas are already reported by their update tasks
+        let is_local = |peer_id: &PeerId| *peer_id == self.this_peer_id;
+        for (peer_id, err) in listener_res.into_iter().filter_map(Result::err) {
+            if is_local(&peer_id) {
+                log::warn!(
+                    "Failed to update local listener replica of shard {}: {err}",
+                    self.shard_id
+                );
                 self.notify_peer_failure(peer_id).await;
-                Err(err)
             }
         }
+        for (peer_id, err) in failed.iter().filter(|(peer_id, _)| is_local(peer_id)) {
+            log::warn!(
+                "Failed to update local replica of shard {}: {err}",
+                self.shard_id
+            );
+            self.notify_peer_failure(*peer_id).await;
+        }
+
+        if acked.len() < required_acks {
+            // the update is not acknowledged
+            return Err(failed
+                .into_iter()
+                .map(|(_, err)| err)
+                .next()
+                .unwrap_or_else(|| {
+                    CollectionError::service_error(format!(
+                        "None of the replicas replied for Replica set {} on peer {}",
+                        self.shard_id, self.this_peer_id
+                    ))
+                }));
+        }
+
+        let session = ShardSession {
+            replicas: acked
+                .iter()
+                .map(|(peer_id, res)| (*peer_id, res.operation_id))
+                .collect(),
+            applied: acked
+                .iter()
+                .all(|(_, res)| res.status == UpdateStatus::Completed),
+        };
+
+        // return first result
+        let (_peer_id, mut res) = acked
+            .into_iter()
+            .next()
+            .expect("at least one replica acked");
+        res.session_token = Some(SessionToken {
+            shards: [(self.shard_id, session)].into(),
+        });
+        Ok(res)
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -307,4 +1290,281 @@ impl ShardOperation for ReplicaSet {
         })
         .await
     }
+
+    /// Checksum of the local replica, if there is one
+    async fn checksum(&self) -> CollectionResult<ShardChecksum> {
+        match &self.local {
+            Some(local) => local.checksum().await,
+            None => self.execute_read_operation(|shard| shard.checksum()).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use segment::common::file_operations::atomic_save_json;
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::shard::ChannelService;
+
+    fn scored_point(id: u64, version: SeqNumberType) -> ScoredPoint {
+        ScoredPoint {
+            id: ExtendedPointId::NumId(id),
+            version,
+            score: 1.0,
+            payload: None,
+            vector: None,
+            payload_truncated: false,
+        }
+    }
+
+    #[test]
+    fn test_resolve_search() {
+        let stale = vec![vec![scored_point(1, 10)], vec![scored_point(2, 5)]];
+        let fresh = vec![vec![scored_point(3, 12)], vec![]];
+
+        let resolved = Vec::<Vec<ScoredPoint>>::resolve(vec![stale, fresh]);
+        assert_eq!(resolved[0][0].id, ExtendedPointId::NumId(3));
+        assert_eq!(resolved[1][0].id, ExtendedPointId::NumId(2));
+    }
+
+    #[test]
+    fn test_resolve_majority() {
+        let counts = [3, 5, 5, 3, 4].map(|count| CountResult { count });
+        assert_eq!(CountResult::resolve(counts.into()).count, 5);
+
+        let counts = [3, 5].map(|count| CountResult { count });
+        assert_eq!(CountResult::resolve(counts.into()).count, 3);
+    }
+
+    #[test]
+    fn test_write_required_acks() {
+        assert_eq!(WriteConsistency::All.required_acks(3), 3);
+        assert_eq!(WriteConsistency::Majority.required_acks(1), 1);
+        assert_eq!(WriteConsistency::Majority.required_acks(3), 2);
+        assert_eq!(WriteConsistency::Majority.required_acks(4), 3);
+    }
+
+    fn replica_set(
+        path: &Path,
+        remotes: Vec<RemoteShard>,
+        replica_state: HashMap<PeerId, ReplicaState>,
+    ) -> ReplicaSet {
+        ReplicaSet::new(
+            0,
+            "test".to_string(),
+            1,
+            path,
+            None,
+            remotes,
+            replica_state,
+            None,
+            ReadConsistency::Single,
+            WriteConsistency::All,
+            ChannelService::default(),
+            Arc::new(move |_peer_id, _shard_id| Box::new(async {})),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_replica_state_persistence() {
+        let dir = Builder::new().prefix("replica_set").tempdir().unwrap();
+        let initial_state =
+            HashMap::from([(1, ReplicaState::Active), (2, ReplicaState::Initializing)]);
+
+        let replicas = replica_set(dir.path(), vec![], initial_state.clone());
+        assert!(replicas.peer_is_active(&1));
+        assert!(!replicas.peer_is_active(&2));
+        assert!(replicas.peer_accepts_updates(&2));
+
+        replicas
+            .set_replica_state(&2, ReplicaState::Recovery)
+            .unwrap();
+        assert!(!replicas.peer_accepts_updates(&2));
+        assert!(replicas
+            .set_replica_state(&3, ReplicaState::Active)
+            .is_err());
+        drop(replicas);
+
+        // Persisted state takes precedence over the initial one
+        let replicas = replica_set(dir.path(), vec![], initial_state);
+        assert_eq!(replicas.peer_state(&2), Some(ReplicaState::Recovery));
+        assert_eq!(replicas.peer_state(&3), None);
+    }
+
+    #[test]
+    fn test_listener_selection() {
+        let dir = Builder::new().prefix("replica_set").tempdir().unwrap();
+        let replicas = replica_set(
+            dir.path(),
+            vec![],
+            HashMap::from([(1, ReplicaState::Listener), (2, ReplicaState::Dead)]),
+        );
+
+        // Listener is not a read target, unless selected explicitly
+        assert!(!replicas.peer_is_active(&1));
+        assert!(replicas.peer_accepts_updates(&1));
+        let res = replicas.peer_replica(1);
+        assert!(matches!(res, Err(CollectionError::NotFound { .. })));
+
+        let res = replicas.peer_replica(2);
+        assert!(matches!(
+            res,
+            Err(CollectionError::BadShardSelection { .. })
+        ));
+    }
+
+    #[test]
+    fn test_local_recovery_source() {
+        let dir = Builder::new().prefix("replica_set").tempdir().unwrap();
+        let remote =
+            |peer_id| RemoteShard::new(0, "test".to_string(), peer_id, ChannelService::default());
+
+        // Local replica was dead before restart, recover from the active one
+        let replicas = replica_set(
+            dir.path(),
+            vec![remote(2), remote(3)],
+            HashMap::from([
+                (1, ReplicaState::Dead),
+                (2, ReplicaState::Partial),
+                (3, ReplicaState::Active),
+            ]),
+        );
+        assert_eq!(replicas.local_recovery_source(), Some(3));
+        drop(replicas);
+
+        // Nothing to recover on a peer without replica
+        let dir = Builder::new().prefix("replica_set").tempdir().unwrap();
+        let replicas = replica_set(
+            dir.path(),
+            vec![remote(3)],
+            HashMap::from([(3, ReplicaState::Active)]),
+        );
+        assert_eq!(replicas.local_recovery_source(), None);
+    }
+
+    #[test]
+    fn test_replication_source() {
+        let dir = Builder::new().prefix("replica_set").tempdir().unwrap();
+        let remote =
+            |peer_id| RemoteShard::new(0, "test".to_string(), peer_id, ChannelService::default());
+        let mut replicas = replica_set(
+            dir.path(),
+            vec![remote(2), remote(3), remote(4)],
+            HashMap::from([
+                (2, ReplicaState::Active),
+                (3, ReplicaState::Active),
+                (4, ReplicaState::Dead),
+            ]),
+        );
+
+        // Least loaded active replica is preferred, dead ones are never a source
+        let load = HashMap::from([(2, 3), (3, 1), (4, 0)]);
+        assert_eq!(replicas.replication_source(&load), Some(3));
+        assert_eq!(replicas.replication_source(&HashMap::new()), Some(2));
+
+        // New replica is not a source until it is synced
+        replicas
+            .add_remote(remote(5), ReplicaState::Partial)
+            .unwrap();
+        assert_eq!(replicas.peer_state(&5), Some(ReplicaState::Partial));
+        let load = HashMap::from([(2, 1), (3, 1), (5, 0)]);
+        assert_eq!(replicas.replication_source(&load), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_session_replica() {
+        let dir = Builder::new().prefix("replica_set").tempdir().unwrap();
+        let remote =
+            |peer_id| RemoteShard::new(0, "test".to_string(), peer_id, ChannelService::default());
+        let replicas = replica_set(
+            dir.path(),
+            vec![remote(2), remote(3)],
+            HashMap::from([(2, ReplicaState::Active), (3, ReplicaState::Dead)]),
+        );
+        let session = |peer_id, applied| ShardSession {
+            replicas: [(peer_id, 7)].into(),
+            applied,
+        };
+
+        assert!(replicas.session_replica(&session(2, true)).await.is_ok());
+        // Dead replica doesn't answer reads, even if it has applied the update
+        assert!(replicas.session_replica(&session(3, true)).await.is_err());
+        // Remote replica can't be checked to have applied an acknowledged update
+        assert!(replicas.session_replica(&session(2, false)).await.is_err());
+    }
+
+    #[test]
+    fn test_replication_lag() {
+        let dir = Builder::new().prefix("replica_set").tempdir().unwrap();
+        let replicas = replica_set(
+            dir.path(),
+            vec![],
+            HashMap::from([(2, ReplicaState::Active), (3, ReplicaState::Active)]),
+        );
+        assert_eq!(replicas.replication_lag(&2), ReplicationLag::default());
+
+        replicas.track_sent_update([2, 3]);
+        replicas.track_sent_update([2, 3]);
+        replicas.track_acked_update(3);
+        let lag = replicas.replication_lag(&2);
+        assert_eq!(lag.unacked_operations, 2);
+        assert!(lag.lag_ms.is_some());
+        assert_eq!(replicas.replication_lag(&3), ReplicationLag::default());
+
+        // Acknowledged update means the replica caught up
+        replicas.track_acked_update(2);
+        assert_eq!(replicas.replication_lag(&2), ReplicationLag::default());
+    }
+
+    #[test]
+    fn test_diverged_replicas() {
+        let checksum = |points_count, checksum| ShardChecksum {
+            points_count,
+            checksum,
+        };
+        assert!(diverged_replicas(&[(1, checksum(10, 7)), (2, checksum(10, 7))]).is_empty());
+
+        // Minority is repaired from the lowest peer of the majority
+        let checksums = [
+            (1, checksum(10, 7)),
+            (2, checksum(9, 3)),
+            (3, checksum(10, 7)),
+        ];
+        assert_eq!(diverged_replicas(&checksums), vec![(2, 1)]);
+
+        // Without majority, the replica with more points wins
+        let checksums = [(1, checksum(9, 3)), (2, checksum(10, 7))];
+        assert_eq!(diverged_replicas(&checksums), vec![(1, 2)]);
+    }
+
+    #[tokio::test]
+    async fn test_remote_replica_set_snapshot() {
+        let dir = Builder::new().prefix("replica_set").tempdir().unwrap();
+        let snapshot_dir = Builder::new().prefix("snapshot").tempdir().unwrap();
+        let remote =
+            |peer_id| RemoteShard::new(0, "test".to_string(), peer_id, ChannelService::default());
+        let replica_state = HashMap::from([(2, ReplicaState::Dead), (3, ReplicaState::Active)]);
+        let replicas = replica_set(
+            dir.path(),
+            vec![remote(2), remote(3)],
+            replica_state.clone(),
+        );
+
+        replicas.create_snapshot(snapshot_dir.path()).await.unwrap();
+        // Snapshots of older versions recorded the replica state
+        atomic_save_json(
+            &snapshot_dir.path().join(REPLICA_STATE_FILE),
+            &replica_state,
+        )
+        .unwrap();
+        ReplicaSet::restore_snapshot(snapshot_dir.path()).unwrap();
+
+        // Shard is restored as a remote shard located on the active replica
+        let shard_config = ShardConfig::load(snapshot_dir.path()).unwrap().unwrap();
+        assert_eq!(shard_config, ShardConfig::new_remote(3));
+        assert!(!snapshot_dir.path().join(REPLICA_STATE_FILE).exists());
+    }
 }
@@ -0,0 +1,157 @@
+use std::future::Future;
+use std::time::Duration;
+
+use api::grpc::transport_channel_pool::RequestError;
+use serde::{Deserialize, Serialize};
+use tonic::{Code, Status};
+
+/// Policy of retrying requests to remote shards, which failed due to transient network errors
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Max number of attempts of a request, including the first one. If 1 - requests are not retried.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled with each next retry
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    /// Max delay between retries
+    #[serde(default = "default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    100
+}
+
+fn default_max_backoff_ms() -> u64 {
+    2000
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_max_attempts(),
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_ms: default_max_backoff_ms(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before the retry number `retry`, starting from 0
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let backoff_ms = self
+            .initial_backoff_ms
+            .saturating_mul(2u64.saturating_pow(retry))
+            .min(self.max_backoff_ms);
+        Duration::from_millis(backoff_ms)
+    }
+
+    /// Call `f` until it succeeds, fails with an error which is not transient,
+    /// or `max_attempts` are made.
+    pub async fn retry<T, O: Future<Output = Result<T, RequestError<Status>>>>(
+        &self,
+        f: impl Fn() -> O,
+    ) -> Result<T, RequestError<Status>> {
+        let mut retry = 0;
+        loop {
+            let result = f().await;
+            match result {
+                Err(err) if retry + 1 < self.max_attempts && is_retryable(&err) => {
+                    let backoff = self.backoff(retry);
+                    log::debug!("Retrying request in {backoff:?} after error: {err}");
+                    tokio::time::sleep(backoff).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Whether the request might succeed, if it is sent again.
+/// Failed connections and unavailable peers are transient, errors of the request itself are not.
+pub fn is_retryable(err: &RequestError<Status>) -> bool {
+    match err {
+        RequestError::Tonic(_) => true,
+        RequestError::FromClosure(status) => matches!(
+            status.code(),
+            Code::Unavailable | Code::ResourceExhausted | Code::Aborted
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    fn policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff_ms: 1,
+            max_backoff_ms: 4,
+        }
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = policy(10);
+        let backoffs: Vec<_> = (0..5)
+            .map(|retry| policy.backoff(retry).as_millis())
+            .collect();
+        assert_eq!(backoffs, vec![1, 2, 4, 4, 4]);
+        assert_eq!(policy.backoff(100), Duration::from_millis(4));
+    }
+
+    #[tokio::test]
+    async fn test_retry_transient_errors() {
+        let attempts = AtomicU32::new(0);
+        let result = policy(3)
+            .retry(|| async {
+                if attempts.fetch_add(1, Ordering::Relaxed) < 2 {
+                    Err(RequestError::FromClosure(Status::unavailable(
+                        "peer is down",
+                    )))
+                } else {
+                    Ok(42)
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+
+        // Gives up after max attempts
+        let attempts = AtomicU32::new(0);
+        let result: Result<(), _> = policy(2)
+            .retry(|| async {
+                attempts.fetch_add(1, Ordering::Relaxed);
+                Err(RequestError::FromClosure(Status::unavailable(
+                    "peer is down",
+                )))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_of_request_errors() {
+        let attempts = AtomicU32::new(0);
+        let result: Result<(), _> = policy(3)
+            .retry(|| async {
+                attempts.fetch_add(1, Ordering::Relaxed);
+                Err(RequestError::FromClosure(Status::invalid_argument(
+                    "bad request",
+                )))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::Relaxed), 1);
+    }
+}
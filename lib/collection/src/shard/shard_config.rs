@@ -11,8 +11,13 @@ pub const SHARD_CONFIG_FILE: &str = "shard_config.json";
 #[derive(Debug, Deserialize, Serialize, Copy, Clone, PartialEq, Eq)]
 pub enum ShardType {
     Local,
-    Remote { peer_id: PeerId },
+    Remote {
+        peer_id: PeerId,
+    },
     Temporary, // same as local, but not ready yet
+    /// Replicas of the shard are listed in the replica state file of the shard.
+    /// Data of the local replica, if this peer holds one, is kept in the same directory.
+    ReplicaSet,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
@@ -35,6 +40,11 @@ impl ShardConfig {
         Self { r#type }
     }
 
+    pub fn new_replica_set() -> Self {
+        let r#type = ShardType::ReplicaSet;
+        Self { r#type }
+    }
+
     pub fn new_temp() -> Self {
         let r#type = ShardType::Temporary;
         Self { r#type }
@@ -2,28 +2,34 @@ use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::sync::Arc;
 
+use futures::future::try_join_all;
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
+use segment::types::PointIdType;
 use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 use super::PeerId;
 use crate::config::CollectionConfig;
 use crate::hash_ring::HashRing;
+use crate::operations::shard_selector::{ShardSelector, ShardSelectorInternal};
 use crate::operations::types::{CollectionError, CollectionResult};
 use crate::operations::{OperationToShard, SplitByShard};
+use crate::resource_watchdog::ResourceThrottle;
 use crate::save_on_disk::SaveOnDisk;
 use crate::shard::local_shard::LocalShard;
 use crate::shard::remote_shard::RemoteShard;
+use crate::shard::replica_set::{OnPeerFailure, ReadConsistency, ReplicaSet, ReplicaState};
 use crate::shard::shard_config::ShardType;
 use crate::shard::shard_versioning::latest_shard_paths;
 use crate::shard::Shard::Local;
-use crate::shard::{ChannelService, CollectionId, Shard, ShardId, ShardTransfer};
+use crate::shard::{ChannelService, CollectionId, Shard, ShardId, ShardOperation, ShardTransfer};
 
 const SHARD_TRANSFERS_FILE: &str = "shard_transfers";
 
 pub struct ShardHolder {
     shards: HashMap<ShardId, Shard>,
-    pub(crate) shard_transfers: SaveOnDisk<HashSet<ShardTransfer>>,
+    /// Locked separately from the holder, so registering transfers doesn't block reads of shards
+    shard_transfers: parking_lot::RwLock<SaveOnDisk<HashSet<ShardTransfer>>>,
     temporary_shards: HashMap<ShardId, Shard>,
     ring: HashRing<ShardId>,
 }
@@ -35,7 +41,7 @@ impl ShardHolder {
         let shard_transfers = SaveOnDisk::load_or_init(collection_path.join(SHARD_TRANSFERS_FILE))?;
         Ok(Self {
             shards: HashMap::new(),
-            shard_transfers,
+            shard_transfers: parking_lot::RwLock::new(shard_transfers),
             temporary_shards: HashMap::new(),
             ring: hashring,
         })
@@ -74,6 +80,11 @@ impl ShardHolder {
         self.shards.get(shard_id)
     }
 
+    /// Shard, which the point belongs to according to the hash ring
+    pub fn point_shard(&self, point_id: &PointIdType) -> Option<ShardId> {
+        self.ring.get(point_id).copied()
+    }
+
     pub fn get_mut_shard(&mut self, shard_id: &ShardId) -> Option<&mut Shard> {
         self.shards.get_mut(shard_id)
     }
@@ -98,20 +109,34 @@ impl ShardHolder {
         self.temporary_shards.values()
     }
 
-    pub fn get_shard_transfers(&self) -> impl Iterator<Item = &ShardTransfer> {
-        self.shard_transfers.iter()
+    /// Copy of the registered shard transfers
+    pub fn shard_transfers(&self) -> HashSet<ShardTransfer> {
+        (**self.shard_transfers.read()).clone()
+    }
+
+    /// Whether a transfer of the shard `shard_id` is registered
+    pub fn is_shard_transferred(&self, shard_id: ShardId) -> bool {
+        self.shard_transfers
+            .read()
+            .iter()
+            .any(|transfer| transfer.shard_id == shard_id)
     }
 
-    pub fn split_by_shard<O: SplitByShard + Clone>(&self, operation: O) -> Vec<(&Shard, O)> {
+    pub fn split_by_shard<O: SplitByShard + Clone>(
+        &self,
+        operation: O,
+    ) -> Vec<(ShardId, &Shard, O)> {
         let operation_to_shard = operation.split_by_shard(&self.ring);
         let shard_ops: Vec<_> = match operation_to_shard {
             OperationToShard::ByShard(by_shard) => by_shard
                 .into_iter()
-                .map(|(shard_id, operation)| (self.shards.get(&shard_id).unwrap(), operation))
+                .map(|(shard_id, operation)| {
+                    (shard_id, self.shards.get(&shard_id).unwrap(), operation)
+                })
                 .collect(),
             OperationToShard::ToAll(operation) => self
-                .all_shards()
-                .map(|shard| (shard, operation.clone()))
+                .get_shards()
+                .map(|(shard_id, shard)| (*shard_id, shard, operation.clone()))
                 .collect(),
         };
         shard_ops
@@ -132,34 +157,44 @@ impl ShardHolder {
         self.temporary_shards.remove(&shard_id)
     }
 
-    pub fn register_start_shard_transfer(
-        &mut self,
-        transfer: ShardTransfer,
-    ) -> CollectionResult<bool> {
+    pub fn register_start_shard_transfer(&self, transfer: ShardTransfer) -> CollectionResult<bool> {
         Ok(self
             .shard_transfers
+            .write()
             .write(|transfers| transfers.insert(transfer))?)
     }
 
-    pub fn register_finish_transfer(&mut self, transfer: &ShardTransfer) -> CollectionResult<bool> {
+    pub fn register_finish_transfer(&self, transfer: &ShardTransfer) -> CollectionResult<bool> {
         Ok(self
             .shard_transfers
+            .write()
             .write(|transfers| transfers.remove(transfer))?)
     }
 
+    /// Replace registered transfers, e.g. with the ones from a snapshot of the consensus state
+    pub fn set_shard_transfers(
+        &self,
+        shard_transfers: HashSet<ShardTransfer>,
+    ) -> CollectionResult<()> {
+        self.shard_transfers
+            .write()
+            .write(|transfers| *transfers = shard_transfers)?;
+        Ok(())
+    }
+
     pub fn set_shard_replica_state(
-        &mut self,
+        &self,
         shard_id: ShardId,
         peer_id: PeerId,
-        active: bool,
+        state: ReplicaState,
     ) -> CollectionResult<()> {
         if let Shard::ReplicaSet(replica_set) =
-            self.get_mut_shard(&shard_id)
+            self.get_shard(&shard_id)
                 .ok_or_else(|| CollectionError::NotFound {
                     what: format!("Shard {shard_id}"),
                 })?
         {
-            replica_set.set_active(&peer_id, active)
+            replica_set.set_replica_state(&peer_id, state)
         } else {
             Err(CollectionError::ServiceError {
                 error: format!("Shard {shard_id} is not a replica set"),
@@ -167,10 +202,119 @@ impl ShardHolder {
         }
     }
 
-    pub fn target_shards(&self, shard_selection: Option<ShardId>) -> CollectionResult<Vec<&Shard>> {
+    /// Apply read fan-out of the collection to all replica sets
+    pub fn set_read_fan_out_factor(&self, read_fan_out_factor: Option<u32>) {
+        for shard in self.shards.values() {
+            if let Shard::ReplicaSet(replica_set) = shard {
+                replica_set.set_read_fan_out_factor(read_fan_out_factor);
+            }
+        }
+    }
+
+    /// Apply read consistency of the collection to all replica sets
+    pub fn set_read_consistency(&self, read_consistency: ReadConsistency) {
+        for shard in self.shards.values() {
+            if let Shard::ReplicaSet(replica_set) = shard {
+                replica_set.set_read_consistency(read_consistency);
+            }
+        }
+    }
+
+    /// Set state of the replica, which receives the transfer.
+    /// Does nothing, unless the shard is a replica set and the receiving peer is one of its replicas.
+    pub fn set_transfer_target_state(
+        &self,
+        transfer: &ShardTransfer,
+        state: ReplicaState,
+    ) -> CollectionResult<()> {
+        if let Some(Shard::ReplicaSet(replica_set)) = self.get_shard(&transfer.shard_id) {
+            if replica_set.peer_state(&transfer.to).is_some() {
+                replica_set.set_replica_state(&transfer.to, state)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Ids of the shards, selected by a client
+    pub fn select_shards(&self, selector: &ShardSelector) -> CollectionResult<Vec<ShardId>> {
+        let mut shard_ids = match selector {
+            ShardSelector::ShardKeys { shard_keys } => shard_keys
+                .iter()
+                .map(|shard_key| {
+                    self.ring.get(shard_key).copied().ok_or_else(|| {
+                        CollectionError::service_error("Hash ring is empty".to_string())
+                    })
+                })
+                .collect::<CollectionResult<Vec<_>>>()?,
+            ShardSelector::ShardIds { shard_ids } => {
+                if let Some(missing) = shard_ids.iter().find(|&&id| !self.contains_shard(&id)) {
+                    return Err(CollectionError::bad_shard_selection(format!(
+                        "Shard {} does not exist",
+                        missing
+                    )));
+                }
+                shard_ids.clone()
+            }
+            ShardSelector::ShardReplica { shard_id, .. } => {
+                if !self.contains_shard(shard_id) {
+                    return Err(CollectionError::bad_shard_selection(format!(
+                        "Shard {} does not exist",
+                        shard_id
+                    )));
+                }
+                vec![*shard_id]
+            }
+        };
+        if shard_ids.is_empty() {
+            return Err(CollectionError::bad_shard_selection(
+                "No shards selected".to_string(),
+            ));
+        }
+        shard_ids.sort_unstable();
+        shard_ids.dedup();
+        Ok(shard_ids)
+    }
+
+    /// Shards to read from: the selected replica or the replicas, consistent with the session of
+    /// the selection, if any
+    pub async fn read_targets(
+        &self,
+        shard_selection: &ShardSelectorInternal,
+    ) -> CollectionResult<Vec<&(dyn ShardOperation + Sync + Send + '_)>> {
+        // Peers read their own replica, so the read is not forwarded to other replicas again
+        if let ShardSelectorInternal::ShardId(shard_id) = shard_selection {
+            if let Some(Shard::ReplicaSet(replica_set)) = self.get_shard(shard_id) {
+                return Ok(vec![replica_set.local_replica()?]);
+            }
+        }
+        let target_shards = self.target_shards(shard_selection)?;
+        try_join_all(
+            target_shards
+                .into_iter()
+                .map(|shard| shard.get_for_read(shard_selection)),
+        )
+        .await
+    }
+
+    pub fn target_shards(
+        &self,
+        shard_selection: &ShardSelectorInternal,
+    ) -> CollectionResult<Vec<&Shard>> {
         match shard_selection {
-            None => Ok(self.all_shards().collect()),
-            Some(shard_selection) => {
+            ShardSelectorInternal::All | ShardSelectorInternal::Session { selector: None, .. } => {
+                Ok(self.all_shards().collect())
+            }
+            ShardSelectorInternal::Selected(selector)
+            | ShardSelectorInternal::Session {
+                selector: Some(selector),
+                ..
+            } => Ok(self
+                .select_shards(selector)?
+                .iter()
+                .filter_map(|shard_id| self.get_shard(shard_id))
+                .collect()),
+            ShardSelectorInternal::ShardId(shard_selection) => {
+                let shard_selection = *shard_selection;
                 let shard_opt = self.get_shard(&shard_selection);
                 let target_shard = match shard_opt {
                     None => {
@@ -198,7 +342,8 @@ impl ShardHolder {
                                 Some(temp) => temp,
                             }
                         }
-                        Shard::ReplicaSet(_) => todo!(),
+                        // Replicas receive transfers without temporary shards
+                        Shard::ReplicaSet(_) => shard,
                     },
                 };
                 Ok(vec![target_shard])
@@ -224,12 +369,16 @@ impl ShardHolder {
         self.shards.is_empty()
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn load_shards(
         &mut self,
         collection_path: &Path,
         collection_id: &CollectionId,
+        this_peer_id: PeerId,
         shared_collection_config: Arc<RwLock<CollectionConfig>>,
         channel_service: ChannelService,
+        on_replica_failure: OnPeerFailure,
+        resource_throttle: ResourceThrottle,
     ) {
         let shard_number = shared_collection_config
             .read()
@@ -252,6 +401,7 @@ impl ShardHolder {
                                     collection_id.clone(),
                                     &path,
                                     shared_collection_config.clone(),
+                                    resource_throttle.clone(),
                                 )
                                 .await,
                             ),
@@ -266,6 +416,23 @@ impl ShardHolder {
                         );
                         self.add_shard(shard_id, Shard::Remote(shard));
                     }
+                    ShardType::ReplicaSet => {
+                        let replica_set = ReplicaSet::load(
+                            shard_id,
+                            collection_id.clone(),
+                            this_peer_id,
+                            &path,
+                            shared_collection_config.clone(),
+                            channel_service.clone(),
+                            on_replica_failure.clone(),
+                            resource_throttle.clone(),
+                        )
+                        .await
+                        .unwrap_or_else(|err| {
+                            panic!("Can't load replica set at {}: {err}", path.display())
+                        });
+                        self.add_shard(shard_id, Shard::ReplicaSet(replica_set));
+                    }
                     ShardType::Temporary => {
                         let replaces_shard = self.add_temporary_shard(
                             shard_id,
@@ -274,6 +441,7 @@ impl ShardHolder {
                                 collection_id.clone(),
                                 &path,
                                 shared_collection_config.clone(),
+                                resource_throttle.clone(),
                             )
                             .await,
                         );
@@ -314,7 +482,16 @@ impl LockedShardHolder {
                     "Shard {} is not local on peer",
                     id
                 ))),
-                Shard::ReplicaSet(_) => todo!(),
+                Shard::ReplicaSet(replica_set) => {
+                    if replica_set.has_local_shard() {
+                        Ok(shard)
+                    } else {
+                        Err(CollectionError::bad_shard_selection(format!(
+                            "Shard {} has no replica on peer",
+                            id
+                        )))
+                    }
+                }
             },
         }
     }
@@ -369,4 +546,41 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_transfer_bookkeeping_under_read_lock() {
+        let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+        let shard_holder = ShardHolder::new(collection_dir.path(), HashRing::fair(100)).unwrap();
+        let locked_shard_holder = LockedShardHolder::new(shard_holder);
+        let transfer = ShardTransfer {
+            shard_id: 0,
+            from: 1,
+            to: 2,
+        };
+
+        // Readers are not blocked while transfers are registered
+        let reader = locked_shard_holder.read().await;
+        let holder = locked_shard_holder.read().await;
+        assert!(holder
+            .register_start_shard_transfer(transfer.clone())
+            .unwrap());
+        assert!(!holder
+            .register_start_shard_transfer(transfer.clone())
+            .unwrap());
+        assert!(reader.is_shard_transferred(0));
+        assert!(!reader.is_shard_transferred(1));
+
+        assert!(holder.register_finish_transfer(&transfer).unwrap());
+        assert!(reader.shard_transfers().is_empty());
+
+        holder
+            .set_shard_transfers(HashSet::from([transfer.clone()]))
+            .unwrap();
+        drop(holder);
+        drop(reader);
+
+        // Transfers are persisted
+        let shard_holder = ShardHolder::new(collection_dir.path(), HashRing::fair(100)).unwrap();
+        assert_eq!(shard_holder.shard_transfers(), HashSet::from([transfer]));
+    }
 }
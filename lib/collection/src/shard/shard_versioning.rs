@@ -105,6 +105,10 @@ pub async fn latest_shard_paths(
                     res.push((path, version, shard_config.r#type));
                     break; // We don't need older remote shards.
                 }
+                ShardType::ReplicaSet => {
+                    res.push((path, version, shard_config.r#type));
+                    break; // We don't need older replica sets.
+                }
                 ShardType::Temporary => {
                     if !seen_temp_shard {
                         res.push((path, version, shard_config.r#type));
@@ -4,12 +4,14 @@ use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::time::Duration;
 
+use tokio::runtime::Handle;
 use tokio::time::sleep;
 
 use crate::common::stoppable_task_async::{spawn_async_stoppable, StoppableAsyncTaskHandle};
 use crate::operations::types::{
     CollectionError, CollectionResult, CollectionStatus, OptimizersStatus,
 };
+use crate::resource_watchdog::ResourceThrottle;
 use crate::shard::forward_proxy_shard::ForwardProxyShard;
 use crate::shard::remote_shard::RemoteShard;
 use crate::shard::shard_config::ShardConfig;
@@ -27,24 +29,31 @@ const INDEXED_THRESHOLD: f64 = 0.85;
 const OPTIMIZATION_CHECK_INTERVALS: Duration = Duration::from_secs(10);
 const MAX_OPTIMIZATION_TIME: Duration = Duration::from_secs(60 * 30); // 30 minutes
 
+/// Replica sets are transferred from the local replica to the replica on `peer_id` directly,
+/// other shards are transferred through the forward proxy.
 async fn transfer_batches(
     shard_holder: Arc<LockedShardHolder>,
     shard_id: ShardId,
+    peer_id: PeerId,
     stopped: Arc<AtomicBool>,
+    resource_throttle: &ResourceThrottle,
 ) -> CollectionResult<()> {
     // Create payload indexes on the remote shard.
     {
         let shard_holder_guard = shard_holder.read().await;
-        let transferring_shard_opt = shard_holder_guard.get_shard(&shard_id);
-        if let Some(Shard::ForwardProxy(transferring_shard)) = transferring_shard_opt {
-            transferring_shard.transfer_indexes().await?;
-        } else {
-            // Forward proxy gone?!
-            // That would be a programming error.
-            return Err(CollectionError::service_error(format!(
-                "Shard {} is not a forward proxy shard",
-                shard_id
-            )));
+        match shard_holder_guard.get_shard(&shard_id) {
+            Some(Shard::ForwardProxy(transferring_shard)) => {
+                transferring_shard.transfer_indexes().await?
+            }
+            Some(Shard::ReplicaSet(replica_set)) => replica_set.transfer_indexes(peer_id).await?,
+            _ => {
+                // Forward proxy gone?!
+                // That would be a programming error.
+                return Err(CollectionError::service_error(format!(
+                    "Shard {} is not a forward proxy shard",
+                    shard_id
+                )));
+            }
         }
     }
 
@@ -57,23 +66,35 @@ async fn transfer_batches(
                 description: "Transfer cancelled".to_string(),
             });
         }
+        if resource_throttle.is_throttled() {
+            log::debug!("Transfer of shard {} paused by resource watchdog", shard_id);
+            resource_throttle.wait_until_unthrottled().await;
+            continue;
+        }
         let shard_holder_guard = shard_holder.read().await;
-        let transferring_shard_opt = shard_holder_guard.get_shard(&shard_id);
-        if let Some(Shard::ForwardProxy(transferring_shard)) = transferring_shard_opt {
-            offset = transferring_shard
-                .transfer_batch(offset, TRANSFER_BATCH_SIZE)
-                .await?;
-            if offset.is_none() {
-                // That was the last batch, all look good
-                break;
+        offset = match shard_holder_guard.get_shard(&shard_id) {
+            Some(Shard::ForwardProxy(transferring_shard)) => {
+                transferring_shard
+                    .transfer_batch(offset, TRANSFER_BATCH_SIZE)
+                    .await?
             }
-        } else {
-            // Forward proxy gone?!
-            // That would be a programming error.
-            return Err(CollectionError::service_error(format!(
-                "Shard {} is not a forward proxy shard",
-                shard_id
-            )));
+            Some(Shard::ReplicaSet(replica_set)) => {
+                replica_set
+                    .transfer_batch(peer_id, offset, TRANSFER_BATCH_SIZE)
+                    .await?
+            }
+            _ => {
+                // Forward proxy gone?!
+                // That would be a programming error.
+                return Err(CollectionError::service_error(format!(
+                    "Shard {} is not a forward proxy shard",
+                    shard_id
+                )));
+            }
+        };
+        if offset.is_none() {
+            // That was the last batch, all look good
+            break;
         }
     }
     Ok(())
@@ -241,6 +262,12 @@ pub async fn promote_proxy_to_remote_shard(
     let proxy_shard_opt = shard_holder_guard.remove_shard(shard_id);
     match proxy_shard_opt {
         Some(Shard::ForwardProxy(proxy_shard)) => {
+            // Updates, queued before the promotion, have to reach the remote shard first
+            if let Err(err) = proxy_shard.flush_forwarded_updates().await {
+                log::warn!(
+                    "Not all updates of shard {shard_id} were forwarded to peer {to}: {err}"
+                );
+            }
             let (mut original_shard, remote_shard) = proxy_shard.deconstruct();
             shard_holder_guard.add_shard(shard_id, Shard::Remote(remote_shard));
             drop(shard_holder_guard);
@@ -270,7 +297,27 @@ pub async fn transfer_shard(
     peer_id: PeerId,
     channel_service: ChannelService,
     stopped: Arc<AtomicBool>,
+    resource_throttle: &ResourceThrottle,
 ) -> CollectionResult<()> {
+    // Replica sets keep serving the shard from the local replica, and the receiving peer
+    // gets the data directly into its own replica, so neither proxy nor temporary shard is needed
+    let is_replica_set = matches!(
+        shard_holder.read().await.get_shard(&shard_id),
+        Some(Shard::ReplicaSet(_))
+    );
+    if is_replica_set {
+        transfer_batches(
+            shard_holder.clone(),
+            shard_id,
+            peer_id,
+            stopped.clone(),
+            resource_throttle,
+        )
+        .await?;
+        return validate_indexing_progress(shard_holder, shard_id, collection_id, peer_id, stopped)
+            .await;
+    }
+
     // Initiate shard on a remote peer
     let remote_shard = RemoteShard::new(shard_id, collection_id.clone(), peer_id, channel_service);
 
@@ -305,7 +352,14 @@ pub async fn transfer_shard(
         }
     };
     // Transfer contents batch by batch
-    transfer_batches(shard_holder.clone(), shard_id, stopped.clone()).await?;
+    transfer_batches(
+        shard_holder.clone(),
+        shard_id,
+        peer_id,
+        stopped.clone(),
+        resource_throttle,
+    )
+    .await?;
 
     // Validate that the new shard reached a certain level of indexing before promoting it to not slowdown the search requests
     validate_indexing_progress(shard_holder, shard_id, collection_id, peer_id, stopped).await
@@ -331,19 +385,20 @@ pub async fn validate_indexing_progress(
         let (local_info, remote_info) = {
             let shard_holder_guard = shard_holder.read().await;
 
-            let proxy_shard = match shard_holder_guard.get_shard(&shard_id) {
-                Some(Shard::ForwardProxy(forward_proxy)) => forward_proxy,
+            match shard_holder_guard.get_shard(&shard_id) {
+                Some(Shard::ForwardProxy(proxy_shard)) => {
+                    let local_info = proxy_shard.wrapped_shard.info().await?;
+                    let remote_info = proxy_shard.remote_shard.info().await?;
+                    (local_info, remote_info)
+                }
+                Some(Shard::ReplicaSet(replica_set)) => replica_set.transfer_info(peer_id).await?,
                 _ => {
                     return Err(CollectionError::service_error(format!(
                         "Proxy shard is gone: {}, {}",
                         shard_id, collection_id
                     )))
                 }
-            };
-
-            let local_info = proxy_shard.wrapped_shard.info().await?;
-            let remote_info = proxy_shard.remote_shard.info().await?;
-            (local_info, remote_info)
+            }
         };
 
         match remote_info.status {
@@ -394,11 +449,15 @@ pub async fn validate_indexing_progress(
     Ok(())
 }
 
+/// Spawn the transfer on the `runtime`, dedicated to transfers, so it doesn't compete with searches
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_transfer_task<T, F>(
+    runtime: &Handle,
     shards_holder: Arc<LockedShardHolder>,
     transfer: ShardTransfer,
     collection_id: CollectionId,
     channel_service: ChannelService,
+    resource_throttle: ResourceThrottle,
     on_finish: T,
     on_error: F,
 ) -> StoppableAsyncTaskHandle<bool>
@@ -406,7 +465,7 @@ where
     T: Future<Output = ()> + Send + 'static,
     F: Future<Output = ()> + Send + 'static,
 {
-    spawn_async_stoppable(move |stopped| async move {
+    spawn_async_stoppable(runtime, move |stopped| async move {
         let mut tries = MAX_RETRY_COUNT;
         let mut finished = false;
         while !finished && tries > 0 {
@@ -417,6 +476,7 @@ where
                 transfer.to,
                 channel_service.clone(),
                 stopped.clone(),
+                &resource_throttle,
             )
             .await;
             finished = match transfer_result {
@@ -20,6 +20,11 @@ pub enum ShardTelemetry {
     },
     Proxy {},
     ForwardProxy {},
+    ReplicaSet {
+        shard_id: ShardId,
+        local: Option<Box<ShardTelemetry>>,
+        remote: Vec<ShardTelemetry>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
@@ -116,6 +121,7 @@ impl Anonymize for CollectionConfig {
             hnsw_config: self.hnsw_config,
             optimizer_config: self.optimizer_config.clone(),
             wal_config: self.wal_config.clone(),
+            version: self.version,
         }
     }
 }
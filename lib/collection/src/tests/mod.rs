@@ -14,6 +14,7 @@ use crate::collection_manager::fixtures::{
     get_indexing_optimizer, get_merge_optimizer, random_segment,
 };
 use crate::collection_manager::holders::segment_holder::{LockedSegment, SegmentHolder, SegmentId};
+use crate::config::PeerMetadata;
 use crate::update_handler::{Optimizer, UpdateHandler};
 
 #[tokio::test]
@@ -140,3 +141,24 @@ fn check_version_upgrade() {
         &"0.4.2".parse().unwrap()
     ));
 }
+
+#[test]
+fn check_peer_version_compatibility() {
+    let peer = |version: Option<&str>| PeerMetadata {
+        version: version.map(|version| version.to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        peer(Some("0.10.2")).is_compatible_with("0.10.0"),
+        Some(true)
+    );
+    assert_eq!(
+        peer(Some("0.10.2")).is_compatible_with("0.11.0"),
+        Some(false)
+    );
+    assert_eq!(
+        peer(Some("1.10.0")).is_compatible_with("0.10.0"),
+        Some(false)
+    );
+    assert_eq!(peer(None).is_compatible_with("0.10.0"), None);
+}
@@ -1,11 +1,15 @@
 use std::num::{NonZeroU32, NonZeroU64};
+use std::sync::Arc;
 
 use segment::types::Distance;
 use tempfile::Builder;
+use tokio::runtime::Handle;
 
 use crate::collection::Collection;
 use crate::config::{CollectionConfig, CollectionParams, VectorParams, VectorsConfig, WalConfig};
-use crate::optimizers_builder::OptimizersConfig;
+use crate::operations::snapshot_ops::check_free_space;
+use crate::operations::snapshot_storage_ops::SnapshotStorage;
+use crate::optimizers_builder::{MergePolicy, OptimizersConfig};
 use crate::shard::collection_shard_distribution::CollectionShardDistribution;
 use crate::shard::replica_set::OnPeerFailure;
 use crate::shard::{ChannelService, Shard};
@@ -19,14 +23,15 @@ const TEST_OPTIMIZERS_CONFIG: OptimizersConfig = OptimizersConfig {
     indexing_threshold: 50_000,
     flush_interval_sec: 30,
     max_optimization_threads: 2,
+    max_segment_disk_size: None,
+    merge_policy: MergePolicy::Auto,
 };
 
 pub fn dummy_on_replica_failure() -> OnPeerFailure {
-    Box::new(move |_peer_id, _shard_id| Box::new(async {}))
+    Arc::new(move |_peer_id, _shard_id| Box::new(async {}))
 }
 
-#[tokio::test]
-async fn test_snapshot_collection() {
+fn test_collection_config() -> CollectionConfig {
     let wal_config = WalConfig {
         wal_capacity_mb: 1,
         wal_segments_ahead: 0,
@@ -36,18 +41,38 @@ async fn test_snapshot_collection() {
         vectors: VectorsConfig::Single(VectorParams {
             size: NonZeroU64::new(4).unwrap(),
             distance: Distance::Dot,
+            quantization_config: None,
+            on_disk: None,
+            multivector_config: None,
+            hnsw_config: None,
+            normalize: None,
         }),
+        sparse_vectors: None,
         shard_number: NonZeroU32::new(3).unwrap(),
         replication_factor: NonZeroU32::new(3).unwrap(),
+        read_fan_out_factor: None,
+        write_consistency: Default::default(),
+        read_consistency: Default::default(),
+        hash_ring: Default::default(),
         on_disk_payload: false,
+        max_response_payload_bytes: None,
+        payload_transformers: vec![],
+        ivf_config: None,
+        placement: Default::default(),
     };
 
-    let config = CollectionConfig {
+    CollectionConfig {
         params: collection_params,
         optimizer_config: TEST_OPTIMIZERS_CONFIG.clone(),
         wal_config,
         hnsw_config: Default::default(),
-    };
+        version: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_snapshot_collection() {
+    let config = test_collection_config();
 
     let snapshots_path = Builder::new().prefix("test_snapshots").tempdir().unwrap();
     let collection_dir = Builder::new().prefix("test_collection").tempdir().unwrap();
@@ -60,12 +85,15 @@ async fn test_snapshot_collection() {
 
     let mut collection = Collection::new(
         collection_name,
+        0,
         collection_dir.path(),
         snapshots_path.path(),
+        SnapshotStorage::LocalFs,
         &config,
         CollectionShardDistribution::new(vec![0, 1], vec![(2, 10000)]),
         ChannelService::default(),
         dummy_on_replica_failure(),
+        Default::default(),
     )
     .await
     .unwrap();
@@ -73,7 +101,7 @@ async fn test_snapshot_collection() {
     let snapshots_tmp_dir = collection_dir.path().join("snapshots_tmp");
     std::fs::create_dir_all(&snapshots_tmp_dir).unwrap();
     let snapshot_description = collection
-        .create_snapshot(&snapshots_tmp_dir)
+        .create_snapshot(&snapshots_tmp_dir, &Handle::current())
         .await
         .unwrap();
 
@@ -85,9 +113,13 @@ async fn test_snapshot_collection() {
 
     let mut recovered_collection = Collection::load(
         collection_name_rec,
+        0,
         recover_dir.path(),
         snapshots_path.path(),
+        SnapshotStorage::LocalFs,
         ChannelService::default(),
+        dummy_on_replica_failure(),
+        Default::default(),
     )
     .await;
 
@@ -105,3 +137,71 @@ async fn test_snapshot_collection() {
     collection.before_drop().await;
     recovered_collection.before_drop().await;
 }
+
+#[tokio::test]
+async fn test_restore_local_shards() {
+    let config = test_collection_config();
+
+    let snapshots_path = Builder::new().prefix("test_snapshots").tempdir().unwrap();
+    let collection_dir = Builder::new().prefix("test_collection").tempdir().unwrap();
+
+    let mut collection = Collection::new(
+        "test".to_string(),
+        0,
+        collection_dir.path(),
+        snapshots_path.path(),
+        SnapshotStorage::LocalFs,
+        &config,
+        CollectionShardDistribution::new(vec![0, 1], vec![(2, 10000)]),
+        ChannelService::default(),
+        dummy_on_replica_failure(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+
+    let snapshots_tmp_dir = collection_dir.path().join("snapshots_tmp");
+    std::fs::create_dir_all(&snapshots_tmp_dir).unwrap();
+    let snapshot_description = collection
+        .create_snapshot(&snapshots_tmp_dir, &Handle::current())
+        .await
+        .unwrap();
+    let metadata = snapshot_description.metadata.as_ref().unwrap();
+    assert_eq!(metadata.shards_count, 3);
+    assert_eq!(metadata.points_count, 0);
+    assert_eq!(metadata.collection_params, config.params);
+    let snapshot_path = snapshots_path.path().join(snapshot_description.name);
+
+    assert_eq!(Collection::snapshot_config(&snapshot_path).unwrap(), config);
+
+    let restored = collection
+        .restore_local_shards(&snapshot_path, &Handle::current())
+        .await
+        .unwrap();
+    assert_eq!(restored, vec![0, 1]);
+
+    {
+        let shards_holder = &collection.shards_holder.read().await;
+        assert!(matches!(shards_holder.get_shard(&0), Some(Shard::Local(_))));
+        assert!(matches!(shards_holder.get_shard(&1), Some(Shard::Local(_))));
+        assert!(matches!(
+            shards_holder.get_shard(&2),
+            Some(Shard::Remote(_))
+        ));
+    }
+
+    collection.before_drop().await;
+}
+
+#[test]
+fn test_check_free_space() {
+    let snapshots_tmp_dir = Builder::new()
+        .prefix("test_snapshots_tmp")
+        .tempdir()
+        .unwrap();
+
+    assert!(check_free_space(snapshots_tmp_dir.path(), 0).is_ok());
+    assert!(check_free_space(snapshots_tmp_dir.path(), u64::MAX).is_err());
+
+    assert_eq!(SnapshotStorage::LocalFs.staging_space(1024), 2048);
+}
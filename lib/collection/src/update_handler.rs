@@ -1,5 +1,6 @@
 use std::cmp::min;
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use itertools::Itertools;
@@ -17,8 +18,10 @@ use crate::collection_manager::collection_updater::CollectionUpdater;
 use crate::collection_manager::holders::segment_holder::LockedSegmentHolder;
 use crate::collection_manager::optimizers::segment_optimizer::SegmentOptimizer;
 use crate::common::stoppable_task::{spawn_stoppable, StoppableTaskHandle};
+use crate::operations::payload_transformers::PayloadTransformer;
 use crate::operations::types::{CollectionError, CollectionResult};
 use crate::operations::CollectionUpdateOperations;
+use crate::resource_watchdog::ResourceThrottle;
 use crate::wal::SerdeWal;
 
 pub const UPDATE_QUEUE_SIZE: usize = 100;
@@ -80,9 +83,14 @@ pub struct UpdateHandler {
     wal: Arc<ParkingMutex<SerdeWal<CollectionUpdateOperations>>>,
     optimization_handles: Arc<TokioMutex<Vec<StoppableTaskHandle<bool>>>>,
     max_optimization_threads: usize,
+    /// Chain of transformations applied to payloads of upserted points and set payloads
+    payload_transformers: Arc<Vec<PayloadTransformer>>,
+    /// Postpones optimizations while the node is short on resources
+    resource_throttle: ResourceThrottle,
 }
 
 impl UpdateHandler {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         optimizers: Arc<Vec<Arc<Optimizer>>>,
         runtime_handle: Handle,
@@ -90,6 +98,8 @@ impl UpdateHandler {
         wal: Arc<ParkingMutex<SerdeWal<CollectionUpdateOperations>>>,
         flush_interval_sec: u64,
         max_optimization_threads: usize,
+        payload_transformers: Vec<PayloadTransformer>,
+        resource_throttle: ResourceThrottle,
     ) -> UpdateHandler {
         UpdateHandler {
             optimizers,
@@ -103,6 +113,8 @@ impl UpdateHandler {
             flush_interval_sec,
             optimization_handles: Arc::new(TokioMutex::new(vec![])),
             max_optimization_threads,
+            payload_transformers: Arc::new(payload_transformers),
+            resource_throttle,
         }
     }
 
@@ -116,11 +128,14 @@ impl UpdateHandler {
             self.wal.clone(),
             self.optimization_handles.clone(),
             self.max_optimization_threads,
+            self.payload_transformers.clone(),
+            self.resource_throttle.clone(),
         )));
         self.update_worker = Some(self.runtime_handle.spawn(Self::update_worker_fn(
             update_receiver,
             tx,
             self.segments.clone(),
+            self.payload_transformers.clone(),
         )));
         let (flush_tx, flush_rx) = oneshot::channel();
         self.flush_worker = Some(self.runtime_handle.spawn(Self::flush_worker(
@@ -172,6 +187,7 @@ impl UpdateHandler {
     async fn try_recover(
         segments: LockedSegmentHolder,
         wal: Arc<ParkingMutex<SerdeWal<CollectionUpdateOperations>>>,
+        payload_transformers: &[PayloadTransformer],
     ) -> CollectionResult<usize> {
         // Try to re-apply everything starting from the first failed operation
         let first_failed_operation_option = segments.read().failed_operation.iter().cloned().min();
@@ -180,7 +196,7 @@ impl UpdateHandler {
             Some(first_failed_op) => {
                 let wal_lock = wal.lock();
                 for (op_num, operation) in wal_lock.read(first_failed_op) {
-                    CollectionUpdater::update(&segments, op_num, operation)?;
+                    CollectionUpdater::update(&segments, op_num, operation, payload_transformers)?;
                 }
             }
         };
@@ -271,6 +287,7 @@ impl UpdateHandler {
         handles.retain(|h| !h.is_finished())
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn optimization_worker_fn(
         optimizers: Arc<Vec<Arc<Optimizer>>>,
         sender: Sender<OptimizerSignal>,
@@ -279,7 +296,10 @@ impl UpdateHandler {
         wal: Arc<ParkingMutex<SerdeWal<CollectionUpdateOperations>>>,
         optimization_handles: Arc<TokioMutex<Vec<StoppableTaskHandle<bool>>>>,
         max_handles: usize,
+        payload_transformers: Arc<Vec<PayloadTransformer>>,
+        resource_throttle: ResourceThrottle,
     ) {
+        let throttle_waiter_active = Arc::new(AtomicBool::new(false));
         while let Some(signal) = receiver.recv().await {
             match signal {
                 OptimizerSignal::Nop | OptimizerSignal::Operation(_) => {
@@ -290,9 +310,25 @@ impl UpdateHandler {
                         handles.retain(|h| !h.is_finished());
                         continue;
                     }
+                    if resource_throttle.is_throttled() {
+                        // Node is short on resources, postpone new optimizations.
+                        // Re-trigger optimizers once the watchdog releases the throttle.
+                        if !throttle_waiter_active.swap(true, Ordering::Relaxed) {
+                            debug!("Optimizations postponed by resource watchdog");
+                            let sender = sender.clone();
+                            let throttle_waiter_active = throttle_waiter_active.clone();
+                            let resource_throttle = resource_throttle.clone();
+                            tokio::spawn(async move {
+                                resource_throttle.wait_until_unthrottled().await;
+                                throttle_waiter_active.store(false, Ordering::Relaxed);
+                                let _ = sender.try_send(OptimizerSignal::Nop);
+                            });
+                        }
+                        continue;
+                    }
                     // We skip the check for number of optimization handles here
                     // Because `Nop` usually means that we need to force the optimization
-                    if Self::try_recover(segments.clone(), wal.clone())
+                    if Self::try_recover(segments.clone(), wal.clone(), &payload_transformers)
                         .await
                         .is_err()
                     {
@@ -315,6 +351,7 @@ impl UpdateHandler {
         mut receiver: Receiver<UpdateSignal>,
         optimize_sender: Sender<OptimizerSignal>,
         segments: LockedSegmentHolder,
+        payload_transformers: Arc<Vec<PayloadTransformer>>,
     ) {
         while let Some(signal) = receiver.recv().await {
             match signal {
@@ -323,7 +360,12 @@ impl UpdateHandler {
                     operation,
                     sender,
                 }) => {
-                    let res = match CollectionUpdater::update(&segments, op_num, operation) {
+                    let res = match CollectionUpdater::update(
+                        &segments,
+                        op_num,
+                        operation,
+                        &payload_transformers,
+                    ) {
                         Ok(update_res) => optimize_sender
                             .send(OptimizerSignal::Operation(op_num))
                             .await
@@ -86,6 +86,16 @@ impl<'s, R: DeserializeOwned + Serialize + Debug> SerdeWal<R> {
         self.wal.num_entries()
     }
 
+    /// Sequence number of the oldest record, which is still stored in WAL
+    pub fn first_index(&self) -> u64 {
+        self.wal.first_index()
+    }
+
+    /// Sequence number of the last record written to WAL
+    pub fn last_index(&self) -> u64 {
+        (self.wal.first_index() + self.wal.num_entries()).saturating_sub(1)
+    }
+
     pub fn read(&'s self, start_from: u64) -> impl Iterator<Item = (u64, R)> + 's {
         let first_index = self.wal.first_index();
         let num_entries = self.wal.num_entries();
@@ -111,6 +121,18 @@ impl<'s, R: DeserializeOwned + Serialize + Debug> SerdeWal<R> {
             .prefix_truncate(until_index)
             .map_err(|err| WalError::TruncateWalError(format!("{:?}", err)))
     }
+
+    /// Remove records starting from `from_index`, so the next written record gets this number.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_index` - the oldest record sequence number to remove
+    ///
+    pub fn truncate(&mut self, from_index: u64) -> Result<()> {
+        self.wal
+            .truncate(from_index)
+            .map_err(|err| WalError::TruncateWalError(format!("{:?}", err)))
+    }
 }
 
 #[cfg(test)]
@@ -173,4 +195,29 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_wal_truncate() {
+        let dir = Builder::new().prefix("wal_test").tempdir().unwrap();
+        let wal_options = WalOptions {
+            segment_capacity: 1024 * 1024,
+            segment_queue_len: 0,
+        };
+
+        let mut serde_wal: SerdeWal<TestRecord> =
+            SerdeWal::new(dir.path().to_str().unwrap(), &wal_options).unwrap();
+        for data in 0..5 {
+            let record = TestRecord::Struct1(TestInternalStruct1 { data });
+            serde_wal.write(&record).unwrap();
+        }
+        assert_eq!(serde_wal.first_index(), 0);
+        assert_eq!(serde_wal.last_index(), 4);
+
+        serde_wal.truncate(3).unwrap();
+        assert_eq!(serde_wal.len(), 3);
+        assert_eq!(serde_wal.last_index(), 2);
+
+        let record = TestRecord::Struct1(TestInternalStruct1 { data: 10 });
+        assert_eq!(serde_wal.write(&record).unwrap(), 3);
+    }
 }
@@ -1,4 +1,6 @@
+use collection::collection::SEGMENT_EXPORTS_DIR;
 use collection::operations::point_ops::{Batch, PointInsertOperations, PointOperations};
+use collection::operations::shard_selector::ShardSelectorInternal;
 use collection::operations::types::ScrollRequest;
 use collection::operations::CollectionUpdateOperations;
 use itertools::Itertools;
@@ -39,7 +41,7 @@ async fn test_collection_reloading_with_shards(shard_number: u32) {
             })),
         );
         collection
-            .update_from_client(insert_points, true)
+            .update_from_client(insert_points, &ShardSelectorInternal::All, true)
             .await
             .unwrap();
         collection.before_drop().await;
@@ -52,7 +54,14 @@ async fn test_collection_reloading_with_shards(shard_number: u32) {
         &collection_path.join("snapshots"),
     )
     .await;
-    assert_eq!(collection.info(None).await.unwrap().vectors_count, 2);
+    assert_eq!(
+        collection
+            .info(&ShardSelectorInternal::All)
+            .await
+            .unwrap()
+            .vectors_count,
+        2
+    );
     collection.before_drop().await;
 }
 
@@ -74,7 +83,7 @@ async fn test_collection_payload_reloading_with_shards(shard_number: u32) {
             })),
         );
         collection
-            .update_from_client(insert_points, true)
+            .update_from_client(insert_points, &ShardSelectorInternal::All, true)
             .await
             .unwrap();
         collection.before_drop().await;
@@ -95,8 +104,9 @@ async fn test_collection_payload_reloading_with_shards(shard_number: u32) {
                 filter: None,
                 with_payload: Some(WithPayloadInterface::Bool(true)),
                 with_vector: true.into(),
+                max_payload_bytes: None,
             },
-            None,
+            &ShardSelectorInternal::All,
         )
         .await
         .unwrap();
@@ -142,7 +152,7 @@ async fn test_collection_payload_custom_payload_with_shards(shard_number: u32) {
             })),
         );
         collection
-            .update_from_client(insert_points, true)
+            .update_from_client(insert_points, &ShardSelectorInternal::All, true)
             .await
             .unwrap();
         collection.before_drop().await;
@@ -165,8 +175,9 @@ async fn test_collection_payload_custom_payload_with_shards(shard_number: u32) {
                 filter: None,
                 with_payload: Some(WithPayloadInterface::Fields(vec![String::from("k2")])),
                 with_vector: true.into(),
+                max_payload_bytes: None,
             },
-            None,
+            &ShardSelectorInternal::All,
         )
         .await
         .unwrap();
@@ -196,8 +207,9 @@ async fn test_collection_payload_custom_payload_with_shards(shard_number: u32) {
                 filter: None,
                 with_payload: Some(PayloadSelectorExclude::new(vec!["k1".to_string()]).into()),
                 with_vector: false.into(),
+                max_payload_bytes: None,
             },
-            None,
+            &ShardSelectorInternal::All,
         )
         .await
         .unwrap();
@@ -228,3 +240,95 @@ async fn test_collection_payload_custom_payload_with_shards(shard_number: u32) {
     }
     collection.before_drop().await;
 }
+
+#[tokio::test]
+async fn test_collection_segments_export_import() {
+    let source_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let target_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let tmp_dir = Builder::new().prefix("segments_tmp").tempdir().unwrap();
+
+    let mut source = simple_collection_fixture(source_dir.path(), 1).await;
+    let insert_points = CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+        PointInsertOperations::PointsBatch(Batch {
+            ids: vec![0, 1].into_iter().map(|x| x.into()).collect_vec(),
+            vectors: vec![vec![1.0, 0.0, 1.0, 1.0], vec![1.0, 0.0, 1.0, 0.0]].into(),
+            payloads: None,
+        }),
+    ));
+    source
+        .update_from_client(insert_points, &ShardSelectorInternal::All, true)
+        .await
+        .unwrap();
+
+    let exported = source.export_segments(0, tmp_dir.path()).await.unwrap();
+    assert!(!exported.is_empty());
+    assert!(source.export_segments(1, tmp_dir.path()).await.is_err());
+
+    let mut target = simple_collection_fixture(target_dir.path(), 1).await;
+    let target_exports_path = target_dir
+        .path()
+        .join("snapshots")
+        .join(SEGMENT_EXPORTS_DIR);
+    std::fs::create_dir_all(&target_exports_path).unwrap();
+    for export in &exported {
+        let export_path = source.get_segment_export_path(&export.name).await.unwrap();
+        std::fs::copy(export_path, target_exports_path.join(&export.name)).unwrap();
+        target.import_segment(0, &export.name).await.unwrap();
+    }
+    assert_eq!(
+        target
+            .info(&ShardSelectorInternal::All)
+            .await
+            .unwrap()
+            .vectors_count,
+        2
+    );
+
+    // Points of the imported segments already exist
+    let mut reimported = Vec::new();
+    for export in &exported {
+        reimported.push(target.import_segment(0, &export.name).await);
+    }
+    assert!(reimported.iter().any(|result| result.is_err()));
+    assert!(target.import_segment(0, "../missing.tar").await.is_err());
+
+    // Imported points can be updated
+    let update_points = CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+        PointInsertOperations::PointsBatch(Batch {
+            ids: vec![0.into()],
+            vectors: vec![vec![0.0, 1.0, 0.0, 1.0]].into(),
+            payloads: None,
+        }),
+    ));
+    target
+        .update_from_client(update_points, &ShardSelectorInternal::All, true)
+        .await
+        .unwrap();
+    assert_eq!(
+        target
+            .info(&ShardSelectorInternal::All)
+            .await
+            .unwrap()
+            .vectors_count,
+        2
+    );
+
+    source.before_drop().await;
+    target.before_drop().await;
+
+    let mut target = load_local_collection(
+        "test".to_string(),
+        target_dir.path(),
+        &target_dir.path().join("snapshots"),
+    )
+    .await;
+    assert_eq!(
+        target
+            .info(&ShardSelectorInternal::All)
+            .await
+            .unwrap()
+            .vectors_count,
+        2
+    );
+    target.before_drop().await;
+}
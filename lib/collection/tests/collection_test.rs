@@ -1,7 +1,9 @@
 use std::collections::HashSet;
 
+use collection::operations::config_diff::CollectionParamsDiff;
 use collection::operations::payload_ops::{PayloadOps, SetPayload};
 use collection::operations::point_ops::{Batch, PointOperations, PointStruct};
+use collection::operations::shard_selector::{ShardSelector, ShardSelectorInternal};
 use collection::operations::types::{
     CountRequest, PointRequest, RecommendRequest, ScrollRequest, SearchRequest, UpdateStatus,
 };
@@ -49,7 +51,9 @@ async fn test_collection_updater_with_shards(shard_number: u32) {
         .into(),
     );
 
-    let insert_result = collection.update_from_client(insert_points, true).await;
+    let insert_result = collection
+        .update_from_client(insert_points, &ShardSelectorInternal::All, true)
+        .await;
 
     match insert_result {
         Ok(res) => {
@@ -67,10 +71,15 @@ async fn test_collection_updater_with_shards(shard_number: u32) {
         limit: 3,
         offset: 0,
         score_threshold: None,
+        max_payload_bytes: None,
     };
 
     let search_res = collection
-        .search(search_request, &Handle::current(), None)
+        .search(
+            search_request,
+            &Handle::current(),
+            &ShardSelectorInternal::All,
+        )
         .await;
 
     match search_res {
@@ -107,7 +116,9 @@ async fn test_collection_search_with_payload_and_vector_with_shards(shard_number
         .into(),
     );
 
-    let insert_result = collection.update_from_client(insert_points, true).await;
+    let insert_result = collection
+        .update_from_client(insert_points, &ShardSelectorInternal::All, true)
+        .await;
 
     match insert_result {
         Ok(res) => {
@@ -125,10 +136,15 @@ async fn test_collection_search_with_payload_and_vector_with_shards(shard_number
         limit: 3,
         offset: 0,
         score_threshold: None,
+        max_payload_bytes: None,
     };
 
     let search_res = collection
-        .search(search_request, &Handle::current(), None)
+        .search(
+            search_request,
+            &Handle::current(),
+            &ShardSelectorInternal::All,
+        )
         .await;
 
     match search_res {
@@ -151,12 +167,16 @@ async fn test_collection_search_with_payload_and_vector_with_shards(shard_number
             range: None,
             geo_bounding_box: None,
             geo_radius: None,
+            geo_polygon: None,
             values_count: None,
         }))),
         exact: true,
     };
 
-    let count_res = collection.count(count_request, None).await.unwrap();
+    let count_res = collection
+        .count(count_request, &ShardSelectorInternal::All)
+        .await
+        .unwrap();
     assert_eq!(count_res.count, 1);
 
     collection.before_drop().await;
@@ -194,7 +214,7 @@ async fn test_collection_loading_with_shards(shard_number: u32) {
         );
 
         collection
-            .update_from_client(insert_points, true)
+            .update_from_client(insert_points, &ShardSelectorInternal::All, true)
             .await
             .unwrap();
 
@@ -207,7 +227,7 @@ async fn test_collection_loading_with_shards(shard_number: u32) {
             }));
 
         collection
-            .update_from_client(assign_payload, true)
+            .update_from_client(assign_payload, &ShardSelectorInternal::All, true)
             .await
             .unwrap();
         collection.before_drop().await;
@@ -224,8 +244,12 @@ async fn test_collection_loading_with_shards(shard_number: u32) {
         ids: vec![1.into(), 2.into()],
         with_payload: Some(WithPayloadInterface::Bool(true)),
         with_vector: true.into(),
+        max_payload_bytes: None,
     };
-    let retrieved = loaded_collection.retrieve(request, None).await.unwrap();
+    let retrieved = loaded_collection
+        .retrieve(request, &ShardSelectorInternal::All)
+        .await
+        .unwrap();
 
     assert_eq!(retrieved.len(), 2);
 
@@ -321,7 +345,7 @@ async fn test_recommendation_api_with_shards(shard_number: u32) {
     );
 
     collection
-        .update_from_client(insert_points, true)
+        .update_from_client(insert_points, &ShardSelectorInternal::All, true)
         .await
         .unwrap();
     let result = collection
@@ -337,6 +361,7 @@ async fn test_recommendation_api_with_shards(shard_number: u32) {
                 with_vector: None,
                 score_threshold: None,
                 using: None,
+                max_payload_bytes: None,
             },
             &Handle::current(),
             None,
@@ -384,7 +409,7 @@ async fn test_read_api_with_shards(shard_number: u32) {
     ));
 
     collection
-        .update_from_client(insert_points, true)
+        .update_from_client(insert_points, &ShardSelectorInternal::All, true)
         .await
         .unwrap();
 
@@ -396,8 +421,9 @@ async fn test_read_api_with_shards(shard_number: u32) {
                 filter: None,
                 with_payload: Some(WithPayloadInterface::Bool(true)),
                 with_vector: false.into(),
+                max_payload_bytes: None,
             },
-            None,
+            &ShardSelectorInternal::All,
         )
         .await
         .unwrap();
@@ -437,7 +463,9 @@ async fn test_collection_delete_points_by_filter_with_shards(shard_number: u32)
         .into(),
     );
 
-    let insert_result = collection.update_from_client(insert_points, true).await;
+    let insert_result = collection
+        .update_from_client(insert_points, &ShardSelectorInternal::All, true)
+        .await;
 
     match insert_result {
         Ok(res) => {
@@ -458,7 +486,9 @@ async fn test_collection_delete_points_by_filter_with_shards(shard_number: u32)
         PointOperations::DeletePointsByFilter(delete_filter),
     );
 
-    let delete_result = collection.update_from_client(delete_points, true).await;
+    let delete_result = collection
+        .update_from_client(delete_points, &ShardSelectorInternal::All, true)
+        .await;
 
     match delete_result {
         Ok(res) => {
@@ -475,8 +505,9 @@ async fn test_collection_delete_points_by_filter_with_shards(shard_number: u32)
                 filter: None,
                 with_payload: Some(WithPayloadInterface::Bool(false)),
                 with_vector: false.into(),
+                max_payload_bytes: None,
             },
-            None,
+            &ShardSelectorInternal::All,
         )
         .await
         .unwrap();
@@ -518,7 +549,7 @@ async fn test_promote_temporary_shards() {
     ));
 
     collection
-        .update_from_client(insert_points, true)
+        .update_from_client(insert_points, &ShardSelectorInternal::All, true)
         .await
         .unwrap();
 
@@ -528,11 +559,12 @@ async fn test_promote_temporary_shards() {
         filter: None,
         with_payload: Some(WithPayloadInterface::Bool(true)),
         with_vector: false.into(),
+        max_payload_bytes: None,
     };
 
     // validate collection non empty
     let result = collection
-        .scroll_by(scroll_request.clone(), None)
+        .scroll_by(scroll_request.clone(), &ShardSelectorInternal::All)
         .await
         .unwrap();
     assert_eq!(result.points.len(), 2);
@@ -542,7 +574,7 @@ async fn test_promote_temporary_shards() {
 
     // validate collection non empty
     let result = collection
-        .scroll_by(scroll_request.clone(), None)
+        .scroll_by(scroll_request.clone(), &ShardSelectorInternal::All)
         .await
         .unwrap();
     assert_eq!(result.points.len(), 2);
@@ -557,8 +589,217 @@ async fn test_promote_temporary_shards() {
         .unwrap();
 
     // validate collection is empty now
-    let result = collection.scroll_by(scroll_request, None).await.unwrap();
+    let result = collection
+        .scroll_by(scroll_request, &ShardSelectorInternal::All)
+        .await
+        .unwrap();
     assert_eq!(result.points.len(), 0);
 
     collection.before_drop().await;
 }
+
+#[tokio::test]
+async fn test_collection_id_order_scroll() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let mut collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let num_points = 100;
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..num_points).map(|x| x.into()).collect_vec(),
+            vectors: (0..num_points)
+                .map(|x| vec![x as f32, 0.0, 1.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, &ShardSelectorInternal::All, true)
+        .await
+        .unwrap();
+
+    // Pages are larger than the pages read from each shard, so shards are read several times
+    let mut scrolled_ids = vec![];
+    let mut offset = None;
+    loop {
+        let result = collection
+            .scroll_by(
+                ScrollRequest {
+                    offset,
+                    limit: Some(40),
+                    filter: None,
+                    with_payload: Some(WithPayloadInterface::Bool(false)),
+                    with_vector: false.into(),
+                    max_payload_bytes: None,
+                },
+                &ShardSelectorInternal::All,
+            )
+            .await
+            .unwrap();
+        scrolled_ids.extend(result.points.iter().map(|point| point.id));
+        offset = result.next_page_offset;
+        if offset.is_none() {
+            break;
+        }
+    }
+
+    let expected_ids: Vec<PointIdType> = (0..num_points).map(|x: u64| x.into()).collect();
+    assert_eq!(scrolled_ids, expected_ids);
+
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_cleanup_shard() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let mut collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    // Put all points into the first shard, regardless of the hash ring
+    let num_points = 20;
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..num_points).map(|x| x.into()).collect_vec(),
+            vectors: (0..num_points)
+                .map(|x| vec![x as f32, 0.0, 1.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_peer(insert_points, 0, true)
+        .await
+        .unwrap();
+
+    let mut progress_updates = 0;
+    let progress = collection
+        .cleanup_shard(0, 3, |_progress| progress_updates += 1)
+        .await
+        .unwrap();
+    assert!(progress.finished);
+    assert_eq!(progress.checked_points, num_points as usize);
+    assert!(progress.deleted_points > 0);
+    assert_eq!(progress_updates, 7);
+
+    let count_request = CountRequest {
+        filter: None,
+        exact: true,
+    };
+    let count_res = collection
+        .count(count_request, &ShardSelectorInternal::ShardId(0))
+        .await
+        .unwrap();
+    assert_eq!(
+        count_res.count,
+        num_points as usize - progress.deleted_points
+    );
+
+    // Only points of the shard are left
+    let progress = collection.cleanup_shard(0, 3, |_| {}).await.unwrap();
+    assert_eq!(progress.checked_points, count_res.count);
+    assert_eq!(progress.deleted_points, 0);
+
+    assert!(collection.cleanup_shard(N_SHARDS, 3, |_| {}).await.is_err());
+
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_shard_selector() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let mut collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    // All points of the tenant are stored in the shard of its key
+    let tenant = ShardSelectorInternal::Selected(ShardSelector::ShardKeys {
+        shard_keys: vec!["tenant".to_string()],
+    });
+    let num_points = 10;
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..num_points).map(|x| x.into()).collect_vec(),
+            vectors: (0..num_points)
+                .map(|x| vec![x as f32, 0.0, 1.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points.clone(), &tenant, true)
+        .await
+        .unwrap();
+
+    let count_request = || CountRequest {
+        filter: None,
+        exact: true,
+    };
+    let tenant_count = collection.count(count_request(), &tenant).await.unwrap();
+    assert_eq!(tenant_count.count, num_points as usize);
+    let total_count = collection
+        .count(count_request(), &ShardSelectorInternal::All)
+        .await
+        .unwrap();
+    assert_eq!(total_count.count, num_points as usize);
+
+    // Points can't be upserted into several shards at once
+    let all_shards = ShardSelectorInternal::Selected(ShardSelector::ShardIds {
+        shard_ids: (0..N_SHARDS).collect(),
+    });
+    assert!(collection
+        .update_from_client(insert_points, &all_shards, true)
+        .await
+        .is_err());
+
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_config_version_conflict() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let mut collection = simple_collection_fixture(collection_dir.path(), 1).await;
+    assert_eq!(collection.config().await.version, 0);
+
+    let params_diff = CollectionParamsDiff {
+        replication_factor: None,
+        read_fan_out_factor: Some(1),
+        read_consistency: None,
+    };
+    assert_eq!(
+        collection
+            .update_config_from_diff(None, None, None)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        collection
+            .update_config_from_diff(Some(1), None, Some(params_diff))
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        collection.config().await.params.read_fan_out_factor,
+        Some(1)
+    );
+
+    // Update, which is based on an outdated config, is rejected and not applied
+    let outdated_diff = CollectionParamsDiff {
+        replication_factor: None,
+        read_fan_out_factor: Some(2),
+        read_consistency: None,
+    };
+    assert!(collection
+        .update_config_from_diff(Some(1), None, Some(outdated_diff))
+        .await
+        .is_err());
+    let config = collection.config().await;
+    assert_eq!(config.version, 2);
+    assert_eq!(config.params.read_fan_out_factor, Some(1));
+
+    collection.before_drop().await;
+}
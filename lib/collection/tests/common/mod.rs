@@ -2,15 +2,36 @@
 
 use std::num::{NonZeroU32, NonZeroU64};
 use std::path::Path;
+use std::sync::Arc;
 
+use api::grpc::qdrant::point_id::PointIdOptions;
+use api::grpc::qdrant::points_internal_server::{PointsInternal, PointsInternalServer};
+use api::grpc::qdrant::{
+    BatchResult, ClearPayloadPointsInternal, CountPointsInternal, CountResponse,
+    CreateFieldIndexCollectionInternal, DeleteFieldIndexCollectionInternal,
+    DeletePayloadPointsInternal, DeletePointsInternal, GetPointsInternal, GetResponse, PointId,
+    PointsOperationResponse, RecommendPointsInternal, RecommendResponse, ScoredPoint,
+    ScrollPointsInternal, ScrollResponse, SearchBatchPointsInternal, SearchBatchResponse,
+    SearchPointsInternal, SearchResponse, SetPayloadPointsInternal, ShardChecksumInternal,
+    ShardChecksumResponse, SyncPointsInternal, UpsertPointsInternal,
+};
 use collection::collection::Collection;
+use collection::collection_state::ShardInfo;
 use collection::config::{CollectionConfig, CollectionParams, VectorParams, WalConfig};
-use collection::operations::types::CollectionError;
-use collection::optimizers_builder::OptimizersConfig;
+use collection::operations::point_ops::Batch;
+use collection::operations::shard_selector::ShardSelectorInternal;
+use collection::operations::snapshot_storage_ops::SnapshotStorage;
+use collection::operations::types::{CollectionError, SearchRequest};
+use collection::operations::CollectionUpdateOperations;
+use collection::optimizers_builder::{MergePolicy, OptimizersConfig};
 use collection::shard::collection_shard_distribution::CollectionShardDistribution;
-use collection::shard::replica_set::OnPeerFailure;
+use collection::shard::replica_set::{OnPeerFailure, ReadConsistency, ReplicaState};
 use collection::shard::{ChannelService, CollectionId};
-use segment::types::Distance;
+use segment::types::{Distance, PointIdType};
+use tokio::net::TcpListener;
+use tokio::runtime::Handle;
+use tonic::transport::{Server, Uri};
+use tonic::{Request, Response, Status};
 
 /// Test collections for this upper bound of shards.
 /// Testing with more shards is problematic due to `number of open files problem`
@@ -27,6 +48,8 @@ pub const TEST_OPTIMIZERS_CONFIG: OptimizersConfig = OptimizersConfig {
     indexing_threshold: 50_000,
     flush_interval_sec: 30,
     max_optimization_threads: 2,
+    max_segment_disk_size: None,
+    merge_policy: MergePolicy::Auto,
 };
 
 #[allow(dead_code)]
@@ -40,11 +63,25 @@ pub async fn simple_collection_fixture(collection_path: &Path, shard_number: u32
         vectors: VectorParams {
             size: NonZeroU64::new(4).unwrap(),
             distance: Distance::Dot,
+            quantization_config: None,
+            on_disk: None,
+            multivector_config: None,
+            hnsw_config: None,
+            normalize: None,
         }
         .into(),
+        sparse_vectors: None,
         shard_number: NonZeroU32::new(shard_number).expect("Shard number can not be zero"),
         replication_factor: NonZeroU32::new(1).unwrap(),
+        read_fan_out_factor: None,
+        write_consistency: Default::default(),
+        read_consistency: Default::default(),
+        hash_ring: Default::default(),
         on_disk_payload: false,
+        max_response_payload_bytes: None,
+        payload_transformers: vec![],
+        ivf_config: None,
+        placement: Default::default(),
     };
 
     let collection_config = CollectionConfig {
@@ -52,6 +89,7 @@ pub async fn simple_collection_fixture(collection_path: &Path, shard_number: u32
         optimizer_config: TEST_OPTIMIZERS_CONFIG.clone(),
         wal_config,
         hnsw_config: Default::default(),
+        version: 0,
     };
 
     let snapshot_path = collection_path.join("snapshots");
@@ -68,7 +106,7 @@ pub async fn simple_collection_fixture(collection_path: &Path, shard_number: u32
 }
 
 pub fn dummy_on_replica_failure() -> OnPeerFailure {
-    Box::new(move |_peer_id, _shard_id| Box::new(async {}))
+    Arc::new(move |_peer_id, _shard_id| Box::new(async {}))
 }
 
 /// Default to a collection with all the shards local
@@ -80,12 +118,15 @@ pub async fn new_local_collection(
 ) -> Result<Collection, CollectionError> {
     Collection::new(
         id,
+        0,
         path,
         snapshots_path,
+        SnapshotStorage::LocalFs,
         config,
         CollectionShardDistribution::all_local(Some(config.params.shard_number.into())),
         ChannelService::default(),
         dummy_on_replica_failure(),
+        Default::default(),
     )
     .await
 }
@@ -97,5 +138,293 @@ pub async fn load_local_collection(
     path: &Path,
     snapshots_path: &Path,
 ) -> Collection {
-    Collection::load(id, path, snapshots_path, ChannelService::default()).await
+    Collection::load(
+        id,
+        0,
+        path,
+        snapshots_path,
+        SnapshotStorage::LocalFs,
+        ChannelService::default(),
+        dummy_on_replica_failure(),
+        Default::default(),
+    )
+    .await
+}
+
+/// Internal points service of a remote peer, which finds the same point with the same version
+/// in every search and supports no other operations
+#[allow(dead_code)]
+pub struct MockPeer {
+    pub point_id: u64,
+    pub version: u64,
+}
+
+#[tonic::async_trait]
+impl PointsInternal for MockPeer {
+    async fn upsert(
+        &self,
+        _request: Request<UpsertPointsInternal>,
+    ) -> Result<Response<PointsOperationResponse>, Status> {
+        Err(Status::unimplemented("upsert"))
+    }
+
+    async fn sync(
+        &self,
+        _request: Request<SyncPointsInternal>,
+    ) -> Result<Response<PointsOperationResponse>, Status> {
+        Err(Status::unimplemented("sync"))
+    }
+
+    async fn delete(
+        &self,
+        _request: Request<DeletePointsInternal>,
+    ) -> Result<Response<PointsOperationResponse>, Status> {
+        Err(Status::unimplemented("delete"))
+    }
+
+    async fn set_payload(
+        &self,
+        _request: Request<SetPayloadPointsInternal>,
+    ) -> Result<Response<PointsOperationResponse>, Status> {
+        Err(Status::unimplemented("set_payload"))
+    }
+
+    async fn delete_payload(
+        &self,
+        _request: Request<DeletePayloadPointsInternal>,
+    ) -> Result<Response<PointsOperationResponse>, Status> {
+        Err(Status::unimplemented("delete_payload"))
+    }
+
+    async fn clear_payload(
+        &self,
+        _request: Request<ClearPayloadPointsInternal>,
+    ) -> Result<Response<PointsOperationResponse>, Status> {
+        Err(Status::unimplemented("clear_payload"))
+    }
+
+    async fn create_field_index(
+        &self,
+        _request: Request<CreateFieldIndexCollectionInternal>,
+    ) -> Result<Response<PointsOperationResponse>, Status> {
+        Err(Status::unimplemented("create_field_index"))
+    }
+
+    async fn delete_field_index(
+        &self,
+        _request: Request<DeleteFieldIndexCollectionInternal>,
+    ) -> Result<Response<PointsOperationResponse>, Status> {
+        Err(Status::unimplemented("delete_field_index"))
+    }
+
+    async fn search(
+        &self,
+        _request: Request<SearchPointsInternal>,
+    ) -> Result<Response<SearchResponse>, Status> {
+        Err(Status::unimplemented("search"))
+    }
+
+    async fn search_batch(
+        &self,
+        request: Request<SearchBatchPointsInternal>,
+    ) -> Result<Response<SearchBatchResponse>, Status> {
+        let result = request
+            .into_inner()
+            .search_points
+            .iter()
+            .map(|_| BatchResult {
+                result: vec![ScoredPoint {
+                    id: Some(PointId {
+                        point_id_options: Some(PointIdOptions::Num(self.point_id)),
+                    }),
+                    score: 1.0,
+                    version: self.version,
+                    ..Default::default()
+                }],
+            })
+            .collect();
+        Ok(Response::new(SearchBatchResponse { result, time: 0.0 }))
+    }
+
+    async fn scroll(
+        &self,
+        _request: Request<ScrollPointsInternal>,
+    ) -> Result<Response<ScrollResponse>, Status> {
+        Err(Status::unimplemented("scroll"))
+    }
+
+    async fn count(
+        &self,
+        _request: Request<CountPointsInternal>,
+    ) -> Result<Response<CountResponse>, Status> {
+        Err(Status::unimplemented("count"))
+    }
+
+    async fn recommend(
+        &self,
+        _request: Request<RecommendPointsInternal>,
+    ) -> Result<Response<RecommendResponse>, Status> {
+        Err(Status::unimplemented("recommend"))
+    }
+
+    async fn get(
+        &self,
+        _request: Request<GetPointsInternal>,
+    ) -> Result<Response<GetResponse>, Status> {
+        Err(Status::unimplemented("get"))
+    }
+
+    async fn checksum(
+        &self,
+        _request: Request<ShardChecksumInternal>,
+    ) -> Result<Response<ShardChecksumResponse>, Status> {
+        Err(Status::unimplemented("checksum"))
+    }
+}
+
+/// Serve `peer` on a free local port in background, returns the address of the peer
+#[allow(dead_code)]
+pub async fn start_mock_peer(peer: MockPeer) -> Uri {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let uri = format!("http://{}", listener.local_addr().unwrap())
+        .parse()
+        .unwrap();
+    let incoming = futures::stream::unfold(listener, |listener| async move {
+        let stream = listener.accept().await.map(|(stream, _)| stream);
+        Some((stream, listener))
+    });
+    tokio::spawn(
+        Server::builder()
+            .add_service(PointsInternalServer::new(peer))
+            .serve_with_incoming(incoming),
+    );
+    uri
+}
+
+#[allow(dead_code)]
+pub const THIS_PEER_ID: u64 = 1;
+#[allow(dead_code)]
+pub const REMOTE_PEER_ID: u64 = 2;
+
+/// Collection with a single shard, replicated to this peer and to a remote peer, which is served
+/// by `remote`
+#[allow(dead_code)]
+pub async fn replicated_collection(
+    collection_path: &Path,
+    read_consistency: ReadConsistency,
+    remote: MockPeer,
+) -> Collection {
+    let collection_params = CollectionParams {
+        vectors: VectorParams {
+            size: NonZeroU64::new(4).unwrap(),
+            distance: Distance::Dot,
+            quantization_config: None,
+            on_disk: None,
+            multivector_config: None,
+            hnsw_config: None,
+            normalize: None,
+        }
+        .into(),
+        sparse_vectors: None,
+        shard_number: NonZeroU32::new(1).unwrap(),
+        replication_factor: NonZeroU32::new(2).unwrap(),
+        read_fan_out_factor: None,
+        write_consistency: Default::default(),
+        read_consistency,
+        hash_ring: Default::default(),
+        on_disk_payload: false,
+        max_response_payload_bytes: None,
+        payload_transformers: vec![],
+        ivf_config: None,
+        placement: Default::default(),
+    };
+    let collection_config = CollectionConfig {
+        params: collection_params,
+        optimizer_config: TEST_OPTIMIZERS_CONFIG.clone(),
+        wal_config: WalConfig {
+            wal_capacity_mb: 1,
+            wal_segments_ahead: 0,
+        },
+        hnsw_config: Default::default(),
+        version: 0,
+    };
+
+    let channel_service = ChannelService::default();
+    channel_service
+        .id_to_address
+        .write()
+        .insert(REMOTE_PEER_ID, start_mock_peer(remote).await);
+
+    let collection = Collection::new(
+        "test".to_string(),
+        THIS_PEER_ID,
+        collection_path,
+        &collection_path.join("snapshots"),
+        SnapshotStorage::LocalFs,
+        &collection_config,
+        CollectionShardDistribution::all_local(Some(1)),
+        channel_service,
+        dummy_on_replica_failure(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+
+    // The local replica receives the point before the remote replica joins,
+    // so the remote replica has a different view of the shard
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![1.into()],
+            vectors: vec![vec![1.0, 1.0, 1.0, 1.0]].into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, &ShardSelectorInternal::All, true)
+        .await
+        .unwrap();
+
+    let mut state = collection.state(THIS_PEER_ID).await;
+    state.shards.insert(
+        0,
+        ShardInfo::ReplicaSet {
+            replicas: [
+                (THIS_PEER_ID, ReplicaState::Active),
+                (REMOTE_PEER_ID, ReplicaState::Active),
+            ]
+            .into_iter()
+            .collect(),
+        },
+    );
+    collection
+        .apply_state(state, THIS_PEER_ID, |_| {})
+        .await
+        .unwrap();
+    collection
+}
+
+/// Id of the best point of a search in `collection`
+#[allow(dead_code)]
+pub async fn search_top_id(
+    collection: &Collection,
+    shard_selection: &ShardSelectorInternal,
+) -> PointIdType {
+    let search_request = SearchRequest {
+        vector: vec![1.0, 1.0, 1.0, 1.0].into(),
+        with_payload: None,
+        with_vector: None,
+        filter: None,
+        params: None,
+        limit: 1,
+        offset: 0,
+        score_threshold: None,
+        max_payload_bytes: None,
+    };
+    let result = collection
+        .search(search_request, &Handle::current(), shard_selection)
+        .await
+        .unwrap();
+    assert_eq!(result.len(), 1);
+    result[0].id
 }
@@ -0,0 +1,83 @@
+use collection::operations::point_ops::Batch;
+use collection::operations::shard_selector::{ShardSelector, ShardSelectorInternal};
+use collection::operations::CollectionUpdateOperations;
+use collection::shard::replica_set::{ReadConsistency, ReplicaState};
+use tempfile::Builder;
+
+use crate::common::{replicated_collection, search_top_id, MockPeer, REMOTE_PEER_ID, THIS_PEER_ID};
+
+mod common;
+
+#[tokio::test]
+async fn test_listener_answers_only_explicit_reads() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let remote = MockPeer {
+        point_id: 2,
+        version: 100,
+    };
+    // All replicas have to answer reads, but listeners are not counted
+    let mut collection =
+        replicated_collection(collection_dir.path(), ReadConsistency::All, remote).await;
+
+    // State change requested by a user, as it is applied by consensus
+    collection
+        .validate_replica_state_change(0, REMOTE_PEER_ID, ReplicaState::Listener)
+        .await
+        .unwrap();
+    collection
+        .set_shard_replica_state(0, REMOTE_PEER_ID, ReplicaState::Listener)
+        .await
+        .unwrap();
+
+    // The last active replica can't become a listener
+    assert!(collection
+        .validate_replica_state_change(0, THIS_PEER_ID, ReplicaState::Listener)
+        .await
+        .is_err());
+    // Other states are managed by the cluster
+    assert!(collection
+        .validate_replica_state_change(0, REMOTE_PEER_ID, ReplicaState::Dead)
+        .await
+        .is_err());
+
+    assert_eq!(
+        search_top_id(&collection, &ShardSelectorInternal::All).await,
+        1.into()
+    );
+
+    let listener = ShardSelectorInternal::Selected(ShardSelector::ShardReplica {
+        shard_id: 0,
+        peer_id: REMOTE_PEER_ID,
+    });
+    assert_eq!(search_top_id(&collection, &listener).await, 2.into());
+
+    // Updates are applied to all replicas, a single one can't be selected
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![3.into()],
+            vectors: vec![vec![1.0, 1.0, 1.0, 1.0]].into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    assert!(collection
+        .update_from_client(insert_points, &listener, true)
+        .await
+        .is_err());
+
+    // Listener becomes an active replica again
+    collection
+        .validate_replica_state_change(0, REMOTE_PEER_ID, ReplicaState::Active)
+        .await
+        .unwrap();
+    collection
+        .set_shard_replica_state(0, REMOTE_PEER_ID, ReplicaState::Active)
+        .await
+        .unwrap();
+    assert_eq!(
+        search_top_id(&collection, &ShardSelectorInternal::All).await,
+        2.into()
+    );
+
+    collection.before_drop().await;
+}
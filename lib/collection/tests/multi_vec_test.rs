@@ -9,6 +9,7 @@ use collection::config::{
     CollectionConfig, CollectionParams, VectorParams, VectorsConfig, WalConfig,
 };
 use collection::operations::point_ops::{PointInsertOperations, PointOperations, PointStruct};
+use collection::operations::shard_selector::ShardSelectorInternal;
 use collection::operations::types::{
     CollectionError, PointRequest, RecommendRequest, SearchRequest,
 };
@@ -41,10 +42,20 @@ pub async fn multi_vec_collection_fixture(collection_path: &Path, shard_number:
     let vector_params1 = VectorParams {
         size: NonZeroU64::new(4).unwrap(),
         distance: Distance::Dot,
+        quantization_config: None,
+        on_disk: None,
+        multivector_config: None,
+        hnsw_config: None,
+        normalize: None,
     };
     let vector_params2 = VectorParams {
         size: NonZeroU64::new(4).unwrap(),
         distance: Distance::Dot,
+        quantization_config: None,
+        on_disk: None,
+        multivector_config: None,
+        hnsw_config: None,
+        normalize: None,
     };
 
     let mut vectors_config = BTreeMap::new();
@@ -54,9 +65,18 @@ pub async fn multi_vec_collection_fixture(collection_path: &Path, shard_number:
 
     let collection_params = CollectionParams {
         vectors: VectorsConfig::Multi(vectors_config),
+        sparse_vectors: None,
         shard_number: NonZeroU32::new(shard_number).expect("Shard number can not be zero"),
         replication_factor: NonZeroU32::new(1).unwrap(),
+        read_fan_out_factor: None,
+        write_consistency: Default::default(),
+        read_consistency: Default::default(),
+        hash_ring: Default::default(),
         on_disk_payload: false,
+        max_response_payload_bytes: None,
+        payload_transformers: vec![],
+        ivf_config: None,
+        placement: Default::default(),
     };
 
     let collection_config = CollectionConfig {
@@ -64,6 +84,7 @@ pub async fn multi_vec_collection_fixture(collection_path: &Path, shard_number:
         optimizer_config: TEST_OPTIMIZERS_CONFIG.clone(),
         wal_config,
         hnsw_config: Default::default(),
+        version: 0,
     };
 
     let snapshot_path = collection_path.join("snapshots");
@@ -104,7 +125,7 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
         PointInsertOperations::PointsList(points),
     ));
     collection
-        .update_from_client(insert_points, true)
+        .update_from_client(insert_points, &ShardSelectorInternal::All, true)
         .await
         .unwrap();
 
@@ -123,10 +144,15 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
         with_vector: Some(true.into()),
         params: None,
         score_threshold: None,
+        max_payload_bytes: None,
     };
 
     let result = collection
-        .search(full_search_request, &Handle::current(), None)
+        .search(
+            full_search_request,
+            &Handle::current(),
+            &ShardSelectorInternal::All,
+        )
         .await
         .unwrap();
 
@@ -151,10 +177,15 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
         with_vector: Some(true.into()),
         params: None,
         score_threshold: None,
+        max_payload_bytes: None,
     };
 
     let result = collection
-        .search(failed_search_request, &Handle::current(), None)
+        .search(
+            failed_search_request,
+            &Handle::current(),
+            &ShardSelectorInternal::All,
+        )
         .await;
 
     assert!(matches!(result, Err(CollectionError::BadInput { .. })));
@@ -172,10 +203,15 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
         with_vector: Some(true.into()),
         params: None,
         score_threshold: None,
+        max_payload_bytes: None,
     };
 
     let result = collection
-        .search(full_search_request, &Handle::current(), None)
+        .search(
+            full_search_request,
+            &Handle::current(),
+            &ShardSelectorInternal::All,
+        )
         .await
         .unwrap();
 
@@ -195,8 +231,9 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
                 ids: vec![6.into()],
                 with_payload: Some(WithPayloadInterface::Bool(false)),
                 with_vector: WithVector::Selector(vec![VEC_NAME1.to_string()]),
+                max_payload_bytes: None,
             },
-            None,
+            &ShardSelectorInternal::All,
         )
         .await
         .unwrap();
@@ -223,6 +260,7 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
                 filter: None,
                 params: None,
                 using: None,
+                max_payload_bytes: None,
             },
             &Handle::current(),
             None,
@@ -250,6 +288,7 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
                 filter: None,
                 params: None,
                 using: Some(VEC_NAME1.to_string().into()),
+                max_payload_bytes: None,
             },
             &Handle::current(),
             None,
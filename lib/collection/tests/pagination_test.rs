@@ -1,4 +1,5 @@
 use collection::operations::point_ops::{PointInsertOperations, PointOperations, PointStruct};
+use collection::operations::shard_selector::ShardSelectorInternal;
 use collection::operations::types::SearchRequest;
 use collection::operations::CollectionUpdateOperations;
 use segment::types::WithPayloadInterface;
@@ -36,7 +37,7 @@ async fn test_collection_paginated_search_with_shards(shard_number: u32) {
         PointInsertOperations::PointsList(points),
     ));
     collection
-        .update_from_client(insert_points, true)
+        .update_from_client(insert_points, &ShardSelectorInternal::All, true)
         .await
         .unwrap();
 
@@ -51,10 +52,15 @@ async fn test_collection_paginated_search_with_shards(shard_number: u32) {
         with_vector: None,
         params: None,
         score_threshold: None,
+        max_payload_bytes: None,
     };
 
     let reference_result = collection
-        .search(full_search_request, &Handle::current(), None)
+        .search(
+            full_search_request,
+            &Handle::current(),
+            &ShardSelectorInternal::All,
+        )
         .await
         .unwrap();
 
@@ -74,10 +80,15 @@ async fn test_collection_paginated_search_with_shards(shard_number: u32) {
         with_vector: None,
         params: None,
         score_threshold: None,
+        max_payload_bytes: None,
     };
 
     let page_1_result = collection
-        .search(page_1_request, &Handle::current(), None)
+        .search(
+            page_1_request,
+            &Handle::current(),
+            &ShardSelectorInternal::All,
+        )
         .await
         .unwrap();
 
@@ -96,10 +107,15 @@ async fn test_collection_paginated_search_with_shards(shard_number: u32) {
         with_vector: None,
         params: None,
         score_threshold: None,
+        max_payload_bytes: None,
     };
 
     let page_9_result = collection
-        .search(page_9_request, &Handle::current(), None)
+        .search(
+            page_9_request,
+            &Handle::current(),
+            &ShardSelectorInternal::All,
+        )
         .await
         .unwrap();
 
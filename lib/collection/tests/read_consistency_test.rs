@@ -0,0 +1,47 @@
+use collection::operations::config_diff::CollectionParamsDiff;
+use collection::operations::shard_selector::ShardSelectorInternal;
+use collection::shard::replica_set::ReadConsistency;
+use tempfile::Builder;
+
+use crate::common::{replicated_collection, search_top_id, MockPeer};
+
+mod common;
+
+#[tokio::test]
+async fn test_quorum_read_prefers_latest_replica() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    // The remote replica has seen a later version of the shard, than the local one
+    let remote = MockPeer {
+        point_id: 2,
+        version: 100,
+    };
+    let mut collection =
+        replicated_collection(collection_dir.path(), ReadConsistency::Quorum, remote).await;
+
+    // Both replicas have to answer, the result of the latest one is returned
+    assert_eq!(
+        search_top_id(&collection, &ShardSelectorInternal::All).await,
+        2.into()
+    );
+
+    // A single read is answered by the local replica, even though it is stale
+    let params_diff = CollectionParamsDiff {
+        replication_factor: None,
+        read_fan_out_factor: None,
+        read_consistency: Some(ReadConsistency::Single),
+    };
+    collection
+        .update_config_from_diff(None, None, Some(params_diff))
+        .await
+        .unwrap();
+    assert_eq!(
+        collection.config().await.params.read_consistency,
+        ReadConsistency::Single
+    );
+    assert_eq!(
+        search_top_id(&collection, &ShardSelectorInternal::All).await,
+        1.into()
+    );
+
+    collection.before_drop().await;
+}
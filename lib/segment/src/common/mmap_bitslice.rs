@@ -0,0 +1,121 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use bitvec::prelude::BitSlice;
+use memmap::MmapMut;
+
+use crate::common::Flusher;
+use crate::entry::entry_point::OperationResult;
+
+const HEADER: &[u8; 4] = b"bits";
+const HEADER_SIZE: usize = HEADER.len();
+
+/// Capacity of a new file, in bytes
+const MIN_CAPACITY_BYTES: usize = 1024;
+
+/// Bit flags, persisted in a mem-mapped file.
+///
+/// Flags are changed in place, without any serialization, and read directly from the mapped pages.
+/// File at least doubles in size, once a flag beyond the current length is set. New flags are `false`.
+pub struct MmapBitSlice {
+    path: PathBuf,
+    mmap: MmapMut,
+}
+
+fn open_write(path: &Path) -> OperationResult<MmapMut> {
+    let file = OpenOptions::new().read(true).write(true).open(path)?;
+    Ok(unsafe { MmapMut::map_mut(&file)? })
+}
+
+impl MmapBitSlice {
+    pub fn open(path: &Path) -> OperationResult<Self> {
+        if !path.exists() {
+            let mut file = File::create(path)?;
+            file.write_all(HEADER)?;
+            file.set_len((HEADER_SIZE + MIN_CAPACITY_BYTES) as u64)?;
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            mmap: open_write(path)?,
+        })
+    }
+
+    fn bits_mut(&mut self) -> &mut BitSlice<u8> {
+        BitSlice::from_slice_mut(&mut self.mmap[HEADER_SIZE..])
+    }
+
+    /// Extend the file, if it can't hold `len` flags
+    pub fn ensure_len(&mut self, len: usize) -> OperationResult<()> {
+        if len <= self.len() {
+            return Ok(());
+        }
+        let current_bytes = self.mmap.len() - HEADER_SIZE;
+        let new_bytes = (current_bytes * 2).max((len + 7) / 8);
+        self.mmap.flush()?;
+        let file = OpenOptions::new().write(true).open(&self.path)?;
+        file.set_len((HEADER_SIZE + new_bytes) as u64)?;
+        self.mmap = open_write(&self.path)?;
+        Ok(())
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) -> OperationResult<()> {
+        self.ensure_len(index + 1)?;
+        self.bits_mut().set(index, value);
+        Ok(())
+    }
+
+    /// Dirty pages of the shared mapping are written back by syncing the file itself,
+    /// so the flusher doesn't need to hold the mapping
+    pub fn flusher(&self) -> Flusher {
+        let path = self.path.clone();
+        Box::new(move || {
+            File::open(path)?.sync_all()?;
+            Ok(())
+        })
+    }
+}
+
+impl Deref for MmapBitSlice {
+    type Target = BitSlice<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        BitSlice::from_slice(&self.mmap[HEADER_SIZE..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+
+    #[test]
+    fn test_mmap_bitslice() {
+        let dir = Builder::new().prefix("bitslice_dir").tempdir().unwrap();
+        let path = dir.path().join("flags.dat");
+
+        {
+            let mut flags = MmapBitSlice::open(&path).unwrap();
+            assert_eq!(flags.len(), MIN_CAPACITY_BYTES * 8);
+            assert_eq!(flags.count_ones(), 0);
+
+            flags.set(3, true).unwrap();
+            flags.set(100_000, true).unwrap();
+            assert!(flags.len() > 100_000);
+            flags.ensure_len(10).unwrap();
+            assert!(flags.len() > 100_000);
+            flags.flusher()().unwrap();
+        }
+
+        let mut flags = MmapBitSlice::open(&path).unwrap();
+        assert!(flags[3]);
+        assert!(flags[100_000]);
+        assert!(!flags[4]);
+        assert_eq!(flags.count_ones(), 2);
+
+        flags.set(3, false).unwrap();
+        assert_eq!(flags.count_ones(), 1);
+    }
+}
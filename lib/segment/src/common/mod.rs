@@ -1,6 +1,7 @@
 pub mod arc_atomic_ref_cell_iterator;
 pub mod error_logging;
 pub mod file_operations;
+pub mod mmap_bitslice;
 pub mod rocksdb_wrapper;
 pub mod utils;
 pub mod version;
@@ -20,6 +21,23 @@ pub fn check_vector_name(vector_name: &str, segment_config: &SegmentConfig) -> O
     Ok(())
 }
 
+/// Check that the vector length matches the config of the named vector.
+/// Multivectors may have any positive number of sub-vectors of the configured size
+pub fn check_vector_dim(
+    vector_name: &str,
+    vector_len: usize,
+    segment_config: &SegmentConfig,
+) -> OperationResult<()> {
+    let vector_config = &segment_config.vector_data[vector_name];
+    if !vector_config.is_valid_vector_len(vector_len) {
+        return Err(OperationError::WrongVector {
+            expected_dim: vector_config.size,
+            received_dim: vector_len,
+        });
+    }
+    Ok(())
+}
+
 pub fn check_vectors_set(
     vectors: &NamedVectors,
     segment_config: &SegmentConfig,
@@ -32,6 +50,15 @@ pub fn check_vectors_set(
         }
     }
 
+    // Sparse vectors are optional, only names are checked
+    for vector_name in vectors.sparse_keys() {
+        if !segment_config.sparse_vector_data.contains_key(vector_name) {
+            return Err(OperationError::VectorNameNotExists {
+                received_name: vector_name.to_string(),
+            });
+        }
+    }
+
     for vector_name in segment_config.vector_data.keys() {
         if !vectors.contains_key(vector_name) {
             return Err(OperationError::MissedVectorName {
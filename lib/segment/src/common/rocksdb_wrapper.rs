@@ -3,7 +3,7 @@ use std::sync::Arc;
 
 use parking_lot::RwLock;
 //use atomic_refcell::{AtomicRef, AtomicRefCell};
-use rocksdb::{ColumnFamily, LogLevel, Options, WriteOptions, DB};
+use rocksdb::{ColumnFamily, LogLevel, Options, WriteBatch, WriteOptions, DB};
 
 use crate::common::Flusher;
 //use crate::common::arc_rwlock_iterator::ArcRwLockIterator;
@@ -14,6 +14,7 @@ const DB_MAX_LOG_SIZE: usize = 1024 * 1024; // 1 mb
 const DB_MAX_OPEN_FILES: usize = 256;
 
 pub const DB_VECTOR_CF: &str = "vector";
+pub const DB_SPARSE_VECTOR_CF: &str = "sparse_vector";
 pub const DB_PAYLOAD_CF: &str = "payload";
 pub const DB_MAPPING_CF: &str = "mapping";
 pub const DB_VERSIONS_CF: &str = "version";
@@ -23,6 +24,13 @@ pub struct DatabaseColumnWrapper {
     pub column_name: String,
 }
 
+/// Buffers records of a column and writes them to RocksDB in batches
+pub struct DatabaseColumnBatchWriter {
+    db_wrapper: DatabaseColumnWrapper,
+    batch_size: usize,
+    records: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
 pub struct DatabaseColumnIterator<'a> {
     pub handle: &'a ColumnFamily,
     pub iter: rocksdb::DBRawIterator<'a>,
@@ -128,6 +136,58 @@ impl DatabaseColumnWrapper {
         Ok(())
     }
 
+    pub fn batch_writer(&self, batch_size: usize) -> DatabaseColumnBatchWriter {
+        DatabaseColumnBatchWriter {
+            db_wrapper: DatabaseColumnWrapper::new(self.database.clone(), &self.column_name),
+            batch_size,
+            records: Vec::with_capacity(batch_size),
+        }
+    }
+
+    /// Write all `records` with a single RocksDB write batch
+    pub fn put_batch<K, V>(&self, records: impl IntoIterator<Item = (K, V)>) -> OperationResult<()>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let db = self.database.read();
+        let cf_handle = self.get_column_family(&db)?;
+        let mut batch = WriteBatch::default();
+        for (key, value) in records {
+            batch.put_cf(cf_handle, key, value);
+        }
+        db.write_opt(batch, &Self::get_write_options())
+            .map_err(|err| {
+                OperationError::service_error(&format!("RocksDB write batch error: {}", err))
+            })?;
+        Ok(())
+    }
+
+    /// Apply all `updates` with a single RocksDB write batch, `None` value removes the key
+    pub fn update_batch<K, V>(
+        &self,
+        updates: impl IntoIterator<Item = (K, Option<V>)>,
+    ) -> OperationResult<()>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let db = self.database.read();
+        let cf_handle = self.get_column_family(&db)?;
+        let mut batch = WriteBatch::default();
+        for (key, value) in updates {
+            match value {
+                Some(value) => batch.put_cf(cf_handle, key, value),
+                None => batch.delete_cf(cf_handle, key),
+            }
+        }
+        db.write_opt(batch, &Self::get_write_options())
+            .map_err(|err| {
+                OperationError::service_error(&format!("RocksDB write batch error: {}", err))
+            })?;
+        Ok(())
+    }
+
     pub fn get_pinned<T, F>(&self, key: &[u8], f: F) -> OperationResult<Option<T>>
     where
         F: FnOnce(&[u8]) -> T,
@@ -155,6 +215,18 @@ impl DatabaseColumnWrapper {
         Ok(())
     }
 
+    /// Total size of SST files of the column, records which are not flushed yet are not counted
+    pub fn disk_usage_bytes(&self) -> OperationResult<usize> {
+        let db = self.database.read();
+        let cf_handle = self.get_column_family(&db)?;
+        let size = db
+            .property_int_value_cf(cf_handle, "rocksdb.total-sst-files-size")
+            .map_err(|err| {
+                OperationError::service_error(&format!("RocksDB property error: {}", err))
+            })?;
+        Ok(size.unwrap_or_default() as usize)
+    }
+
     pub fn lock_db(&self) -> LockedDatabaseColumnWrapper {
         LockedDatabaseColumnWrapper {
             guard: self.database.read(),
@@ -232,10 +304,57 @@ impl DatabaseColumnWrapper {
     }
 }
 
+impl DatabaseColumnBatchWriter {
+    pub fn recreate_column_family(&self) -> OperationResult<()> {
+        self.db_wrapper.recreate_column_family()
+    }
+
+    /// Buffer the record, writes the whole batch once it is full
+    pub fn put<K, V>(&mut self, key: K, value: V) -> OperationResult<()>
+    where
+        K: Into<Vec<u8>>,
+        V: Into<Vec<u8>>,
+    {
+        self.records.push((key.into(), value.into()));
+        if self.records.len() >= self.batch_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Write all buffered records
+    pub fn flush(&mut self) -> OperationResult<()> {
+        if self.records.is_empty() {
+            return Ok(());
+        }
+        self.db_wrapper.put_batch(self.records.drain(..))
+    }
+}
+
 impl<'a> LockedDatabaseColumnWrapper<'a> {
     pub fn iter(&self) -> OperationResult<DatabaseColumnIterator> {
         DatabaseColumnIterator::new(&self.guard, self.column_name)
     }
+
+    /// Returns the last key of the column which is strictly less than `key`
+    pub fn prev_key(&self, key: &[u8]) -> OperationResult<Option<Box<[u8]>>> {
+        let mut iter = self.iter()?.iter;
+        iter.seek_for_prev(key);
+        if iter.valid() && iter.key() == Some(key) {
+            iter.prev();
+        }
+        Ok(iter.key().map(Box::from))
+    }
+
+    /// Returns the first key of the column which is strictly greater than `key`
+    pub fn next_key(&self, key: &[u8]) -> OperationResult<Option<Box<[u8]>>> {
+        let mut iter = self.iter()?.iter;
+        iter.seek(key);
+        if iter.valid() && iter.key() == Some(key) {
+            iter.next();
+        }
+        Ok(iter.key().map(Box::from))
+    }
 }
 
 impl<'a> DatabaseColumnIterator<'a> {
@@ -254,6 +373,13 @@ impl<'a> DatabaseColumnIterator<'a> {
             just_seeked: true,
         })
     }
+
+    /// Moves the iterator to the first key which is greater or equal to `key`
+    pub fn seek(mut self, key: &[u8]) -> Self {
+        self.iter.seek(key);
+        self.just_seeked = true;
+        self
+    }
 }
 
 impl<'a> Iterator for DatabaseColumnIterator<'a> {
@@ -5,6 +5,9 @@ use serde_json::Value;
 use crate::data_types::named_vectors::NamedVectors;
 use crate::data_types::vectors::VectorElementType;
 
+/// Marks array of objects in the payload path, e.g. `reviews[].rating`
+pub const ARRAY_PATH_MARKER: &str = "[]";
+
 pub fn rev_range(a: usize, b: usize) -> impl Iterator<Item = usize> {
     (b + 1..=a).rev()
 }
@@ -26,6 +29,48 @@ pub fn get_value_from_json_map<'a>(
     }
 }
 
+/// Get values by path, which may go through arrays of objects.
+/// E.g. `reviews[].rating` refers to `rating` of every element of `reviews` array.
+pub fn get_values_from_json_map<'a>(
+    path: &str,
+    value: &'a serde_json::Map<String, Value>,
+) -> Vec<&'a Value> {
+    match path.split_once(ARRAY_PATH_MARKER) {
+        None => get_value_from_json_map(path, value).into_iter().collect(),
+        Some((array_path, rest)) => {
+            let rest = rest.strip_prefix('.').unwrap_or(rest);
+            match get_value_from_json_map(array_path, value) {
+                Some(Value::Array(elements)) => elements
+                    .iter()
+                    .flat_map(|element| match element {
+                        _ if rest.is_empty() => vec![element],
+                        Value::Object(map) => get_values_from_json_map(rest, map),
+                        _ => vec![],
+                    })
+                    .collect(),
+                _ => vec![],
+            }
+        }
+    }
+}
+
+pub fn get_value_mut_from_json_map<'a>(
+    path: &str,
+    value: &'a mut serde_json::Map<String, Value>,
+) -> Option<&'a mut Value> {
+    match path.split_once('.') {
+        Some((element, path)) => match value.get_mut(element) {
+            Some(Value::Object(map)) => get_value_mut_from_json_map(path, map),
+            Some(value) => match path.is_empty() {
+                true => Some(value),
+                false => None,
+            },
+            None => None,
+        },
+        None => value.get_mut(path),
+    }
+}
+
 pub fn remove_value_from_json_map(
     path: &str,
     value: &mut serde_json::Map<String, Value>,
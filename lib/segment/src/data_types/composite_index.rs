@@ -0,0 +1,42 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::types::PayloadKeyType;
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Hash, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CompositeIndexType {
+    Composite,
+}
+
+impl Default for CompositeIndexType {
+    fn default() -> Self {
+        CompositeIndexType::Composite
+    }
+}
+
+/// Index over a combination of payload keys.
+/// Points are indexed by tuples of keyword, integer or bool values of all the keys,
+/// so filter with `match` conditions on each of the keys is answered with a single lookup.
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Hash, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct CompositeIndexParams {
+    // Required for OpenAPI pattern matching
+    pub r#type: CompositeIndexType,
+    /// Payload keys, combined by the index. At least two keys are required.
+    pub keys: Vec<PayloadKeyType>,
+}
+
+impl CompositeIndexParams {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.keys.len() < 2 {
+            return Err("Composite index requires at least two keys".to_string());
+        }
+        for (i, key) in self.keys.iter().enumerate() {
+            if self.keys[..i].contains(key) {
+                return Err(format!("Key `{key}` is used twice in composite index"));
+            }
+        }
+        Ok(())
+    }
+}
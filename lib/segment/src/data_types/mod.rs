@@ -1,4 +1,7 @@
+pub mod composite_index;
 pub mod named_vectors;
+pub mod numeric_index;
+pub mod sparse_vector;
 pub mod text_index;
 pub mod tiny_map;
 pub mod vectors;
@@ -1,23 +1,31 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 
+use super::sparse_vector::SparseVector;
 use super::tiny_map;
-use super::vectors::{VectorElementType, DEFAULT_VECTOR_NAME};
+use super::vectors::{Vector, VectorElementType, DEFAULT_VECTOR_NAME};
 
 type CowKey<'a> = Cow<'a, str>;
 type CowValue<'a> = Cow<'a, [VectorElementType]>;
 type TinyMap<'a> = tiny_map::TinyMap<CowKey<'a>, CowValue<'a>>;
+type SparseTinyMap<'a> = tiny_map::TinyMap<CowKey<'a>, SparseVector>;
 
+/// Vectors of a single point by name.
+/// Sparse vectors are kept apart from dense ones, as they are stored and searched separately
 #[derive(Clone, PartialEq, Default)]
 pub struct NamedVectors<'a> {
     map: TinyMap<'a>,
+    sparse_map: SparseTinyMap<'a>,
 }
 
 impl<'a> NamedVectors<'a> {
     pub fn from_ref(key: &'a str, value: &'a [VectorElementType]) -> Self {
         let mut map = TinyMap::new();
         map.insert(Cow::Borrowed(key), Cow::Borrowed(value));
-        Self { map }
+        Self {
+            map,
+            sparse_map: SparseTinyMap::new(),
+        }
     }
 
     pub fn from<const N: usize>(arr: [(String, Vec<VectorElementType>); N]) -> Self {
@@ -26,25 +34,30 @@ impl<'a> NamedVectors<'a> {
                 .into_iter()
                 .map(|(k, v)| (CowKey::from(k), CowValue::from(v)))
                 .collect(),
+            sparse_map: SparseTinyMap::new(),
         }
     }
 
-    pub fn from_map(map: HashMap<String, Vec<VectorElementType>>) -> Self {
-        Self {
-            map: map
-                .into_iter()
-                .map(|(k, v)| (CowKey::from(k), CowValue::from(v)))
-                .collect(),
+    pub fn from_map(map: HashMap<String, Vector>) -> Self {
+        let mut vectors = Self::default();
+        for (name, vector) in map {
+            match vector {
+                Vector::Dense(vector) => vectors.insert(name, vector),
+                Vector::Sparse(vector) => vectors.insert_sparse(name, vector),
+            }
         }
+        vectors
     }
 
-    pub fn from_map_ref(map: &'a HashMap<String, Vec<VectorElementType>>) -> Self {
-        Self {
-            map: map
-                .iter()
-                .map(|(k, v)| (CowKey::from(k), CowValue::from(v)))
-                .collect(),
+    pub fn from_map_ref(map: &'a HashMap<String, Vector>) -> Self {
+        let mut vectors = Self::default();
+        for (name, vector) in map {
+            match vector {
+                Vector::Dense(vector) => vectors.insert_ref(name, vector),
+                Vector::Sparse(vector) => vectors.insert_sparse(name.clone(), vector.clone()),
+            }
         }
+        vectors
     }
 
     pub fn insert(&mut self, name: String, vector: Vec<VectorElementType>) {
@@ -57,18 +70,32 @@ impl<'a> NamedVectors<'a> {
             .insert(CowKey::Borrowed(name), CowValue::Borrowed(vector));
     }
 
+    pub fn insert_sparse(&mut self, name: String, vector: SparseVector) {
+        self.sparse_map.insert(CowKey::Owned(name), vector);
+    }
+
+    pub fn get_sparse(&self, name: &str) -> Option<&SparseVector> {
+        self.sparse_map.get(name)
+    }
+
+    /// Names of sparse vectors
+    pub fn sparse_keys(&self) -> impl Iterator<Item = &str> {
+        self.sparse_map.iter().map(|(k, _)| k.as_ref())
+    }
+
     pub fn contains_key(&self, key: &str) -> bool {
         self.map.contains_key(key)
     }
 
     pub fn len(&self) -> usize {
-        self.map.len()
+        self.map.len() + self.sparse_map.len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.map.is_empty()
+        self.map.is_empty() && self.sparse_map.is_empty()
     }
 
+    /// Names of dense vectors
     pub fn keys(&self) -> impl Iterator<Item = &str> {
         self.map.iter().map(|(k, _)| k.as_ref())
     }
@@ -79,18 +106,25 @@ impl<'a> NamedVectors<'a> {
             .map(|src| std::mem::take(src).into_owned())
     }
 
-    pub fn into_owned_map(self) -> HashMap<String, Vec<VectorElementType>> {
+    pub fn into_owned_map(self) -> HashMap<String, Vector> {
         self.map
             .into_iter()
-            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .map(|(k, v)| (k.into_owned(), Vector::Dense(v.into_owned())))
+            .chain(
+                self.sparse_map
+                    .into_iter()
+                    .map(|(k, v)| (k.into_owned(), Vector::Sparse(v))),
+            )
             .collect()
     }
 
+    /// Iterate over dense vectors
     pub fn iter(&self) -> impl Iterator<Item = (&str, &[VectorElementType])> {
         self.map.iter().map(|(k, v)| (k.as_ref(), v.as_ref()))
     }
 }
 
+/// Iterate over dense vectors
 impl<'a> IntoIterator for NamedVectors<'a> {
     type Item = (CowKey<'a>, CowValue<'a>);
 
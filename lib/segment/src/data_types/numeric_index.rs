@@ -0,0 +1,51 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::types::PayloadSchemaType;
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Hash, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NumericIndexType {
+    Integer,
+    Float,
+}
+
+impl NumericIndexType {
+    pub fn schema_type(&self) -> PayloadSchemaType {
+        match self {
+            NumericIndexType::Integer => PayloadSchemaType::Integer,
+            NumericIndexType::Float => PayloadSchemaType::Float,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Hash, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct NumericIndexParams {
+    // Required for OpenAPI pattern matching
+    pub r#type: NumericIndexType,
+    /// If true, sorted values of the range index and values of each point are read from disk
+    /// instead of being kept in RAM.
+    /// Saves memory for large collections at the cost of slower range filtering. Default: false
+    ///
+    /// Integer fields are also indexed for exact match, this index is kept in RAM regardless.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_disk: Option<bool>,
+    /// If true, the field is the principal dimension of the data, e.g. timestamp of time-partitioned points.
+    /// Segments are kept compact in terms of its values, so range filters by this field skip irrelevant segments.
+    /// Default: false
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_principal: Option<bool>,
+}
+
+impl NumericIndexParams {
+    pub fn is_on_disk(&self) -> bool {
+        self.on_disk.unwrap_or(false)
+    }
+
+    pub fn is_principal(&self) -> bool {
+        self.is_principal.unwrap_or(false)
+    }
+}
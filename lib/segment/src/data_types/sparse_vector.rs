@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::types::ScoreType;
+
+/// Index of a dimension of a sparse vector
+pub type DimId = u32;
+
+/// Sparse vector structure: only non-zero elements are stored,
+/// as pairs of dimension indices and values
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SparseVector {
+    /// Indices of non-zero dimensions
+    pub indices: Vec<DimId>,
+    /// Values of non-zero dimensions
+    pub values: Vec<f32>,
+}
+
+impl SparseVector {
+    pub fn new(indices: Vec<DimId>, values: Vec<f32>) -> Result<Self, String> {
+        let vector = SparseVector { indices, values };
+        vector.validate()?;
+        Ok(vector)
+    }
+
+    /// Check that indices and values match and there are no duplicated indices
+    pub fn validate(&self) -> Result<(), String> {
+        if self.indices.len() != self.values.len() {
+            return Err(format!(
+                "Sparse vector indices and values must have the same length, got {} and {}",
+                self.indices.len(),
+                self.values.len()
+            ));
+        }
+        let mut unique_indices = HashSet::with_capacity(self.indices.len());
+        for index in &self.indices {
+            if !unique_indices.insert(index) {
+                return Err(format!("Sparse vector has duplicated index {}", index));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Sort elements by dimension index, required for [`SparseVector::score`]
+    pub fn sort_by_indices(&mut self) {
+        let mut elements: Vec<_> = self
+            .indices
+            .iter()
+            .copied()
+            .zip(self.values.iter().copied())
+            .collect();
+        elements.sort_unstable_by_key(|(index, _)| *index);
+        (self.indices, self.values) = elements.into_iter().unzip();
+    }
+
+    /// Dot product of two vectors, both are expected to be sorted by indices
+    pub fn score(&self, other: &SparseVector) -> ScoreType {
+        let mut score = 0.0;
+        let mut i = 0;
+        let mut j = 0;
+        while i < self.indices.len() && j < other.indices.len() {
+            match self.indices[i].cmp(&other.indices[j]) {
+                std::cmp::Ordering::Less => i += 1,
+                std::cmp::Ordering::Greater => j += 1,
+                std::cmp::Ordering::Equal => {
+                    score += self.values[i] * other.values[j];
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparse_vector_validation() {
+        assert!(SparseVector::new(vec![1, 2, 3], vec![0.1, 0.2, 0.3]).is_ok());
+        assert!(SparseVector::new(vec![1, 2], vec![0.1, 0.2, 0.3]).is_err());
+        assert!(SparseVector::new(vec![1, 2, 1], vec![0.1, 0.2, 0.3]).is_err());
+    }
+
+    #[test]
+    fn test_sparse_vector_score() {
+        let mut a = SparseVector::new(vec![5, 1, 3], vec![1.0, 2.0, 3.0]).unwrap();
+        let mut b = SparseVector::new(vec![3, 4, 5], vec![2.0, 10.0, 0.5]).unwrap();
+        a.sort_by_indices();
+        b.sort_by_indices();
+        assert_eq!(a.indices, vec![1, 3, 5]);
+        assert_eq!(a.values, vec![2.0, 3.0, 1.0]);
+        assert_eq!(a.score(&b), 3.0 * 2.0 + 1.0 * 0.5);
+    }
+}
@@ -1,3 +1,6 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -13,6 +16,9 @@ pub enum TokenizerType {
     Prefix,
     Whitespace,
     Word,
+    /// Unicode-aware word segmentation with NFKC normalization.
+    /// Chinese and Japanese texts are split into overlapping character bigrams.
+    Multilingual,
 }
 
 impl Default for TokenizerType {
@@ -50,4 +56,88 @@ pub struct TextIndexParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     /// If true, lowercase all tokens. Default: true
     pub lowercase: Option<bool>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// If set, reduce tokens to their stems with the stemmer of the language.
+    /// Not supported by the prefix tokenizer.
+    pub stemmer: Option<Language>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// If set, exclude stopwords from indexing and queries.
+    /// Not supported by the prefix tokenizer.
+    pub stopwords: Option<StopwordsParams>,
+}
+
+impl TextIndexParams {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.tokenizer == TokenizerType::Prefix
+            && (self.stemmer.is_some() || self.stopwords.is_some())
+        {
+            return Err(
+                "Stemming and stopwords are not supported by the prefix tokenizer".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Languages with built-in stemmers and stopword lists
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Hash, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Language {
+    Dutch,
+    English,
+    French,
+    German,
+    Italian,
+    Portuguese,
+    Russian,
+    Spanish,
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dutch" => Ok(Language::Dutch),
+            "english" => Ok(Language::English),
+            "french" => Ok(Language::French),
+            "german" => Ok(Language::German),
+            "italian" => Ok(Language::Italian),
+            "portuguese" => Ok(Language::Portuguese),
+            "russian" => Ok(Language::Russian),
+            "spanish" => Ok(Language::Spanish),
+            _ => Err(format!("Unknown language: {s}")),
+        }
+    }
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Language::Dutch => "dutch",
+            Language::English => "english",
+            Language::French => "french",
+            Language::German => "german",
+            Language::Italian => "italian",
+            Language::Portuguese => "portuguese",
+            Language::Russian => "russian",
+            Language::Spanish => "spanish",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Hash, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct StopwordsParams {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Use the built-in stopword list of the language
+    pub language: Option<Language>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    /// Additional stopwords, compared case-insensitively
+    pub custom: Vec<String>,
 }
@@ -4,6 +4,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use super::named_vectors::NamedVectors;
+use super::sparse_vector::SparseVector;
 use crate::common::utils::transpose_map_into_named_vector;
 
 /// Type of vector element.
@@ -22,13 +23,44 @@ pub fn only_default_vector(vec: &[VectorElementType]) -> NamedVectors {
     NamedVectors::from_ref(DEFAULT_VECTOR_NAME, vec)
 }
 
-/// Full vector data per point separator with single and multiple vector modes
-#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+/// Dense or sparse vector
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+#[serde(untagged)]
+pub enum Vector {
+    Dense(VectorType),
+    Sparse(SparseVector),
+}
+
+impl Vector {
+    pub fn as_dense(&self) -> Option<&VectorType> {
+        match self {
+            Vector::Dense(vector) => Some(vector),
+            Vector::Sparse(_) => None,
+        }
+    }
+}
+
+impl From<VectorType> for Vector {
+    fn from(v: VectorType) -> Self {
+        Vector::Dense(v)
+    }
+}
+
+impl From<SparseVector> for Vector {
+    fn from(v: SparseVector) -> Self {
+        Vector::Sparse(v)
+    }
+}
+
+/// Full vector data per point separator with single and multiple vector modes.
+/// Sparse vectors can only be specified in multiple vector mode
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 #[serde(untagged)]
 pub enum VectorStruct {
     Single(VectorType),
-    Multi(HashMap<String, VectorType>),
+    Multi(HashMap<String, Vector>),
 }
 
 impl From<VectorType> for VectorStruct {
@@ -54,6 +86,7 @@ impl<'a> From<NamedVectors<'a>> for VectorStruct {
 }
 
 impl VectorStruct {
+    /// Get dense vector by name
     pub fn get(&self, name: &str) -> Option<&VectorType> {
         match self {
             VectorStruct::Single(v) => {
@@ -63,7 +96,7 @@ impl VectorStruct {
                     None
                 }
             }
-            VectorStruct::Multi(v) => v.get(name),
+            VectorStruct::Multi(v) => v.get(name).and_then(Vector::as_dense),
         }
     }
 
@@ -85,6 +118,16 @@ pub struct NamedVector {
     pub vector: VectorType,
 }
 
+/// Sparse vector data with name
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct NamedSparseVector {
+    /// Name of sparse vector data
+    pub name: String,
+    /// Sparse vector data
+    pub vector: SparseVector,
+}
+
 /// Vector data separator for named and unnamed modes
 /// Unanmed mode:
 ///
@@ -100,12 +143,22 @@ pub struct NamedVector {
 ///     "name": "image-embeddings"
 ///   }
 /// }
+///
+/// or named sparse mode:
+///
+/// {
+///   "vector": {
+///     "vector": {"indices": [6, 42], "values": [0.3, 0.5]},
+///     "name": "text-keywords"
+///   }
+/// }
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case")]
 #[serde(untagged)]
 pub enum NamedVectorStruct {
     Default(VectorType),
     Named(NamedVector),
+    Sparse(NamedSparseVector),
 }
 
 impl From<VectorType> for NamedVectorStruct {
@@ -114,36 +167,40 @@ impl From<VectorType> for NamedVectorStruct {
     }
 }
 
-impl From<NamedVectorStruct> for NamedVector {
-    fn from(v: NamedVectorStruct) -> Self {
-        match v {
-            NamedVectorStruct::Default(v) => NamedVector {
-                name: DEFAULT_VECTOR_NAME.to_owned(),
-                vector: v,
-            },
-            NamedVectorStruct::Named(v) => v,
-        }
-    }
-}
-
 impl From<NamedVector> for NamedVectorStruct {
     fn from(v: NamedVector) -> Self {
         NamedVectorStruct::Named(v)
     }
 }
 
+impl From<NamedSparseVector> for NamedVectorStruct {
+    fn from(v: NamedSparseVector) -> Self {
+        NamedVectorStruct::Sparse(v)
+    }
+}
+
 impl NamedVectorStruct {
     pub fn get_name(&self) -> &str {
         match self {
             NamedVectorStruct::Default(_) => DEFAULT_VECTOR_NAME,
             NamedVectorStruct::Named(v) => &v.name,
+            NamedVectorStruct::Sparse(v) => &v.name,
+        }
+    }
+
+    /// Get dense vector, if the struct holds one
+    pub fn get_vector(&self) -> Option<&VectorType> {
+        match self {
+            NamedVectorStruct::Default(v) => Some(v),
+            NamedVectorStruct::Named(v) => Some(&v.vector),
+            NamedVectorStruct::Sparse(_) => None,
         }
     }
 
-    pub fn get_vector(&self) -> &VectorType {
+    pub fn get_sparse_vector(&self) -> Option<&SparseVector> {
         match self {
-            NamedVectorStruct::Default(v) => v,
-            NamedVectorStruct::Named(v) => &v.vector,
+            NamedVectorStruct::Sparse(v) => Some(&v.vector),
+            _ => None,
         }
     }
 }
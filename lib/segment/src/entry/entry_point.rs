@@ -9,6 +9,7 @@ use thiserror::Error;
 
 use crate::common::file_operations::FileStorageError;
 use crate::data_types::named_vectors::NamedVectors;
+use crate::data_types::sparse_vector::SparseVector;
 use crate::data_types::vectors::VectorElementType;
 use crate::index::field_index::CardinalityEstimation;
 use crate::telemetry::SegmentTelemetry;
@@ -30,6 +31,8 @@ pub enum OperationError {
     VectorNameNotExists { received_name: String },
     #[error("Missed vector name error: {received_name}")]
     MissedVectorName { received_name: String },
+    #[error("Wrong sparse vector: {description}")]
+    WrongSparseVector { description: String },
     #[error("No point with id {missed_point_id} found")]
     PointIdError { missed_point_id: PointIdType },
     #[error("Payload type does not match with previously given for field {field_name}. Expected: {expected_type}")]
@@ -164,6 +167,17 @@ pub trait SegmentEntry {
         params: Option<&SearchParams>,
     ) -> OperationResult<Vec<ScoredPoint>>;
 
+    /// Search by sparse vector with the given name, scoring points by dot product
+    fn search_sparse(
+        &self,
+        vector_name: &str,
+        vector: &SparseVector,
+        with_payload: &WithPayload,
+        with_vector: &WithVector,
+        filter: Option<&Filter>,
+        top: usize,
+    ) -> OperationResult<Vec<ScoredPoint>>;
+
     #[allow(clippy::too_many_arguments)]
     fn search_batch(
         &self,
@@ -293,9 +307,23 @@ pub trait SegmentEntry {
         field_schema: Option<&PayloadFieldSchema>,
     ) -> OperationResult<bool>;
 
+    /// Drop the index of a payload field and build it again from the stored payload.
+    /// Uses the current schema of the index, unless the new one is given.
+    /// Fields which are not indexed are only indexed if the schema is given.
+    fn rebuild_field_index(
+        &mut self,
+        op_num: SeqNumberType,
+        key: PayloadKeyTypeRef,
+        field_schema: Option<&PayloadFieldSchema>,
+    ) -> OperationResult<bool>;
+
     /// Get indexed fields
     fn get_indexed_fields(&self) -> HashMap<PayloadKeyType, PayloadFieldSchema>;
 
+    /// Principal payload field of the segment along with the smallest and largest of its values.
+    /// `None` if there is no principal field or the segment has no values of it.
+    fn principal_range(&self) -> Option<(PayloadKeyType, (f64, f64))>;
+
     /// Checks if segment errored during last operations
     fn check_error(&self) -> Option<SegmentFailedState>;
 
@@ -24,7 +24,7 @@ impl FilterContext for FakeFilterContext {
 
 pub struct TestRawScorerProducer<TMetric: Metric> {
     pub vectors: ChunkedVectors,
-    pub deleted: BitVec,
+    pub deleted: BitVec<u8>,
     pub metric: PhantomData<TMetric>,
 }
 
@@ -56,6 +56,7 @@ where
             metric: PhantomData,
             vectors: &self.vectors,
             deleted: &self.deleted,
+            norms: None,
         }
     }
 }
@@ -1,4 +1,5 @@
 mod id_tracker_base;
+pub mod points_bloom_filter;
 pub mod simple_id_tracker;
 
 pub use id_tracker_base::*;
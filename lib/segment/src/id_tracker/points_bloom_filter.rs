@@ -0,0 +1,98 @@
+use std::hash::{Hash, Hasher};
+
+use bitvec::prelude::BitVec;
+use seahash::SeaHasher;
+
+use crate::types::PointIdType;
+
+/// Number of bits allocated per expected point, gives ~1% of false positives
+const BITS_PER_POINT: usize = 10;
+/// Optimal number of hash functions for `BITS_PER_POINT`
+const NUM_HASHES: u64 = 7;
+/// Capacity of the filter for empty or small segments
+const MIN_CAPACITY: usize = 1024;
+
+/// Bloom filter over external ids of the points stored in a segment.
+///
+/// Allows to skip id tracker lookups for points, which are definitely not in the segment.
+/// Removed points are not cleared from the filter, so it is rebuilt from scratch
+/// once the number of inserted ids exceeds the capacity.
+#[derive(Debug, Clone)]
+pub struct PointsBloomFilter {
+    bits: BitVec,
+    capacity: usize,
+    inserted: usize,
+}
+
+impl PointsBloomFilter {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(MIN_CAPACITY);
+        PointsBloomFilter {
+            bits: BitVec::repeat(false, capacity * BITS_PER_POINT),
+            capacity,
+            inserted: 0,
+        }
+    }
+
+    /// Build filter for the given points, leaving space for the same amount of new points
+    pub fn from_points(points: impl Iterator<Item = PointIdType>, points_count: usize) -> Self {
+        let mut filter = Self::new(points_count * 2);
+        for point_id in points {
+            filter.insert(point_id);
+        }
+        filter
+    }
+
+    /// Positions of the point in the bit array, computed with double hashing
+    fn bit_positions(point_id: PointIdType, num_bits: usize) -> impl Iterator<Item = usize> {
+        let mut hasher = SeaHasher::new();
+        point_id.hash(&mut hasher);
+        let hash = hasher.finish();
+        let h1 = hash & 0xFFFF_FFFF;
+        let h2 = (hash >> 32) | 1;
+        let num_bits = num_bits as u64;
+        (0..NUM_HASHES).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % num_bits) as usize)
+    }
+
+    pub fn insert(&mut self, point_id: PointIdType) {
+        for position in Self::bit_positions(point_id, self.bits.len()) {
+            self.bits.set(position, true);
+        }
+        self.inserted += 1;
+    }
+
+    /// Returns `false` only if the point was never inserted into the filter
+    pub fn may_contain(&self, point_id: PointIdType) -> bool {
+        Self::bit_positions(point_id, self.bits.len()).all(|position| self.bits[position])
+    }
+
+    /// Filter is saturated if more ids were inserted than it was designed for,
+    /// false positive rate grows quickly after that.
+    pub fn is_saturated(&self) -> bool {
+        self.inserted > self.capacity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_points_bloom_filter() {
+        let num_points = 5_000u64;
+        let filter = PointsBloomFilter::from_points(
+            (0..num_points).map(PointIdType::NumId),
+            num_points as usize,
+        );
+
+        for idx in 0..num_points {
+            assert!(filter.may_contain(PointIdType::NumId(idx)));
+        }
+
+        let false_positives = (num_points..num_points * 2)
+            .filter(|idx| filter.may_contain(PointIdType::NumId(*idx)))
+            .count();
+        assert!(false_positives < (num_points / 20) as usize);
+        assert!(!filter.is_saturated());
+    }
+}
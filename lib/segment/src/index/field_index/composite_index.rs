@@ -0,0 +1,271 @@
+use std::sync::Arc;
+
+use itertools::Itertools;
+use parking_lot::RwLock;
+use rocksdb::DB;
+use serde_json::Value;
+
+use crate::common::Flusher;
+use crate::entry::entry_point::OperationResult;
+use crate::index::field_index::map_index::{MapIndex, MapIndexBuilder};
+use crate::index::field_index::{FieldIndexBuilderTrait, PayloadFieldIndex, ValueIndexer};
+use crate::telemetry::PayloadIndexTelemetry;
+use crate::types::{
+    Condition, FieldCondition, Match, MatchValue, Payload, PayloadKeyType, PointOffsetType,
+    ValueVariants,
+};
+
+/// Lookup of a single tuple of values in a composite index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompositeMatch {
+    /// Name of the composite index
+    pub index: PayloadKeyType,
+    /// Encoded tuple of values of the index keys
+    pub tuple: String,
+}
+
+/// Index of points by combination of values of multiple payload keys.
+///
+/// Tuples of values are encoded into strings, so posting lists are kept in `MapIndex`.
+/// Point with multiple values of some key is indexed by every combination of the values.
+pub struct CompositeIndex {
+    keys: Vec<PayloadKeyType>,
+    map_index: MapIndex<String>,
+}
+
+impl CompositeIndex {
+    pub fn new(
+        db: Arc<RwLock<DB>>,
+        name: &str,
+        keys: Vec<PayloadKeyType>,
+        is_appendable: bool,
+    ) -> Self {
+        let storage_name = Self::storage_name(name);
+        let map_index = if is_appendable {
+            MapIndex::new(db, &storage_name)
+        } else {
+            MapIndex::new_immutable(db, &storage_name)
+        };
+        Self { keys, map_index }
+    }
+
+    fn storage_name(name: &str) -> String {
+        format!("{name}_composite")
+    }
+
+    /// Normalized value of a tuple element, only keyword, integer and bool values are indexed
+    fn tuple_value(value: &Value) -> Option<Value> {
+        match value {
+            Value::String(_) | Value::Bool(_) => Some(value.clone()),
+            Value::Number(number) => number.as_i64().map(Value::from),
+            _ => None,
+        }
+    }
+
+    fn key_values(payload: &Payload, key: &str) -> Vec<Value> {
+        match payload.get_path_value(key).as_deref() {
+            Some(Value::Array(values)) => values.iter().filter_map(Self::tuple_value).collect(),
+            Some(value) => Self::tuple_value(value).into_iter().collect(),
+            None => vec![],
+        }
+    }
+
+    fn encode_tuple(values: Vec<Value>) -> String {
+        Value::Array(values).to_string()
+    }
+
+    /// All tuples of values of the index keys in the payload.
+    /// Empty if some of the keys has no values.
+    fn payload_tuples(keys: &[PayloadKeyType], payload: &Payload) -> Vec<String> {
+        keys.iter()
+            .map(|key| Self::key_values(payload, key))
+            .multi_cartesian_product()
+            .map(Self::encode_tuple)
+            .unique()
+            .collect()
+    }
+
+    /// Value of the condition, which can be looked up in the index
+    fn match_value(condition: &FieldCondition) -> Option<Value> {
+        match condition {
+            FieldCondition {
+                r#match: Some(Match::Value(MatchValue { value })),
+                range: None,
+                geo_bounding_box: None,
+                geo_radius: None,
+                geo_polygon: None,
+                values_count: None,
+                ..
+            } => Some(match value {
+                ValueVariants::Keyword(keyword) => Value::String(keyword.clone()),
+                ValueVariants::Integer(integer) => Value::from(*integer),
+                ValueVariants::Bool(flag) => Value::Bool(*flag),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Find `must` conditions, which match exact values of all keys of the index.
+    /// Returns the tuple to look up and positions of the used conditions.
+    pub fn match_conditions(&self, conditions: &[Condition]) -> Option<(String, Vec<usize>)> {
+        let mut tuple = Vec::with_capacity(self.keys.len());
+        let mut positions = Vec::with_capacity(self.keys.len());
+        for key in &self.keys {
+            let (position, value) = conditions.iter().enumerate().find_map(
+                |(position, condition)| match condition {
+                    Condition::Field(field_condition) if &field_condition.key == key => {
+                        Self::match_value(field_condition).map(|value| (position, value))
+                    }
+                    _ => None,
+                },
+            )?;
+            tuple.push(value);
+            positions.push(position);
+        }
+        Some((Self::encode_tuple(tuple), positions))
+    }
+
+    pub fn points_count(&self, tuple: &str) -> usize {
+        self.map_index.match_cardinality(&tuple.to_owned()).exp
+    }
+
+    pub fn iter_points(&self, tuple: &str) -> Box<dyn Iterator<Item = PointOffsetType> + '_> {
+        self.map_index.get_iterator(&tuple.to_owned())
+    }
+
+    pub fn load(&mut self) -> OperationResult<bool> {
+        PayloadFieldIndex::load(&mut self.map_index)
+    }
+
+    pub fn clear(self) -> OperationResult<()> {
+        PayloadFieldIndex::clear(self.map_index)
+    }
+
+    pub fn flusher(&self) -> Flusher {
+        self.map_index.flusher()
+    }
+
+    pub fn add_point(&mut self, idx: PointOffsetType, payload: &Payload) -> OperationResult<()> {
+        let tuples = Self::payload_tuples(&self.keys, payload);
+        self.map_index.add_many(idx, tuples)
+    }
+
+    pub fn remove_point(&mut self, idx: PointOffsetType) -> OperationResult<()> {
+        ValueIndexer::<String>::remove_point(&mut self.map_index, idx)
+    }
+
+    pub fn get_telemetry_data(&self) -> PayloadIndexTelemetry {
+        PayloadIndexTelemetry {
+            index_type: "composite".to_string(),
+            ..self.map_index.get_telemetry_data()
+        }
+    }
+
+    /// Convert an empty index into the builder, which fills it with points
+    pub fn into_builder(self) -> CompositeIndexBuilder {
+        CompositeIndexBuilder {
+            keys: self.keys,
+            builder: MapIndexBuilder::new(self.map_index),
+        }
+    }
+}
+
+/// Builds composite index from a stream of point payloads, see `FieldIndexBuilderTrait`
+pub struct CompositeIndexBuilder {
+    keys: Vec<PayloadKeyType>,
+    builder: MapIndexBuilder<String>,
+}
+
+impl CompositeIndexBuilder {
+    pub fn init(&mut self) -> OperationResult<()> {
+        self.builder.init()
+    }
+
+    pub fn add_point(&mut self, id: PointOffsetType, payload: &Payload) -> OperationResult<()> {
+        let tuples = CompositeIndex::payload_tuples(&self.keys, payload);
+        self.builder.add_values(id, &tuples)
+    }
+
+    pub fn finalize(self) -> OperationResult<CompositeIndex> {
+        Ok(CompositeIndex {
+            keys: self.keys,
+            map_index: self.builder.finalize()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::common::rocksdb_wrapper::open_db_with_existing_cf;
+
+    #[test]
+    fn test_composite_tuples() {
+        let keys = vec!["tenant".to_string(), "status".to_string()];
+        let payload: Payload = json!({"tenant": 1, "status": ["new", "open", "new"]}).into();
+        let tuples = CompositeIndex::payload_tuples(&keys, &payload);
+        assert_eq!(tuples, vec![r#"[1,"new"]"#, r#"[1,"open"]"#]);
+
+        let payload: Payload = json!({"tenant": 1.5, "status": "new"}).into();
+        assert!(CompositeIndex::payload_tuples(&keys, &payload).is_empty());
+
+        let payload: Payload = json!({"status": "new"}).into();
+        assert!(CompositeIndex::payload_tuples(&keys, &payload).is_empty());
+    }
+
+    #[test]
+    fn test_composite_index() {
+        let tmp_dir = Builder::new().prefix("test_dir").tempdir().unwrap();
+        let keys = vec!["tenant".to_string(), "status".to_string()];
+        let payloads: Vec<Payload> = vec![
+            json!({"tenant": "a", "status": "new"}).into(),
+            json!({"tenant": "a", "status": "done"}).into(),
+            json!({"tenant": "b", "status": "new"}).into(),
+            json!({"tenant": "a", "status": ["new", "done"]}).into(),
+            json!({"tenant": "a"}).into(),
+        ];
+        let conditions = vec![
+            Condition::Field(FieldCondition::new_match(
+                "status".to_string(),
+                "new".to_string().into(),
+            )),
+            Condition::Field(FieldCondition::new_match(
+                "tenant".to_string(),
+                "a".to_string().into(),
+            )),
+        ];
+
+        {
+            let db = open_db_with_existing_cf(tmp_dir.path()).unwrap();
+            let index = CompositeIndex::new(db, "test", keys.clone(), true);
+            let mut builder = index.into_builder();
+            builder.init().unwrap();
+            for (idx, payload) in payloads.iter().enumerate() {
+                builder.add_point(idx as PointOffsetType, payload).unwrap();
+            }
+            let mut index = builder.finalize().unwrap();
+
+            let (tuple, positions) = index.match_conditions(&conditions).unwrap();
+            assert_eq!(positions, vec![1, 0]);
+            assert_eq!(index.points_count(&tuple), 2);
+
+            index
+                .add_point(4, &json!({"tenant": "a", "status": "new"}).into())
+                .unwrap();
+            index.remove_point(0).unwrap();
+            index.flusher()().unwrap();
+        }
+
+        let db = open_db_with_existing_cf(tmp_dir.path()).unwrap();
+        let mut index = CompositeIndex::new(db, "test", keys, false);
+        assert!(index.load().unwrap());
+
+        let (tuple, _) = index.match_conditions(&conditions).unwrap();
+        assert_eq!(index.iter_points(&tuple).collect_vec(), vec![3, 4]);
+        // Not all keys are covered by conditions
+        assert!(index.match_conditions(&conditions[..1]).is_none());
+    }
+}
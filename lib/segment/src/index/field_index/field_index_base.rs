@@ -2,10 +2,11 @@ use serde_json::Value;
 
 use crate::common::Flusher;
 use crate::entry::entry_point::OperationResult;
-use crate::index::field_index::full_text_index::text_index::FullTextIndex;
-use crate::index::field_index::geo_index::GeoMapIndex;
-use crate::index::field_index::map_index::MapIndex;
-use crate::index::field_index::numeric_index::NumericIndex;
+use crate::index::field_index::full_text_index::text_index::{FullTextIndex, FullTextIndexBuilder};
+use crate::index::field_index::geo_index::{GeoMapIndex, GeoMapIndexBuilder};
+use crate::index::field_index::map_index::{MapIndex, MapIndexBuilder};
+use crate::index::field_index::null_index::{NullIndex, NullIndexBuilder};
+use crate::index::field_index::numeric_index::{NumericIndex, NumericIndexBuilder};
 use crate::index::field_index::{CardinalityEstimation, PayloadBlockCondition};
 use crate::telemetry::PayloadIndexTelemetry;
 use crate::types::{
@@ -54,6 +55,14 @@ pub trait ValueIndexer<T> {
     /// Extract index-able value from payload `Value`
     fn get_value(&self, value: &Value) -> Option<T>;
 
+    /// Extract all index-able values from payload `Value`, including values of an array
+    fn extract_values(&self, payload: &Value) -> Vec<T> {
+        match payload {
+            Value::Array(values) => values.iter().flat_map(|x| self.get_value(x)).collect(),
+            _ => self.get_value(payload).into_iter().collect(),
+        }
+    }
+
     /// Add point with payload to index
     fn add_point(&mut self, id: PointOffsetType, payload: &Value) -> OperationResult<()> {
         match payload {
@@ -74,6 +83,26 @@ pub trait ValueIndexer<T> {
     fn remove_point(&mut self, id: PointOffsetType) -> OperationResult<()>;
 }
 
+/// Amount of index records written to RocksDB within a single batch during index building
+pub const INDEX_BUILDER_BATCH_SIZE: usize = 10_000;
+
+/// Constructs a field index from a stream of points.
+///
+/// Unlike `ValueIndexer`, builder doesn't maintain in-memory structures while points are added.
+/// Storage records are written in batches and the index is loaded from them on `finalize`.
+/// Each point is expected to be added at most once.
+pub trait FieldIndexBuilderTrait {
+    type FieldIndexType;
+
+    /// Prepare empty storage for the index, removing previous content if any
+    fn init(&mut self) -> OperationResult<()>;
+
+    fn add_point(&mut self, id: PointOffsetType, payload: &Value) -> OperationResult<()>;
+
+    /// Write pending records and load the index from storage
+    fn finalize(self) -> OperationResult<Self::FieldIndexType>;
+}
+
 /// Common interface for all possible types of field indexes
 /// Enables polymorphism on field indexes
 /// TODO: Rename with major release
@@ -85,6 +114,7 @@ pub enum FieldIndex {
     FloatIndex(NumericIndex<FloatPayloadType>),
     GeoIndex(GeoMapIndex),
     FullTextIndex(FullTextIndex),
+    NullIndex(NullIndex),
 }
 
 impl FieldIndex {
@@ -96,6 +126,7 @@ impl FieldIndex {
             FieldIndex::FloatIndex(payload_field_index) => payload_field_index,
             FieldIndex::GeoIndex(payload_field_index) => payload_field_index,
             FieldIndex::FullTextIndex(payload_field_index) => payload_field_index,
+            FieldIndex::NullIndex(payload_field_index) => payload_field_index,
         }
     }
 
@@ -108,6 +139,7 @@ impl FieldIndex {
             FieldIndex::FloatIndex(ref mut payload_field_index) => payload_field_index,
             FieldIndex::GeoIndex(ref mut payload_field_index) => payload_field_index,
             FieldIndex::FullTextIndex(ref mut payload_field_index) => payload_field_index,
+            FieldIndex::NullIndex(ref mut payload_field_index) => payload_field_index,
         }
     }
 
@@ -119,6 +151,7 @@ impl FieldIndex {
             FieldIndex::FloatIndex(ref mut payload_field_index) => payload_field_index.load(),
             FieldIndex::GeoIndex(ref mut payload_field_index) => payload_field_index.load(),
             FieldIndex::FullTextIndex(ref mut payload_field_index) => payload_field_index.load(),
+            FieldIndex::NullIndex(ref mut payload_field_index) => payload_field_index.load(),
         }
     }
 
@@ -130,6 +163,7 @@ impl FieldIndex {
             FieldIndex::FloatIndex(index) => index.clear(),
             FieldIndex::GeoIndex(index) => index.clear(),
             FieldIndex::FullTextIndex(index) => index.clear(),
+            FieldIndex::NullIndex(index) => index.clear(),
         }
     }
 
@@ -141,6 +175,34 @@ impl FieldIndex {
             FieldIndex::FloatIndex(index) => index.recreate(),
             FieldIndex::GeoIndex(index) => index.recreate(),
             FieldIndex::FullTextIndex(index) => index.recreate(),
+            FieldIndex::NullIndex(index) => index.recreate(),
+        }
+    }
+
+    /// Convert an empty index into the builder, which fills it with points
+    pub fn into_builder(self) -> FieldIndexBuilder {
+        match self {
+            FieldIndex::IntIndex(index) => {
+                FieldIndexBuilder::IntIndex(NumericIndexBuilder::new(index))
+            }
+            FieldIndex::IntMapIndex(index) => {
+                FieldIndexBuilder::IntMapIndex(MapIndexBuilder::new(index))
+            }
+            FieldIndex::KeywordIndex(index) => {
+                FieldIndexBuilder::KeywordIndex(MapIndexBuilder::new(index))
+            }
+            FieldIndex::FloatIndex(index) => {
+                FieldIndexBuilder::FloatIndex(NumericIndexBuilder::new(index))
+            }
+            FieldIndex::GeoIndex(index) => {
+                FieldIndexBuilder::GeoIndex(GeoMapIndexBuilder::new(index))
+            }
+            FieldIndex::FullTextIndex(index) => {
+                FieldIndexBuilder::FullTextIndex(FullTextIndexBuilder::new(index))
+            }
+            FieldIndex::NullIndex(index) => {
+                FieldIndexBuilder::NullIndex(NullIndexBuilder::new(index))
+            }
         }
     }
 
@@ -148,6 +210,19 @@ impl FieldIndex {
         self.get_payload_field_index().indexed_points()
     }
 
+    /// Smallest and largest indexed values, only available for range indexes
+    pub fn values_range(&self) -> Option<(f64, f64)> {
+        match self {
+            FieldIndex::IntIndex(index) => index.values_range(),
+            FieldIndex::FloatIndex(index) => index.values_range(),
+            FieldIndex::IntMapIndex(_)
+            | FieldIndex::KeywordIndex(_)
+            | FieldIndex::GeoIndex(_)
+            | FieldIndex::FullTextIndex(_)
+            | FieldIndex::NullIndex(_) => None,
+        }
+    }
+
     pub fn flusher(&self) -> Flusher {
         self.get_payload_field_index().flusher()
     }
@@ -200,6 +275,9 @@ impl FieldIndex {
             FieldIndex::FullTextIndex(ref mut payload_field_index) => {
                 payload_field_index.add_point(id, payload)
             }
+            FieldIndex::NullIndex(ref mut payload_field_index) => {
+                payload_field_index.add_point(id, payload)
+            }
         }
     }
 
@@ -211,17 +289,91 @@ impl FieldIndex {
             FieldIndex::FloatIndex(index) => index.remove_point(point_id),
             FieldIndex::GeoIndex(index) => index.remove_point(point_id),
             FieldIndex::FullTextIndex(index) => index.remove_point(point_id),
+            FieldIndex::NullIndex(index) => index.remove_point(point_id),
         }
     }
 
-    pub fn get_telemetry_data(&self) -> PayloadIndexTelemetry {
+    /// Name of the index structure, reported in telemetry
+    pub fn index_type(&self) -> &'static str {
         match self {
+            FieldIndex::IntIndex(_) => "int_range",
+            FieldIndex::IntMapIndex(_) => "int_map",
+            FieldIndex::KeywordIndex(_) => "keyword_map",
+            FieldIndex::FloatIndex(_) => "float_range",
+            FieldIndex::GeoIndex(_) => "geo",
+            FieldIndex::FullTextIndex(_) => "full_text",
+            FieldIndex::NullIndex(_) => "null",
+        }
+    }
+
+    pub fn get_telemetry_data(&self) -> PayloadIndexTelemetry {
+        let telemetry = match self {
             FieldIndex::IntIndex(index) => index.get_telemetry_data(),
             FieldIndex::IntMapIndex(index) => index.get_telemetry_data(),
             FieldIndex::KeywordIndex(index) => index.get_telemetry_data(),
             FieldIndex::FloatIndex(index) => index.get_telemetry_data(),
             FieldIndex::GeoIndex(index) => index.get_telemetry_data(),
             FieldIndex::FullTextIndex(index) => index.get_telemetry_data(),
+            FieldIndex::NullIndex(index) => index.get_telemetry_data(),
+        };
+        PayloadIndexTelemetry {
+            index_type: self.index_type().to_string(),
+            ..telemetry
+        }
+    }
+}
+
+/// Builders of all possible types of field indexes, see `FieldIndex::into_builder`
+pub enum FieldIndexBuilder {
+    IntIndex(NumericIndexBuilder<IntPayloadType>),
+    IntMapIndex(MapIndexBuilder<IntPayloadType>),
+    KeywordIndex(MapIndexBuilder<String>),
+    FloatIndex(NumericIndexBuilder<FloatPayloadType>),
+    GeoIndex(GeoMapIndexBuilder),
+    FullTextIndex(FullTextIndexBuilder),
+    NullIndex(NullIndexBuilder),
+}
+
+impl FieldIndexBuilderTrait for FieldIndexBuilder {
+    type FieldIndexType = FieldIndex;
+
+    fn init(&mut self) -> OperationResult<()> {
+        match self {
+            FieldIndexBuilder::IntIndex(builder) => builder.init(),
+            FieldIndexBuilder::IntMapIndex(builder) => builder.init(),
+            FieldIndexBuilder::KeywordIndex(builder) => builder.init(),
+            FieldIndexBuilder::FloatIndex(builder) => builder.init(),
+            FieldIndexBuilder::GeoIndex(builder) => builder.init(),
+            FieldIndexBuilder::FullTextIndex(builder) => builder.init(),
+            FieldIndexBuilder::NullIndex(builder) => builder.init(),
+        }
+    }
+
+    fn add_point(&mut self, id: PointOffsetType, payload: &Value) -> OperationResult<()> {
+        match self {
+            FieldIndexBuilder::IntIndex(builder) => builder.add_point(id, payload),
+            FieldIndexBuilder::IntMapIndex(builder) => builder.add_point(id, payload),
+            FieldIndexBuilder::KeywordIndex(builder) => builder.add_point(id, payload),
+            FieldIndexBuilder::FloatIndex(builder) => builder.add_point(id, payload),
+            FieldIndexBuilder::GeoIndex(builder) => builder.add_point(id, payload),
+            FieldIndexBuilder::FullTextIndex(builder) => builder.add_point(id, payload),
+            FieldIndexBuilder::NullIndex(builder) => builder.add_point(id, payload),
         }
     }
+
+    fn finalize(self) -> OperationResult<FieldIndex> {
+        Ok(match self {
+            FieldIndexBuilder::IntIndex(builder) => FieldIndex::IntIndex(builder.finalize()?),
+            FieldIndexBuilder::IntMapIndex(builder) => FieldIndex::IntMapIndex(builder.finalize()?),
+            FieldIndexBuilder::KeywordIndex(builder) => {
+                FieldIndex::KeywordIndex(builder.finalize()?)
+            }
+            FieldIndexBuilder::FloatIndex(builder) => FieldIndex::FloatIndex(builder.finalize()?),
+            FieldIndexBuilder::GeoIndex(builder) => FieldIndex::GeoIndex(builder.finalize()?),
+            FieldIndexBuilder::FullTextIndex(builder) => {
+                FieldIndex::FullTextIndex(builder.finalize()?)
+            }
+            FieldIndexBuilder::NullIndex(builder) => FieldIndex::NullIndex(builder.finalize()?),
+        })
+    }
 }
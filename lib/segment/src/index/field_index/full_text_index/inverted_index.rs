@@ -1,4 +1,5 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::mem::size_of;
 
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +12,9 @@ type PostingList = BTreeSet<PointOffsetType>;
 #[derive(Default, Serialize, Deserialize, Clone)]
 pub struct Document {
     pub tokens: BTreeSet<String>,
+    /// Tokens of each string value in the order of appearance, used for phrase matching
+    #[serde(default)]
+    pub sequences: Vec<Vec<String>>,
 }
 
 impl Document {
@@ -32,6 +36,46 @@ impl ParsedQuery {
     }
 }
 
+pub struct ParsedPhrase {
+    pub tokens: Vec<String>,
+    /// Phrase tokens only have to be prefixes of document tokens, used with the prefix tokenizer
+    pub prefix: bool,
+}
+
+impl ParsedPhrase {
+    fn token_matches(&self, document_token: &str, phrase_token: &str) -> bool {
+        if self.prefix {
+            document_token.starts_with(phrase_token)
+        } else {
+            document_token == phrase_token
+        }
+    }
+
+    pub fn check_match(&self, document: &Document) -> bool {
+        if self.tokens.is_empty() {
+            return false;
+        }
+        // Check that all tokens follow each other within one of the document values
+        document.sequences.iter().any(|sequence| {
+            sequence.windows(self.tokens.len()).any(|window| {
+                window
+                    .iter()
+                    .zip(&self.tokens)
+                    .all(|(document_token, phrase_token)| {
+                        self.token_matches(document_token, phrase_token)
+                    })
+            })
+        })
+    }
+
+    /// Query of all phrase tokens regardless of their order
+    pub fn to_query(&self) -> ParsedQuery {
+        ParsedQuery {
+            tokens: self.tokens.iter().cloned().collect(),
+        }
+    }
+}
+
 pub struct InvertedIndex {
     postings: BTreeMap<String, PostingList>,
     pub point_to_docs: Vec<Option<Document>>,
@@ -88,6 +132,38 @@ impl InvertedIndex {
         Some(removed_doc)
     }
 
+    /// Amount of distinct tokens of all indexed documents
+    pub fn tokens_count(&self) -> usize {
+        self.postings.len()
+    }
+
+    /// Rough estimate of RAM taken by postings and documents
+    pub fn ram_usage_bytes(&self) -> usize {
+        let postings_size: usize = self
+            .postings
+            .iter()
+            .map(|(token, posting)| {
+                size_of::<String>()
+                    + token.len()
+                    + size_of::<PostingList>()
+                    + posting.len() * size_of::<PointOffsetType>()
+            })
+            .sum();
+        let documents_size: usize = self
+            .point_to_docs
+            .iter()
+            .flatten()
+            .flat_map(|document| {
+                document
+                    .tokens
+                    .iter()
+                    .chain(document.sequences.iter().flatten())
+            })
+            .map(|token| size_of::<String>() + token.len())
+            .sum();
+        postings_size + documents_size + self.point_to_docs.len() * size_of::<Option<Document>>()
+    }
+
     pub fn filter(&self, query: &ParsedQuery) -> Box<dyn Iterator<Item = PointOffsetType> + '_> {
         let postings_opt: Option<Vec<_>> = query
             .tokens
@@ -106,6 +182,32 @@ impl InvertedIndex {
         intersect_btree_iterator(postings)
     }
 
+    pub fn filter_phrase(
+        &self,
+        phrase: ParsedPhrase,
+    ) -> Box<dyn Iterator<Item = PointOffsetType> + '_> {
+        // Postings give candidates with all the tokens, adjacency is checked by documents
+        Box::new(self.filter(&phrase.to_query()).filter(move |idx| {
+            match self.point_to_docs.get(*idx as usize) {
+                Some(Some(document)) => phrase.check_match(document),
+                _ => false,
+            }
+        }))
+    }
+
+    pub fn estimate_phrase_cardinality(
+        &self,
+        phrase: &ParsedPhrase,
+        condition: &FieldCondition,
+    ) -> CardinalityEstimation {
+        let estimation = self.estimate_cardinality(&phrase.to_query(), condition);
+        // Points with all the tokens might still have them apart
+        CardinalityEstimation {
+            min: 0,
+            ..estimation
+        }
+    }
+
     pub fn estimate_cardinality(
         &self,
         query: &ParsedQuery,
@@ -180,6 +282,7 @@ impl InvertedIndex {
                         range: None,
                         geo_bounding_box: None,
                         geo_radius: None,
+                        geo_polygon: None,
                         values_count: None,
                     },
                     cardinality: posting.len(),
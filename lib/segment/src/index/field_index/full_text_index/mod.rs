@@ -1,4 +1,5 @@
 mod inverted_index;
 mod postings_iterator;
+mod stop_words;
 pub mod text_index;
 mod tokenizers;
@@ -5,16 +5,17 @@ use parking_lot::RwLock;
 use rocksdb::DB;
 use serde_json::Value;
 
-use crate::common::rocksdb_wrapper::DatabaseColumnWrapper;
+use crate::common::rocksdb_wrapper::{DatabaseColumnBatchWriter, DatabaseColumnWrapper};
 use crate::common::Flusher;
 use crate::data_types::text_index::TextIndexParams;
 use crate::entry::entry_point::{OperationError, OperationResult};
 use crate::index::field_index::full_text_index::inverted_index::{
-    Document, InvertedIndex, ParsedQuery,
+    Document, InvertedIndex, ParsedPhrase, ParsedQuery,
 };
 use crate::index::field_index::full_text_index::tokenizers::Tokenizer;
 use crate::index::field_index::{
-    CardinalityEstimation, PayloadBlockCondition, PayloadFieldIndex, ValueIndexer,
+    CardinalityEstimation, FieldIndexBuilderTrait, PayloadBlockCondition, PayloadFieldIndex,
+    ValueIndexer, INDEX_BUILDER_BATCH_SIZE,
 };
 use crate::telemetry::PayloadIndexTelemetry;
 use crate::types::{FieldCondition, Match, PayloadKeyType, PointOffsetType};
@@ -22,7 +23,7 @@ use crate::types::{FieldCondition, Match, PayloadKeyType, PointOffsetType};
 pub struct FullTextIndex {
     inverted_index: InvertedIndex,
     db_wrapper: DatabaseColumnWrapper,
-    config: TextIndexParams,
+    tokenizer: Tokenizer,
 }
 
 impl FullTextIndex {
@@ -56,7 +57,7 @@ impl FullTextIndex {
         FullTextIndex {
             inverted_index: InvertedIndex::new(),
             db_wrapper,
-            config,
+            tokenizer: Tokenizer::new(&config),
         }
     }
 
@@ -71,7 +72,32 @@ impl FullTextIndex {
         PayloadIndexTelemetry {
             points_values_count: self.inverted_index.points_count,
             points_count: self.inverted_index.points_count,
-            histogram_bucket_size: None,
+            distinct_values_count: Some(self.inverted_index.tokens_count()),
+            ram_usage_bytes: self.inverted_index.ram_usage_bytes(),
+            disk_usage_bytes: self.db_wrapper.disk_usage_bytes().unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    fn make_document(&self, values: Vec<String>) -> Document {
+        let mut tokens: HashSet<String> = HashSet::new();
+        let mut sequences = Vec::with_capacity(values.len());
+
+        for value in values {
+            self.tokenizer.tokenize_doc(&value, |token| {
+                tokens.insert(token.to_owned());
+            });
+            // Sequences consist of the same tokens as queries, so phrases can be compared with them
+            let mut sequence = vec![];
+            self.tokenizer.tokenize_query(&value, |token| {
+                sequence.push(token.to_owned());
+            });
+            sequences.push(sequence);
+        }
+
+        Document {
+            tokens: tokens.into_iter().collect(),
+            sequences,
         }
     }
 
@@ -81,13 +107,24 @@ impl FullTextIndex {
 
     pub fn parse_query(&self, text: &str) -> ParsedQuery {
         let mut tokens = vec![];
-        Tokenizer::tokenize_query(text, &self.config, |token| {
+        self.tokenizer.tokenize_query(text, |token| {
             tokens.push(token.to_owned());
         });
         ParsedQuery {
             tokens: tokens.into_iter().collect(),
         }
     }
+
+    pub fn parse_phrase(&self, phrase: &str) -> ParsedPhrase {
+        let mut tokens = vec![];
+        self.tokenizer.tokenize_query(phrase, |token| {
+            tokens.push(token.to_owned());
+        });
+        ParsedPhrase {
+            tokens,
+            prefix: self.tokenizer.is_prefix(),
+        }
+    }
 }
 
 impl ValueIndexer<String> for FullTextIndex {
@@ -100,17 +137,7 @@ impl ValueIndexer<String> for FullTextIndex {
             return Ok(());
         }
 
-        let mut tokens: HashSet<String> = HashSet::new();
-
-        for value in values {
-            Tokenizer::tokenize_doc(&value, &self.config, |token| {
-                tokens.insert(token.to_owned());
-            });
-        }
-
-        let document = Document {
-            tokens: tokens.into_iter().collect(),
-        };
+        let document = self.make_document(values);
 
         self.inverted_index.index_document(idx, document);
 
@@ -177,22 +204,37 @@ impl PayloadFieldIndex for FullTextIndex {
         &self,
         condition: &FieldCondition,
     ) -> Option<Box<dyn Iterator<Item = PointOffsetType> + '_>> {
-        if let Some(Match::Text(text_match)) = &condition.r#match {
-            let parsed_query = self.parse_query(&text_match.text);
-            return Some(self.inverted_index.filter(&parsed_query));
+        match &condition.r#match {
+            Some(Match::Text(text_match)) => {
+                let parsed_query = self.parse_query(&text_match.text);
+                Some(self.inverted_index.filter(&parsed_query))
+            }
+            Some(Match::Phrase(phrase_match)) => {
+                let parsed_phrase = self.parse_phrase(&phrase_match.phrase);
+                Some(self.inverted_index.filter_phrase(parsed_phrase))
+            }
+            _ => None,
         }
-        None
     }
 
     fn estimate_cardinality(&self, condition: &FieldCondition) -> Option<CardinalityEstimation> {
-        if let Some(Match::Text(text_match)) = &condition.r#match {
-            let parsed_query = self.parse_query(&text_match.text);
-            return Some(
-                self.inverted_index
-                    .estimate_cardinality(&parsed_query, condition),
-            );
+        match &condition.r#match {
+            Some(Match::Text(text_match)) => {
+                let parsed_query = self.parse_query(&text_match.text);
+                Some(
+                    self.inverted_index
+                        .estimate_cardinality(&parsed_query, condition),
+                )
+            }
+            Some(Match::Phrase(phrase_match)) => {
+                let parsed_phrase = self.parse_phrase(&phrase_match.phrase);
+                Some(
+                    self.inverted_index
+                        .estimate_phrase_cardinality(&parsed_phrase, condition),
+                )
+            }
+            _ => None,
         }
-        None
     }
 
     fn payload_blocks(
@@ -208,6 +250,44 @@ impl PayloadFieldIndex for FullTextIndex {
     }
 }
 
+pub struct FullTextIndexBuilder {
+    index: FullTextIndex,
+    writer: DatabaseColumnBatchWriter,
+}
+
+impl FullTextIndexBuilder {
+    pub fn new(index: FullTextIndex) -> Self {
+        let writer = index.db_wrapper.batch_writer(INDEX_BUILDER_BATCH_SIZE);
+        Self { index, writer }
+    }
+}
+
+impl FieldIndexBuilderTrait for FullTextIndexBuilder {
+    type FieldIndexType = FullTextIndex;
+
+    fn init(&mut self) -> OperationResult<()> {
+        self.writer.recreate_column_family()
+    }
+
+    fn add_point(&mut self, id: PointOffsetType, payload: &Value) -> OperationResult<()> {
+        let values = self.index.extract_values(payload);
+        if values.is_empty() {
+            return Ok(());
+        }
+        let document = self.index.make_document(values);
+        self.writer.put(
+            FullTextIndex::store_key(&id),
+            FullTextIndex::serialize_document(&document)?,
+        )
+    }
+
+    fn finalize(mut self) -> OperationResult<FullTextIndex> {
+        self.writer.flush()?;
+        self.index.load()?;
+        Ok(self.index)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use tempfile::Builder;
@@ -215,7 +295,7 @@ mod tests {
     use super::*;
     use crate::common::rocksdb_wrapper::open_db_with_existing_cf;
     use crate::data_types::text_index::{TextIndexType, TokenizerType};
-    use crate::types::MatchText;
+    use crate::types::{MatchPhrase, MatchText};
 
     fn filter_request(text: &str) -> FieldCondition {
         FieldCondition {
@@ -226,6 +306,21 @@ mod tests {
             range: None,
             geo_bounding_box: None,
             geo_radius: None,
+            geo_polygon: None,
+            values_count: None,
+        }
+    }
+
+    fn phrase_request(phrase: &str) -> FieldCondition {
+        FieldCondition {
+            key: "text".to_owned(),
+            r#match: Some(Match::Phrase(MatchPhrase {
+                phrase: phrase.to_owned(),
+            })),
+            range: None,
+            geo_bounding_box: None,
+            geo_radius: None,
+            geo_polygon: None,
             values_count: None,
         }
     }
@@ -250,6 +345,8 @@ mod tests {
             min_token_len: None,
             max_token_len: None,
             lowercase: None,
+            stemmer: None,
+            stopwords: None,
         };
 
         {
@@ -321,4 +418,71 @@ mod tests {
             assert_eq!(search_res, vec![0, 1, 3, 4]);
         }
     }
+
+    #[test]
+    fn test_phrase_matching() {
+        let payloads: Vec<_> = vec![
+            serde_json::json!("A red sports car is parked outside"),
+            serde_json::json!("The sports car is red"),
+            serde_json::json!(["A red car", "sports are fun"]),
+            serde_json::json!("Red, sports car!"),
+        ];
+
+        let tmp_dir = Builder::new().prefix("test_dir").tempdir().unwrap();
+        let config = TextIndexParams {
+            r#type: TextIndexType::Text,
+            tokenizer: TokenizerType::Word,
+            min_token_len: None,
+            max_token_len: None,
+            lowercase: None,
+            stemmer: None,
+            stopwords: None,
+        };
+
+        {
+            let db = open_db_with_existing_cf(&tmp_dir.path().join("test_db")).unwrap();
+            let mut index = FullTextIndex::new(db, config.clone(), "text");
+            index.recreate().unwrap();
+            for (idx, payload) in payloads.iter().enumerate() {
+                index.add_point(idx as PointOffsetType, payload).unwrap();
+            }
+
+            // All points have the tokens, but only some of them in a row
+            let search_res: Vec<_> = index
+                .filter(&filter_request("red sports car"))
+                .unwrap()
+                .collect();
+            assert_eq!(search_res, vec![0, 1, 2, 3]);
+
+            let search_res: Vec<_> = index
+                .filter(&phrase_request("red sports car"))
+                .unwrap()
+                .collect();
+            assert_eq!(search_res, vec![0, 3]);
+
+            // Phrase doesn't span multiple values
+            let search_res: Vec<_> = index
+                .filter(&phrase_request("car sports"))
+                .unwrap()
+                .collect();
+            assert!(search_res.is_empty());
+
+            let estimation = index
+                .estimate_cardinality(&phrase_request("red sports car"))
+                .unwrap();
+            assert_eq!(estimation.min, 0);
+            assert!(estimation.max >= 2);
+
+            index.flusher()().unwrap();
+        }
+
+        let db = open_db_with_existing_cf(&tmp_dir.path().join("test_db")).unwrap();
+        let mut index = FullTextIndex::new(db, config, "text");
+        assert!(index.load().unwrap());
+        let search_res: Vec<_> = index
+            .filter(&phrase_request("sports car is"))
+            .unwrap()
+            .collect();
+        assert_eq!(search_res, vec![0, 1]);
+    }
 }
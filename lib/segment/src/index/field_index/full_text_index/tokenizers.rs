@@ -1,4 +1,12 @@
-use crate::data_types::text_index::{TextIndexParams, TokenizerType};
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use rust_stemmers::{Algorithm, Stemmer};
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::data_types::text_index::{Language, TextIndexParams, TokenizerType};
+use crate::index::field_index::full_text_index::stop_words::stop_words;
 
 struct WhiteSpaceTokenizer;
 
@@ -60,68 +68,185 @@ impl PrefixTokenizer {
     }
 }
 
-pub struct Tokenizer;
+struct MultilingualTokenizer;
+
+impl MultilingualTokenizer {
+    /// Chinese and Japanese scripts don't separate words with spaces
+    fn is_cjk(c: char) -> bool {
+        matches!(c,
+            '\u{3040}'..='\u{30FF}' // Hiragana, Katakana
+            | '\u{3400}'..='\u{4DBF}' // CJK Unified Ideographs Extension A
+            | '\u{4E00}'..='\u{9FFF}' // CJK Unified Ideographs
+            | '\u{F900}'..='\u{FAFF}' // CJK Compatibility Ideographs
+            | '\u{20000}'..='\u{2FA1F}' // CJK Extensions B-F, Compatibility Supplement
+        )
+    }
+
+    /// Emits overlapping bigrams of a CJK run, or the run itself if it is a single character
+    fn cjk_bigrams<C: FnMut(&str)>(run: &[char], callback: &mut C) {
+        if run.len() == 1 {
+            callback(&run[0].to_string());
+            return;
+        }
+        for pair in run.windows(2) {
+            callback(&pair.iter().collect::<String>());
+        }
+    }
+
+    /// Normalizes text to NFKC and splits it on Unicode word boundaries.
+    /// Without a dictionary, bigrams are a common way to make CJK texts searchable:
+    /// "東京都" -> ["東京", "京都"]
+    fn tokenize<C: FnMut(&str)>(text: &str, mut callback: C) {
+        let normalized: String = text.nfkc().collect();
+        let mut cjk_run: Vec<char> = Vec::new();
+        let mut cjk_run_end = 0;
+        for (start, word) in normalized.unicode_word_indices() {
+            if word.chars().all(Self::is_cjk) {
+                // Segmentation splits ideographs one by one, join adjacent ones back
+                if !cjk_run.is_empty() && cjk_run_end != start {
+                    Self::cjk_bigrams(&cjk_run, &mut callback);
+                    cjk_run.clear();
+                }
+                cjk_run.extend(word.chars());
+                cjk_run_end = start + word.len();
+                continue;
+            }
+            if !cjk_run.is_empty() {
+                Self::cjk_bigrams(&cjk_run, &mut callback);
+                cjk_run.clear();
+            }
+            callback(word);
+        }
+        if !cjk_run.is_empty() {
+            Self::cjk_bigrams(&cjk_run, &mut callback);
+        }
+    }
+}
+
+/// Splits texts into tokens according to the text index parameters
+pub struct Tokenizer {
+    config: TextIndexParams,
+    /// Lowercase stopwords, which are excluded from tokens
+    stopwords: HashSet<String>,
+    stemmer: Option<Stemmer>,
+}
 
 impl Tokenizer {
+    pub fn new(config: &TextIndexParams) -> Self {
+        let stopwords = config
+            .stopwords
+            .iter()
+            .flat_map(|stopwords| {
+                let built_in = stopwords
+                    .language
+                    .map(stop_words)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|word| word.to_string());
+                let custom = stopwords.custom.iter().map(|word| word.to_lowercase());
+                built_in.chain(custom)
+            })
+            .collect();
+        Tokenizer {
+            config: config.clone(),
+            stopwords,
+            stemmer: config
+                .stemmer
+                .map(|language| Stemmer::create(stemmer_algorithm(language))),
+        }
+    }
+
+    pub fn is_prefix(&self) -> bool {
+        self.config.tokenizer == TokenizerType::Prefix
+    }
+
+    fn is_stopword(&self, token: &str) -> bool {
+        !self.stopwords.is_empty() && self.stopwords.contains(&token.to_lowercase())
+    }
+
     fn doc_token_filter<'a, C: FnMut(&str) + 'a>(
-        config: &'a TextIndexParams,
+        &'a self,
         mut callback: C,
     ) -> impl FnMut(&str) + 'a {
         move |token: &str| {
-            if config
+            if self
+                .config
                 .min_token_len
                 .map(|min_len| token.len() < min_len && token.chars().count() < min_len)
                 .unwrap_or(false)
             {
                 return;
             }
-            if config
+            if self
+                .config
                 .max_token_len
                 .map(|max_len| token.len() > max_len && token.chars().count() > max_len)
                 .unwrap_or(false)
             {
                 return;
             }
-            if config.lowercase.unwrap_or(true) {
-                callback(&token.to_lowercase());
+            if self.is_stopword(token) {
+                return;
+            }
+            let token = if self.config.lowercase.unwrap_or(true) {
+                Cow::Owned(token.to_lowercase())
             } else {
-                callback(token);
+                Cow::Borrowed(token)
+            };
+            match &self.stemmer {
+                Some(stemmer) => callback(&stemmer.stem(&token)),
+                None => callback(&token),
             }
         }
     }
 
-    pub fn tokenize_doc<C: FnMut(&str)>(text: &str, config: &TextIndexParams, mut callback: C) {
-        let token_filter = Self::doc_token_filter(config, &mut callback);
-        match config.tokenizer {
+    pub fn tokenize_doc<C: FnMut(&str)>(&self, text: &str, mut callback: C) {
+        let token_filter = self.doc_token_filter(&mut callback);
+        match self.config.tokenizer {
             TokenizerType::Whitespace => WhiteSpaceTokenizer::tokenize(text, token_filter),
             TokenizerType::Word => WordTokenizer::tokenize(text, token_filter),
+            TokenizerType::Multilingual => MultilingualTokenizer::tokenize(text, token_filter),
             TokenizerType::Prefix => PrefixTokenizer::tokenize(
                 text,
-                config.min_token_len.unwrap_or(1),
-                config.max_token_len.unwrap_or(usize::MAX),
+                self.config.min_token_len.unwrap_or(1),
+                self.config.max_token_len.unwrap_or(usize::MAX),
                 token_filter,
             ),
         }
     }
 
-    pub fn tokenize_query<C: FnMut(&str)>(text: &str, config: &TextIndexParams, mut callback: C) {
-        let token_filter = Self::doc_token_filter(config, &mut callback);
-        match config.tokenizer {
+    pub fn tokenize_query<C: FnMut(&str)>(&self, text: &str, mut callback: C) {
+        let token_filter = self.doc_token_filter(&mut callback);
+        match self.config.tokenizer {
             TokenizerType::Whitespace => WhiteSpaceTokenizer::tokenize(text, token_filter),
             TokenizerType::Word => WordTokenizer::tokenize(text, token_filter),
+            TokenizerType::Multilingual => MultilingualTokenizer::tokenize(text, token_filter),
             TokenizerType::Prefix => PrefixTokenizer::tokenize_query(
                 text,
-                config.max_token_len.unwrap_or(usize::MAX),
+                self.config.max_token_len.unwrap_or(usize::MAX),
                 token_filter,
             ),
         }
     }
 }
 
+fn stemmer_algorithm(language: Language) -> Algorithm {
+    match language {
+        Language::Dutch => Algorithm::Dutch,
+        Language::English => Algorithm::English,
+        Language::French => Algorithm::French,
+        Language::German => Algorithm::German,
+        Language::Italian => Algorithm::Italian,
+        Language::Portuguese => Algorithm::Portuguese,
+        Language::Russian => Algorithm::Russian,
+        Language::Spanish => Algorithm::Spanish,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::data_types::text_index::TextIndexType;
+    use crate::data_types::text_index::{StopwordsParams, TextIndexType};
 
     #[test]
     fn test_whitespace_tokenizer() {
@@ -145,6 +270,22 @@ mod tests {
         assert_eq!(tokens.get(3), Some(&"мир".to_owned()));
     }
 
+    #[test]
+    fn test_multilingual_tokenizer() {
+        let mut tokens = Vec::new();
+        MultilingualTokenizer::tokenize("東京都に住む, hello ｗｏｒｌｄ!", |token| {
+            tokens.push(token.to_owned())
+        });
+        assert_eq!(
+            tokens,
+            vec!["東京", "京都", "都に", "に住", "住む", "hello", "world"]
+        );
+
+        let mut tokens = Vec::new();
+        MultilingualTokenizer::tokenize("北 京", |token| tokens.push(token.to_owned()));
+        assert_eq!(tokens, vec!["北", "京"]);
+    }
+
     #[test]
     fn test_prefix_tokenizer() {
         let text = "hello, мир!";
@@ -176,17 +317,16 @@ mod tests {
     fn test_tokenizer() {
         let text = "Hello, Мир!";
         let mut tokens = Vec::new();
-        Tokenizer::tokenize_doc(
-            text,
-            &TextIndexParams {
-                r#type: TextIndexType::Text,
-                tokenizer: TokenizerType::Prefix,
-                min_token_len: Some(1),
-                max_token_len: Some(4),
-                lowercase: Some(true),
-            },
-            |token| tokens.push(token.to_owned()),
-        );
+        Tokenizer::new(&TextIndexParams {
+            r#type: TextIndexType::Text,
+            tokenizer: TokenizerType::Prefix,
+            min_token_len: Some(1),
+            max_token_len: Some(4),
+            lowercase: Some(true),
+            stemmer: None,
+            stopwords: None,
+        })
+        .tokenize_doc(text, |token| tokens.push(token.to_owned()));
         eprintln!("tokens = {:#?}", tokens);
         assert_eq!(tokens.len(), 7);
         assert_eq!(tokens.get(0), Some(&"h".to_owned()));
@@ -197,4 +337,30 @@ mod tests {
         assert_eq!(tokens.get(5), Some(&"ми".to_owned()));
         assert_eq!(tokens.get(6), Some(&"мир".to_owned()));
     }
+
+    #[test]
+    fn test_stemming_and_stopwords() {
+        let tokenizer = Tokenizer::new(&TextIndexParams {
+            r#type: TextIndexType::Text,
+            tokenizer: TokenizerType::Word,
+            min_token_len: None,
+            max_token_len: None,
+            lowercase: None,
+            stemmer: Some(Language::English),
+            stopwords: Some(StopwordsParams {
+                language: Some(Language::English),
+                custom: vec!["Quickly".to_string()],
+            }),
+        });
+
+        let mut tokens = Vec::new();
+        tokenizer.tokenize_doc("The cats were running quickly", |token| {
+            tokens.push(token.to_owned())
+        });
+        assert_eq!(tokens, vec!["cat".to_owned(), "run".to_owned()]);
+
+        let mut query_tokens = Vec::new();
+        tokenizer.tokenize_query("Cat runs", |token| query_tokens.push(token.to_owned()));
+        assert_eq!(query_tokens, tokens);
+    }
 }
@@ -1,11 +1,11 @@
 use std::ops::Range;
 
 use geo::algorithm::haversine_distance::HaversineDistance;
-use geo::{Coordinate, Point};
+use geo::{Coordinate, Intersects, Point, Polygon};
 use geohash::{decode, decode_bbox, encode, Direction, GeohashError};
 use itertools::Itertools;
 
-use crate::types::{GeoBoundingBox, GeoPoint, GeoRadius};
+use crate::types::{GeoBoundingBox, GeoPoint, GeoPolygon, GeoRadius};
 
 pub type GeoHash = String;
 
@@ -195,6 +195,15 @@ fn check_intersection(geohash: &str, circle: &GeoRadius) -> bool {
         > bbox_center.haversine_distance(&Point::new(circle.center.lon, circle.center.lat))
 }
 
+/// Check if geohash tile intersects the polygon
+fn check_polygon_intersection(geohash: &str, polygon: &Polygon<f64>) -> bool {
+    if geohash.is_empty() {
+        return true;
+    }
+    let rect = decode_bbox(geohash).unwrap();
+    polygon.intersects(&rect)
+}
+
 /// Return as-high-as-possible with maximum of `max_regions`
 /// number of geo-hash guaranteed to contain the whole circle.
 pub fn circle_hashes(circle: &GeoRadius, max_regions: usize) -> Vec<GeoHash> {
@@ -233,6 +242,34 @@ pub fn rectangle_hashes(rectangle: &GeoBoundingBox, max_regions: usize) -> Vec<G
         .expect("geo-hash coverage is empty")
 }
 
+/// Return as-high-as-possible with maximum of `max_regions`
+/// number of geo-hash guaranteed to contain the whole polygon.
+pub fn polygon_hashes(polygon: &GeoPolygon, max_regions: usize) -> Vec<GeoHash> {
+    assert_ne!(max_regions, 0, "max_regions cannot be equal to zero");
+    let bounding_box = match polygon.bounding_box() {
+        Some(bounding_box) => bounding_box,
+        None => return vec![],
+    };
+    let full_geohash_bounding_box: GeohashBoundingBox = bounding_box.into();
+    let geo_polygon = polygon.to_polygon();
+
+    (0..=GEOHASH_MAX_LENGTH)
+        .map(|precision| {
+            full_geohash_bounding_box
+                .geohash_regions(precision, max_regions)
+                .map(|hashes| {
+                    hashes
+                        .into_iter()
+                        .filter(|hash| check_polygon_intersection(hash, &geo_polygon))
+                        .collect_vec()
+                })
+        })
+        .take_while(|hashes| hashes.is_some())
+        .last()
+        .expect("no hash coverage for any precision")
+        .expect("geo-hash coverage is empty")
+}
+
 /// A globally-average value is usually considered to be 6,371 kilometres (3,959 mi) with a 0.3% variability (±10 km).
 /// https://en.wikipedia.org/wiki/Earth_radius.
 const EARTH_RADIUS_METERS: f64 = 6371.0 * 1000.;
@@ -289,6 +326,7 @@ mod tests {
     use rand::{Rng, SeedableRng};
 
     use super::*;
+    use crate::types::GeoLineString;
 
     const BERLIN: GeoPoint = GeoPoint {
         lat: 52.52437,
@@ -443,6 +481,69 @@ mod tests {
         assert_eq!(nyc_hashes, ["dr5ru"]);
     }
 
+    #[test]
+    fn polygon_hashes_nyc() {
+        let top_left = GeoPoint {
+            lon: -74.00101399,
+            lat: 40.76517460,
+        };
+        let bottom_right = GeoPoint {
+            lon: -73.98201792,
+            lat: 40.75078539,
+        };
+        let top_right = GeoPoint {
+            lon: bottom_right.lon,
+            lat: top_left.lat,
+        };
+        let bottom_left = GeoPoint {
+            lon: top_left.lon,
+            lat: bottom_right.lat,
+        };
+        let near_nyc_rectangle = GeoBoundingBox {
+            top_left: top_left.clone(),
+            bottom_right: bottom_right.clone(),
+        };
+
+        // Polygon of the same shape as the rectangle is covered by the same hashes
+        let rectangle_polygon = GeoPolygon {
+            exterior: GeoLineString {
+                points: vec![
+                    top_left.clone(),
+                    top_right.clone(),
+                    bottom_right,
+                    bottom_left.clone(),
+                    top_left.clone(),
+                ],
+            },
+            interiors: None,
+        };
+        let mut polygon_covering = polygon_hashes(&rectangle_polygon, 10);
+        polygon_covering.sort_unstable();
+        let mut rectangle_covering = rectangle_hashes(&near_nyc_rectangle, 10);
+        rectangle_covering.sort_unstable();
+        assert_eq!(polygon_covering, rectangle_covering);
+
+        // Tiles outside of the triangle are excluded from the covering
+        let triangle = GeoPolygon {
+            exterior: GeoLineString {
+                points: vec![top_left.clone(), top_right, bottom_left, top_left],
+            },
+            interiors: None,
+        };
+        let triangle_covering = polygon_hashes(&triangle, 10);
+        assert!(triangle_covering.contains(&"dr5ruj".to_string()));
+        assert!(triangle_covering.len() < rectangle_covering.len());
+        assert!(triangle_covering
+            .iter()
+            .all(|hash| rectangle_covering.contains(hash)));
+
+        let empty_polygon = GeoPolygon {
+            exterior: GeoLineString { points: vec![] },
+            interiors: None,
+        };
+        assert!(polygon_hashes(&empty_polygon, 10).is_empty());
+    }
+
     #[test]
     fn random_circles() {
         let mut rnd = StdRng::seed_from_u64(42);
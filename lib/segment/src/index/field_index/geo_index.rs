@@ -1,33 +1,43 @@
 use std::cmp::{max, min};
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::mem::size_of;
 use std::str::FromStr;
 use std::sync::Arc;
 
+use geo::{Contains, Point};
 use itertools::Itertools;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use rocksdb::DB;
 use serde_json::Value;
 
-use crate::common::rocksdb_wrapper::DatabaseColumnWrapper;
+use crate::common::rocksdb_wrapper::{DatabaseColumnBatchWriter, DatabaseColumnWrapper};
 use crate::common::Flusher;
 use crate::entry::entry_point::{OperationError, OperationResult};
 use crate::index::field_index::geo_hash::{
-    circle_hashes, common_hash_prefix, encode_max_precision, geo_hash_to_box, rectangle_hashes,
-    GeoHash,
+    circle_hashes, common_hash_prefix, encode_max_precision, geo_hash_to_box, polygon_hashes,
+    rectangle_hashes, GeoHash,
 };
 use crate::index::field_index::stat_tools::estimate_multi_value_selection_cardinality;
 use crate::index::field_index::{
-    CardinalityEstimation, PayloadBlockCondition, PayloadFieldIndex, PrimaryCondition, ValueIndexer,
+    CardinalityEstimation, FieldIndexBuilderTrait, PayloadBlockCondition, PayloadFieldIndex,
+    PrimaryCondition, ValueIndexer, INDEX_BUILDER_BATCH_SIZE,
 };
 use crate::telemetry::PayloadIndexTelemetry;
 use crate::types::{
-    FieldCondition, GeoBoundingBox, GeoPoint, GeoRadius, PayloadKeyType, PointOffsetType,
+    FieldCondition, GeoBoundingBox, GeoPoint, GeoPolygon, GeoRadius, PayloadKeyType,
+    PointOffsetType,
 };
 
 /// Max number of sub-regions computed for an input geo query
 // TODO discuss value, should it be dynamically computed?
 const GEO_QUERY_MAX_REGION: usize = 12;
 
+/// Max number of polygon coverings kept by the index
+const POLYGON_COVERINGS_CACHE_SIZE: usize = 128;
+
+/// Bit representation of polygon coordinates, each line is prefixed with the number of its points
+type PolygonCacheKey = Vec<u64>;
+
 pub struct GeoMapIndex {
     /**
     {
@@ -54,6 +64,9 @@ pub struct GeoMapIndex {
     points_count: usize,
     values_count: usize,
     max_values_per_point: usize,
+    /// Geo-hash coverings of recently queried polygons.
+    /// Coverings only depend on the polygon, so they stay valid while points are updated.
+    polygon_coverings: Mutex<HashMap<PolygonCacheKey, Vec<GeoHash>>>,
     db_wrapper: DatabaseColumnWrapper,
 }
 
@@ -69,6 +82,7 @@ impl GeoMapIndex {
             points_count: 0,
             values_count: 0,
             max_values_per_point: 1,
+            polygon_coverings: Default::default(),
             db_wrapper,
         }
     }
@@ -105,6 +119,18 @@ impl GeoMapIndex {
             self.point_to_values[idx as usize].push(geo_point);
             self.points_map.entry(geo_hash).or_default().insert(idx);
         }
+
+        let point_to_values = std::mem::take(&mut self.point_to_values);
+        for values in &point_to_values {
+            let geo_hashes = values
+                .iter()
+                .map(|point| encode_max_precision(point.lon, point.lat).unwrap())
+                .collect_vec();
+            self.values_count += geo_hashes.len();
+            self.max_values_per_point = self.max_values_per_point.max(geo_hashes.len());
+            self.increment_hash_counts(&geo_hashes);
+        }
+        self.point_to_values = point_to_values;
         Ok(true)
     }
 
@@ -169,6 +195,42 @@ impl GeoMapIndex {
             .unwrap_or(false)
     }
 
+    pub fn check_polygon(&self, idx: PointOffsetType, polygon: &GeoPolygon) -> bool {
+        self.get_values(idx)
+            .map(|values| values.iter().any(|x| polygon.check_point(x.lon, x.lat)))
+            .unwrap_or(false)
+    }
+
+    fn polygon_cache_key(polygon: &GeoPolygon) -> PolygonCacheKey {
+        let mut key = vec![];
+        for line in std::iter::once(&polygon.exterior).chain(polygon.interiors.iter().flatten()) {
+            key.push(line.points.len() as u64);
+            for point in &line.points {
+                key.push(point.lon.to_bits());
+                key.push(point.lat.to_bits());
+            }
+        }
+        key
+    }
+
+    /// Geo-hashes covering the polygon.
+    ///
+    /// Computing the covering of a complex polygon is expensive, while the same polygons
+    /// (e.g. delivery zones) tend to be queried over and over, so coverings are cached.
+    pub fn polygon_covering(&self, polygon: &GeoPolygon) -> Vec<GeoHash> {
+        let key = Self::polygon_cache_key(polygon);
+        if let Some(covering) = self.polygon_coverings.lock().get(&key) {
+            return covering.clone();
+        }
+        let covering = polygon_hashes(polygon, GEO_QUERY_MAX_REGION);
+        let mut polygon_coverings = self.polygon_coverings.lock();
+        if polygon_coverings.len() >= POLYGON_COVERINGS_CACHE_SIZE {
+            polygon_coverings.clear();
+        }
+        polygon_coverings.insert(key, covering.clone());
+        covering
+    }
+
     pub fn match_cardinality(&self, values: &[GeoHash]) -> CardinalityEstimation {
         let common_hash = common_hash_prefix(values);
 
@@ -211,10 +273,31 @@ impl GeoMapIndex {
     }
 
     pub fn get_telemetry_data(&self) -> PayloadIndexTelemetry {
+        let hashes_ram_usage: usize = self
+            .points_per_hash
+            .keys()
+            .chain(self.values_per_hash.keys())
+            .map(|hash| size_of::<GeoHash>() + hash.len() + size_of::<usize>())
+            .sum();
+        let points_map_ram_usage: usize = self
+            .points_map
+            .iter()
+            .map(|(hash, ids)| {
+                size_of::<GeoHash>()
+                    + hash.len()
+                    + size_of::<HashSet<PointOffsetType>>()
+                    + ids.len() * size_of::<PointOffsetType>()
+            })
+            .sum();
         PayloadIndexTelemetry {
             points_count: self.points_count,
             points_values_count: self.values_count,
-            histogram_bucket_size: None,
+            ram_usage_bytes: hashes_ram_usage
+                + points_map_ram_usage
+                + self.point_to_values.len() * size_of::<Vec<GeoPoint>>()
+                + self.values_count * size_of::<GeoPoint>(),
+            disk_usage_bytes: self.db_wrapper.disk_usage_bytes().unwrap_or_default(),
+            ..Default::default()
         }
     }
 
@@ -301,7 +384,6 @@ impl GeoMapIndex {
 
         self.point_to_values[idx as usize] = values.to_vec();
 
-        let mut seen_hashes: HashSet<&str> = Default::default();
         let mut geo_hashes = vec![];
 
         for added_point in values {
@@ -321,7 +403,19 @@ impl GeoMapIndex {
                 .entry(geo_hash.to_owned())
                 .or_insert_with(HashSet::new)
                 .insert(idx);
+        }
+        self.increment_hash_counts(&geo_hashes);
+
+        self.values_count += values.len();
+        self.points_count += 1;
+        self.max_values_per_point = self.max_values_per_point.max(values.len());
+        Ok(())
+    }
 
+    /// Account values of a single point in the statistics of all enclosing regions
+    fn increment_hash_counts(&mut self, geo_hashes: &[GeoHash]) {
+        let mut seen_hashes: HashSet<&str> = Default::default();
+        for geo_hash in geo_hashes {
             for i in 0..=geo_hash.len() {
                 let sub_geo_hash = &geo_hash[0..i];
                 match self.values_per_hash.get_mut(sub_geo_hash) {
@@ -345,11 +439,6 @@ impl GeoMapIndex {
                 }
             }
         }
-
-        self.values_count += values.len();
-        self.points_count += 1;
-        self.max_values_per_point = self.max_values_per_point.max(values.len());
-        Ok(())
     }
 
     fn get_stored_sub_regions(
@@ -481,6 +570,20 @@ impl PayloadFieldIndex for GeoMapIndex {
             )));
         }
 
+        if let Some(geo_polygon) = &condition.geo_polygon {
+            let geo_hashes = self.polygon_covering(geo_polygon);
+            let polygon = geo_polygon.to_polygon();
+            return Some(Box::new(self.get_iterator(geo_hashes).filter(
+                move |point| {
+                    self.point_to_values
+                        .get(*point as usize)
+                        .unwrap()
+                        .iter()
+                        .any(|point| polygon.contains(&Point::new(point.lon, point.lat)))
+                },
+            )));
+        }
+
         None
     }
 
@@ -503,6 +606,18 @@ impl PayloadFieldIndex for GeoMapIndex {
             return Some(estimation);
         }
 
+        if let Some(geo_polygon) = &condition.geo_polygon {
+            let geo_hashes = self.polygon_covering(geo_polygon);
+            if geo_hashes.is_empty() {
+                return Some(CardinalityEstimation::exact(0));
+            }
+            let mut estimation = self.match_cardinality(&geo_hashes);
+            estimation
+                .primary_clauses
+                .push(PrimaryCondition::Condition(condition.clone()));
+            return Some(estimation);
+        }
+
         None
     }
 
@@ -528,6 +643,43 @@ impl PayloadFieldIndex for GeoMapIndex {
     }
 }
 
+pub struct GeoMapIndexBuilder {
+    index: GeoMapIndex,
+    writer: DatabaseColumnBatchWriter,
+}
+
+impl GeoMapIndexBuilder {
+    pub fn new(index: GeoMapIndex) -> Self {
+        let writer = index.db_wrapper.batch_writer(INDEX_BUILDER_BATCH_SIZE);
+        Self { index, writer }
+    }
+}
+
+impl FieldIndexBuilderTrait for GeoMapIndexBuilder {
+    type FieldIndexType = GeoMapIndex;
+
+    fn init(&mut self) -> OperationResult<()> {
+        self.writer.recreate_column_family()
+    }
+
+    fn add_point(&mut self, id: PointOffsetType, payload: &Value) -> OperationResult<()> {
+        for geo_point in self.index.extract_values(payload) {
+            let geo_hash = encode_max_precision(geo_point.lon, geo_point.lat).unwrap();
+            self.writer.put(
+                GeoMapIndex::encode_db_key(&geo_hash, id),
+                GeoMapIndex::encode_db_value(&geo_point),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn finalize(mut self) -> OperationResult<GeoMapIndex> {
+        self.writer.flush()?;
+        self.index.load()?;
+        Ok(self.index)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
@@ -539,7 +691,7 @@ mod tests {
     use super::*;
     use crate::common::rocksdb_wrapper::open_db_with_existing_cf;
     use crate::fixtures::payload_fixtures::random_geo_payload;
-    use crate::types::GeoRadius;
+    use crate::types::{GeoLineString, GeoRadius};
 
     const NYC: GeoPoint = GeoPoint {
         lat: 40.75798,
@@ -652,6 +804,62 @@ mod tests {
         assert_eq!(matched_points, indexed_matched_points);
     }
 
+    #[test]
+    fn geo_polygon_filtering() {
+        // Square around NYC, with a hole in the middle
+        let square = |half_side: f64| GeoLineString {
+            points: vec![
+                GeoPoint {
+                    lon: NYC.lon - half_side,
+                    lat: NYC.lat - half_side,
+                },
+                GeoPoint {
+                    lon: NYC.lon + half_side,
+                    lat: NYC.lat - half_side,
+                },
+                GeoPoint {
+                    lon: NYC.lon + half_side,
+                    lat: NYC.lat + half_side,
+                },
+                GeoPoint {
+                    lon: NYC.lon - half_side,
+                    lat: NYC.lat + half_side,
+                },
+                GeoPoint {
+                    lon: NYC.lon - half_side,
+                    lat: NYC.lat - half_side,
+                },
+            ],
+        };
+        let geo_polygon = GeoPolygon {
+            exterior: square(20.0),
+            interiors: Some(vec![square(5.0)]),
+        };
+
+        let field_index = build_random_index(1000, 5);
+
+        let mut matched_points = (0..field_index.point_to_values.len() as PointOffsetType)
+            .filter(|idx| field_index.check_polygon(*idx, &geo_polygon))
+            .collect_vec();
+        assert!(!matched_points.is_empty());
+
+        let field_condition = FieldCondition::new_geo_polygon("test".to_string(), geo_polygon);
+
+        let card = field_index.estimate_cardinality(&field_condition).unwrap();
+        assert!(card.min <= matched_points.len());
+        assert!(card.max >= matched_points.len());
+
+        let mut indexed_matched_points =
+            field_index.filter(&field_condition).unwrap().collect_vec();
+
+        matched_points.sort_unstable();
+        indexed_matched_points.sort_unstable();
+        assert_eq!(matched_points, indexed_matched_points);
+
+        // Covering is computed once and reused by subsequent requests
+        assert_eq!(field_index.polygon_coverings.lock().len(), 1);
+    }
+
     #[test]
     fn test_payload_blocks() {
         let field_index = build_random_index(1000, 5);
@@ -807,4 +1015,42 @@ mod tests {
         let point_offsets = new_index.filter(&field_condition).unwrap().collect_vec();
         assert_eq!(point_offsets, vec![1]);
     }
+
+    #[test]
+    fn build_with_builder() {
+        let tmp_dir = Builder::new().prefix("test_dir").tempdir().unwrap();
+        let db = open_db_with_existing_cf(&tmp_dir.path().join("test_db")).unwrap();
+
+        let num_points = 1000;
+        let mut rnd = StdRng::seed_from_u64(42);
+        let payloads = (0..num_points)
+            .map(|_| Value::Array(random_geo_payload(&mut rnd, 1..=3)))
+            .collect_vec();
+
+        let mut index = GeoMapIndex::new(db.clone(), "test_direct");
+        index.recreate().unwrap();
+        for (idx, payload) in payloads.iter().enumerate() {
+            index.add_point(idx as PointOffsetType, payload).unwrap();
+        }
+
+        let mut builder = GeoMapIndexBuilder::new(GeoMapIndex::new(db, "test_built"));
+        builder.init().unwrap();
+        for (idx, payload) in payloads.iter().enumerate() {
+            builder.add_point(idx as PointOffsetType, payload).unwrap();
+        }
+        let built_index = builder.finalize().unwrap();
+
+        assert_eq!(built_index.points_count, index.points_count);
+        assert_eq!(built_index.values_count, index.values_count);
+        assert_eq!(built_index.max_values_per_point, index.max_values_per_point);
+        assert_eq!(built_index.points_per_hash, index.points_per_hash);
+        assert_eq!(built_index.values_per_hash, index.values_per_hash);
+        assert_eq!(built_index.points_map, index.points_map);
+        for idx in 0..num_points as PointOffsetType {
+            assert_eq!(
+                built_index.get_values(idx).map(|values| values.len()),
+                index.get_values(idx).map(|values| values.len()),
+            );
+        }
+    }
 }
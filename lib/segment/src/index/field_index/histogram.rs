@@ -64,10 +64,23 @@ impl Histogram {
         min(max(MIN_BUCKET_SIZE, bucket_size), self.max_bucket_size)
     }
 
+    pub fn buckets_count(&self) -> usize {
+        self.borders.len()
+    }
+
     pub fn get_total_count(&self) -> usize {
         self.total_count
     }
 
+    /// Smallest and largest stored values, extreme values are always kept as borders.
+    ///
+    /// Returns None if there are no points stored
+    pub fn value_range(&self) -> Option<(f64, f64)> {
+        let first = self.borders.keys().next()?;
+        let last = self.borders.keys().next_back()?;
+        Some((first.val, last.val))
+    }
+
     /// Infers boundaries for bucket of given size and staring point.
     /// Returns `to` range of values starting provided `from`value which is expected to contain
     /// `range_size` values
@@ -3,34 +3,68 @@ use std::sync::Arc;
 use parking_lot::RwLock;
 use rocksdb::DB;
 
+use crate::data_types::numeric_index::NumericIndexType;
 use crate::index::field_index::full_text_index::text_index::FullTextIndex;
 use crate::index::field_index::geo_index::GeoMapIndex;
 use crate::index::field_index::map_index::MapIndex;
-use crate::index::field_index::numeric_index::NumericIndex;
-use crate::index::field_index::FieldIndex;
-use crate::types::{
-    FloatPayloadType, IntPayloadType, PayloadFieldSchema, PayloadSchemaParams, PayloadSchemaType,
+use crate::index::field_index::null_index::NullIndex;
+use crate::index::field_index::numeric_index::{
+    FromRangeValue, KeyDecoder, KeyEncoder, NumericIndex, ToRangeValue,
 };
+use crate::index::field_index::FieldIndex;
+use crate::types::{PayloadFieldSchema, PayloadSchemaParams, PayloadSchemaType};
 
 /// Selects index types based on field type
+///
+/// Map and numeric indexes of non-appendable segments are created immutable,
+/// which packs them into sorted arrays on load.
+/// Every indexed field also tracks empty and `null` values with `NullIndex`.
+/// Composite indexes are not bound to a single field, so they are maintained separately.
 pub fn index_selector(
     field: &str,
     payload_schema: &PayloadFieldSchema,
     db: Arc<RwLock<DB>>,
+    is_appendable: bool,
+) -> Vec<FieldIndex> {
+    if matches!(
+        payload_schema,
+        PayloadFieldSchema::FieldType(PayloadSchemaType::Composite)
+            | PayloadFieldSchema::FieldParams(PayloadSchemaParams::Composite(_))
+    ) {
+        return vec![];
+    }
+    let null_index = FieldIndex::NullIndex(NullIndex::new(db.clone(), field));
+    let mut indexes = value_index_selector(field, payload_schema, db, is_appendable);
+    indexes.push(null_index);
+    indexes
+}
+
+fn value_index_selector(
+    field: &str,
+    payload_schema: &PayloadFieldSchema,
+    db: Arc<RwLock<DB>>,
+    is_appendable: bool,
 ) -> Vec<FieldIndex> {
     match payload_schema {
         PayloadFieldSchema::FieldType(payload_type) => match payload_type {
             PayloadSchemaType::Keyword => {
-                vec![FieldIndex::KeywordIndex(MapIndex::new(db, field))]
+                vec![FieldIndex::KeywordIndex(map_index(
+                    db,
+                    field,
+                    is_appendable,
+                ))]
             }
             PayloadSchemaType::Integer => vec![
-                FieldIndex::IntMapIndex(MapIndex::<IntPayloadType>::new(db.clone(), field)),
-                FieldIndex::IntIndex(NumericIndex::<IntPayloadType>::new(db, field)),
+                FieldIndex::IntMapIndex(map_index(db.clone(), field, is_appendable)),
+                FieldIndex::IntIndex(numeric_index(db, field, is_appendable, false)),
             ],
             PayloadSchemaType::Float => {
-                vec![FieldIndex::FloatIndex(
-                    NumericIndex::<FloatPayloadType>::new(db, field),
-                )]
+                vec![FieldIndex::FloatIndex(numeric_index(
+                    db,
+                    field,
+                    is_appendable,
+                    false,
+                ))]
             }
             PayloadSchemaType::Geo => vec![FieldIndex::GeoIndex(GeoMapIndex::new(db, field))],
             PayloadSchemaType::Text => vec![FieldIndex::FullTextIndex(FullTextIndex::new(
@@ -38,11 +72,59 @@ pub fn index_selector(
                 Default::default(),
                 field,
             ))],
+            PayloadSchemaType::Composite => vec![],
         },
         PayloadFieldSchema::FieldParams(payload_params) => match payload_params {
             PayloadSchemaParams::Text(text_index_params) => vec![FieldIndex::FullTextIndex(
                 FullTextIndex::new(db, text_index_params.clone(), field),
             )],
+            PayloadSchemaParams::Numeric(numeric_index_params) => {
+                let on_disk = numeric_index_params.is_on_disk();
+                match numeric_index_params.r#type {
+                    // `on_disk` only applies to the range index, match index is kept in RAM
+                    NumericIndexType::Integer => vec![
+                        FieldIndex::IntMapIndex(map_index(db.clone(), field, is_appendable)),
+                        FieldIndex::IntIndex(numeric_index(db, field, is_appendable, on_disk)),
+                    ],
+                    NumericIndexType::Float => {
+                        vec![FieldIndex::FloatIndex(numeric_index(
+                            db,
+                            field,
+                            is_appendable,
+                            on_disk,
+                        ))]
+                    }
+                }
+            }
+            PayloadSchemaParams::Composite(_) => vec![],
         },
     }
 }
+
+fn map_index<N>(db: Arc<RwLock<DB>>, field: &str, is_appendable: bool) -> MapIndex<N>
+where
+    N: std::hash::Hash + Eq + Clone + std::fmt::Display + std::str::FromStr,
+{
+    if is_appendable {
+        MapIndex::new(db, field)
+    } else {
+        MapIndex::new_immutable(db, field)
+    }
+}
+
+fn numeric_index<T>(
+    db: Arc<RwLock<DB>>,
+    field: &str,
+    is_appendable: bool,
+    on_disk: bool,
+) -> NumericIndex<T>
+where
+    T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone,
+{
+    // On-disk index doesn't keep keys in RAM, so there is nothing to pack
+    if is_appendable || on_disk {
+        NumericIndex::new_with_storage(db, field, on_disk)
+    } else {
+        NumericIndex::new_immutable(db, field)
+    }
+}
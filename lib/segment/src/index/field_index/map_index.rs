@@ -2,6 +2,8 @@ use std::collections::{BTreeSet, HashMap};
 use std::fmt::Display;
 use std::hash::Hash;
 use std::iter;
+use std::mem::size_of;
+use std::ops::Range;
 use std::str::FromStr;
 use std::sync::Arc;
 
@@ -9,11 +11,12 @@ use parking_lot::RwLock;
 use rocksdb::DB;
 use serde_json::Value;
 
-use crate::common::rocksdb_wrapper::DatabaseColumnWrapper;
+use crate::common::rocksdb_wrapper::{DatabaseColumnBatchWriter, DatabaseColumnWrapper};
 use crate::common::Flusher;
 use crate::entry::entry_point::{OperationError, OperationResult};
 use crate::index::field_index::{
-    CardinalityEstimation, PayloadBlockCondition, PayloadFieldIndex, PrimaryCondition, ValueIndexer,
+    CardinalityEstimation, FieldIndexBuilderTrait, PayloadBlockCondition, PayloadFieldIndex,
+    PrimaryCondition, ValueIndexer, INDEX_BUILDER_BATCH_SIZE,
 };
 use crate::telemetry::PayloadIndexTelemetry;
 use crate::types::{
@@ -21,9 +24,137 @@ use crate::types::{
     ValueVariants,
 };
 
+/// Mapping of values into points, which have this value
+enum MapIndexStorage<N: Hash + Eq + Clone + Display> {
+    /// Set of points per value, supports updates
+    Mutable(HashMap<N, BTreeSet<PointOffsetType>>),
+    /// Sorted points of all values packed into a single container.
+    /// Used for read-mostly segments, which only allow removal of points.
+    Immutable {
+        value_to_points: HashMap<N, Range<u32>>,
+        points: Vec<PointOffsetType>,
+    },
+}
+
+impl<N: Hash + Eq + Clone + Display> MapIndexStorage<N> {
+    fn pack(map: HashMap<N, Vec<PointOffsetType>>) -> Self {
+        let mut points = Vec::with_capacity(map.values().map(|ids| ids.len()).sum());
+        let value_to_points = map
+            .into_iter()
+            .map(|(value, mut ids)| {
+                ids.sort_unstable();
+                ids.dedup();
+                let start = points.len() as u32;
+                points.extend(ids);
+                (value, start..points.len() as u32)
+            })
+            .collect();
+        MapIndexStorage::Immutable {
+            value_to_points,
+            points,
+        }
+    }
+
+    fn points_count(&self, value: &N) -> usize {
+        match self {
+            MapIndexStorage::Mutable(map) => map.get(value).map(|ids| ids.len()).unwrap_or(0),
+            MapIndexStorage::Immutable {
+                value_to_points, ..
+            } => value_to_points
+                .get(value)
+                .map(|range| range.len())
+                .unwrap_or(0),
+        }
+    }
+
+    fn iter_points(&self, value: &N) -> Box<dyn Iterator<Item = PointOffsetType> + '_> {
+        match self {
+            MapIndexStorage::Mutable(map) => map
+                .get(value)
+                .map(|ids| {
+                    Box::new(ids.iter().copied()) as Box<dyn Iterator<Item = PointOffsetType>>
+                })
+                .unwrap_or_else(|| Box::new(iter::empty::<PointOffsetType>())),
+            MapIndexStorage::Immutable {
+                value_to_points,
+                points,
+            } => value_to_points
+                .get(value)
+                .map(|range| {
+                    Box::new(
+                        points[range.start as usize..range.end as usize]
+                            .iter()
+                            .copied(),
+                    ) as Box<dyn Iterator<Item = PointOffsetType>>
+                })
+                .unwrap_or_else(|| Box::new(iter::empty::<PointOffsetType>())),
+        }
+    }
+
+    /// Iterate over all values with the amount of points, which have this value
+    fn iter_counts(&self) -> Box<dyn Iterator<Item = (&N, usize)> + '_> {
+        match self {
+            MapIndexStorage::Mutable(map) => {
+                Box::new(map.iter().map(|(value, ids)| (value, ids.len())))
+            }
+            MapIndexStorage::Immutable {
+                value_to_points, ..
+            } => Box::new(
+                value_to_points
+                    .iter()
+                    .map(|(value, range)| (value, range.len())),
+            ),
+        }
+    }
+
+    /// Rough estimate of RAM taken by the mapping, heap data of values is not counted
+    fn ram_usage_bytes(&self) -> usize {
+        match self {
+            MapIndexStorage::Mutable(map) => map
+                .values()
+                .map(|ids| {
+                    size_of::<N>()
+                        + size_of::<BTreeSet<PointOffsetType>>()
+                        + ids.len() * size_of::<PointOffsetType>()
+                })
+                .sum(),
+            MapIndexStorage::Immutable {
+                value_to_points,
+                points,
+            } => {
+                value_to_points.len() * (size_of::<N>() + size_of::<Range<u32>>())
+                    + points.len() * size_of::<PointOffsetType>()
+            }
+        }
+    }
+
+    fn remove(&mut self, value: &N, idx: PointOffsetType) {
+        match self {
+            MapIndexStorage::Mutable(map) => {
+                if let Some(vals) = map.get_mut(value) {
+                    vals.remove(&idx);
+                }
+            }
+            MapIndexStorage::Immutable {
+                value_to_points,
+                points,
+            } => {
+                if let Some(range) = value_to_points.get_mut(value) {
+                    let value_points = &mut points[range.start as usize..range.end as usize];
+                    if let Ok(pos) = value_points.binary_search(&idx) {
+                        // Shift the tail of the range left, so the points stay sorted
+                        value_points.copy_within(pos + 1.., pos);
+                        range.end -= 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// HashMap-based type of index
 pub struct MapIndex<N: Hash + Eq + Clone + Display> {
-    map: HashMap<N, BTreeSet<PointOffsetType>>,
+    map: MapIndexStorage<N>,
     point_to_values: Vec<Vec<N>>,
     /// Amount of point which have at least one indexed payload value
     indexed_points: usize,
@@ -36,7 +167,7 @@ impl<N: Hash + Eq + Clone + Display + FromStr> MapIndex<N> {
         let store_cf_name = Self::storage_cf_name(field_name);
         let db_wrapper = DatabaseColumnWrapper::new(db, &store_cf_name);
         MapIndex {
-            map: Default::default(),
+            map: MapIndexStorage::Mutable(Default::default()),
             point_to_values: Vec::new(),
             indexed_points: 0,
             values_count: 0,
@@ -44,10 +175,22 @@ impl<N: Hash + Eq + Clone + Display + FromStr> MapIndex<N> {
         }
     }
 
+    /// Creates the index, which is packed into sorted arrays on load.
+    /// Immutable index can't be extended with new points, but allows to remove them.
+    pub fn new_immutable(db: Arc<RwLock<DB>>, field_name: &str) -> MapIndex<N> {
+        let mut index = Self::new(db, field_name);
+        index.map = MapIndexStorage::pack(Default::default());
+        index
+    }
+
     fn storage_cf_name(field: &str) -> String {
         format!("{field}_map")
     }
 
+    pub fn is_immutable(&self) -> bool {
+        matches!(self.map, MapIndexStorage::Immutable { .. })
+    }
+
     pub fn recreate(&self) -> OperationResult<()> {
         self.db_wrapper.recreate_column_family()
     }
@@ -57,6 +200,7 @@ impl<N: Hash + Eq + Clone + Display + FromStr> MapIndex<N> {
             return Ok(false);
         }
         self.indexed_points = 0;
+        let mut immutable_map: HashMap<N, Vec<PointOffsetType>> = Default::default();
         for (record, _) in self.db_wrapper.lock_db().iter()? {
             let record = std::str::from_utf8(&record).map_err(|_| {
                 OperationError::service_error("Index load error: UTF8 error while DB parsing")
@@ -70,7 +214,17 @@ impl<N: Hash + Eq + Clone + Display + FromStr> MapIndex<N> {
             }
             self.values_count += 1;
             self.point_to_values[idx as usize].push(value.clone());
-            self.map.entry(value).or_default().insert(idx);
+            match &mut self.map {
+                MapIndexStorage::Mutable(map) => {
+                    map.entry(value).or_default().insert(idx);
+                }
+                MapIndexStorage::Immutable { .. } => {
+                    immutable_map.entry(value).or_default().push(idx);
+                }
+            }
+        }
+        if self.is_immutable() {
+            self.map = MapIndexStorage::pack(immutable_map);
         }
         Ok(true)
     }
@@ -80,10 +234,7 @@ impl<N: Hash + Eq + Clone + Display + FromStr> MapIndex<N> {
     }
 
     pub fn match_cardinality(&self, value: &N) -> CardinalityEstimation {
-        let values_count = match self.map.get(value) {
-            None => 0,
-            Some(points) => points.len(),
-        };
+        let values_count = self.map.points_count(value);
 
         CardinalityEstimation {
             primary_clauses: vec![],
@@ -101,42 +252,63 @@ impl<N: Hash + Eq + Clone + Display + FromStr> MapIndex<N> {
         PayloadIndexTelemetry {
             points_count: self.indexed_points,
             points_values_count: self.values_count,
-            histogram_bucket_size: None,
+            distinct_values_count: Some(
+                self.map
+                    .iter_counts()
+                    .filter(|(_, count)| *count > 0)
+                    .count(),
+            ),
+            ram_usage_bytes: self.map.ram_usage_bytes()
+                + self.point_to_values.len() * size_of::<Vec<N>>()
+                + self.values_count * size_of::<N>(),
+            disk_usage_bytes: self.db_wrapper.disk_usage_bytes().unwrap_or_default(),
+            ..Default::default()
         }
     }
 
+    /// Replace values of the point.
+    /// Storage records of the point are updated with a single write batch.
     fn add_many_to_map(&mut self, idx: PointOffsetType, values: Vec<N>) -> OperationResult<()> {
-        if let Some(existing_vals) = self.get_values(idx) {
-            if !existing_vals.is_empty() {
-                self.remove_point(idx)?;
-            }
+        if self.is_immutable() {
+            return Err(OperationError::service_error(
+                "Immutable map index can't be updated",
+            ));
         }
 
-        if values.is_empty() {
-            return Ok(());
-        }
+        // Records of values, which are both removed and added, are kept
+        let mut db_updates: HashMap<String, bool> = self
+            .remove_from_map(idx)
+            .iter()
+            .map(|removed_value| (Self::encode_db_record(removed_value, idx), false))
+            .collect();
 
-        self.values_count += values.len();
-        if self.point_to_values.len() <= idx as usize {
-            self.point_to_values.resize(idx as usize + 1, Vec::new())
+        if !values.is_empty() {
+            self.values_count += values.len();
+            if self.point_to_values.len() <= idx as usize {
+                self.point_to_values.resize(idx as usize + 1, Vec::new())
+            }
+            for value in &values {
+                if let MapIndexStorage::Mutable(map) = &mut self.map {
+                    map.entry(value.clone()).or_default().insert(idx);
+                }
+                db_updates.insert(Self::encode_db_record(value, idx), true);
+            }
+            self.point_to_values[idx as usize] = values;
+            self.indexed_points += 1;
         }
-        self.point_to_values[idx as usize] = values.into_iter().collect();
-        for value in &self.point_to_values[idx as usize] {
-            let entry = self.map.entry(value.clone()).or_default();
-            entry.insert(idx);
 
-            let db_record = Self::encode_db_record(value, idx);
-            self.db_wrapper.put(&db_record, &[])?;
+        if db_updates.is_empty() {
+            return Ok(());
         }
-        self.indexed_points += 1;
-        Ok(())
+        self.db_wrapper.update_batch(
+            db_updates
+                .into_iter()
+                .map(|(db_record, is_added)| (db_record, is_added.then(Vec::<u8>::new))),
+        )
     }
 
-    fn get_iterator(&self, value: &N) -> Box<dyn Iterator<Item = PointOffsetType> + '_> {
-        self.map
-            .get(value)
-            .map(|ids| Box::new(ids.iter().copied()) as Box<dyn Iterator<Item = PointOffsetType>>)
-            .unwrap_or_else(|| Box::new(iter::empty::<PointOffsetType>()))
+    pub fn get_iterator(&self, value: &N) -> Box<dyn Iterator<Item = PointOffsetType> + '_> {
+        self.map.iter_points(value)
     }
 
     fn encode_db_record(value: &N, idx: PointOffsetType) -> String {
@@ -160,9 +332,10 @@ impl<N: Hash + Eq + Clone + Display + FromStr> MapIndex<N> {
         Ok((value, idx))
     }
 
-    fn remove_point(&mut self, idx: PointOffsetType) -> OperationResult<()> {
+    /// Remove point from in-memory structures, returns removed values
+    fn remove_from_map(&mut self, idx: PointOffsetType) -> Vec<N> {
         if self.point_to_values.len() <= idx as usize {
-            return Ok(());
+            return vec![];
         }
 
         let removed_values = std::mem::take(&mut self.point_to_values[idx as usize]);
@@ -173,14 +346,21 @@ impl<N: Hash + Eq + Clone + Display + FromStr> MapIndex<N> {
         self.values_count -= removed_values.len();
 
         for value in &removed_values {
-            if let Some(vals) = self.map.get_mut(value) {
-                vals.remove(&idx);
-            }
-            let key = MapIndex::encode_db_record(value, idx);
-            self.db_wrapper.remove(&key)?;
+            self.map.remove(value, idx);
         }
+        removed_values
+    }
 
-        Ok(())
+    fn remove_point(&mut self, idx: PointOffsetType) -> OperationResult<()> {
+        let removed_values = self.remove_from_map(idx);
+        if removed_values.is_empty() {
+            return Ok(());
+        }
+        self.db_wrapper.update_batch(
+            removed_values
+                .iter()
+                .map(|value| (MapIndex::encode_db_record(value, idx), None::<Vec<u8>>)),
+        )
     }
 }
 
@@ -235,11 +415,11 @@ impl PayloadFieldIndex for MapIndex<String> {
     ) -> Box<dyn Iterator<Item = PayloadBlockCondition> + '_> {
         let iter = self
             .map
-            .iter()
-            .filter(move |(_value, point_ids)| point_ids.len() > threshold)
-            .map(move |(value, point_ids)| PayloadBlockCondition {
+            .iter_counts()
+            .filter(move |(_value, count)| *count > threshold)
+            .map(move |(value, count)| PayloadBlockCondition {
                 condition: FieldCondition::new_match(key.clone(), value.to_owned().into()),
-                cardinality: point_ids.len(),
+                cardinality: count,
             });
         Box::new(iter)
     }
@@ -300,11 +480,11 @@ impl PayloadFieldIndex for MapIndex<IntPayloadType> {
     ) -> Box<dyn Iterator<Item = PayloadBlockCondition> + '_> {
         let iter = self
             .map
-            .iter()
-            .filter(move |(_value, point_ids)| point_ids.len() >= threshold)
-            .map(move |(value, point_ids)| PayloadBlockCondition {
+            .iter_counts()
+            .filter(move |(_value, count)| *count >= threshold)
+            .map(move |(value, count)| PayloadBlockCondition {
                 condition: FieldCondition::new_match(key.clone(), (*value).into()),
-                cardinality: point_ids.len(),
+                cardinality: count,
             });
         Box::new(iter)
     }
@@ -352,6 +532,49 @@ impl ValueIndexer<IntPayloadType> for MapIndex<IntPayloadType> {
     }
 }
 
+pub struct MapIndexBuilder<N: Hash + Eq + Clone + Display> {
+    index: MapIndex<N>,
+    writer: DatabaseColumnBatchWriter,
+}
+
+impl<N: Hash + Eq + Clone + Display + FromStr> MapIndexBuilder<N> {
+    pub fn new(index: MapIndex<N>) -> Self {
+        let writer = index.db_wrapper.batch_writer(INDEX_BUILDER_BATCH_SIZE);
+        Self { index, writer }
+    }
+
+    /// Add values of the point, which are already extracted from the payload
+    pub fn add_values(&mut self, id: PointOffsetType, values: &[N]) -> OperationResult<()> {
+        for value in values {
+            let db_record = MapIndex::encode_db_record(value, id);
+            self.writer.put(db_record, Vec::<u8>::new())?;
+        }
+        Ok(())
+    }
+}
+
+impl<N: Hash + Eq + Clone + Display + FromStr> FieldIndexBuilderTrait for MapIndexBuilder<N>
+where
+    MapIndex<N>: ValueIndexer<N>,
+{
+    type FieldIndexType = MapIndex<N>;
+
+    fn init(&mut self) -> OperationResult<()> {
+        self.writer.recreate_column_family()
+    }
+
+    fn add_point(&mut self, id: PointOffsetType, payload: &Value) -> OperationResult<()> {
+        let values = self.index.extract_values(payload);
+        self.add_values(id, &values)
+    }
+
+    fn finalize(mut self) -> OperationResult<MapIndex<N>> {
+        self.writer.flush()?;
+        self.index.load()?;
+        Ok(self.index)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
@@ -359,6 +582,8 @@ mod tests {
     use std::iter::FromIterator;
     use std::path::Path;
 
+    use itertools::Itertools;
+    use serde_json::json;
     use tempfile::Builder;
 
     use super::*;
@@ -399,6 +624,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_immutable_map_index() {
+        let data: Vec<Vec<IntPayloadType>> =
+            vec![vec![1, 2, 3], vec![2, 3], vec![3], vec![], vec![1, 3, 5]];
+
+        let tmp_dir = Builder::new().prefix("store_dir").tempdir().unwrap();
+        save_map_index(&data, tmp_dir.path());
+
+        let mut index = MapIndex::<IntPayloadType>::new_immutable(
+            open_db_with_existing_cf(tmp_dir.path()).unwrap(),
+            FIELD_NAME,
+        );
+        index.load().unwrap();
+        assert!(index.is_immutable());
+        assert!(index.add_many_to_map(5, vec![1]).is_err());
+
+        assert_eq!(index.get_iterator(&3).collect::<Vec<_>>(), vec![0, 1, 2, 4]);
+        assert_eq!(index.get_iterator(&1).collect::<Vec<_>>(), vec![0, 4]);
+        assert_eq!(index.get_iterator(&7).count(), 0);
+
+        index.remove_point(1).unwrap();
+        assert_eq!(index.get_iterator(&3).collect::<Vec<_>>(), vec![0, 2, 4]);
+        assert_eq!(index.get_iterator(&2).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(index.match_cardinality(&3).exp, 3);
+        assert_eq!(index.count_indexed_points(), 3);
+    }
+
     #[test]
     fn test_int_disk_map_index() {
         let data = vec![
@@ -444,4 +696,74 @@ mod tests {
         save_map_index(&data, tmp_dir.path());
         load_map_index(&data, tmp_dir.path());
     }
+
+    #[test]
+    fn test_replace_point_values() {
+        let tmp_dir = Builder::new().prefix("store_dir").tempdir().unwrap();
+        {
+            let mut index = MapIndex::<String>::new(
+                open_db_with_existing_cf(tmp_dir.path()).unwrap(),
+                FIELD_NAME,
+            );
+            index.recreate().unwrap();
+            index.add_point(0, &json!(["a", "b"])).unwrap();
+            // Value "a" is both removed and added back by the same write batch
+            index.add_point(0, &json!(["b", "a"])).unwrap();
+            index.add_point(1, &json!(["b", "c"])).unwrap();
+            index.add_point(1, &json!("d")).unwrap();
+            index.add_point(2, &json!([])).unwrap();
+            assert_eq!(index.count_indexed_points(), 2);
+            index.flusher()().unwrap();
+        }
+
+        let data = vec![
+            vec!["b".to_string(), "a".to_string()],
+            vec!["d".to_string()],
+        ];
+        load_map_index(&data, tmp_dir.path());
+    }
+
+    #[test]
+    fn test_remove_point_then_filter() {
+        let data = vec![
+            vec!["red".to_string(), "blue".to_string()],
+            vec!["red".to_string()],
+            vec!["blue".to_string()],
+            vec!["red".to_string()],
+        ];
+        let tmp_dir = Builder::new().prefix("store_dir").tempdir().unwrap();
+        save_map_index(&data, tmp_dir.path());
+
+        let mut index = MapIndex::<String>::new(
+            open_db_with_existing_cf(tmp_dir.path()).unwrap(),
+            FIELD_NAME,
+        );
+        index.load().unwrap();
+        let red_condition =
+            FieldCondition::new_match(FIELD_NAME.to_string(), "red".to_string().into());
+
+        index.remove_point(1).unwrap();
+        // Other points with the same value are kept
+        let red_points = index.filter(&red_condition).unwrap().collect_vec();
+        assert_eq!(red_points, vec![0, 3]);
+        assert_eq!(index.estimate_cardinality(&red_condition).unwrap().exp, 2);
+        assert_eq!(index.count_indexed_points(), 3);
+
+        // Removal of a missing point changes nothing
+        index.remove_point(1).unwrap();
+        index.remove_point(100).unwrap();
+        assert_eq!(index.count_indexed_points(), 3);
+        index.flusher()().unwrap();
+        drop(index);
+
+        let mut index = MapIndex::<String>::new(
+            open_db_with_existing_cf(tmp_dir.path()).unwrap(),
+            FIELD_NAME,
+        );
+        index.load().unwrap();
+        let red_points = index.filter(&red_condition).unwrap().collect_vec();
+        assert_eq!(red_points, vec![0, 3]);
+        assert_eq!(index.count_indexed_points(), 3);
+        assert_eq!(index.get_telemetry_data().points_values_count, 4);
+    }
 }
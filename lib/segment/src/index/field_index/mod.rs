@@ -1,7 +1,9 @@
 use std::collections::HashSet;
 
-use crate::types::{FieldCondition, IsEmptyCondition, PointOffsetType};
+use crate::index::field_index::composite_index::CompositeMatch;
+use crate::types::{FieldCondition, IsEmptyCondition, IsNullCondition, PointOffsetType};
 
+pub mod composite_index;
 mod field_index_base;
 pub mod full_text_index;
 pub mod geo_hash;
@@ -9,7 +11,9 @@ pub mod geo_index;
 mod histogram;
 pub mod index_selector;
 pub mod map_index;
+pub mod null_index;
 pub mod numeric_index;
+pub mod resource_estimation;
 mod stat_tools;
 
 pub use field_index_base::*;
@@ -19,7 +23,9 @@ pub use field_index_base::*;
 pub enum PrimaryCondition {
     Condition(FieldCondition),
     IsEmpty(IsEmptyCondition),
+    IsNull(IsNullCondition),
     Ids(HashSet<PointOffsetType>),
+    Composite(CompositeMatch),
 }
 
 #[derive(Debug, Clone)]
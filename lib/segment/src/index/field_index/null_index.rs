@@ -0,0 +1,300 @@
+use std::iter;
+use std::sync::Arc;
+
+use bitvec::prelude::BitVec;
+use parking_lot::RwLock;
+use rocksdb::DB;
+use serde_json::Value;
+
+use crate::common::rocksdb_wrapper::{DatabaseColumnBatchWriter, DatabaseColumnWrapper};
+use crate::common::Flusher;
+use crate::entry::entry_point::{OperationError, OperationResult};
+use crate::index::field_index::{
+    CardinalityEstimation, FieldIndexBuilderTrait, PayloadBlockCondition, PayloadFieldIndex,
+    INDEX_BUILDER_BATCH_SIZE,
+};
+use crate::telemetry::PayloadIndexTelemetry;
+use crate::types::{FieldCondition, PayloadKeyType, PointOffsetType};
+
+/// Point has a value, which is not `null` or an empty array
+const HAS_VALUES_FLAG: u8 = 0b01;
+/// Point has the `null` value
+const IS_NULL_FLAG: u8 = 0b10;
+
+/// Tracks points with empty and `null` values of the field.
+///
+/// Points which were never added to the index don't have the field,
+/// so they are considered empty.
+pub struct NullIndex {
+    has_values: BitVec,
+    is_null: BitVec,
+    points_with_values: usize,
+    null_points: usize,
+    db_wrapper: DatabaseColumnWrapper,
+}
+
+impl NullIndex {
+    pub fn new(db: Arc<RwLock<DB>>, field: &str) -> Self {
+        let store_cf_name = Self::storage_cf_name(field);
+        let db_wrapper = DatabaseColumnWrapper::new(db, &store_cf_name);
+        Self {
+            has_values: BitVec::new(),
+            is_null: BitVec::new(),
+            points_with_values: 0,
+            null_points: 0,
+            db_wrapper,
+        }
+    }
+
+    fn storage_cf_name(field: &str) -> String {
+        format!("{field}_null")
+    }
+
+    fn encode_db_key(idx: PointOffsetType) -> [u8; 4] {
+        idx.to_be_bytes()
+    }
+
+    fn decode_db_key(key: &[u8]) -> OperationResult<PointOffsetType> {
+        let bytes = key
+            .try_into()
+            .map_err(|_| OperationError::service_error("Index db parsing error: wrong key"))?;
+        Ok(PointOffsetType::from_be_bytes(bytes))
+    }
+
+    /// Flags of the field value, consistent with `check_is_empty_condition` and `check_is_null_condition`
+    fn value_flags(value: &Value) -> u8 {
+        match value {
+            Value::Null => IS_NULL_FLAG,
+            Value::Array(values) if values.is_empty() => 0,
+            _ => HAS_VALUES_FLAG,
+        }
+    }
+
+    pub fn recreate(&self) -> OperationResult<()> {
+        self.db_wrapper.recreate_column_family()
+    }
+
+    fn set_flags(&mut self, idx: PointOffsetType, flags: u8) {
+        let idx = idx as usize;
+        if self.has_values.len() <= idx {
+            self.has_values.resize(idx + 1, false);
+            self.is_null.resize(idx + 1, false);
+        }
+        if flags & HAS_VALUES_FLAG != 0 {
+            self.has_values.set(idx, true);
+            self.points_with_values += 1;
+        }
+        if flags & IS_NULL_FLAG != 0 {
+            self.is_null.set(idx, true);
+            self.null_points += 1;
+        }
+    }
+
+    fn load(&mut self) -> OperationResult<bool> {
+        if !self.db_wrapper.has_column_family()? {
+            return Ok(false);
+        }
+        for (key, value) in self.db_wrapper.lock_db().iter()? {
+            let idx = Self::decode_db_key(&key)?;
+            let flags = value.first().copied().unwrap_or_default();
+            self.set_flags(idx, flags);
+        }
+        Ok(true)
+    }
+
+    pub fn flusher(&self) -> Flusher {
+        self.db_wrapper.flusher()
+    }
+
+    pub fn add_point(&mut self, idx: PointOffsetType, payload: &Value) -> OperationResult<()> {
+        self.remove_point(idx)?;
+        let flags = Self::value_flags(payload);
+        if flags != 0 {
+            self.db_wrapper.put(Self::encode_db_key(idx), [flags])?;
+            self.set_flags(idx, flags);
+        }
+        Ok(())
+    }
+
+    pub fn remove_point(&mut self, idx: PointOffsetType) -> OperationResult<()> {
+        let has_values = self.has_values(idx);
+        let is_null = self.is_null(idx);
+        if !has_values && !is_null {
+            return Ok(());
+        }
+        if has_values {
+            self.has_values.set(idx as usize, false);
+            self.points_with_values -= 1;
+        }
+        if is_null {
+            self.is_null.set(idx as usize, false);
+            self.null_points -= 1;
+        }
+        self.db_wrapper.remove(Self::encode_db_key(idx))
+    }
+
+    /// Point has at least one value of the field
+    pub fn has_values(&self, idx: PointOffsetType) -> bool {
+        self.has_values
+            .get(idx as usize)
+            .map(|flag| *flag)
+            .unwrap_or(false)
+    }
+
+    /// Point has the `null` value of the field
+    pub fn is_null(&self, idx: PointOffsetType) -> bool {
+        self.is_null
+            .get(idx as usize)
+            .map(|flag| *flag)
+            .unwrap_or(false)
+    }
+
+    pub fn iter_null(&self) -> impl Iterator<Item = PointOffsetType> + '_ {
+        self.is_null.iter_ones().map(|idx| idx as PointOffsetType)
+    }
+
+    pub fn points_with_values(&self) -> usize {
+        self.points_with_values
+    }
+
+    pub fn null_points(&self) -> usize {
+        self.null_points
+    }
+
+    pub fn get_telemetry_data(&self) -> PayloadIndexTelemetry {
+        PayloadIndexTelemetry {
+            points_count: self.points_with_values,
+            points_values_count: self.points_with_values,
+            ram_usage_bytes: (self.has_values.len() + self.is_null.len()) / 8,
+            disk_usage_bytes: self.db_wrapper.disk_usage_bytes().unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+impl PayloadFieldIndex for NullIndex {
+    fn indexed_points(&self) -> usize {
+        self.points_with_values
+    }
+
+    fn load(&mut self) -> OperationResult<bool> {
+        NullIndex::load(self)
+    }
+
+    fn clear(self) -> OperationResult<()> {
+        self.db_wrapper.remove_column_family()
+    }
+
+    fn flusher(&self) -> Flusher {
+        NullIndex::flusher(self)
+    }
+
+    fn filter(
+        &self,
+        _condition: &FieldCondition,
+    ) -> Option<Box<dyn Iterator<Item = PointOffsetType> + '_>> {
+        None
+    }
+
+    fn estimate_cardinality(&self, _condition: &FieldCondition) -> Option<CardinalityEstimation> {
+        None
+    }
+
+    fn payload_blocks(
+        &self,
+        _threshold: usize,
+        _key: PayloadKeyType,
+    ) -> Box<dyn Iterator<Item = PayloadBlockCondition> + '_> {
+        Box::new(iter::empty())
+    }
+
+    fn count_indexed_points(&self) -> usize {
+        self.points_with_values
+    }
+}
+
+pub struct NullIndexBuilder {
+    index: NullIndex,
+    writer: DatabaseColumnBatchWriter,
+}
+
+impl NullIndexBuilder {
+    pub fn new(index: NullIndex) -> Self {
+        let writer = index.db_wrapper.batch_writer(INDEX_BUILDER_BATCH_SIZE);
+        Self { index, writer }
+    }
+}
+
+impl FieldIndexBuilderTrait for NullIndexBuilder {
+    type FieldIndexType = NullIndex;
+
+    fn init(&mut self) -> OperationResult<()> {
+        self.writer.recreate_column_family()
+    }
+
+    fn add_point(&mut self, id: PointOffsetType, payload: &Value) -> OperationResult<()> {
+        let flags = NullIndex::value_flags(payload);
+        if flags != 0 {
+            self.writer.put(NullIndex::encode_db_key(id), [flags])?;
+        }
+        Ok(())
+    }
+
+    fn finalize(mut self) -> OperationResult<NullIndex> {
+        self.writer.flush()?;
+        self.index.load()?;
+        Ok(self.index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::common::rocksdb_wrapper::open_db_with_existing_cf;
+
+    #[test]
+    fn test_null_index() {
+        let tmp_dir = Builder::new().prefix("test_dir").tempdir().unwrap();
+        let payloads = [
+            json!(null),
+            json!([]),
+            json!(1),
+            json!([null]),
+            json!("text"),
+            json!(null),
+        ];
+
+        {
+            let db = open_db_with_existing_cf(&tmp_dir.path().join("test_db")).unwrap();
+            let mut index = NullIndex::new(db, "test");
+            index.recreate().unwrap();
+            for (idx, payload) in payloads.iter().enumerate() {
+                index.add_point(idx as PointOffsetType, payload).unwrap();
+            }
+            // Overwrite `null` with a value
+            index.add_point(5, &json!(5)).unwrap();
+            index.flusher()().unwrap();
+        }
+
+        let db = open_db_with_existing_cf(&tmp_dir.path().join("test_db")).unwrap();
+        let mut index = NullIndex::new(db, "test");
+        assert!(index.load().unwrap());
+
+        assert_eq!(index.iter_null().collect::<Vec<_>>(), vec![0]);
+        assert_eq!(index.null_points(), 1);
+        assert_eq!(index.points_with_values(), 4);
+        assert!(!index.has_values(0));
+        assert!(!index.has_values(1));
+        assert!(index.has_values(3));
+        assert!(index.has_values(5));
+        // Not added points don't have values
+        assert!(!index.has_values(100));
+
+        index.remove_point(2).unwrap();
+        assert!(!index.has_values(2));
+        assert_eq!(index.points_with_values(), 3);
+    }
+}
@@ -1,19 +1,27 @@
+use std::borrow::Cow;
 use std::cmp::{max, min};
 use std::collections::BTreeMap;
+use std::mem::size_of;
+use std::ops::Bound;
 use std::ops::Bound::{Excluded, Included, Unbounded};
 use std::sync::Arc;
 
+use bitvec::prelude::BitVec;
+use itertools::Itertools;
 use parking_lot::RwLock;
 use rocksdb::DB;
 use serde_json::Value;
 
-use crate::common::rocksdb_wrapper::DatabaseColumnWrapper;
+use crate::common::rocksdb_wrapper::{
+    DatabaseColumnBatchWriter, DatabaseColumnWrapper, LockedDatabaseColumnWrapper,
+};
 use crate::common::Flusher;
 use crate::entry::entry_point::{OperationError, OperationResult};
 use crate::index::field_index::histogram::{Histogram, Point};
 use crate::index::field_index::stat_tools::estimate_multi_value_selection_cardinality;
 use crate::index::field_index::{
-    CardinalityEstimation, PayloadBlockCondition, PayloadFieldIndex, PrimaryCondition, ValueIndexer,
+    CardinalityEstimation, FieldIndexBuilderTrait, PayloadBlockCondition, PayloadFieldIndex,
+    PrimaryCondition, ValueIndexer, INDEX_BUILDER_BATCH_SIZE,
 };
 use crate::index::key_encoding::{
     decode_f64_key_ascending, decode_i64_key_ascending, encode_f64_key_ascending,
@@ -26,6 +34,8 @@ use crate::types::{
 
 const HISTOGRAM_MAX_BUCKET_SIZE: usize = 10_000;
 const HISTOGRAM_PRECISION: f64 = 0.01;
+/// Number of keys read from the disk at once by range filter of on-disk index
+const DB_RANGE_BATCH_SIZE: usize = 1024;
 
 pub trait KeyEncoder: Clone {
     fn encode_key(&self, id: PointOffsetType) -> Vec<u8>;
@@ -91,21 +101,226 @@ impl ToRangeValue for IntPayloadType {
     }
 }
 
+/// Sorted `(value, point)` pairs of a read-mostly segment, packed into a single array.
+/// Pairs are never added, removed pairs are only marked.
+struct ImmutableNumericStorage<T> {
+    /// Pairs sorted in the order of encoded keys
+    pairs: Vec<(T, PointOffsetType)>,
+    removed: BitVec,
+    removed_count: usize,
+}
+
+impl<T: KeyEncoder> ImmutableNumericStorage<T> {
+    fn new(pairs: Vec<(T, PointOffsetType)>) -> Self {
+        let removed = BitVec::repeat(false, pairs.len());
+        Self {
+            pairs,
+            removed,
+            removed_count: 0,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.pairs.len() - self.removed_count
+    }
+
+    /// Position of the first pair with the key which is greater or equal (`inclusive`)
+    /// or strictly greater than `key`
+    fn lower_position(&self, key: &[u8], inclusive: bool) -> usize {
+        self.pairs.partition_point(|(value, idx)| {
+            let pair_key = value.encode_key(*idx);
+            if inclusive {
+                pair_key.as_slice() < key
+            } else {
+                pair_key.as_slice() <= key
+            }
+        })
+    }
+
+    fn start_position(&self, bound: &Bound<Vec<u8>>) -> usize {
+        match bound {
+            Included(key) => self.lower_position(key, true),
+            Excluded(key) => self.lower_position(key, false),
+            Unbounded => 0,
+        }
+    }
+
+    fn end_position(&self, bound: &Bound<Vec<u8>>) -> usize {
+        match bound {
+            Included(key) => self.lower_position(key, false),
+            Excluded(key) => self.lower_position(key, true),
+            Unbounded => self.pairs.len(),
+        }
+    }
+
+    /// Marks the pair as removed, returns `true` if the pair was present
+    fn remove(&mut self, key: &[u8]) -> bool {
+        let position = self.lower_position(key, true);
+        match self.pairs.get(position) {
+            Some((value, idx)) if value.encode_key(*idx) == key && !self.removed[position] => {
+                self.removed.set(position, true);
+                self.removed_count += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn left_neighbor(&self, key: &[u8]) -> Option<&(T, PointOffsetType)> {
+        let position = self.lower_position(key, true);
+        (0..position)
+            .rev()
+            .find(|&i| !self.removed[i])
+            .map(|i| &self.pairs[i])
+    }
+
+    fn right_neighbor(&self, key: &[u8]) -> Option<&(T, PointOffsetType)> {
+        let position = self.lower_position(key, false);
+        (position..self.pairs.len())
+            .find(|&i| !self.removed[i])
+            .map(|i| &self.pairs[i])
+    }
+
+    fn range(
+        &self,
+        start_bound: &Bound<Vec<u8>>,
+        end_bound: &Bound<Vec<u8>>,
+    ) -> impl Iterator<Item = PointOffsetType> + '_ {
+        let start = self.start_position(start_bound);
+        let end = self.end_position(end_bound).max(start);
+        (start..end)
+            .filter(|&i| !self.removed[i])
+            .map(|i| self.pairs[i].1)
+    }
+}
+
+/// Location of sorted keys of the numeric index
+enum NumericIndexStorage<T> {
+    /// Keys are kept in RAM and can be updated
+    InMemory(BTreeMap<Vec<u8>, PointOffsetType>),
+    /// Keys are packed into a sorted array on load, only removal is allowed
+    Immutable(ImmutableNumericStorage<T>),
+    /// Keys are read from the disk.
+    /// Values of points are read from the column, which maps point ids to their keys.
+    OnDisk(DatabaseColumnWrapper),
+}
+
+/// Reads point ids of the keys within the range from the disk batch by batch.
+/// Database is locked only while a batch is read.
+struct DatabaseRangeIterator<'a> {
+    db_wrapper: &'a DatabaseColumnWrapper,
+    start_bound: Bound<Vec<u8>>,
+    end_bound: Bound<Vec<u8>>,
+    batch: std::vec::IntoIter<PointOffsetType>,
+    is_finished: bool,
+}
+
+impl<'a> DatabaseRangeIterator<'a> {
+    fn new(
+        db_wrapper: &'a DatabaseColumnWrapper,
+        start_bound: Bound<Vec<u8>>,
+        end_bound: Bound<Vec<u8>>,
+    ) -> Self {
+        Self {
+            db_wrapper,
+            start_bound,
+            end_bound,
+            batch: Vec::new().into_iter(),
+            is_finished: false,
+        }
+    }
+
+    /// Reads the next batch and moves the start bound past its last key
+    fn read_batch(&mut self) -> OperationResult<Vec<PointOffsetType>> {
+        let db_wrapper = self.db_wrapper;
+        let db = db_wrapper.lock_db();
+        let iter = match &self.start_bound {
+            Included(start) | Excluded(start) => db.iter()?.seek(start),
+            Unbounded => db.iter()?,
+        };
+        let mut ids = Vec::with_capacity(DB_RANGE_BATCH_SIZE);
+        let mut last_key = None;
+        for (key, value) in iter
+            .skip_while(|(key, _)| match &self.start_bound {
+                Excluded(start) => key.as_ref() == start.as_slice(),
+                _ => false,
+            })
+            .take_while(|(key, _)| match &self.end_bound {
+                Included(end) => key.as_ref() <= end.as_slice(),
+                Excluded(end) => key.as_ref() < end.as_slice(),
+                Unbounded => true,
+            })
+            .take(DB_RANGE_BATCH_SIZE)
+        {
+            ids.push(PointOffsetType::from_be_bytes(
+                value.as_ref().try_into().unwrap(),
+            ));
+            last_key = Some(key);
+        }
+        match last_key {
+            Some(key) if ids.len() == DB_RANGE_BATCH_SIZE => {
+                self.start_bound = Excluded(key.to_vec());
+            }
+            _ => self.is_finished = true,
+        }
+        Ok(ids)
+    }
+}
+
+impl Iterator for DatabaseRangeIterator<'_> {
+    type Item = PointOffsetType;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(id) = self.batch.next() {
+                return Some(id);
+            }
+            if self.is_finished {
+                return None;
+            }
+            match self.read_batch() {
+                Ok(ids) => self.batch = ids.into_iter(),
+                Err(err) => {
+                    log::error!("Can't read on-disk numeric index: {}", err);
+                    self.is_finished = true;
+                    return None;
+                }
+            }
+        }
+    }
+}
+
 pub struct NumericIndex<T: KeyEncoder + KeyDecoder + FromRangeValue + Clone> {
-    map: BTreeMap<Vec<u8>, u32>,
+    map: NumericIndexStorage<T>,
     db_wrapper: DatabaseColumnWrapper,
     histogram: Histogram,
     points_count: usize,
     max_values_per_point: usize,
+    /// Values of each point, empty for on-disk index
     point_to_values: Vec<Vec<T>>,
 }
 
 impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> NumericIndex<T> {
     pub fn new(db: Arc<RwLock<DB>>, field: &str) -> Self {
+        Self::new_with_storage(db, field, false)
+    }
+
+    /// Creates the index which keeps sorted keys and values of points either in RAM or on disk only.
+    /// On-disk index additionally stores values of each point in a separate column.
+    pub fn new_with_storage(db: Arc<RwLock<DB>>, field: &str, on_disk: bool) -> Self {
         let store_cf_name = Self::storage_cf_name(field);
+        let map = if on_disk {
+            let point_values_cf_name = Self::point_values_cf_name(field);
+            NumericIndexStorage::OnDisk(DatabaseColumnWrapper::new(
+                db.clone(),
+                &point_values_cf_name,
+            ))
+        } else {
+            NumericIndexStorage::InMemory(BTreeMap::new())
+        };
         let db_wrapper = DatabaseColumnWrapper::new(db, &store_cf_name);
         Self {
-            map: BTreeMap::new(),
+            map,
             db_wrapper,
             histogram: Histogram::new(HISTOGRAM_MAX_BUCKET_SIZE, HISTOGRAM_PRECISION),
             points_count: 0,
@@ -114,18 +329,122 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Numeric
         }
     }
 
+    /// Creates the index, which packs sorted keys into a single array on load.
+    /// Immutable index can't be extended with new points, but allows to remove them.
+    pub fn new_immutable(db: Arc<RwLock<DB>>, field: &str) -> Self {
+        let mut index = Self::new(db, field);
+        index.map = NumericIndexStorage::Immutable(ImmutableNumericStorage::new(vec![]));
+        index
+    }
+
     fn storage_cf_name(field: &str) -> String {
         format!("{field}_numeric")
     }
 
+    fn point_values_cf_name(field: &str) -> String {
+        format!("{field}_numeric_points")
+    }
+
     pub fn recreate(&self) -> OperationResult<()> {
+        if let NumericIndexStorage::OnDisk(point_values) = &self.map {
+            point_values.recreate_column_family()?;
+        }
         self.db_wrapper.recreate_column_family()
     }
 
+    pub fn is_on_disk(&self) -> bool {
+        matches!(self.map, NumericIndexStorage::OnDisk(_))
+    }
+
+    /// Key of the point values column: point id followed by the key of the value,
+    /// so values of a point are stored next to each other
+    fn point_values_key(id: PointOffsetType, key: &[u8]) -> Vec<u8> {
+        let mut point_values_key = id.to_be_bytes().to_vec();
+        point_values_key.extend_from_slice(key);
+        point_values_key
+    }
+
+    fn read_point_values(
+        point_values: &DatabaseColumnWrapper,
+        idx: PointOffsetType,
+    ) -> OperationResult<Vec<T>> {
+        let prefix = idx.to_be_bytes();
+        let values = point_values
+            .lock_db()
+            .iter()?
+            .seek(&prefix)
+            .map(|(key, _)| key)
+            .take_while(|key| key.starts_with(&prefix))
+            .map(|key| T::decode_key(&key[prefix.len()..]).1)
+            .collect();
+        Ok(values)
+    }
+
+    pub fn is_immutable(&self) -> bool {
+        matches!(self.map, NumericIndexStorage::Immutable(_))
+    }
+
     fn add_value(&mut self, id: PointOffsetType, value: T) -> OperationResult<()> {
         let key = value.encode_key(id);
-        self.db_wrapper.put(&key, id.to_be_bytes())?;
-        Self::add_to_map(&mut self.map, &mut self.histogram, key, id);
+        match &mut self.map {
+            NumericIndexStorage::InMemory(map) => {
+                self.db_wrapper.put(&key, id.to_be_bytes())?;
+                Self::add_to_map(map, &mut self.histogram, key, id);
+            }
+            NumericIndexStorage::Immutable(_) => {
+                return Err(OperationError::service_error(
+                    "Immutable numeric index can't be updated",
+                ));
+            }
+            NumericIndexStorage::OnDisk(point_values) => {
+                let existed = self.db_wrapper.get_pinned(&key, |_| ())?.is_some();
+                self.db_wrapper.put(&key, id.to_be_bytes())?;
+                point_values.put(Self::point_values_key(id, &key), b"")?;
+                if !existed {
+                    let db = self.db_wrapper.lock_db();
+                    self.histogram.insert(
+                        Self::key_to_histogram_point(&key),
+                        |x| Self::get_db_left_neighbor(&db, x),
+                        |x| Self::get_db_right_neighbor(&db, x),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_value(&mut self, key: Vec<u8>) -> OperationResult<()> {
+        match &mut self.map {
+            NumericIndexStorage::InMemory(map) => {
+                self.db_wrapper.remove(&key)?;
+                Self::remove_from_map(map, &mut self.histogram, key);
+            }
+            NumericIndexStorage::Immutable(storage) => {
+                self.db_wrapper.remove(&key)?;
+                if storage.remove(&key) {
+                    self.histogram.remove(
+                        &Self::key_to_histogram_point(&key),
+                        |x| Self::get_immutable_left_neighbor(storage, x),
+                        |x| Self::get_immutable_right_neighbor(storage, x),
+                    );
+                }
+            }
+            NumericIndexStorage::OnDisk(point_values) => {
+                let existed = self.db_wrapper.get_pinned(&key, |_| ())?.is_some();
+                // Histogram is updated first, so the removed key is still there to seek from
+                if existed {
+                    let db = self.db_wrapper.lock_db();
+                    self.histogram.remove(
+                        &Self::key_to_histogram_point(&key),
+                        |x| Self::get_db_left_neighbor(&db, x),
+                        |x| Self::get_db_right_neighbor(&db, x),
+                    );
+                }
+                self.db_wrapper.remove(&key)?;
+                let (idx, _) = T::decode_key(&key);
+                point_values.remove(Self::point_values_key(idx, &key))?;
+            }
+        }
         Ok(())
     }
 
@@ -134,15 +453,18 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Numeric
         idx: PointOffsetType,
         values: impl IntoIterator<Item = T>,
     ) -> OperationResult<()> {
-        if let Some(existing_vals) = self.get_values(idx) {
-            if !existing_vals.is_empty() {
-                self.remove_point(idx)?;
-            }
+        if self.is_immutable() {
+            return Err(OperationError::service_error(
+                "Immutable numeric index can't be updated",
+            ));
         }
-
-        if self.point_to_values.len() <= idx as usize {
-            self.point_to_values.resize(idx as usize + 1, Vec::new())
+        let has_values = self
+            .get_values(idx)
+            .map_or(false, |existing_vals| !existing_vals.is_empty());
+        if has_values {
+            self.remove_point(idx)?;
         }
+
         let values: Vec<T> = values.into_iter().collect();
         for value in &values {
             self.add_value(idx, value.clone())?;
@@ -151,7 +473,12 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Numeric
             self.points_count += 1;
             self.max_values_per_point = self.max_values_per_point.max(values.len());
         }
-        self.point_to_values[idx as usize] = values;
+        if !self.is_on_disk() {
+            if self.point_to_values.len() <= idx as usize {
+                self.point_to_values.resize(idx as usize + 1, Vec::new())
+            }
+            self.point_to_values[idx as usize] = values;
+        }
         Ok(())
     }
 
@@ -159,7 +486,15 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Numeric
         if !self.db_wrapper.has_column_family()? {
             return Ok(false);
         };
+        if let NumericIndexStorage::OnDisk(point_values) = &self.map {
+            if !point_values.has_column_family()? {
+                return Ok(false);
+            }
+        }
 
+        // Keys are loaded in ascending order, so the previous key is the only inserted neighbor
+        let mut previous_key: Option<Box<[u8]>> = None;
+        let mut immutable_pairs = vec![];
         for (key, value) in self.db_wrapper.lock_db().iter()? {
             let value_idx = u32::from_be_bytes(value.as_ref().try_into().unwrap());
             let (idx, value) = T::decode_key(&key);
@@ -168,38 +503,83 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Numeric
                 return Err(OperationError::service_error("incorrect key value"));
             }
 
-            if self.point_to_values.len() <= idx as usize {
-                self.point_to_values.resize(idx as usize + 1, Vec::new())
+            if !self.is_on_disk() {
+                if self.point_to_values.len() <= idx as usize {
+                    self.point_to_values.resize(idx as usize + 1, Vec::new())
+                }
+                self.point_to_values[idx as usize].push(value.clone());
             }
 
-            self.point_to_values[idx as usize].push(value);
-
-            Self::add_to_map(&mut self.map, &mut self.histogram, key.to_vec(), idx);
+            if let NumericIndexStorage::InMemory(map) = &mut self.map {
+                Self::add_to_map(map, &mut self.histogram, key.to_vec(), idx);
+            } else {
+                self.histogram.insert(
+                    Self::key_to_histogram_point(&key),
+                    |_| previous_key.as_deref().map(Self::key_to_histogram_point),
+                    |_| None,
+                );
+                if let NumericIndexStorage::Immutable(_) = self.map {
+                    immutable_pairs.push((value, idx));
+                }
+                previous_key = Some(key);
+            }
+        }
+        if let NumericIndexStorage::Immutable(storage) = &mut self.map {
+            *storage = ImmutableNumericStorage::new(immutable_pairs);
         }
-        for values in &self.point_to_values {
-            if !values.is_empty() {
+        let values_counts: Vec<usize> = match &self.map {
+            // Keys of the same point are stored next to each other, so they are counted in runs
+            NumericIndexStorage::OnDisk(point_values) => point_values
+                .lock_db()
+                .iter()?
+                .map(|(key, _)| {
+                    PointOffsetType::from_be_bytes(
+                        key[..size_of::<PointOffsetType>()].try_into().unwrap(),
+                    )
+                })
+                .dedup_with_count()
+                .map(|(count, _)| count)
+                .collect(),
+            _ => self.point_to_values.iter().map(Vec::len).collect(),
+        };
+        for values_count in values_counts {
+            if values_count > 0 {
                 self.points_count += 1;
-                self.max_values_per_point = self.max_values_per_point.max(values.len());
+                self.max_values_per_point = self.max_values_per_point.max(values_count);
             }
         }
         Ok(true)
     }
 
     pub fn flusher(&self) -> Flusher {
-        self.db_wrapper.flusher()
+        match &self.map {
+            NumericIndexStorage::OnDisk(point_values) => {
+                let keys_flusher = self.db_wrapper.flusher();
+                let point_values_flusher = point_values.flusher();
+                Box::new(move || {
+                    keys_flusher()?;
+                    point_values_flusher()
+                })
+            }
+            _ => self.db_wrapper.flusher(),
+        }
     }
 
     pub fn remove_point(&mut self, idx: PointOffsetType) -> OperationResult<()> {
-        if self.point_to_values.len() <= idx as usize {
-            return Ok(());
-        }
-
-        let removed_values = std::mem::take(&mut self.point_to_values[idx as usize]);
+        let removed_values = match &self.map {
+            NumericIndexStorage::OnDisk(point_values) => {
+                Self::read_point_values(point_values, idx)?
+            }
+            _ => {
+                if self.point_to_values.len() <= idx as usize {
+                    return Ok(());
+                }
+                std::mem::take(&mut self.point_to_values[idx as usize])
+            }
+        };
 
         for value in &removed_values {
-            let key = value.encode_key(idx);
-            self.db_wrapper.remove(&key)?;
-            Self::remove_from_map(&mut self.map, &mut self.histogram, key);
+            self.remove_value(value.encode_key(idx))?;
         }
 
         if !removed_values.is_empty() {
@@ -209,8 +589,28 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Numeric
         Ok(())
     }
 
-    pub fn get_values(&self, idx: PointOffsetType) -> Option<&Vec<T>> {
-        self.point_to_values.get(idx as usize)
+    /// Values of the point. On-disk index reads them from the disk, `None` if the read fails.
+    pub fn get_values(&self, idx: PointOffsetType) -> Option<Cow<'_, [T]>> {
+        match &self.map {
+            NumericIndexStorage::OnDisk(point_values) => {
+                match Self::read_point_values(point_values, idx) {
+                    Ok(values) => Some(Cow::Owned(values)),
+                    Err(err) => {
+                        log::error!("Can't read point values of on-disk numeric index: {}", err);
+                        None
+                    }
+                }
+            }
+            _ => self
+                .point_to_values
+                .get(idx as usize)
+                .map(|values| Cow::Borrowed(values.as_slice())),
+        }
+    }
+
+    /// Smallest and largest indexed values, `None` if the index is empty
+    pub fn values_range(&self) -> Option<(f64, f64)> {
+        self.histogram.value_range()
     }
 
     fn range_cardinality(&self, range: &Range) -> CardinalityEstimation {
@@ -234,7 +634,7 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Numeric
         let min_estimation = histogram_estimation.0;
         let max_estimation = histogram_estimation.2;
 
-        let total_values = self.map.len();
+        let total_values = self.values_count();
         // Example: points_count = 1000, total values = 2000, values_count = 500
         // min = max(1, 500 - (2000 - 1000)) = 1
         // exp = 500 / (2000 / 1000) = 250
@@ -302,6 +702,97 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Numeric
         }
     }
 
+    /// Number of unique (value, point) pairs in the index
+    fn values_count(&self) -> usize {
+        match &self.map {
+            NumericIndexStorage::InMemory(map) => map.len(),
+            NumericIndexStorage::Immutable(storage) => storage.len(),
+            NumericIndexStorage::OnDisk(_) => self.histogram.get_total_count(),
+        }
+    }
+
+    /// Finds the stored key of the histogram point.
+    /// Histogram keeps values as `f64`, which can't represent large integers exactly,
+    /// so the key is searched among the stored keys with the same `f64` value.
+    fn find_db_key(db: &LockedDatabaseColumnWrapper, point: &Point) -> Option<Box<[u8]>> {
+        let approx_key = T::from_range(point.val).encode_key(point.idx as PointOffsetType);
+        let is_same_value = |key: &[u8]| T::to_range(T::decode_key(key).1) == point.val;
+        let is_point = |key: &[u8]| T::decode_key(key).0 as usize == point.idx;
+
+        // Keys with the same `f64` value are stored next to each other around the approximate key
+        let following_key = db
+            .iter()
+            .ok()?
+            .seek(&approx_key)
+            .map(|(key, _)| key)
+            .take_while(|key| is_same_value(&key[..]))
+            .find(|key| is_point(&key[..]));
+        if following_key.is_some() {
+            return following_key;
+        }
+
+        let mut key = db.prev_key(&approx_key).ok()??;
+        while is_same_value(&key[..]) {
+            if is_point(&key[..]) {
+                return Some(key);
+            }
+            key = db.prev_key(&key).ok()??;
+        }
+        None
+    }
+
+    fn get_db_left_neighbor(db: &LockedDatabaseColumnWrapper, point: &Point) -> Option<Point> {
+        let key = Self::find_db_key(db, point)?;
+        db.prev_key(&key)
+            .ok()
+            .flatten()
+            .map(|key| Self::key_to_histogram_point(&key))
+    }
+
+    fn get_db_right_neighbor(db: &LockedDatabaseColumnWrapper, point: &Point) -> Option<Point> {
+        let key = Self::find_db_key(db, point)?;
+        db.next_key(&key)
+            .ok()
+            .flatten()
+            .map(|key| Self::key_to_histogram_point(&key))
+    }
+
+    fn get_immutable_left_neighbor(
+        storage: &ImmutableNumericStorage<T>,
+        point: &Point,
+    ) -> Option<Point> {
+        let key = T::from_range(point.val).encode_key(point.idx as PointOffsetType);
+        storage
+            .left_neighbor(&key)
+            .map(|(value, idx)| Self::pair_to_histogram_point(value, *idx))
+    }
+
+    fn get_immutable_right_neighbor(
+        storage: &ImmutableNumericStorage<T>,
+        point: &Point,
+    ) -> Option<Point> {
+        let key = T::from_range(point.val).encode_key(point.idx as PointOffsetType);
+        storage
+            .right_neighbor(&key)
+            .map(|(value, idx)| Self::pair_to_histogram_point(value, *idx))
+    }
+
+    fn pair_to_histogram_point(value: &T, idx: PointOffsetType) -> Point {
+        Point {
+            val: T::to_range(value.clone()),
+            idx: idx as usize,
+        }
+    }
+
+    /// Lazily reads point ids of the keys within the range from the disk
+    fn read_db_range(
+        &self,
+        start_bound: Bound<Vec<u8>>,
+        end_bound: Bound<Vec<u8>>,
+    ) -> impl Iterator<Item = PointOffsetType> + '_ {
+        DatabaseRangeIterator::new(&self.db_wrapper, start_bound, end_bound)
+    }
+
     fn key_to_histogram_point(key: &[u8]) -> Point {
         let (decoded_idx, decoded_val) = T::decode_key(key);
         Point {
@@ -331,10 +822,35 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Numeric
     }
 
     pub fn get_telemetry_data(&self) -> PayloadIndexTelemetry {
+        let keys_ram_usage: usize = match &self.map {
+            NumericIndexStorage::InMemory(map) => map
+                .keys()
+                .map(|key| size_of::<Vec<u8>>() + key.len() + size_of::<PointOffsetType>())
+                .sum(),
+            NumericIndexStorage::Immutable(storage) => {
+                storage.pairs.len() * size_of::<(T, PointOffsetType)>() + storage.removed.len() / 8
+            }
+            NumericIndexStorage::OnDisk(_) => 0,
+        };
+        let values_count = self.histogram.get_total_count();
+        let (point_values_ram_usage, point_values_disk_usage) = match &self.map {
+            NumericIndexStorage::OnDisk(point_values) => {
+                (0, point_values.disk_usage_bytes().unwrap_or_default())
+            }
+            _ => (
+                self.point_to_values.len() * size_of::<Vec<T>>() + values_count * size_of::<T>(),
+                0,
+            ),
+        };
         PayloadIndexTelemetry {
             points_count: self.points_count,
-            points_values_count: self.histogram.get_total_count(),
+            points_values_count: values_count,
             histogram_bucket_size: Some(self.histogram.current_bucket_size()),
+            histogram_buckets_count: Some(self.histogram.buckets_count()),
+            ram_usage_bytes: keys_ram_usage + point_values_ram_usage,
+            disk_usage_bytes: self.db_wrapper.disk_usage_bytes().unwrap_or_default()
+                + point_values_disk_usage,
+            ..Default::default()
         }
     }
 }
@@ -351,7 +867,7 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Payload
     }
 
     fn clear(self) -> OperationResult<()> {
-        self.db_wrapper.recreate_column_family()
+        NumericIndex::recreate(&self)
     }
 
     fn flusher(&self) -> Flusher {
@@ -402,9 +918,17 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Payload
             _ => {}
         }
 
-        Some(Box::new(
-            self.map.range((start_bound, end_bound)).map(|(_, v)| *v),
-        ))
+        match &self.map {
+            NumericIndexStorage::InMemory(map) => Some(Box::new(
+                map.range((start_bound, end_bound)).map(|(_, v)| *v),
+            )),
+            NumericIndexStorage::Immutable(storage) => {
+                Some(Box::new(storage.range(&start_bound, &end_bound)))
+            }
+            NumericIndexStorage::OnDisk(_) => {
+                Some(Box::new(self.read_db_range(start_bound, end_bound)))
+            }
+        }
     }
 
     fn estimate_cardinality(&self, condition: &FieldCondition) -> Option<CardinalityEstimation> {
@@ -426,7 +950,7 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Payload
         let mut pre_lower_bound = None;
         let mut payload_conditions = Vec::new();
 
-        let value_per_point = self.map.len() as f64 / self.points_count as f64;
+        let value_per_point = self.values_count() as f64 / self.points_count as f64;
         let effective_threshold = (threshold as f64 * value_per_point) as usize;
 
         loop {
@@ -534,6 +1058,66 @@ impl ValueIndexer<FloatPayloadType> for NumericIndex<FloatPayloadType> {
     }
 }
 
+pub struct NumericIndexBuilder<T: KeyEncoder + KeyDecoder + FromRangeValue + Clone> {
+    index: NumericIndex<T>,
+    writer: DatabaseColumnBatchWriter,
+    /// Writer of the point values column of on-disk index
+    point_values_writer: Option<DatabaseColumnBatchWriter>,
+}
+
+impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> NumericIndexBuilder<T> {
+    pub fn new(index: NumericIndex<T>) -> Self {
+        let writer = index.db_wrapper.batch_writer(INDEX_BUILDER_BATCH_SIZE);
+        let point_values_writer = match &index.map {
+            NumericIndexStorage::OnDisk(point_values) => {
+                Some(point_values.batch_writer(INDEX_BUILDER_BATCH_SIZE))
+            }
+            _ => None,
+        };
+        Self {
+            index,
+            writer,
+            point_values_writer,
+        }
+    }
+}
+
+impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> FieldIndexBuilderTrait
+    for NumericIndexBuilder<T>
+where
+    NumericIndex<T>: ValueIndexer<T>,
+{
+    type FieldIndexType = NumericIndex<T>;
+
+    fn init(&mut self) -> OperationResult<()> {
+        if let Some(point_values_writer) = &self.point_values_writer {
+            point_values_writer.recreate_column_family()?;
+        }
+        self.writer.recreate_column_family()
+    }
+
+    fn add_point(&mut self, id: PointOffsetType, payload: &Value) -> OperationResult<()> {
+        for value in self.index.extract_values(payload) {
+            let key = value.encode_key(id);
+            if let Some(point_values_writer) = &mut self.point_values_writer {
+                point_values_writer
+                    .put(NumericIndex::<T>::point_values_key(id, &key), Vec::new())?;
+            }
+            self.writer.put(key, id.to_be_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn finalize(mut self) -> OperationResult<NumericIndex<T>> {
+        self.writer.flush()?;
+        if let Some(point_values_writer) = &mut self.point_values_writer {
+            point_values_writer.flush()?;
+        }
+        self.index.load()?;
+        Ok(self.index)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
@@ -651,6 +1235,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cardinality_exp_accuracy() {
+        let num_points = 1000;
+        let (_tmp_dir, index) = random_index(num_points, 1);
+        let db = index.db_wrapper.database.clone();
+        let mut immutable_index: NumericIndex<f64> = NumericIndex::new_immutable(db, COLUMN_NAME);
+        immutable_index.load().unwrap();
+
+        // Values are uniform in [0, 100), so histogram should give estimation close to exact
+        let max_error = num_points / 20;
+        for (gte, lt) in [(10.0, 20.0), (0.0, 50.0), (33.3, 99.0), (95.0, 95.5)] {
+            let range = Range {
+                lt: Some(lt),
+                gt: None,
+                gte: Some(gte),
+                lte: None,
+            };
+            for index in [&index, &immutable_index] {
+                let estimation = cardinality_request(index, range.clone());
+                let exact = index
+                    .filter(&FieldCondition::new_range("".to_string(), range.clone()))
+                    .unwrap()
+                    .count();
+                assert!(
+                    estimation.exp.abs_diff(exact) <= max_error,
+                    "exp = {}, exact = {exact}",
+                    estimation.exp
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_payload_blocks() {
         let (_tmp_dir, index) = random_index(1000, 2);
@@ -829,6 +1445,175 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_on_disk_numeric_index() {
+        let (_tmp_dir, mut index) = random_index(1000, 2);
+        let on_disk_db = Builder::new()
+            .prefix("test_on_disk_numeric_index")
+            .tempdir()
+            .unwrap();
+        let db = open_db_with_existing_cf(on_disk_db.path()).unwrap();
+        let mut on_disk_index: NumericIndex<f64> =
+            NumericIndex::new_with_storage(db, COLUMN_NAME, true);
+        on_disk_index.recreate().unwrap();
+        assert!(on_disk_index.is_on_disk());
+
+        let mut rng = StdRng::seed_from_u64(42);
+        for i in 0..1000 {
+            let values = (0..2).map(|_| rng.gen_range(0.0..100.0));
+            on_disk_index
+                .add_many_to_list(i as PointOffsetType, values)
+                .unwrap();
+        }
+        for i in (0..1000).step_by(3) {
+            index.remove_point(i).unwrap();
+            on_disk_index.remove_point(i).unwrap();
+        }
+
+        let ranges = [
+            Range {
+                lt: Some(20.0),
+                gt: None,
+                gte: Some(10.0),
+                lte: None,
+            },
+            Range {
+                lt: None,
+                gt: Some(50.0),
+                gte: None,
+                lte: None,
+            },
+            Range {
+                lt: None,
+                gt: None,
+                gte: None,
+                lte: Some(0.5),
+            },
+            // Read from the disk in several batches
+            Range {
+                lt: None,
+                gt: None,
+                gte: Some(0.0),
+                lte: None,
+            },
+        ];
+        for range in ranges {
+            let condition = FieldCondition::new_range("".to_string(), range.clone());
+            let expected = index.filter(&condition).unwrap().collect_vec();
+            let actual = on_disk_index.filter(&condition).unwrap().collect_vec();
+            assert_eq!(actual, expected);
+
+            let on_disk_estimation = on_disk_index.range_cardinality(&range);
+            assert!(on_disk_estimation.min <= actual.iter().unique().count());
+            assert!(on_disk_estimation.max >= actual.iter().unique().count());
+        }
+        assert_eq!(on_disk_index.values_count(), index.values_count());
+        assert_eq!(
+            on_disk_index.count_indexed_points(),
+            index.count_indexed_points()
+        );
+        assert!(on_disk_index.point_to_values.is_empty());
+        for i in 0..1000 {
+            assert_eq!(on_disk_index.get_values(i), index.get_values(i));
+        }
+
+        // Point values are loaded from the disk as well
+        let db = on_disk_index.db_wrapper.database.clone();
+        let mut loaded_index: NumericIndex<f64> =
+            NumericIndex::new_with_storage(db, COLUMN_NAME, true);
+        assert!(loaded_index.load().unwrap());
+        assert_eq!(loaded_index.values_count(), index.values_count());
+        assert_eq!(
+            loaded_index.count_indexed_points(),
+            index.count_indexed_points()
+        );
+        assert_eq!(loaded_index.max_values_per_point, 2);
+        assert_eq!(loaded_index.get_values(1), index.get_values(1));
+    }
+
+    #[test]
+    fn test_on_disk_numeric_index_large_integers() {
+        let tmp_dir = Builder::new()
+            .prefix("test_on_disk_numeric_index_large_integers")
+            .tempdir()
+            .unwrap();
+        let db = open_db_with_existing_cf(tmp_dir.path()).unwrap();
+        let mut index: NumericIndex<IntPayloadType> =
+            NumericIndex::new_with_storage(db, COLUMN_NAME, true);
+        index.recreate().unwrap();
+
+        // All values are converted to the same `f64` value
+        let base: IntPayloadType = 1 << 60;
+        for i in 0..100 {
+            index
+                .add_many_to_list(i as PointOffsetType, [base + i as IntPayloadType])
+                .unwrap();
+        }
+        for i in (0..100).step_by(2) {
+            index.remove_point(i).unwrap();
+        }
+        assert_eq!(index.values_count(), 50);
+
+        let db = index.db_wrapper.lock_db();
+        let point =
+            NumericIndex::<IntPayloadType>::key_to_histogram_point(&(base + 3).encode_key(3));
+        let left = NumericIndex::<IntPayloadType>::get_db_left_neighbor(&db, &point).unwrap();
+        let right = NumericIndex::<IntPayloadType>::get_db_right_neighbor(&db, &point).unwrap();
+        assert_eq!(left.idx, 1);
+        assert_eq!(right.idx, 5);
+    }
+
+    #[test]
+    fn test_immutable_numeric_index() {
+        let (_tmp_dir, mut index) = random_index(1000, 2);
+        let db = index.db_wrapper.database.clone();
+        let mut immutable_index: NumericIndex<f64> = NumericIndex::new_immutable(db, COLUMN_NAME);
+        immutable_index.load().unwrap();
+        assert!(immutable_index.is_immutable());
+        assert!(immutable_index.add_many_to_list(1000, vec![1.0]).is_err());
+
+        for i in (0..1000).step_by(3) {
+            immutable_index.remove_point(i).unwrap();
+            index.remove_point(i).unwrap();
+        }
+
+        let ranges = [
+            Range {
+                lt: Some(20.0),
+                gt: None,
+                gte: Some(10.0),
+                lte: None,
+            },
+            Range {
+                lt: None,
+                gt: Some(50.0),
+                gte: None,
+                lte: Some(70.0),
+            },
+            Range {
+                lt: None,
+                gt: None,
+                gte: None,
+                lte: Some(0.5),
+            },
+        ];
+        for range in ranges {
+            let condition = FieldCondition::new_range("".to_string(), range.clone());
+            let expected = index.filter(&condition).unwrap().collect_vec();
+            let actual = immutable_index.filter(&condition).unwrap().collect_vec();
+            assert_eq!(actual, expected);
+
+            let estimation = immutable_index.range_cardinality(&range);
+            assert!(estimation.min <= actual.iter().unique().count());
+            assert!(estimation.max >= actual.iter().unique().count());
+        }
+        assert_eq!(immutable_index.values_count(), index.values_count());
+        assert_eq!(
+            immutable_index.count_indexed_points(),
+            index.count_indexed_points()
+        );
+    }
+
     fn test_cond<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone>(
         index: &NumericIndex<T>,
         rng: Range,
@@ -840,6 +1625,7 @@ mod tests {
             range: Some(rng),
             geo_bounding_box: None,
             geo_radius: None,
+            geo_polygon: None,
             values_count: None,
         };
 
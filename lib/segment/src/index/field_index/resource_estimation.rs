@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::mem::size_of;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::types::{GeoPoint, PayloadSchemaType, PointOffsetType};
+
+/// Overhead of a single entry of hash- or tree-based containers
+const CONTAINER_ENTRY_OVERHEAD: usize = 32;
+/// Overhead of a single RocksDB record, besides its key and value
+const DB_RECORD_OVERHEAD: usize = 16;
+/// Size of the sortable key of the numeric index: value and point offset
+const NUMERIC_KEY_SIZE: usize = 12;
+/// Length of geohashes stored by the geo index, each of their prefixes is counted
+const GEO_HASH_LENGTH: usize = 12;
+
+/// Statistics of the values of a field, which would be taken by the index of given type
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FieldValuesStats {
+    /// Amount of points the statistics is collected from
+    pub points_count: usize,
+    /// Amount of points with at least one value
+    pub points_with_values: usize,
+    /// Amount of values, or tokens for the text index
+    pub values_count: usize,
+    pub distinct_values_count: usize,
+    /// Amount of distinct values, which only occur once
+    pub unique_values_count: usize,
+    /// Total size of string values in bytes
+    pub values_size_bytes: usize,
+}
+
+impl FieldValuesStats {
+    /// Collect statistics from the field values of a sample of points
+    pub fn collect<'a>(
+        data_type: PayloadSchemaType,
+        points_values: impl IntoIterator<Item = Option<&'a Value>>,
+    ) -> Self {
+        let mut stats = FieldValuesStats::default();
+        let mut occurrences: HashMap<String, usize> = HashMap::new();
+        for point_value in points_values {
+            stats.points_count += 1;
+            let values = match point_value {
+                Some(Value::Array(values)) => values.iter().collect(),
+                Some(value) => vec![value],
+                None => vec![],
+            };
+            let mut has_values = false;
+            for value in values {
+                for key in Self::index_keys(data_type, value) {
+                    has_values = true;
+                    stats.values_count += 1;
+                    if matches!(
+                        data_type,
+                        PayloadSchemaType::Keyword | PayloadSchemaType::Text
+                    ) {
+                        stats.values_size_bytes += key.len();
+                    }
+                    *occurrences.entry(key).or_default() += 1;
+                }
+            }
+            if has_values {
+                stats.points_with_values += 1;
+            }
+        }
+        stats.distinct_values_count = occurrences.len();
+        stats.unique_values_count = occurrences.values().filter(|count| **count == 1).count();
+        stats
+    }
+
+    /// Keys of the value which the index of given type would store
+    fn index_keys(data_type: PayloadSchemaType, value: &Value) -> Vec<String> {
+        match (data_type, value) {
+            (PayloadSchemaType::Keyword, Value::String(keyword)) => vec![keyword.clone()],
+            (PayloadSchemaType::Integer, Value::Number(number)) if number.is_i64() => {
+                vec![number.to_string()]
+            }
+            (PayloadSchemaType::Float, Value::Number(number)) => vec![number.to_string()],
+            (PayloadSchemaType::Geo, Value::Object(_)) => {
+                serde_json::from_value::<GeoPoint>(value.clone())
+                    .map(|point| vec![format!("{},{}", point.lon, point.lat)])
+                    .unwrap_or_default()
+            }
+            // Whitespace tokenization is a good enough approximation for the estimation
+            (PayloadSchemaType::Text, Value::String(text)) => text
+                .split_whitespace()
+                .map(|token| token.to_lowercase())
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Extrapolate statistics of the sample to the given amount of points
+    pub fn extrapolate(&self, points_count: usize) -> Self {
+        if self.points_count == 0 || points_count <= self.points_count {
+            return self.clone();
+        }
+        let ratio = points_count as f64 / self.points_count as f64;
+        let scale = |count: usize| (count as f64 * ratio).round() as usize;
+        // Guaranteed-error estimator: values which are seen once in the sample are expected to be rare,
+        // values which are seen several times are likely to be all the values there are.
+        let repeated_values_count = self.distinct_values_count - self.unique_values_count;
+        let distinct_values_count = repeated_values_count
+            + (self.unique_values_count as f64 * ratio.sqrt()).round() as usize;
+        let values_count = scale(self.values_count);
+        FieldValuesStats {
+            points_count,
+            points_with_values: scale(self.points_with_values),
+            values_count,
+            distinct_values_count: distinct_values_count.min(values_count),
+            unique_values_count: scale(self.unique_values_count),
+            values_size_bytes: scale(self.values_size_bytes),
+        }
+    }
+
+    fn avg_value_size(&self) -> usize {
+        if self.values_count == 0 {
+            0
+        } else {
+            self.values_size_bytes / self.values_count
+        }
+    }
+}
+
+/// Estimated resources required by a payload index
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct IndexResourceEstimation {
+    pub ram_usage_bytes: usize,
+    pub disk_usage_bytes: usize,
+}
+
+impl IndexResourceEstimation {
+    /// Estimate resources of the index of given type built over values with the statistics.
+    /// Returns `None` if the index type does not support the estimation.
+    pub fn estimate(data_type: PayloadSchemaType, stats: &FieldValuesStats) -> Option<Self> {
+        let estimation = match data_type {
+            PayloadSchemaType::Keyword => Self::map_index(stats, size_of::<String>()),
+            PayloadSchemaType::Integer => {
+                Self::map_index(stats, size_of::<i64>()).add(&Self::numeric_index(stats))
+            }
+            PayloadSchemaType::Float => Self::numeric_index(stats),
+            PayloadSchemaType::Geo => Self::geo_index(stats),
+            PayloadSchemaType::Text => Self::text_index(stats),
+            PayloadSchemaType::Composite => return None,
+        };
+        Some(estimation)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        IndexResourceEstimation {
+            ram_usage_bytes: self.ram_usage_bytes + other.ram_usage_bytes,
+            disk_usage_bytes: self.disk_usage_bytes + other.disk_usage_bytes,
+        }
+    }
+
+    fn map_index(stats: &FieldValuesStats, value_size: usize) -> Self {
+        let value_size = value_size + stats.avg_value_size();
+        let map_size = stats.distinct_values_count * (value_size + CONTAINER_ENTRY_OVERHEAD)
+            + stats.values_count * size_of::<PointOffsetType>();
+        let point_to_values_size =
+            stats.points_count * size_of::<Vec<()>>() + stats.values_count * value_size;
+        // Records are `{value}/{point}` strings
+        let record_size = value_size + 11 + DB_RECORD_OVERHEAD;
+        IndexResourceEstimation {
+            ram_usage_bytes: map_size + point_to_values_size,
+            disk_usage_bytes: stats.values_count * record_size,
+        }
+    }
+
+    fn numeric_index(stats: &FieldValuesStats) -> Self {
+        let keys_size = stats.values_count
+            * (size_of::<Vec<u8>>()
+                + NUMERIC_KEY_SIZE
+                + size_of::<PointOffsetType>()
+                + CONTAINER_ENTRY_OVERHEAD);
+        let point_to_values_size =
+            stats.points_count * size_of::<Vec<()>>() + stats.values_count * size_of::<f64>();
+        IndexResourceEstimation {
+            ram_usage_bytes: keys_size + point_to_values_size,
+            disk_usage_bytes: stats.values_count
+                * (NUMERIC_KEY_SIZE + size_of::<PointOffsetType>() + DB_RECORD_OVERHEAD),
+        }
+    }
+
+    fn geo_index(stats: &FieldValuesStats) -> Self {
+        // Counters of points and values are kept for each prefix of the hashes
+        let hash_entry_size = size_of::<String>() + GEO_HASH_LENGTH + CONTAINER_ENTRY_OVERHEAD;
+        let counters_size = 2
+            * stats.distinct_values_count
+            * GEO_HASH_LENGTH
+            * (hash_entry_size + size_of::<usize>());
+        let points_map_size = stats.distinct_values_count * hash_entry_size
+            + stats.values_count * size_of::<PointOffsetType>();
+        let point_to_values_size =
+            stats.points_count * size_of::<Vec<()>>() + stats.values_count * size_of::<GeoPoint>();
+        IndexResourceEstimation {
+            ram_usage_bytes: counters_size + points_map_size + point_to_values_size,
+            disk_usage_bytes: stats.values_count
+                * (GEO_HASH_LENGTH
+                    + size_of::<PointOffsetType>()
+                    + size_of::<GeoPoint>()
+                    + DB_RECORD_OVERHEAD),
+        }
+    }
+
+    fn text_index(stats: &FieldValuesStats) -> Self {
+        let token_size = size_of::<String>() + stats.avg_value_size();
+        let postings_size = stats.distinct_values_count * (token_size + CONTAINER_ENTRY_OVERHEAD)
+            + stats.values_count * size_of::<PointOffsetType>();
+        // Documents keep a set of tokens and their sequence
+        let documents_size =
+            stats.points_count * size_of::<Option<Vec<()>>>() + 2 * stats.values_count * token_size;
+        IndexResourceEstimation {
+            ram_usage_bytes: postings_size + documents_size,
+            disk_usage_bytes: 2 * stats.values_size_bytes
+                + stats.points_with_values * (size_of::<PointOffsetType>() + DB_RECORD_OVERHEAD),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_collect_keyword_stats() {
+        let payloads = [
+            json!("red"),
+            json!(["red", "green"]),
+            json!("blue"),
+            json!(1),
+            json!(null),
+        ];
+        let stats = FieldValuesStats::collect(
+            PayloadSchemaType::Keyword,
+            payloads.iter().map(Some).chain([None]),
+        );
+        assert_eq!(stats.points_count, 6);
+        assert_eq!(stats.points_with_values, 3);
+        assert_eq!(stats.values_count, 4);
+        assert_eq!(stats.distinct_values_count, 3);
+        assert_eq!(stats.unique_values_count, 2);
+        assert_eq!(stats.values_size_bytes, 15);
+    }
+
+    #[test]
+    fn test_extrapolate_stats() {
+        // Low-cardinality field: all values are repeated in the sample
+        let payloads: Vec<_> = (0..1000).map(|i| json!(i % 10)).collect();
+        let stats =
+            FieldValuesStats::collect(PayloadSchemaType::Integer, payloads.iter().map(Some));
+        let extrapolated = stats.extrapolate(100_000);
+        assert_eq!(extrapolated.points_with_values, 100_000);
+        assert_eq!(extrapolated.distinct_values_count, 10);
+
+        // Unique values keep growing with the amount of points
+        let payloads: Vec<_> = (0..1000).map(|i| json!(i)).collect();
+        let stats =
+            FieldValuesStats::collect(PayloadSchemaType::Integer, payloads.iter().map(Some));
+        let extrapolated = stats.extrapolate(100_000);
+        assert_eq!(extrapolated.distinct_values_count, 10_000);
+
+        let small = IndexResourceEstimation::estimate(PayloadSchemaType::Integer, &stats).unwrap();
+        let large =
+            IndexResourceEstimation::estimate(PayloadSchemaType::Integer, &extrapolated).unwrap();
+        assert!(small.ram_usage_bytes > 0);
+        assert!(large.ram_usage_bytes > small.ram_usage_bytes);
+        assert!(large.disk_usage_bytes > small.disk_usage_bytes);
+        assert!(IndexResourceEstimation::estimate(PayloadSchemaType::Composite, &stats).is_none());
+    }
+}
@@ -20,6 +20,16 @@ pub struct HnswGraphConfig {
     pub indexing_threshold: usize,
     #[serde(default)]
     pub max_indexing_threads: usize,
+    /// Keep graph links in a memory-mapped file after the index is built
+    #[serde(default)]
+    pub on_disk: bool,
+    /// M for additional payload-aware links. If not set - `m` is used
+    #[serde(default)]
+    pub payload_m: Option<usize>,
+    /// Minimal number of vectors in a payload block to build additional links for.
+    /// If not set - `indexing_threshold` is used
+    #[serde(default)]
+    pub payload_indexing_threshold: Option<usize>,
 }
 
 impl HnswGraphConfig {
@@ -28,6 +38,9 @@ impl HnswGraphConfig {
         ef_construct: usize,
         indexing_threshold: usize,
         max_indexing_threads: usize,
+        on_disk: bool,
+        payload_m: Option<usize>,
+        payload_indexing_threshold: Option<usize>,
     ) -> Self {
         HnswGraphConfig {
             m,
@@ -36,6 +49,9 @@ impl HnswGraphConfig {
             ef: ef_construct,
             indexing_threshold,
             max_indexing_threads,
+            on_disk,
+            payload_m,
+            payload_indexing_threshold,
         }
     }
 
@@ -51,6 +67,15 @@ impl HnswGraphConfig {
         Ok(atomic_save_json(path, self)?)
     }
 
+    pub fn payload_m(&self) -> usize {
+        self.payload_m.unwrap_or(self.m)
+    }
+
+    pub fn payload_indexing_threshold(&self) -> usize {
+        self.payload_indexing_threshold
+            .unwrap_or(self.indexing_threshold)
+    }
+
     pub fn max_rayon_threads(&self) -> usize {
         let max_threads = self.max_indexing_threads;
 
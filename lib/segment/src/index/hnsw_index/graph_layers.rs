@@ -1,4 +1,5 @@
 use std::cmp::max;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use itertools::Itertools;
@@ -8,6 +9,7 @@ use crate::common::file_operations::{atomic_save_bin, read_bin};
 use crate::common::utils::rev_range;
 use crate::entry::entry_point::OperationResult;
 use crate::index::hnsw_index::entry_points::EntryPoints;
+use crate::index::hnsw_index::graph_links::GraphLinksMmap;
 use crate::index::hnsw_index::point_scorer::FilteredScorer;
 use crate::index::hnsw_index::search_context::SearchContext;
 use crate::index::visited_pool::{VisitedList, VisitedPool};
@@ -42,6 +44,7 @@ impl From<GraphLayersBackwardCompatibility> for GraphLayers {
             ef_construct: gl.ef_construct,
             links_layers: gl.links_layers,
             entry_points: gl.entry_points,
+            links_mmap: None,
             visited_pool: VisitedPool::new(),
         }
     }
@@ -56,6 +59,10 @@ pub struct GraphLayers {
     pub(super) links_layers: Vec<LayersContainer>,
     pub(super) entry_points: EntryPoints,
 
+    /// If set, links are read from the memory-mapped file and `links_layers` is empty
+    #[serde(skip)]
+    pub(super) links_mmap: Option<GraphLinksMmap>,
+
     #[serde(skip)]
     pub(super) visited_pool: VisitedPool,
 }
@@ -183,8 +190,13 @@ impl GraphLayersBase for GraphLayers {
     where
         F: FnMut(PointOffsetType),
     {
-        for link in &self.links_layers[point_id as usize][level] {
-            f(*link);
+        match &self.links_mmap {
+            Some(links_mmap) => links_mmap.links(point_id, level).for_each(f),
+            None => {
+                for link in &self.links_layers[point_id as usize][level] {
+                    f(*link);
+                }
+            }
         }
     }
 
@@ -226,6 +238,7 @@ impl GraphLayers {
             ef_construct,
             links_layers,
             entry_points: EntryPoints::new(entry_points_num),
+            links_mmap: None,
             visited_pool: VisitedPool::new(),
         }
     }
@@ -241,11 +254,69 @@ impl GraphLayers {
     }
 
     fn num_points(&self) -> usize {
-        self.links_layers.len()
+        match &self.links_mmap {
+            Some(links_mmap) => links_mmap.num_points(),
+            None => self.links_layers.len(),
+        }
     }
 
     pub fn point_level(&self, point_id: PointOffsetType) -> usize {
-        self.links_layers[point_id as usize].len() - 1
+        match &self.links_mmap {
+            Some(links_mmap) => links_mmap.point_level(point_id),
+            None => self.links_layers[point_id as usize].len() - 1,
+        }
+    }
+
+    /// Move links into the memory-mapped file at `links_path` to free RAM
+    pub fn convert_to_mmap(&mut self, links_path: &Path) -> OperationResult<()> {
+        if self.links_mmap.is_none() {
+            GraphLinksMmap::save(links_path, &self.links_layers)?;
+            self.links_mmap = Some(GraphLinksMmap::open(links_path)?);
+            self.links_layers = vec![];
+        }
+        Ok(())
+    }
+
+    /// Use links from the memory-mapped file at `links_path`, if graph was stored on disk
+    pub fn load_mmap_links(&mut self, links_path: &Path) -> OperationResult<()> {
+        if links_path.exists() {
+            self.links_mmap = Some(GraphLinksMmap::open(links_path)?);
+            self.links_layers = vec![];
+        }
+        Ok(())
+    }
+
+    /// Links of the points from `mapping` on every level, with ids translated by `mapping`.
+    ///
+    /// Only the first `m` links of each level are taken, which are the links of the main graph.
+    /// Links to the points, which are missing in `mapping`, are dropped.
+    pub fn export_links(
+        &self,
+        mapping: &HashMap<PointOffsetType, PointOffsetType>,
+    ) -> HashMap<PointOffsetType, LayersContainer> {
+        mapping
+            .iter()
+            .filter(|(&old_id, _)| (old_id as usize) < self.num_points())
+            .map(|(&old_id, &new_id)| {
+                let layers = (0..=self.point_level(old_id))
+                    .map(|level| {
+                        let level_m = self.get_m(level);
+                        let mut links = Vec::with_capacity(level_m);
+                        let mut position = 0;
+                        self.links_map(old_id, level, |link| {
+                            if position < level_m {
+                                if let Some(&new_link) = mapping.get(&link) {
+                                    links.push(new_link);
+                                }
+                            }
+                            position += 1;
+                        });
+                        links
+                    })
+                    .collect();
+                (new_id, layers)
+            })
+            .collect()
     }
 
     pub fn merge_from_other(&mut self, other: GraphLayers) {
@@ -443,6 +514,38 @@ mod tests {
         assert_eq!(res1, res2)
     }
 
+    #[test]
+    fn test_save_and_load_mmap_links() {
+        let num_vectors = 100;
+        let dim = 8;
+        let top = 5;
+
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let (vector_holder, mut graph_layers) =
+            create_graph_layer_fixture::<CosineMetric, _>(num_vectors, M, dim, false, &mut rng);
+
+        let query = random_vector(&mut rng, dim);
+        let res1 = search_in_graph(&query, top, &vector_holder, &graph_layers);
+
+        let dir = Builder::new().prefix("graph_dir").tempdir().unwrap();
+        let links_path = GraphLinksMmap::get_path(dir.path());
+        graph_layers.convert_to_mmap(&links_path).unwrap();
+        assert!(graph_layers.links_layers.is_empty());
+
+        let res2 = search_in_graph(&query, top, &vector_holder, &graph_layers);
+        assert_eq!(res1, res2);
+
+        let path = GraphLayers::get_path(dir.path());
+        graph_layers.save(&path).unwrap();
+
+        let mut graph2 = GraphLayers::load(&path).unwrap();
+        graph2.load_mmap_links(&links_path).unwrap();
+
+        let res3 = search_in_graph(&query, top, &vector_holder, &graph2);
+        assert_eq!(res1, res3)
+    }
+
     #[test]
     fn test_add_points() {
         let num_vectors = 1000;
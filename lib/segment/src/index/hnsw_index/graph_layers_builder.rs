@@ -7,7 +7,9 @@ use rand::distributions::Uniform;
 use rand::Rng;
 
 use crate::index::hnsw_index::entry_points::EntryPoints;
-use crate::index::hnsw_index::graph_layers::{GraphLayers, GraphLayersBase, LinkContainer};
+use crate::index::hnsw_index::graph_layers::{
+    GraphLayers, GraphLayersBase, LayersContainer, LinkContainer,
+};
 use crate::index::hnsw_index::point_scorer::FilteredScorer;
 use crate::index::visited_pool::{VisitedList, VisitedPool};
 use crate::spaces::tools::FixedLengthPriorityQueue;
@@ -78,6 +80,7 @@ impl GraphLayersBuilder {
             ef_construct: self.ef_construct,
             links_layers: unlocker_links_layers,
             entry_points: self.entry_points.into_inner(),
+            links_mmap: None,
             visited_pool: self.visited_pool,
         }
     }
@@ -168,6 +171,22 @@ impl GraphLayersBuilder {
             .fetch_max(level, std::sync::atomic::Ordering::Relaxed);
     }
 
+    /// Set links of the point on every level, taken from a graph built before,
+    /// instead of searching for the closest points.
+    ///
+    /// Levels of the point must be already set with `set_levels`.
+    pub fn reuse_point_links(&self, point_id: PointOffsetType, layers: LayersContainer) {
+        let level = self.get_point_level(point_id);
+        for (curr_level, links) in layers.into_iter().take(level + 1).enumerate() {
+            self.links_layers[point_id as usize][curr_level]
+                .write()
+                .clone_from(&links);
+        }
+        self.entry_points
+            .lock()
+            .new_point(point_id, level, |_point_id| true);
+    }
+
     /// Connect new point to links, so that links contains only closest points
     fn connect_new_point<F>(
         links: &mut LinkContainer,
@@ -0,0 +1,178 @@
+use std::fs::OpenOptions;
+use std::io::{BufWriter, Write};
+use std::mem::size_of;
+use std::path::{Path, PathBuf};
+
+use atomicwrites::AtomicFile;
+use atomicwrites::OverwriteBehavior::AllowOverwrite;
+use memmap::{Mmap, MmapOptions};
+
+use crate::common::error_logging::LogError;
+use crate::common::file_operations::FileStorageError;
+use crate::entry::entry_point::{OperationError, OperationResult};
+use crate::index::hnsw_index::graph_layers::LayersContainer;
+use crate::types::PointOffsetType;
+
+pub const HNSW_LINKS_FILE: &str = "links.bin";
+
+const OFFSET_SIZE: usize = size_of::<u64>();
+const LINK_SIZE: usize = size_of::<PointOffsetType>();
+
+/// Read-only links of the HNSW graph, stored in a memory-mapped file.
+///
+/// File layout:
+///
+/// * `num_points: u64`
+/// * `level_offsets: [u64; num_points + 1]` - index of the first (point, level) entry of each point
+/// * `link_offsets: [u64; num_entries + 1]` - index of the first link of each (point, level) entry
+/// * `links: [u32; num_links]`
+pub struct GraphLinksMmap {
+    mmap: Mmap,
+    num_points: usize,
+    level_offsets_start: usize,
+    link_offsets_start: usize,
+    links_start: usize,
+}
+
+impl std::fmt::Debug for GraphLinksMmap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GraphLinksMmap")
+            .field("num_points", &self.num_points)
+            .finish()
+    }
+}
+
+impl GraphLinksMmap {
+    pub fn get_path(path: &Path) -> PathBuf {
+        path.join(HNSW_LINKS_FILE)
+    }
+
+    /// Write links of all points into the file, in the layout expected by `open`
+    pub fn save(path: &Path, links_layers: &[LayersContainer]) -> OperationResult<()> {
+        let af = AtomicFile::new(path, AllowOverwrite);
+        af.write(|f| {
+            let mut writer = BufWriter::new(f);
+            writer.write_all(&(links_layers.len() as u64).to_ne_bytes())?;
+
+            let mut entries = 0u64;
+            writer.write_all(&entries.to_ne_bytes())?;
+            for layers in links_layers {
+                entries += layers.len() as u64;
+                writer.write_all(&entries.to_ne_bytes())?;
+            }
+
+            let mut links = 0u64;
+            writer.write_all(&links.to_ne_bytes())?;
+            for links_container in links_layers.iter().flatten() {
+                links += links_container.len() as u64;
+                writer.write_all(&links.to_ne_bytes())?;
+            }
+
+            for link in links_layers.iter().flatten().flatten() {
+                writer.write_all(&link.to_ne_bytes())?;
+            }
+            writer.flush()
+        })
+        .map_err(FileStorageError::from)?;
+        Ok(())
+    }
+
+    pub fn open(path: &Path) -> OperationResult<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(false)
+            .open(path)
+            .describe("Open HNSW links file")?;
+        let mmap = unsafe { MmapOptions::new().map(&file)? };
+
+        if mmap.len() < OFFSET_SIZE {
+            return Err(OperationError::service_error("Malformed HNSW links file"));
+        }
+
+        let mut links_mmap = GraphLinksMmap {
+            mmap,
+            num_points: 0,
+            level_offsets_start: OFFSET_SIZE,
+            link_offsets_start: 0,
+            links_start: 0,
+        };
+        links_mmap.num_points = links_mmap.read_offset(0) as usize;
+        links_mmap.link_offsets_start =
+            links_mmap.level_offsets_start + (links_mmap.num_points + 1) * OFFSET_SIZE;
+        let num_entries = links_mmap.level_offset(links_mmap.num_points);
+        links_mmap.links_start = links_mmap.link_offsets_start + (num_entries + 1) * OFFSET_SIZE;
+        let num_links = links_mmap.link_offset(num_entries);
+
+        if links_mmap.links_start + num_links * LINK_SIZE != links_mmap.mmap.len() {
+            return Err(OperationError::service_error("Malformed HNSW links file"));
+        }
+        Ok(links_mmap)
+    }
+
+    fn read_offset(&self, position: usize) -> u64 {
+        let bytes = &self.mmap[position..position + OFFSET_SIZE];
+        u64::from_ne_bytes(bytes.try_into().unwrap())
+    }
+
+    fn level_offset(&self, point_id: usize) -> usize {
+        self.read_offset(self.level_offsets_start + point_id * OFFSET_SIZE) as usize
+    }
+
+    fn link_offset(&self, entry: usize) -> usize {
+        self.read_offset(self.link_offsets_start + entry * OFFSET_SIZE) as usize
+    }
+
+    pub fn num_points(&self) -> usize {
+        self.num_points
+    }
+
+    pub fn point_level(&self, point_id: PointOffsetType) -> usize {
+        let point_id = point_id as usize;
+        self.level_offset(point_id + 1) - self.level_offset(point_id) - 1
+    }
+
+    pub fn links(
+        &self,
+        point_id: PointOffsetType,
+        level: usize,
+    ) -> impl Iterator<Item = PointOffsetType> + '_ {
+        let entry = self.level_offset(point_id as usize) + level;
+        let start = self.links_start + self.link_offset(entry) * LINK_SIZE;
+        let end = self.links_start + self.link_offset(entry + 1) * LINK_SIZE;
+        self.mmap[start..end]
+            .chunks_exact(LINK_SIZE)
+            .map(|bytes| PointOffsetType::from_ne_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+
+    #[test]
+    fn test_save_and_open_links() {
+        let dir = Builder::new().prefix("graph_links").tempdir().unwrap();
+        let path = GraphLinksMmap::get_path(dir.path());
+
+        let links_layers: Vec<LayersContainer> = vec![
+            vec![vec![1, 2], vec![2]],
+            vec![vec![0]],
+            vec![vec![0, 1], vec![0], vec![]],
+            vec![vec![]],
+        ];
+        GraphLinksMmap::save(&path, &links_layers).unwrap();
+
+        let links = GraphLinksMmap::open(&path).unwrap();
+        assert_eq!(links.num_points(), links_layers.len());
+        for (point_id, layers) in links_layers.iter().enumerate() {
+            let point_id = point_id as PointOffsetType;
+            assert_eq!(links.point_level(point_id), layers.len() - 1);
+            for (level, expected) in layers.iter().enumerate() {
+                let actual: Vec<_> = links.links(point_id, level).collect();
+                assert_eq!(&actual, expected);
+            }
+        }
+    }
+}
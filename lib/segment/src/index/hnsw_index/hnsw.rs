@@ -1,4 +1,5 @@
 use std::cmp::max;
+use std::collections::HashMap;
 use std::fs::create_dir_all;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -15,8 +16,10 @@ use crate::data_types::vectors::VectorElementType;
 use crate::entry::entry_point::{OperationError, OperationResult};
 use crate::index::hnsw_index::build_condition_checker::BuildConditionChecker;
 use crate::index::hnsw_index::config::HnswGraphConfig;
-use crate::index::hnsw_index::graph_layers::GraphLayers;
+use crate::index::hnsw_index::graph_layers::{GraphLayers, LayersContainer};
 use crate::index::hnsw_index::graph_layers_builder::GraphLayersBuilder;
+use crate::index::hnsw_index::graph_links::GraphLinksMmap;
+use crate::index::hnsw_index::old_graph_links::OldGraphLinks;
 use crate::index::hnsw_index::point_scorer::FilteredScorer;
 use crate::index::sample_estimation::sample_check_cardinality;
 use crate::index::struct_payload_index::StructPayloadIndex;
@@ -26,7 +29,11 @@ use crate::telemetry::{
     TelemetryOperationAggregator, TelemetryOperationTimer, VectorIndexTelemetry,
 };
 use crate::types::Condition::Field;
-use crate::types::{FieldCondition, Filter, HnswConfig, SearchParams, VECTOR_ELEMENT_SIZE};
+use crate::types::{
+    FieldCondition, Filter, HnswConfig, PointOffsetType, QuantizationConfig, SearchParams,
+    VECTOR_ELEMENT_SIZE,
+};
+use crate::vector_storage::quantized_vectors::QuantizedVectors;
 use crate::vector_storage::{ScoredPointOffset, VectorStorageSS};
 
 const HNSW_USE_HEURISTIC: bool = true;
@@ -38,6 +45,9 @@ pub struct HNSWIndex {
     config: HnswGraphConfig,
     path: PathBuf,
     graph: GraphLayers,
+    quantization_config: Option<QuantizationConfig>,
+    /// Built along with the graph, if quantization is configured
+    quantized_vectors: Option<QuantizedVectors>,
     small_cardinality_search_telemetry: Arc<Mutex<TelemetryOperationAggregator>>,
     large_cardinality_search_telemetry: Arc<Mutex<TelemetryOperationAggregator>>,
     positive_check_cardinality_search_telemetry: Arc<Mutex<TelemetryOperationAggregator>>,
@@ -50,6 +60,7 @@ impl HNSWIndex {
         vector_storage: Arc<AtomicRefCell<VectorStorageSS>>,
         payload_index: Arc<AtomicRefCell<StructPayloadIndex>>,
         hnsw_config: HnswConfig,
+        quantization_config: Option<QuantizationConfig>,
     ) -> OperationResult<Self> {
         create_dir_all(path)?;
 
@@ -57,20 +68,29 @@ impl HNSWIndex {
         let config = if config_path.exists() {
             HnswGraphConfig::load(&config_path)?
         } else {
-            let indexing_threshold = hnsw_config.full_scan_threshold.saturating_mul(BYTES_IN_KB)
-                / (vector_storage.borrow().vector_dim() * VECTOR_ELEMENT_SIZE);
+            let vector_size = vector_storage.borrow().vector_dim() * VECTOR_ELEMENT_SIZE;
+            let indexing_threshold =
+                hnsw_config.full_scan_threshold.saturating_mul(BYTES_IN_KB) / vector_size;
+            let payload_indexing_threshold = hnsw_config
+                .payload_block_threshold
+                .map(|threshold| threshold.saturating_mul(BYTES_IN_KB) / vector_size);
 
             HnswGraphConfig::new(
                 hnsw_config.m,
                 hnsw_config.ef_construct,
                 indexing_threshold,
                 hnsw_config.max_indexing_threads,
+                hnsw_config.on_disk.unwrap_or(false),
+                hnsw_config.payload_m,
+                payload_indexing_threshold,
             )
         };
 
         let graph_path = GraphLayers::get_path(path);
         let graph = if graph_path.exists() {
-            GraphLayers::load(&graph_path)?
+            let mut graph = GraphLayers::load(&graph_path)?;
+            graph.load_mmap_links(&GraphLinksMmap::get_path(path))?;
+            graph
         } else {
             let borrowed_vector_storage = vector_storage.borrow();
             let total_points = borrowed_vector_storage.total_vector_count();
@@ -85,12 +105,19 @@ impl HNSWIndex {
             )
         };
 
+        let quantized_vectors = match &quantization_config {
+            Some(quantization_config) => QuantizedVectors::load(path, quantization_config)?,
+            None => None,
+        };
+
         Ok(HNSWIndex {
             vector_storage,
             payload_index,
             config,
             path: path.to_owned(),
             graph,
+            quantization_config,
+            quantized_vectors,
             small_cardinality_search_telemetry: TelemetryOperationAggregator::new(),
             large_cardinality_search_telemetry: TelemetryOperationAggregator::new(),
             positive_check_cardinality_search_telemetry: TelemetryOperationAggregator::new(),
@@ -108,9 +135,17 @@ impl HNSWIndex {
         self.graph.save(&graph_path)
     }
 
+    fn save_quantized_vectors(&self) -> OperationResult<()> {
+        match &self.quantized_vectors {
+            Some(quantized_vectors) => quantized_vectors.save(&self.path),
+            None => Ok(()),
+        }
+    }
+
     pub fn save(&self) -> OperationResult<()> {
         self.save_config()?;
         self.save_graph()?;
+        self.save_quantized_vectors()?;
         Ok(())
     }
 
@@ -179,15 +214,45 @@ impl HNSWIndex {
         // ef should always be bigger that required top
         let ef = max(req_ef, top);
 
+        let quantization_params = params.and_then(|params| params.quantization);
+        let quantized_vectors = self
+            .quantized_vectors
+            .as_ref()
+            .filter(|_| !quantization_params.map_or(false, |params| params.ignore));
+
         let vector_storage = self.vector_storage.borrow();
-        let raw_scorer = vector_storage.raw_scorer(vector.to_owned());
+        let raw_scorer = match quantized_vectors {
+            Some(quantized_vectors) => quantized_vectors.raw_scorer(vector, &*vector_storage),
+            None => vector_storage.raw_scorer(vector.to_owned()),
+        };
         let payload_index = self.payload_index.borrow();
 
         let filter_context = filter.map(|f| payload_index.filter_context(f));
 
         let points_scorer = FilteredScorer::new(raw_scorer.as_ref(), filter_context.as_deref());
 
-        self.graph.search(top, ef, points_scorer)
+        let rescore = quantized_vectors.map_or(false, |quantized_vectors| {
+            quantized_vectors.requires_rescoring()
+                || quantization_params.map_or(false, |params| params.rescore)
+        });
+
+        if rescore {
+            // Take more candidates, so that re-scoring could recover points underestimated by quantization
+            let candidates_count = quantization_params
+                .unwrap_or_default()
+                .rescore_candidates(top, ef);
+            let candidates_ef = max(ef, candidates_count);
+            let candidates = self
+                .graph
+                .search(candidates_count, candidates_ef, points_scorer);
+            vector_storage.score_points(
+                vector,
+                &mut candidates.iter().map(|scored_point| scored_point.idx),
+                top,
+            )
+        } else {
+            self.graph.search(top, ef, points_scorer)
+        }
     }
 
     fn search_vectors_with_graph(
@@ -289,6 +354,14 @@ impl VectorIndex for HNSWIndex {
     }
 
     fn build_index(&mut self, stopped: &AtomicBool) -> OperationResult<()> {
+        self.build_index_with_old_links(&[], stopped)
+    }
+
+    fn build_index_with_old_links(
+        &mut self,
+        old_links: &[OldGraphLinks],
+        stopped: &AtomicBool,
+    ) -> OperationResult<()> {
         // Build main index graph
         let vector_storage = self.vector_storage.borrow();
         let mut rng = thread_rng();
@@ -305,11 +378,61 @@ impl VectorIndex for HNSWIndex {
             HNSW_USE_HEURISTIC,
         );
 
+        // Links of different old graphs are not connected with each other,
+        // so only the largest of them is reused
+        let old_links = old_links
+            .iter()
+            .filter(|old_links| {
+                old_links.is_compatible(self.config.m, self.config.m0, self.config.ef_construct)
+            })
+            .max_by_key(|old_links| old_links.len());
+
         let mut ids = vec![];
+        let mut reused_ids = vec![];
         for vector_id in vector_storage.iter_ids() {
-            let level = graph_layers_builder.get_random_layer(&mut rng);
-            graph_layers_builder.set_levels(vector_id, level);
-            ids.push(vector_id);
+            match old_links.and_then(|old_links| old_links.point_level(vector_id)) {
+                Some(level) => {
+                    graph_layers_builder.set_levels(vector_id, level);
+                    reused_ids.push(vector_id);
+                }
+                None => {
+                    let level = graph_layers_builder.get_random_layer(&mut rng);
+                    graph_layers_builder.set_levels(vector_id, level);
+                    ids.push(vector_id);
+                }
+            }
+        }
+
+        if let Some(old_links) = old_links {
+            let is_reused = |point_id: PointOffsetType| {
+                old_links.links.contains_key(&point_id) && !vector_storage.is_deleted(point_id)
+            };
+            let reused_count = reused_ids.len();
+            let mut relinked_ids = vec![];
+            for point_id in reused_ids {
+                let layers: LayersContainer = old_links.links[&point_id]
+                    .iter()
+                    .map(|links| {
+                        links
+                            .iter()
+                            .copied()
+                            .filter(|&link| is_reused(link))
+                            .collect()
+                    })
+                    .collect();
+                // Point, which lost most of its neighbours, is inserted as a new one
+                let old_links_count = old_links.links[&point_id][0].len();
+                if layers[0].len() * 2 < old_links_count || layers[0].is_empty() {
+                    relinked_ids.push(point_id);
+                } else {
+                    graph_layers_builder.reuse_point_links(point_id, layers);
+                }
+            }
+            debug!(
+                "reused links of {} points",
+                reused_count - relinked_ids.len()
+            );
+            ids.append(&mut relinked_ids);
         }
 
         let pool = rayon::ThreadPoolBuilder::new()
@@ -336,12 +459,25 @@ impl VectorIndex for HNSWIndex {
 
         debug!("finish main graph");
 
+        self.quantized_vectors = self.quantization_config.map(|quantization_config| {
+            debug!("building quantized vectors for {}", total_points);
+            QuantizedVectors::encode(&*vector_storage, &quantization_config)
+        });
+
         let total_vectors_count = vector_storage.total_vector_count();
         let mut block_filter_list = VisitedList::new(total_vectors_count);
 
         let payload_index = self.payload_index.borrow();
 
-        for (field, _) in payload_index.indexed_fields() {
+        // Additional payload-aware links are disabled with `payload_m = 0`
+        let payload_m = self.config.payload_m();
+        let indexed_fields = if payload_m > 0 {
+            payload_index.indexed_fields()
+        } else {
+            Default::default()
+        };
+
+        for (field, _) in indexed_fields {
             debug!("building additional index for field {}", &field);
 
             // It is expected, that graph will become disconnected less than
@@ -349,8 +485,8 @@ impl VectorIndex for HNSWIndex {
             // So blocks larger than $1/m$ are not needed.
             // We add multiplier for the extra safety.
             let percolation_multiplier = 2;
-            let max_block_size = total_points / self.config.m * percolation_multiplier;
-            let min_block_size = self.config.indexing_threshold;
+            let max_block_size = total_points / payload_m * percolation_multiplier;
+            let min_block_size = self.config.payload_indexing_threshold();
 
             for payload_block in payload_index.payload_blocks(&field, min_block_size) {
                 if stopped.load(Ordering::Relaxed) {
@@ -364,8 +500,8 @@ impl VectorIndex for HNSWIndex {
                 // ToDo: re-use graph layer for same payload
                 let mut additional_graph = GraphLayersBuilder::new_with_params(
                     self.vector_storage.borrow().total_vector_count(),
-                    self.config.m,
-                    self.config.m0,
+                    payload_m,
+                    payload_m * 2,
                     self.config.ef_construct,
                     1,
                     HNSW_USE_HEURISTIC,
@@ -383,9 +519,30 @@ impl VectorIndex for HNSWIndex {
             }
         }
         debug!("finish additional payload field indexing");
+
+        if self.config.on_disk {
+            self.graph
+                .convert_to_mmap(&GraphLinksMmap::get_path(&self.path))?;
+        }
         self.save()
     }
 
+    fn export_graph_links(
+        &self,
+        mapping: &HashMap<PointOffsetType, PointOffsetType>,
+    ) -> Option<OldGraphLinks> {
+        let links = self.graph.export_links(mapping);
+        if links.is_empty() {
+            return None;
+        }
+        Some(OldGraphLinks {
+            m: self.config.m,
+            m0: self.config.m0,
+            ef_construct: self.config.ef_construct,
+            links,
+        })
+    }
+
     fn get_telemetry_data(&self) -> VectorIndexTelemetry {
         VectorIndexTelemetry {
             small_cardinality_searches: self
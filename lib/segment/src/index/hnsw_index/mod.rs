@@ -4,7 +4,9 @@ mod config;
 mod entry_points;
 pub mod graph_layers;
 pub mod graph_layers_builder;
+pub mod graph_links;
 pub mod hnsw;
+pub mod old_graph_links;
 pub mod point_scorer;
 mod search_context;
 
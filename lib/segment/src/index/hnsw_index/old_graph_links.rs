@@ -0,0 +1,40 @@
+use std::collections::HashMap;
+
+use crate::index::hnsw_index::graph_layers::LayersContainer;
+use crate::types::PointOffsetType;
+
+/// Links of an HNSW graph, which was built before for some of the points of a new index.
+///
+/// When the optimizer merges indexed segments, points of the merged segment keep their links
+/// from the graph of the source segment, and only the rest of the points are inserted into the graph.
+/// All ids are internal ids of the new index.
+#[derive(Debug, Clone, Default)]
+pub struct OldGraphLinks {
+    pub m: usize,
+    pub m0: usize,
+    pub ef_construct: usize,
+    /// Links of each point on every level of the old graph, starting from the level 0
+    pub links: HashMap<PointOffsetType, LayersContainer>,
+}
+
+impl OldGraphLinks {
+    /// Links can only be reused by a graph, built with the same parameters
+    pub fn is_compatible(&self, m: usize, m0: usize, ef_construct: usize) -> bool {
+        self.m == m && self.m0 == m0 && self.ef_construct == ef_construct
+    }
+
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    /// Top level of the point in the old graph, if the point was there
+    pub fn point_level(&self, point_id: PointOffsetType) -> Option<usize> {
+        self.links
+            .get(&point_id)
+            .map(|layers| layers.len().saturating_sub(1))
+    }
+}
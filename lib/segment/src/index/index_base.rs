@@ -7,6 +7,7 @@ use crate::common::Flusher;
 use crate::data_types::vectors::VectorElementType;
 use crate::entry::entry_point::OperationResult;
 use crate::index::field_index::{CardinalityEstimation, PayloadBlockCondition};
+use crate::index::hnsw_index::old_graph_links::OldGraphLinks;
 use crate::payload_storage::FilterContext;
 use crate::telemetry::VectorIndexTelemetry;
 use crate::types::{
@@ -29,6 +30,25 @@ pub trait VectorIndex {
     /// Force internal index rebuild.
     fn build_index(&mut self, stopped: &AtomicBool) -> OperationResult<()>;
 
+    /// Rebuild index, reusing graph links of the points, which were indexed before.
+    /// Indexes, which are not graphs, ignore `old_links` and are built from scratch.
+    fn build_index_with_old_links(
+        &mut self,
+        _old_links: &[OldGraphLinks],
+        stopped: &AtomicBool,
+    ) -> OperationResult<()> {
+        self.build_index(stopped)
+    }
+
+    /// Graph links of the points, with ids translated from this index to a new one by `mapping`.
+    /// `None` if the index has no graph to reuse.
+    fn export_graph_links(
+        &self,
+        _mapping: &HashMap<PointOffsetType, PointOffsetType>,
+    ) -> Option<OldGraphLinks> {
+        None
+    }
+
     fn get_telemetry_data(&self) -> VectorIndexTelemetry;
 }
 
@@ -0,0 +1,386 @@
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use log::debug;
+use parking_lot::Mutex;
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::common::file_operations::{atomic_save_bin, read_bin};
+use crate::data_types::vectors::VectorElementType;
+use crate::entry::entry_point::{OperationError, OperationResult};
+use crate::index::struct_payload_index::StructPayloadIndex;
+use crate::index::{PayloadIndex, VectorIndex};
+use crate::payload_storage::FilterContext;
+use crate::spaces::tools::peek_top_largest_iterable;
+use crate::telemetry::{
+    TelemetryOperationAggregator, TelemetryOperationStatistics, TelemetryOperationTimer,
+    VectorIndexTelemetry,
+};
+use crate::types::{Distance, Filter, IvfConfig, PointOffsetType, SearchParams};
+use crate::vector_storage::{ScoredPointOffset, VectorStorageSS};
+
+pub const IVF_INDEX_FILE: &str = "ivf_index.bin";
+
+/// Number of k-means iterations to find list centroids
+const KMEANS_ITERATIONS: usize = 10;
+/// Centroids are trained on a sample of vectors, limited by this number of vectors per list
+const MAX_TRAINING_VECTORS_PER_LIST: usize = 256;
+
+/// Centroids and ids of vectors assigned to each of them
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct IvfLists {
+    centroids: Vec<Vec<VectorElementType>>,
+    lists: Vec<Vec<PointOffsetType>>,
+}
+
+impl IvfLists {
+    fn num_points(&self) -> usize {
+        self.lists.iter().map(|list| list.len()).sum()
+    }
+}
+
+fn closest_centroid(
+    distance: Distance,
+    centroids: &[Vec<VectorElementType>],
+    vector: &[VectorElementType],
+) -> usize {
+    let mut best_centroid = 0;
+    let mut best_score = f32::NEG_INFINITY;
+    for (centroid_id, centroid) in centroids.iter().enumerate() {
+        let score = distance.similarity(vector, centroid);
+        if score > best_score {
+            best_score = score;
+            best_centroid = centroid_id;
+        }
+    }
+    best_centroid
+}
+
+fn check_stopped(stopped: &AtomicBool) -> OperationResult<()> {
+    if stopped.load(Ordering::Relaxed) {
+        return Err(OperationError::Cancelled {
+            description: "Cancelled by external thread".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Inverted file index: vectors are split into lists by the closest k-means centroid.
+/// Search only scores vectors of `num_probes` lists, which centroids are the closest to the query.
+pub struct IvfIndex {
+    vector_storage: Arc<AtomicRefCell<VectorStorageSS>>,
+    payload_index: Arc<AtomicRefCell<StructPayloadIndex>>,
+    config: IvfConfig,
+    path: PathBuf,
+    lists: IvfLists,
+    plain_search_telemetry: Arc<Mutex<TelemetryOperationAggregator>>,
+    ivf_search_telemetry: Arc<Mutex<TelemetryOperationAggregator>>,
+}
+
+impl IvfIndex {
+    pub fn open(
+        path: &Path,
+        vector_storage: Arc<AtomicRefCell<VectorStorageSS>>,
+        payload_index: Arc<AtomicRefCell<StructPayloadIndex>>,
+        config: IvfConfig,
+    ) -> OperationResult<Self> {
+        create_dir_all(path)?;
+
+        let index_path = Self::get_index_path(path);
+        let lists = if index_path.exists() {
+            read_bin(&index_path)?
+        } else {
+            Default::default()
+        };
+
+        Ok(IvfIndex {
+            vector_storage,
+            payload_index,
+            config,
+            path: path.to_owned(),
+            lists,
+            plain_search_telemetry: TelemetryOperationAggregator::new(),
+            ivf_search_telemetry: TelemetryOperationAggregator::new(),
+        })
+    }
+
+    pub fn get_index_path(path: &Path) -> PathBuf {
+        path.join(IVF_INDEX_FILE)
+    }
+
+    pub fn save(&self) -> OperationResult<()> {
+        Ok(atomic_save_bin(
+            &Self::get_index_path(&self.path),
+            &self.lists,
+        )?)
+    }
+
+    fn num_probes(&self) -> usize {
+        self.config
+            .num_probes
+            .clamp(1, self.lists.lists.len().max(1))
+    }
+
+    /// Expected number of vectors, scored by a single search in the index
+    fn expected_candidates(&self) -> usize {
+        if self.lists.lists.is_empty() {
+            return 0;
+        }
+        self.lists.num_points() * self.num_probes() / self.lists.lists.len()
+    }
+
+    fn search_with_lists(
+        &self,
+        vector: &[VectorElementType],
+        filter_context: Option<&dyn FilterContext>,
+        top: usize,
+    ) -> Vec<ScoredPointOffset> {
+        let vector_storage = self.vector_storage.borrow();
+        let distance = vector_storage.distance();
+        let query = distance
+            .preprocess_vector(vector)
+            .unwrap_or_else(|| vector.to_vec());
+
+        let closest_lists = peek_top_largest_iterable(
+            self.lists
+                .centroids
+                .iter()
+                .enumerate()
+                .map(|(list_id, centroid)| ScoredPointOffset {
+                    idx: list_id as PointOffsetType,
+                    score: distance.similarity(&query, centroid),
+                }),
+            self.num_probes(),
+        );
+
+        let mut candidates = closest_lists
+            .iter()
+            .flat_map(|list| self.lists.lists[list.idx as usize].iter().copied())
+            .filter(|point_id| filter_context.map_or(true, |context| context.check(*point_id)));
+
+        vector_storage.score_points(vector, &mut candidates, top)
+    }
+
+    fn search_plain(
+        &self,
+        vectors: &[&[VectorElementType]],
+        filter: &Filter,
+        top: usize,
+    ) -> Vec<Vec<ScoredPointOffset>> {
+        let payload_index = self.payload_index.borrow();
+        let vector_storage = self.vector_storage.borrow();
+        let filtered_ids: Vec<_> = payload_index.query_points(filter).collect();
+        vectors
+            .iter()
+            .map(|vector| {
+                vector_storage.score_points(vector, &mut filtered_ids.iter().copied(), top)
+            })
+            .collect()
+    }
+}
+
+impl VectorIndex for IvfIndex {
+    fn search(
+        &self,
+        vectors: &[&[VectorElementType]],
+        filter: Option<&Filter>,
+        top: usize,
+        _params: Option<&SearchParams>,
+    ) -> Vec<Vec<ScoredPointOffset>> {
+        match filter {
+            None => {
+                let _timer = TelemetryOperationTimer::new(&self.ivf_search_telemetry);
+                vectors
+                    .iter()
+                    .map(|vector| self.search_with_lists(vector, None, top))
+                    .collect()
+            }
+            Some(query_filter) => {
+                let payload_index = self.payload_index.borrow();
+                let query_cardinality = payload_index.estimate_cardinality(query_filter);
+
+                // If filter is restrictive enough, exact search over filtered points
+                // is not slower than scanning the lists
+                if query_cardinality.max <= self.expected_candidates() {
+                    let _timer = TelemetryOperationTimer::new(&self.plain_search_telemetry);
+                    return self.search_plain(vectors, query_filter, top);
+                }
+
+                let _timer = TelemetryOperationTimer::new(&self.ivf_search_telemetry);
+                let filter_context = payload_index.filter_context(query_filter);
+                vectors
+                    .iter()
+                    .map(|vector| {
+                        self.search_with_lists(vector, Some(filter_context.as_ref()), top)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    fn build_index(&mut self, stopped: &AtomicBool) -> OperationResult<()> {
+        let vector_storage = self.vector_storage.borrow();
+        let distance = vector_storage.distance();
+        let dim = vector_storage.vector_dim();
+        let mut rng = thread_rng();
+
+        let ids: Vec<_> = vector_storage.iter_ids().collect();
+        let num_lists = self.config.get_num_lists(ids.len());
+
+        debug!("building ivf with {} lists for {}", num_lists, ids.len());
+
+        // Storage may keep Cosine vectors without normalization, so vectors are preprocessed
+        // to be comparable with centroids
+        let preprocessed_vector = |point_id: PointOffsetType| {
+            let vector = vector_storage.get_vector(point_id).unwrap();
+            distance.preprocess_vector(&vector).unwrap_or(vector)
+        };
+        let training_vectors: Vec<_> = ids
+            .choose_multiple(&mut rng, num_lists * MAX_TRAINING_VECTORS_PER_LIST)
+            .map(|point_id| preprocessed_vector(*point_id))
+            .collect();
+
+        let mut centroids: Vec<_> = training_vectors
+            .choose_multiple(&mut rng, num_lists)
+            .cloned()
+            .collect();
+
+        for _ in 0..KMEANS_ITERATIONS {
+            check_stopped(stopped)?;
+
+            let assignments: Vec<_> = training_vectors
+                .par_iter()
+                .map(|vector| closest_centroid(distance, &centroids, vector))
+                .collect();
+
+            let mut sums = vec![vec![0.0; dim]; centroids.len()];
+            let mut counts = vec![0usize; centroids.len()];
+            for (vector, centroid_id) in training_vectors.iter().zip(assignments) {
+                counts[centroid_id] += 1;
+                for (sum, value) in sums[centroid_id].iter_mut().zip(vector) {
+                    *sum += value;
+                }
+            }
+
+            for ((centroid, sum), count) in centroids.iter_mut().zip(sums).zip(counts) {
+                // Centroid without assigned vectors keeps its previous position
+                if count == 0 {
+                    continue;
+                }
+                let mean: Vec<_> = sum.into_iter().map(|x| x / count as f32).collect();
+                *centroid = distance.preprocess_vector(&mean).unwrap_or(mean);
+            }
+        }
+
+        let assignments = ids
+            .par_iter()
+            .map(|point_id| {
+                check_stopped(stopped)?;
+                let vector = preprocessed_vector(*point_id);
+                Ok(closest_centroid(distance, &centroids, &vector))
+            })
+            .collect::<OperationResult<Vec<_>>>()?;
+
+        let mut lists = vec![vec![]; centroids.len()];
+        for (point_id, centroid_id) in ids.into_iter().zip(assignments) {
+            lists[centroid_id].push(point_id);
+        }
+
+        debug!("finish ivf lists");
+
+        self.lists = IvfLists { centroids, lists };
+        self.save()
+    }
+
+    fn get_telemetry_data(&self) -> VectorIndexTelemetry {
+        VectorIndexTelemetry {
+            small_cardinality_searches: self.plain_search_telemetry.lock().get_statistics(),
+            large_cardinality_searches: self.ivf_search_telemetry.lock().get_statistics(),
+            positive_check_cardinality_searches: TelemetryOperationStatistics::default(),
+            negative_check_cardinality_searches: TelemetryOperationStatistics::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::data_types::vectors::{only_default_vector, DEFAULT_VECTOR_NAME};
+    use crate::entry::entry_point::SegmentEntry;
+    use crate::fixtures::index_fixtures::random_vector;
+    use crate::segment_constructor::simple_segment_constructor::build_simple_segment;
+
+    #[test]
+    fn test_ivf_search() {
+        let dim = 8;
+        let num_vectors = 1000;
+        let top = 10;
+        let mut rng = thread_rng();
+
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let index_dir = Builder::new().prefix("ivf_dir").tempdir().unwrap();
+
+        let mut segment = build_simple_segment(dir.path(), dim, Distance::Cosine).unwrap();
+        for n in 0..num_vectors {
+            let vector = random_vector(&mut rng, dim);
+            segment
+                .upsert_vector(n as u64, (n as u64).into(), &only_default_vector(&vector))
+                .unwrap();
+        }
+
+        let vector_storage = segment.vector_data[DEFAULT_VECTOR_NAME]
+            .vector_storage
+            .clone();
+        let config = IvfConfig {
+            num_lists: 10,
+            // Scan all lists, so result must be exact
+            num_probes: 10,
+        };
+
+        let mut ivf_index = IvfIndex::open(
+            index_dir.path(),
+            vector_storage.clone(),
+            segment.payload_index.clone(),
+            config,
+        )
+        .unwrap();
+        ivf_index.build_index(&AtomicBool::new(false)).unwrap();
+        assert_eq!(ivf_index.lists.lists.len(), 10);
+        assert_eq!(ivf_index.lists.num_points(), num_vectors);
+
+        let query = random_vector(&mut rng, dim);
+        let ivf_result = &ivf_index.search(&[&query], None, top, None)[0];
+        let plain_result = vector_storage.borrow().score_all(&query, top);
+        assert_eq!(ivf_result, &plain_result);
+
+        // Lists are persisted
+        drop(ivf_index);
+        let config = IvfConfig {
+            num_lists: 10,
+            num_probes: 2,
+        };
+        let ivf_index = IvfIndex::open(
+            index_dir.path(),
+            vector_storage,
+            segment.payload_index.clone(),
+            config,
+        )
+        .unwrap();
+        assert_eq!(ivf_index.lists.num_points(), num_vectors);
+
+        let ivf_result = &ivf_index.search(&[&query], None, top, None)[0];
+        assert_eq!(ivf_result.len(), top);
+        let unique_ids: HashSet<_> = ivf_result.iter().map(|x| x.idx).collect();
+        assert_eq!(unique_ids.len(), top);
+    }
+}
@@ -1,12 +1,14 @@
 pub mod field_index;
 pub mod hnsw_index;
 mod index_base;
+pub mod ivf_index;
 mod key_encoding;
 mod payload_config;
 pub mod plain_payload_index;
 pub mod query_estimator;
 mod query_optimization;
 mod sample_estimation;
+pub mod sparse_index;
 mod struct_filter_context;
 pub mod struct_payload_index;
 mod visited_pool;
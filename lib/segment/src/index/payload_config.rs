@@ -9,10 +9,20 @@ use crate::types::{PayloadFieldSchema, PayloadKeyType};
 
 pub const PAYLOAD_INDEX_CONFIG_FILE: &str = "config.json";
 
+/// Version of the storage format of field indexes.
+/// Should be increased on incompatible changes of index records, so existing indexes are rebuilt on load.
+///
+/// Version 2: documents of full-text indexes keep token sequences for phrase matching
+pub const FIELD_INDEX_FORMAT_VERSION: u32 = 2;
+
 /// Keeps information of which field should be index
 #[derive(Debug, Default, Deserialize, Serialize, Clone)]
 pub struct PayloadConfig {
     pub indexed_fields: HashMap<PayloadKeyType, PayloadFieldSchema>,
+    /// Storage format version of each built field index.
+    /// Indexes built before versioning was introduced have no version.
+    #[serde(default)]
+    pub index_versions: HashMap<PayloadKeyType, u32>,
 }
 
 impl PayloadConfig {
@@ -27,4 +37,14 @@ impl PayloadConfig {
     pub fn save(&self, path: &Path) -> OperationResult<()> {
         Ok(atomic_save_json(path, self)?)
     }
+
+    /// Check if index of the field is stored in the current format and can be loaded as is
+    pub fn is_index_format_current(&self, field: &str) -> bool {
+        self.index_versions.get(field) == Some(&FIELD_INDEX_FORMAT_VERSION)
+    }
+
+    pub fn set_index_format_current(&mut self, field: &str) {
+        self.index_versions
+            .insert(field.to_owned(), FIELD_INDEX_FORMAT_VERSION);
+    }
 }
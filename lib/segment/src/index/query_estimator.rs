@@ -186,6 +186,7 @@ mod tests {
             range: None,
             geo_bounding_box: None,
             geo_radius: None,
+            geo_polygon: None,
             values_count: None,
         })
     }
@@ -232,6 +233,12 @@ mod tests {
                 exp: TOTAL / 2,
                 max: TOTAL,
             },
+            Condition::IsNull(condition) => CardinalityEstimation {
+                primary_clauses: vec![PrimaryCondition::IsNull(condition.to_owned())],
+                min: 0,
+                exp: TOTAL / 2,
+                max: TOTAL,
+            },
         }
     }
 
@@ -261,6 +268,8 @@ mod tests {
             PrimaryCondition::Condition(field) => assert_eq!(&field.key, "size"),
             PrimaryCondition::Ids(_) => panic!(),
             PrimaryCondition::IsEmpty(_) => panic!(),
+            PrimaryCondition::IsNull(_) => panic!(),
+            PrimaryCondition::Composite(_) => panic!(),
         }
         assert!(estimation.max <= TOTAL);
         assert!(estimation.exp <= estimation.max);
@@ -374,6 +383,8 @@ mod tests {
             }
             PrimaryCondition::Ids(_) => panic!("Should not go here"),
             PrimaryCondition::IsEmpty(_) => panic!("Should not go here"),
+            PrimaryCondition::IsNull(_) => panic!("Should not go here"),
+            PrimaryCondition::Composite(_) => panic!("Should not go here"),
         });
         assert!(estimation.max <= TOTAL);
         assert!(estimation.exp <= estimation.max);
@@ -1,14 +1,19 @@
 use std::collections::HashSet;
 
+use geo::{Contains, Point};
+
 use crate::id_tracker::IdTrackerSS;
+use crate::index::field_index::null_index::NullIndex;
 use crate::index::field_index::FieldIndex;
 use crate::index::query_optimization::optimized_filter::ConditionCheckerFn;
 use crate::index::query_optimization::optimizer::IndexesMap;
 use crate::index::query_optimization::payload_provider::PayloadProvider;
-use crate::payload_storage::query_checker::{check_field_condition, check_is_empty_condition};
+use crate::payload_storage::query_checker::{
+    check_field_condition, check_is_empty_condition, check_is_null_condition,
+};
 use crate::types::{
-    Condition, FieldCondition, FloatPayloadType, GeoBoundingBox, GeoRadius, Match, MatchText,
-    MatchValue, PointOffsetType, Range, ValueVariants,
+    Condition, FieldCondition, FloatPayloadType, GeoBoundingBox, GeoPolygon, GeoRadius, Match,
+    MatchPhrase, MatchText, MatchValue, PointOffsetType, Range, ValueVariants,
 };
 
 pub fn condition_converter<'a>(
@@ -33,14 +38,23 @@ pub fn condition_converter<'a>(
                     })
                 })
             }),
-        // ToDo: It might be possible to make this condition faster by using index to check
-        //       if there is any value. But if value if not found,
-        //       it does not mean that there are no values in payload
-        Condition::IsEmpty(is_empty) => Box::new(move |point_id| {
-            payload_provider.with_payload(point_id, |payload| {
-                check_is_empty_condition(is_empty, &payload)
-            })
-        }),
+        Condition::IsEmpty(is_empty) => match get_null_index(field_indexes, &is_empty.is_empty.key)
+        {
+            Some(null_index) => Box::new(move |point_id| !null_index.has_values(point_id)),
+            None => Box::new(move |point_id| {
+                payload_provider.with_payload(point_id, |payload| {
+                    check_is_empty_condition(is_empty, &payload)
+                })
+            }),
+        },
+        Condition::IsNull(is_null) => match get_null_index(field_indexes, &is_null.is_null.key) {
+            Some(null_index) => Box::new(move |point_id| null_index.is_null(point_id)),
+            None => Box::new(move |point_id| {
+                payload_provider.with_payload(point_id, |payload| {
+                    check_is_null_condition(is_null, &payload)
+                })
+            }),
+        },
         // ToDo: It might be possible to make this condition faster by using `VisitedPool` instead of HashSet
         Condition::HasId(has_id) => {
             let segment_ids: HashSet<_> = has_id
@@ -54,6 +68,15 @@ pub fn condition_converter<'a>(
     }
 }
 
+fn get_null_index<'a>(field_indexes: &'a IndexesMap, key: &str) -> Option<&'a NullIndex> {
+    field_indexes.get(key).and_then(|indexes| {
+        indexes.iter().find_map(|index| match index {
+            FieldIndex::NullIndex(null_index) => Some(null_index),
+            _ => None,
+        })
+    })
+}
+
 pub fn field_condition_index<'a>(
     index: &'a FieldIndex,
     field_condition: &FieldCondition,
@@ -90,6 +113,14 @@ pub fn field_condition_index<'a>(
         return Some(checker);
     }
 
+    if let Some(checker) = field_condition
+        .geo_polygon
+        .clone()
+        .and_then(|cond| get_geo_polygon_checkers(index, cond))
+    {
+        return Some(checker);
+    }
+
     None
 }
 
@@ -127,6 +158,24 @@ pub fn get_geo_bounding_box_checkers(
     }
 }
 
+pub fn get_geo_polygon_checkers(
+    index: &FieldIndex,
+    geo_polygon: GeoPolygon,
+) -> Option<ConditionCheckerFn> {
+    let polygon = geo_polygon.to_polygon();
+    match index {
+        FieldIndex::GeoIndex(geo_index) => Some(Box::new(move |point_id: PointOffsetType| {
+            match geo_index.get_values(point_id) {
+                None => false,
+                Some(values) => values
+                    .iter()
+                    .any(|geo_point| polygon.contains(&Point::new(geo_point.lon, geo_point.lat))),
+            }
+        })),
+        _ => None,
+    }
+}
+
 pub fn get_range_checkers(index: &FieldIndex, range: Range) -> Option<ConditionCheckerFn> {
     match index {
         FieldIndex::IntIndex(num_index) => Some(Box::new(move |point_id: PointOffsetType| {
@@ -183,5 +232,17 @@ pub fn get_match_checkers(index: &FieldIndex, cond_match: Match) -> Option<Condi
             }
             _ => None,
         },
+        Match::Phrase(MatchPhrase { phrase }) => match index {
+            FieldIndex::FullTextIndex(full_text_index) => {
+                let parsed_phrase = full_text_index.parse_phrase(&phrase);
+                Some(Box::new(
+                    move |point_id: PointOffsetType| match full_text_index.get_doc(point_id) {
+                        None => false,
+                        Some(doc) => parsed_phrase.check_match(doc),
+                    },
+                ))
+            }
+            _ => None,
+        },
     }
 }
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use parking_lot::RwLock;
+use rocksdb::DB;
+
+use crate::common::rocksdb_wrapper::DatabaseColumnWrapper;
+use crate::common::Flusher;
+use crate::data_types::sparse_vector::{DimId, SparseVector};
+use crate::entry::entry_point::{OperationError, OperationResult};
+use crate::spaces::tools::peek_top_largest_iterable;
+use crate::types::{PointOffsetType, ScoreType};
+use crate::vector_storage::ScoredPointOffset;
+
+/// Element of a posting list: point, which has non-zero value in the dimension
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PostingElement {
+    point_id: PointOffsetType,
+    weight: f32,
+}
+
+/// In-memory inverted index of sparse vectors with on-update persistence using `store`.
+///
+/// Points are identified by internal ids, assigned by dense vector storages of the segment.
+/// Search scores points by dot product with the query, considering only the points
+/// which share at least one non-zero dimension with it.
+pub struct SparseVectorIndex {
+    /// Original vectors, sorted by indices
+    vectors: HashMap<PointOffsetType, SparseVector>,
+    postings: HashMap<DimId, Vec<PostingElement>>,
+    db_wrapper: DatabaseColumnWrapper,
+}
+
+pub fn open_sparse_vector_index(
+    database: Arc<RwLock<DB>>,
+    database_column_name: &str,
+) -> OperationResult<Arc<AtomicRefCell<SparseVectorIndex>>> {
+    let db_wrapper = DatabaseColumnWrapper::new(database, database_column_name);
+    let mut index = SparseVectorIndex {
+        vectors: HashMap::new(),
+        postings: HashMap::new(),
+        db_wrapper,
+    };
+
+    let mut stored_vectors = vec![];
+    for (key, value) in index.db_wrapper.lock_db().iter()? {
+        let point_id: PointOffsetType = bincode::deserialize(&key)
+            .map_err(|_| OperationError::service_error("cannot deserialize point id from db"))?;
+        let vector: SparseVector = bincode::deserialize(&value).map_err(|_| {
+            OperationError::service_error("cannot deserialize sparse vector from db")
+        })?;
+        stored_vectors.push((point_id, vector));
+    }
+    for (point_id, vector) in stored_vectors {
+        index.insert_postings(point_id, vector);
+    }
+
+    Ok(Arc::new(AtomicRefCell::new(index)))
+}
+
+impl SparseVectorIndex {
+    fn insert_postings(&mut self, point_id: PointOffsetType, vector: SparseVector) {
+        for (index, value) in vector.indices.iter().zip(vector.values.iter()) {
+            self.postings
+                .entry(*index)
+                .or_default()
+                .push(PostingElement {
+                    point_id,
+                    weight: *value,
+                });
+        }
+        self.vectors.insert(point_id, vector);
+    }
+
+    fn remove_postings(&mut self, point_id: PointOffsetType) -> Option<SparseVector> {
+        let vector = self.vectors.remove(&point_id)?;
+        for index in &vector.indices {
+            if let Some(posting) = self.postings.get_mut(index) {
+                posting.retain(|element| element.point_id != point_id);
+                if posting.is_empty() {
+                    self.postings.remove(index);
+                }
+            }
+        }
+        Some(vector)
+    }
+
+    /// Number of points with sparse vectors
+    pub fn vector_count(&self) -> usize {
+        self.vectors.len()
+    }
+
+    pub fn get_vector(&self, point_id: PointOffsetType) -> Option<&SparseVector> {
+        self.vectors.get(&point_id)
+    }
+
+    /// Insert or replace vector of the point. Empty vector removes the point from the index
+    pub fn upsert(
+        &mut self,
+        point_id: PointOffsetType,
+        mut vector: SparseVector,
+    ) -> OperationResult<()> {
+        vector
+            .validate()
+            .map_err(|err| OperationError::WrongSparseVector { description: err })?;
+        self.remove_postings(point_id);
+        if vector.is_empty() {
+            return self
+                .db_wrapper
+                .remove(bincode::serialize(&point_id).unwrap());
+        }
+        vector.sort_by_indices();
+        self.db_wrapper.put(
+            bincode::serialize(&point_id).unwrap(),
+            bincode::serialize(&vector).unwrap(),
+        )?;
+        self.insert_postings(point_id, vector);
+        Ok(())
+    }
+
+    pub fn delete(&mut self, point_id: PointOffsetType) -> OperationResult<()> {
+        if self.remove_postings(point_id).is_some() {
+            self.db_wrapper
+                .remove(bincode::serialize(&point_id).unwrap())?;
+        }
+        Ok(())
+    }
+
+    /// Find `top` points with the largest dot product with the `query`.
+    /// Only points, accepted by `check_point`, are considered
+    pub fn search(
+        &self,
+        query: &SparseVector,
+        top: usize,
+        check_point: impl Fn(PointOffsetType) -> bool,
+    ) -> Vec<ScoredPointOffset> {
+        let mut scores: HashMap<PointOffsetType, ScoreType> = HashMap::new();
+        for (index, value) in query.indices.iter().zip(query.values.iter()) {
+            if let Some(posting) = self.postings.get(index) {
+                for element in posting {
+                    *scores.entry(element.point_id).or_default() += value * element.weight;
+                }
+            }
+        }
+        let scored_points = scores
+            .into_iter()
+            .filter(|(point_id, _)| check_point(*point_id))
+            .map(|(idx, score)| ScoredPointOffset { idx, score });
+        peek_top_largest_iterable(scored_points, top)
+    }
+
+    pub fn flusher(&self) -> Flusher {
+        self.db_wrapper.flusher()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::common::rocksdb_wrapper::open_db;
+
+    const DB_SPARSE_CF: &str = "sparse_vector";
+
+    #[test]
+    fn test_sparse_index_search() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let db = open_db(dir.path(), &[DB_SPARSE_CF]).unwrap();
+        let index = open_sparse_vector_index(db.clone(), DB_SPARSE_CF).unwrap();
+
+        {
+            let mut index = index.borrow_mut();
+            index
+                .upsert(0, SparseVector::new(vec![1, 2], vec![1.0, 1.0]).unwrap())
+                .unwrap();
+            index
+                .upsert(1, SparseVector::new(vec![2, 3], vec![2.0, 1.0]).unwrap())
+                .unwrap();
+            index
+                .upsert(2, SparseVector::new(vec![4], vec![5.0]).unwrap())
+                .unwrap();
+            index
+                .upsert(3, SparseVector::new(vec![3, 1], vec![0.5, 3.0]).unwrap())
+                .unwrap();
+            // Replace vector of the point
+            index
+                .upsert(2, SparseVector::new(vec![3], vec![4.0]).unwrap())
+                .unwrap();
+            index.delete(0).unwrap();
+        }
+
+        let query = SparseVector::new(vec![3, 2], vec![1.0, 1.0]).unwrap();
+        let result = index.borrow().search(&query, 10, |_| true);
+        let ids: Vec<_> = result.iter().map(|point| point.idx).collect();
+        assert_eq!(ids, vec![2, 1, 3]);
+        assert_eq!(result[0].score, 4.0);
+        assert_eq!(result[1].score, 3.0);
+
+        let result = index.borrow().search(&query, 10, |point_id| point_id != 2);
+        assert_eq!(result[0].idx, 1);
+
+        // Index is restored from the storage
+        drop(index);
+        let index = open_sparse_vector_index(db, DB_SPARSE_CF).unwrap();
+        let index = index.borrow();
+        assert_eq!(index.vector_count(), 3);
+        assert!(index.get_vector(0).is_none());
+        assert_eq!(index.get_vector(3).unwrap().indices, vec![1, 3]);
+        assert_eq!(index.search(&query, 2, |_| true).len(), 2);
+    }
+}
@@ -5,6 +5,7 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use atomic_refcell::AtomicRefCell;
+use itertools::Itertools;
 use log::debug;
 use parking_lot::RwLock;
 use rocksdb::DB;
@@ -13,14 +14,18 @@ use schemars::_serde_json::Value;
 use crate::common::arc_atomic_ref_cell_iterator::ArcAtomicRefCellIterator;
 use crate::common::rocksdb_wrapper::open_db_with_existing_cf;
 use crate::common::Flusher;
+use crate::data_types::composite_index::CompositeIndexParams;
 use crate::entry::entry_point::{OperationError, OperationResult};
 use crate::id_tracker::IdTrackerSS;
+use crate::index::field_index::composite_index::{CompositeIndex, CompositeMatch};
 use crate::index::field_index::index_selector::index_selector;
+use crate::index::field_index::null_index::NullIndex;
 use crate::index::field_index::{
-    CardinalityEstimation, FieldIndex, PayloadBlockCondition, PrimaryCondition,
+    CardinalityEstimation, FieldIndex, FieldIndexBuilderTrait, PayloadBlockCondition,
+    PrimaryCondition,
 };
 use crate::index::payload_config::PayloadConfig;
-use crate::index::query_estimator::estimate_filter;
+use crate::index::query_estimator::{combine_must_estimations, estimate_filter};
 use crate::index::query_optimization::optimizer::IndexesMap;
 use crate::index::query_optimization::payload_provider::PayloadProvider;
 use crate::index::struct_filter_context::StructFilterContext;
@@ -30,8 +35,9 @@ use crate::payload_storage::payload_storage_enum::PayloadStorageEnum;
 use crate::payload_storage::{FilterContext, PayloadStorage};
 use crate::telemetry::PayloadIndexTelemetry;
 use crate::types::{
-    infer_value_type, Condition, FieldCondition, Filter, IsEmptyCondition, Payload,
-    PayloadFieldSchema, PayloadKeyType, PayloadKeyTypeRef, PayloadSchemaType, PointOffsetType,
+    infer_value_type, Condition, FieldCondition, Filter, IsEmptyCondition, IsNullCondition,
+    Payload, PayloadFieldSchema, PayloadKeyType, PayloadKeyTypeRef, PayloadSchemaParams,
+    PayloadSchemaType, PointOffsetType,
 };
 
 pub const PAYLOAD_FIELD_INDEX_PATH: &str = "fields";
@@ -43,11 +49,15 @@ pub struct StructPayloadIndex {
     id_tracker: Arc<AtomicRefCell<IdTrackerSS>>,
     /// Indexes, associated with fields
     pub field_indexes: IndexesMap,
+    /// Indexes over multiple fields, by index name
+    composite_indexes: HashMap<PayloadKeyType, CompositeIndex>,
     config: PayloadConfig,
     /// Root of index persistence dir
     path: PathBuf,
     visited_pool: VisitedPool,
     db: Arc<RwLock<DB>>,
+    /// If false - map and numeric indexes are kept in immutable packed form
+    is_appendable: bool,
 }
 
 impl StructPayloadIndex {
@@ -102,21 +112,85 @@ impl StructPayloadIndex {
 
     fn load_all_fields(&mut self) -> OperationResult<()> {
         let mut field_indexes: IndexesMap = Default::default();
+        let mut composite_indexes: HashMap<PayloadKeyType, CompositeIndex> = Default::default();
 
         for (field, payload_schema) in &self.config.indexed_fields {
+            if let Some(params) = payload_schema.composite_params() {
+                let composite_index = self.load_composite_index(field, params)?;
+                composite_indexes.insert(field.clone(), composite_index);
+                continue;
+            }
             let field_index = self.load_from_db(field, payload_schema.to_owned())?;
             field_indexes.insert(field.clone(), field_index);
         }
         self.field_indexes = field_indexes;
+        self.composite_indexes = composite_indexes;
+
+        // Outdated indexes are rebuilt in the current format at this point
+        let rebuilt_fields = self
+            .config
+            .indexed_fields
+            .keys()
+            .filter(|field| !self.config.is_index_format_current(field))
+            .cloned()
+            .collect_vec();
+        if !rebuilt_fields.is_empty() {
+            for field in &rebuilt_fields {
+                self.mark_index_format_current(field)?;
+            }
+            self.save_config()?;
+        }
         Ok(())
     }
 
+    /// Persist built indexes of the field and record their storage format version.
+    /// Version is recorded only after the flush, so partially persisted index is rebuilt on load.
+    fn mark_index_format_current(&mut self, field: PayloadKeyTypeRef) -> OperationResult<()> {
+        if let Some(indexes) = self.field_indexes.get(field) {
+            for index in indexes {
+                index.flusher()()?;
+            }
+        }
+        if let Some(composite_index) = self.composite_indexes.get(field) {
+            composite_index.flusher()()?;
+        }
+        self.config.set_index_format_current(field);
+        Ok(())
+    }
+
+    fn load_composite_index(
+        &self,
+        name: PayloadKeyTypeRef,
+        params: &CompositeIndexParams,
+    ) -> OperationResult<CompositeIndex> {
+        if !self.config.is_index_format_current(name) {
+            debug!("Composite index `{name}` has outdated storage format. Rebuilding...");
+            return self.build_composite_index(name, params);
+        }
+        let mut index = CompositeIndex::new(
+            self.db.clone(),
+            name,
+            params.keys.clone(),
+            self.is_appendable,
+        );
+        if !index.load()? {
+            debug!("Composite index `{name}` was not loaded. Building...");
+            index = self.build_composite_index(name, params)?;
+        }
+        Ok(index)
+    }
+
     fn load_from_db(
         &self,
         field: PayloadKeyTypeRef,
         payload_schema: PayloadFieldSchema,
     ) -> OperationResult<Vec<FieldIndex>> {
-        let mut indexes = index_selector(field, &payload_schema, self.db.clone());
+        if !self.config.is_index_format_current(field) {
+            debug!("Index for `{field}` has outdated storage format. Rebuilding...");
+            return self.build_field_indexes(field, payload_schema);
+        }
+        let mut indexes =
+            index_selector(field, &payload_schema, self.db.clone(), self.is_appendable);
 
         let mut is_loaded = true;
         for ref mut index in indexes.iter_mut() {
@@ -137,6 +211,7 @@ impl StructPayloadIndex {
         payload: Arc<AtomicRefCell<PayloadStorageEnum>>,
         id_tracker: Arc<AtomicRefCell<IdTrackerSS>>,
         path: &Path,
+        is_appendable: bool,
     ) -> OperationResult<Self> {
         create_dir_all(path)?;
         let config_path = PayloadConfig::get_config_path(path);
@@ -154,10 +229,12 @@ impl StructPayloadIndex {
             payload,
             id_tracker,
             field_indexes: Default::default(),
+            composite_indexes: Default::default(),
             config,
             path: path.to_owned(),
             visited_pool: Default::default(),
             db,
+            is_appendable,
         };
 
         if !index.config_path().exists() {
@@ -176,21 +253,52 @@ impl StructPayloadIndex {
         payload_schema: PayloadFieldSchema,
     ) -> OperationResult<Vec<FieldIndex>> {
         let payload_storage = self.payload.borrow();
-        let mut field_indexes = index_selector(field, &payload_schema, self.db.clone());
-        for index in &field_indexes {
-            index.recreate()?;
+        let mut builders =
+            index_selector(field, &payload_schema, self.db.clone(), self.is_appendable)
+                .into_iter()
+                .map(FieldIndex::into_builder)
+                .collect_vec();
+        for builder in builders.iter_mut() {
+            builder.init()?;
         }
 
         payload_storage.iter(|point_id, point_payload| {
-            let field_value_opt = point_payload.get_value(field);
+            let field_value_opt = point_payload.get_path_value(field);
             if let Some(field_value) = field_value_opt {
-                for field_index in field_indexes.iter_mut() {
-                    field_index.add_point(point_id, field_value)?;
+                for builder in builders.iter_mut() {
+                    builder.add_point(point_id, &field_value)?;
                 }
             }
             Ok(true)
         })?;
-        Ok(field_indexes)
+
+        builders
+            .into_iter()
+            .map(|builder| builder.finalize())
+            .collect()
+    }
+
+    pub fn build_composite_index(
+        &self,
+        name: PayloadKeyTypeRef,
+        params: &CompositeIndexParams,
+    ) -> OperationResult<CompositeIndex> {
+        let payload_storage = self.payload.borrow();
+        let mut builder = CompositeIndex::new(
+            self.db.clone(),
+            name,
+            params.keys.clone(),
+            self.is_appendable,
+        )
+        .into_builder();
+        builder.init()?;
+
+        payload_storage.iter(|point_id, point_payload| {
+            builder.add_point(point_id, point_payload)?;
+            Ok(true)
+        })?;
+
+        builder.finalize()
     }
 
     fn build_and_save(
@@ -198,11 +306,84 @@ impl StructPayloadIndex {
         field: PayloadKeyTypeRef,
         payload_schema: PayloadFieldSchema,
     ) -> OperationResult<()> {
+        if let Some(params) = payload_schema.composite_params() {
+            let composite_index = self.build_composite_index(field, params)?;
+            self.composite_indexes.insert(field.into(), composite_index);
+            return Ok(());
+        }
         let field_indexes = self.build_field_indexes(field, payload_schema)?;
         self.field_indexes.insert(field.into(), field_indexes);
         Ok(())
     }
 
+    /// Re-index the point in composite indexes, which depend on the whole payload of the point
+    fn update_composite_indexes(&mut self, point_id: PointOffsetType) -> OperationResult<()> {
+        if self.composite_indexes.is_empty() {
+            return Ok(());
+        }
+        let payload = self.payload.borrow().payload(point_id)?;
+        for composite_index in self.composite_indexes.values_mut() {
+            composite_index.add_point(point_id, &payload)?;
+        }
+        Ok(())
+    }
+
+    /// Estimate the filter, answering `must` conditions on all keys of a composite index
+    /// with a single lookup. Returns `None` if no composite index is applicable.
+    fn estimate_with_composite_index(&self, query: &Filter) -> Option<CardinalityEstimation> {
+        let must = query.must.as_ref()?;
+        let (name, tuple, positions, points_count) = self
+            .composite_indexes
+            .iter()
+            .filter_map(|(name, composite_index)| {
+                let (tuple, positions) = composite_index.match_conditions(must)?;
+                let points_count = composite_index.points_count(&tuple);
+                Some((name, tuple, positions, points_count))
+            })
+            .min_by_key(|(_, _, _, points_count)| *points_count)?;
+
+        let total_points = self.total_points();
+        let composite_estimation = CardinalityEstimation {
+            primary_clauses: vec![PrimaryCondition::Composite(CompositeMatch {
+                index: name.clone(),
+                tuple,
+            })],
+            min: points_count,
+            exp: points_count,
+            max: points_count,
+        };
+
+        // Conditions, which are not covered by the composite index, are estimated as usual
+        let rest_query = Filter {
+            should: query.should.clone(),
+            must: Some(
+                must.iter()
+                    .enumerate()
+                    .filter(|(position, _)| !positions.contains(position))
+                    .map(|(_, condition)| condition.clone())
+                    .collect(),
+            ),
+            must_not: query.must_not.clone(),
+        };
+        let estimator = |condition: &Condition| self.condition_cardinality(condition);
+        let rest_estimation = estimate_filter(&estimator, &rest_query, total_points);
+
+        Some(combine_must_estimations(
+            &[composite_estimation, rest_estimation],
+            total_points,
+        ))
+    }
+
+    /// Index of empty and `null` values, available for each indexed field
+    fn null_index(&self, field: PayloadKeyTypeRef) -> Option<&NullIndex> {
+        self.field_indexes.get(field).and_then(|indexes| {
+            indexes.iter().find_map(|index| match index {
+                FieldIndex::NullIndex(null_index) => Some(null_index),
+                _ => None,
+            })
+        })
+    }
+
     pub fn total_points(&self) -> usize {
         self.id_tracker.borrow().points_count()
     }
@@ -226,25 +407,44 @@ impl StructPayloadIndex {
             Condition::Filter(_) => panic!("Unexpected branching"),
             Condition::IsEmpty(IsEmptyCondition { is_empty: field }) => {
                 let total_points = self.total_points();
+                let primary_clauses = vec![PrimaryCondition::IsEmpty(IsEmptyCondition {
+                    is_empty: field.to_owned(),
+                })];
 
-                let mut indexed_points = 0;
-                if let Some(field_indexes) = self.field_indexes.get(&field.key) {
-                    for index in field_indexes {
-                        indexed_points = indexed_points.max(index.count_indexed_points())
+                if let Some(null_index) = self.null_index(&field.key) {
+                    let empty_points = total_points.saturating_sub(null_index.points_with_values());
+                    CardinalityEstimation {
+                        primary_clauses,
+                        min: empty_points,
+                        exp: empty_points,
+                        max: empty_points,
+                    }
+                } else {
+                    CardinalityEstimation {
+                        primary_clauses,
+                        min: 0,
+                        exp: total_points / 2,
+                        max: total_points,
                     }
+                }
+            }
+            Condition::IsNull(IsNullCondition { is_null: field }) => {
+                let total_points = self.total_points();
+                let primary_clauses = vec![PrimaryCondition::IsNull(IsNullCondition {
+                    is_null: field.to_owned(),
+                })];
+
+                if let Some(null_index) = self.null_index(&field.key) {
+                    let null_points = null_index.null_points();
                     CardinalityEstimation {
-                        primary_clauses: vec![PrimaryCondition::IsEmpty(IsEmptyCondition {
-                            is_empty: field.to_owned(),
-                        })],
-                        min: 0, // It is possible, that some non-empty payloads are not indexed
-                        exp: total_points.saturating_sub(indexed_points), // Expect field type consistency
-                        max: total_points.saturating_sub(indexed_points),
+                        primary_clauses,
+                        min: null_points,
+                        exp: null_points,
+                        max: null_points,
                     }
                 } else {
                     CardinalityEstimation {
-                        primary_clauses: vec![PrimaryCondition::IsEmpty(IsEmptyCondition {
-                            is_empty: field.to_owned(),
-                        })],
+                        primary_clauses,
                         min: 0,
                         exp: total_points / 2,
                         max: total_points,
@@ -272,15 +472,74 @@ impl StructPayloadIndex {
         }
     }
 
+    /// Fields, indexed with `is_principal` flag
+    pub fn principal_fields(&self) -> Vec<PayloadKeyType> {
+        self.config
+            .indexed_fields
+            .iter()
+            .filter(|(_, schema)| {
+                matches!(
+                    schema,
+                    PayloadFieldSchema::FieldParams(PayloadSchemaParams::Numeric(params))
+                        if params.is_principal()
+                )
+            })
+            .map(|(field, _)| field.clone())
+            .sorted()
+            .collect()
+    }
+
+    /// Smallest and largest indexed values of the field, `None` if there are no values
+    /// or the field has no range index
+    pub fn field_values_range(&self, field: PayloadKeyTypeRef) -> Option<(f64, f64)> {
+        self.field_indexes
+            .get(field)?
+            .iter()
+            .find_map(|index| index.values_range())
+    }
+
+    /// Checks if the filter requires values of a principal field, which are out of the range of
+    /// values stored in this index. No point can match such filter, so it can be skipped entirely.
+    pub fn is_out_of_principal_range(&self, filter: &Filter) -> bool {
+        let must = match &filter.must {
+            Some(must) => must,
+            None => return false,
+        };
+        let principal_fields = self.principal_fields();
+        must.iter().any(|condition| match condition {
+            Condition::Field(FieldCondition {
+                key,
+                range: Some(range),
+                ..
+            }) if principal_fields.contains(key) => match self.field_values_range(key) {
+                Some((min, max)) => !range.overlaps(min, max),
+                // Points without values never match the range
+                None => true,
+            },
+            _ => false,
+        })
+    }
+
     pub fn get_telemetry_data(&self) -> Vec<PayloadIndexTelemetry> {
         self.field_indexes
             .iter()
-            .flat_map(|(_, field)| -> Vec<PayloadIndexTelemetry> {
+            .flat_map(|(name, field)| -> Vec<PayloadIndexTelemetry> {
                 field
                     .iter()
-                    .map(|field| field.get_telemetry_data())
+                    .map(|field| PayloadIndexTelemetry {
+                        field_name: Some(name.clone()),
+                        ..field.get_telemetry_data()
+                    })
                     .collect()
             })
+            .chain(
+                self.composite_indexes
+                    .iter()
+                    .map(|(name, composite_index)| PayloadIndexTelemetry {
+                        field_name: Some(name.clone()),
+                        ..composite_index.get_telemetry_data()
+                    }),
+            )
             .collect()
     }
 }
@@ -301,8 +560,11 @@ impl PayloadIndex for StructPayloadIndex {
             .insert(field.to_owned(), payload_schema.clone())
             .is_none()
         {
+            self.config.index_versions.remove(field);
             self.save_config()?;
             self.build_and_save(field, payload_schema)?;
+            self.mark_index_format_current(field)?;
+            self.save_config()?;
         }
 
         Ok(())
@@ -310,8 +572,10 @@ impl PayloadIndex for StructPayloadIndex {
 
     fn drop_index(&mut self, field: PayloadKeyTypeRef) -> OperationResult<()> {
         self.config.indexed_fields.remove(field);
+        self.config.index_versions.remove(field);
         self.save_config()?;
         self.field_indexes.remove(field);
+        self.composite_indexes.remove(field);
 
         let field_index_path = Self::get_field_index_path(&self.path, field);
 
@@ -323,6 +587,10 @@ impl PayloadIndex for StructPayloadIndex {
     }
 
     fn estimate_cardinality(&self, query: &Filter) -> CardinalityEstimation {
+        if let Some(estimation) = self.estimate_with_composite_index(query) {
+            return estimation;
+        }
+
         let total_points = self.total_points();
 
         let estimator = |condition: &Condition| self.condition_cardinality(condition);
@@ -360,7 +628,7 @@ impl PayloadIndex for StructPayloadIndex {
                 .get(points_iterator_ref.max_id() as usize + 1);
 
             #[allow(clippy::needless_collect)]
-                let preselected: Vec<PointOffsetType> = query_cardinality
+            let preselected: Vec<PointOffsetType> = query_cardinality
                 .primary_clauses
                 .iter()
                 .flat_map(|clause| {
@@ -371,7 +639,28 @@ impl PayloadIndex for StructPayloadIndex {
                             )
                         }
                         PrimaryCondition::Ids(ids) => Box::new(ids.iter().copied()),
-                        PrimaryCondition::IsEmpty(_) => points_iterator_ref.iter_ids() /* there are no fast index for IsEmpty */
+                        PrimaryCondition::IsEmpty(IsEmptyCondition { is_empty: field }) => {
+                            match self.null_index(&field.key) {
+                                None => points_iterator_ref.iter_ids(),
+                                Some(null_index) => Box::new(
+                                    points_iterator_ref
+                                        .iter_ids()
+                                        .filter(move |&id| !null_index.has_values(id)),
+                                ),
+                            }
+                        }
+                        PrimaryCondition::IsNull(IsNullCondition { is_null: field }) => {
+                            match self.null_index(&field.key) {
+                                None => points_iterator_ref.iter_ids(),
+                                Some(null_index) => Box::new(null_index.iter_null()),
+                            }
+                        }
+                        PrimaryCondition::Composite(CompositeMatch { index, tuple }) => {
+                            match self.composite_indexes.get(index) {
+                                None => points_iterator_ref.iter_ids(),
+                                Some(composite_index) => composite_index.iter_points(tuple),
+                            }
+                        }
                     }
                 })
                 .filter(|&id| !visited_list.check_and_update_visited(id))
@@ -407,13 +696,14 @@ impl PayloadIndex for StructPayloadIndex {
 
     fn assign(&mut self, point_id: PointOffsetType, payload: &Payload) -> OperationResult<()> {
         for (field, field_index) in &mut self.field_indexes {
-            if let Some(field_value) = payload.get_value(field) {
+            if let Some(field_value) = payload.get_path_value(field) {
                 for index in field_index {
-                    index.add_point(point_id, field_value)?;
+                    index.add_point(point_id, &field_value)?;
                 }
             }
         }
-        self.payload.borrow_mut().assign(point_id, payload)
+        self.payload.borrow_mut().assign(point_id, payload)?;
+        self.update_composite_indexes(point_id)
     }
 
     fn payload(&self, point_id: PointOffsetType) -> OperationResult<Payload> {
@@ -430,7 +720,9 @@ impl PayloadIndex for StructPayloadIndex {
                 index.remove_point(point_id)?;
             }
         }
-        self.payload.borrow_mut().delete(point_id, key)
+        let value = self.payload.borrow_mut().delete(point_id, key)?;
+        self.update_composite_indexes(point_id)?;
+        Ok(value)
     }
 
     fn drop(&mut self, point_id: PointOffsetType) -> OperationResult<Option<Payload>> {
@@ -439,6 +731,9 @@ impl PayloadIndex for StructPayloadIndex {
                 index.remove_point(point_id)?;
             }
         }
+        for composite_index in self.composite_indexes.values_mut() {
+            composite_index.remove_point(point_id)?;
+        }
         self.payload.borrow_mut().drop(point_id)
     }
 
@@ -449,6 +744,9 @@ impl PayloadIndex for StructPayloadIndex {
                 index.clear()?;
             }
         }
+        for (_, composite_index) in self.composite_indexes.drain() {
+            composite_index.clear()?;
+        }
         self.load_all_fields()
     }
 
@@ -459,6 +757,9 @@ impl PayloadIndex for StructPayloadIndex {
                 flushers.push(index.flusher());
             }
         }
+        for composite_index in self.composite_indexes.values() {
+            flushers.push(composite_index.flusher());
+        }
         flushers.push(self.payload.borrow().flusher());
         Box::new(move || {
             for flusher in flushers {
@@ -474,8 +775,8 @@ impl PayloadIndex for StructPayloadIndex {
     ) -> OperationResult<Option<PayloadSchemaType>> {
         let mut schema = None;
         self.payload.borrow().iter(|_id, payload: &Payload| {
-            let field_value = payload.get_value(key);
-            schema = field_value.and_then(infer_value_type);
+            let field_value = payload.get_path_value(key);
+            schema = field_value.as_deref().and_then(infer_value_type);
             Ok(false)
         })?;
         Ok(schema)
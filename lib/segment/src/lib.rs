@@ -16,8 +16,6 @@ pub mod data_types;
 pub mod types;
 pub mod vector_storage;
 
-#[macro_use]
-extern crate num_derive;
 extern crate core;
 
 #[cfg(test)]
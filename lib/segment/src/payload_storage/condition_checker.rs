@@ -3,7 +3,8 @@
 use serde_json::Value;
 
 use crate::types::{
-    GeoBoundingBox, GeoRadius, Match, MatchText, MatchValue, Range, ValueVariants, ValuesCount,
+    GeoBoundingBox, GeoPolygon, GeoRadius, Match, MatchPhrase, MatchText, MatchValue, Range,
+    ValueVariants, ValuesCount,
 };
 
 pub trait ValueChecker {
@@ -32,6 +33,10 @@ impl ValueChecker for Match {
                 Value::String(stored) => stored.contains(text),
                 _ => false,
             },
+            Match::Phrase(MatchPhrase { phrase }) => match payload {
+                Value::String(stored) => stored.contains(phrase),
+                _ => false,
+            },
         }
     }
 }
@@ -65,6 +70,23 @@ impl ValueChecker for GeoBoundingBox {
     }
 }
 
+impl ValueChecker for GeoPolygon {
+    fn check_match(&self, payload: &Value) -> bool {
+        match payload {
+            Value::Object(obj) => {
+                let lon_op = obj.get("lon").and_then(|x| x.as_f64());
+                let lat_op = obj.get("lat").and_then(|x| x.as_f64());
+
+                if let (Some(lon), Some(lat)) = (lon_op, lat_op) {
+                    return self.check_point(lon, lat);
+                }
+                false
+            }
+            _ => false,
+        }
+    }
+}
+
 impl ValueChecker for GeoRadius {
     fn check_match(&self, payload: &Value) -> bool {
         match payload {
@@ -10,7 +10,8 @@ use crate::payload_storage::condition_checker::ValueChecker;
 use crate::payload_storage::payload_storage_enum::PayloadStorageEnum;
 use crate::payload_storage::ConditionChecker;
 use crate::types::{
-    Condition, FieldCondition, Filter, IsEmptyCondition, OwnedPayloadRef, Payload, PointOffsetType,
+    Condition, FieldCondition, Filter, IsEmptyCondition, IsNullCondition, OwnedPayloadRef, Payload,
+    PointOffsetType,
 };
 
 fn check_condition<F>(checker: &F, condition: &Condition) -> bool
@@ -79,6 +80,7 @@ where
             check_field_condition(field_condition, get_payload().deref())
         }
         Condition::IsEmpty(is_empty) => check_is_empty_condition(is_empty, get_payload().deref()),
+        Condition::IsNull(is_null) => check_is_null_condition(is_null, get_payload().deref()),
         Condition::HasId(has_id) => {
             let external_id = match id_tracker.external_id(point_id) {
                 None => return false,
@@ -93,7 +95,7 @@ where
 }
 
 pub fn check_is_empty_condition(is_empty: &IsEmptyCondition, payload: &Payload) -> bool {
-    match payload.get_value(&is_empty.is_empty.key) {
+    match payload.get_path_value(&is_empty.is_empty.key).as_deref() {
         None => true,
         Some(value) => match value {
             Value::Null => true,
@@ -103,8 +105,16 @@ pub fn check_is_empty_condition(is_empty: &IsEmptyCondition, payload: &Payload)
     }
 }
 
+pub fn check_is_null_condition(is_null: &IsNullCondition, payload: &Payload) -> bool {
+    matches!(
+        payload.get_path_value(&is_null.is_null.key).as_deref(),
+        Some(Value::Null)
+    )
+}
+
 pub fn check_field_condition(field_condition: &FieldCondition, payload: &Payload) -> bool {
-    payload.get_value(&field_condition.key).map_or(false, |p| {
+    let value = payload.get_path_value(&field_condition.key);
+    value.as_deref().map_or(false, |p| {
         let mut res = false;
         // ToDo: Convert onto iterator over checkers, so it would be impossible to forget a condition
         res = res
@@ -127,6 +137,11 @@ pub fn check_field_condition(field_condition: &FieldCondition, payload: &Payload
                 .geo_bounding_box
                 .as_ref()
                 .map_or(false, |condition| condition.check(p));
+        res = res
+            || field_condition
+                .geo_polygon
+                .as_ref()
+                .map_or(false, |condition| condition.check(p));
         res = res
             || field_condition
                 .values_count
@@ -239,6 +254,7 @@ mod tests {
             "rating": vec![3, 7, 9, 9],
             "color": "red",
             "has_delivery": true,
+            "parts": null,
         })
         .into();
 
@@ -272,6 +288,26 @@ mod tests {
         assert!(!payload_checker.check(0, &is_empty_condition_1));
         assert!(payload_checker.check(0, &is_empty_condition_2));
 
+        let is_null_condition = |key: &str| {
+            Filter::new_must(Condition::IsNull(IsNullCondition {
+                is_null: PayloadField {
+                    key: key.to_string(),
+                },
+            }))
+        };
+
+        assert!(payload_checker.check(0, &is_null_condition("parts")));
+        assert!(!payload_checker.check(0, &is_null_condition("price")));
+        assert!(!payload_checker.check(0, &is_null_condition("something_new")));
+        assert!(payload_checker.check(
+            0,
+            &Filter::new_must(Condition::IsEmpty(IsEmptyCondition {
+                is_empty: PayloadField {
+                    key: "parts".to_string(),
+                },
+            }))
+        ));
+
         let match_red = Condition::Field(FieldCondition::new_match(
             "color".to_string(),
             "red".to_owned().into(),
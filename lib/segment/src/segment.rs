@@ -12,23 +12,26 @@ use tar::Builder;
 
 use crate::common::file_operations::{atomic_save_json, read_json};
 use crate::common::version::StorageVersion;
-use crate::common::{check_vector_name, check_vectors_set};
+use crate::common::{check_vector_dim, check_vector_name, check_vectors_set};
 use crate::data_types::named_vectors::NamedVectors;
+use crate::data_types::sparse_vector::SparseVector;
 use crate::data_types::vectors::VectorElementType;
 use crate::entry::entry_point::OperationError::TypeInferenceError;
 use crate::entry::entry_point::{
     get_service_error, OperationError, OperationResult, SegmentEntry, SegmentFailedState,
 };
+use crate::id_tracker::points_bloom_filter::PointsBloomFilter;
 use crate::id_tracker::IdTrackerSS;
 use crate::index::field_index::CardinalityEstimation;
+use crate::index::sparse_index::SparseVectorIndex;
 use crate::index::struct_payload_index::StructPayloadIndex;
 use crate::index::{PayloadIndex, VectorIndexSS};
 use crate::spaces::tools::peek_top_smallest_iterable;
 use crate::telemetry::SegmentTelemetry;
 use crate::types::{
-    Filter, Payload, PayloadFieldSchema, PayloadKeyType, PayloadKeyTypeRef, PayloadSchemaType,
-    PointIdType, PointOffsetType, ScoredPoint, SearchParams, SegmentConfig, SegmentInfo,
-    SegmentState, SegmentType, SeqNumberType, WithPayload, WithVector,
+    Filter, Indexes, Payload, PayloadFieldSchema, PayloadKeyType, PayloadKeyTypeRef,
+    PayloadSchemaType, PointIdType, PointOffsetType, ScoredPoint, SearchParams, SegmentConfig,
+    SegmentInfo, SegmentState, SegmentType, SeqNumberType, WithPayload, WithVector,
 };
 use crate::vector_storage::{ScoredPointOffset, VectorStorageSS};
 
@@ -57,7 +60,11 @@ pub struct Segment {
     pub current_path: PathBuf,
     /// Component for mapping external ids to internal and also keeping track of point versions
     pub id_tracker: Arc<AtomicRefCell<IdTrackerSS>>,
+    /// Filter of external ids, allows to skip id tracker lookups for points which are not in this segment
+    pub points_filter: PointsBloomFilter,
     pub vector_data: HashMap<String, VectorData>,
+    /// Inverted indexes of sparse vectors by name
+    pub sparse_vector_data: HashMap<String, Arc<AtomicRefCell<SparseVectorIndex>>>,
     pub payload_index: Arc<AtomicRefCell<StructPayloadIndex>>,
     /// Shows if it is possible to insert more points into this segment
     pub appendable_flag: bool,
@@ -104,6 +111,32 @@ impl Segment {
         Ok(new_internal_index)
     }
 
+    /// Set sparse vectors of the point to the given ones.
+    /// Sparse vectors, which are not present in `vectors`, are removed from the point
+    fn update_sparse_vectors(
+        &self,
+        internal_id: PointOffsetType,
+        vectors: &NamedVectors,
+    ) -> OperationResult<()> {
+        for (vector_name, sparse_index) in &self.sparse_vector_data {
+            let mut sparse_index = sparse_index.borrow_mut();
+            match vectors.get_sparse(vector_name) {
+                Some(vector) => sparse_index.upsert(internal_id, vector.clone())?,
+                None => sparse_index.delete(internal_id)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn check_sparse_vector_name(&self, vector_name: &str) -> OperationResult<()> {
+        if !self.sparse_vector_data.contains_key(vector_name) {
+            return Err(OperationError::VectorNameNotExists {
+                received_name: vector_name.to_owned(),
+            });
+        }
+        Ok(())
+    }
+
     fn handle_version_and_failure<F>(
         &mut self,
         op_num: SeqNumberType,
@@ -199,8 +232,20 @@ impl Segment {
         res
     }
 
+    /// Re-create filter of external ids from the id tracker.
+    /// Required once the filter is saturated, as removed points are never cleared from it.
+    fn rebuild_points_filter(&mut self) {
+        let id_tracker = self.id_tracker.borrow();
+        self.points_filter =
+            PointsBloomFilter::from_points(id_tracker.iter_external(), id_tracker.points_count());
+    }
+
     fn lookup_internal_id(&self, point_id: PointIdType) -> OperationResult<PointOffsetType> {
-        let internal_id_opt = self.id_tracker.borrow().internal_id(point_id);
+        let internal_id_opt = if self.points_filter.may_contain(point_id) {
+            self.id_tracker.borrow().internal_id(point_id)
+        } else {
+            None
+        };
         match internal_id_opt {
             Some(internal_id) => Ok(internal_id),
             None => Err(OperationError::PointIdError {
@@ -259,6 +304,11 @@ impl Segment {
                     .unwrap(),
             );
         }
+        for (vector_name, sparse_index) in &self.sparse_vector_data {
+            if let Some(vector) = sparse_index.borrow().get_vector(point_offset) {
+                vectors.insert_sparse(vector_name.clone(), vector.clone());
+            }
+        }
         Ok(vectors)
     }
 
@@ -288,6 +338,27 @@ impl Segment {
         Ok(())
     }
 
+    /// Set version of the segment and of all its points.
+    ///
+    /// Used to fit a segment, imported from another collection, into the sequence of operations
+    /// of the target shard: operations older than `version` are skipped, newer are applied.
+    pub fn set_version(&mut self, version: SeqNumberType) -> OperationResult<()> {
+        // Segment data is not changed, so it is enough to persist versions and state
+        let _flush_lock = self.lock_flushing()?;
+        {
+            let mut id_tracker = self.id_tracker.borrow_mut();
+            let point_ids: Vec<_> = id_tracker.iter_external().collect();
+            for point_id in point_ids {
+                id_tracker.set_version(point_id, version)?;
+            }
+            id_tracker.versions_flusher()()?;
+        }
+        self.version = version;
+        self.save_current_state()?;
+        *self.persisted_version.lock() = version;
+        Ok(())
+    }
+
     // Joins flush thread if exists
     // Returns lock to guarantee that there will be no other flush in a different thread
     fn lock_flushing(
@@ -315,6 +386,22 @@ impl Segment {
         }
     }
 
+    /// Checks if the filter can't match any point of the segment because of the principal field range
+    fn is_out_of_principal_range(&self, filter: Option<&Filter>) -> bool {
+        filter.map_or(false, |filter| {
+            self.payload_index
+                .borrow()
+                .is_out_of_principal_range(filter)
+        })
+    }
+
+    /// Checks if the segment should not be searched because it has no vector index yet
+    /// and the search requires `indexed_only`
+    fn is_skipped_by_params(&self, params: Option<&SearchParams>) -> bool {
+        params.map_or(false, |params| params.indexed_only)
+            && matches!(self.segment_config.index, Indexes::Plain {})
+    }
+
     /// Converts raw ScoredPointOffset search result into ScoredPoint result
     fn process_search_result(
         &self,
@@ -358,6 +445,13 @@ impl Segment {
                     WithVector::Selector(vectors) => {
                         let mut result = NamedVectors::default();
                         for vector_name in vectors {
+                            if let Some(sparse_index) = self.sparse_vector_data.get(vector_name) {
+                                if let Some(vector) = sparse_index.borrow().get_vector(point_offset)
+                                {
+                                    result.insert_sparse(vector_name.clone(), vector.clone());
+                                }
+                                continue;
+                            }
                             result.insert(
                                 vector_name.clone(),
                                 self.vector_by_offset(vector_name, point_offset)?,
@@ -373,6 +467,7 @@ impl Segment {
                     score: scored_point_offset.score,
                     payload,
                     vector,
+                    payload_truncated: false,
                 })
             })
             .collect()
@@ -434,6 +529,9 @@ impl SegmentEntry for Segment {
     }
 
     fn point_version(&self, point_id: PointIdType) -> Option<SeqNumberType> {
+        if !self.points_filter.may_contain(point_id) {
+            return None;
+        }
         self.id_tracker.borrow().version(point_id)
     }
 
@@ -449,12 +547,9 @@ impl SegmentEntry for Segment {
     ) -> OperationResult<Vec<ScoredPoint>> {
         check_vector_name(vector_name, &self.segment_config)?;
         let vector_data = &self.vector_data[vector_name];
-        let expected_vector_dim = vector_data.vector_storage.borrow().vector_dim();
-        if vector.len() != expected_vector_dim {
-            return Err(OperationError::WrongVector {
-                expected_dim: expected_vector_dim,
-                received_dim: vector.len(),
-            });
+        check_vector_dim(vector_name, vector.len(), &self.segment_config)?;
+        if self.is_out_of_principal_range(filter) || self.is_skipped_by_params(params) {
+            return Ok(vec![]);
         }
 
         let internal_result =
@@ -466,6 +561,36 @@ impl SegmentEntry for Segment {
         self.process_search_result(internal_result, with_payload, with_vector)
     }
 
+    fn search_sparse(
+        &self,
+        vector_name: &str,
+        vector: &SparseVector,
+        with_payload: &WithPayload,
+        with_vector: &WithVector,
+        filter: Option<&Filter>,
+        top: usize,
+    ) -> OperationResult<Vec<ScoredPoint>> {
+        self.check_sparse_vector_name(vector_name)?;
+        vector
+            .validate()
+            .map_err(|description| OperationError::WrongSparseVector { description })?;
+        if self.is_out_of_principal_range(filter) {
+            return Ok(vec![]);
+        }
+
+        let sparse_index = self.sparse_vector_data[vector_name].borrow();
+        let internal_result = match filter {
+            None => sparse_index.search(vector, top, |_| true),
+            Some(filter) => {
+                let payload_index = self.payload_index.borrow();
+                let filter_context = payload_index.filter_context(filter);
+                sparse_index.search(vector, top, |point_id| filter_context.check(point_id))
+            }
+        };
+
+        self.process_search_result(&internal_result, with_payload, with_vector)
+    }
+
     fn search_batch(
         &self,
         vector_name: &str,
@@ -478,14 +603,11 @@ impl SegmentEntry for Segment {
     ) -> OperationResult<Vec<Vec<ScoredPoint>>> {
         check_vector_name(vector_name, &self.segment_config)?;
         let vector_data = &self.vector_data[vector_name];
-        let expected_vector_dim = vector_data.vector_storage.borrow().vector_dim();
         for vector in vectors {
-            if vector.len() != expected_vector_dim {
-                return Err(OperationError::WrongVector {
-                    expected_dim: expected_vector_dim,
-                    received_dim: vector.len(),
-                });
-            }
+            check_vector_dim(vector_name, vector.len(), &self.segment_config)?;
+        }
+        if self.is_out_of_principal_range(filter) || self.is_skipped_by_params(params) {
+            return Ok(vec![vec![]; vectors.len()]);
         }
 
         let internal_results = vector_data
@@ -510,23 +632,22 @@ impl SegmentEntry for Segment {
         vectors: &NamedVectors,
     ) -> OperationResult<bool> {
         check_vectors_set(vectors, &self.segment_config)?;
+        for vector_name in vectors.sparse_keys() {
+            if let Some(vector) = vectors.get_sparse(vector_name) {
+                vector
+                    .validate()
+                    .map_err(|description| OperationError::WrongSparseVector { description })?;
+            }
+        }
         self.handle_version_and_failure(op_num, Some(point_id), |segment| {
             let mut processed_vectors = NamedVectors::default();
             for (vector_name, vector) in vectors.iter() {
                 let vector_name: &str = vector_name;
                 let vector: &[VectorElementType] = vector;
-                let vector_data = &segment.vector_data[vector_name];
-                let vector_dim = vector_data.vector_storage.borrow().vector_dim();
-                if vector_dim != vector.len() {
-                    return Err(OperationError::WrongVector {
-                        expected_dim: vector_dim,
-                        received_dim: vector.len(),
-                    });
-                }
+                check_vector_dim(vector_name, vector.len(), &segment.segment_config)?;
 
-                let processed_vector_opt = segment.segment_config.vector_data[vector_name]
-                    .distance
-                    .preprocess_vector(vector);
+                let processed_vector_opt =
+                    segment.segment_config.vector_data[vector_name].preprocess_vector(vector);
                 match processed_vector_opt {
                     None => processed_vectors.insert_ref(vector_name, vector),
                     Some(preprocess_vector) => {
@@ -540,10 +661,14 @@ impl SegmentEntry for Segment {
             let was_replaced = if let Some(existing_internal_id) = stored_internal_point {
                 let new_index = segment.update_vector(existing_internal_id, processed_vectors)?;
                 if new_index != existing_internal_id {
+                    for sparse_index in segment.sparse_vector_data.values() {
+                        sparse_index.borrow_mut().delete(existing_internal_id)?;
+                    }
                     let mut id_tracker = segment.id_tracker.borrow_mut();
                     id_tracker.drop(point_id)?;
                     id_tracker.set_link(point_id, new_index)?;
                 }
+                segment.update_sparse_vectors(new_index, vectors)?;
                 true
             } else {
                 let mut new_index = 0;
@@ -555,10 +680,15 @@ impl SegmentEntry for Segment {
                         .borrow_mut()
                         .put_vector(processed_vector)?;
                 }
+                segment.update_sparse_vectors(new_index, vectors)?;
                 segment
                     .id_tracker
                     .borrow_mut()
                     .set_link(point_id, new_index)?;
+                segment.points_filter.insert(point_id);
+                if segment.points_filter.is_saturated() {
+                    segment.rebuild_points_filter();
+                }
                 false
             };
 
@@ -582,6 +712,9 @@ impl SegmentEntry for Segment {
                             .borrow_mut()
                             .delete(internal_id)?;
                     }
+                    for sparse_index in segment.sparse_vector_data.values() {
+                        sparse_index.borrow_mut().delete(internal_id)?;
+                    }
                     segment.payload_index.borrow_mut().drop(internal_id)?;
                     id_tracker.drop(point_id)?;
                     Ok(true)
@@ -662,8 +795,17 @@ impl SegmentEntry for Segment {
 
     fn all_vectors(&self, point_id: PointIdType) -> OperationResult<NamedVectors> {
         let mut result = NamedVectors::default();
+        let internal_id = self.lookup_internal_id(point_id)?;
         for vector_name in self.vector_data.keys() {
-            result.insert(vector_name.clone(), self.vector(vector_name, point_id)?);
+            result.insert(
+                vector_name.clone(),
+                self.vector_by_offset(vector_name, internal_id)?,
+            );
+        }
+        for (vector_name, sparse_index) in &self.sparse_vector_data {
+            if let Some(vector) = sparse_index.borrow().get_vector(internal_id) {
+                result.insert_sparse(vector_name.clone(), vector.clone());
+            }
         }
         Ok(result)
     }
@@ -695,6 +837,7 @@ impl SegmentEntry for Segment {
                 .map(|x| x.0)
                 .take(limit.unwrap_or(usize::MAX))
                 .collect(),
+            Some(condition) if self.is_out_of_principal_range(Some(condition)) => vec![],
             Some(condition) => {
                 let query_cardinality = {
                     let payload_index = self.payload_index.borrow();
@@ -749,7 +892,8 @@ impl SegmentEntry for Segment {
     }
 
     fn has_point(&self, point_id: PointIdType) -> bool {
-        self.id_tracker.borrow().internal_id(point_id).is_some()
+        self.points_filter.may_contain(point_id)
+            && self.id_tracker.borrow().internal_id(point_id).is_some()
     }
 
     fn points_count(&self) -> usize {
@@ -769,6 +913,9 @@ impl SegmentEntry for Segment {
             }
             Some(filter) => {
                 let payload_index = self.payload_index.borrow();
+                if payload_index.is_out_of_principal_range(filter) {
+                    return CardinalityEstimation::exact(0);
+                }
                 payload_index.estimate_cardinality(filter)
             }
         }
@@ -831,6 +978,11 @@ impl SegmentEntry for Segment {
             .vector_data
             .iter()
             .map(|(_, v)| v.vector_storage.borrow().flusher())
+            .chain(
+                self.sparse_vector_data
+                    .values()
+                    .map(|v| v.borrow().flusher()),
+            )
             .collect();
         let state = self.get_state();
         let current_path = self.current_path.clone();
@@ -945,10 +1097,38 @@ impl SegmentEntry for Segment {
         })
     }
 
+    fn rebuild_field_index(
+        &mut self,
+        op_num: SeqNumberType,
+        key: PayloadKeyTypeRef,
+        field_schema: Option<&PayloadFieldSchema>,
+    ) -> OperationResult<bool> {
+        self.handle_version_and_failure(op_num, None, |segment| {
+            let schema = match field_schema
+                .cloned()
+                .or_else(|| segment.get_indexed_fields().remove(key))
+            {
+                Some(schema) => schema,
+                None => return Ok(false),
+            };
+            let mut payload_index = segment.payload_index.borrow_mut();
+            payload_index.drop_index(key)?;
+            payload_index.set_indexed(key, schema)?;
+            Ok(true)
+        })
+    }
+
     fn get_indexed_fields(&self) -> HashMap<PayloadKeyType, PayloadFieldSchema> {
         self.payload_index.borrow().indexed_fields()
     }
 
+    fn principal_range(&self) -> Option<(PayloadKeyType, (f64, f64))> {
+        let payload_index = self.payload_index.borrow();
+        let field = payload_index.principal_fields().into_iter().next()?;
+        let range = payload_index.field_values_range(&field)?;
+        Some((field, range))
+    }
+
     fn check_error(&self) -> Option<SegmentFailedState> {
         self.error_status.clone()
     }
@@ -1084,6 +1264,7 @@ impl Drop for Segment {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
     use std::fs;
 
     use tar::Archive;
@@ -1091,9 +1272,12 @@ mod tests {
     use walkdir::WalkDir;
 
     use super::*;
-    use crate::data_types::vectors::{only_default_vector, DEFAULT_VECTOR_NAME};
-    use crate::segment_constructor::build_segment;
-    use crate::types::{Distance, Indexes, SegmentConfig, StorageType, VectorDataConfig};
+    use crate::data_types::vectors::{default_vector, only_default_vector, DEFAULT_VECTOR_NAME};
+    use crate::segment_constructor::{build_segment, import_segment, load_segment};
+    use crate::types::{
+        Condition, Distance, MultiVectorComparator, MultiVectorConfig, SegmentConfig,
+        SparseVectorDataConfig, StorageType, VectorDataConfig,
+    };
 
     // no longer valid since users are now allowed to store arbitrary json objects.
     // TODO(gvelo): add tests for invalid payload types on indexed fields.
@@ -1139,8 +1323,14 @@ mod tests {
                 VectorDataConfig {
                     size: dim,
                     distance: Distance::Dot,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
                 },
             )]),
+            sparse_vector_data: Default::default(),
             index: Indexes::Plain {},
             storage_type: StorageType::InMemory,
             payload_storage_type: Default::default(),
@@ -1185,7 +1375,148 @@ mod tests {
         eprintln!("search_batch_result = {:#?}", search_batch_result);
 
         assert!(!search_result.is_empty());
-        assert_eq!(search_result, search_batch_result[0].clone())
+        assert_eq!(search_result, search_batch_result[0].clone());
+
+        // Plain segment is skipped if only indexed segments are requested
+        let indexed_only_params = SearchParams {
+            hnsw_ef: None,
+            quantization: None,
+            indexed_only: true,
+        };
+        let indexed_only_result = segment
+            .search(
+                DEFAULT_VECTOR_NAME,
+                &query_vector,
+                &WithPayload::default(),
+                &false.into(),
+                None,
+                10,
+                Some(&indexed_only_params),
+            )
+            .unwrap();
+        assert!(indexed_only_result.is_empty());
+    }
+
+    #[test]
+    fn test_sparse_vectors_search() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let sparse_name = "text";
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                DEFAULT_VECTOR_NAME.to_owned(),
+                VectorDataConfig {
+                    size: 2,
+                    distance: Distance::Dot,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
+                },
+            )]),
+            sparse_vector_data: HashMap::from([(
+                sparse_name.to_owned(),
+                SparseVectorDataConfig::default(),
+            )]),
+            index: Indexes::Plain {},
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+        };
+        let mut segment = build_segment(dir.path(), &config).unwrap();
+
+        let point_vectors = |sparse: Option<SparseVector>| {
+            let mut vectors = default_vector(vec![1.0, 1.0]);
+            if let Some(sparse) = sparse {
+                vectors.insert_sparse(sparse_name.to_owned(), sparse);
+            }
+            vectors
+        };
+        let sparse =
+            |indices: Vec<u32>, values: Vec<f32>| SparseVector::new(indices, values).unwrap();
+
+        segment
+            .upsert_vector(
+                1,
+                1.into(),
+                &point_vectors(Some(sparse(vec![1, 5], vec![1.0, 2.0]))),
+            )
+            .unwrap();
+        segment
+            .upsert_vector(
+                2,
+                2.into(),
+                &point_vectors(Some(sparse(vec![5], vec![1.0]))),
+            )
+            .unwrap();
+        segment
+            .upsert_vector(
+                3,
+                3.into(),
+                &point_vectors(Some(sparse(vec![7], vec![3.0]))),
+            )
+            .unwrap();
+        segment
+            .upsert_vector(4, 4.into(), &point_vectors(None))
+            .unwrap();
+
+        let mut unknown_vectors = point_vectors(None);
+        unknown_vectors.insert_sparse("unknown".to_owned(), sparse(vec![1], vec![1.0]));
+        assert!(segment
+            .upsert_vector(5, 5.into(), &unknown_vectors)
+            .is_err());
+
+        let query = sparse(vec![5, 1], vec![1.0, 0.5]);
+        let search = |segment: &Segment, filter: Option<&Filter>| {
+            segment
+                .search_sparse(
+                    sparse_name,
+                    &query,
+                    &WithPayload::default(),
+                    &true.into(),
+                    filter,
+                    10,
+                )
+                .unwrap()
+        };
+
+        let result = search(&segment, None);
+        let ids: Vec<_> = result.iter().map(|point| point.id).collect();
+        assert_eq!(ids, vec![PointIdType::from(1), PointIdType::from(2)]);
+        assert_eq!(result[0].score, 2.5);
+        let vectors = result[0].vector.clone().unwrap().into_all_vectors();
+        assert_eq!(vectors.get_sparse(sparse_name).unwrap().indices, vec![1, 5]);
+
+        let filter = Filter::new_must_not(Condition::HasId(
+            HashSet::<PointIdType>::from([1.into()]).into(),
+        ));
+        let ids: Vec<_> = search(&segment, Some(&filter))
+            .iter()
+            .map(|point| point.id)
+            .collect();
+        assert_eq!(ids, vec![PointIdType::from(2)]);
+
+        // Upsert without sparse vector removes it, deleted points are not found
+        segment
+            .upsert_vector(6, 1.into(), &point_vectors(None))
+            .unwrap();
+        segment.delete_point(7, 2.into()).unwrap();
+        assert!(search(&segment, None).is_empty());
+        assert!(segment
+            .all_vectors(1.into())
+            .unwrap()
+            .get_sparse(sparse_name)
+            .is_none());
+
+        assert!(segment
+            .search_sparse(
+                "unknown",
+                &query,
+                &WithPayload::default(),
+                &false.into(),
+                None,
+                10
+            )
+            .is_err());
     }
 
     #[test]
@@ -1208,8 +1539,14 @@ mod tests {
                 VectorDataConfig {
                     size: dim,
                     distance: Distance::Dot,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
                 },
             )]),
+            sparse_vector_data: Default::default(),
             index: Indexes::Plain {},
             storage_type: StorageType::InMemory,
             payload_storage_type: Default::default(),
@@ -1296,8 +1633,14 @@ mod tests {
                 VectorDataConfig {
                     size: 2,
                     distance: Distance::Dot,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
                 },
             )]),
+            sparse_vector_data: Default::default(),
             index: Indexes::Plain {},
             storage_type: StorageType::InMemory,
             payload_storage_type: Default::default(),
@@ -1360,6 +1703,133 @@ mod tests {
         assert_eq!(decompressed_file_count, segment_file_count);
     }
 
+    #[test]
+    fn test_import_segment() {
+        let segment_base_dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                DEFAULT_VECTOR_NAME.to_owned(),
+                VectorDataConfig {
+                    size: 2,
+                    distance: Distance::Dot,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
+                },
+            )]),
+            sparse_vector_data: Default::default(),
+            index: Indexes::Plain {},
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+        };
+
+        let mut segment = build_segment(segment_base_dir.path(), &config).unwrap();
+        segment
+            .upsert_vector(10, 0.into(), &only_default_vector(&[1.0, 1.0]))
+            .unwrap();
+        segment
+            .upsert_vector(11, 1.into(), &only_default_vector(&[1.0, 0.0]))
+            .unwrap();
+
+        let snapshot_dir = Builder::new().prefix("snapshot_dir").tempdir().unwrap();
+        segment.take_snapshot(snapshot_dir.path()).unwrap();
+        let archive = fs::read_dir(snapshot_dir.path())
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+
+        let import_dir = Builder::new().prefix("import_dir").tempdir().unwrap();
+        let mut imported = import_segment(&archive, import_dir.path()).unwrap();
+        assert_ne!(imported.current_path, segment.current_path);
+        assert_eq!(imported.version(), 11);
+        assert_eq!(imported.points_count(), 2);
+        assert_eq!(
+            imported.vector(DEFAULT_VECTOR_NAME, 1.into()).unwrap(),
+            vec![1.0, 0.0]
+        );
+
+        imported.set_version(3).unwrap();
+        assert_eq!(imported.version(), 3);
+        assert_eq!(imported.point_version(0.into()), Some(3));
+        assert_eq!(imported.point_version(1.into()), Some(3));
+
+        // Versions survive reload
+        let imported_path = imported.current_path.clone();
+        drop(imported);
+        let reloaded = load_segment(&imported_path).unwrap().unwrap();
+        assert_eq!(reloaded.version(), 3);
+        assert_eq!(reloaded.point_version(0.into()), Some(3));
+
+        // Archive without a segment is rejected and leaves nothing behind
+        let garbage_archive = snapshot_dir.path().join("garbage.tar");
+        let mut builder = tar::Builder::new(File::create(&garbage_archive).unwrap());
+        builder
+            .append_dir_all(".", segment_base_dir.path())
+            .unwrap();
+        builder.finish().unwrap();
+        drop(builder);
+        assert!(import_segment(&garbage_archive, import_dir.path()).is_err());
+        assert_eq!(fs::read_dir(import_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_points_filter() {
+        let segment_base_dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                DEFAULT_VECTOR_NAME.to_owned(),
+                VectorDataConfig {
+                    size: 2,
+                    distance: Distance::Dot,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
+                },
+            )]),
+            sparse_vector_data: Default::default(),
+            index: Indexes::Plain {},
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+        };
+
+        // Insert more points than the initial capacity of the filter, to trigger rebuild
+        let num_points = 3000u64;
+        let mut segment = build_segment(segment_base_dir.path(), &config).unwrap();
+        for idx in 0..num_points {
+            segment
+                .upsert_vector(idx, idx.into(), &only_default_vector(&[1.0, idx as f32]))
+                .unwrap();
+        }
+        segment.delete_point(num_points, 0.into()).unwrap();
+
+        assert!(!segment.has_point(0.into()));
+        for idx in 1..num_points {
+            assert!(segment.has_point(idx.into()));
+            assert_eq!(segment.point_version(idx.into()), Some(idx));
+        }
+        assert!(!segment.has_point(num_points.into()));
+        assert!(segment
+            .vector(DEFAULT_VECTOR_NAME, num_points.into())
+            .is_err());
+
+        // Filter is restored on load
+        let segment_path = segment.current_path.clone();
+        segment.flush(true).unwrap();
+        drop(segment);
+        let segment = load_segment(&segment_path).unwrap().unwrap();
+        assert!(!segment.points_filter.is_saturated());
+        assert!(!segment.has_point(0.into()));
+        for idx in 1..num_points {
+            assert!(segment.has_point(idx.into()));
+        }
+    }
+
     #[test]
     fn test_copy_segment_directory() {
         let data = r#"
@@ -1379,8 +1849,14 @@ mod tests {
                 VectorDataConfig {
                     size: 2,
                     distance: Distance::Dot,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
                 },
             )]),
+            sparse_vector_data: Default::default(),
             index: Indexes::Plain {},
             storage_type: StorageType::InMemory,
             payload_storage_type: Default::default(),
@@ -1431,8 +1907,14 @@ mod tests {
                 VectorDataConfig {
                     size: 2,
                     distance: Distance::Dot,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
                 },
             )]),
+            sparse_vector_data: Default::default(),
             index: Indexes::Plain {},
             storage_type: StorageType::InMemory,
             payload_storage_type: Default::default(),
@@ -1450,4 +1932,87 @@ mod tests {
         // call flush second time to check that background flush finished successful
         segment.flush(true).unwrap();
     }
+
+    #[test]
+    fn test_multivector_search() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                DEFAULT_VECTOR_NAME.to_owned(),
+                VectorDataConfig {
+                    size: 2,
+                    distance: Distance::Cosine,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: Some(MultiVectorConfig {
+                        comparator: MultiVectorComparator::MaxSim,
+                    }),
+                    hnsw_config: None,
+                    normalize: None,
+                },
+            )]),
+            sparse_vector_data: Default::default(),
+            index: Indexes::Plain {},
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+        };
+        let mut segment = build_segment(dir.path(), &config).unwrap();
+
+        // Points with different number of sub-vectors
+        segment
+            .upsert_vector(1, 1.into(), &only_default_vector(&[1.0, 0.0]))
+            .unwrap();
+        segment
+            .upsert_vector(2, 2.into(), &only_default_vector(&[0.0, 1.0, 1.0, 1.0]))
+            .unwrap();
+        segment
+            .upsert_vector(3, 3.into(), &only_default_vector(&[3.0, 0.0, 0.0, 5.0]))
+            .unwrap();
+
+        // Length must be a multiple of the sub-vector size
+        assert!(segment
+            .upsert_vector(4, 4.into(), &only_default_vector(&[1.0, 0.0, 1.0]))
+            .is_err());
+
+        // Each sub-vector is normalized independently
+        assert_eq!(
+            segment.vector(DEFAULT_VECTOR_NAME, 3.into()).unwrap(),
+            vec![1.0, 0.0, 0.0, 1.0]
+        );
+
+        let result = segment
+            .search(
+                DEFAULT_VECTOR_NAME,
+                &[1.0, 0.0, 0.0, 1.0],
+                &WithPayload::default(),
+                &false.into(),
+                None,
+                10,
+                None,
+            )
+            .unwrap();
+        let ids: Vec<_> = result.iter().map(|point| point.id).collect();
+        assert_eq!(
+            ids,
+            vec![
+                PointIdType::from(3),
+                PointIdType::from(2),
+                PointIdType::from(1)
+            ]
+        );
+        assert!((result[0].score - 2.0).abs() < 1e-5);
+        assert!((result[2].score - 1.0).abs() < 1e-5);
+
+        assert!(segment
+            .search(
+                DEFAULT_VECTOR_NAME,
+                &[1.0, 0.0, 0.0],
+                &WithPayload::default(),
+                &false.into(),
+                None,
+                10,
+                None,
+            )
+            .is_err());
+    }
 }
@@ -6,6 +6,7 @@ use std::sync::atomic::{AtomicBool, Ordering};
 
 use crate::common::error_logging::LogError;
 use crate::entry::entry_point::{OperationError, OperationResult, SegmentEntry};
+use crate::index::hnsw_index::old_graph_links::OldGraphLinks;
 use crate::index::PayloadIndex;
 use crate::segment::Segment;
 use crate::segment_constructor::{build_segment, load_segment};
@@ -17,6 +18,8 @@ pub struct SegmentBuilder {
     pub destination_path: PathBuf,
     pub temp_path: PathBuf,
     pub indexed_fields: HashMap<PayloadKeyType, PayloadFieldSchema>,
+    /// Graph links of the source segments for each vector, reused to build the new index faster
+    pub old_graph_links: HashMap<String, Vec<OldGraphLinks>>,
 }
 
 impl SegmentBuilder {
@@ -35,6 +38,7 @@ impl SegmentBuilder {
             destination_path,
             temp_path,
             indexed_fields: Default::default(),
+            old_graph_links: Default::default(),
         })
     }
 
@@ -79,6 +83,31 @@ impl SegmentBuilder {
                     })
                     .collect();
                 let mut payload_index = self_segment.payload_index.borrow_mut();
+                let mut sparse_indexes: HashMap<_, _> = self_segment
+                    .sparse_vector_data
+                    .iter()
+                    .map(|(vector_name, sparse_index)| (vector_name, sparse_index.borrow_mut()))
+                    .collect();
+                let other_sparse_indexes: HashMap<_, _> = other
+                    .sparse_vector_data
+                    .iter()
+                    .map(|(vector_name, sparse_index)| (vector_name, sparse_index.borrow()))
+                    .collect();
+                // Copy sparse vectors of the point from `other`, or remove them if there is no source
+                let mut set_sparse_vectors = |new_internal_id, old_internal_id: Option<_>| {
+                    for (vector_name, sparse_index) in sparse_indexes.iter_mut() {
+                        let vector = old_internal_id.and_then(|old_internal_id| {
+                            other_sparse_indexes
+                                .get(vector_name)
+                                .and_then(|other_index| other_index.get_vector(old_internal_id))
+                        });
+                        match vector {
+                            Some(vector) => sparse_index.upsert(new_internal_id, vector.clone())?,
+                            None => sparse_index.delete(new_internal_id)?,
+                        }
+                    }
+                    OperationResult::Ok(())
+                };
 
                 if vector_storages.len() != other_vector_storages.len() {
                     return Err(OperationError::ServiceError {
@@ -107,6 +136,9 @@ impl SegmentBuilder {
                     });
                 }
 
+                // Points of `other`, which are moved into the new segment: old internal id -> new internal id
+                let mut moved_points = HashMap::new();
+
                 for (new_internal_id, old_internal_id) in internal_id_iter.unwrap() {
                     if stopped.load(Ordering::Relaxed) {
                         return Err(OperationError::Cancelled {
@@ -119,16 +151,19 @@ impl SegmentBuilder {
                     match id_tracker.version(external_id) {
                         None => {
                             // New point, just insert
+                            moved_points.insert(old_internal_id, new_internal_id);
                             id_tracker.set_link(external_id, new_internal_id)?;
                             id_tracker.set_version(external_id, other_version)?;
                             payload_index.assign(
                                 new_internal_id,
                                 &other_payload_index.payload(old_internal_id)?,
                             )?;
+                            set_sparse_vectors(new_internal_id, Some(old_internal_id))?;
                         }
                         Some(existing_version) => {
                             let remove_id = if existing_version < other_version {
                                 // Other version is the newest, remove the existing one and replace
+                                moved_points.insert(old_internal_id, new_internal_id);
                                 let existing_internal_id =
                                     id_tracker.internal_id(external_id).unwrap();
                                 id_tracker.drop(external_id)?;
@@ -138,6 +173,7 @@ impl SegmentBuilder {
                                     new_internal_id,
                                     &other_payload_index.payload(old_internal_id)?,
                                 )?;
+                                set_sparse_vectors(new_internal_id, Some(old_internal_id))?;
                                 existing_internal_id
                             } else {
                                 // Old version is still good, do not move anything else
@@ -147,10 +183,24 @@ impl SegmentBuilder {
                             for vector_storage in vector_storages.values_mut() {
                                 vector_storage.delete(remove_id)?;
                             }
+                            set_sparse_vectors(remove_id, None)?;
                         }
                     }
                 }
 
+                for (vector_name, vector_data) in &other.vector_data {
+                    let old_links = vector_data
+                        .vector_index
+                        .borrow()
+                        .export_graph_links(&moved_points);
+                    if let Some(old_links) = old_links {
+                        self.old_graph_links
+                            .entry(vector_name.to_owned())
+                            .or_default()
+                            .push(old_links);
+                    }
+                }
+
                 for (field, payload_schema) in other.payload_index.borrow().indexed_fields() {
                     self.indexed_fields.insert(field, payload_schema);
                 }
@@ -176,8 +226,16 @@ impl SegmentBuilder {
                 }
             }
 
-            for vector_data in segment.vector_data.values_mut() {
-                vector_data.vector_index.borrow_mut().build_index(stopped)?;
+            for (vector_name, vector_data) in segment.vector_data.iter_mut() {
+                let old_links = self
+                    .old_graph_links
+                    .get(vector_name)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                vector_data
+                    .vector_index
+                    .borrow_mut()
+                    .build_index_with_old_links(old_links, stopped)?;
             }
 
             segment.flush(true)?;
@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use std::fs::{create_dir_all, File};
+use std::fs::{create_dir_all, remove_dir_all, File};
 use std::io::Read;
 use std::path::Path;
 use std::sync::Arc;
@@ -11,13 +11,16 @@ use semver::Version;
 use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::common::rocksdb_wrapper::{open_db, DB_VECTOR_CF};
+use crate::common::rocksdb_wrapper::{open_db, DB_SPARSE_VECTOR_CF, DB_VECTOR_CF};
 use crate::common::version::StorageVersion;
 use crate::data_types::vectors::DEFAULT_VECTOR_NAME;
 use crate::entry::entry_point::{OperationError, OperationResult};
+use crate::id_tracker::points_bloom_filter::PointsBloomFilter;
 use crate::id_tracker::simple_id_tracker::SimpleIdTracker;
 use crate::index::hnsw_index::hnsw::HNSWIndex;
+use crate::index::ivf_index::IvfIndex;
 use crate::index::plain_payload_index::PlainIndex;
+use crate::index::sparse_index::open_sparse_vector_index;
 use crate::index::struct_payload_index::StructPayloadIndex;
 use crate::index::VectorIndexSS;
 use crate::payload_storage::on_disk_payload_storage::OnDiskPayloadStorage;
@@ -28,6 +31,7 @@ use crate::types::{
     StorageType, VectorDataConfig,
 };
 use crate::vector_storage::memmap_vector_storage::open_memmap_vector_storage;
+use crate::vector_storage::multi_vector_storage::open_multi_vector_storage;
 use crate::vector_storage::simple_vector_storage::open_simple_vector_storage;
 use crate::vector_storage::VectorStorageSS;
 
@@ -51,6 +55,12 @@ fn create_segment(
         .vector_data
         .iter()
         .map(|(vector_name, _)| get_vector_name_with_prefix(DB_VECTOR_CF, vector_name))
+        .chain(
+            config
+                .sparse_vector_data
+                .keys()
+                .map(|vector_name| get_vector_name_with_prefix(DB_SPARSE_VECTOR_CF, vector_name)),
+        )
         .collect();
     let database = open_db(segment_path, &vector_db_names)
         .map_err(|err| OperationError::service_error(&format!("RocksDB open error: {}", err)))?;
@@ -62,11 +72,16 @@ fn create_segment(
 
     let id_tracker = sp(SimpleIdTracker::open(database.clone())?);
 
+    let appendable_flag = matches!(config.index, Indexes::Plain { .. })
+        && config.storage_type == StorageType::InMemory
+        && !config.is_any_vector_on_disk();
+
     let payload_index_path = segment_path.join("payload_index");
     let payload_index: Arc<AtomicRefCell<StructPayloadIndex>> = sp(StructPayloadIndex::open(
         payload_storage,
         id_tracker.clone(),
         &payload_index_path,
+        appendable_flag,
     )?);
 
     let mut vector_data = HashMap::new();
@@ -76,22 +91,45 @@ fn create_segment(
         let vector_index_path =
             segment_path.join(&get_vector_name_with_prefix("vector_index", vector_name));
 
-        let vector_storage: Arc<AtomicRefCell<VectorStorageSS>> = match config.storage_type {
-            StorageType::InMemory => {
+        let storage_type = if vector_config.on_disk.unwrap_or(false) {
+            StorageType::Mmap
+        } else {
+            config.storage_type
+        };
+
+        let vector_storage: Arc<AtomicRefCell<VectorStorageSS>> =
+            if let Some(multivector_config) = vector_config.multivector_config {
+                // Multivectors have variable length and are always kept in memory
                 let db_column_name = get_vector_name_with_prefix(DB_VECTOR_CF, vector_name);
-                open_simple_vector_storage(
+                open_multi_vector_storage(
                     database.clone(),
                     &db_column_name,
+                    &vector_storage_path,
                     vector_config.size,
                     vector_config.distance,
+                    multivector_config.comparator,
                 )?
-            }
-            StorageType::Mmap => open_memmap_vector_storage(
-                &vector_storage_path,
-                vector_config.size,
-                vector_config.distance,
-            )?,
-        };
+            } else {
+                match storage_type {
+                    StorageType::InMemory => {
+                        let db_column_name = get_vector_name_with_prefix(DB_VECTOR_CF, vector_name);
+                        open_simple_vector_storage(
+                            database.clone(),
+                            &db_column_name,
+                            &vector_storage_path,
+                            vector_config.size,
+                            vector_config.distance,
+                            vector_config.is_normalized(),
+                        )?
+                    }
+                    StorageType::Mmap => open_memmap_vector_storage(
+                        &vector_storage_path,
+                        vector_config.size,
+                        vector_config.distance,
+                        vector_config.is_normalized(),
+                    )?,
+                }
+            };
 
         let vector_index: Arc<AtomicRefCell<VectorIndexSS>> = match config.index {
             Indexes::Plain { .. } => sp(PlainIndex::new(
@@ -102,7 +140,22 @@ fn create_segment(
                 &vector_index_path,
                 vector_storage.clone(),
                 payload_index.clone(),
-                hnsw_config,
+                vector_config.hnsw_config.unwrap_or(hnsw_config),
+                // Quantization relies on fixed size vectors
+                vector_config
+                    .quantization_config
+                    .filter(|_| !vector_config.is_multivector()),
+            )?),
+            // Centroids can't be computed for multivectors of variable length
+            Indexes::Ivf(_) if vector_config.is_multivector() => sp(PlainIndex::new(
+                vector_storage.clone(),
+                payload_index.clone(),
+            )),
+            Indexes::Ivf(ivf_config) => sp(IvfIndex::open(
+                &vector_index_path,
+                vector_storage.clone(),
+                payload_index.clone(),
+                ivf_config,
             )?),
         };
 
@@ -115,20 +168,33 @@ fn create_segment(
         );
     }
 
+    let mut sparse_vector_data = HashMap::new();
+    for vector_name in config.sparse_vector_data.keys() {
+        let db_column_name = get_vector_name_with_prefix(DB_SPARSE_VECTOR_CF, vector_name);
+        sparse_vector_data.insert(
+            vector_name.to_owned(),
+            open_sparse_vector_index(database.clone(), &db_column_name)?,
+        );
+    }
+
     let segment_type = match config.index {
         Indexes::Plain { .. } => SegmentType::Plain,
-        Indexes::Hnsw { .. } => SegmentType::Indexed,
+        Indexes::Hnsw { .. } | Indexes::Ivf { .. } => SegmentType::Indexed,
     };
 
-    let appendable_flag =
-        segment_type == SegmentType::Plain {} && config.storage_type == StorageType::InMemory;
+    let points_filter = {
+        let id_tracker = id_tracker.borrow();
+        PointsBloomFilter::from_points(id_tracker.iter_external(), id_tracker.points_count())
+    };
 
     Ok(Segment {
         version,
         persisted_version: Arc::new(Mutex::new(version)),
         current_path: segment_path.to_owned(),
         id_tracker,
+        points_filter,
         vector_data,
+        sparse_vector_data,
         segment_type,
         appendable_flag,
         payload_index,
@@ -178,6 +244,38 @@ pub fn load_segment(path: &Path) -> OperationResult<Option<Segment>> {
     )?))
 }
 
+/// Import segment from the archive, made by `SegmentEntry::take_snapshot`.
+///
+/// Archive is unpacked into a new segment folder inside `path`, which is removed if the archive
+/// does not contain a valid segment.
+///
+/// # Arguments
+///
+/// * `archive_path` - path to the segment archive
+/// * `path` - A path to collection segments. Segment folder will be created in this directory
+///
+pub fn import_segment(archive_path: &Path, path: &Path) -> OperationResult<Segment> {
+    let segment_path = path.join(Uuid::new_v4().to_string());
+
+    let segment = File::open(archive_path)
+        .and_then(|archive_file| tar::Archive::new(archive_file).unpack(&segment_path))
+        .map_err(OperationError::from)
+        .and_then(|_| load_segment(&segment_path))
+        .and_then(|segment| {
+            segment.ok_or_else(|| {
+                OperationError::service_error(&format!(
+                    "Archive {} does not contain a segment",
+                    archive_path.display()
+                ))
+            })
+        });
+
+    if segment.is_err() && segment_path.exists() {
+        remove_dir_all(&segment_path)?;
+    }
+    segment
+}
+
 /// Build segment instance using given configuration.
 /// Builder will generate folder for the segment and store all segment information inside it.
 ///
@@ -238,11 +336,17 @@ fn load_segment_state_v3(segment_path: &Path) -> OperationResult<SegmentState> {
             let vector_data = VectorDataConfig {
                 size: state.config.vector_size,
                 distance: state.config.distance,
+                quantization_config: None,
+                on_disk: None,
+                multivector_config: None,
+                hnsw_config: None,
+                normalize: None,
             };
             SegmentState {
                 version: state.version,
                 config: SegmentConfig {
                     vector_data: HashMap::from([(DEFAULT_VECTOR_NAME.to_owned(), vector_data)]),
+                    sparse_vector_data: Default::default(),
                     index: state.config.index,
                     storage_type: state.config.storage_type,
                     payload_storage_type: state.config.payload_storage_type,
@@ -26,8 +26,14 @@ pub fn build_simple_segment(
                 VectorDataConfig {
                     size: dim,
                     distance,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
                 },
             )]),
+            sparse_vector_data: Default::default(),
             index: Indexes::Plain {},
             storage_type: Default::default(),
             payload_storage_type: Default::default(),
@@ -47,6 +53,11 @@ pub fn build_multivec_segment(
         VectorDataConfig {
             size: dim1,
             distance,
+            quantization_config: None,
+            on_disk: None,
+            multivector_config: None,
+            hnsw_config: None,
+            normalize: None,
         },
     );
     vectors_config.insert(
@@ -54,6 +65,11 @@ pub fn build_multivec_segment(
         VectorDataConfig {
             size: dim2,
             distance,
+            quantization_config: None,
+            on_disk: None,
+            multivector_config: None,
+            hnsw_config: None,
+            normalize: None,
         },
     );
 
@@ -61,6 +77,7 @@ pub fn build_multivec_segment(
         path,
         &SegmentConfig {
             vector_data: vectors_config,
+            sparse_vector_data: Default::default(),
             index: Indexes::Plain {},
             storage_type: Default::default(),
             payload_storage_type: Default::default(),
@@ -0,0 +1,249 @@
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use parking_lot::RwLock;
+use rocksdb::DB;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Schema, SchemaObject};
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::data_types::vectors::VectorElementType;
+use crate::entry::entry_point::{OperationError, OperationResult};
+use crate::spaces::metric::Metric;
+use crate::types::{Distance, MultiVectorComparator, Order, ScoreType};
+use crate::vector_storage::memmap_vector_storage::open_memmap_vector_storage_with_metric;
+use crate::vector_storage::multi_vector_storage::open_multi_vector_storage_with_metric;
+use crate::vector_storage::simple_vector_storage::open_simple_vector_storage_with_metric;
+use crate::vector_storage::VectorStorageSS;
+
+/// Names, which can't be used for custom metrics
+const BUILTIN_DISTANCES: [&str; 3] = ["Cosine", "Euclid", "Dot"];
+
+type OpenSimpleStorage = fn(
+    Arc<RwLock<DB>>,
+    &str,
+    &Path,
+    usize,
+    bool,
+) -> OperationResult<Arc<AtomicRefCell<VectorStorageSS>>>;
+
+type OpenMemmapStorage =
+    fn(&Path, usize, bool) -> OperationResult<Arc<AtomicRefCell<VectorStorageSS>>>;
+
+type OpenMultiStorage = fn(
+    Arc<RwLock<DB>>,
+    &str,
+    &Path,
+    usize,
+    MultiVectorComparator,
+) -> OperationResult<Arc<AtomicRefCell<VectorStorageSS>>>;
+
+/// Distance function, registered in runtime with `register_custom_metric`.
+///
+/// Holds functions of the metric and constructors of vector storages, specialized for it,
+/// so custom metrics are scored the same way as builtin ones.
+/// Serialized by name, so metric should be registered before any collection, which uses it, is loaded.
+#[derive(Clone, Copy)]
+pub struct CustomDistance {
+    name: &'static str,
+    order: Order,
+    similarity: fn(&[VectorElementType], &[VectorElementType]) -> ScoreType,
+    preprocess: fn(&[VectorElementType]) -> Option<Vec<VectorElementType>>,
+    postprocess: fn(ScoreType) -> ScoreType,
+    pub(crate) open_simple_storage: OpenSimpleStorage,
+    pub(crate) open_memmap_storage: OpenMemmapStorage,
+    pub(crate) open_multi_storage: OpenMultiStorage,
+}
+
+static CUSTOM_DISTANCES: RwLock<Vec<CustomDistance>> = parking_lot::const_rwlock(Vec::new());
+
+impl CustomDistance {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn order(&self) -> Order {
+        self.order
+    }
+
+    pub fn similarity(&self, v1: &[VectorElementType], v2: &[VectorElementType]) -> ScoreType {
+        (self.similarity)(v1, v2)
+    }
+
+    pub fn preprocess(&self, vector: &[VectorElementType]) -> Option<Vec<VectorElementType>> {
+        (self.preprocess)(vector)
+    }
+
+    pub fn postprocess(&self, score: ScoreType) -> ScoreType {
+        (self.postprocess)(score)
+    }
+
+    /// Find registered metric by name
+    pub fn get(name: &str) -> Option<Self> {
+        CUSTOM_DISTANCES
+            .read()
+            .iter()
+            .find(|custom| custom.name == name)
+            .copied()
+    }
+}
+
+/// Register custom metric `M` under the given name, so it could be used as `Distance::Custom`.
+///
+/// `order` defines, how scores of the metric are compared after `Metric::postprocess`.
+/// Implementation of `Metric::distance` for `M` is expected to return `Distance::custom(name)`.
+pub fn register_custom_metric<M>(name: &'static str, order: Order) -> OperationResult<Distance>
+where
+    M: Metric + Send + Sync + 'static,
+{
+    if BUILTIN_DISTANCES.contains(&name) {
+        return Err(OperationError::service_error(&format!(
+            "distance {name} is builtin and can't be registered"
+        )));
+    }
+
+    let mut registry = CUSTOM_DISTANCES.write();
+    if registry.iter().any(|custom| custom.name == name) {
+        return Err(OperationError::service_error(&format!(
+            "distance {name} is already registered"
+        )));
+    }
+
+    let custom = CustomDistance {
+        name,
+        order,
+        similarity: M::similarity,
+        preprocess: M::preprocess,
+        postprocess: M::postprocess,
+        open_simple_storage: open_simple_vector_storage_with_metric::<M>,
+        open_memmap_storage: open_memmap_vector_storage_with_metric::<M>,
+        open_multi_storage: open_multi_vector_storage_with_metric::<M>,
+    };
+    registry.push(custom);
+    Ok(Distance::Custom(custom))
+}
+
+impl Debug for CustomDistance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("CustomDistance").field(&self.name).finish()
+    }
+}
+
+impl PartialEq for CustomDistance {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for CustomDistance {}
+
+impl Hash for CustomDistance {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state)
+    }
+}
+
+impl Serialize for CustomDistance {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name)
+    }
+}
+
+impl<'de> Deserialize<'de> for CustomDistance {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        CustomDistance::get(&name).ok_or_else(|| {
+            serde::de::Error::custom(format!("custom distance {name} is not registered"))
+        })
+    }
+}
+
+impl JsonSchema for CustomDistance {
+    fn schema_name() -> String {
+        "CustomDistance".to_string()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        let mut schema = SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            ..Default::default()
+        };
+        schema.metadata().description =
+            Some("Name of the distance function, registered on the server".to_string());
+        schema.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::common::rocksdb_wrapper::{open_db, DB_VECTOR_CF};
+    use crate::spaces::simple::EuclidMetric;
+    use crate::vector_storage::simple_vector_storage::open_simple_vector_storage;
+    use crate::vector_storage::ScoredPointOffset;
+
+    const MANHATTAN: &str = "Manhattan";
+
+    struct ManhattanMetric;
+
+    impl Metric for ManhattanMetric {
+        fn distance() -> Distance {
+            Distance::custom(MANHATTAN)
+        }
+
+        fn similarity(v1: &[VectorElementType], v2: &[VectorElementType]) -> ScoreType {
+            -v1.iter()
+                .zip(v2)
+                .map(|(a, b)| (a - b).abs())
+                .sum::<ScoreType>()
+        }
+
+        fn preprocess(_vector: &[VectorElementType]) -> Option<Vec<VectorElementType>> {
+            None
+        }
+
+        fn postprocess(score: ScoreType) -> ScoreType {
+            score.abs()
+        }
+    }
+
+    #[test]
+    fn test_custom_metric() {
+        let distance =
+            register_custom_metric::<ManhattanMetric>(MANHATTAN, Order::SmallBetter).unwrap();
+        assert!(register_custom_metric::<ManhattanMetric>(MANHATTAN, Order::SmallBetter).is_err());
+        assert!(register_custom_metric::<EuclidMetric>("Euclid", Order::SmallBetter).is_err());
+
+        let serialized = serde_json::to_string(&distance).unwrap();
+        assert_eq!(serialized, r#"{"Custom":"Manhattan"}"#);
+        let deserialized: Distance = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, distance);
+        assert!(serde_json::from_str::<Distance>(r#"{"Custom":"Unknown"}"#).is_err());
+
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+        let storage =
+            open_simple_vector_storage(db, DB_VECTOR_CF, dir.path(), 2, distance, true).unwrap();
+        let mut borrowed_storage = storage.borrow_mut();
+        borrowed_storage.put_vector(vec![1.0, 1.0]).unwrap();
+        borrowed_storage.put_vector(vec![3.0, 0.0]).unwrap();
+        borrowed_storage.put_vector(vec![0.0, 3.0]).unwrap();
+        assert_eq!(borrowed_storage.distance(), distance);
+
+        let raw_scorer = borrowed_storage.raw_scorer(vec![2.5, 0.0]);
+        let mut res = vec![ScoredPointOffset { idx: 0, score: 0. }; 3];
+        let res_count = raw_scorer.score_points(&[0, 1, 2], &mut res);
+        assert_eq!(res_count, 3);
+        assert_eq!(res[0].score, -2.5);
+        assert_eq!(res[1].score, -0.5);
+        assert_eq!(res[2].score, -5.5);
+        assert_eq!(distance.postprocess_score(res[1].score), 0.5);
+        assert!(distance.check_threshold(0.5, 1.0));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod custom;
 pub mod metric;
 pub mod simple;
 pub mod tools;
@@ -8,7 +8,7 @@ use parking_lot::Mutex;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::types::{SegmentConfig, SegmentInfo, VectorDataConfig};
+use crate::types::{PayloadKeyType, SegmentConfig, SegmentInfo, VectorDataConfig};
 
 const AVG_DATASET_LEN: usize = 128;
 const SLIDING_WINDOW_LEN: usize = 8;
@@ -25,11 +25,25 @@ pub struct SegmentTelemetry {
     pub payload_field_indices: Vec<PayloadIndexTelemetry>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, JsonSchema)]
 pub struct PayloadIndexTelemetry {
+    /// Indexed payload field, or the name of the composite index
+    pub field_name: Option<PayloadKeyType>,
+    /// Type of the index structure, e.g. `keyword_map` or `int_range`
+    #[serde(default)]
+    pub index_type: String,
     pub points_values_count: usize,
     pub points_count: usize,
+    /// Amount of distinct indexed values, if the index keeps track of them
+    pub distinct_values_count: Option<usize>,
     pub histogram_bucket_size: Option<usize>,
+    pub histogram_buckets_count: Option<usize>,
+    /// Estimated size of in-memory structures of the index
+    #[serde(default)]
+    pub ram_usage_bytes: usize,
+    /// Size of the index data persisted on disk
+    #[serde(default)]
+    pub disk_usage_bytes: usize,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
@@ -213,6 +227,11 @@ impl Anonymize for SegmentConfig {
                 .iter()
                 .map(|(k, v)| (telemetry_hash(k), v.anonymize()))
                 .collect(),
+            sparse_vector_data: self
+                .sparse_vector_data
+                .iter()
+                .map(|(k, v)| (telemetry_hash(k), *v))
+                .collect(),
             index: self.index,
             storage_type: self.storage_type,
             payload_storage_type: self.payload_storage_type,
@@ -225,6 +244,11 @@ impl Anonymize for VectorDataConfig {
         VectorDataConfig {
             size: telemetry_round(self.size),
             distance: self.distance,
+            quantization_config: self.quantization_config,
+            on_disk: self.on_disk,
+            multivector_config: self.multivector_config,
+            hnsw_config: self.hnsw_config,
+            normalize: self.normalize,
         }
     }
 }
@@ -247,9 +271,15 @@ impl Anonymize for VectorIndexTelemetry {
 impl Anonymize for PayloadIndexTelemetry {
     fn anonymize(&self) -> Self {
         PayloadIndexTelemetry {
+            field_name: self.field_name.as_deref().map(telemetry_hash),
+            index_type: self.index_type.clone(),
             points_count: telemetry_round(self.points_count),
             points_values_count: telemetry_round(self.points_values_count),
+            distinct_values_count: self.distinct_values_count.map(telemetry_round),
             histogram_bucket_size: self.histogram_bucket_size,
+            histogram_buckets_count: self.histogram_buckets_count,
+            ram_usage_bytes: self.ram_usage_bytes,
+            disk_usage_bytes: self.disk_usage_bytes,
         }
     }
 }
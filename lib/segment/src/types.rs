@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Formatter;
@@ -7,7 +8,7 @@ use std::rc::Rc;
 use std::str::FromStr;
 
 use geo::prelude::HaversineDistance;
-use geo::Point;
+use geo::{BoundingRect, Contains, LineString, Point, Polygon};
 use itertools::Itertools;
 use ordered_float::OrderedFloat;
 use schemars::JsonSchema;
@@ -16,8 +17,11 @@ use serde_json::{Map, Value};
 use uuid::Uuid;
 
 use crate::common::utils;
+use crate::data_types::composite_index::CompositeIndexParams;
+use crate::data_types::numeric_index::NumericIndexParams;
 use crate::data_types::text_index::TextIndexParams;
 use crate::data_types::vectors::{VectorElementType, VectorStruct};
+use crate::spaces::custom::CustomDistance;
 use crate::spaces::metric::Metric;
 use crate::spaces::simple::{CosineMetric, DotProductMetric, EuclidMetric};
 
@@ -82,9 +86,7 @@ impl FromStr for ExtendedPointId {
 pub type PointIdType = ExtendedPointId;
 
 /// Type of internal tags, build from payload
-#[derive(
-    Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, FromPrimitive, PartialEq, Eq, Hash,
-)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash)]
 /// Distance function types used to compare vectors
 pub enum Distance {
     /// <https://en.wikipedia.org/wiki/Cosine_similarity>
@@ -93,9 +95,23 @@ pub enum Distance {
     Euclid,
     /// <https://en.wikipedia.org/wiki/Dot_product>
     Dot,
+    /// Distance function, registered on the server with `register_custom_metric`
+    Custom(CustomDistance),
 }
 
 impl Distance {
+    /// Custom distance, registered under the given name.
+    ///
+    /// # Panics
+    ///
+    /// If the distance is not registered
+    pub fn custom(name: &str) -> Distance {
+        match CustomDistance::get(name) {
+            Some(custom) => Distance::Custom(custom),
+            None => panic!("custom distance {name} is not registered"),
+        }
+    }
+
     pub fn preprocess_vector(
         &self,
         vector: &[VectorElementType],
@@ -104,6 +120,18 @@ impl Distance {
             Distance::Cosine => CosineMetric::preprocess(vector),
             Distance::Euclid => EuclidMetric::preprocess(vector),
             Distance::Dot => DotProductMetric::preprocess(vector),
+            Distance::Custom(custom) => custom.preprocess(vector),
+        }
+    }
+
+    /// Similarity of vectors, which were already preprocessed with `preprocess_vector`.
+    /// Greater the value - closer the vectors
+    pub fn similarity(&self, v1: &[VectorElementType], v2: &[VectorElementType]) -> ScoreType {
+        match self {
+            Distance::Cosine => CosineMetric::similarity(v1, v2),
+            Distance::Euclid => EuclidMetric::similarity(v1, v2),
+            Distance::Dot => DotProductMetric::similarity(v1, v2),
+            Distance::Custom(custom) => custom.similarity(v1, v2),
         }
     }
 
@@ -112,6 +140,7 @@ impl Distance {
             Distance::Cosine => CosineMetric::postprocess(score),
             Distance::Euclid => EuclidMetric::postprocess(score),
             Distance::Dot => DotProductMetric::postprocess(score),
+            Distance::Custom(custom) => custom.postprocess(score),
         }
     }
 
@@ -119,6 +148,7 @@ impl Distance {
         match self {
             Distance::Cosine | Distance::Dot => Order::LargeBetter,
             Distance::Euclid => Order::SmallBetter,
+            Distance::Custom(custom) => custom.order(),
         }
     }
 
@@ -131,6 +161,7 @@ impl Distance {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Order {
     LargeBetter,
     SmallBetter,
@@ -149,6 +180,10 @@ pub struct ScoredPoint {
     pub payload: Option<Payload>,
     /// Vector of the point
     pub vector: Option<VectorStruct>,
+    /// True if some payload fields were omitted to fit the response size limit
+    #[serde(default)]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub payload_truncated: bool,
 }
 
 impl Eq for ScoredPoint {}
@@ -183,6 +218,24 @@ pub enum SegmentType {
     Special,
 }
 
+/// Build status of the payload index
+#[derive(
+    Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadIndexStatus {
+    /// Index is built for all points, filters by the field are served by the index
+    Ready,
+    /// Index is not built in some segments yet, filters by the field may not use it there
+    Building,
+}
+
+impl Default for PayloadIndexStatus {
+    fn default() -> Self {
+        PayloadIndexStatus::Ready
+    }
+}
+
 /// Display payload field type & index information
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -191,6 +244,9 @@ pub struct PayloadIndexInfo {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub params: Option<PayloadSchemaParams>,
+    /// Build status of the index
+    #[serde(default)]
+    pub status: PayloadIndexStatus,
 }
 
 /// Aggregated information about segment
@@ -208,12 +264,107 @@ pub struct SegmentInfo {
 }
 
 /// Additional parameters of the search
-#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct SearchParams {
     /// Params relevant to HNSW index
     /// /// Size of the beam in a beam-search. Larger the value - more accurate the result, more time required for search.
     pub hnsw_ef: Option<usize>,
+    /// Params relevant to quantized vectors
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantization: Option<QuantizationSearchParams>,
+    /// If true, search is performed only in segments with built vector index (e.g. HNSW).
+    /// Segments, which are not indexed yet, are skipped.
+    /// Gives predictable search latency during massive upload, but recently inserted points may be missing in the result.
+    #[serde(default)]
+    pub indexed_only: bool,
+}
+
+/// Parameters of the search with quantized vectors
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct QuantizationSearchParams {
+    /// If true, quantized vectors are not used and search is performed on original vectors
+    #[serde(default)]
+    pub ignore: bool,
+    /// If true, candidates found with quantized vectors are re-scored with original vectors.
+    /// Binary quantized vectors are always re-scored
+    #[serde(default)]
+    pub rescore: bool,
+    /// Number of candidates to re-score, relative to the requested limit: `limit * oversampling`.
+    /// Values below 1.0 are treated as 1.0. If not set, all `ef` candidates of the HNSW search are re-scored.
+    /// Has no effect without re-scoring
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oversampling: Option<f64>,
+}
+
+impl QuantizationSearchParams {
+    /// Number of candidates to select with quantized vectors for re-scoring
+    pub fn rescore_candidates(&self, top: usize, ef: usize) -> usize {
+        match self.oversampling {
+            Some(oversampling) => (top as f64 * oversampling.max(1.0)).ceil() as usize,
+            None => top.max(ef),
+        }
+    }
+}
+
+/// Type of quantized vector elements
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ScalarType {
+    Int8,
+}
+
+/// Lossy compression of vector elements into a smaller numeric type
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ScalarQuantizationConfig {
+    /// Type of quantized vector elements
+    pub r#type: ScalarType,
+    /// Quantile of vector element values, which defines the quantization range.
+    /// Values outside of the range are clipped. Must be in range (0.5, 1.0]. Default: 1.0
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantile: Option<f32>,
+}
+
+impl Eq for ScalarQuantizationConfig {}
+
+impl std::hash::Hash for ScalarQuantizationConfig {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.r#type.hash(state);
+        self.quantile.map(f32::to_bits).hash(state);
+    }
+}
+
+/// Compression of each vector element into a single bit, by the sign of the element.
+/// Suitable for high-dimensional embeddings with elements centered around zero
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct BinaryQuantizationConfig {}
+
+/// Quantized copies of vectors are used for fast approximate scoring, originals are kept for re-scoring
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum QuantizationConfig {
+    Scalar(ScalarQuantizationConfig),
+    Binary(BinaryQuantizationConfig),
+}
+
+impl QuantizationConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            QuantizationConfig::Scalar(ScalarQuantizationConfig {
+                quantile: Some(quantile),
+                ..
+            }) if !(*quantile > 0.5 && *quantile <= 1.0) => Err(format!(
+                "Quantile must be in range (0.5, 1.0], got {quantile}"
+            )),
+            _ => Ok(()),
+        }
+    }
 }
 
 /// Vector index configuration of the segment
@@ -227,6 +378,10 @@ pub enum Indexes {
     /// Use filterable HNSW index for approximate search. Is very fast even on a very huge collections,
     /// but require additional space to store index and additional time to build it.
     Hnsw(HnswConfig),
+    /// Use IVF (inverted file) index for approximate search. Vectors are split into lists by the closest
+    /// k-means centroid, search only scans lists closest to the query.
+    /// Builds faster and requires less memory than HNSW, at the cost of lower precision.
+    Ivf(IvfConfig),
 }
 
 /// Config of HNSW index
@@ -246,6 +401,22 @@ pub struct HnswConfig {
     /// Number of parallel threads used for background index building. If 0 - auto selection.
     #[serde(default = "default_max_indexing_threads")]
     pub max_indexing_threads: usize,
+    /// Store HNSW graph links in a memory-mapped file instead of RAM. Default: false
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_disk: Option<bool>,
+    /// Number of edges per node in additional payload-aware graph links, used by filtered search.
+    /// If not set - `m` is used. If 0 - additional payload-aware links are not built.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload_m: Option<usize>,
+    /// Minimal size (in KiloBytes) of vectors with the same payload value to build additional links for.
+    /// Smaller payload blocks are expected to be handled with full-scan by query planner.
+    /// If not set - `full_scan_threshold` is used.
+    /// Note: 1Kb = 1 vector of size 256
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload_block_threshold: Option<usize>,
 }
 
 fn default_max_indexing_threads() -> usize {
@@ -259,10 +430,54 @@ impl Default for HnswConfig {
             ef_construct: 100,
             full_scan_threshold: DEFAULT_FULL_SCAN_THRESHOLD,
             max_indexing_threads: 0,
+            on_disk: None,
+            payload_m: None,
+            payload_block_threshold: None,
+        }
+    }
+}
+
+/// Config of IVF index
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct IvfConfig {
+    /// Number of lists (k-means centroids) to split vectors into.
+    /// If 0 - selected automatically as a square root of the number of vectors.
+    #[serde(default)]
+    pub num_lists: usize,
+    /// Number of lists closest to the query to scan during search.
+    /// Larger the value - more accurate the search, more time required.
+    #[serde(default = "default_ivf_num_probes")]
+    pub num_probes: usize,
+}
+
+const DEFAULT_IVF_NUM_PROBES: usize = 8;
+
+fn default_ivf_num_probes() -> usize {
+    DEFAULT_IVF_NUM_PROBES
+}
+
+impl Default for IvfConfig {
+    fn default() -> Self {
+        IvfConfig {
+            num_lists: 0,
+            num_probes: DEFAULT_IVF_NUM_PROBES,
         }
     }
 }
 
+impl IvfConfig {
+    /// Number of lists for the given amount of vectors
+    pub fn get_num_lists(&self, num_vectors: usize) -> usize {
+        let num_lists = if self.num_lists == 0 {
+            (num_vectors as f64).sqrt() as usize
+        } else {
+            self.num_lists
+        };
+        num_lists.clamp(1, num_vectors.max(1))
+    }
+}
+
 impl Indexes {
     pub fn default_hnsw() -> Self {
         Indexes::Hnsw(Default::default())
@@ -330,6 +545,10 @@ impl Default for PayloadStorageType {
 #[serde(rename_all = "snake_case")]
 pub struct SegmentConfig {
     pub vector_data: HashMap<String, VectorDataConfig>,
+    /// Sparse vectors, stored in inverted indexes
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub sparse_vector_data: HashMap<String, SparseVectorDataConfig>,
     /// Type of index used for search
     pub index: Indexes,
     /// Type of vector storage
@@ -339,6 +558,15 @@ pub struct SegmentConfig {
     pub payload_storage_type: PayloadStorageType,
 }
 
+impl SegmentConfig {
+    /// Check if any of the vectors is forced to be stored on disk
+    pub fn is_any_vector_on_disk(&self) -> bool {
+        self.vector_data
+            .values()
+            .any(|config| config.on_disk.unwrap_or(false))
+    }
+}
+
 /// Config of single vector data storage
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case")]
@@ -347,8 +575,95 @@ pub struct VectorDataConfig {
     pub size: usize,
     /// Type of distance function used for measuring distance between vectors
     pub distance: Distance,
+    /// If set, quantized copies of vectors are built along with the vector index
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantization_config: Option<QuantizationConfig>,
+    /// If true, vectors are stored in memmap storage regardless of the segment `storage_type`.
+    /// Segment with such vectors is not appendable
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_disk: Option<bool>,
+    /// If set, each point stores one or more sub-vectors of `size` under this name,
+    /// concatenated into a single vector, and points are compared with the given comparator
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multivector_config: Option<MultiVectorConfig>,
+    /// If set, overrides the HNSW config of the segment for this vector
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hnsw_config: Option<HnswConfig>,
+    /// If false, Cosine vectors are stored as is, and their norms are cached to score them.
+    /// Default: true
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalize: Option<bool>,
 }
 
+impl VectorDataConfig {
+    pub fn is_multivector(&self) -> bool {
+        self.multivector_config.is_some()
+    }
+
+    /// Check if vectors are normalized on ingest. Only matters for Cosine distance.
+    /// Sub-vectors of multivectors are always normalized
+    pub fn is_normalized(&self) -> bool {
+        self.is_multivector() || self.normalize.unwrap_or(true)
+    }
+
+    /// Check if a vector of given length can be stored or queried with this config.
+    /// Multivectors may contain any positive number of sub-vectors of `size` each
+    pub fn is_valid_vector_len(&self, len: usize) -> bool {
+        if self.is_multivector() {
+            len > 0 && len % self.size == 0
+        } else {
+            len == self.size
+        }
+    }
+
+    /// Preprocess vector before storing it.
+    /// Sub-vectors of multivectors are preprocessed independently
+    pub fn preprocess_vector(
+        &self,
+        vector: &[VectorElementType],
+    ) -> Option<Vec<VectorElementType>> {
+        if !self.is_normalized() {
+            return None;
+        }
+        if !self.is_multivector() {
+            return self.distance.preprocess_vector(vector);
+        }
+        let mut processed = Vec::with_capacity(vector.len());
+        for sub_vector in vector.chunks(self.size) {
+            processed.extend(self.distance.preprocess_vector(sub_vector)?);
+        }
+        Some(processed)
+    }
+}
+
+/// Function used to combine similarities of sub-vectors into a single score of multivectors
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum MultiVectorComparator {
+    /// Sum over query sub-vectors of the maximal similarity against any of the stored
+    /// sub-vectors, as used by late-interaction models like ColBERT
+    MaxSim,
+}
+
+/// Config of multivector storage
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct MultiVectorConfig {
+    /// How to compare multivectors
+    pub comparator: MultiVectorComparator,
+}
+
+/// Config of single sparse vector data storage.
+/// Sparse vectors are always compared by dot product
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct SparseVectorDataConfig {}
+
 /// Default value based on <https://github.com/google-research/google-research/blob/master/scann/docs/algorithms.md>
 pub const DEFAULT_FULL_SCAN_THRESHOLD: usize = 20_000;
 
@@ -420,6 +735,30 @@ impl Payload {
         utils::get_value_from_json_map(path, &self.0)
     }
 
+    /// Get value by path, which may go through arrays of objects, e.g. `reviews[].rating`.
+    /// In this case values of all array elements are collected into a single array.
+    pub fn get_path_value(&self, path: &str) -> Option<Cow<Value>> {
+        if !path.contains(utils::ARRAY_PATH_MARKER) {
+            return self.get_value(path).map(Cow::Borrowed);
+        }
+        let values = utils::get_values_from_json_map(path, &self.0);
+        if values.is_empty() {
+            return None;
+        }
+        let mut flattened = Vec::with_capacity(values.len());
+        for value in values {
+            match value {
+                Value::Array(array) => flattened.extend(array.iter().cloned()),
+                _ => flattened.push(value.clone()),
+            }
+        }
+        Some(Cow::Owned(Value::Array(flattened)))
+    }
+
+    pub fn get_value_mut(&mut self, path: &str) -> Option<&mut Value> {
+        utils::get_value_mut_from_json_map(path, &mut self.0)
+    }
+
     pub fn remove(&mut self, path: &str) -> Option<Value> {
         utils::remove_value_from_json_map(path, &mut self.0)
     }
@@ -556,6 +895,8 @@ pub enum PayloadSchemaType {
     Float,
     Geo,
     Text,
+    /// Index over multiple payload keys, only available with `CompositeIndexParams`
+    Composite,
 }
 
 /// Payload type with parameters
@@ -564,6 +905,8 @@ pub enum PayloadSchemaType {
 #[serde(untagged)]
 pub enum PayloadSchemaParams {
     Text(TextIndexParams),
+    Numeric(NumericIndexParams),
+    Composite(CompositeIndexParams),
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
@@ -574,6 +917,30 @@ pub enum PayloadFieldSchema {
     FieldParams(PayloadSchemaParams),
 }
 
+impl PayloadFieldSchema {
+    /// Parameters of the index over multiple payload keys, if this is one
+    pub fn composite_params(&self) -> Option<&CompositeIndexParams> {
+        match self {
+            PayloadFieldSchema::FieldParams(PayloadSchemaParams::Composite(params)) => Some(params),
+            _ => None,
+        }
+    }
+
+    /// Check that the schema can be used to create an index
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            PayloadFieldSchema::FieldType(PayloadSchemaType::Composite) => {
+                Err("Keys of composite index are not specified".to_string())
+            }
+            PayloadFieldSchema::FieldParams(PayloadSchemaParams::Composite(params)) => {
+                params.validate()
+            }
+            PayloadFieldSchema::FieldParams(PayloadSchemaParams::Text(params)) => params.validate(),
+            _ => Ok(()),
+        }
+    }
+}
+
 impl From<PayloadSchemaType> for PayloadFieldSchema {
     fn from(payload_schema_type: PayloadSchemaType) -> Self {
         PayloadFieldSchema::FieldType(payload_schema_type)
@@ -588,6 +955,18 @@ impl TryFrom<PayloadIndexInfo> for PayloadFieldSchema {
             (PayloadSchemaType::Text, Some(PayloadSchemaParams::Text(params))) => Ok(
                 PayloadFieldSchema::FieldParams(PayloadSchemaParams::Text(params)),
             ),
+            (
+                data_type @ (PayloadSchemaType::Integer | PayloadSchemaType::Float),
+                Some(PayloadSchemaParams::Numeric(params)),
+            ) if params.r#type.schema_type() == data_type => Ok(PayloadFieldSchema::FieldParams(
+                PayloadSchemaParams::Numeric(params),
+            )),
+            (PayloadSchemaType::Composite, Some(PayloadSchemaParams::Composite(params))) => Ok(
+                PayloadFieldSchema::FieldParams(PayloadSchemaParams::Composite(params)),
+            ),
+            (PayloadSchemaType::Composite, None) => {
+                Err("Payload field with type Composite requires params".to_string())
+            }
             (data_type, Some(_)) => Err(format!(
                 "Payload field with type {:?} has unexpected params",
                 data_type
@@ -603,11 +982,23 @@ impl From<PayloadFieldSchema> for PayloadIndexInfo {
             PayloadFieldSchema::FieldType(data_type) => PayloadIndexInfo {
                 data_type,
                 params: None,
+                status: PayloadIndexStatus::Ready,
             },
             PayloadFieldSchema::FieldParams(schema_params) => match schema_params {
                 PayloadSchemaParams::Text(_) => PayloadIndexInfo {
                     data_type: PayloadSchemaType::Text,
                     params: Some(schema_params),
+                    status: PayloadIndexStatus::Ready,
+                },
+                PayloadSchemaParams::Numeric(ref numeric_index_params) => PayloadIndexInfo {
+                    data_type: numeric_index_params.r#type.schema_type(),
+                    params: Some(schema_params),
+                    status: PayloadIndexStatus::Ready,
+                },
+                PayloadSchemaParams::Composite(_) => PayloadIndexInfo {
+                    data_type: PayloadSchemaType::Composite,
+                    params: Some(schema_params),
+                    status: PayloadIndexStatus::Ready,
                 },
             },
         }
@@ -683,6 +1074,20 @@ impl From<String> for MatchText {
     }
 }
 
+/// Full-text match of the phrase.
+/// Tokens of the phrase have to follow each other in the same order within a single string.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct MatchPhrase {
+    pub phrase: String,
+}
+
+impl From<String> for MatchPhrase {
+    fn from(phrase: String) -> Self {
+        MatchPhrase { phrase }
+    }
+}
+
 /// Match filter request
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -690,6 +1095,7 @@ impl From<String> for MatchText {
 pub enum MatchInterface {
     Value(MatchValue),
     Text(MatchText),
+    Phrase(MatchPhrase),
 }
 
 /// Match filter request
@@ -699,6 +1105,7 @@ pub enum MatchInterface {
 pub enum Match {
     Value(MatchValue),
     Text(MatchText),
+    Phrase(MatchPhrase),
 }
 
 impl From<MatchInterface> for Match {
@@ -706,6 +1113,9 @@ impl From<MatchInterface> for Match {
         match value {
             MatchInterface::Value(value) => Self::Value(MatchValue { value: value.value }),
             MatchInterface::Text(text) => Self::Text(MatchText { text: text.text }),
+            MatchInterface::Phrase(phrase) => Self::Phrase(MatchPhrase {
+                phrase: phrase.phrase,
+            }),
         }
     }
 }
@@ -755,6 +1165,14 @@ impl Range {
             && self.lte.map_or(true, |x| number <= x)
             && self.gte.map_or(true, |x| number >= x)
     }
+
+    /// Checks if any number within `[min, max]` might satisfy the range
+    pub fn overlaps(&self, min: FloatPayloadType, max: FloatPayloadType) -> bool {
+        self.lt.map_or(true, |x| min < x)
+            && self.gt.map_or(true, |x| max > x)
+            && self.lte.map_or(true, |x| min <= x)
+            && self.gte.map_or(true, |x| max >= x)
+    }
 }
 
 /// Values count filter request
@@ -826,11 +1244,72 @@ impl GeoRadius {
     }
 }
 
+/// Ordered sequence of GeoPoints representing the line
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct GeoLineString {
+    pub points: Vec<GeoPoint>,
+}
+
+impl GeoLineString {
+    fn to_line_string(&self) -> LineString<f64> {
+        self.points
+            .iter()
+            .map(|point| (point.lon, point.lat))
+            .collect_vec()
+            .into()
+    }
+}
+
+/// Geo filter request
+///
+/// Matches coordinates inside the polygon, defined by `exterior` and `interiors`
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct GeoPolygon {
+    /// The exterior line bounds the surface
+    pub exterior: GeoLineString,
+    /// Interior lines (if present) bound holes within the surface
+    pub interiors: Option<Vec<GeoLineString>>,
+}
+
+impl GeoPolygon {
+    /// Convert into the polygon of `geo` crate, which is suitable for repeated checks
+    pub fn to_polygon(&self) -> Polygon<f64> {
+        let interiors = self
+            .interiors
+            .iter()
+            .flatten()
+            .map(|interior| interior.to_line_string())
+            .collect();
+        Polygon::new(self.exterior.to_line_string(), interiors)
+    }
+
+    pub fn check_point(&self, lon: f64, lat: f64) -> bool {
+        self.to_polygon().contains(&Point::new(lon, lat))
+    }
+
+    /// Smallest rectangle which contains the whole polygon
+    pub fn bounding_box(&self) -> Option<GeoBoundingBox> {
+        let rect = self.exterior.to_line_string().bounding_rect()?;
+        Some(GeoBoundingBox {
+            top_left: GeoPoint {
+                lon: rect.min().x,
+                lat: rect.max().y,
+            },
+            bottom_right: GeoPoint {
+                lon: rect.max().x,
+                lat: rect.min().y,
+            },
+        })
+    }
+}
+
 /// All possible payload filtering conditions
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct FieldCondition {
-    /// Payload key
+    /// Payload key. Use `[]` to refer to fields of array elements, e.g. `reviews[].rating`
     pub key: PayloadKeyType,
     /// Check if point has field with a given value
     pub r#match: Option<Match>,
@@ -840,6 +1319,8 @@ pub struct FieldCondition {
     pub geo_bounding_box: Option<GeoBoundingBox>,
     /// Check if geo point is within a given radius
     pub geo_radius: Option<GeoRadius>,
+    /// Check if geo point is within a given polygon
+    pub geo_polygon: Option<GeoPolygon>,
     /// Check number of values of the field
     pub values_count: Option<ValuesCount>,
 }
@@ -852,6 +1333,7 @@ impl FieldCondition {
             range: None,
             geo_bounding_box: None,
             geo_radius: None,
+            geo_polygon: None,
             values_count: None,
         }
     }
@@ -863,6 +1345,7 @@ impl FieldCondition {
             range: Some(range),
             geo_bounding_box: None,
             geo_radius: None,
+            geo_polygon: None,
             values_count: None,
         }
     }
@@ -874,6 +1357,7 @@ impl FieldCondition {
             range: None,
             geo_bounding_box: Some(geo_bounding_box),
             geo_radius: None,
+            geo_polygon: None,
             values_count: None,
         }
     }
@@ -885,6 +1369,19 @@ impl FieldCondition {
             range: None,
             geo_bounding_box: None,
             geo_radius: Some(geo_radius),
+            geo_polygon: None,
+            values_count: None,
+        }
+    }
+
+    pub fn new_geo_polygon(key: PayloadKeyType, geo_polygon: GeoPolygon) -> Self {
+        Self {
+            key,
+            r#match: None,
+            range: None,
+            geo_bounding_box: None,
+            geo_radius: None,
+            geo_polygon: Some(geo_polygon),
             values_count: None,
         }
     }
@@ -896,6 +1393,7 @@ impl FieldCondition {
             range: None,
             geo_bounding_box: None,
             geo_radius: None,
+            geo_polygon: None,
             values_count: Some(values_count),
         }
     }
@@ -914,6 +1412,12 @@ pub struct IsEmptyCondition {
     pub is_empty: PayloadField,
 }
 
+/// Select points with `null` payload for a specified field
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
+pub struct IsNullCondition {
+    pub is_null: PayloadField,
+}
+
 /// ID-based filtering condition
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
 pub struct HasIdCondition {
@@ -933,6 +1437,8 @@ pub enum Condition {
     Field(FieldCondition),
     /// Check if payload field is empty: equals to `NULL`, empty array, or does not exists
     IsEmpty(IsEmptyCondition),
+    /// Check if payload field equals to `NULL`
+    IsNull(IsNullCondition),
     /// Check if points id is in a given set
     HasId(HasIdCondition),
     /// Nested filter
@@ -1374,6 +1880,29 @@ mod tests {
         let field_type: PayloadSchemaType = serde_json::from_str(query).unwrap();
         eprintln!("field_type = {:?}", field_type);
     }
+
+    #[test]
+    fn test_rescore_candidates() {
+        let params = QuantizationSearchParams {
+            ignore: false,
+            rescore: true,
+            oversampling: None,
+        };
+        assert_eq!(params.rescore_candidates(10, 64), 64);
+        assert_eq!(params.rescore_candidates(100, 64), 100);
+
+        let params = QuantizationSearchParams {
+            oversampling: Some(2.5),
+            ..params
+        };
+        assert_eq!(params.rescore_candidates(10, 64), 25);
+
+        let params = QuantizationSearchParams {
+            oversampling: Some(0.5),
+            ..params
+        };
+        assert_eq!(params.rescore_candidates(10, 64), 10);
+    }
 }
 
 pub type TheMap<K, V> = BTreeMap<K, V>;
@@ -0,0 +1,120 @@
+use std::fs::File;
+use std::os::unix::io::AsRawFd;
+
+use io_uring::{opcode, types, IoUring};
+
+use crate::data_types::vectors::VectorElementType;
+use crate::entry::entry_point::{OperationError, OperationResult};
+use crate::types::PointOffsetType;
+
+/// Max number of reads, which are submitted to the ring at once
+const DISK_PARALLELISM: usize = 16;
+
+/// Reads vectors from file with io_uring, keeping up to `DISK_PARALLELISM` reads in flight.
+pub struct UringReader {
+    file: File,
+    io_uring: IoUring,
+    /// Read buffers, one per in-flight request
+    buffers: Vec<Vec<VectorElementType>>,
+    raw_size: usize,
+}
+
+impl UringReader {
+    pub fn new(file: File, raw_size: usize) -> OperationResult<Self> {
+        let io_uring = IoUring::new(DISK_PARALLELISM as u32)
+            .map_err(|err| OperationError::service_error(&format!("io_uring error: {}", err)))?;
+        let dim = raw_size / std::mem::size_of::<VectorElementType>();
+        Ok(Self {
+            file,
+            io_uring,
+            buffers: vec![vec![0.0; dim]; DISK_PARALLELISM],
+            raw_size,
+        })
+    }
+
+    /// Read vectors at given file offsets and call `callback` for each of them.
+    ///
+    /// Callback is called in order of read completion, not in order of `points`.
+    pub fn read_stream(
+        &mut self,
+        points: impl IntoIterator<Item = (PointOffsetType, usize)>,
+        mut callback: impl FnMut(PointOffsetType, &[VectorElementType]),
+    ) -> OperationResult<()> {
+        let fd = types::Fd(self.file.as_raw_fd());
+        let mut points = points.into_iter();
+        // Point id of the request, which currently owns each buffer
+        let mut in_flight: Vec<Option<PointOffsetType>> = vec![None; DISK_PARALLELISM];
+        let mut free_buffers: Vec<usize> = (0..DISK_PARALLELISM).collect();
+        let mut pending = 0;
+        let mut exhausted = false;
+        let mut error = None;
+
+        loop {
+            while !exhausted && !free_buffers.is_empty() {
+                let (point, offset) = match points.next() {
+                    None => {
+                        exhausted = true;
+                        break;
+                    }
+                    Some(next) => next,
+                };
+                let buffer_id = free_buffers.pop().unwrap();
+                let buffer = &mut self.buffers[buffer_id];
+                let entry =
+                    opcode::Read::new(fd, buffer.as_mut_ptr() as *mut u8, self.raw_size as u32)
+                        .offset(offset as _)
+                        .build()
+                        .user_data(buffer_id as u64);
+
+                // Safety: buffer is not touched until the read is completed
+                unsafe { self.io_uring.submission().push(&entry) }.map_err(|err| {
+                    OperationError::service_error(&format!("io_uring push error: {}", err))
+                })?;
+                in_flight[buffer_id] = Some(point);
+                pending += 1;
+            }
+
+            if pending == 0 {
+                return match error {
+                    None => Ok(()),
+                    Some(error) => Err(error),
+                };
+            }
+
+            self.io_uring.submit_and_wait(1)?;
+
+            let completed: Vec<_> = self
+                .io_uring
+                .completion()
+                .map(|entry| (entry.user_data() as usize, entry.result()))
+                .collect();
+
+            for (buffer_id, result) in completed {
+                pending -= 1;
+                free_buffers.push(buffer_id);
+                let point = in_flight[buffer_id].take().unwrap();
+                if error.is_some() {
+                    continue;
+                }
+                if result < 0 {
+                    error = Some(OperationError::service_error(&format!(
+                        "io_uring read error: {}",
+                        std::io::Error::from_raw_os_error(-result)
+                    )));
+                } else if result as usize != self.raw_size {
+                    error = Some(OperationError::service_error(&format!(
+                        "io_uring short read of point {}: {} of {} bytes",
+                        point, result, self.raw_size
+                    )));
+                } else {
+                    callback(point, &self.buffers[buffer_id]);
+                }
+            }
+
+            if error.is_some() {
+                // Stop submitting new reads, but wait for in-flight ones to release the buffers
+                exhausted = true;
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::marker::PhantomData;
+
+use crate::data_types::vectors::VectorElementType;
+use crate::spaces::metric::Metric;
+use crate::types::{PointOffsetType, ScoreType};
+use crate::vector_storage::mmap_vectors::MmapVectors;
+use crate::vector_storage::vector_norms::{apply_inverse_norm, InverseNorms};
+use crate::vector_storage::{RawScorer, ScoredPointOffset};
+
+/// Raw scorer over mem-mapped vectors, which reads vectors of a batch with io_uring instead of
+/// page faults on the mmap.
+///
+/// Falls back to regular mmap reads if async reader is busy or failed.
+pub struct AsyncRawScorer<'a, TMetric: Metric> {
+    query: Vec<VectorElementType>,
+    metric: PhantomData<TMetric>,
+    mmap_store: &'a MmapVectors,
+    norms: Option<&'a InverseNorms>,
+}
+
+impl<'a, TMetric: Metric> AsyncRawScorer<'a, TMetric> {
+    pub fn new(
+        query: Vec<VectorElementType>,
+        mmap_store: &'a MmapVectors,
+        norms: Option<&'a InverseNorms>,
+    ) -> Self {
+        Self {
+            query,
+            metric: PhantomData,
+            mmap_store,
+            norms,
+        }
+    }
+
+    fn score_points_mmap(
+        &self,
+        points: &[PointOffsetType],
+        scores: &mut [ScoredPointOffset],
+    ) -> usize {
+        let mut size: usize = 0;
+        for point in points {
+            let other_vector = self.mmap_store.raw_vector(*point).unwrap();
+            let score = TMetric::similarity(&self.query, other_vector);
+            scores[size] = ScoredPointOffset {
+                idx: *point,
+                score: apply_inverse_norm(self.norms, *point, score),
+            };
+            size += 1;
+        }
+        size
+    }
+}
+
+impl<TMetric> RawScorer for AsyncRawScorer<'_, TMetric>
+where
+    TMetric: Metric,
+{
+    fn score_points(&self, points: &[PointOffsetType], scores: &mut [ScoredPointOffset]) -> usize {
+        let points: Vec<_> = {
+            // Use `read_deleted_map` instead of `deleted` to prevent multiple locks
+            let deleted_map = self.mmap_store.read_deleted_map();
+            points
+                .iter()
+                .copied()
+                .filter(|point| !MmapVectors::check_deleted(&deleted_map, *point).unwrap_or(true))
+                .take(scores.len())
+                .collect()
+        };
+
+        let mut size: usize = 0;
+        let res =
+            self.mmap_store
+                .read_vectors_async(points.iter().copied(), |point, other_vector| {
+                    let score = TMetric::similarity(&self.query, other_vector);
+                    scores[size] = ScoredPointOffset {
+                        idx: point,
+                        score: apply_inverse_norm(self.norms, point, score),
+                    };
+                    size += 1;
+                });
+
+        match res {
+            Some(Ok(())) => size,
+            Some(Err(err)) => {
+                log::warn!("Async vectors read failed, falling back to mmap: {}", err);
+                self.score_points_mmap(&points, scores)
+            }
+            None => self.score_points_mmap(&points, scores),
+        }
+    }
+
+    fn check_point(&self, point: PointOffsetType) -> bool {
+        (point < self.mmap_store.num_vectors as PointOffsetType)
+            && !self.mmap_store.deleted(point).unwrap_or(true)
+    }
+
+    fn score_point(&self, point: PointOffsetType) -> ScoreType {
+        let other_vector = self.mmap_store.raw_vector(point).unwrap();
+        apply_inverse_norm(
+            self.norms,
+            point,
+            TMetric::similarity(&self.query, other_vector),
+        )
+    }
+
+    fn score_internal(&self, point_a: PointOffsetType, point_b: PointOffsetType) -> ScoreType {
+        let vector_a = self.mmap_store.raw_vector(point_a).unwrap();
+        let vector_b = self.mmap_store.raw_vector(point_b).unwrap();
+        let score = TMetric::similarity(vector_a, vector_b);
+        apply_inverse_norm(
+            self.norms,
+            point_b,
+            apply_inverse_norm(self.norms, point_a, score),
+        )
+    }
+}
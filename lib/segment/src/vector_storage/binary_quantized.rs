@@ -0,0 +1,224 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::file_operations::{atomic_save_bin, read_bin};
+use crate::data_types::vectors::VectorElementType;
+use crate::entry::entry_point::OperationResult;
+use crate::types::{PointOffsetType, ScoreType};
+use crate::vector_storage::{RawScorer, ScoredPointOffset, VectorStorageSS};
+
+const BINARY_QUANTIZED_VECTORS_FILE: &str = "binary_quantized_vectors.bin";
+
+const BITS_PER_WORD: usize = u64::BITS as usize;
+
+/// Vectors with each element encoded into a single bit: 1 for positive values, 0 otherwise.
+/// Similarity of encoded vectors is the dot product of their `{-1, 1}` representations,
+/// computed from the Hamming distance as `dim - 2 * hamming`.
+///
+/// Encoding works best for high-dimensional embeddings with elements centered around zero.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BinaryQuantizedVectors {
+    dim: usize,
+    words_per_vector: usize,
+    /// Bits of all vectors, `words_per_vector` words per vector
+    bits: Vec<u64>,
+}
+
+impl BinaryQuantizedVectors {
+    pub fn get_path(path: &Path) -> PathBuf {
+        path.join(BINARY_QUANTIZED_VECTORS_FILE)
+    }
+
+    pub fn load(path: &Path) -> OperationResult<Self> {
+        Ok(read_bin(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> OperationResult<()> {
+        Ok(atomic_save_bin(path, self)?)
+    }
+
+    fn words_per_vector(dim: usize) -> usize {
+        (dim + BITS_PER_WORD - 1) / BITS_PER_WORD
+    }
+
+    fn encode_vector(vector: &[VectorElementType], words: &mut [u64]) {
+        for (i, value) in vector.iter().enumerate() {
+            if *value > 0.0 {
+                words[i / BITS_PER_WORD] |= 1 << (i % BITS_PER_WORD);
+            }
+        }
+    }
+
+    /// Quantize all vectors of the storage. Deleted vectors are encoded as zero bits.
+    pub fn encode(vector_storage: &VectorStorageSS) -> Self {
+        let dim = vector_storage.vector_dim();
+        let words_per_vector = Self::words_per_vector(dim);
+        let total_vectors = vector_storage.total_vector_count();
+
+        let mut bits = vec![0; total_vectors * words_per_vector];
+        for point_id in 0..total_vectors as PointOffsetType {
+            if let Some(vector) = vector_storage.get_vector(point_id) {
+                let start = point_id as usize * words_per_vector;
+                Self::encode_vector(&vector, &mut bits[start..start + words_per_vector]);
+            }
+        }
+
+        Self {
+            dim,
+            words_per_vector,
+            bits,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        if self.words_per_vector == 0 {
+            0
+        } else {
+            self.bits.len() / self.words_per_vector
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Sign of the element doesn't depend on normalization, so query is encoded as is
+    pub fn prepare_query(&self, query: &[VectorElementType]) -> Vec<u64> {
+        let mut words = vec![0; self.words_per_vector];
+        Self::encode_vector(query, &mut words);
+        words
+    }
+
+    fn bits(&self, point_id: PointOffsetType) -> &[u64] {
+        let start = point_id as usize * self.words_per_vector;
+        &self.bits[start..start + self.words_per_vector]
+    }
+
+    fn similarity(&self, a: &[u64], b: &[u64]) -> ScoreType {
+        let hamming: u32 = a.iter().zip(b).map(|(a, b)| (a ^ b).count_ones()).sum();
+        self.dim as ScoreType - 2.0 * hamming as ScoreType
+    }
+
+    pub fn score(&self, query: &[u64], point_id: PointOffsetType) -> ScoreType {
+        self.similarity(query, self.bits(point_id))
+    }
+
+    /// Scorer which uses binary codes for scoring and the storage to check deleted points
+    pub fn raw_scorer<'a>(
+        &'a self,
+        query: &[VectorElementType],
+        vector_storage: &'a VectorStorageSS,
+    ) -> Box<dyn RawScorer + 'a> {
+        Box::new(BinaryRawScorer {
+            query: self.prepare_query(query),
+            quantized: self,
+            vector_storage,
+        })
+    }
+}
+
+pub struct BinaryRawScorer<'a> {
+    query: Vec<u64>,
+    quantized: &'a BinaryQuantizedVectors,
+    vector_storage: &'a VectorStorageSS,
+}
+
+impl RawScorer for BinaryRawScorer<'_> {
+    fn score_points(&self, points: &[PointOffsetType], scores: &mut [ScoredPointOffset]) -> usize {
+        let mut size: usize = 0;
+        for point_id in points.iter().copied() {
+            if !self.check_point(point_id) {
+                continue;
+            }
+            scores[size] = ScoredPointOffset {
+                idx: point_id,
+                score: self.quantized.score(&self.query, point_id),
+            };
+
+            size += 1;
+            if size == scores.len() {
+                return size;
+            }
+        }
+        size
+    }
+
+    fn check_point(&self, point: PointOffsetType) -> bool {
+        // Points added after quantization are not scored
+        (point as usize) < self.quantized.len() && !self.vector_storage.is_deleted(point)
+    }
+
+    fn score_point(&self, point: PointOffsetType) -> ScoreType {
+        self.quantized.score(&self.query, point)
+    }
+
+    fn score_internal(&self, point_a: PointOffsetType, point_b: PointOffsetType) -> ScoreType {
+        self.quantized
+            .similarity(self.quantized.bits(point_a), self.quantized.bits(point_b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::common::rocksdb_wrapper::{open_db, DB_VECTOR_CF};
+    use crate::types::Distance;
+    use crate::vector_storage::simple_vector_storage::open_simple_vector_storage;
+
+    fn sign_similarity(a: &[f32], b: &[f32]) -> f32 {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| if (*a > 0.0) == (*b > 0.0) { 1.0 } else { -1.0 })
+            .sum()
+    }
+
+    #[test]
+    fn test_binary_quantized_scores() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+        // Not a multiple of the word size, to check the padding
+        let dim = 100;
+        let storage =
+            open_simple_vector_storage(db, DB_VECTOR_CF, dir.path(), dim, Distance::Dot, true)
+                .unwrap();
+        let mut borrowed_storage = storage.borrow_mut();
+
+        let mut rnd = StdRng::seed_from_u64(42);
+        let vectors: Vec<Vec<f32>> = (0..50)
+            .map(|_| (0..dim).map(|_| rnd.gen_range(-1.0..1.0)).collect())
+            .collect();
+        for vector in &vectors {
+            borrowed_storage.put_vector(vector.clone()).unwrap();
+        }
+        borrowed_storage.delete(10).unwrap();
+
+        let quantized = BinaryQuantizedVectors::encode(&*borrowed_storage);
+        assert_eq!(quantized.len(), vectors.len());
+
+        let path = BinaryQuantizedVectors::get_path(dir.path());
+        quantized.save(&path).unwrap();
+        let quantized = BinaryQuantizedVectors::load(&path).unwrap();
+
+        let query: Vec<f32> = (0..dim).map(|_| rnd.gen_range(-1.0..1.0)).collect();
+        let raw_scorer = quantized.raw_scorer(&query, &*borrowed_storage);
+        assert!(!raw_scorer.check_point(10));
+        assert!(!raw_scorer.check_point(vectors.len() as PointOffsetType));
+
+        for (point_id, vector) in vectors.iter().enumerate() {
+            if point_id == 10 {
+                continue;
+            }
+            let point_id = point_id as PointOffsetType;
+            assert_eq!(
+                raw_scorer.score_point(point_id),
+                sign_similarity(&query, vector)
+            );
+            assert_eq!(raw_scorer.score_internal(point_id, point_id), dim as f32);
+        }
+    }
+}
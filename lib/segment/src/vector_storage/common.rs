@@ -0,0 +1,26 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// File with deleted flags of in-memory vector storages, which persist vectors in RocksDB
+pub const DELETED_FLAGS_FILE: &str = "deleted_flags.dat";
+
+/// Use async (io_uring) scorer for mem-mapped vector storages, if compiled with `async_scorer`
+static ASYNC_SCORER: AtomicBool = AtomicBool::new(false);
+
+/// Read mem-mapped vector storages into page cache in background, right after they are loaded
+static MMAP_PREFETCH: AtomicBool = AtomicBool::new(false);
+
+pub fn set_async_scorer(async_scorer: bool) {
+    ASYNC_SCORER.store(async_scorer, Ordering::Relaxed);
+}
+
+pub fn get_async_scorer() -> bool {
+    ASYNC_SCORER.load(Ordering::Relaxed)
+}
+
+pub fn set_mmap_prefetch(mmap_prefetch: bool) {
+    MMAP_PREFETCH.store(mmap_prefetch, Ordering::Relaxed);
+}
+
+pub fn get_mmap_prefetch() -> bool {
+    MMAP_PREFETCH.load(Ordering::Relaxed)
+}
@@ -15,7 +15,12 @@ use crate::spaces::metric::Metric;
 use crate::spaces::simple::{CosineMetric, DotProductMetric, EuclidMetric};
 use crate::spaces::tools::peek_top_largest_iterable;
 use crate::types::{Distance, PointOffsetType, ScoreType};
+#[cfg(all(target_os = "linux", feature = "async_scorer"))]
+use crate::vector_storage::async_raw_scorer::AsyncRawScorer;
+#[cfg(all(target_os = "linux", feature = "async_scorer"))]
+use crate::vector_storage::common::get_async_scorer;
 use crate::vector_storage::mmap_vectors::MmapVectors;
+use crate::vector_storage::vector_norms::{apply_inverse_norm, InverseNorms};
 use crate::vector_storage::{RawScorer, ScoredPointOffset, VectorStorage, VectorStorageSS};
 
 fn vf_to_u8<T>(v: &[T]) -> &[u8] {
@@ -28,6 +33,7 @@ pub struct MemmapRawScorer<'a, TMetric: Metric> {
     query: Vec<VectorElementType>,
     metric: std::marker::PhantomData<TMetric>,
     mmap_store: &'a MmapVectors,
+    norms: Option<&'a InverseNorms>,
 }
 
 impl<TMetric> RawScorer for MemmapRawScorer<'_, TMetric>
@@ -43,9 +49,10 @@ where
                 continue;
             }
             let other_vector = self.mmap_store.raw_vector(*point).unwrap();
+            let score = TMetric::similarity(&self.query, other_vector);
             scores[size] = ScoredPointOffset {
                 idx: *point,
-                score: TMetric::similarity(&self.query, other_vector),
+                score: apply_inverse_norm(self.norms, *point, score),
             };
 
             size += 1;
@@ -63,13 +70,22 @@ where
 
     fn score_point(&self, point: PointOffsetType) -> ScoreType {
         let other_vector = self.mmap_store.raw_vector(point).unwrap();
-        TMetric::similarity(&self.query, other_vector)
+        apply_inverse_norm(
+            self.norms,
+            point,
+            TMetric::similarity(&self.query, other_vector),
+        )
     }
 
     fn score_internal(&self, point_a: PointOffsetType, point_b: PointOffsetType) -> ScoreType {
         let vector_a = self.mmap_store.raw_vector(point_a).unwrap();
         let vector_b = self.mmap_store.raw_vector(point_b).unwrap();
-        TMetric::similarity(vector_a, vector_b)
+        let score = TMetric::similarity(vector_a, vector_b);
+        apply_inverse_norm(
+            self.norms,
+            point_b,
+            apply_inverse_norm(self.norms, point_a, score),
+        )
     }
 }
 
@@ -82,48 +98,83 @@ where
 pub struct MemmapVectorStorage<TMetric: Metric> {
     vectors_path: PathBuf,
     deleted_path: PathBuf,
+    /// Path of cached norms, if Cosine vectors are stored without normalization
+    norms_path: Option<PathBuf>,
     mmap_store: Option<MmapVectors>,
+    norms: Option<InverseNorms>,
     metric: PhantomData<TMetric>,
 }
 
+/// Load cached norms of the vectors, or compute them if the file is missing or incomplete
+fn open_norms(norms_path: &Path, mmap_store: &MmapVectors) -> OperationResult<InverseNorms> {
+    if norms_path.exists() {
+        let norms = InverseNorms::load(norms_path)?;
+        if norms.len() == mmap_store.num_vectors {
+            return Ok(norms);
+        }
+    }
+    let mut norms = InverseNorms::default();
+    for point_id in 0..mmap_store.num_vectors as PointOffsetType {
+        norms.push(mmap_store.raw_vector(point_id).unwrap());
+    }
+    Ok(norms)
+}
+
+/// Open mem-mapped vector storage.
+///
+/// If `normalize` is false, Cosine vectors are stored as is and scored with norms,
+/// cached in a separate file along with vectors.
 pub fn open_memmap_vector_storage(
     path: &Path,
     dim: usize,
     distance: Distance,
+    normalize: bool,
 ) -> OperationResult<Arc<AtomicRefCell<VectorStorageSS>>> {
+    match distance {
+        Distance::Cosine => {
+            open_memmap_vector_storage_with_metric::<CosineMetric>(path, dim, normalize)
+        }
+        Distance::Euclid => {
+            open_memmap_vector_storage_with_metric::<EuclidMetric>(path, dim, normalize)
+        }
+        Distance::Dot => {
+            open_memmap_vector_storage_with_metric::<DotProductMetric>(path, dim, normalize)
+        }
+        Distance::Custom(custom) => (custom.open_memmap_storage)(path, dim, normalize),
+    }
+}
+
+pub(crate) fn open_memmap_vector_storage_with_metric<TMetric>(
+    path: &Path,
+    dim: usize,
+    normalize: bool,
+) -> OperationResult<Arc<AtomicRefCell<VectorStorageSS>>>
+where
+    TMetric: Metric + Send + Sync + 'static,
+{
     create_dir_all(path)?;
 
     let vectors_path = path.join("matrix.dat");
     let deleted_path = path.join("deleted.dat");
+    let norms_path =
+        (TMetric::distance() == Distance::Cosine && !normalize).then(|| path.join("norms.dat"));
 
     let mmap_store = MmapVectors::open(&vectors_path, &deleted_path, dim)?;
+    let norms = norms_path
+        .as_deref()
+        .map(|norms_path| open_norms(norms_path, &mmap_store))
+        .transpose()?;
 
-    match distance {
-        Distance::Cosine => Ok(Arc::new(AtomicRefCell::new(MemmapVectorStorage::<
-            CosineMetric,
-        > {
-            vectors_path,
-            deleted_path,
-            mmap_store: Some(mmap_store),
-            metric: PhantomData,
-        }))),
-        Distance::Euclid => Ok(Arc::new(AtomicRefCell::new(MemmapVectorStorage::<
-            EuclidMetric,
-        > {
+    Ok(Arc::new(AtomicRefCell::new(
+        MemmapVectorStorage::<TMetric> {
             vectors_path,
             deleted_path,
+            norms_path,
             mmap_store: Some(mmap_store),
+            norms,
             metric: PhantomData,
-        }))),
-        Distance::Dot => Ok(Arc::new(AtomicRefCell::new(MemmapVectorStorage::<
-            DotProductMetric,
-        > {
-            vectors_path,
-            deleted_path,
-            mmap_store: Some(mmap_store),
-            metric: PhantomData,
-        }))),
-    }
+        },
+    )))
 }
 
 impl<TMetric> VectorStorage for MemmapVectorStorage<TMetric>
@@ -134,6 +185,10 @@ where
         self.mmap_store.as_ref().unwrap().dim
     }
 
+    fn distance(&self) -> Distance {
+        TMetric::distance()
+    }
+
     fn vector_count(&self) -> usize {
         self.mmap_store
             .as_ref()
@@ -172,6 +227,7 @@ where
         let mut end_index = start_index;
 
         self.mmap_store = None;
+        let mut new_norms = vec![];
 
         {
             let mut file = OpenOptions::new()
@@ -185,11 +241,17 @@ where
                 let vector = &other.get_vector(id).unwrap();
                 let raw_bites = vf_to_u8(vector);
                 file.write_all(raw_bites)?;
+                if self.norms_path.is_some() {
+                    new_norms.push(InverseNorms::compute(vector));
+                }
                 end_index += 1;
             }
 
             file.flush()?;
         }
+        if let Some(norms_path) = &self.norms_path {
+            InverseNorms::append_to_file(norms_path, &new_norms)?;
+        }
         {
             let mut file = OpenOptions::new()
                 .read(false)
@@ -204,11 +266,13 @@ where
             file.flush()?;
         }
 
-        self.mmap_store = Some(MmapVectors::open(
-            &self.vectors_path,
-            &self.deleted_path,
-            dim,
-        )?);
+        let mmap_store = MmapVectors::open(&self.vectors_path, &self.deleted_path, dim)?;
+        self.norms = self
+            .norms_path
+            .as_deref()
+            .map(|norms_path| open_norms(norms_path, &mmap_store))
+            .transpose()?;
+        self.mmap_store = Some(mmap_store);
 
         Ok(start_index..end_index)
     }
@@ -240,18 +304,35 @@ where
     }
 
     fn raw_scorer(&self, vector: Vec<VectorElementType>) -> Box<dyn RawScorer + '_> {
+        let query = TMetric::preprocess(&vector).unwrap_or(vector);
+        #[cfg(all(target_os = "linux", feature = "async_scorer"))]
+        if get_async_scorer() {
+            return Box::new(AsyncRawScorer::<TMetric>::new(
+                query,
+                self.mmap_store.as_ref().unwrap(),
+                self.norms.as_ref(),
+            ));
+        }
         Box::new(MemmapRawScorer::<TMetric> {
-            query: TMetric::preprocess(&vector).unwrap_or(vector),
+            query,
             metric: PhantomData,
             mmap_store: self.mmap_store.as_ref().unwrap(),
+            norms: self.norms.as_ref(),
         })
     }
 
     fn raw_scorer_internal(&self, point_id: PointOffsetType) -> Box<dyn RawScorer + '_> {
+        let vector = self.get_vector(point_id).unwrap();
+        // Stored vector is not normalized, if norms are cached
+        let query = match self.norms {
+            Some(_) => TMetric::preprocess(&vector).unwrap_or(vector),
+            None => vector,
+        };
         Box::new(MemmapRawScorer::<TMetric> {
-            query: self.get_vector(point_id).unwrap(),
+            query,
             metric: PhantomData,
             mmap_store: self.mmap_store.as_ref().unwrap(),
+            norms: self.norms.as_ref(),
         })
     }
 
@@ -276,9 +357,10 @@ where
             })
             .map(|point| {
                 let other_vector = self.mmap_store.as_ref().unwrap().raw_vector(point).unwrap();
+                let score = TMetric::similarity(preprocessed_vector, other_vector);
                 ScoredPointOffset {
                     idx: point,
-                    score: TMetric::similarity(preprocessed_vector, other_vector),
+                    score: apply_inverse_norm(self.norms.as_ref(), point, score),
                 }
             });
         peek_top_largest_iterable(scores, top)
@@ -291,9 +373,10 @@ where
             .map_or(vector, |x| x as &[_]);
         let scores = self.iter_ids().map(|point| {
             let other_vector = self.mmap_store.as_ref().unwrap().raw_vector(point).unwrap();
+            let score = TMetric::similarity(preprocessed_vector, other_vector);
             ScoredPointOffset {
                 idx: point,
-                score: TMetric::similarity(preprocessed_vector, other_vector),
+                score: apply_inverse_norm(self.norms.as_ref(), point, score),
             }
         });
 
@@ -326,7 +409,7 @@ mod tests {
         let dist = Distance::Dot;
         let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
 
-        let storage = open_memmap_vector_storage(dir.path(), 4, dist).unwrap();
+        let storage = open_memmap_vector_storage(dir.path(), 4, dist, true).unwrap();
         let mut borrowed_storage = storage.borrow_mut();
 
         let vec1 = vec![1.0, 0.0, 1.0, 1.0];
@@ -338,7 +421,8 @@ mod tests {
         {
             let dir2 = Builder::new().prefix("db_dir").tempdir().unwrap();
             let db = open_db(dir2.path(), &[DB_VECTOR_CF]).unwrap();
-            let storage2 = open_simple_vector_storage(db, DB_VECTOR_CF, 4, dist).unwrap();
+            let storage2 =
+                open_simple_vector_storage(db, DB_VECTOR_CF, dir2.path(), 4, dist, true).unwrap();
             {
                 let mut borrowed_storage2 = storage2.borrow_mut();
                 borrowed_storage2.put_vector(vec1).unwrap();
@@ -361,7 +445,8 @@ mod tests {
         {
             let dir2 = Builder::new().prefix("db_dir").tempdir().unwrap();
             let db = open_db(dir2.path(), &[DB_VECTOR_CF]).unwrap();
-            let storage2 = open_simple_vector_storage(db, DB_VECTOR_CF, 4, dist).unwrap();
+            let storage2 =
+                open_simple_vector_storage(db, DB_VECTOR_CF, dir2.path(), 4, dist, true).unwrap();
             {
                 let mut borrowed_storage2 = storage2.borrow_mut();
                 borrowed_storage2.put_vector(vec4).unwrap();
@@ -392,7 +477,7 @@ mod tests {
     fn test_mmap_raw_scorer() {
         let dist = Distance::Dot;
         let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
-        let storage = open_memmap_vector_storage(dir.path(), 4, dist).unwrap();
+        let storage = open_memmap_vector_storage(dir.path(), 4, dist, true).unwrap();
         let mut borrowed_storage = storage.borrow_mut();
 
         let vec1 = vec![1.0, 0.0, 1.0, 1.0];
@@ -404,7 +489,8 @@ mod tests {
         {
             let dir2 = Builder::new().prefix("db_dir").tempdir().unwrap();
             let db = open_db(dir2.path(), &[DB_VECTOR_CF]).unwrap();
-            let storage2 = open_simple_vector_storage(db, DB_VECTOR_CF, 4, dist).unwrap();
+            let storage2 =
+                open_simple_vector_storage(db, DB_VECTOR_CF, dir2.path(), 4, dist, true).unwrap();
             {
                 let mut borrowed_storage2 = storage2.borrow_mut();
                 borrowed_storage2.put_vector(vec1).unwrap();
@@ -433,6 +519,145 @@ mod tests {
         assert_eq!(res[2].score, -1.0);
     }
 
+    #[cfg(all(target_os = "linux", feature = "async_scorer"))]
+    #[test]
+    fn test_async_raw_scorer() {
+        let dist = Distance::Dot;
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let storage = open_memmap_vector_storage(dir.path(), 4, dist, true).unwrap();
+        let mut borrowed_storage = storage.borrow_mut();
+
+        {
+            let dir2 = Builder::new().prefix("db_dir").tempdir().unwrap();
+            let db = open_db(dir2.path(), &[DB_VECTOR_CF]).unwrap();
+            let storage2 =
+                open_simple_vector_storage(db, DB_VECTOR_CF, dir2.path(), 4, dist, true).unwrap();
+            {
+                let mut borrowed_storage2 = storage2.borrow_mut();
+                for i in 0..100 {
+                    borrowed_storage2
+                        .put_vector(vec![i as f32, 0.0, 1.0, 1.0])
+                        .unwrap();
+                }
+            }
+            borrowed_storage.update_from(&*storage2.borrow()).unwrap();
+        }
+        borrowed_storage.delete(10).unwrap();
+        borrowed_storage.flusher()().unwrap();
+        drop(borrowed_storage);
+        drop(storage);
+
+        let mmap_store = MmapVectors::open(
+            &dir.path().join("matrix.dat"),
+            &dir.path().join("deleted.dat"),
+            4,
+        )
+        .unwrap();
+
+        let query = vec![1.0, 1.0, 1.0, 1.0];
+        let query_points: Vec<PointOffsetType> = (0..50).collect();
+
+        let scorer = AsyncRawScorer::<DotProductMetric>::new(query, &mmap_store, None);
+
+        let mut res = vec![ScoredPointOffset { idx: 0, score: 0. }; query_points.len()];
+        let res_count = scorer.score_points(&query_points, &mut res);
+        res.resize(res_count, ScoredPointOffset { idx: 0, score: 0. });
+        res.sort_by_key(|x| x.idx);
+
+        assert_eq!(res.len(), 49);
+        for scored in res {
+            assert_ne!(scored.idx, 10);
+            assert_eq!(scored.score, scored.idx as f32 + 2.0);
+        }
+    }
+
+    #[test]
+    fn test_chunked_mmap_vectors() {
+        let dist = Distance::Dot;
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let storage = open_memmap_vector_storage(dir.path(), 4, dist, true).unwrap();
+        {
+            let dir2 = Builder::new().prefix("db_dir").tempdir().unwrap();
+            let db = open_db(dir2.path(), &[DB_VECTOR_CF]).unwrap();
+            let storage2 =
+                open_simple_vector_storage(db, DB_VECTOR_CF, dir2.path(), 4, dist, true).unwrap();
+            {
+                let mut borrowed_storage2 = storage2.borrow_mut();
+                for i in 0..100 {
+                    borrowed_storage2
+                        .put_vector(vec![i as f32, 1.0, 2.0, 3.0])
+                        .unwrap();
+                }
+            }
+            let mut borrowed_storage = storage.borrow_mut();
+            borrowed_storage.update_from(&*storage2.borrow()).unwrap();
+            borrowed_storage.delete(42).unwrap();
+            borrowed_storage.flusher()().unwrap();
+        }
+        drop(storage);
+
+        // 7 vectors of 16 bytes per chunk, the last chunk is incomplete
+        let mmap_store = MmapVectors::open_with_chunk_size(
+            &dir.path().join("matrix.dat"),
+            &dir.path().join("deleted.dat"),
+            4,
+            7 * 16 + 5,
+        )
+        .unwrap();
+
+        assert_eq!(mmap_store.num_vectors, 100);
+        assert_eq!(mmap_store.deleted_count, 1);
+        for i in 0..100 {
+            assert_eq!(
+                mmap_store.raw_vector(i).unwrap(),
+                &[i as f32, 1.0, 2.0, 3.0]
+            );
+        }
+        assert!(mmap_store.raw_vector(100).is_none());
+        assert!(mmap_store.get_vector(42).is_none());
+        assert_eq!(
+            mmap_store.get_vector(43).unwrap(),
+            vec![43.0, 1.0, 2.0, 3.0]
+        );
+    }
+
+    #[test]
+    fn test_cosine_norms_persistence() {
+        let dist = Distance::Cosine;
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let storage = open_memmap_vector_storage(dir.path(), 2, dist, false).unwrap();
+        {
+            let dir2 = Builder::new().prefix("db_dir").tempdir().unwrap();
+            let db = open_db(dir2.path(), &[DB_VECTOR_CF]).unwrap();
+            let storage2 =
+                open_simple_vector_storage(db, DB_VECTOR_CF, dir2.path(), 2, dist, false).unwrap();
+            {
+                let mut borrowed_storage2 = storage2.borrow_mut();
+                borrowed_storage2.put_vector(vec![3.0, 4.0]).unwrap();
+                borrowed_storage2.put_vector(vec![0.0, 10.0]).unwrap();
+            }
+            storage
+                .borrow_mut()
+                .update_from(&*storage2.borrow())
+                .unwrap();
+        }
+        drop(storage);
+        assert!(dir.path().join("norms.dat").exists());
+
+        let storage = open_memmap_vector_storage(dir.path(), 2, dist, false).unwrap();
+        let borrowed_storage = storage.borrow();
+        assert_eq!(borrowed_storage.get_vector(0).unwrap(), vec![3.0, 4.0]);
+
+        let raw_scorer = borrowed_storage.raw_scorer(vec![0.0, 2.0]);
+        assert!((raw_scorer.score_point(0) - 0.8).abs() < 1e-6);
+        assert!((raw_scorer.score_point(1) - 1.0).abs() < 1e-6);
+        assert!((raw_scorer.score_internal(0, 1) - 0.8).abs() < 1e-6);
+
+        let top = borrowed_storage.score_points(&[1.0, 0.0], &mut [0, 1].into_iter(), 2);
+        assert_eq!(top[0].idx, 0);
+        assert!((top[0].score - 0.6).abs() < 1e-6);
+    }
+
     #[test]
     fn test_casts() {
         let data: Vec<VectorElementType> = vec![0.42, 0.069, 333.1, 100500.];
@@ -3,8 +3,11 @@ use std::io::Write;
 use std::mem::{size_of, transmute};
 use std::path::Path;
 use std::sync::Arc;
+use std::thread;
 
 use memmap::{Mmap, MmapMut, MmapOptions};
+#[cfg(all(target_os = "linux", feature = "async_scorer"))]
+use parking_lot::Mutex;
 use parking_lot::{RwLock, RwLockReadGuard};
 
 use crate::common::error_logging::LogError;
@@ -12,29 +15,72 @@ use crate::common::Flusher;
 use crate::data_types::vectors::VectorElementType;
 use crate::entry::entry_point::OperationResult;
 use crate::types::PointOffsetType;
+#[cfg(all(target_os = "linux", feature = "async_scorer"))]
+use crate::vector_storage::async_io::UringReader;
+use crate::vector_storage::common::get_mmap_prefetch;
 
 const HEADER_SIZE: usize = 4;
 const DELETED_HEADER: &[u8; 4] = b"drop";
 const VECTORS_HEADER: &[u8; 4] = b"data";
 
+/// Maximal size of a single mem-mapped region of the vectors file
+const MMAP_CHUNK_SIZE_BYTES: usize = 32 * 1024 * 1024;
+const PAGE_SIZE_BYTES: usize = 4096;
+
 /// Mem-mapped file with vectors and soft-delete flags
+///
+/// Vectors file is mapped by chunks of whole vectors instead of a single huge mmap,
+/// so that chunks can be read into page cache independently.
 pub struct MmapVectors {
     pub dim: usize,
     pub num_vectors: usize,
-    mmap: Mmap,
+    /// Number of vectors in each chunk, the last chunk may contain less
+    chunk_vectors: usize,
+    chunks: Arc<Vec<Mmap>>,
     deleted_mmap: Arc<RwLock<MmapMut>>,
     pub deleted_count: usize,
+    /// Reader for batched async reads of vectors, bypassing the mmap
+    #[cfg(all(target_os = "linux", feature = "async_scorer"))]
+    uring_reader: Mutex<UringReader>,
 }
 
-fn open_read(path: &Path) -> OperationResult<Mmap> {
-    let file = OpenOptions::new()
-        .read(true)
-        .write(false)
-        .append(true)
-        .create(true)
-        .open(path)?;
+/// Map `num_vectors` vectors of the file by chunks of `chunk_vectors` vectors
+fn open_read_chunks(
+    file: &File,
+    raw_size: usize,
+    num_vectors: usize,
+    chunk_vectors: usize,
+) -> OperationResult<Vec<Mmap>> {
+    let mut chunks = Vec::with_capacity(num_vectors / chunk_vectors + 1);
+    for first_vector in (0..num_vectors).step_by(chunk_vectors) {
+        let chunk_len = chunk_vectors.min(num_vectors - first_vector) * raw_size;
+        let chunk_offset = HEADER_SIZE + first_vector * raw_size;
+        let chunk = unsafe {
+            MmapOptions::new()
+                .offset(chunk_offset as u64)
+                .len(chunk_len)
+                .map(file)?
+        };
+        chunks.push(chunk);
+    }
+    Ok(chunks)
+}
 
-    Ok(unsafe { MmapOptions::new().map(&file)? })
+/// Touch every page of the chunks in a background thread, so that first searches
+/// after the load are not slowed down by page faults
+fn prefetch_chunks(chunks: Arc<Vec<Mmap>>) {
+    let spawn_result = thread::Builder::new()
+        .name("mmap-prefetch".to_string())
+        .spawn(move || {
+            for chunk in chunks.iter() {
+                for position in (0..chunk.len()).step_by(PAGE_SIZE_BYTES) {
+                    unsafe { std::ptr::read_volatile(chunk.as_ptr().add(position)) };
+                }
+            }
+        });
+    if let Err(err) = spawn_result {
+        log::warn!("Failed to spawn mmap prefetch thread: {}", err);
+    }
 }
 
 fn open_write(path: &Path) -> OperationResult<MmapMut> {
@@ -58,12 +104,35 @@ fn ensure_mmap_file_exists(path: &Path, header: &[u8]) -> OperationResult<()> {
 
 impl MmapVectors {
     pub fn open(vectors_path: &Path, deleted_path: &Path, dim: usize) -> OperationResult<Self> {
+        Self::open_with_chunk_size(vectors_path, deleted_path, dim, MMAP_CHUNK_SIZE_BYTES)
+    }
+
+    pub(crate) fn open_with_chunk_size(
+        vectors_path: &Path,
+        deleted_path: &Path,
+        dim: usize,
+        chunk_size_bytes: usize,
+    ) -> OperationResult<Self> {
         ensure_mmap_file_exists(vectors_path, VECTORS_HEADER).describe("Create mmap data file")?;
         ensure_mmap_file_exists(deleted_path, DELETED_HEADER)
             .describe("Create mmap deleted flags file")?;
 
-        let mmap = open_read(vectors_path).describe("Open mmap for reading")?;
-        let num_vectors = (mmap.len() - HEADER_SIZE) / dim / size_of::<VectorElementType>();
+        let raw_size = dim * size_of::<VectorElementType>();
+        let vectors_file = OpenOptions::new()
+            .read(true)
+            .write(false)
+            .open(vectors_path)
+            .describe("Open vectors file for reading")?;
+        let file_len = vectors_file.metadata()?.len() as usize;
+        let num_vectors = file_len.saturating_sub(HEADER_SIZE) / raw_size;
+        let chunk_vectors = (chunk_size_bytes / raw_size).max(1);
+        let chunks = Arc::new(
+            open_read_chunks(&vectors_file, raw_size, num_vectors, chunk_vectors)
+                .describe("Open mmap for reading")?,
+        );
+        if get_mmap_prefetch() {
+            prefetch_chunks(chunks.clone());
+        }
 
         let deleted_mmap = open_write(deleted_path).describe("Open mmap for writing")?;
 
@@ -71,12 +140,28 @@ impl MmapVectors {
             .map(|idx| *deleted_mmap.get(idx).unwrap() as usize)
             .sum();
 
+        #[cfg(all(target_os = "linux", feature = "async_scorer"))]
+        let uring_reader = {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(false)
+                .open(vectors_path)
+                .describe("Open vectors file for async reading")?;
+            Mutex::new(UringReader::new(
+                file,
+                dim * size_of::<VectorElementType>(),
+            )?)
+        };
+
         Ok(MmapVectors {
             dim,
             num_vectors,
-            mmap,
+            chunk_vectors,
+            chunks,
             deleted_mmap: Arc::new(RwLock::new(deleted_mmap)),
             deleted_count,
+            #[cfg(all(target_os = "linux", feature = "async_scorer"))]
+            uring_reader,
         })
     }
 
@@ -93,15 +178,33 @@ impl MmapVectors {
         self.dim * size_of::<VectorElementType>()
     }
 
-    pub fn raw_vector_offset(&self, offset: usize) -> &[VectorElementType] {
-        let byte_slice = &self.mmap[offset..(offset + self.raw_size())];
+    pub fn raw_vector(&self, key: PointOffsetType) -> Option<&[VectorElementType]> {
+        let key = key as usize;
+        if key >= self.num_vectors {
+            return None;
+        }
+        let chunk = &self.chunks[key / self.chunk_vectors];
+        let offset = (key % self.chunk_vectors) * self.raw_size();
+        let byte_slice = &chunk[offset..(offset + self.raw_size())];
         let arr: &[VectorElementType] = unsafe { transmute(byte_slice) };
-        &arr[0..self.dim]
+        Some(&arr[0..self.dim])
     }
 
-    pub fn raw_vector(&self, key: PointOffsetType) -> Option<&[VectorElementType]> {
-        self.data_offset(key)
-            .map(|offset| self.raw_vector_offset(offset))
+    /// Read vectors of `points` with io_uring and call `callback` for each of them in order of
+    /// read completion.
+    ///
+    /// Returns `None` without reading anything if the reader is busy with another request.
+    #[cfg(all(target_os = "linux", feature = "async_scorer"))]
+    pub fn read_vectors_async(
+        &self,
+        points: impl IntoIterator<Item = PointOffsetType>,
+        callback: impl FnMut(PointOffsetType, &[VectorElementType]),
+    ) -> Option<OperationResult<()>> {
+        let mut reader = self.uring_reader.try_lock()?;
+        let points = points
+            .into_iter()
+            .filter_map(|point| self.data_offset(point).map(|offset| (point, offset)));
+        Some(reader.read_stream(points, callback))
     }
 
     pub fn check_deleted(mmap: &MmapMut, key: PointOffsetType) -> Option<bool> {
@@ -120,9 +223,7 @@ impl MmapVectors {
     pub fn get_vector(&self, key: PointOffsetType) -> Option<Vec<VectorElementType>> {
         match self.deleted(key) {
             None | Some(true) => None,
-            Some(false) => self
-                .data_offset(key)
-                .map(|offset| self.raw_vector_offset(offset).to_vec()),
+            Some(false) => self.raw_vector(key).map(|vector| vector.to_vec()),
         }
     }
 
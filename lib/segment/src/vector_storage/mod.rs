@@ -1,7 +1,17 @@
+#[cfg(all(target_os = "linux", feature = "async_scorer"))]
+mod async_io;
+#[cfg(all(target_os = "linux", feature = "async_scorer"))]
+pub mod async_raw_scorer;
+pub mod binary_quantized;
 pub mod chunked_vectors;
+pub mod common;
 pub mod memmap_vector_storage;
 mod mmap_vectors;
+pub mod multi_vector_storage;
+pub mod quantized_vectors;
+pub mod scalar_quantized;
 pub mod simple_vector_storage;
+pub mod vector_norms;
 mod vector_storage_base;
 
 pub use vector_storage_base::*;
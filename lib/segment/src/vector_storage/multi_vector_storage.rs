@@ -0,0 +1,501 @@
+use std::fs::create_dir_all;
+use std::marker::PhantomData;
+use std::mem::size_of;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use bitvec::prelude::BitSlice;
+use log::debug;
+use parking_lot::RwLock;
+use rocksdb::DB;
+use serde::{Deserialize, Serialize};
+
+use super::vector_storage_base::VectorStorage;
+use crate::common::mmap_bitslice::MmapBitSlice;
+use crate::common::rocksdb_wrapper::DatabaseColumnWrapper;
+use crate::common::Flusher;
+use crate::data_types::vectors::VectorElementType;
+use crate::entry::entry_point::{OperationError, OperationResult};
+use crate::spaces::metric::Metric;
+use crate::spaces::simple::{CosineMetric, DotProductMetric, EuclidMetric};
+use crate::spaces::tools::peek_top_largest_iterable;
+use crate::types::{Distance, MultiVectorComparator, PointOffsetType, ScoreType};
+use crate::vector_storage::common::DELETED_FLAGS_FILE;
+use crate::vector_storage::{RawScorer, ScoredPointOffset, VectorStorageSS};
+
+/// In-memory storage of multivectors: every point holds one or more sub-vectors of size `dim`,
+/// concatenated into a single flat vector.
+///
+/// Sub-vectors are compared with `TMetric` and the per-pair scores are combined with the
+/// configured `MultiVectorComparator`.
+pub struct MultiVectorStorage<TMetric: Metric> {
+    dim: usize,
+    comparator: MultiVectorComparator,
+    metric: PhantomData<TMetric>,
+    vectors: Vec<Vec<VectorElementType>>,
+    deleted: MmapBitSlice,
+    deleted_count: usize,
+    db_wrapper: DatabaseColumnWrapper,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct StoredRecord {
+    /// Only used to migrate storages, created before deleted flags were moved to `DELETED_FLAGS_FILE`
+    pub deleted: bool,
+    pub vector: Vec<VectorElementType>,
+}
+
+/// Sum over the query sub-vectors of the best similarity against any sub-vector of the point
+fn max_sim<TMetric: Metric>(
+    dim: usize,
+    query: &[VectorElementType],
+    vector: &[VectorElementType],
+) -> ScoreType {
+    query
+        .chunks(dim)
+        .map(|query_sub_vector| {
+            vector
+                .chunks(dim)
+                .map(|sub_vector| TMetric::similarity(query_sub_vector, sub_vector))
+                .fold(ScoreType::NEG_INFINITY, ScoreType::max)
+        })
+        .sum()
+}
+
+fn multi_similarity<TMetric: Metric>(
+    comparator: MultiVectorComparator,
+    dim: usize,
+    query: &[VectorElementType],
+    vector: &[VectorElementType],
+) -> ScoreType {
+    match comparator {
+        MultiVectorComparator::MaxSim => max_sim::<TMetric>(dim, query, vector),
+    }
+}
+
+/// Apply metric preprocessing to each sub-vector independently
+fn preprocess_multi<TMetric: Metric>(
+    dim: usize,
+    vector: &[VectorElementType],
+) -> Option<Vec<VectorElementType>> {
+    let mut processed = Vec::with_capacity(vector.len());
+    for sub_vector in vector.chunks(dim) {
+        processed.extend(TMetric::preprocess(sub_vector)?);
+    }
+    Some(processed)
+}
+
+pub struct MultiRawScorer<'a, TMetric: Metric> {
+    pub dim: usize,
+    pub comparator: MultiVectorComparator,
+    pub query: Vec<VectorElementType>,
+    pub vectors: &'a [Vec<VectorElementType>],
+    pub deleted: &'a BitSlice<u8>,
+    pub metric: PhantomData<TMetric>,
+}
+
+impl<TMetric> MultiRawScorer<'_, TMetric>
+where
+    TMetric: Metric,
+{
+    fn similarity(&self, query: &[VectorElementType], vector: &[VectorElementType]) -> ScoreType {
+        multi_similarity::<TMetric>(self.comparator, self.dim, query, vector)
+    }
+}
+
+impl<TMetric> RawScorer for MultiRawScorer<'_, TMetric>
+where
+    TMetric: Metric,
+{
+    fn score_points(&self, points: &[PointOffsetType], scores: &mut [ScoredPointOffset]) -> usize {
+        let mut size: usize = 0;
+        for point_id in points.iter().copied() {
+            if self.deleted[point_id as usize] {
+                continue;
+            }
+            let other_vector = &self.vectors[point_id as usize];
+            scores[size] = ScoredPointOffset {
+                idx: point_id,
+                score: self.similarity(&self.query, other_vector),
+            };
+
+            size += 1;
+            if size == scores.len() {
+                return size;
+            }
+        }
+        size
+    }
+
+    fn check_point(&self, point: PointOffsetType) -> bool {
+        (point as usize) < self.vectors.len() && !self.deleted[point as usize]
+    }
+
+    fn score_point(&self, point: PointOffsetType) -> ScoreType {
+        let other_vector = &self.vectors[point as usize];
+        self.similarity(&self.query, other_vector)
+    }
+
+    fn score_internal(&self, point_a: PointOffsetType, point_b: PointOffsetType) -> ScoreType {
+        let vector_a = &self.vectors[point_a as usize];
+        let vector_b = &self.vectors[point_b as usize];
+        self.similarity(vector_a, vector_b)
+    }
+}
+
+pub fn open_multi_vector_storage(
+    database: Arc<RwLock<DB>>,
+    database_column_name: &str,
+    path: &Path,
+    dim: usize,
+    distance: Distance,
+    comparator: MultiVectorComparator,
+) -> OperationResult<Arc<AtomicRefCell<VectorStorageSS>>> {
+    match distance {
+        Distance::Cosine => open_multi_vector_storage_with_metric::<CosineMetric>(
+            database,
+            database_column_name,
+            path,
+            dim,
+            comparator,
+        ),
+        Distance::Euclid => open_multi_vector_storage_with_metric::<EuclidMetric>(
+            database,
+            database_column_name,
+            path,
+            dim,
+            comparator,
+        ),
+        Distance::Dot => open_multi_vector_storage_with_metric::<DotProductMetric>(
+            database,
+            database_column_name,
+            path,
+            dim,
+            comparator,
+        ),
+        Distance::Custom(custom) => {
+            (custom.open_multi_storage)(database, database_column_name, path, dim, comparator)
+        }
+    }
+}
+
+pub(crate) fn open_multi_vector_storage_with_metric<TMetric>(
+    database: Arc<RwLock<DB>>,
+    database_column_name: &str,
+    path: &Path,
+    dim: usize,
+    comparator: MultiVectorComparator,
+) -> OperationResult<Arc<AtomicRefCell<VectorStorageSS>>>
+where
+    TMetric: Metric + Send + Sync + 'static,
+{
+    create_dir_all(path)?;
+    let deleted_path = path.join(DELETED_FLAGS_FILE);
+    // Storages, created before the flags file was introduced, keep deleted flags in RocksDB
+    let migrate_deleted = !deleted_path.exists();
+
+    let mut vectors: Vec<Vec<VectorElementType>> = vec![];
+    let mut deleted = MmapBitSlice::open(&deleted_path)?;
+
+    let db_wrapper = DatabaseColumnWrapper::new(database, database_column_name);
+    for (key, value) in db_wrapper.lock_db().iter()? {
+        let point_id: PointOffsetType = bincode::deserialize(&key)
+            .map_err(|_| OperationError::service_error("cannot deserialize point id from db"))?;
+        let stored_record: StoredRecord = bincode::deserialize(&value)
+            .map_err(|_| OperationError::service_error("cannot deserialize record from db"))?;
+        if migrate_deleted && stored_record.deleted {
+            deleted.set(point_id as usize, true)?;
+        }
+
+        if vectors.len() <= (point_id as usize) {
+            vectors.resize(point_id as usize + 1, vec![]);
+        }
+        vectors[point_id as usize] = stored_record.vector;
+    }
+    deleted.ensure_len(vectors.len())?;
+    let deleted_count = deleted[..vectors.len()].count_ones();
+
+    debug!("Segment multivectors: {}", vectors.len());
+    debug!(
+        "Estimated segment size {} MB",
+        vectors.iter().map(|vector| vector.len()).sum::<usize>() * size_of::<VectorElementType>()
+            / 1024
+            / 1024
+    );
+
+    Ok(Arc::new(AtomicRefCell::new(
+        MultiVectorStorage::<TMetric> {
+            dim,
+            comparator,
+            metric: PhantomData,
+            vectors,
+            deleted,
+            deleted_count,
+            db_wrapper,
+        },
+    )))
+}
+
+impl<TMetric> MultiVectorStorage<TMetric>
+where
+    TMetric: Metric,
+{
+    fn update_stored(&self, point_id: PointOffsetType) -> OperationResult<()> {
+        let record = StoredRecord {
+            deleted: self.deleted[point_id as usize],
+            vector: self.vectors[point_id as usize].clone(),
+        };
+
+        self.db_wrapper.put(
+            &bincode::serialize(&point_id).unwrap(),
+            &bincode::serialize(&record).unwrap(),
+        )?;
+
+        Ok(())
+    }
+
+    fn preprocess(&self, vector: &[VectorElementType]) -> Vec<VectorElementType> {
+        preprocess_multi::<TMetric>(self.dim, vector).unwrap_or_else(|| vector.to_owned())
+    }
+
+    fn similarity(&self, query: &[VectorElementType], vector: &[VectorElementType]) -> ScoreType {
+        multi_similarity::<TMetric>(self.comparator, self.dim, query, vector)
+    }
+
+    fn raw_scorer_for(&self, query: Vec<VectorElementType>) -> Box<dyn RawScorer + '_> {
+        Box::new(MultiRawScorer::<TMetric> {
+            dim: self.dim,
+            comparator: self.comparator,
+            query,
+            vectors: &self.vectors,
+            deleted: &self.deleted,
+            metric: PhantomData,
+        })
+    }
+}
+
+impl<TMetric> VectorStorage for MultiVectorStorage<TMetric>
+where
+    TMetric: Metric,
+{
+    /// Size of a single sub-vector
+    fn vector_dim(&self) -> usize {
+        self.dim
+    }
+
+    fn distance(&self) -> Distance {
+        TMetric::distance()
+    }
+
+    fn vector_count(&self) -> usize {
+        self.vectors.len() - self.deleted_count
+    }
+
+    fn deleted_count(&self) -> usize {
+        self.deleted_count
+    }
+
+    fn total_vector_count(&self) -> usize {
+        self.vectors.len()
+    }
+
+    fn get_vector(&self, key: PointOffsetType) -> Option<Vec<VectorElementType>> {
+        if (key as usize) >= self.vectors.len() || self.deleted[key as usize] {
+            return None;
+        }
+        Some(self.vectors[key as usize].clone())
+    }
+
+    fn put_vector(&mut self, vector: Vec<VectorElementType>) -> OperationResult<PointOffsetType> {
+        assert!(!vector.is_empty() && vector.len() % self.dim == 0);
+        let new_id = self.vectors.len() as PointOffsetType;
+        self.vectors.push(vector);
+        self.deleted.set(new_id as usize, false)?;
+        self.update_stored(new_id)?;
+        Ok(new_id)
+    }
+
+    fn update_vector(
+        &mut self,
+        key: PointOffsetType,
+        vector: Vec<VectorElementType>,
+    ) -> OperationResult<PointOffsetType> {
+        if self.vectors.len() <= (key as usize) {
+            self.vectors.resize(key as usize + 1, vec![]);
+        }
+        self.vectors[key as usize] = vector;
+        self.deleted.set(key as usize, false)?;
+        self.update_stored(key)?;
+        Ok(key)
+    }
+
+    fn update_from(&mut self, other: &VectorStorageSS) -> OperationResult<Range<PointOffsetType>> {
+        let start_index = self.vectors.len() as PointOffsetType;
+        for point_id in other.iter_ids() {
+            let other_vector = other.get_vector(point_id).unwrap();
+            // Do not perform preprocessing - vectors should be already processed
+            let new_id = self.vectors.len() as PointOffsetType;
+            self.vectors.push(other_vector);
+            self.deleted.set(new_id as usize, false)?;
+            self.update_stored(new_id)?;
+        }
+        let end_index = self.vectors.len() as PointOffsetType;
+        Ok(start_index..end_index)
+    }
+
+    fn delete(&mut self, key: PointOffsetType) -> OperationResult<()> {
+        if (key as usize) >= self.vectors.len() {
+            return Ok(());
+        }
+        if !self.deleted[key as usize] {
+            self.deleted_count += 1;
+        }
+        self.deleted.set(key as usize, true)
+    }
+
+    fn is_deleted(&self, key: PointOffsetType) -> bool {
+        self.deleted[key as usize]
+    }
+
+    fn iter_ids(&self) -> Box<dyn Iterator<Item = PointOffsetType> + '_> {
+        let iter = (0..self.vectors.len() as PointOffsetType)
+            .filter(move |id| !self.deleted[*id as usize]);
+        Box::new(iter)
+    }
+
+    fn flusher(&self) -> Flusher {
+        let deleted_flusher = self.deleted.flusher();
+        let db_flusher = self.db_wrapper.flusher();
+        Box::new(move || {
+            deleted_flusher()?;
+            db_flusher()
+        })
+    }
+
+    fn raw_scorer(&self, vector: Vec<VectorElementType>) -> Box<dyn RawScorer + '_> {
+        self.raw_scorer_for(self.preprocess(&vector))
+    }
+
+    fn raw_scorer_internal(&self, point_id: PointOffsetType) -> Box<dyn RawScorer + '_> {
+        self.raw_scorer_for(self.vectors[point_id as usize].clone())
+    }
+
+    fn score_points(
+        &self,
+        vector: &[VectorElementType],
+        points: &mut dyn Iterator<Item = PointOffsetType>,
+        top: usize,
+    ) -> Vec<ScoredPointOffset> {
+        let preprocessed_vector = self.preprocess(vector);
+        let scores = points
+            .filter(|point_id| !self.deleted[*point_id as usize])
+            .map(|point_id| ScoredPointOffset {
+                idx: point_id,
+                score: self.similarity(&preprocessed_vector, &self.vectors[point_id as usize]),
+            });
+        peek_top_largest_iterable(scores, top)
+    }
+
+    fn score_all(&self, vector: &[VectorElementType], top: usize) -> Vec<ScoredPointOffset> {
+        let preprocessed_vector = self.preprocess(vector);
+        let scores = (0..self.vectors.len())
+            .filter(|point_id| !self.deleted[*point_id])
+            .map(|point_id| ScoredPointOffset {
+                idx: point_id as PointOffsetType,
+                score: self.similarity(&preprocessed_vector, &self.vectors[point_id]),
+            });
+        peek_top_largest_iterable(scores, top)
+    }
+
+    fn score_internal(
+        &self,
+        point: PointOffsetType,
+        points: &mut dyn Iterator<Item = PointOffsetType>,
+        top: usize,
+    ) -> Vec<ScoredPointOffset> {
+        let vector = self.get_vector(point).unwrap();
+        self.score_points(&vector, points, top)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::common::rocksdb_wrapper::{open_db, DB_VECTOR_CF};
+
+    #[test]
+    fn test_max_sim_score_points() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+        let dim = 2;
+        let storage = open_multi_vector_storage(
+            db.clone(),
+            DB_VECTOR_CF,
+            dir.path(),
+            dim,
+            Distance::Dot,
+            MultiVectorComparator::MaxSim,
+        )
+        .unwrap();
+
+        {
+            let mut borrowed_storage = storage.borrow_mut();
+            // Single sub-vector
+            borrowed_storage.put_vector(vec![1.0, 0.0]).unwrap();
+            // Three sub-vectors
+            borrowed_storage
+                .put_vector(vec![0.0, 1.0, 0.5, 0.5, 2.0, 0.0])
+                .unwrap();
+            // Two sub-vectors
+            borrowed_storage
+                .put_vector(vec![0.0, 3.0, 0.1, 0.1])
+                .unwrap();
+        }
+
+        let borrowed_storage = storage.borrow();
+        assert_eq!(borrowed_storage.vector_count(), 3);
+        assert_eq!(borrowed_storage.get_vector(1).unwrap().len(), 6);
+
+        // Query of two sub-vectors: [1, 0] and [0, 1]
+        let query = vec![1.0, 0.0, 0.0, 1.0];
+
+        // point 0: max(1) + max(0) = 1
+        // point 1: max(0, 0.5, 2) + max(1, 0.5, 0) = 3
+        // point 2: max(0, 0.1) + max(3, 0.1) = 3.1
+        let scored = borrowed_storage.score_points(&query, &mut [0, 1, 2].iter().cloned(), 3);
+        let ids: Vec<_> = scored.iter().map(|x| x.idx).collect();
+        assert_eq!(ids, vec![2, 1, 0]);
+        assert!((scored[0].score - 3.1).abs() < 1e-5);
+        assert!((scored[1].score - 3.0).abs() < 1e-5);
+        assert!((scored[2].score - 1.0).abs() < 1e-5);
+
+        let raw_scorer = borrowed_storage.raw_scorer(query);
+        for scored_point in &scored {
+            assert!((raw_scorer.score_point(scored_point.idx) - scored_point.score).abs() < 1e-5);
+        }
+        drop(raw_scorer);
+        drop(borrowed_storage);
+        drop(storage);
+
+        // Multivectors are restored from the database as is
+        let storage = open_multi_vector_storage(
+            db,
+            DB_VECTOR_CF,
+            dir.path(),
+            dim,
+            Distance::Dot,
+            MultiVectorComparator::MaxSim,
+        )
+        .unwrap();
+        let borrowed_storage = storage.borrow();
+        assert_eq!(borrowed_storage.vector_count(), 3);
+        assert_eq!(
+            borrowed_storage.get_vector(2).unwrap(),
+            vec![0.0, 3.0, 0.1, 0.1]
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use std::path::Path;
+
+use crate::data_types::vectors::VectorElementType;
+use crate::entry::entry_point::OperationResult;
+use crate::types::QuantizationConfig;
+use crate::vector_storage::binary_quantized::BinaryQuantizedVectors;
+use crate::vector_storage::scalar_quantized::ScalarQuantizedVectors;
+use crate::vector_storage::{RawScorer, VectorStorageSS};
+
+/// Quantized copy of vectors of the storage, according to the configured quantization type
+pub enum QuantizedVectors {
+    Scalar(ScalarQuantizedVectors),
+    Binary(BinaryQuantizedVectors),
+}
+
+impl QuantizedVectors {
+    pub fn encode(vector_storage: &VectorStorageSS, config: &QuantizationConfig) -> Self {
+        match config {
+            QuantizationConfig::Scalar(scalar_config) => QuantizedVectors::Scalar(
+                ScalarQuantizedVectors::encode(vector_storage, scalar_config),
+            ),
+            QuantizationConfig::Binary(_) => {
+                QuantizedVectors::Binary(BinaryQuantizedVectors::encode(vector_storage))
+            }
+        }
+    }
+
+    /// Load previously saved quantized vectors, if there are any
+    pub fn load(path: &Path, config: &QuantizationConfig) -> OperationResult<Option<Self>> {
+        let quantized_vectors = match config {
+            QuantizationConfig::Scalar(_) => {
+                let quantized_path = ScalarQuantizedVectors::get_path(path);
+                if !quantized_path.exists() {
+                    return Ok(None);
+                }
+                QuantizedVectors::Scalar(ScalarQuantizedVectors::load(&quantized_path)?)
+            }
+            QuantizationConfig::Binary(_) => {
+                let quantized_path = BinaryQuantizedVectors::get_path(path);
+                if !quantized_path.exists() {
+                    return Ok(None);
+                }
+                QuantizedVectors::Binary(BinaryQuantizedVectors::load(&quantized_path)?)
+            }
+        };
+        Ok(Some(quantized_vectors))
+    }
+
+    pub fn save(&self, path: &Path) -> OperationResult<()> {
+        match self {
+            QuantizedVectors::Scalar(quantized) => {
+                quantized.save(&ScalarQuantizedVectors::get_path(path))
+            }
+            QuantizedVectors::Binary(quantized) => {
+                quantized.save(&BinaryQuantizedVectors::get_path(path))
+            }
+        }
+    }
+
+    /// Binary codes lose too much information to be used for final scores,
+    /// so results are always re-scored with original vectors
+    pub fn requires_rescoring(&self) -> bool {
+        matches!(self, QuantizedVectors::Binary(_))
+    }
+
+    pub fn raw_scorer<'a>(
+        &'a self,
+        query: &[VectorElementType],
+        vector_storage: &'a VectorStorageSS,
+    ) -> Box<dyn RawScorer + 'a> {
+        match self {
+            QuantizedVectors::Scalar(quantized) => quantized.raw_scorer(query, vector_storage),
+            QuantizedVectors::Binary(quantized) => quantized.raw_scorer(query, vector_storage),
+        }
+    }
+}
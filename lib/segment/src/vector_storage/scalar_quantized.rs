@@ -0,0 +1,347 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::file_operations::{atomic_save_bin, read_bin};
+use crate::data_types::vectors::VectorElementType;
+use crate::entry::entry_point::OperationResult;
+use crate::types::{Distance, PointOffsetType, ScalarQuantizationConfig, ScoreType};
+use crate::vector_storage::{RawScorer, ScoredPointOffset, VectorStorageSS};
+
+const QUANTIZED_VECTORS_FILE: &str = "quantized_vectors.bin";
+
+/// Max number of vector elements used to find quantization range
+const QUANTILE_SAMPLE_SIZE: usize = 100_000;
+
+/// Number of distinct values of a quantized element
+const QUANTIZATION_LEVELS: f32 = u8::MAX as f32;
+
+/// Vectors with elements encoded into `u8` codes.
+/// Original element is approximated as `offset + alpha * code`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ScalarQuantizedVectors {
+    dim: usize,
+    distance: Distance,
+    alpha: f32,
+    offset: f32,
+    /// Codes of all vectors, `dim` elements per vector
+    codes: Vec<u8>,
+    /// Squared norms of approximated vectors, required for Euclid distance
+    squared_norms: Vec<f32>,
+}
+
+/// Query, prepared for scoring against quantized vectors
+pub struct QuantizedQuery {
+    /// Query elements multiplied by `alpha`
+    scaled: Vec<VectorElementType>,
+    /// Part of the dot product which doesn't depend on codes: `offset * sum(query)`
+    shift: ScoreType,
+    squared_norm: ScoreType,
+    /// Preprocessed query, custom distances are scored against restored vectors
+    vector: Vec<VectorElementType>,
+}
+
+impl ScalarQuantizedVectors {
+    pub fn get_path(path: &Path) -> PathBuf {
+        path.join(QUANTIZED_VECTORS_FILE)
+    }
+
+    pub fn load(path: &Path) -> OperationResult<Self> {
+        Ok(read_bin(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> OperationResult<()> {
+        Ok(atomic_save_bin(path, self)?)
+    }
+
+    /// Stored vector, preprocessed according to the distance.
+    /// Storage may keep Cosine vectors without normalization.
+    fn preprocessed_vector(
+        vector_storage: &VectorStorageSS,
+        point_id: PointOffsetType,
+    ) -> Option<Vec<VectorElementType>> {
+        vector_storage.get_vector(point_id).map(|vector| {
+            vector_storage
+                .distance()
+                .preprocess_vector(&vector)
+                .unwrap_or(vector)
+        })
+    }
+
+    /// Quantize all vectors of the storage. Deleted vectors are encoded as zero vectors.
+    pub fn encode(vector_storage: &VectorStorageSS, config: &ScalarQuantizationConfig) -> Self {
+        let dim = vector_storage.vector_dim();
+        let distance = vector_storage.distance();
+        let total_vectors = vector_storage.total_vector_count();
+        let (min, max) = Self::find_range(vector_storage, config.quantile.unwrap_or(1.0));
+
+        let offset = min;
+        let alpha = if max > min {
+            (max - min) / QUANTIZATION_LEVELS
+        } else {
+            1.0
+        };
+
+        let mut codes = Vec::with_capacity(total_vectors * dim);
+        let mut squared_norms = Vec::with_capacity(total_vectors);
+        for point_id in 0..total_vectors as PointOffsetType {
+            match Self::preprocessed_vector(vector_storage, point_id) {
+                Some(vector) => {
+                    let mut squared_norm = 0.0;
+                    for value in vector {
+                        let code = ((value - offset) / alpha)
+                            .round()
+                            .clamp(0.0, QUANTIZATION_LEVELS)
+                            as u8;
+                        let restored = offset + alpha * code as f32;
+                        squared_norm += restored * restored;
+                        codes.push(code);
+                    }
+                    squared_norms.push(squared_norm);
+                }
+                None => {
+                    codes.resize(codes.len() + dim, 0);
+                    squared_norms.push(0.0);
+                }
+            }
+        }
+
+        Self {
+            dim,
+            distance,
+            alpha,
+            offset,
+            codes,
+            squared_norms,
+        }
+    }
+
+    /// Range of element values, which covers `quantile` of sampled elements
+    fn find_range(vector_storage: &VectorStorageSS, quantile: f32) -> (f32, f32) {
+        let dim = vector_storage.vector_dim().max(1);
+        let sample_vectors = QUANTILE_SAMPLE_SIZE / dim + 1;
+        let mut values: Vec<f32> = vector_storage
+            .iter_ids()
+            .step_by(vector_storage.vector_count() / sample_vectors + 1)
+            .filter_map(|point_id| Self::preprocessed_vector(vector_storage, point_id))
+            .flatten()
+            .filter(|value| value.is_finite())
+            .collect();
+        if values.is_empty() {
+            return (0.0, 0.0);
+        }
+        values.sort_unstable_by(|a, b| a.total_cmp(b));
+
+        let last = (values.len() - 1) as f32;
+        let lower = ((1.0 - quantile) / 2.0 * last).floor() as usize;
+        let upper = ((1.0 + quantile) / 2.0 * last).ceil() as usize;
+        (values[lower], values[upper.min(values.len() - 1)])
+    }
+
+    pub fn len(&self) -> usize {
+        self.squared_norms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.squared_norms.is_empty()
+    }
+
+    pub fn prepare_query(&self, query: &[VectorElementType]) -> QuantizedQuery {
+        let preprocessed = self.distance.preprocess_vector(query);
+        let query = preprocessed.as_deref().unwrap_or(query);
+        QuantizedQuery {
+            scaled: query.iter().map(|value| value * self.alpha).collect(),
+            shift: self.offset * query.iter().sum::<f32>(),
+            squared_norm: query.iter().map(|value| value * value).sum(),
+            vector: query.to_vec(),
+        }
+    }
+
+    fn codes(&self, point_id: PointOffsetType) -> &[u8] {
+        let start = point_id as usize * self.dim;
+        &self.codes[start..start + self.dim]
+    }
+
+    /// Approximation of the original vector
+    pub fn restore(&self, point_id: PointOffsetType) -> Vec<VectorElementType> {
+        self.codes(point_id)
+            .iter()
+            .map(|code| self.offset + self.alpha * *code as f32)
+            .collect()
+    }
+
+    fn dot(&self, query: &QuantizedQuery, point_id: PointOffsetType) -> ScoreType {
+        query.shift
+            + query
+                .scaled
+                .iter()
+                .zip(self.codes(point_id))
+                .map(|(value, code)| value * *code as f32)
+                .sum::<f32>()
+    }
+
+    pub fn score(&self, query: &QuantizedQuery, point_id: PointOffsetType) -> ScoreType {
+        match self.distance {
+            Distance::Cosine | Distance::Dot => self.dot(query, point_id),
+            Distance::Euclid => {
+                let dot = self.dot(query, point_id);
+                -(query.squared_norm - 2.0 * dot + self.squared_norms[point_id as usize])
+            }
+            Distance::Custom(custom) => custom.similarity(&query.vector, &self.restore(point_id)),
+        }
+    }
+
+    /// Scorer which uses quantized vectors for scoring and the storage to check deleted points
+    pub fn raw_scorer<'a>(
+        &'a self,
+        query: &[VectorElementType],
+        vector_storage: &'a VectorStorageSS,
+    ) -> Box<dyn RawScorer + 'a> {
+        Box::new(QuantizedRawScorer {
+            query: self.prepare_query(query),
+            quantized: self,
+            vector_storage,
+        })
+    }
+}
+
+pub struct QuantizedRawScorer<'a> {
+    query: QuantizedQuery,
+    quantized: &'a ScalarQuantizedVectors,
+    vector_storage: &'a VectorStorageSS,
+}
+
+impl RawScorer for QuantizedRawScorer<'_> {
+    fn score_points(&self, points: &[PointOffsetType], scores: &mut [ScoredPointOffset]) -> usize {
+        let mut size: usize = 0;
+        for point_id in points.iter().copied() {
+            if !self.check_point(point_id) {
+                continue;
+            }
+            scores[size] = ScoredPointOffset {
+                idx: point_id,
+                score: self.quantized.score(&self.query, point_id),
+            };
+
+            size += 1;
+            if size == scores.len() {
+                return size;
+            }
+        }
+        size
+    }
+
+    fn check_point(&self, point: PointOffsetType) -> bool {
+        // Points added after quantization are not scored
+        (point as usize) < self.quantized.len() && !self.vector_storage.is_deleted(point)
+    }
+
+    fn score_point(&self, point: PointOffsetType) -> ScoreType {
+        self.quantized.score(&self.query, point)
+    }
+
+    fn score_internal(&self, point_a: PointOffsetType, point_b: PointOffsetType) -> ScoreType {
+        let query = self
+            .quantized
+            .prepare_query(&self.quantized.restore(point_a));
+        self.quantized.score(&query, point_b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::common::rocksdb_wrapper::{open_db, DB_VECTOR_CF};
+    use crate::spaces::metric::Metric;
+    use crate::spaces::simple::{DotProductMetric, EuclidMetric};
+    use crate::types::ScalarType;
+    use crate::vector_storage::simple_vector_storage::open_simple_vector_storage;
+
+    fn check_quantized_scores(distance: Distance, similarity: fn(&[f32], &[f32]) -> f32) {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+        let dim = 32;
+        let storage =
+            open_simple_vector_storage(db, DB_VECTOR_CF, dir.path(), dim, distance, true).unwrap();
+        let mut borrowed_storage = storage.borrow_mut();
+
+        let mut rnd = StdRng::seed_from_u64(42);
+        let vectors: Vec<Vec<f32>> = (0..100)
+            .map(|_| (0..dim).map(|_| rnd.gen_range(-1.0..1.0)).collect())
+            .collect();
+        for vector in &vectors {
+            borrowed_storage.put_vector(vector.clone()).unwrap();
+        }
+        borrowed_storage.delete(10).unwrap();
+
+        let config = ScalarQuantizationConfig {
+            r#type: ScalarType::Int8,
+            quantile: None,
+        };
+        let quantized = ScalarQuantizedVectors::encode(&*borrowed_storage, &config);
+        assert_eq!(quantized.len(), vectors.len());
+
+        let path = ScalarQuantizedVectors::get_path(dir.path());
+        quantized.save(&path).unwrap();
+        let quantized = ScalarQuantizedVectors::load(&path).unwrap();
+
+        let query: Vec<f32> = (0..dim).map(|_| rnd.gen_range(-1.0..1.0)).collect();
+        let raw_scorer = quantized.raw_scorer(&query, &*borrowed_storage);
+        assert!(!raw_scorer.check_point(10));
+        assert!(!raw_scorer.check_point(vectors.len() as PointOffsetType));
+
+        // Each element is approximated within `alpha / 2`, which is less than 0.004 here
+        for (point_id, vector) in vectors.iter().enumerate() {
+            if point_id == 10 {
+                continue;
+            }
+            let expected = similarity(&query, vector);
+            let score = raw_scorer.score_point(point_id as PointOffsetType);
+            assert!(
+                (expected - score).abs() < 0.1,
+                "expected {expected}, got {score}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_quantized_dot_scores() {
+        check_quantized_scores(Distance::Dot, DotProductMetric::similarity);
+    }
+
+    #[test]
+    fn test_quantized_euclid_scores() {
+        check_quantized_scores(Distance::Euclid, EuclidMetric::similarity);
+    }
+
+    #[test]
+    fn test_quantile_clips_outliers() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+        let storage =
+            open_simple_vector_storage(db, DB_VECTOR_CF, dir.path(), 4, Distance::Dot, true)
+                .unwrap();
+        let mut borrowed_storage = storage.borrow_mut();
+        for i in 0..100 {
+            let value = i as f32 / 100.0;
+            borrowed_storage
+                .put_vector(vec![value, value, value, value])
+                .unwrap();
+        }
+        borrowed_storage
+            .put_vector(vec![1000.0, 1000.0, 1000.0, 1000.0])
+            .unwrap();
+
+        let config = ScalarQuantizationConfig {
+            r#type: ScalarType::Int8,
+            quantile: Some(0.95),
+        };
+        let quantized = ScalarQuantizedVectors::encode(&*borrowed_storage, &config);
+        assert!(quantized.offset >= 0.0);
+        assert!(quantized.offset + quantized.alpha * QUANTIZATION_LEVELS < 1.0);
+    }
+}
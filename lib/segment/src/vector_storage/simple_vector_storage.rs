@@ -1,10 +1,12 @@
+use std::fs::create_dir_all;
 use std::marker::PhantomData;
 use std::mem::size_of;
 use std::ops::Range;
+use std::path::Path;
 use std::sync::Arc;
 
 use atomic_refcell::AtomicRefCell;
-use bitvec::prelude::BitVec;
+use bitvec::prelude::BitSlice;
 use log::debug;
 use parking_lot::RwLock;
 use rocksdb::DB;
@@ -12,6 +14,7 @@ use serde::{Deserialize, Serialize};
 
 use super::chunked_vectors::ChunkedVectors;
 use super::vector_storage_base::VectorStorage;
+use crate::common::mmap_bitslice::MmapBitSlice;
 use crate::common::rocksdb_wrapper::DatabaseColumnWrapper;
 use crate::common::Flusher;
 use crate::data_types::vectors::VectorElementType;
@@ -20,6 +23,8 @@ use crate::spaces::metric::Metric;
 use crate::spaces::simple::{CosineMetric, DotProductMetric, EuclidMetric};
 use crate::spaces::tools::peek_top_largest_iterable;
 use crate::types::{Distance, PointOffsetType, ScoreType};
+use crate::vector_storage::common::DELETED_FLAGS_FILE;
+use crate::vector_storage::vector_norms::{apply_inverse_norm, InverseNorms};
 use crate::vector_storage::{RawScorer, ScoredPointOffset, VectorStorageSS};
 
 /// In-memory vector storage with on-update persistence using `store`
@@ -27,13 +32,17 @@ pub struct SimpleVectorStorage<TMetric: Metric> {
     dim: usize,
     metric: PhantomData<TMetric>,
     vectors: ChunkedVectors,
-    deleted: BitVec,
+    /// Deleted flags are kept out of RocksDB, so deletion doesn't rewrite the stored vector
+    deleted: MmapBitSlice,
     deleted_count: usize,
+    /// Present if Cosine vectors are stored without normalization
+    norms: Option<InverseNorms>,
     db_wrapper: DatabaseColumnWrapper,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 struct StoredRecord {
+    /// Only used to migrate storages, created before deleted flags were moved to `DELETED_FLAGS_FILE`
     pub deleted: bool,
     pub vector: Vec<VectorElementType>,
 }
@@ -41,7 +50,8 @@ struct StoredRecord {
 pub struct SimpleRawScorer<'a, TMetric: Metric> {
     pub query: Vec<VectorElementType>,
     pub vectors: &'a ChunkedVectors,
-    pub deleted: &'a BitVec,
+    pub deleted: &'a BitSlice<u8>,
+    pub norms: Option<&'a InverseNorms>,
     pub metric: PhantomData<TMetric>,
 }
 
@@ -56,9 +66,10 @@ where
                 continue;
             }
             let other_vector = self.vectors.get(point_id);
+            let score = TMetric::similarity(&self.query, other_vector);
             scores[size] = ScoredPointOffset {
                 idx: point_id,
-                score: TMetric::similarity(&self.query, other_vector),
+                score: apply_inverse_norm(self.norms, point_id, score),
             };
 
             size += 1;
@@ -75,25 +86,84 @@ where
 
     fn score_point(&self, point: PointOffsetType) -> ScoreType {
         let other_vector = self.vectors.get(point);
-        TMetric::similarity(&self.query, other_vector)
+        apply_inverse_norm(
+            self.norms,
+            point,
+            TMetric::similarity(&self.query, other_vector),
+        )
     }
 
     fn score_internal(&self, point_a: PointOffsetType, point_b: PointOffsetType) -> ScoreType {
         let vector_a = self.vectors.get(point_a);
         let vector_b = self.vectors.get(point_b);
-        TMetric::similarity(vector_a, vector_b)
+        let score = TMetric::similarity(vector_a, vector_b);
+        apply_inverse_norm(
+            self.norms,
+            point_b,
+            apply_inverse_norm(self.norms, point_a, score),
+        )
     }
 }
 
+/// Open in-memory vector storage.
+///
+/// Vectors are persisted in RocksDB, deleted flags - in a mem-mapped file under `path`.
+/// If `normalize` is false, Cosine vectors are stored as is and scored with cached norms.
 pub fn open_simple_vector_storage(
     database: Arc<RwLock<DB>>,
     database_column_name: &str,
+    path: &Path,
     dim: usize,
     distance: Distance,
+    normalize: bool,
 ) -> OperationResult<Arc<AtomicRefCell<VectorStorageSS>>> {
+    match distance {
+        Distance::Cosine => open_simple_vector_storage_with_metric::<CosineMetric>(
+            database,
+            database_column_name,
+            path,
+            dim,
+            normalize,
+        ),
+        Distance::Euclid => open_simple_vector_storage_with_metric::<EuclidMetric>(
+            database,
+            database_column_name,
+            path,
+            dim,
+            normalize,
+        ),
+        Distance::Dot => open_simple_vector_storage_with_metric::<DotProductMetric>(
+            database,
+            database_column_name,
+            path,
+            dim,
+            normalize,
+        ),
+        Distance::Custom(custom) => {
+            (custom.open_simple_storage)(database, database_column_name, path, dim, normalize)
+        }
+    }
+}
+
+pub(crate) fn open_simple_vector_storage_with_metric<TMetric>(
+    database: Arc<RwLock<DB>>,
+    database_column_name: &str,
+    path: &Path,
+    dim: usize,
+    normalize: bool,
+) -> OperationResult<Arc<AtomicRefCell<VectorStorageSS>>>
+where
+    TMetric: Metric + Send + Sync + 'static,
+{
+    create_dir_all(path)?;
+    let deleted_path = path.join(DELETED_FLAGS_FILE);
+    // Storages, created before the flags file was introduced, keep deleted flags in RocksDB
+    let migrate_deleted = !deleted_path.exists();
+
     let mut vectors = ChunkedVectors::new(dim);
-    let mut deleted = BitVec::new();
-    let mut deleted_count = 0;
+    let mut deleted = MmapBitSlice::open(&deleted_path)?;
+    let mut norms =
+        (TMetric::distance() == Distance::Cosine && !normalize).then(InverseNorms::default);
 
     let db_wrapper = DatabaseColumnWrapper::new(database, database_column_name);
     for (key, value) in db_wrapper.lock_db().iter()? {
@@ -101,17 +171,17 @@ pub fn open_simple_vector_storage(
             .map_err(|_| OperationError::service_error("cannot deserialize point id from db"))?;
         let stored_record: StoredRecord = bincode::deserialize(&value)
             .map_err(|_| OperationError::service_error("cannot deserialize record from db"))?;
-        if stored_record.deleted {
-            deleted_count += 1;
+        if migrate_deleted && stored_record.deleted {
+            deleted.set(point_id as usize, true)?;
         }
 
-        while deleted.len() <= (point_id as usize) {
-            deleted.push(false);
-        }
-
-        deleted.set(point_id as usize, stored_record.deleted);
         vectors.insert(point_id, &stored_record.vector);
+        if let Some(norms) = &mut norms {
+            norms.set(point_id, &stored_record.vector);
+        }
     }
+    deleted.ensure_len(vectors.len())?;
+    let deleted_count = deleted[..vectors.len()].count_ones();
 
     debug!("Segment vectors: {}", vectors.len());
     debug!(
@@ -119,46 +189,28 @@ pub fn open_simple_vector_storage(
         vectors.len() * dim * size_of::<VectorElementType>() / 1024 / 1024
     );
 
-    match distance {
-        Distance::Cosine => Ok(Arc::new(AtomicRefCell::new(SimpleVectorStorage::<
-            CosineMetric,
-        > {
-            dim,
-            metric: PhantomData,
-            vectors,
-            deleted,
-            deleted_count,
-            db_wrapper,
-        }))),
-        Distance::Euclid => Ok(Arc::new(AtomicRefCell::new(SimpleVectorStorage::<
-            EuclidMetric,
-        > {
-            dim,
-            metric: PhantomData,
-            vectors,
-            deleted,
-            deleted_count,
-            db_wrapper,
-        }))),
-        Distance::Dot => Ok(Arc::new(AtomicRefCell::new(SimpleVectorStorage::<
-            DotProductMetric,
-        > {
+    Ok(Arc::new(AtomicRefCell::new(
+        SimpleVectorStorage::<TMetric> {
             dim,
             metric: PhantomData,
             vectors,
             deleted,
             deleted_count,
+            norms,
             db_wrapper,
-        }))),
-    }
+        },
+    )))
 }
 
 impl<TMetric> SimpleVectorStorage<TMetric>
 where
     TMetric: Metric,
 {
-    fn update_stored(&self, point_id: PointOffsetType) -> OperationResult<()> {
+    fn update_stored(&mut self, point_id: PointOffsetType) -> OperationResult<()> {
         let v = self.vectors.get(point_id);
+        if let Some(norms) = &mut self.norms {
+            norms.set(point_id, v);
+        }
 
         let record = StoredRecord {
             deleted: self.deleted[point_id as usize],
@@ -182,6 +234,10 @@ where
         self.dim
     }
 
+    fn distance(&self) -> Distance {
+        TMetric::distance()
+    }
+
     fn vector_count(&self) -> usize {
         self.vectors.len() - self.deleted_count
     }
@@ -195,7 +251,7 @@ where
     }
 
     fn get_vector(&self, key: PointOffsetType) -> Option<Vec<VectorElementType>> {
-        if self.deleted.get(key as usize).map(|x| *x).unwrap_or(true) {
+        if (key as usize) >= self.vectors.len() || self.deleted[key as usize] {
             return None;
         }
         Some(self.vectors.get(key).to_vec())
@@ -204,7 +260,7 @@ where
     fn put_vector(&mut self, vector: Vec<VectorElementType>) -> OperationResult<PointOffsetType> {
         assert_eq!(self.dim, vector.len());
         let new_id = self.vectors.push(&vector);
-        self.deleted.push(false);
+        self.deleted.set(new_id as usize, false)?;
         self.update_stored(new_id)?;
         Ok(new_id)
     }
@@ -215,10 +271,7 @@ where
         vector: Vec<VectorElementType>,
     ) -> OperationResult<PointOffsetType> {
         self.vectors.insert(key, &vector);
-        if self.deleted.len() <= (key as usize) {
-            self.deleted.resize(key as usize + 1, false);
-        }
-        self.deleted.set(key as usize, false);
+        self.deleted.set(key as usize, false)?;
         self.update_stored(key)?;
         Ok(key)
     }
@@ -228,8 +281,8 @@ where
         for point_id in other.iter_ids() {
             let other_vector = other.get_vector(point_id).unwrap();
             // Do not perform preprocessing - vectors should be already processed
-            self.deleted.push(false);
             let new_id = self.vectors.push(&other_vector);
+            self.deleted.set(new_id as usize, false)?;
             self.update_stored(new_id)?;
         }
         let end_index = self.vectors.len() as PointOffsetType;
@@ -237,15 +290,13 @@ where
     }
 
     fn delete(&mut self, key: PointOffsetType) -> OperationResult<()> {
-        if (key as usize) >= self.deleted.len() {
+        if (key as usize) >= self.vectors.len() {
             return Ok(());
         }
         if !self.deleted[key as usize] {
             self.deleted_count += 1;
         }
-        self.deleted.set(key as usize, true);
-        self.update_stored(key)?;
-        Ok(())
+        self.deleted.set(key as usize, true)
     }
 
     fn is_deleted(&self, key: PointOffsetType) -> bool {
@@ -259,7 +310,12 @@ where
     }
 
     fn flusher(&self) -> Flusher {
-        self.db_wrapper.flusher()
+        let deleted_flusher = self.deleted.flusher();
+        let db_flusher = self.db_wrapper.flusher();
+        Box::new(move || {
+            deleted_flusher()?;
+            db_flusher()
+        })
     }
 
     fn raw_scorer(&self, vector: Vec<VectorElementType>) -> Box<dyn RawScorer + '_> {
@@ -267,15 +323,23 @@ where
             query: TMetric::preprocess(&vector).unwrap_or(vector),
             vectors: &self.vectors,
             deleted: &self.deleted,
+            norms: self.norms.as_ref(),
             metric: PhantomData,
         })
     }
 
     fn raw_scorer_internal(&self, point_id: PointOffsetType) -> Box<dyn RawScorer + '_> {
+        let vector = self.vectors.get(point_id).to_vec();
+        // Stored vector is not normalized, if norms are cached
+        let query = match self.norms {
+            Some(_) => TMetric::preprocess(&vector).unwrap_or(vector),
+            None => vector,
+        };
         Box::new(SimpleRawScorer::<TMetric> {
-            query: self.vectors.get(point_id).to_vec(),
+            query,
             vectors: &self.vectors,
             deleted: &self.deleted,
+            norms: self.norms.as_ref(),
             metric: PhantomData,
         })
     }
@@ -291,9 +355,10 @@ where
             .filter(|point_id| !self.deleted[*point_id as usize])
             .map(|point_id| {
                 let other_vector = self.vectors.get(point_id);
+                let score = TMetric::similarity(&preprocessed_vector, other_vector);
                 ScoredPointOffset {
                     idx: point_id,
-                    score: TMetric::similarity(&preprocessed_vector, other_vector),
+                    score: apply_inverse_norm(self.norms.as_ref(), point_id, score),
                 }
             });
         peek_top_largest_iterable(scores, top)
@@ -307,9 +372,10 @@ where
             .map(|point_id| {
                 let point_id = point_id as PointOffsetType;
                 let other_vector = &self.vectors.get(point_id);
+                let score = TMetric::similarity(&preprocessed_vector, other_vector);
                 ScoredPointOffset {
                     idx: point_id,
-                    score: TMetric::similarity(&preprocessed_vector, other_vector),
+                    score: apply_inverse_norm(self.norms.as_ref(), point_id, score),
                 }
             });
         peek_top_largest_iterable(scores, top)
@@ -339,7 +405,8 @@ mod tests {
         let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
         let distance = Distance::Dot;
         let dim = 4;
-        let storage = open_simple_vector_storage(db, DB_VECTOR_CF, dim, distance).unwrap();
+        let storage =
+            open_simple_vector_storage(db, DB_VECTOR_CF, dir.path(), dim, distance, true).unwrap();
         let mut borrowed_storage = storage.borrow_mut();
 
         let vec0 = vec![1.0, 0.0, 1.0, 1.0];
@@ -407,4 +474,99 @@ mod tests {
 
         assert!(!all_ids1.contains(&top_idx))
     }
+
+    #[test]
+    fn test_cosine_without_normalization() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+        let storage = open_simple_vector_storage(
+            db.clone(),
+            DB_VECTOR_CF,
+            dir.path(),
+            2,
+            Distance::Cosine,
+            false,
+        )
+        .unwrap();
+        {
+            let mut borrowed_storage = storage.borrow_mut();
+            borrowed_storage.put_vector(vec![3.0, 4.0]).unwrap();
+            borrowed_storage.put_vector(vec![0.0, 10.0]).unwrap();
+            borrowed_storage.put_vector(vec![0.0, 0.0]).unwrap();
+
+            // Original vectors are preserved
+            assert_eq!(borrowed_storage.get_vector(0).unwrap(), vec![3.0, 4.0]);
+
+            let raw_scorer = borrowed_storage.raw_scorer(vec![0.0, 2.0]);
+            assert!((raw_scorer.score_point(0) - 0.8).abs() < 1e-6);
+            assert!((raw_scorer.score_point(1) - 1.0).abs() < 1e-6);
+            assert_eq!(raw_scorer.score_point(2), 0.0);
+            assert!((raw_scorer.score_internal(0, 1) - 0.8).abs() < 1e-6);
+
+            let top = borrowed_storage.score_all(&[0.0, 2.0], 1);
+            assert_eq!(top[0].idx, 1);
+        }
+        drop(storage);
+
+        // Norms are restored on load
+        let storage =
+            open_simple_vector_storage(db, DB_VECTOR_CF, dir.path(), 2, Distance::Cosine, false)
+                .unwrap();
+        let borrowed_storage = storage.borrow();
+        let raw_scorer = borrowed_storage.raw_scorer_internal(1);
+        assert!((raw_scorer.score_point(0) - 0.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_deleted_flags_persistence() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+        let open_storage = || {
+            open_simple_vector_storage(db.clone(), DB_VECTOR_CF, dir.path(), 2, Distance::Dot, true)
+                .unwrap()
+        };
+
+        let storage = open_storage();
+        {
+            let mut borrowed_storage = storage.borrow_mut();
+            for idx in 0..10 {
+                borrowed_storage.put_vector(vec![idx as f32, 1.0]).unwrap();
+            }
+            borrowed_storage.delete(3).unwrap();
+            borrowed_storage.delete(7).unwrap();
+            borrowed_storage.flusher()().unwrap();
+        }
+        drop(storage);
+
+        let storage = open_storage();
+        {
+            let borrowed_storage = storage.borrow();
+            assert_eq!(borrowed_storage.deleted_count(), 2);
+            assert!(borrowed_storage.is_deleted(3));
+            assert!(borrowed_storage.get_vector(7).is_none());
+            assert_eq!(borrowed_storage.iter_ids().count(), 8);
+        }
+        drop(storage);
+
+        // Storage without flags file takes deleted flags from RocksDB records
+        std::fs::remove_file(dir.path().join(DELETED_FLAGS_FILE)).unwrap();
+        let db_wrapper = DatabaseColumnWrapper::new(db.clone(), DB_VECTOR_CF);
+        let record = StoredRecord {
+            deleted: true,
+            vector: vec![5.0, 1.0],
+        };
+        let point_id: PointOffsetType = 5;
+        db_wrapper
+            .put(
+                &bincode::serialize(&point_id).unwrap(),
+                &bincode::serialize(&record).unwrap(),
+            )
+            .unwrap();
+
+        let storage = open_storage();
+        let borrowed_storage = storage.borrow();
+        assert_eq!(borrowed_storage.deleted_count(), 1);
+        assert!(borrowed_storage.is_deleted(5));
+        assert!(!borrowed_storage.is_deleted(3));
+    }
 }
@@ -0,0 +1,87 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use crate::data_types::vectors::VectorElementType;
+use crate::entry::entry_point::OperationResult;
+use crate::spaces::simple::dot_similarity;
+use crate::types::{PointOffsetType, ScoreType};
+
+/// Inverse norms of vectors, stored without normalization.
+///
+/// Allows to score Cosine distance of original vectors without computing their norms on each
+/// comparison: `cos(q, v) = dot(q, v) / |v|` for normalized query `q`.
+#[derive(Debug, Default, Clone)]
+pub struct InverseNorms {
+    values: Vec<ScoreType>,
+}
+
+impl InverseNorms {
+    /// Zero vectors have zero inverse norm, so their score is always zero
+    pub fn compute(vector: &[VectorElementType]) -> ScoreType {
+        let norm = dot_similarity(vector, vector).sqrt();
+        if norm > 0.0 {
+            1.0 / norm
+        } else {
+            0.0
+        }
+    }
+
+    pub fn set(&mut self, key: PointOffsetType, vector: &[VectorElementType]) {
+        let key = key as usize;
+        if self.values.len() <= key {
+            self.values.resize(key + 1, 0.0);
+        }
+        self.values[key] = Self::compute(vector);
+    }
+
+    pub fn push(&mut self, vector: &[VectorElementType]) {
+        self.values.push(Self::compute(vector));
+    }
+
+    pub fn get(&self, key: PointOffsetType) -> ScoreType {
+        self.values[key as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Read norms, previously written with `append_to_file`
+    pub fn load(path: &Path) -> OperationResult<Self> {
+        let mut bytes = vec![];
+        File::open(path)?.read_to_end(&mut bytes)?;
+        let values = bytes
+            .chunks_exact(std::mem::size_of::<ScoreType>())
+            .map(|chunk| ScoreType::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        Ok(Self { values })
+    }
+
+    /// Append inverse norms, computed with `compute`, to the end of the file
+    pub fn append_to_file(path: &Path, inverse_norms: &[ScoreType]) -> OperationResult<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        for inverse_norm in inverse_norms {
+            file.write_all(&inverse_norm.to_le_bytes())?;
+        }
+        file.flush()?;
+        Ok(())
+    }
+}
+
+/// Correct similarity of a stored vector, if vectors are stored without normalization
+#[inline]
+pub fn apply_inverse_norm(
+    norms: Option<&InverseNorms>,
+    point: PointOffsetType,
+    score: ScoreType,
+) -> ScoreType {
+    match norms {
+        None => score,
+        Some(norms) => score * norms.get(point),
+    }
+}
@@ -7,7 +7,7 @@ use rand::Rng;
 use crate::common::Flusher;
 use crate::data_types::vectors::VectorElementType;
 use crate::entry::entry_point::OperationResult;
-use crate::types::{PointOffsetType, ScoreType};
+use crate::types::{Distance, PointOffsetType, ScoreType};
 
 #[derive(Copy, Clone, PartialEq, Debug, Default)]
 pub struct ScoredPointOffset {
@@ -49,6 +49,7 @@ pub trait RawScorer {
 /// Storage operates with internal IDs (`PointOffsetType`), which always starts with zero and have no skips
 pub trait VectorStorage {
     fn vector_dim(&self) -> usize;
+    fn distance(&self) -> Distance;
     fn vector_count(&self) -> usize;
     /// Number of searchable vectors (not deleted)
     fn deleted_count(&self) -> usize;
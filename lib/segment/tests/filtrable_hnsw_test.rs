@@ -12,8 +12,9 @@ mod tests {
     use segment::index::{PayloadIndex, VectorIndex};
     use segment::segment_constructor::build_segment;
     use segment::types::{
-        Condition, Distance, FieldCondition, Filter, HnswConfig, Indexes, Payload,
-        PayloadSchemaType, PointOffsetType, Range, SearchParams, SegmentConfig, SeqNumberType,
+        BinaryQuantizationConfig, Condition, Distance, FieldCondition, Filter, HnswConfig, Indexes,
+        Payload, PayloadSchemaType, PointOffsetType, QuantizationConfig, QuantizationSearchParams,
+        Range, ScalarQuantizationConfig, ScalarType, SearchParams, SegmentConfig, SeqNumberType,
         StorageType, VectorDataConfig,
     };
     use serde_json::json;
@@ -44,8 +45,14 @@ mod tests {
                 VectorDataConfig {
                     size: dim,
                     distance,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
                 },
             )]),
+            sparse_vector_data: Default::default(),
             index: Indexes::Plain {},
             storage_type: StorageType::InMemory,
             payload_storage_type: Default::default(),
@@ -77,6 +84,9 @@ mod tests {
             ef_construct,
             full_scan_threshold,
             max_indexing_threads: 2,
+            on_disk: None,
+            payload_m: None,
+            payload_block_threshold: None,
         };
 
         let mut hnsw_index = HNSWIndex::open(
@@ -86,6 +96,7 @@ mod tests {
                 .clone(),
             payload_index_ptr.clone(),
             hnsw_config,
+            None,
         )
         .unwrap();
 
@@ -158,7 +169,11 @@ mod tests {
                 &query,
                 filter_query,
                 top,
-                Some(&SearchParams { hnsw_ef: Some(ef) }),
+                Some(&SearchParams {
+                    hnsw_ef: Some(ef),
+                    quantization: None,
+                    indexed_only: false,
+                }),
             );
 
             let plain_result = segment.vector_data[DEFAULT_VECTOR_NAME]
@@ -173,4 +188,140 @@ mod tests {
         assert!(attempts - hits < 5, "hits: {} of {}", hits, attempts); // Not more than 5% failures
         eprintln!("hits = {:#?} out of {}", hits, attempts);
     }
+
+    fn check_quantized_hnsw(
+        quantization_config: QuantizationConfig,
+        dim: usize,
+        ef: usize,
+        min_recall: f64,
+    ) {
+        let stopped = AtomicBool::new(false);
+
+        let num_vectors: u64 = 2_000;
+        let top = 10;
+
+        let mut rnd = thread_rng();
+        // Elements are centered around zero, like in real embeddings
+        let mut centered_vector =
+            || -> Vec<f32> { (0..dim).map(|_| rnd.gen_range(-1.0..1.0)).collect() };
+
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let hnsw_dir = Builder::new().prefix("hnsw_dir").tempdir().unwrap();
+
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                DEFAULT_VECTOR_NAME.to_owned(),
+                VectorDataConfig {
+                    size: dim,
+                    distance: Distance::Cosine,
+                    quantization_config: Some(quantization_config),
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
+                },
+            )]),
+            sparse_vector_data: Default::default(),
+            index: Indexes::Plain {},
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+        };
+
+        let mut segment = build_segment(dir.path(), &config).unwrap();
+        for n in 0..num_vectors {
+            let vector = centered_vector();
+            segment
+                .upsert_vector(n as SeqNumberType, n.into(), &only_default_vector(&vector))
+                .unwrap();
+        }
+
+        let hnsw_config = HnswConfig {
+            m: 16,
+            ef_construct: 64,
+            full_scan_threshold: 16,
+            max_indexing_threads: 2,
+            on_disk: None,
+            payload_m: None,
+            payload_block_threshold: None,
+        };
+        let vector_storage = segment.vector_data[DEFAULT_VECTOR_NAME]
+            .vector_storage
+            .clone();
+
+        let mut hnsw_index = HNSWIndex::open(
+            hnsw_dir.path(),
+            vector_storage.clone(),
+            segment.payload_index.clone(),
+            hnsw_config,
+            Some(quantization_config),
+        )
+        .unwrap();
+        hnsw_index.build_index(&stopped).unwrap();
+
+        // Quantized vectors are saved along with the graph
+        let hnsw_index = HNSWIndex::open(
+            hnsw_dir.path(),
+            vector_storage,
+            segment.payload_index.clone(),
+            hnsw_config,
+            Some(quantization_config),
+        )
+        .unwrap();
+
+        let search_params = SearchParams {
+            hnsw_ef: Some(ef),
+            quantization: Some(QuantizationSearchParams {
+                ignore: false,
+                // Binary quantized vectors are re-scored anyway
+                rescore: matches!(quantization_config, QuantizationConfig::Scalar(_)),
+                oversampling: None,
+            }),
+            indexed_only: false,
+        };
+
+        let attempts = 20;
+        let mut found = 0;
+        for _ in 0..attempts {
+            let query = centered_vector();
+            let index_result =
+                hnsw_index.search_with_graph(&query, None, top, Some(&search_params));
+            let plain_result = segment.vector_data[DEFAULT_VECTOR_NAME]
+                .vector_index
+                .borrow()
+                .search(&[&query], None, top, None);
+
+            assert_eq!(index_result.len(), top);
+            let plain_ids = plain_result[0].iter().map(|point| point.idx).collect_vec();
+            found += index_result
+                .iter()
+                .filter(|point| plain_ids.contains(&point.idx))
+                .count();
+        }
+        let recall = found as f64 / (attempts * top) as f64;
+        assert!(recall > min_recall, "recall: {}", recall);
+    }
+
+    #[test]
+    fn test_scalar_quantized_hnsw() {
+        check_quantized_hnsw(
+            QuantizationConfig::Scalar(ScalarQuantizationConfig {
+                r#type: ScalarType::Int8,
+                quantile: Some(0.99),
+            }),
+            16,
+            64,
+            0.8,
+        );
+    }
+
+    #[test]
+    fn test_binary_quantized_hnsw() {
+        // Binary codes are only meaningful for high-dimensional vectors
+        check_quantized_hnsw(
+            QuantizationConfig::Binary(BinaryQuantizationConfig {}),
+            256,
+            512,
+            0.6,
+        );
+    }
 }
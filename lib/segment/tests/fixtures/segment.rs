@@ -117,6 +117,11 @@ pub fn build_segment_3(path: &Path) -> Segment {
                     VectorDataConfig {
                         size: 4,
                         distance: Distance::Dot,
+                        quantization_config: None,
+                        on_disk: None,
+                        multivector_config: None,
+                        hnsw_config: None,
+                        normalize: None,
                     },
                 ),
                 (
@@ -124,6 +129,11 @@ pub fn build_segment_3(path: &Path) -> Segment {
                     VectorDataConfig {
                         size: 1,
                         distance: Distance::Dot,
+                        quantization_config: None,
+                        on_disk: None,
+                        multivector_config: None,
+                        hnsw_config: None,
+                        normalize: None,
                     },
                 ),
                 (
@@ -131,9 +141,15 @@ pub fn build_segment_3(path: &Path) -> Segment {
                     VectorDataConfig {
                         size: 4,
                         distance: Distance::Euclid,
+                        quantization_config: None,
+                        on_disk: None,
+                        multivector_config: None,
+                        hnsw_config: None,
+                        normalize: None,
                     },
                 ),
             ]),
+            sparse_vector_data: Default::default(),
             index: Indexes::Plain {},
             storage_type: Default::default(),
             payload_storage_type: Default::default(),
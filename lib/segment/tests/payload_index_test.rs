@@ -6,6 +6,8 @@ mod tests {
     use itertools::Itertools;
     use rand::prelude::StdRng;
     use rand::{Rng, SeedableRng};
+    use segment::data_types::composite_index::{CompositeIndexParams, CompositeIndexType};
+    use segment::data_types::numeric_index::{NumericIndexParams, NumericIndexType};
     use segment::data_types::vectors::{only_default_vector, DEFAULT_VECTOR_NAME};
     use segment::entry::entry_point::SegmentEntry;
     use segment::fixtures::payload_fixtures::{
@@ -14,12 +16,14 @@ mod tests {
     };
     use segment::index::PayloadIndex;
     use segment::segment::Segment;
-    use segment::segment_constructor::build_segment;
+    use segment::segment_constructor::{build_segment, load_segment};
     use segment::types::{
         Condition, Distance, FieldCondition, Filter, GeoPoint, GeoRadius, Indexes,
-        IsEmptyCondition, Payload, PayloadField, PayloadSchemaType, Range, SegmentConfig,
+        IsEmptyCondition, IsNullCondition, Match, MatchText, Payload, PayloadField,
+        PayloadFieldSchema, PayloadSchemaParams, PayloadSchemaType, Range, SegmentConfig,
         StorageType, VectorDataConfig, WithPayload,
     };
+    use serde_json::json;
     use tempfile::Builder;
 
     fn build_test_segments(path_struct: &Path, path_plain: &Path) -> (Segment, Segment) {
@@ -32,8 +36,14 @@ mod tests {
                 VectorDataConfig {
                     size: dim,
                     distance: Distance::Dot,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
                 },
             )]),
+            sparse_vector_data: Default::default(),
             index: Indexes::Plain {},
             storage_type: StorageType::InMemory,
             payload_storage_type: Default::default(),
@@ -384,4 +394,649 @@ mod tests {
                 });
         }
     }
+
+    #[test]
+    fn test_principal_range_skip() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let dim = 5;
+        let mut rnd = StdRng::seed_from_u64(42);
+
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                DEFAULT_VECTOR_NAME.to_owned(),
+                VectorDataConfig {
+                    size: dim,
+                    distance: Distance::Dot,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
+                },
+            )]),
+            sparse_vector_data: Default::default(),
+            index: Indexes::Plain {},
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+        };
+        let mut segment = build_segment(dir.path(), &config).unwrap();
+
+        let principal_schema =
+            PayloadFieldSchema::FieldParams(PayloadSchemaParams::Numeric(NumericIndexParams {
+                r#type: NumericIndexType::Integer,
+                on_disk: None,
+                is_principal: Some(true),
+            }));
+        segment
+            .create_field_index(0, "timestamp", Some(&principal_schema))
+            .unwrap();
+
+        for n in 0..100u64 {
+            let payload: Payload = json!({ "timestamp": 1000 + n }).into();
+            segment
+                .upsert_vector(
+                    n + 1,
+                    n.into(),
+                    &only_default_vector(&random_vector(&mut rnd, dim)),
+                )
+                .unwrap();
+            segment.set_full_payload(n + 1, n.into(), &payload).unwrap();
+        }
+
+        assert_eq!(
+            segment.principal_range(),
+            Some(("timestamp".to_string(), (1000.0, 1099.0)))
+        );
+
+        let range_filter = |gte: f64, lt: f64| {
+            Filter::new_must(Condition::Field(FieldCondition::new_range(
+                "timestamp".to_string(),
+                Range {
+                    lt: Some(lt),
+                    gt: None,
+                    gte: Some(gte),
+                    lte: None,
+                },
+            )))
+        };
+
+        let outside_filter = range_filter(2000.0, 3000.0);
+        assert!(segment
+            .payload_index
+            .borrow()
+            .is_out_of_principal_range(&outside_filter));
+        assert_eq!(segment.estimate_points_count(Some(&outside_filter)).max, 0);
+        assert!(segment
+            .read_filtered(None, None, Some(&outside_filter))
+            .is_empty());
+
+        let inside_filter = range_filter(1090.0, 3000.0);
+        assert!(!segment
+            .payload_index
+            .borrow()
+            .is_out_of_principal_range(&inside_filter));
+        let result = segment
+            .search(
+                DEFAULT_VECTOR_NAME,
+                &random_vector(&mut rnd, dim),
+                &WithPayload::default(),
+                &false.into(),
+                Some(&inside_filter),
+                100,
+                None,
+            )
+            .unwrap();
+        assert_eq!(result.len(), 10);
+    }
+
+    #[test]
+    fn test_null_index_conditions() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let dim = 5;
+        let mut rnd = StdRng::seed_from_u64(42);
+
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                DEFAULT_VECTOR_NAME.to_owned(),
+                VectorDataConfig {
+                    size: dim,
+                    distance: Distance::Dot,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
+                },
+            )]),
+            sparse_vector_data: Default::default(),
+            index: Indexes::Plain {},
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+        };
+        let mut segment = build_segment(dir.path(), &config).unwrap();
+        segment
+            .create_field_index(0, "color", Some(&PayloadSchemaType::Keyword.into()))
+            .unwrap();
+
+        let num_points = 100u64;
+        for n in 0..num_points {
+            let payload: Payload = match n % 4 {
+                0 => json!({ "color": "red" }),
+                1 => json!({ "color": null }),
+                2 => json!({ "color": [] }),
+                _ => json!({ "size": 10 }),
+            }
+            .into();
+            segment
+                .upsert_vector(
+                    n + 1,
+                    n.into(),
+                    &only_default_vector(&random_vector(&mut rnd, dim)),
+                )
+                .unwrap();
+            segment.set_full_payload(n + 1, n.into(), &payload).unwrap();
+        }
+
+        let is_null = Condition::IsNull(IsNullCondition {
+            is_null: PayloadField {
+                key: "color".to_string(),
+            },
+        });
+        let is_empty = Condition::IsEmpty(IsEmptyCondition {
+            is_empty: PayloadField {
+                key: "color".to_string(),
+            },
+        });
+
+        let null_filter = Filter::new_must(is_null);
+        let empty_filter = Filter::new_must(is_empty.clone());
+        let not_empty_filter = Filter::new_must_not(is_empty);
+
+        let null_estimation = segment.estimate_points_count(Some(&null_filter));
+        assert_eq!(null_estimation.min, 25);
+        assert_eq!(null_estimation.max, 25);
+        let empty_estimation = segment.estimate_points_count(Some(&empty_filter));
+        assert_eq!(empty_estimation.min, 75);
+        assert_eq!(empty_estimation.max, 75);
+
+        assert_eq!(
+            segment.read_filtered(None, None, Some(&null_filter)).len(),
+            25
+        );
+        assert_eq!(
+            segment.read_filtered(None, None, Some(&empty_filter)).len(),
+            75
+        );
+        assert_eq!(
+            segment
+                .read_filtered(None, None, Some(&not_empty_filter))
+                .len(),
+            25
+        );
+
+        // Payload updates are reflected in the index
+        segment
+            .set_payload(num_points + 1, 1.into(), &json!({ "color": "blue" }).into())
+            .unwrap();
+        segment.delete_point(num_points + 2, 5.into()).unwrap();
+        assert_eq!(
+            segment.read_filtered(None, None, Some(&null_filter)).len(),
+            23
+        );
+        assert_eq!(
+            segment.read_filtered(None, None, Some(&empty_filter)).len(),
+            73
+        );
+        assert_eq!(
+            segment
+                .read_filtered(None, None, Some(&not_empty_filter))
+                .len(),
+            26
+        );
+    }
+
+    #[test]
+    fn test_composite_index_conditions() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let dim = 5;
+        let mut rnd = StdRng::seed_from_u64(42);
+
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                DEFAULT_VECTOR_NAME.to_owned(),
+                VectorDataConfig {
+                    size: dim,
+                    distance: Distance::Dot,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
+                },
+            )]),
+            sparse_vector_data: Default::default(),
+            index: Indexes::Plain {},
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+        };
+        let mut segment = build_segment(dir.path(), &config).unwrap();
+
+        let num_points = 100u64;
+        for n in 0..num_points {
+            let payload: Payload = json!({
+                "tenant": format!("tenant_{}", n % 5),
+                "status": n % 4,
+            })
+            .into();
+            segment
+                .upsert_vector(
+                    n + 1,
+                    n.into(),
+                    &only_default_vector(&random_vector(&mut rnd, dim)),
+                )
+                .unwrap();
+            segment.set_full_payload(n + 1, n.into(), &payload).unwrap();
+        }
+
+        let composite_schema =
+            PayloadFieldSchema::FieldParams(PayloadSchemaParams::Composite(CompositeIndexParams {
+                r#type: CompositeIndexType::Composite,
+                keys: vec!["tenant".to_string(), "status".to_string()],
+            }));
+        segment
+            .create_field_index(num_points + 1, "tenant_status", Some(&composite_schema))
+            .unwrap();
+
+        let filter = Filter {
+            should: None,
+            must: Some(vec![
+                Condition::Field(FieldCondition::new_match(
+                    "status".to_string(),
+                    1_i64.into(),
+                )),
+                Condition::Field(FieldCondition::new_match(
+                    "tenant".to_string(),
+                    "tenant_1".to_string().into(),
+                )),
+            ]),
+            must_not: None,
+        };
+
+        // Points with n % 20 == 1
+        let estimation = segment.estimate_points_count(Some(&filter));
+        assert_eq!(estimation.min, 5);
+        assert_eq!(estimation.max, 5);
+        assert_eq!(segment.read_filtered(None, None, Some(&filter)).len(), 5);
+
+        // Conditions, not covered by the index, are still applied
+        let mut narrow_filter = filter.clone();
+        narrow_filter
+            .must
+            .as_mut()
+            .unwrap()
+            .push(Condition::Field(FieldCondition::new_range(
+                "status".to_string(),
+                Range {
+                    lt: None,
+                    gt: None,
+                    gte: None,
+                    lte: Some(0.0),
+                },
+            )));
+        assert!(segment
+            .read_filtered(None, None, Some(&narrow_filter))
+            .is_empty());
+
+        // Payload updates are reflected in the index
+        segment
+            .set_payload(num_points + 2, 1.into(), &json!({ "status": 2 }).into())
+            .unwrap();
+        segment.delete_point(num_points + 3, 21.into()).unwrap();
+        assert_eq!(segment.read_filtered(None, None, Some(&filter)).len(), 3);
+        assert_eq!(segment.estimate_points_count(Some(&filter)).max, 3);
+    }
+
+    #[test]
+    fn test_rebuild_outdated_index() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let dim = 5;
+        let mut rnd = StdRng::seed_from_u64(42);
+
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                DEFAULT_VECTOR_NAME.to_owned(),
+                VectorDataConfig {
+                    size: dim,
+                    distance: Distance::Dot,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
+                },
+            )]),
+            sparse_vector_data: Default::default(),
+            index: Indexes::Plain {},
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+        };
+        let mut segment = build_segment(dir.path(), &config).unwrap();
+        segment
+            .create_field_index(0, "color", Some(&PayloadSchemaType::Keyword.into()))
+            .unwrap();
+
+        let num_points = 20u64;
+        for n in 0..num_points {
+            let color = if n % 2 == 0 { "red" } else { "blue" };
+            segment
+                .upsert_vector(
+                    n + 1,
+                    n.into(),
+                    &only_default_vector(&random_vector(&mut rnd, dim)),
+                )
+                .unwrap();
+            segment
+                .set_full_payload(n + 1, n.into(), &json!({ "color": color }).into())
+                .unwrap();
+        }
+        segment.flush(true).unwrap();
+        let segment_path = segment.current_path.clone();
+        drop(segment);
+
+        // Simulate index, built before format versions were introduced
+        let config_path = segment_path.join("payload_index").join("config.json");
+        let mut index_config: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert!(index_config["index_versions"]["color"].is_u64());
+        index_config
+            .as_object_mut()
+            .unwrap()
+            .remove("index_versions");
+        std::fs::write(&config_path, index_config.to_string()).unwrap();
+
+        let segment = load_segment(&segment_path).unwrap().unwrap();
+        let filter = Filter::new_must(Condition::Field(FieldCondition::new_match(
+            "color".to_string(),
+            "red".to_string().into(),
+        )));
+        let estimation = segment.estimate_points_count(Some(&filter));
+        assert_eq!(estimation.min, 10);
+        assert_eq!(estimation.max, 10);
+        assert_eq!(segment.read_filtered(None, None, Some(&filter)).len(), 10);
+
+        // Version of the rebuilt index is recorded
+        let index_config: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert!(index_config["index_versions"]["color"].is_u64());
+    }
+
+    #[test]
+    fn test_rebuild_field_index() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let dim = 5;
+        let mut rnd = StdRng::seed_from_u64(42);
+
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                DEFAULT_VECTOR_NAME.to_owned(),
+                VectorDataConfig {
+                    size: dim,
+                    distance: Distance::Dot,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
+                },
+            )]),
+            sparse_vector_data: Default::default(),
+            index: Indexes::Plain {},
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+        };
+        let mut segment = build_segment(dir.path(), &config).unwrap();
+
+        let num_points = 20u64;
+        for n in 0..num_points {
+            let color = if n % 2 == 0 { "red" } else { "blue" };
+            segment
+                .upsert_vector(
+                    n + 1,
+                    n.into(),
+                    &only_default_vector(&random_vector(&mut rnd, dim)),
+                )
+                .unwrap();
+            segment
+                .set_full_payload(n + 1, n.into(), &json!({ "color": color }).into())
+                .unwrap();
+        }
+        segment
+            .create_field_index(
+                num_points + 1,
+                "color",
+                Some(&PayloadSchemaType::Keyword.into()),
+            )
+            .unwrap();
+
+        // Rebuild with the current schema
+        assert!(segment
+            .rebuild_field_index(num_points + 2, "color", None)
+            .unwrap());
+        assert!(matches!(
+            segment.get_indexed_fields().get("color"),
+            Some(PayloadFieldSchema::FieldType(PayloadSchemaType::Keyword))
+        ));
+        let filter = Filter::new_must(Condition::Field(FieldCondition::new_match(
+            "color".to_string(),
+            "red".to_string().into(),
+        )));
+        let estimation = segment.estimate_points_count(Some(&filter));
+        assert_eq!(estimation.min, 10);
+        assert_eq!(estimation.max, 10);
+
+        // Rebuild with the new schema
+        assert!(segment
+            .rebuild_field_index(
+                num_points + 3,
+                "color",
+                Some(&PayloadSchemaType::Text.into())
+            )
+            .unwrap());
+        assert!(matches!(
+            segment.get_indexed_fields().get("color"),
+            Some(PayloadFieldSchema::FieldType(PayloadSchemaType::Text))
+        ));
+        let filter = Filter::new_must(Condition::Field(FieldCondition::new_match(
+            "color".to_string(),
+            Match::Text(MatchText {
+                text: "red".to_string(),
+            }),
+        )));
+        assert_eq!(segment.read_filtered(None, None, Some(&filter)).len(), 10);
+
+        // Not indexed fields are skipped, unless the schema is given
+        assert!(!segment
+            .rebuild_field_index(num_points + 4, "size", None)
+            .unwrap());
+        assert!(!segment.get_indexed_fields().contains_key("size"));
+    }
+
+    #[test]
+    fn test_nested_array_index() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let dim = 5;
+        let mut rnd = StdRng::seed_from_u64(42);
+
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                DEFAULT_VECTOR_NAME.to_owned(),
+                VectorDataConfig {
+                    size: dim,
+                    distance: Distance::Dot,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
+                },
+            )]),
+            sparse_vector_data: Default::default(),
+            index: Indexes::Plain {},
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+        };
+        let mut segment = build_segment(dir.path(), &config).unwrap();
+
+        let num_points = 30u64;
+        let payload = |n: u64| {
+            json!({
+                "reviews": [
+                    { "rating": n % 5, "author": "alice" },
+                    { "rating": 10 + n % 3 },
+                    { "author": "bob" },
+                ]
+            })
+        };
+        // Index is built from existing points and updated with the new ones
+        for n in 0..num_points / 2 {
+            segment
+                .upsert_vector(
+                    n + 1,
+                    n.into(),
+                    &only_default_vector(&random_vector(&mut rnd, dim)),
+                )
+                .unwrap();
+            segment
+                .set_full_payload(n + 1, n.into(), &payload(n).into())
+                .unwrap();
+        }
+        segment
+            .create_field_index(
+                num_points + 1,
+                "reviews[].rating",
+                Some(&PayloadSchemaType::Integer.into()),
+            )
+            .unwrap();
+        for n in num_points / 2..num_points {
+            segment
+                .upsert_vector(
+                    num_points + n + 2,
+                    n.into(),
+                    &only_default_vector(&random_vector(&mut rnd, dim)),
+                )
+                .unwrap();
+            segment
+                .set_full_payload(num_points + n + 2, n.into(), &payload(n).into())
+                .unwrap();
+        }
+
+        let filter = Filter::new_must(Condition::Field(FieldCondition::new_match(
+            "reviews[].rating".to_string(),
+            12.into(),
+        )));
+        let expected = (0..num_points).filter(|n| n % 3 == 2).count();
+
+        // Index gives exact cardinality, payload scan would only give an estimation range
+        let estimation = segment.estimate_points_count(Some(&filter));
+        assert_eq!(estimation.min, expected);
+        assert_eq!(estimation.max, expected);
+        assert_eq!(
+            segment.read_filtered(None, None, Some(&filter)).len(),
+            expected
+        );
+
+        // Not indexed array paths are checked against payload
+        let filter = Filter::new_must(Condition::Field(FieldCondition::new_match(
+            "reviews[].author".to_string(),
+            "bob".to_string().into(),
+        )));
+        assert_eq!(
+            segment.read_filtered(None, None, Some(&filter)).len(),
+            num_points as usize
+        );
+    }
+
+    #[test]
+    fn test_payload_index_telemetry() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let dim = 5;
+        let mut rnd = StdRng::seed_from_u64(42);
+
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                DEFAULT_VECTOR_NAME.to_owned(),
+                VectorDataConfig {
+                    size: dim,
+                    distance: Distance::Dot,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
+                },
+            )]),
+            sparse_vector_data: Default::default(),
+            index: Indexes::Plain {},
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+        };
+        let mut segment = build_segment(dir.path(), &config).unwrap();
+
+        let num_points = 100u64;
+        for n in 0..num_points {
+            let payload: Payload = json!({
+                "tenant": format!("tenant_{}", n % 5),
+                "status": n,
+            })
+            .into();
+            segment
+                .upsert_vector(
+                    n + 1,
+                    n.into(),
+                    &only_default_vector(&random_vector(&mut rnd, dim)),
+                )
+                .unwrap();
+            segment.set_full_payload(n + 1, n.into(), &payload).unwrap();
+        }
+        segment
+            .create_field_index(
+                num_points + 1,
+                "tenant",
+                Some(&PayloadSchemaType::Keyword.into()),
+            )
+            .unwrap();
+        segment
+            .create_field_index(
+                num_points + 2,
+                "status",
+                Some(&PayloadSchemaType::Integer.into()),
+            )
+            .unwrap();
+
+        let telemetry = segment.get_telemetry_data().payload_field_indices;
+        let find_index = |field: &str, index_type: &str| {
+            telemetry
+                .iter()
+                .find(|index| {
+                    index.field_name.as_deref() == Some(field) && index.index_type == index_type
+                })
+                .unwrap_or_else(|| panic!("no {index_type} index of {field}"))
+        };
+
+        let tenant_index = find_index("tenant", "keyword_map");
+        assert_eq!(tenant_index.points_count, num_points as usize);
+        assert_eq!(tenant_index.distinct_values_count, Some(5));
+        assert!(tenant_index.ram_usage_bytes > 0);
+
+        let status_map_index = find_index("status", "int_map");
+        assert_eq!(
+            status_map_index.distinct_values_count,
+            Some(num_points as usize)
+        );
+
+        let status_range_index = find_index("status", "int_range");
+        assert_eq!(status_range_index.points_values_count, num_points as usize);
+        assert!(status_range_index.histogram_buckets_count.unwrap() > 0);
+        assert!(status_range_index.ram_usage_bytes > 0);
+    }
 }
@@ -8,6 +8,8 @@ mod tests {
     use std::time::{Duration, Instant};
 
     use itertools::Itertools;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
     use segment::data_types::vectors::{only_default_vector, DEFAULT_VECTOR_NAME};
     use segment::entry::entry_point::{OperationError, SegmentEntry};
     use segment::segment::Segment;
@@ -72,6 +74,114 @@ mod tests {
         assert_eq!(merged_segment.point_version(3.into()), Some(100));
     }
 
+    #[test]
+    fn test_building_segment_with_on_disk_vectors() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let temp_dir = Builder::new().prefix("segment_temp_dir").tempdir().unwrap();
+
+        let stopped = AtomicBool::new(false);
+
+        let segment1 = build_segment_1(dir.path());
+        assert!(segment1.is_appendable());
+
+        let mut segment_config = segment1.segment_config.clone();
+        segment_config
+            .vector_data
+            .get_mut(DEFAULT_VECTOR_NAME)
+            .unwrap()
+            .on_disk = Some(true);
+
+        let mut builder =
+            SegmentBuilder::new(dir.path(), temp_dir.path(), &segment_config).unwrap();
+        builder.update_from(&segment1, &stopped).unwrap();
+        let built_segment: Segment = builder.build(&stopped).unwrap();
+
+        // Vectors can't be appended to memmap storage
+        assert!(!built_segment.is_appendable());
+        assert!(built_segment.current_path.join("vector_storage").exists());
+        assert_eq!(built_segment.points_count(), segment1.points_count());
+        for point_id in segment1.iter_points() {
+            assert_eq!(
+                built_segment.vector(DEFAULT_VECTOR_NAME, point_id).unwrap(),
+                segment1.vector(DEFAULT_VECTOR_NAME, point_id).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_building_segment_with_old_graph_links() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let temp_dir = Builder::new().prefix("segment_temp_dir").tempdir().unwrap();
+        let stopped = AtomicBool::new(false);
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut random_vector = || (0..4).map(|_| rng.gen_range(-1.0..1.0)).collect_vec();
+
+        let mut all_points = empty_segment(dir.path());
+        let mut old_points = empty_segment(dir.path());
+        let mut new_points = empty_segment(dir.path());
+        for idx in 0..1000 {
+            let vector = only_default_vector(&random_vector());
+            old_points.upsert_vector(1, idx.into(), &vector).unwrap();
+            all_points.upsert_vector(1, idx.into(), &vector).unwrap();
+        }
+        // New points and updates of some of the old ones
+        for idx in (0..10).chain(1000..1100) {
+            let vector = only_default_vector(&random_vector());
+            new_points.upsert_vector(2, idx.into(), &vector).unwrap();
+            all_points.upsert_vector(2, idx.into(), &vector).unwrap();
+        }
+
+        let mut segment_config = old_points.segment_config.clone();
+        segment_config.index = Indexes::Hnsw(Default::default());
+
+        let mut builder =
+            SegmentBuilder::new(dir.path(), temp_dir.path(), &segment_config).unwrap();
+        builder.update_from(&old_points, &stopped).unwrap();
+        let indexed_points = builder.build(&stopped).unwrap();
+
+        let mut builder =
+            SegmentBuilder::new(dir.path(), temp_dir.path(), &segment_config).unwrap();
+        builder.update_from(&indexed_points, &stopped).unwrap();
+        builder.update_from(&new_points, &stopped).unwrap();
+        assert_eq!(builder.old_graph_links[DEFAULT_VECTOR_NAME].len(), 1);
+        assert_eq!(builder.old_graph_links[DEFAULT_VECTOR_NAME][0].len(), 1000);
+        let merged = builder.build(&stopped).unwrap();
+
+        assert_eq!(merged.points_count(), all_points.points_count());
+
+        let top = 10;
+        let mut hits = 0;
+        let queries = 20;
+        for _ in 0..queries {
+            let query = random_vector();
+            let search = |segment: &Segment| {
+                segment
+                    .search(
+                        DEFAULT_VECTOR_NAME,
+                        &query,
+                        &false.into(),
+                        &false.into(),
+                        None,
+                        top,
+                        None,
+                    )
+                    .unwrap()
+                    .into_iter()
+                    .map(|hit| hit.id)
+                    .collect_vec()
+            };
+            let expected = search(&all_points);
+            let found = search(&merged);
+            hits += found.iter().filter(|id| expected.contains(id)).count();
+        }
+        assert!(
+            hits as f64 / (top * queries) as f64 > 0.9,
+            "merged graph precision is too low: {} of {}",
+            hits,
+            top * queries
+        );
+    }
+
     fn estimate_build_time(segment: &Segment, stop_timeout_millis: u64) -> (u64, bool) {
         let stopped = Arc::new(AtomicBool::new(false));
 
@@ -84,8 +194,14 @@ mod tests {
                 VectorDataConfig {
                     size: segment.segment_config.vector_data[DEFAULT_VECTOR_NAME].size,
                     distance: segment.segment_config.vector_data[DEFAULT_VECTOR_NAME].distance,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
                 },
             )]),
+            sparse_vector_data: Default::default(),
             index: Indexes::Hnsw(Default::default()),
             storage_type: Default::default(),
             payload_storage_type: Default::default(),
@@ -24,6 +24,18 @@ impl AliasMapping {
     pub fn save(&self, path: &Path) -> Result<(), StorageError> {
         Ok(atomic_save_json(path, self)?)
     }
+
+    pub fn get(&self, alias: &str) -> Option<&CollectionId> {
+        self.0.get(alias)
+    }
+
+    pub fn insert(&mut self, alias: Alias, collection_name: CollectionId) -> Option<CollectionId> {
+        self.0.insert(alias, collection_name)
+    }
+
+    pub fn remove(&mut self, alias: &str) -> Option<CollectionId> {
+        self.0.remove(alias)
+    }
 }
 
 /// Persists mapping between alias and collection name. The data is assumed to be relatively small.
@@ -1,7 +1,18 @@
-use collection::config::VectorsConfig;
-use collection::operations::config_diff::{HnswConfigDiff, OptimizersConfigDiff, WalConfigDiff};
+use std::collections::BTreeMap;
+
+use collection::config::{
+    CollectionConfig, CollectionParams, HashRingType, PlacementConstraints, SparseVectorParams,
+    VectorsConfig,
+};
+use collection::operations::config_diff::{
+    config_to_diff, CollectionParamsDiff, HnswConfigDiff, OptimizersConfigDiff, WalConfigDiff,
+};
+use collection::operations::payload_transformers::PayloadTransformer;
+use collection::operations::types::CollectionError;
+use collection::shard::replica_set::{ReadConsistency, ReplicaState, WriteConsistency};
 use collection::shard::{CollectionId, PeerId, ShardId, ShardTransfer};
 use schemars::JsonSchema;
+use segment::types::IvfConfig;
 use serde::{Deserialize, Serialize};
 
 use crate::content_manager::shard_distribution::ShardDistributionProposal;
@@ -87,6 +98,10 @@ pub struct CreateCollection {
     /// Vector data config.
     /// It is possible to provide one config for single vector mode and list of configs for multiple vectors mode.
     pub vectors: VectorsConfig,
+    /// Sparse vector data config by name. Sparse vectors are optional for points.
+    /// If none - collection has no sparse vectors.
+    #[serde(default)]
+    pub sparse_vectors: Option<BTreeMap<String, SparseVectorParams>>,
     /// Number of shards in collection.
     /// Default is 1 for standalone, otherwise equal to the number of nodes
     /// Minimum is 1
@@ -98,8 +113,39 @@ pub struct CreateCollection {
     /// Note: those payload values that are involved in filtering and are indexed - remain in RAM.
     #[serde(default)]
     pub on_disk_payload: Option<bool>,
+    /// Maximum total size of payloads in a single read response, in bytes.
+    /// Largest payload fields are omitted once the limit is reached. If none - no limit.
+    #[serde(default)]
+    pub max_response_payload_bytes: Option<usize>,
+    /// Transformations applied in order to payloads of upserted points and set payloads, before they are stored and indexed.
+    /// If none - payloads are stored as is.
+    #[serde(default)]
+    pub payload_transformers: Option<Vec<PayloadTransformer>>,
     /// Custom params for HNSW index. If none - values from service configuration file are used.
     pub hnsw_config: Option<HnswConfigDiff>,
+    /// Use IVF index instead of HNSW for this collection. If none - HNSW index is used.
+    /// IVF index builds faster and requires less memory, but is less precise.
+    #[serde(default)]
+    pub ivf_config: Option<IvfConfig>,
+    /// Constraints for placing shard replicas on peers. If none - shards are placed on any peer.
+    #[serde(default)]
+    pub placement: Option<PlacementConstraints>,
+    /// Number of active remote replicas, which are read in parallel, if the local replica can't answer.
+    /// If none - all active remote replicas are read.
+    #[serde(default)]
+    pub read_fan_out_factor: Option<u32>,
+    /// Number of active replicas, which have to acknowledge an update for it to succeed.
+    /// If none - `all`.
+    #[serde(default)]
+    pub write_consistency: Option<WriteConsistency>,
+    /// Number of active replicas, which have to answer a read.
+    /// If none - `single`.
+    #[serde(default)]
+    pub read_consistency: Option<ReadConsistency>,
+    /// Method to distribute points between the shards. If none - `fair` is used.
+    /// Can't be changed after the collection is created.
+    #[serde(default)]
+    pub hash_ring: Option<HashRingType>,
     /// Custom params for WAL. If none - values from service configuration file are used.
     pub wal_config: Option<WalConfigDiff>,
     /// Custom params for Optimizers.  If none - values from service configuration file are used.
@@ -115,6 +161,53 @@ pub struct CreateCollectionOperation {
     pub create_collection: CreateCollection,
 }
 
+/// Request, which creates a collection with the same parameters as the given config
+impl TryFrom<CollectionConfig> for CreateCollection {
+    type Error = CollectionError;
+
+    fn try_from(config: CollectionConfig) -> Result<Self, Self::Error> {
+        let CollectionConfig {
+            params,
+            hnsw_config,
+            optimizer_config,
+            wal_config,
+            version: _,
+        } = config;
+        let CollectionParams {
+            vectors,
+            sparse_vectors,
+            shard_number,
+            hash_ring,
+            replication_factor: _,
+            read_fan_out_factor,
+            write_consistency,
+            read_consistency,
+            on_disk_payload,
+            max_response_payload_bytes,
+            payload_transformers,
+            ivf_config,
+            placement,
+        } = params;
+        Ok(CreateCollection {
+            vectors,
+            sparse_vectors,
+            shard_number: Some(shard_number.get()),
+            on_disk_payload: Some(on_disk_payload),
+            max_response_payload_bytes,
+            payload_transformers: Some(payload_transformers),
+            hnsw_config: Some(config_to_diff(&hnsw_config)?),
+            ivf_config,
+            placement: Some(placement),
+            read_fan_out_factor,
+            write_consistency: Some(write_consistency),
+            read_consistency: Some(read_consistency),
+            hash_ring: Some(hash_ring),
+            wal_config: Some(config_to_diff(&wal_config)?),
+            optimizers_config: Some(config_to_diff(&optimizer_config)?),
+        })
+    }
+}
+
 /// Operation for updating parameters of the existing collection
 #[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq, Hash, Clone)]
 #[serde(rename_all = "snake_case")]
@@ -122,6 +215,12 @@ pub struct UpdateCollection {
     /// Custom params for Optimizers.  If none - values from service configuration file are used.
     /// This operation is blocking, it will only proceed ones all current optimizations are complete
     pub optimizers_config: Option<OptimizersConfigDiff>, // ToDo: Allow updates for other configuration params as well
+    /// Collection base params.  If none - values from the collection are kept.
+    /// Missing replicas are synced from existing ones, if the replication factor is increased
+    pub params: Option<CollectionParamsDiff>,
+    /// If set - the update is only applied if the current version of the collection config matches.
+    /// Prevents overwriting concurrent updates of the config.
+    pub expected_version: Option<u64>,
 }
 
 /// Operation for updating parameters of the existing collection
@@ -163,8 +262,8 @@ pub struct SetShardReplicaState {
     pub collection_name: String,
     pub shard_id: ShardId,
     pub peer_id: PeerId,
-    /// If `true` then the replica is up to date and can receive updates and answer requests
-    pub active: bool,
+    /// New state of the replica
+    pub state: ReplicaState,
 }
 
 /// Enumeration of all possible collection update operations
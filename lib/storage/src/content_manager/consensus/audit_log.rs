@@ -0,0 +1,134 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use parking_lot::Mutex;
+
+use crate::types::AuditRecord;
+use crate::StorageError;
+
+const AUDIT_LOG_FILE: &str = "consensus_audit.jsonl";
+
+/// Append-only log of operations applied by consensus, one JSON record per line.
+///
+/// Operations received with a consensus snapshot are not recorded.
+/// Entry might be applied again after a restart, if the restart happened before its application
+/// was saved, so the log might contain repeated records of the same entry.
+pub struct AuditLog {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl AuditLog {
+    pub fn new(storage_path: &str) -> Self {
+        let path = Path::new(storage_path).join(AUDIT_LOG_FILE);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)
+            .expect("Can't open consensus audit log");
+        // Finish the record, torn by a crash, so that it does not corrupt the next one
+        if !ends_with_newline(&mut file).expect("Can't read consensus audit log") {
+            file.write_all(b"\n")
+                .expect("Can't write consensus audit log");
+        }
+        Self {
+            path,
+            file: Mutex::new(file),
+        }
+    }
+
+    pub fn append(&self, record: &AuditRecord) -> Result<(), StorageError> {
+        let mut line = serde_json::to_vec(record).map_err(|err| {
+            StorageError::service_error(&format!("Failed to serialize audit record: {err}"))
+        })?;
+        line.push(b'\n');
+        self.file.lock().write_all(&line)?;
+        Ok(())
+    }
+
+    /// Read at most `limit` records of entries with index not less than `from_index`, in order of application
+    pub fn read(&self, from_index: u64, limit: usize) -> Result<Vec<AuditRecord>, StorageError> {
+        let reader = BufReader::new(File::open(&self.path)?);
+        let mut records = vec![];
+        for line in reader.lines() {
+            if records.len() >= limit {
+                break;
+            }
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            let record: AuditRecord = match serde_json::from_str(&line) {
+                Ok(record) => record,
+                Err(err) => {
+                    log::warn!("Skipping corrupted record of consensus audit log: {err}");
+                    continue;
+                }
+            };
+            if record.index >= from_index {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+}
+
+fn ends_with_newline(file: &mut File) -> std::io::Result<bool> {
+    if file.metadata()?.len() == 0 {
+        return Ok(true);
+    }
+    file.seek(SeekFrom::End(-1))?;
+    let mut last_byte = [0; 1];
+    file.read_exact(&mut last_byte)?;
+    Ok(last_byte[0] == b'\n')
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+
+    fn record(index: u64) -> AuditRecord {
+        AuditRecord {
+            index,
+            term: 1,
+            proposer: Some(1),
+            operation: "DrainPeer(2)".to_string(),
+            result: Some(true),
+            error: None,
+            timestamp: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    #[test]
+    fn records_are_persisted() {
+        let dir = Builder::new().prefix("audit_log_test").tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        {
+            let audit_log = AuditLog::new(path);
+            for index in 1..=5 {
+                audit_log.append(&record(index)).unwrap();
+            }
+        }
+        // Simulate a record torn by a crash
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(dir.path().join(AUDIT_LOG_FILE))
+            .unwrap();
+        file.write_all(b"{\"index\":").unwrap();
+
+        let audit_log = AuditLog::new(path);
+        audit_log.append(&record(6)).unwrap();
+        let indexes = |records: Vec<AuditRecord>| -> Vec<u64> {
+            records.into_iter().map(|record| record.index).collect()
+        };
+        assert_eq!(
+            indexes(audit_log.read(0, 100).unwrap()),
+            vec![1, 2, 3, 4, 5, 6]
+        );
+        assert_eq!(indexes(audit_log.read(3, 2).unwrap()), vec![3, 4]);
+    }
+}
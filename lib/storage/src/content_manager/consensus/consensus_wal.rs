@@ -1,3 +1,4 @@
+use std::cmp;
 use std::fs::create_dir_all;
 use std::path::Path;
 
@@ -14,6 +15,16 @@ const COLLECTIONS_META_WAL_DIR: &str = "collections_meta_wal";
 
 pub struct ConsensusOpWal(pub Wal);
 
+/// Entry of the consensus WAL, which can't be read, e.g. due to a torn write
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorruptedEntry {
+    /// Position of the entry in WAL
+    pub wal_index: u64,
+    /// Raft index, which the entry is expected to have
+    pub raft_index: u64,
+    pub description: String,
+}
+
 impl ConsensusOpWal {
     pub fn new(storage_path: &str) -> Self {
         let collections_meta_wal_path = Path::new(storage_path).join(&COLLECTIONS_META_WAL_DIR);
@@ -74,6 +85,87 @@ impl ConsensusOpWal {
         Ok(entry.transpose()?)
     }
 
+    /// Remove entries with index less than `until_index`.
+    /// WAL is truncated by whole segments, so some of the older entries might be retained.
+    pub fn compact(&mut self, until_index: u64) -> Result<(), StorageError> {
+        let first_entry = match self.first_entry()? {
+            Some(first_entry) => first_entry,
+            None => return Ok(()),
+        };
+        if until_index <= first_entry.index {
+            return Ok(());
+        }
+        // Due to snapshots there might be different offsets between wal index and raft entry index
+        let offset = first_entry.index - self.0.first_index();
+        self.0.prefix_truncate(until_index - offset)?;
+        Ok(())
+    }
+
+    /// Check that all entries can be decoded and have consecutive indexes.
+    /// Checksums of the records are verified by WAL itself on open.
+    ///
+    /// `expected_first_index` - Raft index of the first entry, used if it can't be decoded.
+    pub fn find_corrupted_entry(&self, expected_first_index: u64) -> Option<CorruptedEntry> {
+        let first_wal_index = self.0.first_index();
+        let mut expected_index = None;
+        for wal_index in first_wal_index..first_wal_index + self.0.num_entries() {
+            let raft_index = expected_index.unwrap_or(expected_first_index);
+            let corrupted = |description: String| CorruptedEntry {
+                wal_index,
+                raft_index,
+                description,
+            };
+            let record = match self.0.entry(wal_index) {
+                Some(record) => record,
+                None => return Some(corrupted("entry is missing".to_string())),
+            };
+            let entry = match <RaftEntry as prost::Message>::decode(record.as_ref()) {
+                Ok(entry) => entry,
+                Err(err) => return Some(corrupted(format!("can't decode entry: {err}"))),
+            };
+            match expected_index {
+                Some(index) if entry.index != index => {
+                    return Some(corrupted(format!(
+                        "unexpected entry index {}, expected {index}",
+                        entry.index
+                    )))
+                }
+                _ => expected_index = Some(entry.index + 1),
+            }
+        }
+        None
+    }
+
+    /// Remove the entry at `wal_index` and all the following ones
+    pub fn truncate(&mut self, wal_index: u64) -> Result<(), StorageError> {
+        self.0.truncate(wal_index)?;
+        Ok(())
+    }
+
+    /// Remove entries with raft index greater than `last_index`
+    pub fn truncate_after(&mut self, last_index: u64) -> Result<(), StorageError> {
+        let first_entry = match self.first_entry()? {
+            Some(first_entry) => first_entry,
+            None => return Ok(()),
+        };
+        // Due to snapshots there might be different offsets between wal index and raft entry index
+        let offset = first_entry.index - self.0.first_index();
+        let wal_index = cmp::max(last_index + 1, first_entry.index) - offset;
+        if wal_index < self.0.first_index() + self.0.num_entries() {
+            self.0.truncate(wal_index)?;
+        }
+        Ok(())
+    }
+
+    /// Write all entries into a new WAL in `storage_path`
+    pub fn copy_to(&self, storage_path: &str) -> Result<(), StorageError> {
+        let mut copy = ConsensusOpWal::new(storage_path);
+        if let (Some(first), Some(last)) = (self.first_entry()?, self.last_entry()?) {
+            copy.append_entries(self.entries(first.index, last.index + 1, None)?)?;
+        }
+        Ok(())
+    }
+
     pub fn append_entries(&mut self, entries: Vec<RaftEntry>) -> Result<(), StorageError> {
         for entry in entries {
             log::debug!("Appending entry: {entry:?}");
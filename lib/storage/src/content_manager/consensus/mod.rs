@@ -1,3 +1,4 @@
+pub mod audit_log;
 pub mod consensus_wal;
 pub mod entry_queue;
 pub mod is_ready;
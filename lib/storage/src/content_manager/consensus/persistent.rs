@@ -1,4 +1,5 @@
 use std::cmp;
+use std::collections::{HashSet, VecDeque};
 use std::fs::{create_dir_all, File};
 use std::io::BufWriter;
 use std::path::{Path, PathBuf};
@@ -6,6 +7,7 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use atomicwrites::{AllowOverwrite, AtomicFile};
+use collection::config::{PeerMetadata, PeerMetadataById};
 use collection::shard::PeerId;
 use http::Uri;
 use parking_lot::RwLock;
@@ -14,6 +16,7 @@ use raft::RaftState;
 use serde::{Deserialize, Serialize};
 
 use crate::content_manager::consensus::entry_queue::{EntryApplyProgressQueue, EntryId};
+use crate::content_manager::consensus_ops::{OperationId, OPERATION_DEDUP_WINDOW};
 use crate::types::PeerAddressById;
 use crate::StorageError;
 
@@ -29,6 +32,17 @@ pub struct Persistent {
     pub apply_progress_queue: EntryApplyProgressQueue,
     #[serde(with = "serialize_peer_addresses")]
     pub peer_address_by_id: Arc<RwLock<PeerAddressById>>,
+    /// Location labels of peers, set through consensus
+    #[serde(default)]
+    #[serde(with = "serialize_shared")]
+    pub peer_metadata_by_id: Arc<RwLock<PeerMetadataById>>,
+    /// Peers, which are being drained: their shards are moved away before they leave the cluster
+    #[serde(default)]
+    #[serde(with = "serialize_shared")]
+    pub draining_peers: Arc<RwLock<HashSet<PeerId>>>,
+    /// Ids of the latest applied operations, used to skip duplicate proposals
+    #[serde(default)]
+    pub applied_operation_ids: VecDeque<OperationId>,
     pub this_peer_id: u64,
     #[serde(skip)]
     pub path: PathBuf,
@@ -50,8 +64,14 @@ impl Persistent {
         &mut self,
         meta: &SnapshotMetadata,
         address_by_id: PeerAddressById,
+        metadata_by_id: PeerMetadataById,
+        draining_peers: HashSet<PeerId>,
+        applied_operation_ids: VecDeque<OperationId>,
     ) -> Result<(), StorageError> {
         *self.peer_address_by_id.write() = address_by_id;
+        *self.peer_metadata_by_id.write() = metadata_by_id;
+        *self.draining_peers.write() = draining_peers;
+        self.applied_operation_ids = applied_operation_ids;
         self.state.conf_state = meta.get_conf_state().clone();
         self.state.hard_state.term = cmp::max(self.state.hard_state.term, meta.term);
         self.state.hard_state.commit = meta.index;
@@ -60,6 +80,13 @@ impl Persistent {
         self.save()
     }
 
+    /// Mark entries up to `index` as compacted: they are removed from the WAL,
+    /// as their effect is already applied to the local state.
+    pub fn set_compacted_until(&mut self, index: u64, term: u64) -> Result<(), StorageError> {
+        self.latest_snapshot_meta = SnapshotMetadataSer { term, index };
+        self.save()
+    }
+
     /// Returns state and if it was initialized for the first time
     pub fn load_or_init(
         storage_path: impl AsRef<Path>,
@@ -131,6 +158,42 @@ impl Persistent {
         self.save()
     }
 
+    pub fn update_peer_metadata(
+        &mut self,
+        peer_id: PeerId,
+        metadata: PeerMetadata,
+    ) -> Result<(), StorageError> {
+        log::debug!("Updated metadata of peer {peer_id}: {metadata:?}");
+        self.peer_metadata_by_id.write().insert(peer_id, metadata);
+        self.save()
+    }
+
+    pub fn drain_peer(&mut self, peer_id: PeerId) -> Result<(), StorageError> {
+        if self.draining_peers.write().insert(peer_id) {
+            log::info!("Peer {peer_id} is draining");
+        }
+        self.save()
+    }
+
+    /// Forget metadata and draining state of the peer, which left the cluster
+    pub fn remove_peer_state(&mut self, peer_id: PeerId) -> Result<(), StorageError> {
+        self.peer_metadata_by_id.write().remove(&peer_id);
+        self.draining_peers.write().remove(&peer_id);
+        self.save()
+    }
+
+    pub fn is_operation_applied(&self, id: OperationId) -> bool {
+        self.applied_operation_ids.contains(&id)
+    }
+
+    /// Remember id of the applied operation. It is saved together with the applied entry.
+    pub fn operation_applied(&mut self, id: OperationId) {
+        self.applied_operation_ids.push_back(id);
+        while self.applied_operation_ids.len() > OPERATION_DEDUP_WINDOW {
+            self.applied_operation_ids.pop_front();
+        }
+    }
+
     pub fn last_applied_entry(&self) -> Option<u64> {
         self.apply_progress_queue.get_last_applied()
     }
@@ -139,6 +202,14 @@ impl Persistent {
         self.peer_address_by_id.read().clone()
     }
 
+    pub fn peer_metadata_by_id(&self) -> PeerMetadataById {
+        self.peer_metadata_by_id.read().clone()
+    }
+
+    pub fn draining_peers(&self) -> HashSet<PeerId> {
+        self.draining_peers.read().clone()
+    }
+
     pub fn this_peer_id(&self) -> u64 {
         self.this_peer_id
     }
@@ -167,6 +238,9 @@ impl Persistent {
             },
             apply_progress_queue: Default::default(),
             peer_address_by_id: Default::default(),
+            peer_metadata_by_id: Default::default(),
+            draining_peers: Default::default(),
+            applied_operation_ids: Default::default(),
             this_peer_id,
             path,
             latest_snapshot_meta: Default::default(),
@@ -184,12 +258,22 @@ impl Persistent {
     }
 
     pub fn save(&self) -> Result<(), StorageError> {
-        let result = AtomicFile::new(&self.path, AllowOverwrite).write(|file| {
+        let result = self.write(&self.path);
+        self.dirty.store(result.is_err(), Ordering::Relaxed);
+        result
+    }
+
+    /// Save a copy of the state into `storage_path`, the state keeps being saved at its own path
+    pub fn save_copy(&self, storage_path: &Path) -> Result<(), StorageError> {
+        self.write(&storage_path.join(STATE_FILE_NAME))
+    }
+
+    fn write(&self, path: &Path) -> Result<(), StorageError> {
+        AtomicFile::new(path, AllowOverwrite).write(|file| {
             let writer = BufWriter::new(file);
             serde_cbor::to_writer(writer, self)
-        });
-        self.dirty.store(result.is_err(), Ordering::Relaxed);
-        Ok(result?)
+        })?;
+        Ok(())
     }
 
     pub fn save_if_dirty(&mut self) -> Result<(), StorageError> {
@@ -245,6 +329,31 @@ mod serialize_peer_addresses {
     }
 }
 
+/// Serialization of state, which is shared with other components behind a lock
+mod serialize_shared {
+    use std::sync::Arc;
+
+    use parking_lot::RwLock;
+    use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S, T>(value: &Arc<RwLock<T>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        value.read().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Arc<RwLock<T>>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        let value = T::deserialize(deserializer)?;
+        Ok(Arc::new(RwLock::new(value)))
+    }
+}
+
 /// Definition of struct to help with serde serialization.
 /// Should be used only in `[serde(with=...)]`
 #[derive(Serialize, Deserialize)]
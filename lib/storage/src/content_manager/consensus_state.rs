@@ -1,10 +1,14 @@
-use std::collections::HashMap;
+use std::cmp;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Display;
 use std::ops::Deref;
+use std::path::Path;
 use std::sync::Arc;
+use std::thread;
 use std::time::Duration;
 
 use collection::collection_state;
+use collection::config::PeerMetadataById;
 use collection::shard::{CollectionId, PeerId};
 use parking_lot::{Mutex, RwLock};
 use raft::eraftpb::{ConfChangeType, ConfChangeV2, Entry as RaftEntry};
@@ -14,20 +18,33 @@ use tokio::sync::oneshot;
 use tonic::transport::Uri;
 
 use super::alias_mapping::AliasMapping;
-use super::consensus_ops::ConsensusOperations;
+use super::collection_meta_ops::CollectionMetaOperations;
+use super::consensus_ops::{
+    operation_id_from_context, operation_ids_from_context, proposal_to_context,
+    proposer_from_context, ConsensusOperations, OperationId,
+};
 use super::errors::StorageError;
 use super::CollectionContainer;
+use crate::content_manager::consensus::audit_log::AuditLog;
 use crate::content_manager::consensus::consensus_wal::ConsensusOpWal;
 use crate::content_manager::consensus::entry_queue::EntryId;
 use crate::content_manager::consensus::is_ready::IsReady;
 use crate::content_manager::consensus::operation_sender::OperationSender;
 use crate::content_manager::consensus::persistent::Persistent;
 use crate::types::{
-    ClusterInfo, ClusterStatus, ConsensusThreadStatus, PeerAddressById, PeerInfo, RaftInfo,
+    AuditRecord, ClusterInfo, ClusterStatus, ConsensusDebugInfo, ConsensusThreadStatus,
+    EntryDebugInfo, PeerAddressById, PeerInfo, PeerProgress, RaftInfo, SnapshotDebugInfo,
 };
 
 pub const DEFAULT_META_OP_WAIT: Duration = Duration::from_secs(10);
 
+const READ_INDEX_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+const LEADER_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Max number of unapplied entries, reported by [`ConsensusState::debug_info`]
+const MAX_DEBUG_UNAPPLIED_ENTRIES: usize = 100;
+
 pub mod prelude {
     use crate::content_manager::toc::TableOfContent;
 
@@ -39,6 +56,12 @@ pub struct SnapshotData {
     pub collections_data: CollectionsSnapshot,
     #[serde(with = "crate::serialize_peer_addresses")]
     pub address_by_id: PeerAddressById,
+    #[serde(default)]
+    pub metadata_by_id: PeerMetadataById,
+    #[serde(default)]
+    pub draining_peers: HashSet<PeerId>,
+    #[serde(default)]
+    pub applied_operation_ids: VecDeque<OperationId>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -55,17 +78,71 @@ impl TryFrom<&[u8]> for SnapshotData {
     }
 }
 
+type OnApplySender = oneshot::Sender<Result<bool, StorageError>>;
+
+/// Consensus snapshot, which is built in background to not block consensus
+enum SnapshotBuild {
+    Idle,
+    InProgress,
+    Ready(raft::eraftpb::Snapshot),
+}
+
+/// Sets the result of the snapshot build.
+/// If the build fails without a result, e.g. panics, the build is reset, so that a new one can be started.
+struct SnapshotBuildGuard {
+    build: Arc<Mutex<SnapshotBuild>>,
+    finished: bool,
+}
+
+impl SnapshotBuildGuard {
+    fn new(build: Arc<Mutex<SnapshotBuild>>) -> Self {
+        Self {
+            build,
+            finished: false,
+        }
+    }
+
+    fn finish(mut self, result: SnapshotBuild) {
+        *self.build.lock() = result;
+        self.finished = true;
+    }
+}
+
+impl Drop for SnapshotBuildGuard {
+    fn drop(&mut self) {
+        if !self.finished {
+            *self.build.lock() = SnapshotBuild::Idle;
+        }
+    }
+}
+
+/// Operation proposed by this peer, which is not applied yet
+struct PendingOperation {
+    id: OperationId,
+    on_apply: Vec<OnApplySender>,
+}
+
 pub struct ConsensusState<C: CollectionContainer> {
     pub persistent: RwLock<Persistent>,
     pub is_leader_established: Arc<IsReady>,
     wal: Mutex<ConsensusOpWal>,
+    /// Log of operations applied by consensus on this peer
+    audit_log: AuditLog,
     soft_state: RwLock<Option<SoftState>>,
+    /// Replication progress of peers, known if this peer is the leader
+    peers_progress: RwLock<Option<HashMap<PeerId, PeerProgress>>>,
     toc: Arc<C>,
-    on_consensus_op_apply:
-        Mutex<HashMap<ConsensusOperations, oneshot::Sender<Result<bool, StorageError>>>>,
+    on_consensus_op_apply: Mutex<HashMap<ConsensusOperations, PendingOperation>>,
+    /// Index of the batch entry and number of its operations, which are already applied
+    batch_progress: Mutex<Option<(EntryId, usize)>>,
+    snapshot_build: Arc<Mutex<SnapshotBuild>>,
+    /// Requests of linearizable reads, awaiting the read index from the leader
+    on_read_index: Mutex<HashMap<u64, oneshot::Sender<u64>>>,
     propose_sender: OperationSender,
     first_voter: RwLock<Option<PeerId>>,
     consensus_thread_status: RwLock<ConsensusThreadStatus>,
+    /// Number of latest applied entries, which are kept in WAL on compaction
+    compact_wal_entries: u64,
 }
 
 impl<C: CollectionContainer> ConsensusState<C> {
@@ -74,17 +151,24 @@ impl<C: CollectionContainer> ConsensusState<C> {
         toc: Arc<C>,
         propose_sender: OperationSender,
         storage_path: &str,
+        compact_wal_entries: u64,
     ) -> Self {
         Self {
             persistent: RwLock::new(persistent_state),
             is_leader_established: Arc::new(IsReady::default()),
             wal: Mutex::new(ConsensusOpWal::new(storage_path)),
+            audit_log: AuditLog::new(storage_path),
             soft_state: RwLock::new(None),
+            peers_progress: RwLock::new(None),
             toc,
             on_consensus_op_apply: Default::default(),
+            batch_progress: Default::default(),
+            snapshot_build: Arc::new(Mutex::new(SnapshotBuild::Idle)),
+            on_read_index: Default::default(),
             propose_sender,
             first_voter: Default::default(),
             consensus_thread_status: RwLock::new(ConsensusThreadStatus::Working),
+            compact_wal_entries,
         }
     }
 
@@ -105,6 +189,10 @@ impl<C: CollectionContainer> ConsensusState<C> {
         }
     }
 
+    pub fn set_peers_progress(&self, progress: Option<HashMap<PeerId, PeerProgress>>) {
+        *self.peers_progress.write() = progress;
+    }
+
     pub fn this_peer_id(&self) -> PeerId {
         self.persistent.read().this_peer_id
     }
@@ -131,6 +219,12 @@ impl<C: CollectionContainer> ConsensusState<C> {
                     peer_id,
                     PeerInfo {
                         uri: uri.to_string(),
+                        draining: persistent.draining_peers.read().contains(&peer_id),
+                        witness: persistent
+                            .peer_metadata_by_id
+                            .read()
+                            .get(&peer_id)
+                            .map_or(false, |metadata| metadata.witness),
                     },
                 )
             })
@@ -156,6 +250,45 @@ impl<C: CollectionContainer> ConsensusState<C> {
         })
     }
 
+    /// Internal state of the consensus, which is useful to diagnose why it is stuck
+    pub fn debug_info(&self) -> Result<ConsensusDebugInfo, StorageError> {
+        let first_index = self.first_index()?;
+        let last_index = self.last_index()?;
+        let persistent = self.persistent.read();
+        let unapplied_entries_count = persistent.unapplied_entities_count();
+        let unapplied_entries = match persistent.current_unapplied_entry() {
+            Some(first_unapplied) => {
+                let count = cmp::min(unapplied_entries_count, MAX_DEBUG_UNAPPLIED_ENTRIES);
+                let wal = self.wal.lock();
+                (first_unapplied..first_unapplied + count as u64)
+                    .map(|index| {
+                        let entry = wal.entry(index)?;
+                        Ok(EntryDebugInfo {
+                            index: entry.index,
+                            term: entry.term,
+                            content: describe_entry(&entry),
+                        })
+                    })
+                    .collect::<Result<_, StorageError>>()?
+            }
+            None => vec![],
+        };
+        let snapshot_meta = persistent.latest_snapshot_meta();
+        Ok(ConsensusDebugInfo {
+            first_index,
+            last_index,
+            commit: persistent.state().hard_state.commit,
+            last_applied: persistent.last_applied_entry(),
+            unapplied_entries_count,
+            unapplied_entries,
+            latest_snapshot: SnapshotDebugInfo {
+                index: snapshot_meta.index,
+                term: snapshot_meta.term,
+            },
+            peers_progress: self.peers_progress.read().clone(),
+        })
+    }
+
     pub fn apply_conf_change_entry<T: Storage>(
         &self,
         entry: &RaftEntry,
@@ -187,12 +320,8 @@ impl<C: CollectionContainer> ConsensusState<C> {
                     }
                     self.remove_peer(single_change.node_id)?;
                     let operation = ConsensusOperations::RemovePeer(single_change.node_id);
-                    let on_apply = self.on_consensus_op_apply.lock().remove(&operation);
-                    if let Some(on_apply) = on_apply {
-                        if on_apply.send(Ok(true)).is_err() {
-                            log::warn!("Failed to notify on consensus operation completion: channel receiver is dropped")
-                        }
-                    }
+                    self.audit(entry, None, &operation, &Ok(true));
+                    self.notify_on_apply(&operation, None, &Ok(true));
                 }
                 ConfChangeType::AddLearnerNode => {
                     log::debug!("Adding learner node {}", single_change.node_id);
@@ -206,12 +335,8 @@ impl<C: CollectionContainer> ConsensusState<C> {
                             single_change.node_id,
                             peer_uri.to_string(),
                         );
-                        let on_apply = self.on_consensus_op_apply.lock().remove(&operation);
-                        if let Some(on_apply) = on_apply {
-                            if on_apply.send(Ok(true)).is_err() {
-                                log::warn!("Failed to notify on consensus operation completion: channel receiver is dropped")
-                            }
-                        }
+                        self.audit(entry, None, &operation, &Ok(true));
+                        self.notify_on_apply(&operation, None, &Ok(true));
                     } else if entry.get_context().is_empty() {
                         // Allow empty context for compatibility
                         // TODO: remove in the next version after 0.10
@@ -329,26 +454,223 @@ impl<C: CollectionContainer> ConsensusState<C> {
 
     pub fn apply_normal_entry(&self, entry: &RaftEntry) -> Result<bool, StorageError> {
         let operation: ConsensusOperations = entry.try_into()?;
-        let on_apply = self.on_consensus_op_apply.lock().remove(&operation);
-        let result = if let ConsensusOperations::CollectionMeta(operation) = operation {
-            self.toc.perform_collection_meta_op(*operation)
-        } else {
-            // RemovePeer or AddPeer should be converted into native ConfChangeV2 message before sending to the Raft.
-            // So we do not expect to receive these operations as a normal entry.
-            // This is a debug assert so production migrations should be ok.
-            // TODO: parse into CollectionMetaOperation as we will not handle other cases here, but this removes compatibility with previous entry storage
-            debug_assert!(
-                false,
-                "Do not expect RemovePeer or AddPeer to be directly proposed"
-            );
-            Ok(false)
+        if let ConsensusOperations::CollectionMetaBatch(operations) = operation {
+            return self.apply_batch_entry(entry, operations);
+        }
+        let operation_id = operation_ids_from_context(entry.get_context(), 1)
+            .pop()
+            .flatten();
+        let proposer = proposer_from_context(entry.get_context(), 1);
+        self.apply_operation(entry, proposer, operation, operation_id)
+    }
+
+    /// Apply operations of the batch in order.
+    ///
+    /// If an operation fails with a service error, the entry is applied again later,
+    /// starting from the failed operation.
+    fn apply_batch_entry(
+        &self,
+        entry: &RaftEntry,
+        operations: Vec<CollectionMetaOperations>,
+    ) -> Result<bool, StorageError> {
+        let operation_ids = operation_ids_from_context(entry.get_context(), operations.len());
+        let proposer = proposer_from_context(entry.get_context(), operations.len());
+        let applied_count = match *self.batch_progress.lock() {
+            Some((index, applied_count)) if index == entry.index => applied_count,
+            _ => 0,
         };
-        if let Some(on_apply) = on_apply {
+        for (position, (operation, operation_id)) in operations
+            .into_iter()
+            .zip(operation_ids)
+            .enumerate()
+            .skip(applied_count)
+        {
+            let operation = ConsensusOperations::CollectionMeta(Box::new(operation));
+            match self.apply_operation(entry, proposer, operation, operation_id) {
+                Ok(_) => {}
+                Err(err @ StorageError::ServiceError { .. }) => {
+                    *self.batch_progress.lock() = Some((entry.index, position));
+                    return Err(err);
+                }
+                Err(err) => {
+                    log::warn!(
+                        "Failed to apply operation {position} of the batch with user error: {err}"
+                    )
+                }
+            }
+        }
+        *self.batch_progress.lock() = None;
+        Ok(true)
+    }
+
+    fn apply_operation(
+        &self,
+        entry: &RaftEntry,
+        proposer: Option<PeerId>,
+        operation: ConsensusOperations,
+        operation_id: Option<OperationId>,
+    ) -> Result<bool, StorageError> {
+        if let Some(operation_id) = operation_id {
+            if self.persistent.read().is_operation_applied(operation_id) {
+                log::debug!(
+                    "Skipping duplicate proposal of operation {operation_id}: {operation:?}"
+                );
+                return Ok(false);
+            }
+        }
+        let result = match operation.clone() {
+            ConsensusOperations::CollectionMeta(operation) => {
+                self.toc.perform_collection_meta_op(*operation)
+            }
+            ConsensusOperations::UpdatePeerMetadata(peer_id, metadata) => {
+                let is_witness = self
+                    .persistent
+                    .read()
+                    .peer_metadata_by_id
+                    .read()
+                    .get(&peer_id)
+                    .map_or(false, |metadata| metadata.witness);
+                if metadata.witness && !is_witness && self.toc.peer_has_shards(peer_id) {
+                    Err(StorageError::BadRequest {
+                        description: format!(
+                            "Peer {peer_id} hosts shards and can't become a witness, drain it first"
+                        ),
+                    })
+                } else {
+                    self.persistent
+                        .write()
+                        .update_peer_metadata(peer_id, metadata)
+                        .map(|()| true)
+                }
+            }
+            ConsensusOperations::DrainPeer(peer_id) => {
+                self.persistent.write().drain_peer(peer_id)?;
+                self.toc.drain_peer(peer_id);
+                Ok(true)
+            }
+            ConsensusOperations::AddPeer(..)
+            | ConsensusOperations::RemovePeer(_)
+            | ConsensusOperations::ReadIndex(_)
+            | ConsensusOperations::TransferLeadership(_)
+            | ConsensusOperations::CollectionMetaBatch(_) => {
+                // RemovePeer or AddPeer should be converted into native ConfChangeV2 message before sending to the Raft.
+                // ReadIndex and TransferLeadership are handled by the consensus thread directly.
+                // Batches are not nested.
+                // So we do not expect to receive these operations as a normal entry.
+                // This is a debug assert so production migrations should be ok.
+                // TODO: parse into CollectionMetaOperation as we will not handle other cases here, but this removes compatibility with previous entry storage
+                debug_assert!(
+                    false,
+                    "Do not expect RemovePeer or AddPeer to be directly proposed"
+                );
+                Ok(false)
+            }
+        };
+        // Service errors are not considered applied, the entry is applied again later
+        let is_applied = !matches!(result, Err(StorageError::ServiceError { .. }));
+        if is_applied {
+            if let Some(operation_id) = operation_id {
+                self.persistent.write().operation_applied(operation_id);
+            }
+            self.audit(entry, proposer, &operation, &result);
+            self.notify_on_apply(&operation, operation_id, &result);
+        }
+        result
+    }
+
+    /// Record the applied operation into the audit log.
+    /// Failure to record does not fail the operation, as it is already applied.
+    fn audit(
+        &self,
+        entry: &RaftEntry,
+        proposer: Option<PeerId>,
+        operation: &ConsensusOperations,
+        result: &Result<bool, StorageError>,
+    ) {
+        let record = AuditRecord {
+            index: entry.index,
+            term: entry.term,
+            proposer,
+            operation: format!("{operation:?}"),
+            result: result.as_ref().ok().copied(),
+            error: result.as_ref().err().map(ToString::to_string),
+            timestamp: chrono::Utc::now().naive_utc(),
+        };
+        if let Err(err) = self.audit_log.append(&record) {
+            log::error!("Failed to write consensus audit log: {err}");
+        }
+    }
+
+    /// Read at most `limit` audit records of entries with index not less than `from_index`
+    pub fn audit_records(
+        &self,
+        from_index: u64,
+        limit: usize,
+    ) -> Result<Vec<AuditRecord>, StorageError> {
+        self.audit_log.read(from_index, limit)
+    }
+
+    /// Save copies of the raft state and of the consensus WAL into `target_dir`.
+    /// Entries are neither appended nor applied while copying, so the copies are consistent with each other.
+    pub fn save_consensus_copy(&self, target_dir: &Path) -> Result<(), StorageError> {
+        let persistent = self.persistent.read();
+        let wal = self.wal.lock();
+        std::fs::create_dir_all(target_dir)?;
+        persistent.save_copy(target_dir)?;
+        wal.copy_to(&target_dir.to_string_lossy())
+    }
+
+    /// Notify everyone awaiting the pending operation.
+    /// If `operation_id` is known, only the pending operation with the same id is notified.
+    fn notify_on_apply(
+        &self,
+        operation: &ConsensusOperations,
+        operation_id: Option<OperationId>,
+        result: &Result<bool, StorageError>,
+    ) {
+        let pending_operation = {
+            let mut on_consensus_op_apply = self.on_consensus_op_apply.lock();
+            match on_consensus_op_apply.get(operation) {
+                Some(pending) if operation_id.map_or(true, |id| id == pending.id) => {
+                    on_consensus_op_apply.remove(operation)
+                }
+                _ => None,
+            }
+        };
+        for on_apply in pending_operation
+            .into_iter()
+            .flat_map(|pending| pending.on_apply)
+        {
             if on_apply.send(result.clone()).is_err() {
                 log::warn!("Failed to notify on consensus operation completion: channel receiver is dropped")
             }
         }
-        result
+    }
+
+    /// Id of the pending operation proposed by this peer, if any
+    pub fn pending_operation_id(&self, operation: &ConsensusOperations) -> Option<OperationId> {
+        self.on_consensus_op_apply
+            .lock()
+            .get(operation)
+            .map(|pending| pending.id)
+    }
+
+    /// Context of the Raft entry, which proposes `operation`.
+    /// Holds ids of pending operations, so that duplicate proposals are skipped on apply,
+    /// and id of this peer, so that the proposer is known in the audit log.
+    pub fn proposal_context(&self, operation: &ConsensusOperations) -> Vec<u8> {
+        let operation_ids = match operation {
+            ConsensusOperations::CollectionMetaBatch(operations) => operations
+                .iter()
+                .map(|operation| {
+                    self.pending_operation_id(&ConsensusOperations::CollectionMeta(Box::new(
+                        operation.clone(),
+                    )))
+                })
+                .collect(),
+            operation => vec![self.pending_operation_id(operation)],
+        };
+        proposal_to_context(&operation_ids, self.this_peer_id())
     }
 
     pub fn apply_snapshot(&self, snapshot: &raft::eraftpb::Snapshot) -> Result<(), StorageError> {
@@ -361,9 +683,13 @@ impl<C: CollectionContainer> ConsensusState<C> {
         let data: SnapshotData = snapshot.get_data().try_into()?;
         self.toc.apply_collections_snapshot(data.collections_data)?;
         self.wal.lock().0.clear()?;
-        self.persistent
-            .write()
-            .update_from_snapshot(meta, data.address_by_id)?;
+        self.persistent.write().update_from_snapshot(
+            meta,
+            data.address_by_id,
+            data.metadata_by_id,
+            data.draining_peers,
+            data.applied_operation_ids,
+        )?;
         Ok(())
     }
 
@@ -399,7 +725,7 @@ impl<C: CollectionContainer> ConsensusState<C> {
 
     pub fn remove_peer(&self, peer_id: PeerId) -> Result<(), StorageError> {
         self.toc.remove_peer(peer_id);
-        self.persistent.read().save()
+        self.persistent.write().remove_peer_state(peer_id)
     }
 
     pub async fn propose_consensus_op(
@@ -430,8 +756,17 @@ impl<C: CollectionContainer> ConsensusState<C> {
         let (sender, receiver) = oneshot::channel();
         {
             let mut on_apply_lock = self.on_consensus_op_apply.lock();
-            self.propose_sender.send(operation.clone())?;
-            on_apply_lock.insert(operation, sender);
+            // Retries of the pending operation are proposed with the same id,
+            // so that the operation is applied only once and every caller receives its result.
+            on_apply_lock
+                .entry(operation.clone())
+                .or_insert_with(|| PendingOperation {
+                    id: rand::random(),
+                    on_apply: vec![],
+                })
+                .on_apply
+                .push(sender);
+            self.propose_sender.send(operation)?;
         }
         tokio::time::timeout(wait_timeout, receiver)
             .await
@@ -446,6 +781,119 @@ impl<C: CollectionContainer> ConsensusState<C> {
             )??
     }
 
+    /// Wait until this peer applies all operations, which are committed at the moment of the call.
+    ///
+    /// Read index is confirmed by the leader, so that the local state read afterwards
+    /// is guaranteed to observe every operation committed before.
+    pub async fn await_read_index(
+        &self,
+        wait_timeout: Option<Duration>,
+    ) -> Result<(), StorageError> {
+        let wait_timeout = wait_timeout.unwrap_or(DEFAULT_META_OP_WAIT);
+        let deadline = tokio::time::Instant::now() + wait_timeout;
+
+        if !self
+            .is_leader_established
+            .await_ready_for_timeout(wait_timeout)
+        {
+            return Err(StorageError::service_error(&format!(
+                "Failed to request read index: leader is not established within {} secs",
+                wait_timeout.as_secs()
+            )));
+        }
+
+        let request_id = rand::random();
+        let (sender, receiver) = oneshot::channel();
+        self.on_read_index.lock().insert(request_id, sender);
+        if let Err(err) = self
+            .propose_sender
+            .send(ConsensusOperations::ReadIndex(request_id))
+        {
+            self.on_read_index.lock().remove(&request_id);
+            return Err(err);
+        }
+        let read_index = tokio::time::timeout_at(deadline, receiver).await;
+        self.on_read_index.lock().remove(&request_id);
+        let read_index = read_index
+            .map_err(|_: tokio::time::error::Elapsed| {
+                StorageError::service_error(&format!(
+                    "Read index is not confirmed by the leader within {} secs",
+                    wait_timeout.as_secs()
+                ))
+            })?
+            .map_err(|_| StorageError::service_error("Read index request is dropped"))?;
+
+        while self.last_applied_entry().unwrap_or(0) < read_index {
+            if tokio::time::Instant::now() >= deadline {
+                return Err(StorageError::service_error(&format!(
+                    "Operations up to read index {read_index} are not applied within {} secs",
+                    wait_timeout.as_secs()
+                )));
+            }
+            tokio::time::sleep(READ_INDEX_POLL_INTERVAL).await;
+        }
+        Ok(())
+    }
+
+    /// Transfer leadership from this peer to `peer_id` and wait until it becomes the leader.
+    ///
+    /// Allows to restart the leader without an election gap,
+    /// during which no operations can be proposed.
+    pub async fn transfer_leadership(
+        &self,
+        peer_id: PeerId,
+        wait_timeout: Option<Duration>,
+    ) -> Result<bool, StorageError> {
+        let wait_timeout = wait_timeout.unwrap_or(DEFAULT_META_OP_WAIT);
+        let this_peer_id = self.this_peer_id();
+        let leader = self.soft_state.read().as_ref().map(|state| state.leader_id);
+        if leader == Some(peer_id) {
+            return Ok(true);
+        }
+        if leader != Some(this_peer_id) {
+            return Err(StorageError::BadRequest {
+                description: format!(
+                    "Leadership can only be transferred by the leader, peer {this_peer_id} is not a leader"
+                ),
+            });
+        }
+        if !self.conf_state().get_voters().contains(&peer_id) {
+            return Err(StorageError::BadRequest {
+                description: format!("Peer {peer_id} is not a voter of the cluster"),
+            });
+        }
+
+        self.propose_sender
+            .send(ConsensusOperations::TransferLeadership(peer_id))?;
+
+        let deadline = tokio::time::Instant::now() + wait_timeout;
+        loop {
+            let leader = self.soft_state.read().as_ref().map(|state| state.leader_id);
+            if leader == Some(peer_id) {
+                return Ok(true);
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(StorageError::service_error(&format!(
+                    "Peer {peer_id} did not become a leader within {} secs",
+                    wait_timeout.as_secs()
+                )));
+            }
+            tokio::time::sleep(LEADER_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Notify the request of linearizable read, that its read index is confirmed by the leader
+    pub fn on_read_index(&self, request_context: &[u8], index: u64) {
+        let request_id = match operation_id_from_context(request_context) {
+            Some(request_id) => request_id,
+            None => return,
+        };
+        if let Some(sender) = self.on_read_index.lock().remove(&request_id) {
+            // Receiver might be dropped due to timeout
+            let _ = sender.send(index);
+        }
+    }
+
     pub fn peer_address_by_id(&self) -> PeerAddressById {
         self.persistent.read().peer_address_by_id()
     }
@@ -461,9 +909,169 @@ impl<C: CollectionContainer> ConsensusState<C> {
     pub fn last_applied_entry(&self) -> Option<u64> {
         self.persistent.read().last_applied_entry()
     }
+
+    /// Verify integrity of the consensus WAL, which might be broken by a torn write.
+    ///
+    /// Corrupted entries, which are not committed, are truncated, as the leader sends them again.
+    /// Committed entries, which are not applied yet, are only truncated if `force_repair` is set,
+    /// otherwise an error with the position of the corruption is returned.
+    /// Applied entries can't be repaired, the peer should be recovered from a snapshot instead.
+    pub fn check_wal_integrity(&self, force_repair: bool) -> Result<(), StorageError> {
+        let mut persistent = self.persistent.write();
+        let mut wal = self.wal.lock();
+        let expected_first_index = persistent.latest_snapshot_meta().index + 1;
+        let corrupted = match wal.find_corrupted_entry(expected_first_index) {
+            Some(corrupted) => corrupted,
+            None => return Ok(()),
+        };
+        let commit = persistent.state().hard_state.commit;
+        let last_applied = persistent.last_applied_entry().unwrap_or(0);
+        let report = format!(
+            "Consensus WAL is corrupted at entry {} (WAL position {}): {}. Commit index: {commit}, last applied entry: {last_applied}",
+            corrupted.raft_index, corrupted.wal_index, corrupted.description
+        );
+        if corrupted.raft_index > commit {
+            log::warn!("{report}. Truncating entries, which are not committed");
+        } else if corrupted.raft_index <= last_applied {
+            return Err(StorageError::service_error(&format!(
+                "{report}. Applied entries can't be repaired, recover the peer from a snapshot"
+            )));
+        } else if force_repair {
+            log::warn!(
+                "{report}. Truncating committed entries, they are received from the leader again"
+            );
+            persistent
+                .apply_state_update(|state| state.hard_state.commit = corrupted.raft_index - 1)?;
+        } else {
+            return Err(StorageError::service_error(&format!(
+                "{report}. Use `--repair-consensus-wal` to truncate committed entries"
+            )));
+        }
+        wal.truncate(corrupted.wal_index)
+    }
+
+    /// Unsafe recovery of a cluster, which permanently lost the quorum.
+    ///
+    /// Makes this peer the only voter and forgets all other peers, so that it elects itself
+    /// as the leader and continues with the local data. Entries, which are not committed, are
+    /// removed, as they might never be accepted by the old cluster.
+    /// Returns ids of the removed peers. Replicas of shards on them have to be recovered manually.
+    pub fn force_single_node_recovery(&self) -> Result<Vec<PeerId>, StorageError> {
+        let this_peer_id = self.this_peer_id();
+        {
+            let mut persistent = self.persistent.write();
+            let commit = persistent.state().hard_state.commit;
+            self.wal.lock().truncate_after(commit)?;
+            persistent.apply_state_update(|state| {
+                state.conf_state = raft::eraftpb::ConfState::from((vec![this_peer_id], vec![]))
+            })?;
+        }
+        let stale_peers: Vec<_> = self
+            .peer_address_by_id()
+            .into_keys()
+            .filter(|peer_id| *peer_id != this_peer_id)
+            .collect();
+        for peer_id in &stale_peers {
+            self.remove_peer(*peer_id)?;
+        }
+        log::warn!(
+            "Forced single node recovery: peer {this_peer_id} is the only voter now, removed peers: {stale_peers:?}"
+        );
+        Ok(stale_peers)
+    }
+
+    /// Remove applied entries from WAL, keeping `compact_wal_entries` latest of them,
+    /// so that slightly lagging peers can catch up without a snapshot.
+    ///
+    /// Unless `force` is set, compaction is postponed until at least `compact_wal_entries`
+    /// entries can be removed.
+    /// Returns `true` if some entries were compacted.
+    pub fn compact_wal(&self, force: bool) -> Result<bool, StorageError> {
+        let last_applied = match self.last_applied_entry() {
+            Some(last_applied) => last_applied,
+            None => return Ok(false),
+        };
+        let compact_until = last_applied.saturating_sub(self.compact_wal_entries);
+        let mut persistent = self.persistent.write();
+        let compacted_until = persistent.latest_snapshot_meta().index;
+        let min_entries_to_compact = if force {
+            1
+        } else {
+            cmp::max(self.compact_wal_entries, 1)
+        };
+        if compact_until < compacted_until + min_entries_to_compact {
+            return Ok(false);
+        }
+        let mut wal = self.wal.lock();
+        let term = wal.entry(compact_until)?.term;
+        // Persist compaction point first, so that the remaining WAL is consistent even on failure
+        persistent.set_compacted_until(compact_until, term)?;
+        wal.compact(compact_until + 1)?;
+        log::debug!("Compacted consensus WAL until entry {compact_until}");
+        Ok(true)
+    }
+}
+
+impl<C: CollectionContainer + Send + Sync + 'static> ConsensusState<C> {
+    /// Capture the consensus state and a copy of the collections state at the latest applied entry,
+    /// and serialize the snapshot in background.
+    /// The copy only holds metadata of collections, so entries continue to be applied meanwhile.
+    fn start_snapshot_build(&self) -> raft::Result<()> {
+        let index = match self.last_applied_entry() {
+            Some(index) => index,
+            None => {
+                return Err(raft::Error::Store(
+                    raft::StorageError::SnapshotTemporarilyUnavailable,
+                ))
+            }
+        };
+        let term = self.term(index)?;
+        let persistent = self.persistent.read();
+        let conf_state = persistent.state().conf_state.clone();
+        let address_by_id = persistent.peer_address_by_id();
+        let metadata_by_id = persistent.peer_metadata_by_id();
+        let draining_peers = persistent.draining_peers();
+        let applied_operation_ids = persistent.applied_operation_ids.clone();
+        drop(persistent);
+
+        let snapshot = SnapshotData {
+            collections_data: self.toc.collections_snapshot(),
+            address_by_id,
+            metadata_by_id,
+            draining_peers,
+            applied_operation_ids,
+        };
+        let snapshot_build = self.snapshot_build.clone();
+        log::debug!("Building consensus snapshot at entry {index}");
+        thread::Builder::new()
+            .name("consensus-snapshot".to_string())
+            .spawn(move || {
+                let guard = SnapshotBuildGuard::new(snapshot_build);
+                let build = match serde_cbor::to_vec(&snapshot) {
+                    Ok(data) => {
+                        log::debug!("Consensus snapshot at entry {index} is ready");
+                        SnapshotBuild::Ready(raft::eraftpb::Snapshot {
+                            data,
+                            metadata: Some(raft::eraftpb::SnapshotMetadata {
+                                conf_state: Some(conf_state),
+                                index,
+                                term,
+                            }),
+                        })
+                    }
+                    Err(err) => {
+                        log::error!("Failed to serialize consensus snapshot: {err}");
+                        SnapshotBuild::Idle
+                    }
+                };
+                guard.finish(build);
+            })
+            .map_err(raft_error_other)?;
+        Ok(())
+    }
 }
 
-impl<C: CollectionContainer> Storage for ConsensusState<C> {
+impl<C: CollectionContainer + Send + Sync + 'static> Storage for ConsensusState<C> {
     fn initial_state(&self) -> raft::Result<RaftState> {
         Ok(self.persistent.read().state.clone())
     }
@@ -500,9 +1108,11 @@ impl<C: CollectionContainer> Storage for ConsensusState<C> {
     }
 
     fn first_index(&self) -> raft::Result<u64> {
+        let compacted_until = self.persistent.read().latest_snapshot_meta().index;
         let index = match self.wal.lock().first_entry().map_err(raft_error_other)? {
-            Some(entry) => entry.index,
-            None => self.persistent.read().latest_snapshot_meta().index + 1,
+            // WAL might still contain some of the compacted entries
+            Some(entry) => cmp::max(entry.index, compacted_until + 1),
+            None => compacted_until + 1,
         };
         Ok(index)
     }
@@ -515,28 +1125,32 @@ impl<C: CollectionContainer> Storage for ConsensusState<C> {
         Ok(index)
     }
 
+    /// Snapshot is built in background, `SnapshotTemporarilyUnavailable` is returned until it is ready.
+    /// Ready snapshot is reused, while the peer receiving it is able to catch up with entries after it.
     fn snapshot(&self, request_index: u64, _to: u64) -> raft::Result<raft::eraftpb::Snapshot> {
-        let collections_data = self.toc.collections_snapshot();
-        let persistent = self.persistent.read();
-        let raft_state = persistent.state().clone();
-        if raft_state.hard_state.commit >= request_index {
-            let snapshot = SnapshotData {
-                collections_data,
-                address_by_id: persistent.peer_address_by_id(),
-            };
-            Ok(raft::eraftpb::Snapshot {
-                data: serde_cbor::to_vec(&snapshot).map_err(raft_error_other)?,
-                metadata: Some(raft::eraftpb::SnapshotMetadata {
-                    conf_state: Some(raft_state.conf_state),
-                    index: raft_state.hard_state.commit,
-                    term: raft_state.hard_state.term,
-                }),
-            })
-        } else {
-            Err(raft::Error::Store(
-                raft::StorageError::SnapshotTemporarilyUnavailable,
-            ))
+        let mut snapshot_build = self.snapshot_build.lock();
+        match &*snapshot_build {
+            SnapshotBuild::InProgress => {
+                return Err(raft::Error::Store(
+                    raft::StorageError::SnapshotTemporarilyUnavailable,
+                ))
+            }
+            SnapshotBuild::Ready(snapshot) => {
+                let index = snapshot.get_metadata().index;
+                if index >= request_index && index + 1 >= self.first_index()? {
+                    return Ok(snapshot.clone());
+                }
+            }
+            SnapshotBuild::Idle => {}
+        }
+        *snapshot_build = SnapshotBuild::InProgress;
+        if let Err(err) = self.start_snapshot_build() {
+            *snapshot_build = SnapshotBuild::Idle;
+            return Err(err);
         }
+        Err(raft::Error::Store(
+            raft::StorageError::SnapshotTemporarilyUnavailable,
+        ))
     }
 }
 
@@ -589,6 +1203,25 @@ impl Storage for ConsensusStateRef {
     }
 }
 
+/// Human-readable content of the entry, `None` for empty entries or unknown content
+fn describe_entry(entry: &RaftEntry) -> Option<String> {
+    use raft::eraftpb::EntryType;
+
+    if entry.data.is_empty() {
+        return None;
+    }
+    match entry.get_entry_type() {
+        EntryType::EntryNormal => ConsensusOperations::try_from(entry)
+            .ok()
+            .map(|operation| format!("{operation:?}")),
+        EntryType::EntryConfChangeV2 => {
+            let change: Option<ConfChangeV2> = prost::Message::decode(entry.get_data()).ok();
+            change.map(|change| format!("{change:?}"))
+        }
+        EntryType::EntryConfChange => None,
+    }
+}
+
 pub fn raft_error_other(e: impl std::error::Error) -> raft::Error {
     #[derive(thiserror::Error, Debug)]
     #[error("{0}")]
@@ -602,16 +1235,25 @@ mod tests {
     use std::sync::{mpsc, Arc};
 
     use collection::shard::PeerId;
+    use parking_lot::Mutex;
     use proptest::prelude::*;
-    use raft::eraftpb::Entry;
+    use raft::eraftpb::{ConfState, Entry};
     use raft::storage::{MemStorage, Storage};
+    use raft::GetEntriesContext;
     use tempfile::Builder;
 
-    use super::ConsensusState;
+    use super::{ConsensusState, SnapshotBuild, SnapshotBuildGuard};
+    use crate::content_manager::collection_meta_ops::{
+        CollectionMetaOperations, DeleteCollectionOperation,
+    };
     use crate::content_manager::consensus::consensus_wal::ConsensusOpWal;
     use crate::content_manager::consensus::entry_queue::EntryApplyProgressQueue;
     use crate::content_manager::consensus::operation_sender::OperationSender;
     use crate::content_manager::consensus::persistent::Persistent;
+    use crate::content_manager::consensus_ops::{
+        operation_id_to_context, proposal_to_context, ConsensusOperations, OperationId,
+        OPERATION_DEDUP_WINDOW,
+    };
     use crate::content_manager::CollectionContainer;
 
     #[test]
@@ -706,6 +1348,325 @@ mod tests {
         assert_eq!(wal.entries(4, 5, Some(0)).unwrap().len(), 1)
     }
 
+    #[test]
+    fn repair_corrupted_wal() {
+        let dir = Builder::new().prefix("raft_state_test").tempdir().unwrap();
+        let entries = (1..=5)
+            .map(|index| Entry {
+                index,
+                term: 1,
+                ..Default::default()
+            })
+            .collect();
+        let (consensus_state, _) = setup_storages(entries, dir.path());
+        assert!(consensus_state.check_wal_integrity(false).is_ok());
+
+        // Torn write at the end of WAL
+        consensus_state
+            .wal
+            .lock()
+            .0
+            .append(&b"garbage".to_vec())
+            .unwrap();
+        let corrupted = consensus_state.wal.lock().find_corrupted_entry(1).unwrap();
+        assert_eq!(corrupted.raft_index, 6);
+
+        // Committed entries are not truncated without explicit repair
+        consensus_state.set_commit_index(6).unwrap();
+        assert!(consensus_state.check_wal_integrity(false).is_err());
+        assert_eq!(consensus_state.wal.lock().0.num_entries(), 6);
+
+        consensus_state.check_wal_integrity(true).unwrap();
+        assert_eq!(consensus_state.last_index().unwrap(), 5);
+        assert_eq!(consensus_state.hard_state().commit, 5);
+        assert!(consensus_state.check_wal_integrity(false).is_ok());
+    }
+
+    #[test]
+    fn force_single_node_recovery() {
+        let dir = Builder::new().prefix("raft_state_test").tempdir().unwrap();
+        let entries = (1..=5)
+            .map(|index| Entry {
+                index,
+                term: 1,
+                ..Default::default()
+            })
+            .collect();
+        let (consensus_state, _) = setup_storages(entries, dir.path());
+        let this_peer_id = consensus_state.this_peer_id();
+        consensus_state
+            .add_peer(this_peer_id, "http://127.0.0.1:6335".parse().unwrap())
+            .unwrap();
+        consensus_state
+            .add_peer(this_peer_id + 1, "http://127.0.0.2:6335".parse().unwrap())
+            .unwrap();
+        consensus_state
+            .set_conf_state(ConfState::from((
+                vec![this_peer_id, this_peer_id + 1],
+                vec![],
+            )))
+            .unwrap();
+        consensus_state.set_commit_index(3).unwrap();
+
+        let removed_peers = consensus_state.force_single_node_recovery().unwrap();
+        assert_eq!(removed_peers, vec![this_peer_id + 1]);
+        assert_eq!(consensus_state.conf_state().voters, vec![this_peer_id]);
+        // Entries, which are not committed, are removed
+        assert_eq!(consensus_state.last_index().unwrap(), 3);
+        assert_eq!(consensus_state.hard_state().commit, 3);
+    }
+
+    #[test]
+    fn snapshot_is_built_in_background() {
+        let dir = Builder::new().prefix("raft_state_test").tempdir().unwrap();
+        let entries = (1..=5)
+            .map(|index| Entry {
+                index,
+                term: index / 3 + 1,
+                ..Default::default()
+            })
+            .collect();
+        let (consensus_state, _) = setup_storages(entries, dir.path());
+        consensus_state.set_unapplied_entries(1, 5).unwrap();
+        for _ in 0..3 {
+            consensus_state.persistent.write().entry_applied().unwrap();
+        }
+
+        let unavailable = raft::Error::Store(raft::StorageError::SnapshotTemporarilyUnavailable);
+        assert_eq!(consensus_state.snapshot(0, 2).unwrap_err(), unavailable);
+        let snapshot = loop {
+            match consensus_state.snapshot(0, 2) {
+                Ok(snapshot) => break snapshot,
+                Err(err) => assert_eq!(err, unavailable),
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+        // Snapshot is taken at the latest applied entry
+        assert_eq!(snapshot.get_metadata().index, 3);
+        assert_eq!(snapshot.get_metadata().term, 2);
+        // Ready snapshot is reused
+        assert_eq!(consensus_state.snapshot(0, 2).unwrap(), snapshot);
+        // Newer snapshot is built, if requested
+        assert_eq!(consensus_state.snapshot(4, 2).unwrap_err(), unavailable);
+    }
+
+    #[test]
+    fn failed_snapshot_build_is_reset() {
+        let snapshot_build = Arc::new(Mutex::new(SnapshotBuild::InProgress));
+        let build = snapshot_build.clone();
+        let result = std::thread::spawn(move || {
+            let _guard = SnapshotBuildGuard::new(build);
+            panic!("snapshot build failed");
+        })
+        .join();
+        assert!(result.is_err());
+        assert!(matches!(*snapshot_build.lock(), SnapshotBuild::Idle));
+    }
+
+    #[test]
+    fn consensus_copy_is_restorable() {
+        let dir = Builder::new().prefix("raft_state_test").tempdir().unwrap();
+        let copy_dir = Builder::new().prefix("raft_state_copy").tempdir().unwrap();
+        let entries: Vec<_> = (1..=5)
+            .map(|index| Entry {
+                index,
+                term: 1,
+                ..Default::default()
+            })
+            .collect();
+        let (consensus_state, _) = setup_storages(entries.clone(), dir.path());
+        consensus_state.set_unapplied_entries(1, 5).unwrap();
+        consensus_state.persistent.write().entry_applied().unwrap();
+
+        consensus_state
+            .save_consensus_copy(copy_dir.path())
+            .unwrap();
+
+        let (restored_state, _) = setup_storages(vec![], copy_dir.path());
+        assert_eq!(
+            restored_state.this_peer_id(),
+            consensus_state.this_peer_id()
+        );
+        assert_eq!(
+            restored_state.persistent.read().current_unapplied_entry(),
+            Some(2)
+        );
+        assert_eq!(
+            restored_state
+                .entries(1, 6, None, GetEntriesContext::empty(false))
+                .unwrap(),
+            entries
+        );
+    }
+
+    #[test]
+    fn compact_applied_entries() {
+        let dir = Builder::new().prefix("raft_state_test").tempdir().unwrap();
+        let entries = (1..=50)
+            .map(|index| Entry {
+                index,
+                term: 1,
+                ..Default::default()
+            })
+            .collect();
+        let (consensus_state, _) = setup_storages(entries, dir.path());
+        consensus_state.set_unapplied_entries(1, 50).unwrap();
+        let apply = |count| {
+            for _ in 0..count {
+                consensus_state.persistent.write().entry_applied().unwrap();
+            }
+        };
+        apply(45);
+
+        // Latest 10 applied entries are retained
+        assert!(consensus_state.compact_wal(false).unwrap());
+        assert_eq!(consensus_state.first_index().unwrap(), 36);
+        assert_eq!(consensus_state.last_index().unwrap(), 50);
+        assert_eq!(consensus_state.term(35).unwrap(), 1);
+        assert_eq!(
+            consensus_state.entries(30, 40, None, GetEntriesContext::empty(false)),
+            Err(raft::Error::Store(raft::StorageError::Compacted))
+        );
+        assert_eq!(
+            consensus_state
+                .entries(36, 51, None, GetEntriesContext::empty(false))
+                .unwrap()
+                .len(),
+            15
+        );
+
+        // Automatic compaction waits for enough entries, forced one does not
+        apply(3);
+        assert!(!consensus_state.compact_wal(false).unwrap());
+        assert!(consensus_state.compact_wal(true).unwrap());
+        assert_eq!(consensus_state.first_index().unwrap(), 39);
+        assert!(!consensus_state.compact_wal(true).unwrap());
+    }
+
+    #[test]
+    fn debug_info_reports_unapplied_entries() {
+        let dir = Builder::new().prefix("raft_state_test").tempdir().unwrap();
+        let mut entries: Vec<_> = (1..=3)
+            .map(|index| Entry {
+                index,
+                term: 1,
+                ..Default::default()
+            })
+            .collect();
+        entries[2].data = serde_cbor::to_vec(&ConsensusOperations::DrainPeer(1)).unwrap();
+        let (consensus_state, _) = setup_storages(entries, dir.path());
+        consensus_state.set_unapplied_entries(1, 3).unwrap();
+        consensus_state.persistent.write().entry_applied().unwrap();
+
+        let debug_info = consensus_state.debug_info().unwrap();
+        assert_eq!(debug_info.first_index, 1);
+        assert_eq!(debug_info.last_index, 3);
+        assert_eq!(debug_info.last_applied, Some(1));
+        assert_eq!(debug_info.unapplied_entries_count, 2);
+        let unapplied: Vec<_> = debug_info
+            .unapplied_entries
+            .iter()
+            .map(|entry| (entry.index, entry.content.clone()))
+            .collect();
+        assert_eq!(
+            unapplied,
+            vec![(2, None), (3, Some("DrainPeer(1)".to_string()))]
+        );
+        // Progress of peers is only known by the leader
+        assert!(debug_info.peers_progress.is_none());
+    }
+
+    #[test]
+    fn skip_duplicate_operations() {
+        let dir = Builder::new().prefix("raft_state_test").tempdir().unwrap();
+        let (consensus_state, _) = setup_storages(vec![], dir.path());
+        let entry = |operation_id: Option<OperationId>| Entry {
+            data: serde_cbor::to_vec(&ConsensusOperations::DrainPeer(1)).unwrap(),
+            context: operation_id
+                .map(operation_id_to_context)
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+
+        assert!(consensus_state
+            .apply_normal_entry(&entry(Some(42)))
+            .unwrap());
+        // Repeated proposal of the same operation is skipped
+        assert!(!consensus_state
+            .apply_normal_entry(&entry(Some(42)))
+            .unwrap());
+        // Same operation proposed separately is applied
+        assert!(consensus_state
+            .apply_normal_entry(&entry(Some(43)))
+            .unwrap());
+        assert!(consensus_state.apply_normal_entry(&entry(None)).unwrap());
+        assert!(consensus_state.apply_normal_entry(&entry(None)).unwrap());
+
+        // Only the latest operations are remembered
+        for operation_id in 100..100 + OPERATION_DEDUP_WINDOW as u64 {
+            consensus_state
+                .persistent
+                .write()
+                .operation_applied(operation_id);
+        }
+        assert!(consensus_state
+            .apply_normal_entry(&entry(Some(42)))
+            .unwrap());
+    }
+
+    #[test]
+    fn batch_operations() {
+        let delete = |name: &str| {
+            CollectionMetaOperations::DeleteCollection(DeleteCollectionOperation(name.to_string()))
+        };
+        let batched = ConsensusOperations::batch(vec![
+            ConsensusOperations::CollectionMeta(Box::new(delete("a"))),
+            ConsensusOperations::CollectionMeta(Box::new(delete("b"))),
+            ConsensusOperations::DrainPeer(1),
+            ConsensusOperations::CollectionMeta(Box::new(delete("c"))),
+        ]);
+        assert_eq!(
+            batched,
+            vec![
+                ConsensusOperations::CollectionMetaBatch(vec![delete("a"), delete("b")]),
+                ConsensusOperations::DrainPeer(1),
+                ConsensusOperations::CollectionMeta(Box::new(delete("c"))),
+            ]
+        );
+
+        let dir = Builder::new().prefix("raft_state_test").tempdir().unwrap();
+        let (consensus_state, _) = setup_storages(vec![], dir.path());
+        let batch_entry = Entry {
+            index: 1,
+            data: serde_cbor::to_vec(&batched[0]).unwrap(),
+            context: proposal_to_context(&[Some(7), None], 3),
+            ..Default::default()
+        };
+        assert!(consensus_state.apply_normal_entry(&batch_entry).unwrap());
+        assert!(consensus_state.persistent.read().is_operation_applied(7));
+
+        // Operation of the batch is not applied again, if proposed separately
+        let entry = Entry {
+            index: 2,
+            data: serde_cbor::to_vec(&ConsensusOperations::CollectionMeta(Box::new(delete("a"))))
+                .unwrap(),
+            context: operation_id_to_context(7),
+            ..Default::default()
+        };
+        assert!(!consensus_state.apply_normal_entry(&entry).unwrap());
+
+        // Both operations of the batch are recorded with the proposer, skipped duplicate is not
+        let audit_records = consensus_state.audit_records(0, 100).unwrap();
+        let audited: Vec<_> = audit_records
+            .iter()
+            .map(|record| (record.index, record.proposer, record.result))
+            .collect();
+        assert_eq!(
+            audited,
+            vec![(1, Some(3), Some(true)), (1, Some(3), Some(true))]
+        );
+    }
+
     struct NoCollections;
 
     impl CollectionContainer for NoCollections {
@@ -732,6 +1693,8 @@ mod tests {
         }
 
         fn remove_peer(&self, _peer_id: PeerId) {}
+
+        fn drain_peer(&self, _peer_id: PeerId) {}
     }
 
     fn setup_storages(
@@ -745,6 +1708,7 @@ mod tests {
             Arc::new(NoCollections),
             OperationSender::new(sender),
             path.to_str().unwrap(),
+            10,
         );
         let mem_storage = MemStorage::new();
         mem_storage.wl().append(entries.as_ref()).unwrap();
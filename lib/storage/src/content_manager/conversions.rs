@@ -12,13 +12,17 @@ use crate::content_manager::collection_meta_ops::{
 use crate::content_manager::errors::StorageError;
 
 pub fn error_to_status(error: StorageError) -> tonic::Status {
-    let error_code = match &error {
+    tonic::Status::new(error_code(&error), format!("{}", error))
+}
+
+fn error_code(error: &StorageError) -> tonic::Code {
+    match error {
         StorageError::BadInput { .. } => tonic::Code::InvalidArgument,
         StorageError::NotFound { .. } => tonic::Code::NotFound,
         StorageError::ServiceError { .. } => tonic::Code::Internal,
         StorageError::BadRequest { .. } => tonic::Code::InvalidArgument,
-    };
-    tonic::Status::new(error_code, format!("{}", error))
+        StorageError::PartialUpdate { error, .. } => error_code(error),
+    }
 }
 
 impl TryFrom<api::grpc::qdrant::CreateCollection> for CollectionMetaOperations {
@@ -48,11 +52,47 @@ impl TryFrom<api::grpc::qdrant::CreateCollection> for CollectionMetaOperations {
                     },
                     None => return Err(Status::invalid_argument("vectors config is required")),
                 },
+                sparse_vectors: value.sparse_vectors_config.map(|config| {
+                    config
+                        .map
+                        .into_iter()
+                        .map(|(name, params)| (name, params.into()))
+                        .collect()
+                }),
                 hnsw_config: value.hnsw_config.map(|v| v.into()),
+                ivf_config: value.ivf_config.map(|v| v.into()),
+                placement: value.placement.map(|v| v.into()),
+                read_fan_out_factor: value.read_fan_out_factor,
+                write_consistency: value
+                    .write_consistency
+                    .and_then(api::grpc::qdrant::WriteConsistency::from_i32)
+                    .map(|write_consistency| write_consistency.into()),
+                read_consistency: value
+                    .read_consistency
+                    .and_then(api::grpc::qdrant::ReadConsistency::from_i32)
+                    .map(|read_consistency| read_consistency.into()),
+                hash_ring: value
+                    .hash_ring
+                    .and_then(api::grpc::qdrant::HashRingType::from_i32)
+                    .map(|hash_ring| hash_ring.into()),
                 wal_config: value.wal_config.map(|v| v.into()),
                 optimizers_config: value.optimizers_config.map(|v| v.into()),
                 shard_number: value.shard_number,
                 on_disk_payload: value.on_disk_payload,
+                max_response_payload_bytes: value
+                    .max_response_payload_bytes
+                    .map(|bytes| bytes as usize),
+                payload_transformers: if value.payload_transformers.is_empty() {
+                    None
+                } else {
+                    Some(
+                        value
+                            .payload_transformers
+                            .into_iter()
+                            .map(|transformer| transformer.try_into())
+                            .collect::<Result<_, Status>>()?,
+                    )
+                },
             },
         }))
     }
@@ -66,6 +106,8 @@ impl TryFrom<api::grpc::qdrant::UpdateCollection> for CollectionMetaOperations {
             collection_name: value.collection_name,
             update_collection: UpdateCollection {
                 optimizers_config: value.optimizers_config.map(|v| v.into()),
+                params: value.params.map(|v| v.try_into()).transpose()?,
+                expected_version: value.expected_version,
             },
         }))
     }
@@ -1,6 +1,6 @@
 use std::io::Error as IoError;
 
-use collection::operations::types::CollectionError;
+use collection::operations::types::{CollectionError, ShardUpdateResult};
 use segment::common::file_operations::FileStorageError;
 use thiserror::Error;
 
@@ -15,6 +15,12 @@ pub enum StorageError {
     ServiceError { description: String },
     #[error("Bad request: {description}")]
     BadRequest { description: String },
+    /// Update failed in some of the shards only, `error` describes the failure
+    #[error("{error}")]
+    PartialUpdate {
+        error: Box<StorageError>,
+        shard_results: Vec<ShardUpdateResult>,
+    },
 }
 
 impl StorageError {
@@ -78,9 +84,19 @@ impl From<CollectionError> for StorageError {
             CollectionError::Cancelled { description } => StorageError::ServiceError {
                 description: format!("Operation cancelled: {description}"),
             },
-            CollectionError::InconsistentShardFailure { ref first_err, .. } => {
+            CollectionError::InconsistentShardFailure {
+                ref first_err,
+                ref shard_results,
+                ..
+            } => {
                 let full_description = format!("{}", &err);
-                StorageError::from_inconsistent_shard_failure(*first_err.clone(), full_description)
+                StorageError::PartialUpdate {
+                    error: Box::new(StorageError::from_inconsistent_shard_failure(
+                        *first_err.clone(),
+                        full_description,
+                    )),
+                    shard_results: shard_results.clone(),
+                }
             }
             CollectionError::BadShardSelection { description } => {
                 StorageError::BadRequest { description }
@@ -11,25 +11,94 @@ pub mod consensus;
 pub mod consensus_state;
 pub mod conversions;
 pub mod errors;
+pub mod replica_verification;
 pub mod shard_distribution;
 pub mod snapshots;
 pub mod toc;
 
 pub mod consensus_ops {
-    use collection::shard::{CollectionId, PeerId, ShardTransfer};
+    use collection::config::PeerMetadata;
+    use collection::shard::replica_set::ReplicaState;
+    use collection::shard::{CollectionId, PeerId, ShardId, ShardTransfer};
     use raft::eraftpb::Entry as RaftEntry;
     use serde::{Deserialize, Serialize};
 
     use crate::content_manager::collection_meta_ops::{
-        CollectionMetaOperations, ShardTransferOperations,
+        CollectionMetaOperations, SetShardReplicaState, ShardTransferOperations,
     };
 
+    /// Id of a proposed operation, passed in the context of the Raft entry.
+    /// Repeated proposals of the same pending operation share the id, so that it is applied only once.
+    pub type OperationId = u64;
+
+    /// Number of latest applied operation ids, which are checked for duplicates
+    pub const OPERATION_DEDUP_WINDOW: usize = 128;
+
+    pub fn operation_id_to_context(id: OperationId) -> Vec<u8> {
+        id.to_le_bytes().to_vec()
+    }
+
+    /// Entries proposed without operation id have empty context
+    pub fn operation_id_from_context(context: &[u8]) -> Option<OperationId> {
+        let bytes: [u8; 8] = context.try_into().ok()?;
+        Some(OperationId::from_le_bytes(bytes))
+    }
+
+    /// Max number of collection meta operations, proposed in a single Raft entry
+    pub const MAX_PROPOSAL_BATCH: usize = 64;
+
+    /// Context of a proposed entry holds ids of all its operations, followed by the id of the proposer peer.
+    /// `0` stands for operation without id.
+    pub fn proposal_to_context(ids: &[Option<OperationId>], proposer: PeerId) -> Vec<u8> {
+        ids.iter()
+            .map(|id| id.unwrap_or(0))
+            .chain([proposer])
+            .flat_map(u64::to_le_bytes)
+            .collect()
+    }
+
+    /// Entries proposed by previous versions have no proposer in the context
+    /// and have either empty context or only operation ids.
+    pub fn operation_ids_from_context(
+        context: &[u8],
+        operations_count: usize,
+    ) -> Vec<Option<OperationId>> {
+        if context.len() != operations_count * 8 && context.len() != (operations_count + 1) * 8 {
+            return vec![None; operations_count];
+        }
+        context
+            .chunks_exact(8)
+            .take(operations_count)
+            .map(|chunk| operation_id_from_context(chunk).filter(|id| *id != 0))
+            .collect()
+    }
+
+    pub fn proposer_from_context(context: &[u8], operations_count: usize) -> Option<PeerId> {
+        if context.len() != (operations_count + 1) * 8 {
+            return None;
+        }
+        let bytes: [u8; 8] = context[operations_count * 8..].try_into().ok()?;
+        Some(PeerId::from_le_bytes(bytes))
+    }
+
     /// Operation that should pass consensus
     #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Clone)]
     pub enum ConsensusOperations {
         CollectionMeta(Box<CollectionMetaOperations>),
+        /// Multiple collection meta operations, proposed in a single Raft entry and applied in order
+        CollectionMetaBatch(Vec<CollectionMetaOperations>),
         AddPeer(PeerId, String),
         RemovePeer(PeerId),
+        /// Set location labels of the peer
+        UpdatePeerMetadata(PeerId, PeerMetadata),
+        /// Move all shards away from the peer and remove it from the cluster afterwards
+        DrainPeer(PeerId),
+        /// Request the commit index confirmed by the leader, used for linearizable reads.
+        /// It is handled by the consensus thread and never proposed as an entry.
+        ReadIndex(u64),
+        /// Transfer leadership of this peer to another one, e.g. before maintenance.
+        /// It is handled by the consensus thread and never proposed as an entry.
+        TransferLeadership(PeerId),
     }
 
     impl TryFrom<&RaftEntry> for ConsensusOperations {
@@ -41,6 +110,45 @@ pub mod consensus_ops {
     }
 
     impl ConsensusOperations {
+        /// Combine consecutive collection meta operations into batches,
+        /// so that each batch passes consensus as a single entry.
+        /// Order of operations is preserved.
+        pub fn batch(operations: Vec<ConsensusOperations>) -> Vec<ConsensusOperations> {
+            let mut batched = vec![];
+            let mut batch = vec![];
+            let flush = |batch: &mut Vec<CollectionMetaOperations>,
+                         batched: &mut Vec<ConsensusOperations>| {
+                match batch.len() {
+                    0 => {}
+                    1 => batched.push(ConsensusOperations::CollectionMeta(Box::new(
+                        batch.pop().unwrap(),
+                    ))),
+                    _ => batched.push(ConsensusOperations::CollectionMetaBatch(std::mem::take(
+                        batch,
+                    ))),
+                }
+            };
+            for operation in operations {
+                match operation {
+                    ConsensusOperations::CollectionMeta(operation)
+                        if batch.len() < MAX_PROPOSAL_BATCH =>
+                    {
+                        batch.push(*operation)
+                    }
+                    ConsensusOperations::CollectionMeta(operation) => {
+                        flush(&mut batch, &mut batched);
+                        batch.push(*operation);
+                    }
+                    operation => {
+                        flush(&mut batch, &mut batched);
+                        batched.push(operation);
+                    }
+                }
+            }
+            flush(&mut batch, &mut batched);
+            batched
+        }
+
         pub fn abort_transfer(
             collection_id: CollectionId,
             transfer: ShardTransfer,
@@ -55,6 +163,29 @@ pub mod consensus_ops {
             )))
         }
 
+        pub fn start_transfer(collection_id: CollectionId, transfer: ShardTransfer) -> Self {
+            ConsensusOperations::CollectionMeta(Box::new(CollectionMetaOperations::TransferShard(
+                collection_id,
+                ShardTransferOperations::Start(transfer),
+            )))
+        }
+
+        pub fn set_replica_state(
+            collection_name: CollectionId,
+            shard_id: ShardId,
+            peer_id: PeerId,
+            state: ReplicaState,
+        ) -> Self {
+            ConsensusOperations::CollectionMeta(Box::new(
+                CollectionMetaOperations::SetShardReplicaState(SetShardReplicaState {
+                    collection_name,
+                    shard_id,
+                    peer_id,
+                    state,
+                }),
+            ))
+        }
+
         pub fn finish_transfer(collection_id: CollectionId, transfer: ShardTransfer) -> Self {
             ConsensusOperations::CollectionMeta(Box::new(CollectionMetaOperations::TransferShard(
                 collection_id,
@@ -79,4 +210,7 @@ pub trait CollectionContainer {
     fn peer_has_shards(&self, peer_id: PeerId) -> bool;
 
     fn remove_peer(&self, peer_id: PeerId);
+
+    /// Start moving shards away from the peer, which is being drained
+    fn drain_peer(&self, peer_id: PeerId);
 }
@@ -0,0 +1,70 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use collection::shard::{CollectionId, ShardTransfer};
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+
+use crate::content_manager::toc::TableOfContent;
+
+/// Background job, which periodically compares checksums of shard replicas
+/// and syncs the replicas, which silently diverged from the others, e.g. missed updates.
+pub struct ReplicaVerifier {
+    toc: Arc<TableOfContent>,
+    interval: Duration,
+    /// Repairs found in the previous round.
+    /// Checksums may differ for a moment due to in-flight updates, so a replica is only repaired
+    /// if it is found diverged in two rounds in a row.
+    suspected: HashSet<(CollectionId, ShardTransfer)>,
+}
+
+impl ReplicaVerifier {
+    pub fn new(toc: Arc<TableOfContent>, interval: Duration) -> Self {
+        Self {
+            toc,
+            interval,
+            suspected: Default::default(),
+        }
+    }
+
+    /// Run verification rounds every `interval`. Does nothing if `interval` is zero.
+    pub fn run(mut self, runtime: &Handle) -> Option<JoinHandle<()>> {
+        if self.interval.is_zero() {
+            return None;
+        }
+        let handle = runtime.spawn(async move {
+            loop {
+                tokio::time::sleep(self.interval).await;
+                self.verify().await;
+            }
+        });
+        Some(handle)
+    }
+
+    async fn verify(&mut self) {
+        let repairs: HashSet<_> = self
+            .toc
+            .suggest_replica_repairs()
+            .await
+            .into_iter()
+            .collect();
+        for (collection_name, transfer) in repairs.intersection(&self.suspected) {
+            log::warn!(
+                "Replica of shard {} of collection {} on peer {} diverged, syncing it from peer {}",
+                transfer.shard_id,
+                collection_name,
+                transfer.to,
+                transfer.from
+            );
+            if let Err(err) = self
+                .toc
+                .propose_replica_repair(collection_name.clone(), transfer.clone())
+            {
+                log::error!("Can't propose repair of replica: {err}");
+            }
+        }
+        // Confirmed repairs are proposed, others are re-checked in the next round
+        self.suspected = repairs.difference(&self.suspected).cloned().collect();
+    }
+}
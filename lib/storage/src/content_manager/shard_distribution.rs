@@ -1,10 +1,10 @@
-use std::cmp::Reverse;
-use std::collections::BinaryHeap;
-
+use collection::config::{colocated_replicas, PeerMetadataById, PlacementConstraints};
 use collection::shard::{PeerId, ShardId};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::content_manager::errors::StorageError;
+
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
 struct PeerShardCount {
     shard_count: usize, // self.shard_count and other.shard_count are compared first to determine eq & ord
@@ -31,37 +31,59 @@ pub struct ShardDistributionProposal {
 
 impl ShardDistributionProposal {
     /// Builds a proposal for the distribution of shards.
-    /// It will propose to allocate shards so that all peers have the same number of shards at the end.
+    /// It will propose to allocate shards so that all peers have the same number of shards at the end,
+    /// as far as `placement` constraints allow.
+    /// Peers in zones and racks without replicas of the shard are preferred, according to `peer_metadata`.
     pub fn new(
         config_shard_number: u32,
         known_peers: &[PeerId],
         known_shards: Vec<(ShardId, PeerId)>,
-    ) -> Self {
-        // min number of shard_count on top to make this a min-heap
-        let mut min_heap: BinaryHeap<Reverse<PeerShardCount>> =
-            BinaryHeap::with_capacity(known_peers.len());
-
+        placement: &PlacementConstraints,
+        peer_metadata: &PeerMetadataById,
+    ) -> Result<Self, StorageError> {
         // count number of existing shards per peers
-        for &peer in known_peers {
-            let shard_count_on_peer = known_shards
-                .iter()
-                .filter(|(_shard_id, peer_id)| *peer_id == peer)
-                .count();
-            min_heap.push(Reverse(PeerShardCount::new(shard_count_on_peer, peer)))
-        }
+        let mut peer_shard_counts: Vec<PeerShardCount> = known_peers
+            .iter()
+            .map(|&peer| {
+                let shard_count_on_peer = known_shards
+                    .iter()
+                    .filter(|(_shard_id, peer_id)| *peer_id == peer)
+                    .count();
+                PeerShardCount::new(shard_count_on_peer, peer)
+            })
+            .collect();
 
         let mut distribution: Vec<(ShardId, PeerId)> =
             Vec::with_capacity(config_shard_number as usize);
 
-        // propose the peer with the least amount of existing shards to host the next shard
+        // propose the allowed peer with the least amount of existing shards to host the next shard
         for shard_id in 0..config_shard_number {
-            let mut least_loaded_peer = min_heap.peek_mut().unwrap();
-            let selected_peer = least_loaded_peer.0.peer_id;
-            least_loaded_peer.0.inc_shard_count();
-            distribution.push((shard_id, selected_peer));
+            let shard_peers: Vec<PeerId> = known_shards
+                .iter()
+                .filter(|(known_shard_id, _peer_id)| *known_shard_id == shard_id)
+                .map(|(_shard_id, peer_id)| *peer_id)
+                .collect();
+            let least_loaded_peer = peer_shard_counts
+                .iter_mut()
+                .filter(|count| {
+                    !shard_peers.contains(&count.peer_id)
+                        && placement.allows(count.peer_id, &shard_peers)
+                })
+                .min_by(|a, b| {
+                    let a_colocated = colocated_replicas(peer_metadata, a.peer_id, &shard_peers);
+                    let b_colocated = colocated_replicas(peer_metadata, b.peer_id, &shard_peers);
+                    a_colocated.cmp(&b_colocated).then_with(|| a.cmp(b))
+                })
+                .ok_or_else(|| StorageError::BadInput {
+                    description: format!(
+                        "No peer satisfies placement constraints for shard {shard_id}"
+                    ),
+                })?;
+            least_loaded_peer.inc_shard_count();
+            distribution.push((shard_id, least_loaded_peer.peer_id));
         }
 
-        Self { distribution }
+        Ok(Self { distribution })
     }
 
     pub fn local_shards_for(&self, peer_id: PeerId) -> Vec<ShardId> {
@@ -90,12 +112,21 @@ impl ShardDistributionProposal {
 
 #[cfg(test)]
 mod tests {
+    use collection::config::PeerMetadata;
+
     use super::*;
 
     #[test]
     fn test_distribution() {
         let known_peers = vec![1, 2, 3, 4];
-        let distribution = ShardDistributionProposal::new(6, &known_peers, vec![]);
+        let distribution = ShardDistributionProposal::new(
+            6,
+            &known_peers,
+            vec![],
+            &Default::default(),
+            &Default::default(),
+        )
+        .unwrap();
 
         // Check it distribution is as even as possible
         let mut shard_counts: Vec<usize> = vec![0; known_peers.len()];
@@ -113,4 +144,75 @@ mod tests {
         assert_eq!(shard_counts.iter().min(), Some(&1));
         assert_eq!(shard_counts.iter().max(), Some(&2));
     }
+
+    #[test]
+    fn test_distribution_placement() {
+        let known_peers = vec![1, 2, 3, 4];
+        let placement = PlacementConstraints {
+            failure_domains: [(1, "a"), (2, "a"), (3, "b")]
+                .into_iter()
+                .map(|(peer_id, domain)| (peer_id, domain.to_string()))
+                .collect(),
+            excluded_peers: [4].into(),
+        };
+
+        // shard 0 already has a replica on peer 1, so it only fits peer 3 of another domain
+        let distribution = ShardDistributionProposal::new(
+            2,
+            &known_peers,
+            vec![(0, 1)],
+            &placement,
+            &Default::default(),
+        )
+        .unwrap();
+        assert_eq!(distribution.distribution, vec![(0, 3), (1, 2)]);
+
+        let placement = PlacementConstraints {
+            failure_domains: Default::default(),
+            excluded_peers: known_peers.iter().copied().collect(),
+        };
+        assert!(ShardDistributionProposal::new(
+            1,
+            &known_peers,
+            vec![],
+            &placement,
+            &Default::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_distribution_zones() {
+        let known_peers = vec![1, 2, 3, 4];
+        let peer_metadata: PeerMetadataById = [
+            (1, "a", "r1"),
+            (2, "a", "r2"),
+            (3, "b", "r1"),
+            (4, "b", "r1"),
+        ]
+        .into_iter()
+        .map(|(peer_id, zone, rack)| {
+            let metadata = PeerMetadata {
+                zone: Some(zone.to_string()),
+                rack: Some(rack.to_string()),
+                version: None,
+                witness: false,
+            };
+            (peer_id, metadata)
+        })
+        .collect();
+
+        let known_shards = vec![(0, 1), (0, 3), (1, 3)];
+        let distribution = ShardDistributionProposal::new(
+            2,
+            &known_peers,
+            known_shards,
+            &Default::default(),
+            &peer_metadata,
+        )
+        .unwrap();
+        // shard 0 has replicas in both zones, so it goes to the rack without replicas.
+        // Peer 4 is the least loaded one, but shares the zone with the replica of shard 1.
+        assert_eq!(distribution.distribution, vec![(0, 2), (1, 1)]);
+    }
 }
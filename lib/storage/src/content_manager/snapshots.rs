@@ -1,16 +1,33 @@
 use std::collections::HashMap;
+use std::io::SeekFrom;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use collection::collection::Collection;
 use collection::operations::snapshot_ops::{
     get_snapshot_description, list_snapshots_in_directory, SnapshotDescription,
 };
+use collection::operations::types::CollectionError;
+use collection::shard::ShardId;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tar::Builder as TarBuilder;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
 
+use crate::content_manager::collection_meta_ops::{
+    CollectionMetaOperations, CreateCollection, CreateCollectionOperation,
+    DeleteCollectionOperation,
+};
+use crate::content_manager::consensus_state::ConsensusStateRef;
 use crate::content_manager::toc::FULL_SNAPSHOT_FILE_NAME;
+use crate::dispatcher::Dispatcher;
 use crate::{StorageError, TableOfContent};
 
+pub const CLUSTER_SNAPSHOT_FILE_NAME: &str = "cluster-snapshot";
+/// Directory of the cluster snapshot with the raft state and the consensus WAL
+pub const CONSENSUS_SNAPSHOT_DIR: &str = "consensus";
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SnapshotConfig {
     /// Map collection name to snapshot file name
@@ -20,6 +37,170 @@ pub struct SnapshotConfig {
     pub collections_aliases: HashMap<String, String>,
 }
 
+/// Mount collection snapshot as a read-only collection
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct MountSnapshotRequest {
+    /// Temporary name of the collection to mount the snapshot under
+    pub mount_name: String,
+}
+
+/// Restore collection snapshot into a new collection
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct RestoreSnapshotRequest {
+    /// Name of the new collection. It is created with the configuration of the snapshot
+    pub collection_name: String,
+}
+
+/// Recover collection or shard from a snapshot, downloaded from the given location
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SnapshotRecover {
+    /// HTTP(S) URL of the snapshot file, e.g. `http://localhost:6333/collections/test/snapshots/test.snapshot`
+    pub location: String,
+    /// Expected SHA-256 checksum of the snapshot file in hex. Snapshot is rejected if it does not match
+    #[serde(default)]
+    pub checksum: Option<String>,
+}
+
+/// Number of attempts to download a snapshot, each retry resumes after the already received bytes
+const DOWNLOAD_ATTEMPTS: usize = 5;
+
+/// Download snapshot from `location` into `target_dir`, keeping the file name from the URL.
+///
+/// Snapshot is downloaded into a temporary file first, so that an incomplete or a corrupted
+/// download never appears in the snapshots directory.
+pub async fn download_snapshot(
+    location: &str,
+    checksum: Option<&str>,
+    target_dir: &Path,
+) -> Result<PathBuf, StorageError> {
+    let url = reqwest::Url::parse(location).map_err(|err| StorageError::BadInput {
+        description: format!("Invalid snapshot location {location}: {err}"),
+    })?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(StorageError::BadInput {
+            description: format!("Snapshot can only be downloaded over HTTP(S), got {location}"),
+        });
+    }
+    // Do not allow to write files outside of the target directory
+    let snapshot_name = match url.path_segments().and_then(|segments| segments.last()) {
+        Some(name) if Path::new(name).file_name() == Some(std::ffi::OsStr::new(name)) => {
+            name.to_string()
+        }
+        _ => {
+            return Err(StorageError::BadInput {
+                description: format!("Snapshot location {location} does not contain a file name"),
+            })
+        }
+    };
+
+    tokio::fs::create_dir_all(target_dir).await?;
+    let snapshot_path = target_dir.join(&snapshot_name);
+    let download_path = target_dir.join(format!("{snapshot_name}.download"));
+    let client = reqwest::Client::new();
+    let mut hasher = Sha256::new();
+    let downloaded: Result<(), StorageError> = async {
+        let mut file = tokio::fs::File::create(&download_path).await?;
+        let mut received = 0;
+        let mut attempt = 1;
+        loop {
+            match receive_snapshot(&client, &url, &mut file, &mut hasher, &mut received).await? {
+                Ok(()) => break,
+                Err(err) if attempt < DOWNLOAD_ATTEMPTS => {
+                    log::warn!(
+                        "Download of snapshot from {location} interrupted after {received} bytes, resuming (attempt {attempt}): {err}"
+                    );
+                    attempt += 1;
+                }
+                Err(err) => {
+                    return Err(StorageError::BadRequest {
+                        description: format!(
+                            "Failed to download snapshot from {location} in {DOWNLOAD_ATTEMPTS} attempts: {err}"
+                        ),
+                    })
+                }
+            }
+        }
+        file.sync_all().await?;
+        Ok(())
+    }
+    .await;
+    if let Err(err) = downloaded {
+        tokio::fs::remove_file(&download_path).await.ok();
+        return Err(err);
+    }
+
+    if let Some(expected) = checksum {
+        let actual = format!("{:x}", hasher.finalize());
+        if !actual.eq_ignore_ascii_case(expected) {
+            tokio::fs::remove_file(&download_path).await?;
+            return Err(StorageError::BadInput {
+                description: format!(
+                    "Checksum of snapshot {snapshot_name} is {actual}, expected {expected}"
+                ),
+            });
+        }
+    }
+    tokio::fs::rename(&download_path, &snapshot_path).await?;
+    log::info!("Downloaded snapshot {snapshot_name} from {location}");
+    Ok(snapshot_path)
+}
+
+/// Continue download of the snapshot into `file` after the `received` bytes.
+///
+/// Returns an inner error if the transfer was interrupted and can be resumed by the next attempt.
+/// If the server does not support range requests, the download starts over.
+async fn receive_snapshot(
+    client: &reqwest::Client,
+    url: &reqwest::Url,
+    file: &mut tokio::fs::File,
+    hasher: &mut Sha256,
+    received: &mut u64,
+) -> Result<Result<(), String>, StorageError> {
+    let mut request = client.get(url.clone());
+    if *received > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={received}-"));
+    }
+    let mut response = match request.send().await {
+        Ok(response) => response,
+        Err(err) => return Ok(Err(err.to_string())),
+    };
+
+    let status = response.status();
+    let restart = *received > 0 && status != reqwest::StatusCode::PARTIAL_CONTENT;
+    if restart {
+        file.set_len(0).await?;
+        file.seek(SeekFrom::Start(0)).await?;
+        *hasher = Sha256::new();
+        *received = 0;
+    }
+    if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && restart {
+        return Ok(Err(format!("server rejected range request with {status}")));
+    }
+    if status.is_server_error() {
+        return Ok(Err(format!("server responded with {status}")));
+    }
+    if let Err(err) = response.error_for_status_ref() {
+        return Err(StorageError::BadRequest {
+            description: format!("Failed to download snapshot from {url}: {err}"),
+        });
+    }
+
+    loop {
+        match response.chunk().await {
+            Ok(Some(chunk)) => {
+                hasher.update(&chunk);
+                file.write_all(&chunk).await?;
+                *received += chunk.len() as u64;
+            }
+            Ok(None) => return Ok(Ok(())),
+            Err(err) => return Ok(Err(err.to_string())),
+        }
+    }
+}
+
 pub async fn get_full_snapshot_path(
     toc: &TableOfContent,
     snapshot_name: &str,
@@ -42,18 +223,145 @@ pub async fn do_list_full_snapshots(
 
 pub async fn do_create_full_snapshot(
     toc: &TableOfContent,
+) -> Result<SnapshotDescription, StorageError> {
+    create_full_snapshot(toc, FULL_SNAPSHOT_FILE_NAME, None).await
+}
+
+/// Create snapshot of all collections and aliases together with the consensus state of this peer.
+///
+/// It is restored like a full snapshot, with `--storage-snapshot`, raft state and consensus WAL
+/// are restored as well. Consensus state is copied before the collections, so the collections
+/// might be slightly newer than the consensus state.
+pub async fn do_create_cluster_snapshot(
+    toc: &TableOfContent,
+    consensus_state: &ConsensusStateRef,
+) -> Result<SnapshotDescription, StorageError> {
+    create_full_snapshot(toc, CLUSTER_SNAPSHOT_FILE_NAME, Some(consensus_state)).await
+}
+
+/// Create a new collection with the configuration of the collection snapshot and restore
+/// its local shards from the snapshot.
+///
+/// Allows to make a copy of the collection under a different name, e.g. for staging.
+/// Shards, which are placed on other peers, are not restored.
+/// The new collection is deleted, if none of its shards can be restored.
+///
+/// Returns ids of the restored shards.
+pub async fn do_restore_snapshot_as(
+    dispatcher: &Dispatcher,
+    collection_name: &str,
+    snapshot_name: &str,
+    target_name: &str,
+    wait_timeout: Option<Duration>,
+) -> Result<Vec<ShardId>, StorageError> {
+    let toc = dispatcher.toc();
+    let snapshot_path = toc.collection_snapshot_path(collection_name, snapshot_name);
+    let tmp_dir = toc.snapshots_temp_path();
+    tokio::fs::create_dir_all(&tmp_dir).await?;
+    let snapshot_file = match toc
+        .snapshot_storage()
+        .fetch_snapshot(&snapshot_path, &tmp_dir)
+        .await
+    {
+        Ok(snapshot_file) => snapshot_file,
+        Err(CollectionError::NotFound { .. }) => {
+            return Err(StorageError::NotFound {
+                description: format!(
+                    "Snapshot {} of collection {} not found",
+                    snapshot_name, collection_name
+                ),
+            })
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    let result =
+        restore_snapshot_file_as(dispatcher, &snapshot_file, target_name, wait_timeout).await;
+
+    // Snapshot, downloaded from the object store, is not needed after restoring
+    if snapshot_file != snapshot_path {
+        tokio::fs::remove_file(&snapshot_file).await?;
+    }
+    result
+}
+
+async fn restore_snapshot_file_as(
+    dispatcher: &Dispatcher,
+    snapshot_file: &Path,
+    target_name: &str,
+    wait_timeout: Option<Duration>,
+) -> Result<Vec<ShardId>, StorageError> {
+    let config_source = snapshot_file.to_owned();
+    let config = tokio::task::spawn_blocking(move || Collection::snapshot_config(&config_source))
+        .await
+        .map_err(|err| {
+            StorageError::service_error(&format!("Can't read snapshot config: {err}"))
+        })??;
+
+    dispatcher
+        .submit_collection_meta_op(
+            CollectionMetaOperations::CreateCollection(CreateCollectionOperation {
+                collection_name: target_name.to_string(),
+                create_collection: CreateCollection::try_from(config)?,
+            }),
+            wait_timeout,
+        )
+        .await?;
+
+    let restored = dispatcher
+        .toc()
+        .restore_local_shards(target_name, snapshot_file)
+        .await;
+    if restored.is_err() {
+        dispatcher
+            .submit_collection_meta_op(
+                CollectionMetaOperations::DeleteCollection(DeleteCollectionOperation(
+                    target_name.to_string(),
+                )),
+                wait_timeout,
+            )
+            .await?;
+    }
+    restored
+}
+
+async fn create_full_snapshot(
+    toc: &TableOfContent,
+    snapshot_file_name: &str,
+    consensus_state: Option<&ConsensusStateRef>,
 ) -> Result<SnapshotDescription, StorageError> {
     let snapshot_dir = Path::new(toc.snapshots_path());
+    let current_time = chrono::Utc::now().format("%Y-%m-%d-%H-%M-%S").to_string();
+    let tmp_dir = toc.snapshots_temp_path();
+    tokio::fs::create_dir_all(&tmp_dir).await?;
 
-    let all_collections = toc.all_collections().await;
+    let consensus_path = match consensus_state {
+        Some(consensus_state) => {
+            let consensus_path = tmp_dir.join(format!("consensus-{current_time}"));
+            let target = consensus_path.clone();
+            let consensus_state = consensus_state.clone();
+            tokio::task::spawn_blocking(move || consensus_state.save_consensus_copy(&target))
+                .await
+                .map_err(|err| {
+                    StorageError::service_error(&format!("Can't copy consensus state: {err}"))
+                })??;
+            Some(consensus_path)
+        }
+        None => None,
+    };
+
+    let mut all_collections = toc.all_collections().await;
+    // Snapshot mounts are temporary, they are not restored from a full snapshot
+    for mount_name in toc.snapshot_mounts().await {
+        all_collections.retain(|collection_name| collection_name != &mount_name);
+    }
     let mut created_snapshots: Vec<(&str, SnapshotDescription)> = vec![];
     for collection_name in &all_collections {
         let snapshot_details = toc.create_snapshot(collection_name).await?;
         created_snapshots.push((collection_name, snapshot_details));
     }
-    let current_time = chrono::Utc::now().format("%Y-%m-%d-%H-%M-%S").to_string();
 
-    let snapshot_name = format!("{}-{}.snapshot", FULL_SNAPSHOT_FILE_NAME, &current_time);
+    let snapshot_name = format!("{}-{}.snapshot", snapshot_file_name, &current_time);
 
     let collection_name_to_snapshot_path: HashMap<_, _> = created_snapshots
         .iter()
@@ -90,18 +398,34 @@ pub async fn do_create_full_snapshot(
     // have to use std here, cause TarBuilder is not async
     let file = std::fs::File::create(&full_snapshot_path)?;
     let mut builder = TarBuilder::new(file);
+    // Full snapshot is kept locally, collection snapshots might be in the object store
     for (collection_name, snapshot_details) in created_snapshots {
         let snapshot_path = snapshot_dir
             .join(collection_name)
             .join(&snapshot_details.name);
-        builder.append_path_with_name(&snapshot_path, &snapshot_details.name)?;
-        tokio::fs::remove_file(snapshot_path).await?;
+        let snapshot_file = toc
+            .snapshot_storage()
+            .fetch_snapshot(&snapshot_path, &tmp_dir)
+            .await?;
+        builder.append_path_with_name(&snapshot_file, &snapshot_details.name)?;
+        if snapshot_file != snapshot_path {
+            tokio::fs::remove_file(&snapshot_file).await?;
+        }
+        toc.snapshot_storage()
+            .delete_snapshot(&snapshot_path)
+            .await?;
     }
     builder.append_path_with_name(&config_path, "config.json")?;
+    if let Some(consensus_path) = &consensus_path {
+        builder.append_dir_all(CONSENSUS_SNAPSHOT_DIR, consensus_path)?;
+    }
 
     builder.finish()?;
 
     tokio::fs::remove_file(&config_path).await?;
+    if let Some(consensus_path) = &consensus_path {
+        tokio::fs::remove_dir_all(consensus_path).await?;
+    }
 
     Ok(get_snapshot_description(&full_snapshot_path).await?)
 }
@@ -2,24 +2,40 @@ use std::collections::{HashMap, HashSet};
 use std::fs::{create_dir_all, read_dir, remove_dir_all};
 use std::num::NonZeroU32;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
+use api::grpc::api_crate_version;
 use collection::collection::Collection;
 use collection::collection_state;
 use collection::collection_state::ShardInfo;
-use collection::config::{CollectionConfig, CollectionParams};
-use collection::operations::config_diff::{CollectionParamsDiff, DiffConfig};
+use collection::config::{CollectionConfig, CollectionParams, PeerMetadataById};
+use collection::operations::config_diff::DiffConfig;
+use collection::operations::point_ops::{PointInsertOperations, PointOperations, PointStruct};
+use collection::operations::shard_selector::ShardSelectorInternal;
 use collection::operations::snapshot_ops::SnapshotDescription;
+use collection::operations::snapshot_storage_ops::SnapshotStorage;
 use collection::operations::types::{
-    CountRequest, CountResult, PointRequest, RecommendRequest, RecommendRequestBatch, Record,
-    ScrollRequest, ScrollResult, SearchRequest, SearchRequestBatch, UpdateResult,
+    CollectionError, CountRequest, CountResult, FieldIndexEstimation, FieldIndexEstimationRequest,
+    MovePointsMode, MovePointsRequest, MovePointsResult, PointRequest, RecommendRequest,
+    RecommendRequestBatch, Record, ScrollRequest, ScrollResult, SearchRequest, SearchRequestBatch,
+    ShardCleanupProgress, UpdateResult, WalInfo,
 };
 use collection::operations::CollectionUpdateOperations;
+use collection::resource_watchdog::ResourceThrottle;
+use collection::shard::checksum::ShardChecksum;
 use collection::shard::collection_shard_distribution::CollectionShardDistribution;
-use collection::shard::{replica_set, ChannelService, CollectionId, PeerId, ShardId};
+use collection::shard::replica_set::ReplicaState;
+use collection::shard::{
+    replica_set, ChannelService, CollectionId, PeerId, ShardId, ShardTransfer,
+};
 use collection::telemetry::CollectionTelemetry;
-use segment::types::ScoredPoint;
-use tokio::runtime::Runtime;
+use segment::types::{
+    Condition, Filter, HasIdCondition, PointIdType, ScoredPoint, SeqNumberType,
+    WithPayloadInterface, WithVector,
+};
+use tokio::runtime;
+use tokio::runtime::{Handle, Runtime};
 use tokio::sync::{RwLock, RwLockReadGuard};
 
 use super::collection_meta_ops::{
@@ -36,13 +52,16 @@ use crate::content_manager::collections_ops::{Checker, Collections};
 use crate::content_manager::consensus::operation_sender::OperationSender;
 use crate::content_manager::errors::StorageError;
 use crate::content_manager::shard_distribution::ShardDistributionProposal;
+use crate::content_manager::snapshots::{download_snapshot, SnapshotRecover};
 use crate::types::{PeerAddressById, StorageConfig};
 use crate::ConsensusOperations;
 
 pub const ALIASES_PATH: &str = "aliases";
 pub const COLLECTIONS_DIR: &str = "collections";
 pub const SNAPSHOTS_TMP_DIR: &str = "snapshots_tmp";
+pub const SNAPSHOT_MOUNTS_DIR: &str = "snapshot_mounts";
 pub const FULL_SNAPSHOT_FILE_NAME: &str = "full-snapshot";
+pub const DEFAULT_MOVE_POINTS_BATCH_SIZE: usize = 100;
 
 /// The main object of the service. It holds all objects, required for proper functioning.
 /// In most cases only one `TableOfContent` is enough for service. It is created only once during
@@ -50,13 +69,24 @@ pub const FULL_SNAPSHOT_FILE_NAME: &str = "full-snapshot";
 pub struct TableOfContent {
     collections: Arc<RwLock<Collections>>,
     storage_config: StorageConfig,
+    snapshot_storage: SnapshotStorage,
     search_runtime: Runtime,
     collection_management_runtime: Runtime,
+    /// Runs shard transfers and snapshot IO, so replication traffic doesn't starve searches
+    transfer_runtime: Runtime,
     alias_persistence: RwLock<AliasPersistence>,
+    /// Read-only collections mounted from snapshots, mapped to their unpacked data
+    snapshot_mounts: RwLock<HashMap<String, PathBuf>>,
     pub this_peer_id: PeerId,
     channel_service: ChannelService,
+    /// Location labels of peers, shared with the consensus state
+    peer_metadata_by_id: Arc<parking_lot::RwLock<PeerMetadataById>>,
+    /// Peers, which are being drained, shared with the consensus state
+    draining_peers: Arc<parking_lot::RwLock<HashSet<PeerId>>>,
     /// Backlink to the consensus
     consensus_proposal_sender: OperationSender,
+    /// Pauses optimizations and shard transfers of all collections, set by the resource watchdog
+    resource_throttle: ResourceThrottle,
 }
 
 impl TableOfContent {
@@ -70,11 +100,18 @@ impl TableOfContent {
     ) -> Self {
         let snapshots_path = Path::new(&storage_config.snapshots_path.clone()).to_owned();
         create_dir_all(&snapshots_path).expect("Can't create Snapshots directory");
+        let snapshot_storage =
+            SnapshotStorage::from_config(&storage_config.snapshots_config, &snapshots_path)
+                .expect("Can't configure snapshots storage");
         let collections_path = Path::new(&storage_config.storage_path).join(&COLLECTIONS_DIR);
         let collection_management_runtime = Runtime::new().unwrap();
+        let transfer_runtime =
+            create_transfer_runtime(storage_config.performance.max_transfer_threads)
+                .expect("Can't create transfer runtime");
         create_dir_all(&collections_path).expect("Can't create Collections directory");
         let collection_paths =
             read_dir(&collections_path).expect("Can't read Collections directory");
+        let resource_throttle = ResourceThrottle::default();
         let mut collections: HashMap<String, Collection> = Default::default();
         for entry in collection_paths {
             let collection_path = entry
@@ -104,30 +141,71 @@ impl TableOfContent {
                 )
             });
             log::info!("Loading collection: {}", collection_name);
+            let on_peer_failure = Self::on_peer_failure_callback(
+                consensus_proposal_sender.clone(),
+                collection_name.clone(),
+            );
             let collection = collection_management_runtime.block_on(Collection::load(
                 collection_name.clone(),
+                this_peer_id,
                 &collection_path,
                 &collection_snapshots_path,
+                snapshot_storage.clone(),
                 channel_service.clone(),
+                on_peer_failure,
+                resource_throttle.clone(),
             ));
 
             collections.insert(collection_name, collection);
         }
+        // Snapshot mounts are temporary and do not survive restart
+        let snapshot_mounts_path =
+            Path::new(&storage_config.storage_path).join(SNAPSHOT_MOUNTS_DIR);
+        if snapshot_mounts_path.exists() {
+            log::info!("Removing snapshot mounts left from the previous run");
+            remove_dir_all(&snapshot_mounts_path).expect("Can't remove snapshot mounts directory");
+        }
         let alias_path = Path::new(&storage_config.storage_path).join(ALIASES_PATH);
         let alias_persistence =
             AliasPersistence::open(alias_path).expect("Can't open database by the provided config");
         TableOfContent {
             collections: Arc::new(RwLock::new(collections)),
             storage_config: storage_config.clone(),
+            snapshot_storage,
             search_runtime,
             alias_persistence: RwLock::new(alias_persistence),
+            snapshot_mounts: Default::default(),
             collection_management_runtime,
+            transfer_runtime,
             this_peer_id,
             channel_service,
+            peer_metadata_by_id: Default::default(),
+            draining_peers: Default::default(),
             consensus_proposal_sender,
+            resource_throttle,
         }
     }
 
+    /// Share location labels of peers, maintained by the consensus, with the ToC.
+    /// Those are used to spread replicas of shards across zones and racks.
+    pub fn with_peer_metadata(
+        mut self,
+        peer_metadata_by_id: Arc<parking_lot::RwLock<PeerMetadataById>>,
+    ) -> Self {
+        self.peer_metadata_by_id = peer_metadata_by_id;
+        self
+    }
+
+    /// Share the set of draining peers, maintained by the consensus, with the ToC.
+    /// Draining peers receive no new shards.
+    pub fn with_draining_peers(
+        mut self,
+        draining_peers: Arc<parking_lot::RwLock<HashSet<PeerId>>>,
+    ) -> Self {
+        self.draining_peers = draining_peers;
+        self
+    }
+
     fn get_collection_path(&self, collection_name: &str) -> PathBuf {
         Path::new(&self.storage_config.storage_path)
             .join(&COLLECTIONS_DIR)
@@ -138,14 +216,48 @@ impl TableOfContent {
         &self.storage_config.storage_path
     }
 
+    /// Runtime for background replication work: shard transfers, snapshot IO, replica verification
+    pub fn transfer_runtime_handle(&self) -> &Handle {
+        self.transfer_runtime.handle()
+    }
+
     pub fn snapshots_path(&self) -> &str {
         &self.storage_config.snapshots_path
     }
 
+    /// Directory to stage snapshots in while they are created or fetched.
+    ///
+    /// By default it is inside the storage, because it is possible, that
+    /// snapshot directory is mounted as network share and multiple writes to it could be slow
+    pub fn snapshots_temp_path(&self) -> PathBuf {
+        match &self.storage_config.snapshots_temp_path {
+            Some(path) => PathBuf::from(path),
+            None => Path::new(&self.storage_config.storage_path).join(SNAPSHOTS_TMP_DIR),
+        }
+    }
+
+    pub fn snapshot_storage(&self) -> &SnapshotStorage {
+        &self.snapshot_storage
+    }
+
+    /// Throttle shared by all collections, to be controlled by the resource watchdog
+    pub fn resource_throttle(&self) -> ResourceThrottle {
+        self.resource_throttle.clone()
+    }
+
     fn collection_snapshots_path(snapshots_path: &Path, collection_name: &str) -> PathBuf {
         snapshots_path.join(collection_name)
     }
 
+    /// Path to the snapshot of the collection, in the local file system or in the object store
+    pub fn collection_snapshot_path(&self, collection_name: &str, snapshot_name: &str) -> PathBuf {
+        Self::collection_snapshots_path(
+            Path::new(&self.storage_config.snapshots_path),
+            collection_name,
+        )
+        .join(snapshot_name)
+    }
+
     async fn create_snapshots_path(&self, collection_name: &str) -> Result<PathBuf, StorageError> {
         let snapshots_path = Self::collection_snapshots_path(
             Path::new(&self.storage_config.snapshots_path),
@@ -212,9 +324,18 @@ impl TableOfContent {
     ) -> Result<bool, StorageError> {
         let CreateCollection {
             vectors,
+            sparse_vectors,
             shard_number,
             on_disk_payload,
+            max_response_payload_bytes,
+            payload_transformers,
             hnsw_config: hnsw_config_diff,
+            ivf_config,
+            placement,
+            read_fan_out_factor,
+            write_consistency,
+            read_consistency,
+            hash_ring,
             wal_config: wal_config_diff,
             optimizers_config: optimizers_config_diff,
         } = operation;
@@ -225,6 +346,9 @@ impl TableOfContent {
             .validate_collection_not_exists(collection_name)
             .await?;
 
+        vectors.validate()?;
+        vectors.check_sparse_vector_names(sparse_vectors.as_ref())?;
+
         let collection_path = self.create_collection_path(collection_name).await?;
         let snapshots_path = self.create_snapshots_path(collection_name).await?;
 
@@ -238,13 +362,22 @@ impl TableOfContent {
 
         let collection_params = CollectionParams {
             vectors,
+            sparse_vectors,
             shard_number: NonZeroU32::new(collection_shard_distribution.shard_count() as u32)
                 .ok_or(StorageError::BadInput {
                     description: "`shard_number` cannot be 0".to_string(),
                 })?,
+            hash_ring: hash_ring.unwrap_or_default(),
             on_disk_payload: on_disk_payload.unwrap_or(self.storage_config.on_disk_payload),
+            max_response_payload_bytes,
+            payload_transformers: payload_transformers.unwrap_or_default(),
+            ivf_config,
+            placement: placement.unwrap_or_default(),
             // TODO: use `replication_factor` supplied in `CreateCollection`
             replication_factor: collection::config::default_replication_factor(),
+            read_fan_out_factor,
+            write_consistency: write_consistency.unwrap_or_default(),
+            read_consistency: read_consistency.unwrap_or_default(),
         };
         let wal_config = match wal_config_diff {
             None => self.storage_config.wal.clone(),
@@ -266,15 +399,22 @@ impl TableOfContent {
             params: collection_params,
             optimizer_config: optimizers_config,
             hnsw_config,
+            version: 0,
         };
         let collection = Collection::new(
             collection_name.to_string(),
+            self.this_peer_id,
             &collection_path,
             &snapshots_path,
+            self.snapshot_storage.clone(),
             &collection_config,
             collection_shard_distribution,
             self.channel_service.clone(),
-            self.on_peer_failure_callback(collection_name.to_string()),
+            Self::on_peer_failure_callback(
+                self.consensus_proposal_sender.clone(),
+                collection_name.to_string(),
+            ),
+            self.resource_throttle.clone(),
         )
         .await?;
 
@@ -286,9 +426,11 @@ impl TableOfContent {
         Ok(true)
     }
 
-    fn on_peer_failure_callback(&self, collection_name: String) -> replica_set::OnPeerFailure {
-        let proposal_sender = self.consensus_proposal_sender.clone();
-        Box::new(move |peer_id, shard_id| {
+    fn on_peer_failure_callback(
+        proposal_sender: OperationSender,
+        collection_name: String,
+    ) -> replica_set::OnPeerFailure {
+        Arc::new(move |peer_id, shard_id| {
             let proposal_sender = proposal_sender.clone();
             let collection_name = collection_name.clone();
             Box::new(async move {
@@ -298,7 +440,7 @@ impl TableOfContent {
                             collection_name,
                             shard_id,
                             peer_id,
-                            active: false,
+                            state: ReplicaState::Dead,
                         })
                         .into(),
                     ))
@@ -312,20 +454,54 @@ impl TableOfContent {
         collection_name: &str,
         operation: UpdateCollection,
     ) -> Result<bool, StorageError> {
-        let UpdateCollection { optimizers_config } = operation;
-        // TODO: get `params` from `UpdateCollection`
-        let params: Option<CollectionParamsDiff> = None;
+        let UpdateCollection {
+            optimizers_config,
+            params,
+            expected_version,
+        } = operation;
+        self.validate_not_snapshot_mount(collection_name).await?;
         let collection = self.get_collection(collection_name).await?;
-        if let Some(diff) = optimizers_config {
-            collection.update_optimizer_params_from_diff(diff).await?
-        }
-        if let Some(diff) = params {
-            collection.update_params_from_diff(diff).await?;
+        let syncs_replicas = params
+            .as_ref()
+            .map_or(false, |diff| diff.replication_factor.is_some());
+        collection
+            .update_config_from_diff(expected_version, optimizers_config, params)
+            .await?;
+        if syncs_replicas {
+            collection.upgrade_to_replica_sets().await?;
+            self.propose_replica_syncs(&collection).await?;
         }
         Ok(true)
     }
 
+    /// Proposes transfers to sync the missing replicas of the collection's shards.
+    /// Transfers are computed from the consensus state, which every peer applies the same way,
+    /// so each transfer is proposed by its source peer only.
+    async fn propose_replica_syncs(&self, collection: &Collection) -> Result<(), StorageError> {
+        let transfers = collection
+            .suggest_shard_replica_changes(&self.known_peers(), &self.peer_metadata_by_id())
+            .await;
+        for transfer in transfers {
+            if transfer.from != self.this_peer_id {
+                continue;
+            }
+            log::info!(
+                "Syncing new replica of shard {} of collection {} to peer {}",
+                transfer.shard_id,
+                collection.name(),
+                transfer.to
+            );
+            self.consensus_proposal_sender
+                .send(ConsensusOperations::start_transfer(
+                    collection.name(),
+                    transfer,
+                ))?;
+        }
+        Ok(())
+    }
+
     async fn delete_collection(&self, collection_name: &str) -> Result<bool, StorageError> {
+        self.validate_not_snapshot_mount(collection_name).await?;
         if let Some(mut removed) = self.collections.write().await.remove(collection_name) {
             removed.before_drop().await;
             let path = self.get_collection_path(collection_name);
@@ -363,6 +539,7 @@ impl TableOfContent {
                     collection_lock
                         .validate_collection_exists(&collection_name)
                         .await?;
+                    self.validate_not_snapshot_mount(&collection_name).await?;
                     collection_lock
                         .validate_collection_not_exists(&alias_name)
                         .await?;
@@ -443,13 +620,250 @@ impl TableOfContent {
         }
     }
 
+    /// Checks, if the operation can be applied, without applying it.
+    ///
+    /// Returns descriptions of the changes, which the operation would make.
+    pub async fn validate_collection_meta_op(
+        &self,
+        operation: &CollectionMetaOperations,
+    ) -> Result<Vec<String>, StorageError> {
+        match operation {
+            CollectionMetaOperations::CreateCollectionDistributed(operation, distribution) => {
+                let mut changes = self
+                    .validate_create_collection(operation, distribution.distribution.len())
+                    .await?;
+                changes.extend(distribution.distribution.iter().map(|(shard_id, peer_id)| {
+                    format!("Place shard {shard_id} on peer {peer_id}")
+                }));
+                Ok(changes)
+            }
+            CollectionMetaOperations::CreateCollection(operation) => {
+                let shard_count = operation.create_collection.shard_number.unwrap_or(1);
+                self.validate_create_collection(operation, shard_count as usize)
+                    .await
+            }
+            CollectionMetaOperations::UpdateCollection(operation) => {
+                let collection_name = &operation.collection_name;
+                self.validate_not_snapshot_mount(collection_name).await?;
+                let collection = self.get_collection(collection_name).await?;
+                let config = collection.config().await;
+                config.check_version(operation.update_collection.expected_version)?;
+                let mut changes = vec![];
+                if let Some(diff) = &operation.update_collection.optimizers_config {
+                    diff.clone().update(&config.optimizer_config)?;
+                    changes.push(format!(
+                        "Update optimizers config of collection `{collection_name}` and restart its optimizations"
+                    ));
+                }
+                if let Some(diff) = &operation.update_collection.params {
+                    let params = diff.clone().update(&config.params)?;
+                    changes.push(format!(
+                        "Update parameters of collection `{collection_name}`"
+                    ));
+                    if params.replication_factor > config.params.replication_factor {
+                        changes.push(format!(
+                            "Sync new replicas of shards of collection `{collection_name}` to reach replication factor {}",
+                            params.replication_factor
+                        ));
+                    }
+                }
+                Ok(changes)
+            }
+            CollectionMetaOperations::DeleteCollection(operation) => {
+                let collection_name = &operation.0;
+                self.validate_not_snapshot_mount(collection_name).await?;
+                if !self.collections.read().await.contains_key(collection_name) {
+                    return Ok(vec![]);
+                }
+                let mut changes = vec![format!("Delete collection `{collection_name}`")];
+                let aliases = self
+                    .alias_persistence
+                    .read()
+                    .await
+                    .collection_aliases(collection_name);
+                if !aliases.is_empty() {
+                    changes.push(format!(
+                        "Aliases {} keep pointing to deleted collection `{collection_name}`",
+                        aliases.join(", ")
+                    ));
+                }
+                Ok(changes)
+            }
+            CollectionMetaOperations::ChangeAliases(operation) => {
+                self.validate_update_aliases(operation).await
+            }
+            CollectionMetaOperations::TransferShard(collection_name, operation) => {
+                let collection = self.get_collection(collection_name).await?;
+                match operation {
+                    ShardTransferOperations::Start(transfer) => {
+                        if !collection.contains_shard(&transfer.shard_id).await {
+                            return Err(StorageError::BadRequest {
+                                description: format!(
+                                    "Shard {} of collection `{collection_name}` does not exist",
+                                    transfer.shard_id
+                                ),
+                            });
+                        }
+                        if !self.known_peers().contains(&transfer.to) {
+                            return Err(StorageError::BadRequest {
+                                description: format!(
+                                    "Target peer {} does not exist, is being drained or is a witness",
+                                    transfer.to
+                                ),
+                            });
+                        }
+                        collection
+                            .validate_replica_placement(
+                                transfer.shard_id,
+                                transfer.to,
+                                Some(transfer.from),
+                                self.this_peer_id,
+                            )
+                            .await?;
+                        Ok(vec![format!(
+                            "Start transfer of shard {} of collection `{collection_name}` from peer {} to peer {}",
+                            transfer.shard_id, transfer.from, transfer.to
+                        )])
+                    }
+                    ShardTransferOperations::Finish(transfer) => Ok(vec![format!(
+                        "Finish transfer of shard {} of collection `{collection_name}` from peer {} to peer {}",
+                        transfer.shard_id, transfer.from, transfer.to
+                    )]),
+                    ShardTransferOperations::Abort { transfer, reason } => Ok(vec![format!(
+                        "Abort transfer of shard {} of collection `{collection_name}` from peer {} to peer {}: {reason}",
+                        transfer.shard_id, transfer.from, transfer.to
+                    )]),
+                }
+            }
+            CollectionMetaOperations::SetShardReplicaState(operation) => {
+                let collection_name = &operation.collection_name;
+                let collection = self.get_collection(collection_name).await?;
+                if !collection.contains_shard(&operation.shard_id).await {
+                    return Err(StorageError::BadRequest {
+                        description: format!(
+                            "Shard {} of collection `{collection_name}` does not exist",
+                            operation.shard_id
+                        ),
+                    });
+                }
+                Ok(vec![format!(
+                    "Set state of replica of shard {} of collection `{collection_name}` on peer {} to {:?}",
+                    operation.shard_id, operation.peer_id, operation.state
+                )])
+            }
+        }
+    }
+
+    async fn validate_create_collection(
+        &self,
+        operation: &CreateCollectionOperation,
+        shard_count: usize,
+    ) -> Result<Vec<String>, StorageError> {
+        let collection_name = &operation.collection_name;
+        let create_collection = &operation.create_collection;
+        self.collections
+            .read()
+            .await
+            .validate_collection_not_exists(collection_name)
+            .await?;
+        create_collection.vectors.validate()?;
+        create_collection
+            .vectors
+            .check_sparse_vector_names(create_collection.sparse_vectors.as_ref())?;
+        if shard_count == 0 {
+            return Err(StorageError::BadInput {
+                description: "`shard_number` cannot be 0".to_string(),
+            });
+        }
+        if let Some(diff) = &create_collection.wal_config {
+            diff.clone().update(&self.storage_config.wal)?;
+        }
+        if let Some(diff) = &create_collection.optimizers_config {
+            diff.clone().update(&self.storage_config.optimizers)?;
+        }
+        if let Some(diff) = &create_collection.hnsw_config {
+            diff.update(&self.storage_config.hnsw_index)?;
+        }
+        Ok(vec![format!(
+            "Create collection `{collection_name}` with {shard_count} shards"
+        )])
+    }
+
+    /// Applies alias changes to a copy of the alias mapping, so that actions
+    /// depending on the previous ones in the same operation are validated correctly
+    async fn validate_update_aliases(
+        &self,
+        operation: &ChangeAliasesOperation,
+    ) -> Result<Vec<String>, StorageError> {
+        let collections = self.collections.read().await;
+        let mut aliases = self.alias_persistence.read().await.state().clone();
+        let mut changes = vec![];
+        for action in &operation.actions {
+            match action {
+                AliasOperations::CreateAlias(CreateAliasOperation {
+                    create_alias:
+                        CreateAlias {
+                            collection_name,
+                            alias_name,
+                        },
+                }) => {
+                    collections
+                        .validate_collection_exists(collection_name)
+                        .await?;
+                    self.validate_not_snapshot_mount(collection_name).await?;
+                    collections
+                        .validate_collection_not_exists(alias_name)
+                        .await?;
+                    match aliases.insert(alias_name.clone(), collection_name.clone()) {
+                        Some(previous) => changes.push(format!(
+                            "Point alias `{alias_name}` to collection `{collection_name}` instead of `{previous}`"
+                        )),
+                        None => changes.push(format!(
+                            "Create alias `{alias_name}` for collection `{collection_name}`"
+                        )),
+                    }
+                }
+                AliasOperations::DeleteAlias(DeleteAliasOperation {
+                    delete_alias: DeleteAlias { alias_name },
+                }) => {
+                    if let Some(collection_name) = aliases.remove(alias_name) {
+                        changes.push(format!(
+                            "Delete alias `{alias_name}` of collection `{collection_name}`"
+                        ));
+                    }
+                }
+                AliasOperations::RenameAlias(RenameAliasOperation {
+                    rename_alias:
+                        RenameAlias {
+                            old_alias_name,
+                            new_alias_name,
+                        },
+                }) => {
+                    let collection_name = match aliases.remove(old_alias_name) {
+                        Some(collection_name) => collection_name,
+                        None => {
+                            return Err(StorageError::NotFound {
+                                description: format!("Alias {} does not exists!", old_alias_name),
+                            })
+                        }
+                    };
+                    changes.push(format!(
+                        "Rename alias `{old_alias_name}` of collection `{collection_name}` to `{new_alias_name}`"
+                    ));
+                    aliases.insert(new_alias_name.clone(), collection_name);
+                }
+            }
+        }
+        Ok(changes)
+    }
+
     pub async fn set_shard_replica_state(
         &self,
         operation: SetShardReplicaState,
     ) -> Result<(), StorageError> {
         self.get_collection(&operation.collection_name)
             .await?
-            .set_shard_replica_state(operation.shard_id, operation.peer_id, operation.active)
+            .set_shard_replica_state(operation.shard_id, operation.peer_id, operation.state)
             .await?;
         Ok(())
     }
@@ -468,11 +882,123 @@ impl TableOfContent {
         Ok(())
     }
 
+    /// Starts recovery of local replicas, which can't be trusted after restart,
+    /// by transferring shards from active replicas on other peers.
+    pub async fn recover_local_replicas(&self) -> Result<(), StorageError> {
+        let collections = self.collections.read().await;
+        let proposal_sender = self.consensus_proposal_sender.clone();
+        for collection in collections.values() {
+            for transfer in collection.start_local_replicas_recovery().await? {
+                log::info!(
+                    "Recovering replica of shard {} of collection {} from peer {}",
+                    transfer.shard_id,
+                    collection.name(),
+                    transfer.from
+                );
+                proposal_sender.send(ConsensusOperations::set_replica_state(
+                    collection.name(),
+                    transfer.shard_id,
+                    transfer.to,
+                    ReplicaState::Partial,
+                ))?;
+                proposal_sender.send(ConsensusOperations::start_transfer(
+                    collection.name(),
+                    transfer,
+                ))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Proposes to move local shards to other peers, if this peer is being drained.
+    /// Once no shards are left on this peer, proposes to remove it from the cluster.
+    pub async fn drain_local_shards(&self) -> Result<(), StorageError> {
+        if !self.draining_peers.read().contains(&self.this_peer_id) {
+            return Ok(());
+        }
+        if !self.peer_has_shards(self.this_peer_id).await {
+            log::info!("Peer {} is drained, leaving the cluster", self.this_peer_id);
+            return self
+                .consensus_proposal_sender
+                .send(ConsensusOperations::RemovePeer(self.this_peer_id));
+        }
+        let target_peers = self.known_peers();
+        let collections = self.collections.read().await;
+        for collection in collections.values() {
+            let transfers = collection
+                .suggest_drain_transfers(&target_peers, self.this_peer_id)
+                .await;
+            for transfer in transfers {
+                log::info!(
+                    "Moving shard {} of collection {} to peer {} to drain this peer",
+                    transfer.shard_id,
+                    collection.name(),
+                    transfer.to
+                );
+                self.consensus_proposal_sender
+                    .send(ConsensusOperations::start_transfer(
+                        collection.name(),
+                        transfer,
+                    ))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Transfers to repair diverged replicas of shards, coordinated by this peer
+    pub async fn suggest_replica_repairs(&self) -> Vec<(CollectionId, ShardTransfer)> {
+        let mut repairs = vec![];
+        for collection_name in self.all_collections().await {
+            if let Ok(collection) = self.get_collection(&collection_name).await {
+                for transfer in collection.suggest_replica_repairs().await {
+                    repairs.push((collection_name.clone(), transfer));
+                }
+            }
+        }
+        repairs
+    }
+
+    /// Proposes to sync the diverged replica from another replica of the shard
+    pub fn propose_replica_repair(
+        &self,
+        collection_name: CollectionId,
+        transfer: ShardTransfer,
+    ) -> Result<(), StorageError> {
+        self.consensus_proposal_sender
+            .send(ConsensusOperations::set_replica_state(
+                collection_name.clone(),
+                transfer.shard_id,
+                transfer.to,
+                ReplicaState::Partial,
+            ))?;
+        self.consensus_proposal_sender
+            .send(ConsensusOperations::start_transfer(
+                collection_name,
+                transfer,
+            ))
+    }
+
+    /// Checksum of the points of the collection's shard, stored on this peer
+    pub async fn shard_checksum(
+        &self,
+        collection_name: &str,
+        shard_id: ShardId,
+    ) -> Result<ShardChecksum, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        Ok(collection.shard_checksum(shard_id).await?)
+    }
+
     pub async fn handle_transfer(
         &self,
         collection_id: CollectionId,
         transfer_operation: ShardTransferOperations,
     ) -> Result<(), StorageError> {
+        // Draining continues once an outgoing transfer is over
+        let continues_drain = match &transfer_operation {
+            ShardTransferOperations::Start(_) => false,
+            ShardTransferOperations::Finish(transfer)
+            | ShardTransferOperations::Abort { transfer, .. } => transfer.from == self.this_peer_id,
+        };
         let collection = self.get_collection(&collection_id).await?;
         match transfer_operation {
             ShardTransferOperations::Start(transfer) => {
@@ -526,7 +1052,12 @@ impl TableOfContent {
                 };
 
                 collection
-                    .start_shard_transfer(transfer, on_finish, on_failure)
+                    .start_shard_transfer(
+                        transfer,
+                        self.transfer_runtime.handle(),
+                        on_finish,
+                        on_failure,
+                    )
                     .await
             }
             ShardTransferOperations::Finish(transfer) => {
@@ -537,6 +1068,10 @@ impl TableOfContent {
                 collection.abort_shard_transfer(transfer).await
             }
         }?;
+        drop(collection);
+        if continues_drain {
+            self.drain_local_shards().await?;
+        }
         Ok(())
     }
 
@@ -584,11 +1119,11 @@ impl TableOfContent {
         &self,
         collection_name: &str,
         request: RecommendRequest,
-        shard_selection: Option<ShardId>,
+        shard_selection: ShardSelectorInternal,
     ) -> Result<Vec<ScoredPoint>, StorageError> {
         let collection = self.get_collection(collection_name).await?;
         collection
-            .recommend_by(request, self.search_runtime.handle(), shard_selection)
+            .recommend_by(request, self.search_runtime.handle(), &shard_selection)
             .await
             .map_err(|err| err.into())
     }
@@ -607,11 +1142,11 @@ impl TableOfContent {
         &self,
         collection_name: &str,
         request: RecommendRequestBatch,
-        shard_selection: Option<ShardId>,
+        shard_selection: ShardSelectorInternal,
     ) -> Result<Vec<Vec<ScoredPoint>>, StorageError> {
         let collection = self.get_collection(collection_name).await?;
         collection
-            .recommend_batch_by(request, self.search_runtime.handle(), shard_selection)
+            .recommend_batch_by(request, self.search_runtime.handle(), &shard_selection)
             .await
             .map_err(|err| err.into())
     }
@@ -623,7 +1158,7 @@ impl TableOfContent {
     ///
     /// * `collection_name` - in what collection do we search
     /// * `request` - [`SearchRequest`]
-    /// * `shard_selection` - which local shard to use
+    /// * `shard_selection` - which shards to use
     /// # Result
     ///
     /// Points with search score
@@ -631,11 +1166,11 @@ impl TableOfContent {
         &self,
         collection_name: &str,
         request: SearchRequest,
-        shard_selection: Option<ShardId>,
+        shard_selection: ShardSelectorInternal,
     ) -> Result<Vec<ScoredPoint>, StorageError> {
         let collection = self.get_collection(collection_name).await?;
         collection
-            .search(request, self.search_runtime.handle(), shard_selection)
+            .search(request, self.search_runtime.handle(), &shard_selection)
             .await
             .map_err(|err| err.into())
     }
@@ -647,7 +1182,7 @@ impl TableOfContent {
     ///
     /// * `collection_name` - in what collection do we search
     /// * `request` - [`SearchRequestBatch`]
-    /// * `shard_selection` - which local shard to use
+    /// * `shard_selection` - which shards to use
     /// # Result
     ///
     /// Points with search score
@@ -655,11 +1190,11 @@ impl TableOfContent {
         &self,
         collection_name: &str,
         request: SearchRequestBatch,
-        shard_selection: Option<ShardId>,
+        shard_selection: ShardSelectorInternal,
     ) -> Result<Vec<Vec<ScoredPoint>>, StorageError> {
         let collection = self.get_collection(collection_name).await?;
         collection
-            .search_batch(request, self.search_runtime.handle(), shard_selection)
+            .search_batch(request, self.search_runtime.handle(), &shard_selection)
             .await
             .map_err(|err| err.into())
     }
@@ -670,7 +1205,7 @@ impl TableOfContent {
     ///
     /// * `collection_name` - in what collection do we count
     /// * `request` - [`CountRequest`]
-    /// * `shard_selection` - which local shard to use
+    /// * `shard_selection` - which shards to use
     ///
     /// # Result
     ///
@@ -680,11 +1215,39 @@ impl TableOfContent {
         &self,
         collection_name: &str,
         request: CountRequest,
-        shard_selection: Option<ShardId>,
+        shard_selection: ShardSelectorInternal,
     ) -> Result<CountResult, StorageError> {
         let collection = self.get_collection(collection_name).await?;
         collection
-            .count(request, shard_selection)
+            .count(request, &shard_selection)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// Wait until the payload index on `field_name` is built in all segments
+    pub async fn wait_field_index_ready(
+        &self,
+        collection_name: &str,
+        field_name: &str,
+        shard_selection: ShardSelectorInternal,
+    ) -> Result<(), StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        collection
+            .wait_field_index_ready(field_name, &shard_selection)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// Estimate resources of the payload index without creating it
+    pub async fn estimate_field_index(
+        &self,
+        collection_name: &str,
+        request: FieldIndexEstimationRequest,
+        shard_selection: ShardSelectorInternal,
+    ) -> Result<FieldIndexEstimation, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        collection
+            .estimate_field_index(request, &shard_selection)
             .await
             .map_err(|err| err.into())
     }
@@ -695,7 +1258,7 @@ impl TableOfContent {
     ///
     /// * `collection_name` - select from this collection
     /// * `request` - [`PointRequest`]
-    /// * `shard_selection` - which local shard to use
+    /// * `shard_selection` - which shards to use
     ///
     /// # Result
     ///
@@ -704,11 +1267,11 @@ impl TableOfContent {
         &self,
         collection_name: &str,
         request: PointRequest,
-        shard_selection: Option<ShardId>,
+        shard_selection: ShardSelectorInternal,
     ) -> Result<Vec<Record>, StorageError> {
         let collection = self.get_collection(collection_name).await?;
         collection
-            .retrieve(request, shard_selection)
+            .retrieve(request, &shard_selection)
             .await
             .map_err(|err| err.into())
     }
@@ -746,7 +1309,7 @@ impl TableOfContent {
     ///
     /// * `collection_name` - which collection to use
     /// * `request` - [`ScrollRequest`]
-    /// * `shard_selection` - which local shard to use
+    /// * `shard_selection` - which shards to use
     ///
     /// # Result
     ///
@@ -755,11 +1318,11 @@ impl TableOfContent {
         &self,
         collection_name: &str,
         request: ScrollRequest,
-        shard_selection: Option<ShardId>,
+        shard_selection: ShardSelectorInternal,
     ) -> Result<ScrollResult, StorageError> {
         let collection = self.get_collection(collection_name).await?;
         collection
-            .scroll_by(request, shard_selection)
+            .scroll_by(request, &shard_selection)
             .await
             .map_err(|err| err.into())
     }
@@ -768,21 +1331,232 @@ impl TableOfContent {
         &self,
         collection_name: &str,
         operation: CollectionUpdateOperations,
-        shard_selection: Option<ShardId>,
+        shard_selection: ShardSelectorInternal,
         wait: bool,
     ) -> Result<UpdateResult, StorageError> {
+        self.validate_not_snapshot_mount(&self.resolve_name(collection_name).await?)
+            .await?;
         let collection = self.get_collection(collection_name).await?;
         let result = match shard_selection {
-            Some(shard_selection) => {
+            ShardSelectorInternal::ShardId(shard_id) => {
+                collection.update_from_peer(operation, shard_id, wait).await
+            }
+            shard_selection => {
                 collection
-                    .update_from_peer(operation, shard_selection, wait)
+                    .update_from_client(operation, &shard_selection, wait)
                     .await
             }
-            None => collection.update_from_client(operation, wait).await,
         };
         result.map_err(|err| err.into())
     }
 
+    /// Move or copy points which match the filter into another collection
+    ///
+    /// Points are transferred shard by shard in batches. Each batch is verified by counting its
+    /// points in the target collection. Points of a shard are deleted from the source collection
+    /// only once all of them are copied and the number of copied points matches the number of
+    /// points in the shard.
+    ///
+    /// The move is not atomic:
+    /// * If the move fails, points of the shards which are not finished yet are kept in the source
+    ///   collection, while some of them might be copied already. The move can be repeated.
+    /// * Points are deleted by their ids. If a point is updated in the source collection after
+    ///   it is copied, but before its shard is finished, the update is lost, as the target
+    ///   collection keeps the copied version. Updates of the moved points should be stopped
+    ///   for the time of the move.
+    ///
+    /// # Arguments
+    ///
+    /// * `collection_name` - collection to take points from
+    /// * `request` - [`MovePointsRequest`]
+    ///
+    /// # Result
+    ///
+    /// Number of points stored in the target collection
+    pub async fn move_points(
+        &self,
+        collection_name: &str,
+        request: MovePointsRequest,
+    ) -> Result<MovePointsResult, StorageError> {
+        let source_name = self.resolve_name(collection_name).await?;
+        let target_name = self.resolve_name(&request.target_collection).await?;
+        if source_name == target_name {
+            return Err(StorageError::BadInput {
+                description: "Source and target collections must be different".to_string(),
+            });
+        }
+
+        if request.mode == MovePointsMode::Move {
+            self.validate_not_snapshot_mount(&source_name).await?;
+        }
+        self.validate_not_snapshot_mount(&target_name).await?;
+
+        let batch_size = request.batch_size.unwrap_or(DEFAULT_MOVE_POINTS_BATCH_SIZE);
+        if batch_size == 0 {
+            return Err(StorageError::BadInput {
+                description: "`batch_size` cannot be 0".to_string(),
+            });
+        }
+
+        // Collections are looked up for each step separately,
+        // so that the long-running transfer does not block collection management
+        let source_config = self.get_collection(&source_name).await?.config().await;
+        let target_config = self.get_collection(&target_name).await?.config().await;
+        if source_config.params.vectors != target_config.params.vectors {
+            return Err(StorageError::BadInput {
+                description: format!(
+                    "Collections {} and {} have incompatible vectors configuration",
+                    source_name, target_name
+                ),
+            });
+        }
+
+        let mut shard_ids: Vec<ShardId> = self
+            .get_collection(&source_name)
+            .await?
+            .shards_distribution(self.this_peer_id)
+            .await
+            .into_iter()
+            .map(|(shard_id, _peer_id)| shard_id)
+            .collect();
+        shard_ids.sort_unstable();
+
+        let mut points_count = 0;
+        for shard_id in shard_ids {
+            let expected_count = self
+                .count(
+                    &source_name,
+                    CountRequest {
+                        filter: request.filter.clone(),
+                        exact: true,
+                    },
+                    ShardSelectorInternal::ShardId(shard_id),
+                )
+                .await?
+                .count;
+
+            // Points are only deleted from the source once the whole shard is copied and verified
+            let mut moved_ids: Vec<PointIdType> = vec![];
+            let mut offset = None;
+            loop {
+                let scroll_result = self
+                    .scroll(
+                        &source_name,
+                        ScrollRequest {
+                            offset,
+                            limit: Some(batch_size),
+                            filter: request.filter.clone(),
+                            with_payload: Some(WithPayloadInterface::Bool(true)),
+                            with_vector: WithVector::Bool(true),
+                            ..Default::default()
+                        },
+                        ShardSelectorInternal::ShardId(shard_id),
+                    )
+                    .await?;
+                if scroll_result.points.is_empty() {
+                    break;
+                }
+
+                let ids: Vec<PointIdType> =
+                    scroll_result.points.iter().map(|point| point.id).collect();
+                let points = scroll_result
+                    .points
+                    .into_iter()
+                    .map(PointStruct::try_from)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|err| {
+                        StorageError::service_error(&format!(
+                            "Can't convert point for the move: {}",
+                            err
+                        ))
+                    })?;
+                self.update(
+                    &target_name,
+                    CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+                        PointInsertOperations::PointsList(points),
+                    )),
+                    ShardSelectorInternal::All,
+                    true,
+                )
+                .await?;
+
+                let stored_count = self
+                    .count(
+                        &target_name,
+                        CountRequest {
+                            filter: Some(Filter::new_must(Condition::HasId(HasIdCondition::from(
+                                ids.iter().copied().collect::<HashSet<_>>(),
+                            )))),
+                            exact: true,
+                        },
+                        ShardSelectorInternal::All,
+                    )
+                    .await?
+                    .count;
+                if stored_count != ids.len() {
+                    return Err(StorageError::service_error(&format!(
+                        "Integrity check failed: only {} of {} points are stored in collection {}",
+                        stored_count,
+                        ids.len(),
+                        target_name
+                    )));
+                }
+
+                moved_ids.extend(ids);
+
+                match scroll_result.next_page_offset {
+                    None => break,
+                    Some(next_page_offset) => offset = Some(next_page_offset),
+                }
+            }
+
+            if moved_ids.len() != expected_count {
+                return Err(StorageError::service_error(&format!(
+                    "Shard {} of collection {}: {} points expected, {} points copied. Collection was modified during the move, points of the shard are kept in the source collection",
+                    shard_id,
+                    source_name,
+                    expected_count,
+                    moved_ids.len()
+                )));
+            }
+
+            points_count += moved_ids.len();
+            if request.mode == MovePointsMode::Move {
+                for ids in moved_ids.chunks(batch_size) {
+                    self.update(
+                        &source_name,
+                        CollectionUpdateOperations::PointOperation(PointOperations::DeletePoints {
+                            ids: ids.to_vec(),
+                        }),
+                        ShardSelectorInternal::All,
+                        true,
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        Ok(MovePointsResult { points_count })
+    }
+
+    /// Peers of the cluster, known to this peer, including itself.
+    /// Draining and witness peers are excluded, as they should not receive new shards.
+    fn known_peers(&self) -> Vec<PeerId> {
+        let draining_peers = self.draining_peers.read();
+        let mut known_peers: Vec<_> = self
+            .channel_service
+            .id_to_address
+            .read()
+            .keys()
+            .copied()
+            .chain([self.this_peer_id])
+            .filter(|peer_id| !draining_peers.contains(peer_id) && !self.is_witness_peer(*peer_id))
+            .collect();
+        known_peers.sort_unstable();
+        known_peers.dedup();
+        known_peers
+    }
+
     fn this_peer_id(&self) -> PeerId {
         self.this_peer_id
     }
@@ -791,6 +1565,50 @@ impl TableOfContent {
         self.channel_service.id_to_address.read().clone()
     }
 
+    pub fn peer_metadata_by_id(&self) -> PeerMetadataById {
+        self.peer_metadata_by_id.read().clone()
+    }
+
+    /// Whether the peer is a witness, which only votes in consensus and never hosts shards
+    pub fn is_witness_peer(&self, peer_id: PeerId) -> bool {
+        self.peer_metadata_by_id
+            .read()
+            .get(&peer_id)
+            .map_or(false, |metadata| metadata.witness)
+    }
+
+    /// Check that `peers` run a version of Qdrant compatible with this peer,
+    /// so that they are able to exchange data of collections.
+    /// Peers, which have not reported their version yet, are only warned about.
+    pub fn check_peers_compatibility(&self, peers: &[PeerId]) -> Result<(), StorageError> {
+        let this_version = api_crate_version();
+        let peer_metadata_by_id = self.peer_metadata_by_id.read();
+        for peer_id in peers {
+            if *peer_id == self.this_peer_id {
+                continue;
+            }
+            let metadata = peer_metadata_by_id
+                .get(peer_id)
+                .cloned()
+                .unwrap_or_default();
+            match metadata.is_compatible_with(this_version) {
+                Some(true) => {}
+                Some(false) => {
+                    return Err(StorageError::BadRequest {
+                        description: format!(
+                            "Peer {peer_id} runs version {} incompatible with version {this_version} of this peer",
+                            metadata.version.unwrap_or_default()
+                        ),
+                    })
+                }
+                None => log::warn!(
+                    "Version of peer {peer_id} is unknown, it might be incompatible with version {this_version} of this peer"
+                ),
+            }
+        }
+        Ok(())
+    }
+
     pub fn collections_snapshot_sync(&self) -> consensus_state::CollectionsSnapshot {
         self.collection_management_runtime
             .block_on(self.collections_snapshot())
@@ -798,7 +1616,12 @@ impl TableOfContent {
 
     pub async fn collections_snapshot(&self) -> consensus_state::CollectionsSnapshot {
         let mut collections: HashMap<CollectionId, collection_state::State> = HashMap::new();
-        for (id, collection) in self.collections.read().await.iter() {
+        let all_collections = self.collections.read().await;
+        let snapshot_mounts = self.snapshot_mounts.read().await;
+        for (id, collection) in all_collections.iter() {
+            if snapshot_mounts.contains_key(id) {
+                continue;
+            }
             collections.insert(id.clone(), collection.state(self.this_peer_id()).await);
         }
         consensus_state::CollectionsSnapshot {
@@ -815,26 +1638,21 @@ impl TableOfContent {
             let mut collections = self.collections.write().await;
             for (id, state) in &data.collections {
                 let collection = collections.get(id);
+                let proposal_sender = self.consensus_proposal_sender.clone();
+                // In some cases on state application it might be needed to abort the transfer
+                let abort_transfer = |transfer| {
+                    if let Err(error) = proposal_sender.send(ConsensusOperations::abort_transfer(
+                        id.clone(),
+                        transfer,
+                        "sender was not up to date",
+                    )) {
+                        log::error!("Can't report transfer progress to consensus: {}", error)
+                    };
+                };
                 match collection {
                     // Update state if collection present locally
                     Some(collection) => {
                         if &collection.state(self.this_peer_id()).await != state {
-                            let proposal_sender = self.consensus_proposal_sender.clone();
-                            // In some cases on state application it might be needed to abort the transfer
-                            let abort_transfer = |transfer| {
-                                if let Err(error) =
-                                    proposal_sender.send(ConsensusOperations::abort_transfer(
-                                        id.clone(),
-                                        transfer,
-                                        "sender was not up to date",
-                                    ))
-                                {
-                                    log::error!(
-                                        "Can't report transfer progress to consensus: {}",
-                                        error
-                                    )
-                                };
-                            };
                             collection
                                 .apply_state(state.clone(), self.this_peer_id(), abort_transfer)
                                 .await?;
@@ -850,8 +1668,18 @@ impl TableOfContent {
                                 .shards
                                 .iter()
                                 .map(|(id, info)| match info {
-                                    ShardInfo::ReplicaSet { replicas: _ } => {
-                                        todo!("Handle multiple replicas in shard distribution")
+                                    // Replica sets are built from the state below
+                                    ShardInfo::ReplicaSet { replicas } => {
+                                        let peer_id = if replicas.contains_key(&self.this_peer_id) {
+                                            self.this_peer_id
+                                        } else {
+                                            replicas
+                                                .keys()
+                                                .min()
+                                                .copied()
+                                                .unwrap_or(self.this_peer_id)
+                                        };
+                                        (*id, peer_id)
                                     }
                                     ShardInfo::Single(peer_id) => (*id, *peer_id),
                                 })
@@ -859,14 +1687,24 @@ impl TableOfContent {
                         );
                         let collection = Collection::new(
                             id.to_string(),
+                            self.this_peer_id,
                             &collection_path,
                             &snapshots_path,
+                            self.snapshot_storage.clone(),
                             &state.config,
                             shard_distribution,
                             self.channel_service.clone(),
-                            self.on_peer_failure_callback(id.to_string()),
+                            Self::on_peer_failure_callback(
+                                self.consensus_proposal_sender.clone(),
+                                id.to_string(),
+                            ),
+                            self.resource_throttle.clone(),
                         )
                         .await?;
+                        // Adds the other replicas of replica sets
+                        collection
+                            .apply_state(state.clone(), self.this_peer_id(), abort_transfer)
+                            .await?;
                         collections.validate_collection_not_exists(id).await?;
                         collections.insert(id.to_string(), collection);
                     }
@@ -874,8 +1712,11 @@ impl TableOfContent {
             }
 
             // Remove collections that are present locally but are not in the snapshot state
+            let snapshot_mounts = self.snapshot_mounts.read().await;
             for collection_name in collections.keys() {
-                if !data.collections.contains_key(collection_name) {
+                if !data.collections.contains_key(collection_name)
+                    && !snapshot_mounts.contains_key(collection_name)
+                {
                     log::debug!(
                         "Deleting collection {} because it is not part of the consensus snapshot",
                         collection_name
@@ -898,33 +1739,376 @@ impl TableOfContent {
         collection_name: &str,
     ) -> Result<SnapshotDescription, StorageError> {
         let collection = self.get_collection(collection_name).await?;
-        // We want to use tmp dir inside the storage, because it is possible, that
-        // snapshot directory is mounted as network share and multiple writes to it could be slow
-        let tmp_dir = Path::new(&self.storage_config.storage_path).join(SNAPSHOTS_TMP_DIR);
+        let tmp_dir = self.snapshots_temp_path();
         tokio::fs::create_dir_all(&tmp_dir).await?;
-        Ok(collection.create_snapshot(&tmp_dir).await?)
+        Ok(collection
+            .create_snapshot(&tmp_dir, self.transfer_runtime.handle())
+            .await?)
+    }
+
+    /// Create snapshot of the local shard, see `Collection::create_shard_snapshot`
+    pub async fn create_shard_snapshot(
+        &self,
+        collection_name: &str,
+        shard_id: ShardId,
+    ) -> Result<SnapshotDescription, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        let tmp_dir = self.snapshots_temp_path();
+        tokio::fs::create_dir_all(&tmp_dir).await?;
+        Ok(collection
+            .create_shard_snapshot(shard_id, &tmp_dir, self.transfer_runtime.handle())
+            .await?)
+    }
+
+    /// Restore the local shard from its snapshot, see `Collection::restore_shard_snapshot`
+    pub async fn restore_shard_snapshot(
+        &self,
+        collection_name: &str,
+        shard_id: ShardId,
+        snapshot_name: &str,
+    ) -> Result<bool, StorageError> {
+        self.validate_not_snapshot_mount(collection_name).await?;
+        let collection = self.get_collection(collection_name).await?;
+        collection
+            .restore_shard_snapshot(shard_id, snapshot_name, self.transfer_runtime.handle())
+            .await?;
+        Ok(true)
+    }
+
+    /// Download snapshot of the local shard and restore the shard from it, see `Collection::restore_shard_snapshot`
+    pub async fn recover_shard_snapshot(
+        &self,
+        collection_name: &str,
+        shard_id: ShardId,
+        recover: &SnapshotRecover,
+    ) -> Result<bool, StorageError> {
+        self.validate_not_snapshot_mount(collection_name).await?;
+        let collection = self.get_collection(collection_name).await?;
+        let snapshot_path = download_snapshot(
+            &recover.location,
+            recover.checksum.as_deref(),
+            &collection.shard_snapshots_path(shard_id),
+        )
+        .await?;
+        let snapshot_name = snapshot_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        collection
+            .restore_shard_snapshot(shard_id, snapshot_name, self.transfer_runtime.handle())
+            .await?;
+        Ok(true)
+    }
+
+    /// Download snapshot of the collection and restore local shards of the collection from it,
+    /// see `Collection::restore_local_shards`
+    pub async fn recover_snapshot(
+        &self,
+        collection_name: &str,
+        recover: &SnapshotRecover,
+    ) -> Result<bool, StorageError> {
+        self.validate_not_snapshot_mount(collection_name).await?;
+        let collection = self.get_collection(collection_name).await?;
+        let snapshot_path = download_snapshot(
+            &recover.location,
+            recover.checksum.as_deref(),
+            &self.create_snapshots_path(collection_name).await?,
+        )
+        .await?;
+        collection
+            .restore_local_shards(&snapshot_path, self.transfer_runtime.handle())
+            .await?;
+        Ok(true)
+    }
+
+    /// Restore local shards of the collection from the local snapshot file,
+    /// see `Collection::restore_local_shards`
+    pub async fn restore_local_shards(
+        &self,
+        collection_name: &str,
+        snapshot_path: &Path,
+    ) -> Result<Vec<ShardId>, StorageError> {
+        self.validate_not_snapshot_mount(collection_name).await?;
+        let collection = self.get_collection(collection_name).await?;
+        Ok(collection
+            .restore_local_shards(snapshot_path, self.transfer_runtime.handle())
+            .await?)
+    }
+
+    /// List operations of the WAL of the local shard, see `Collection::wal_operations`
+    pub async fn wal_operations(
+        &self,
+        collection_name: &str,
+        shard_id: ShardId,
+        offset: Option<SeqNumberType>,
+        limit: usize,
+    ) -> Result<WalInfo, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        Ok(collection.wal_operations(shard_id, offset, limit).await?)
+    }
+
+    /// Re-apply operations of the WAL of the local shard, see `Collection::replay_wal`
+    pub async fn replay_wal(
+        &self,
+        collection_name: &str,
+        shard_id: ShardId,
+        from_op_num: SeqNumberType,
+    ) -> Result<usize, StorageError> {
+        self.validate_not_snapshot_mount(collection_name).await?;
+        let collection = self.get_collection(collection_name).await?;
+        Ok(collection.replay_wal(shard_id, from_op_num).await?)
+    }
+
+    /// Remove operations of the WAL of the local shard, see `Collection::truncate_wal`
+    pub async fn truncate_wal(
+        &self,
+        collection_name: &str,
+        shard_id: ShardId,
+        from_op_num: SeqNumberType,
+    ) -> Result<bool, StorageError> {
+        self.validate_not_snapshot_mount(collection_name).await?;
+        let collection = self.get_collection(collection_name).await?;
+        collection.truncate_wal(shard_id, from_op_num).await?;
+        Ok(true)
+    }
+
+    /// Delete points of the local shard, which belong to other shards, see `Collection::cleanup_shard`
+    pub async fn cleanup_shard(
+        &self,
+        collection_name: &str,
+        shard_id: ShardId,
+        batch_size: usize,
+        on_progress: impl FnMut(&ShardCleanupProgress),
+    ) -> Result<ShardCleanupProgress, StorageError> {
+        self.validate_not_snapshot_mount(collection_name).await?;
+        let collection = self.get_collection(collection_name).await?;
+        Ok(collection
+            .cleanup_shard(shard_id, batch_size, on_progress)
+            .await?)
+    }
+
+    /// Export segments of the local shard with built indexes, see `Collection::export_segments`
+    pub async fn export_segments(
+        &self,
+        collection_name: &str,
+        shard_id: ShardId,
+    ) -> Result<Vec<SnapshotDescription>, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        let tmp_dir = self.snapshots_temp_path();
+        tokio::fs::create_dir_all(&tmp_dir).await?;
+        Ok(collection.export_segments(shard_id, &tmp_dir).await?)
+    }
+
+    /// Import exported segment into the local shard, see `Collection::import_segment`
+    pub async fn import_segment(
+        &self,
+        collection_name: &str,
+        shard_id: ShardId,
+        export_name: &str,
+    ) -> Result<bool, StorageError> {
+        self.validate_not_snapshot_mount(collection_name).await?;
+        let collection = self.get_collection(collection_name).await?;
+        collection.import_segment(shard_id, export_name).await?;
+        Ok(true)
+    }
+
+    fn snapshot_mounts_path(&self) -> PathBuf {
+        Path::new(&self.storage_config.storage_path).join(SNAPSHOT_MOUNTS_DIR)
+    }
+
+    /// Returns `true` if the collection is a read-only snapshot mount
+    pub async fn is_snapshot_mount(&self, collection_name: &str) -> bool {
+        self.snapshot_mounts
+            .read()
+            .await
+            .contains_key(collection_name)
+    }
+
+    /// List of all collections mounted from snapshots
+    pub async fn snapshot_mounts(&self) -> Vec<String> {
+        self.snapshot_mounts.read().await.keys().cloned().collect()
+    }
+
+    async fn validate_not_snapshot_mount(&self, collection_name: &str) -> Result<(), StorageError> {
+        if self.is_snapshot_mount(collection_name).await {
+            return Err(StorageError::BadRequest {
+                description: format!(
+                    "Collection `{}` is a read-only snapshot mount",
+                    collection_name
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Mount a snapshot of the collection as a read-only collection under a temporary name
+    ///
+    /// The snapshot is unpacked into a separate directory, so the live collection is not affected.
+    /// Mounted collections exist only on this peer, are not a part of the consensus state
+    /// and are removed on unmount or restart of the service.
+    ///
+    /// # Arguments
+    ///
+    /// * `collection_name` - collection the snapshot was made of, the collection itself might be already deleted
+    /// * `snapshot_name` - name of the snapshot file
+    /// * `mount_name` - name to mount the snapshot under
+    pub async fn mount_snapshot(
+        &self,
+        collection_name: &str,
+        snapshot_name: &str,
+        mount_name: &str,
+    ) -> Result<bool, StorageError> {
+        let snapshot_path = self.collection_snapshot_path(collection_name, snapshot_name);
+
+        self.collections
+            .read()
+            .await
+            .validate_collection_not_exists(mount_name)
+            .await?;
+        if self
+            .alias_persistence
+            .read()
+            .await
+            .get(mount_name)
+            .is_some()
+        {
+            return Err(StorageError::BadInput {
+                description: format!("Alias `{}` already exists!", mount_name),
+            });
+        }
+
+        let mount_path = self.snapshot_mounts_path().join(mount_name);
+        if mount_path.exists() {
+            return Err(StorageError::BadInput {
+                description: format!("Snapshot mount `{}` already exists!", mount_name),
+            });
+        }
+        let collection_path = mount_path.join("collection");
+        let snapshots_path = mount_path.join("snapshots");
+        tokio::fs::create_dir_all(&snapshots_path).await?;
+
+        let fetched = self
+            .snapshot_storage
+            .fetch_snapshot(&snapshot_path, &mount_path)
+            .await;
+        let snapshot_file = match fetched {
+            Ok(snapshot_file) => snapshot_file,
+            Err(err) => {
+                tokio::fs::remove_dir_all(&mount_path).await?;
+                return Err(match err {
+                    CollectionError::NotFound { .. } => StorageError::NotFound {
+                        description: format!(
+                            "Snapshot {} of collection {} not found",
+                            snapshot_name, collection_name
+                        ),
+                    },
+                    err => err.into(),
+                });
+            }
+        };
+        let unpack_path = collection_path.clone();
+        let unpack_source = snapshot_file.clone();
+        let restored = tokio::task::spawn_blocking(move || {
+            Collection::restore_snapshot(&unpack_source, &unpack_path)
+        })
+        .await
+        .map_err(|err| StorageError::ServiceError {
+            description: format!("Can't unpack snapshot {}: {}", snapshot_name, err),
+        })
+        .and_then(|result| result.map_err(StorageError::from));
+        // Snapshot, downloaded from the object store, is not needed after unpacking
+        if snapshot_file != snapshot_path {
+            tokio::fs::remove_file(&snapshot_file).await?;
+        }
+        if let Err(err) = restored {
+            tokio::fs::remove_dir_all(&mount_path).await?;
+            return Err(err);
+        }
+
+        let mut collection = Collection::load(
+            mount_name.to_string(),
+            self.this_peer_id,
+            &collection_path,
+            &snapshots_path,
+            // Snapshots of the mount are only kept locally, next to its data
+            SnapshotStorage::LocalFs,
+            self.channel_service.clone(),
+            // Mounts are not part of the consensus, failures of replicas are not reported
+            Arc::new(|_peer_id, _shard_id| Box::new(async {})),
+            self.resource_throttle.clone(),
+        )
+        .await;
+
+        // Remote shards would serve the live data of other peers
+        let has_remote_shards = collection
+            .shards_distribution(self.this_peer_id)
+            .await
+            .into_iter()
+            .any(|(_shard_id, peer_id)| peer_id != self.this_peer_id);
+
+        let mut collections = self.collections.write().await;
+        let validation = if has_remote_shards {
+            Err(StorageError::BadRequest {
+                description: format!(
+                    "Snapshot {} contains remote shards, only fully local snapshots can be mounted",
+                    snapshot_name
+                ),
+            })
+        } else {
+            collections.validate_collection_not_exists(mount_name).await
+        };
+        if let Err(err) = validation {
+            drop(collections);
+            collection.before_drop().await;
+            drop(collection);
+            tokio::fs::remove_dir_all(&mount_path).await?;
+            return Err(err);
+        }
+
+        collections.insert(mount_name.to_string(), collection);
+        self.snapshot_mounts
+            .write()
+            .await
+            .insert(mount_name.to_string(), mount_path);
+        Ok(true)
+    }
+
+    /// Remove collection mounted from a snapshot along with its unpacked data
+    pub async fn unmount_snapshot(&self, mount_name: &str) -> Result<bool, StorageError> {
+        let mut collections = self.collections.write().await;
+        let mount_path = match self.snapshot_mounts.write().await.remove(mount_name) {
+            Some(mount_path) => mount_path,
+            None => return Ok(false),
+        };
+        if let Some(mut removed) = collections.remove(mount_name) {
+            removed.before_drop().await;
+        }
+        drop(collections);
+        remove_dir_all(&mount_path).map_err(|err| StorageError::ServiceError {
+            description: format!("Can't unmount snapshot {}, error: {}", mount_name, err),
+        })?;
+        Ok(true)
     }
 
     pub async fn suggest_shard_distribution(
         &self,
         op: &CreateCollectionOperation,
         suggested_shard_number: u32,
-    ) -> ShardDistributionProposal {
+    ) -> Result<ShardDistributionProposal, StorageError> {
         let shard_number = op
             .create_collection
             .shard_number
             .unwrap_or(suggested_shard_number);
-        let mut known_peers_set: HashSet<_> = self
-            .channel_service
-            .id_to_address
-            .read()
-            .keys()
-            .copied()
-            .collect();
-        known_peers_set.insert(self.this_peer_id());
-        let known_peers: Vec<_> = known_peers_set.into_iter().collect();
+        let known_peers = self.known_peers();
 
-        let shard_distribution = ShardDistributionProposal::new(shard_number, &known_peers, vec![]);
+        let placement = op.create_collection.placement.clone().unwrap_or_default();
+        let shard_distribution = ShardDistributionProposal::new(
+            shard_number,
+            &known_peers,
+            vec![],
+            &placement,
+            &self.peer_metadata_by_id(),
+        )?;
 
         log::debug!(
             "Suggesting distribution for {} shards for collection '{}' among {} peers {:?}",
@@ -933,7 +2117,7 @@ impl TableOfContent {
             known_peers.len(),
             shard_distribution.distribution
         );
-        shard_distribution
+        Ok(shard_distribution)
     }
 
     pub async fn get_telemetry_data(&self) -> Vec<CollectionTelemetry> {
@@ -950,6 +2134,9 @@ impl TableOfContent {
     }
 
     pub async fn peer_has_shards(&self, peer_id: PeerId) -> bool {
+        if self.is_witness_peer(peer_id) {
+            return false;
+        }
         for collection in self.collections.read().await.values() {
             let state = collection.state(self.this_peer_id()).await;
             let peers_with_shards: HashSet<_> = state
@@ -1019,6 +2206,18 @@ impl CollectionContainer for TableOfContent {
                 .block_on(self.channel_service.remove_peer(peer_id));
         }
     }
+
+    fn drain_peer(&self, peer_id: PeerId) {
+        if self.this_peer_id != peer_id {
+            return;
+        }
+        if let Err(err) = self
+            .collection_management_runtime
+            .block_on(self.drain_local_shards())
+        {
+            log::error!("Can't drain peer {peer_id}: {err}");
+        }
+    }
 }
 
 // `TableOfContent` should not be dropped from async context.
@@ -1031,3 +2230,21 @@ impl Drop for TableOfContent {
         });
     }
 }
+
+fn create_transfer_runtime(max_transfer_threads: usize) -> std::io::Result<Runtime> {
+    let mut transfer_threads = max_transfer_threads;
+
+    if transfer_threads == 0 {
+        transfer_threads = std::cmp::max(1, num_cpus::get() / 4);
+    }
+
+    runtime::Builder::new_multi_thread()
+        .worker_threads(transfer_threads)
+        .enable_all()
+        .thread_name_fn(|| {
+            static ATOMIC_ID: AtomicUsize = AtomicUsize::new(0);
+            let id = ATOMIC_ID.fetch_add(1, Ordering::SeqCst);
+            format!("transfer-{}", id)
+        })
+        .build()
+}
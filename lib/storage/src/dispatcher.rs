@@ -2,6 +2,7 @@ use std::ops::Deref;
 use std::sync::Arc;
 use std::time::Duration;
 
+use crate::content_manager::collection_meta_ops::ShardTransferOperations;
 use crate::{
     ClusterStatus, CollectionMetaOperations, ConsensusOperations, ConsensusStateRef, StorageError,
     TableOfContent,
@@ -44,17 +45,7 @@ impl Dispatcher {
     ) -> Result<bool, StorageError> {
         // if distributed deployment is enabled
         if let Some(state) = self.consensus_state.as_ref() {
-            let op = match operation {
-                CollectionMetaOperations::CreateCollection(op) => {
-                    let number_of_peers = state.0.peer_count();
-                    let shard_distribution = self
-                        .toc
-                        .suggest_shard_distribution(&op, number_of_peers as u32)
-                        .await;
-                    CollectionMetaOperations::CreateCollectionDistributed(op, shard_distribution)
-                }
-                op => op,
-            };
+            let op = self.prepare_distributed_op(state, operation).await?;
             state
                 .propose_consensus_op_with_await(
                     ConsensusOperations::CollectionMeta(Box::new(op)),
@@ -66,6 +57,70 @@ impl Dispatcher {
         }
     }
 
+    /// Checks, if the operation can be applied, without submitting it.
+    /// Returns descriptions of the changes, which the operation would make.
+    pub async fn validate_collection_meta_op(
+        &self,
+        operation: CollectionMetaOperations,
+    ) -> Result<Vec<String>, StorageError> {
+        let operation = match self.consensus_state.as_ref() {
+            Some(state) => self.prepare_distributed_op(state, operation).await?,
+            None => operation,
+        };
+        self.toc.validate_collection_meta_op(&operation).await
+    }
+
+    /// Converts the operation into the form, which is proposed to consensus,
+    /// and checks that the involved peers can perform it.
+    async fn prepare_distributed_op(
+        &self,
+        state: &ConsensusStateRef,
+        operation: CollectionMetaOperations,
+    ) -> Result<CollectionMetaOperations, StorageError> {
+        let op = match operation {
+            CollectionMetaOperations::CreateCollection(op) => {
+                let number_of_peers = state.0.peer_count();
+                let shard_distribution = self
+                    .toc
+                    .suggest_shard_distribution(&op, number_of_peers as u32)
+                    .await?;
+                CollectionMetaOperations::CreateCollectionDistributed(op, shard_distribution)
+            }
+            op => op,
+        };
+        // Peers, which exchange data of collections, should run compatible versions
+        let involved_peers = match &op {
+            CollectionMetaOperations::CreateCollectionDistributed(_, shard_distribution) => {
+                shard_distribution
+                    .distribution
+                    .iter()
+                    .map(|(_, peer_id)| *peer_id)
+                    .collect()
+            }
+            CollectionMetaOperations::TransferShard(
+                _,
+                ShardTransferOperations::Start(transfer),
+            ) => {
+                vec![transfer.from, transfer.to]
+            }
+            _ => vec![],
+        };
+        self.toc.check_peers_compatibility(&involved_peers)?;
+        Ok(op)
+    }
+
+    /// Wait until the local state reflects all operations, committed by consensus at the moment of the call.
+    /// Does nothing if distributed deployment is disabled.
+    pub async fn await_consistency(
+        &self,
+        wait_timeout: Option<Duration>,
+    ) -> Result<(), StorageError> {
+        match self.consensus_state.as_ref() {
+            Some(state) => state.await_read_index(wait_timeout).await,
+            None => Ok(()),
+        }
+    }
+
     pub fn cluster_status(&self) -> ClusterStatus {
         match self.consensus_state.as_ref() {
             Some(state) => state.cluster_status(),
@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 
+use chrono::NaiveDateTime;
 use collection::config::WalConfig;
+use collection::operations::snapshot_storage_ops::SnapshotsConfig;
 use collection::optimizers_builder::OptimizersConfig;
+use collection::resource_watchdog::ResourceWatchdogConfig;
 use collection::shard::PeerId;
 use schemars::JsonSchema;
 use segment::telemetry::{telemetry_hash, Anonymize};
@@ -14,6 +17,18 @@ pub type PeerAddressById = HashMap<PeerId, Uri>;
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 pub struct PerformanceConfig {
     pub max_search_threads: usize,
+    /// Use io_uring to read vectors of on-disk (mmap) storages during search.
+    /// Only takes effect on Linux with the `async_scorer` feature compiled in.
+    #[serde(default)]
+    pub async_scorer: bool,
+    /// Read mem-mapped vector storages into page cache in background right after loading,
+    /// to avoid slow first searches after a cold start.
+    #[serde(default)]
+    pub mmap_prefetch: bool,
+    /// Number of threads of the runtime for shard transfers and snapshot IO, separate from search.
+    /// If 0 - auto selection.
+    #[serde(default)]
+    pub max_transfer_threads: usize,
 }
 
 /// Global configuration of the storage, loaded on the service launch, default stored in ./config
@@ -22,12 +37,21 @@ pub struct StorageConfig {
     pub storage_path: String,
     #[serde(default = "default_snapshots_path")]
     pub snapshots_path: String,
+    /// Directory to stage snapshots in while they are being created.
+    /// If not set - `snapshots_tmp` inside the storage directory.
+    #[serde(default)]
+    pub snapshots_temp_path: Option<String>,
+    /// Where collection snapshots are kept, local snapshots directory by default
+    #[serde(default)]
+    pub snapshots_config: SnapshotsConfig,
     #[serde(default = "default_on_disk_payload")]
     pub on_disk_payload: bool,
     pub optimizers: OptimizersConfig,
     pub wal: WalConfig,
     pub performance: PerformanceConfig,
     pub hnsw_index: HnswConfig,
+    #[serde(default)]
+    pub resource_watchdog: ResourceWatchdogConfig,
 }
 
 fn default_snapshots_path() -> String {
@@ -42,6 +66,12 @@ fn default_on_disk_payload() -> bool {
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 pub struct PeerInfo {
     pub uri: String,
+    /// If true, shards are moved away from the peer, and it leaves the cluster once empty
+    #[serde(default)]
+    pub draining: bool,
+    /// If true, the peer only votes in consensus and never hosts shards
+    #[serde(default)]
+    pub witness: bool,
     // ToDo: How long ago was the last communication? In milliseconds
     // pub last_responded_millis: usize
 }
@@ -121,10 +151,107 @@ pub enum ConsensusThreadStatus {
     StoppedWithErr { err: String },
 }
 
+/// Internal state of the Raft consensus on this peer, used to diagnose stuck consensus
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct ConsensusDebugInfo {
+    /// Index of the first entry, available in the consensus WAL
+    pub first_index: u64,
+    /// Index of the last entry, stored in the consensus WAL
+    pub last_index: u64,
+    /// Index of the latest committed entry
+    pub commit: u64,
+    /// Index of the latest entry, applied on this peer
+    pub last_applied: Option<u64>,
+    /// Number of committed entries, which are not applied yet
+    pub unapplied_entries_count: usize,
+    /// Oldest entries, which are not applied yet
+    pub unapplied_entries: Vec<EntryDebugInfo>,
+    /// Latest entry, which is compacted from WAL or received with a snapshot
+    pub latest_snapshot: SnapshotDebugInfo,
+    /// Replication progress of each peer. Only known if this peer is the leader
+    pub peers_progress: Option<HashMap<PeerId, PeerProgress>>,
+}
+
+/// Raft entry stored in the consensus WAL
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct EntryDebugInfo {
+    pub index: u64,
+    pub term: u64,
+    /// Human-readable content of the entry, if it can be decoded
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct SnapshotDebugInfo {
+    pub index: u64,
+    pub term: u64,
+}
+
+/// Replication progress of a peer, as seen by the leader
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct PeerProgress {
+    /// Index of the latest entry, known to be replicated to the peer
+    pub matched: u64,
+    /// Index of the next entry to send to the peer
+    pub next_index: u64,
+    pub state: ProgressState,
+    /// Index of the snapshot being sent to the peer, 0 if there is none
+    pub pending_snapshot: u64,
+    /// If the peer was active during the last election timeout
+    pub recent_active: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, JsonSchema, Deserialize)]
+pub enum ProgressState {
+    /// The leader is searching for the latest entry matched by the peer
+    Probe,
+    /// Entries are replicated to the peer optimistically
+    Replicate,
+    /// The peer is receiving a snapshot
+    Snapshot,
+}
+
+/// Record of the operation, applied by consensus on this peer
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct AuditRecord {
+    /// Index of the Raft entry, which holds the operation
+    pub index: u64,
+    /// Term of the Raft entry, which holds the operation
+    pub term: u64,
+    /// Peer, which proposed the operation. Unknown for configuration changes and entries proposed by previous versions
+    pub proposer: Option<PeerId>,
+    /// Human-readable operation
+    pub operation: String,
+    /// Result of the operation, if it succeeded
+    pub result: Option<bool>,
+    /// Error of the operation, if it failed
+    pub error: Option<String>,
+    /// Time of application on this peer, UTC
+    pub timestamp: NaiveDateTime,
+}
+
+impl From<&raft::Progress> for PeerProgress {
+    fn from(progress: &raft::Progress) -> Self {
+        Self {
+            matched: progress.matched,
+            next_index: progress.next_idx,
+            state: match progress.state {
+                raft::ProgressState::Probe => ProgressState::Probe,
+                raft::ProgressState::Replicate => ProgressState::Replicate,
+                raft::ProgressState::Snapshot => ProgressState::Snapshot,
+            },
+            pending_snapshot: progress.pending_snapshot,
+            recent_active: progress.recent_active,
+        }
+    }
+}
+
 impl Anonymize for PeerInfo {
     fn anonymize(&self) -> Self {
         PeerInfo {
             uri: telemetry_hash(&self.uri),
+            draining: self.draining,
+            witness: self.witness,
         }
     }
 }
@@ -6,7 +6,7 @@ mod tests {
     use std::sync::Arc;
 
     use collection::config::VectorParams;
-    use collection::optimizers_builder::OptimizersConfig;
+    use collection::optimizers_builder::{MergePolicy, OptimizersConfig};
     use segment::types::Distance;
     use storage::content_manager::collection_meta_ops::{
         ChangeAliasesOperation, CollectionMetaOperations, CreateAlias, CreateCollection,
@@ -31,6 +31,8 @@ mod tests {
                 .to_str()
                 .unwrap()
                 .to_string(),
+            snapshots_temp_path: None,
+            snapshots_config: Default::default(),
             on_disk_payload: false,
             optimizers: OptimizersConfig {
                 deleted_threshold: 0.5,
@@ -41,12 +43,18 @@ mod tests {
                 indexing_threshold: 100,
                 flush_interval_sec: 2,
                 max_optimization_threads: 2,
+                max_segment_disk_size: None,
+                merge_policy: MergePolicy::Auto,
             },
             wal: Default::default(),
             performance: PerformanceConfig {
                 max_search_threads: 1,
+                async_scorer: false,
+                mmap_prefetch: false,
+                max_transfer_threads: 1,
             },
             hnsw_index: Default::default(),
+            resource_watchdog: Default::default(),
         };
 
         let runtime = Runtime::new().unwrap();
@@ -73,13 +81,27 @@ mod tests {
                             vectors: VectorParams {
                                 size: NonZeroU64::new(10).unwrap(),
                                 distance: Distance::Cosine,
+                                quantization_config: None,
+                                on_disk: None,
+                                multivector_config: None,
+                                hnsw_config: None,
+                                normalize: None,
                             }
                             .into(),
                             hnsw_config: None,
                             wal_config: None,
                             optimizers_config: None,
+                            sparse_vectors: None,
                             shard_number: Some(1),
                             on_disk_payload: None,
+                            max_response_payload_bytes: None,
+                            payload_transformers: None,
+                            ivf_config: None,
+                            placement: None,
+                            read_fan_out_factor: None,
+                            write_consistency: None,
+                            read_consistency: None,
+                            hash_ring: None,
                         },
                     }),
                     None,
@@ -127,5 +149,41 @@ mod tests {
         let _ = handle
             .block_on(dispatcher.get_collection("test_alias3"))
             .unwrap();
+
+        // Dry run only describes the changes, depending on the previous actions
+        let changes = handle
+            .block_on(dispatcher.validate_collection_meta_op(
+                CollectionMetaOperations::ChangeAliases(ChangeAliasesOperation {
+                    actions: vec![
+                        CreateAlias {
+                            collection_name: "test".to_string(),
+                            alias_name: "test_alias4".to_string(),
+                        }
+                        .into(),
+                        RenameAlias {
+                            old_alias_name: "test_alias4".to_string(),
+                            new_alias_name: "test_alias5".to_string(),
+                        }
+                        .into(),
+                    ],
+                }),
+            ))
+            .unwrap();
+        assert_eq!(changes.len(), 2);
+        assert!(handle
+            .block_on(dispatcher.get_collection("test_alias4"))
+            .is_err());
+
+        assert!(handle
+            .block_on(dispatcher.validate_collection_meta_op(
+                CollectionMetaOperations::ChangeAliases(ChangeAliasesOperation {
+                    actions: vec![RenameAlias {
+                        old_alias_name: "test_alias".to_string(),
+                        new_alias_name: "test_alias6".to_string(),
+                    }
+                    .into()],
+                }),
+            ))
+            .is_err());
     }
 }
@@ -0,0 +1,322 @@
+#[cfg(all(test))]
+mod tests {
+    use std::num::{NonZeroU32, NonZeroU64};
+    use std::path::Path;
+    use std::sync::mpsc::Receiver;
+    use std::sync::Arc;
+
+    use collection::config::VectorParams;
+    use collection::operations::config_diff::CollectionParamsDiff;
+    use collection::operations::point_ops::{PointInsertOperations, PointOperations, PointStruct};
+    use collection::operations::shard_selector::ShardSelectorInternal;
+    use collection::operations::types::{CountRequest, MovePointsMode, MovePointsRequest};
+    use collection::operations::CollectionUpdateOperations;
+    use collection::optimizers_builder::{MergePolicy, OptimizersConfig};
+    use collection::shard::replica_set::ReplicaState;
+    use segment::types::{Condition, Distance, FieldCondition, Filter};
+    use serde_json::json;
+    use storage::content_manager::collection_meta_ops::{
+        CollectionMetaOperations, CreateCollection, CreateCollectionOperation,
+        SetShardReplicaState, UpdateCollection, UpdateCollectionOperation,
+    };
+    use storage::content_manager::consensus::operation_sender::OperationSender;
+    use storage::content_manager::consensus_ops::ConsensusOperations;
+    use storage::content_manager::toc::TableOfContent;
+    use storage::dispatcher::Dispatcher;
+    use storage::types::{PerformanceConfig, StorageConfig};
+    use tempfile::Builder;
+    use tokio::runtime::{Handle, Runtime};
+
+    fn create_collection_op(name: &str, size: u64) -> CollectionMetaOperations {
+        CollectionMetaOperations::CreateCollection(CreateCollectionOperation {
+            collection_name: name.to_string(),
+            create_collection: CreateCollection {
+                vectors: VectorParams {
+                    size: NonZeroU64::new(size).unwrap(),
+                    distance: Distance::Dot,
+                    quantization_config: None,
+                    on_disk: None,
+                    multivector_config: None,
+                    hnsw_config: None,
+                    normalize: None,
+                }
+                .into(),
+                hnsw_config: None,
+                wal_config: None,
+                optimizers_config: None,
+                sparse_vectors: None,
+                shard_number: Some(2),
+                on_disk_payload: None,
+                max_response_payload_bytes: None,
+                payload_transformers: None,
+                ivf_config: None,
+                placement: None,
+                read_fan_out_factor: None,
+                write_consistency: None,
+                read_consistency: None,
+                hash_ring: None,
+            },
+        })
+    }
+
+    fn set_replica_state_op(
+        name: &str,
+        shard_id: u32,
+        state: ReplicaState,
+    ) -> CollectionMetaOperations {
+        CollectionMetaOperations::SetShardReplicaState(SetShardReplicaState {
+            collection_name: name.to_string(),
+            shard_id,
+            peer_id: 0,
+            state,
+        })
+    }
+
+    fn count_request(filter: Option<Filter>) -> CountRequest {
+        CountRequest {
+            filter,
+            exact: true,
+        }
+    }
+
+    fn move_request(target_collection: &str, filter: Option<Filter>) -> MovePointsRequest {
+        MovePointsRequest {
+            target_collection: target_collection.to_string(),
+            filter,
+            mode: MovePointsMode::Move,
+            batch_size: Some(7),
+        }
+    }
+
+    fn count(toc: &TableOfContent, handle: &Handle, collection_name: &str) -> usize {
+        handle
+            .block_on(toc.count(
+                collection_name,
+                count_request(None),
+                ShardSelectorInternal::All,
+            ))
+            .unwrap()
+            .count
+    }
+
+    /// Creates a single peer storage with the given collections
+    fn init_toc(
+        storage_path: &Path,
+        collections: &[(&str, u64)],
+    ) -> (Arc<TableOfContent>, Handle, Receiver<ConsensusOperations>) {
+        let config = StorageConfig {
+            storage_path: storage_path.to_str().unwrap().to_string(),
+            snapshots_path: storage_path.join("snapshots").to_str().unwrap().to_string(),
+            snapshots_temp_path: None,
+            snapshots_config: Default::default(),
+            on_disk_payload: false,
+            optimizers: OptimizersConfig {
+                deleted_threshold: 0.5,
+                vacuum_min_vector_number: 100,
+                default_segment_number: 2,
+                max_segment_size: None,
+                memmap_threshold: Some(100),
+                indexing_threshold: 100,
+                flush_interval_sec: 2,
+                max_optimization_threads: 2,
+                max_segment_disk_size: None,
+                merge_policy: MergePolicy::Auto,
+            },
+            wal: Default::default(),
+            performance: PerformanceConfig {
+                max_search_threads: 1,
+                async_scorer: false,
+                mmap_prefetch: false,
+                max_transfer_threads: 1,
+            },
+            hnsw_index: Default::default(),
+            resource_watchdog: Default::default(),
+        };
+
+        let runtime = Runtime::new().unwrap();
+        let handle = runtime.handle().clone();
+
+        let (propose_sender, propose_receiver) = std::sync::mpsc::channel();
+        let propose_operation_sender = OperationSender::new(propose_sender);
+
+        let toc = Arc::new(TableOfContent::new(
+            &config,
+            runtime,
+            Default::default(),
+            0,
+            propose_operation_sender,
+        ));
+        let dispatcher = Dispatcher::new(toc.clone());
+        for (name, size) in collections {
+            handle
+                .block_on(
+                    dispatcher.submit_collection_meta_op(create_collection_op(name, *size), None),
+                )
+                .unwrap();
+        }
+
+        (toc, handle, propose_receiver)
+    }
+
+    fn upsert_points(
+        toc: &TableOfContent,
+        handle: &Handle,
+        collection_name: &str,
+        num_points: usize,
+    ) {
+        let points = (0..num_points)
+            .map(|i| PointStruct {
+                id: (i as u64).into(),
+                vector: vec![i as f32, 0.0, 0.0, 1.0].into(),
+                payload: Some(json!({ "even": i % 2 == 0 }).into()),
+            })
+            .collect();
+        handle
+            .block_on(toc.update(
+                collection_name,
+                CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+                    PointInsertOperations::PointsList(points),
+                )),
+                ShardSelectorInternal::All,
+                true,
+            ))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_move_points() {
+        let storage_dir = Builder::new().prefix("storage").tempdir().unwrap();
+        let (toc, handle, _propose_receiver) = init_toc(
+            storage_dir.path(),
+            &[("source", 4), ("target", 4), ("other", 8)],
+        );
+
+        let num_points = 50;
+        upsert_points(&toc, &handle, "source", num_points);
+
+        // Vectors configuration must match
+        let incompatible = handle.block_on(toc.move_points(
+            "source",
+            MovePointsRequest {
+                target_collection: "other".to_string(),
+                filter: None,
+                mode: MovePointsMode::Copy,
+                batch_size: None,
+            },
+        ));
+        assert!(incompatible.is_err());
+
+        let even_filter = Filter::new_must(Condition::Field(FieldCondition::new_match(
+            "even".to_string(),
+            true.into(),
+        )));
+
+        // Copy keeps points in the source collection
+        let copied = handle
+            .block_on(toc.move_points(
+                "source",
+                MovePointsRequest {
+                    target_collection: "target".to_string(),
+                    filter: Some(even_filter.clone()),
+                    mode: MovePointsMode::Copy,
+                    batch_size: Some(7),
+                },
+            ))
+            .unwrap();
+        assert_eq!(copied.points_count, num_points / 2);
+        assert_eq!(count(&toc, &handle, "source"), num_points);
+
+        // Move deletes points from the source collection
+        let moved = handle
+            .block_on(toc.move_points("source", move_request("target", None)))
+            .unwrap();
+        assert_eq!(moved.points_count, num_points);
+
+        assert_eq!(count(&toc, &handle, "source"), 0);
+        assert_eq!(count(&toc, &handle, "target"), num_points);
+        let target_even_count = handle
+            .block_on(toc.count(
+                "target",
+                count_request(Some(even_filter)),
+                ShardSelectorInternal::All,
+            ))
+            .unwrap();
+        assert_eq!(target_even_count.count, num_points / 2);
+    }
+
+    #[test]
+    fn test_move_points_to_missing_collection() {
+        let storage_dir = Builder::new().prefix("storage").tempdir().unwrap();
+        let (toc, handle, _propose_receiver) = init_toc(storage_dir.path(), &[("source", 4)]);
+
+        let num_points = 20;
+        upsert_points(&toc, &handle, "source", num_points);
+
+        let result = handle.block_on(toc.move_points("source", move_request("missing", None)));
+        assert!(result.is_err());
+        assert_eq!(count(&toc, &handle, "source"), num_points);
+    }
+
+    #[test]
+    fn test_move_points_partial_failure() {
+        let storage_dir = Builder::new().prefix("storage").tempdir().unwrap();
+        let (toc, handle, _propose_receiver) =
+            init_toc(storage_dir.path(), &[("source", 4), ("target", 4)]);
+        let dispatcher = Dispatcher::new(toc.clone());
+
+        let num_points = 50;
+        upsert_points(&toc, &handle, "source", num_points);
+
+        // The second shard of the target collection is not available
+        let replicate = CollectionMetaOperations::UpdateCollection(UpdateCollectionOperation {
+            collection_name: "target".to_string(),
+            update_collection: UpdateCollection {
+                optimizers_config: None,
+                params: Some(CollectionParamsDiff {
+                    replication_factor: Some(NonZeroU32::new(2).unwrap()),
+                    read_fan_out_factor: None,
+                    read_consistency: None,
+                }),
+                expected_version: None,
+            },
+        });
+        handle
+            .block_on(dispatcher.submit_collection_meta_op(replicate, None))
+            .unwrap();
+        handle
+            .block_on(dispatcher.submit_collection_meta_op(
+                set_replica_state_op("target", 1, ReplicaState::Dead),
+                None,
+            ))
+            .unwrap();
+
+        // The move fails on the first batch, as it can't be verified.
+        // The copied points are kept in both collections.
+        let result = handle.block_on(toc.move_points("source", move_request("target", None)));
+        assert!(result.is_err());
+        assert_eq!(count(&toc, &handle, "source"), num_points);
+        let copied_count = handle
+            .block_on(toc.count(
+                "target",
+                count_request(None),
+                ShardSelectorInternal::ShardId(0),
+            ))
+            .unwrap()
+            .count;
+        assert!(copied_count > 0);
+
+        // The move can be repeated, once the target collection is available
+        handle
+            .block_on(dispatcher.submit_collection_meta_op(
+                set_replica_state_op("target", 1, ReplicaState::Active),
+                None,
+            ))
+            .unwrap();
+        let moved = handle
+            .block_on(toc.move_points("source", move_request("target", None)))
+            .unwrap();
+        assert_eq!(moved.points_count, num_points);
+        assert_eq!(count(&toc, &handle, "source"), 0);
+        assert_eq!(count(&toc, &handle, "target"), num_points);
+    }
+}
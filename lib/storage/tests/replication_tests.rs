@@ -0,0 +1,154 @@
+#![allow(deprecated)]
+
+#[cfg(all(test))]
+mod tests {
+    use std::num::{NonZeroU32, NonZeroU64};
+    use std::sync::Arc;
+
+    use collection::config::VectorParams;
+    use collection::operations::config_diff::CollectionParamsDiff;
+    use collection::optimizers_builder::{MergePolicy, OptimizersConfig};
+    use collection::shard::{ChannelService, ShardTransfer};
+    use segment::types::Distance;
+    use storage::content_manager::collection_meta_ops::{
+        CollectionMetaOperations, CreateCollection, CreateCollectionOperation, UpdateCollection,
+        UpdateCollectionOperation,
+    };
+    use storage::content_manager::consensus::operation_sender::OperationSender;
+    use storage::content_manager::consensus_ops::ConsensusOperations;
+    use storage::content_manager::toc::TableOfContent;
+    use storage::dispatcher::Dispatcher;
+    use storage::types::{PerformanceConfig, StorageConfig};
+    use tempfile::Builder;
+    use tokio::runtime::Runtime;
+
+    #[test]
+    fn test_replication_factor_increase_syncs_replicas() {
+        let storage_dir = Builder::new().prefix("storage").tempdir().unwrap();
+
+        let config = StorageConfig {
+            storage_path: storage_dir.path().to_str().unwrap().to_string(),
+            snapshots_path: storage_dir
+                .path()
+                .join("snapshots")
+                .to_str()
+                .unwrap()
+                .to_string(),
+            snapshots_temp_path: None,
+            snapshots_config: Default::default(),
+            on_disk_payload: false,
+            optimizers: OptimizersConfig {
+                deleted_threshold: 0.5,
+                vacuum_min_vector_number: 100,
+                default_segment_number: 2,
+                max_segment_size: None,
+                memmap_threshold: Some(100),
+                indexing_threshold: 100,
+                flush_interval_sec: 2,
+                max_optimization_threads: 2,
+                max_segment_disk_size: None,
+                merge_policy: MergePolicy::Auto,
+            },
+            wal: Default::default(),
+            performance: PerformanceConfig {
+                max_search_threads: 1,
+                async_scorer: false,
+                mmap_prefetch: false,
+                max_transfer_threads: 1,
+            },
+            hnsw_index: Default::default(),
+            resource_watchdog: Default::default(),
+        };
+
+        let runtime = Runtime::new().unwrap();
+        let handle = runtime.handle().clone();
+
+        let (propose_sender, propose_receiver) = std::sync::mpsc::channel();
+        let propose_operation_sender = OperationSender::new(propose_sender);
+
+        // Peer 1 is known to this peer, but holds no data yet
+        let channel_service = ChannelService::default();
+        channel_service
+            .id_to_address
+            .write()
+            .insert(1, "http://127.0.0.1:6335".parse().unwrap());
+
+        let toc = Arc::new(TableOfContent::new(
+            &config,
+            runtime,
+            channel_service,
+            0,
+            propose_operation_sender,
+        ));
+        let dispatcher = Dispatcher::new(toc);
+
+        handle
+            .block_on(
+                dispatcher.submit_collection_meta_op(
+                    CollectionMetaOperations::CreateCollection(CreateCollectionOperation {
+                        collection_name: "test".to_string(),
+                        create_collection: CreateCollection {
+                            vectors: VectorParams {
+                                size: NonZeroU64::new(10).unwrap(),
+                                distance: Distance::Cosine,
+                                quantization_config: None,
+                                on_disk: None,
+                                multivector_config: None,
+                                hnsw_config: None,
+                                normalize: None,
+                            }
+                            .into(),
+                            hnsw_config: None,
+                            wal_config: None,
+                            optimizers_config: None,
+                            sparse_vectors: None,
+                            shard_number: Some(1),
+                            on_disk_payload: None,
+                            max_response_payload_bytes: None,
+                            payload_transformers: None,
+                            ivf_config: None,
+                            placement: None,
+                            read_fan_out_factor: None,
+                            write_consistency: None,
+                            read_consistency: None,
+                            hash_ring: None,
+                        },
+                    }),
+                    None,
+                ),
+            )
+            .unwrap();
+        assert_eq!(propose_receiver.try_iter().count(), 0);
+
+        handle
+            .block_on(dispatcher.submit_collection_meta_op(
+                CollectionMetaOperations::UpdateCollection(UpdateCollectionOperation {
+                    collection_name: "test".to_string(),
+                    update_collection: UpdateCollection {
+                        optimizers_config: None,
+                        params: Some(CollectionParamsDiff {
+                            replication_factor: Some(NonZeroU32::new(2).unwrap()),
+                            read_fan_out_factor: None,
+                            read_consistency: None,
+                        }),
+                        expected_version: None,
+                    },
+                }),
+                None,
+            ))
+            .unwrap();
+
+        let proposals: Vec<_> = propose_receiver.try_iter().collect();
+        assert_eq!(
+            proposals,
+            vec![ConsensusOperations::start_transfer(
+                "test".to_string(),
+                ShardTransfer {
+                    shard_id: 0,
+                    from: 0,
+                    to: 1,
+                },
+            )]
+        );
+    }
+}
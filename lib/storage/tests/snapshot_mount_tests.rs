@@ -0,0 +1,191 @@
+#[cfg(all(test))]
+mod tests {
+    use std::num::NonZeroU64;
+    use std::sync::Arc;
+
+    use collection::config::VectorParams;
+    use collection::operations::point_ops::{PointInsertOperations, PointOperations, PointStruct};
+    use collection::operations::shard_selector::ShardSelectorInternal;
+    use collection::operations::types::CountRequest;
+    use collection::operations::CollectionUpdateOperations;
+    use collection::optimizers_builder::{MergePolicy, OptimizersConfig};
+    use segment::types::Distance;
+    use storage::content_manager::collection_meta_ops::{
+        CollectionMetaOperations, CreateCollection, CreateCollectionOperation,
+    };
+    use storage::content_manager::consensus::operation_sender::OperationSender;
+    use storage::content_manager::toc::TableOfContent;
+    use storage::dispatcher::Dispatcher;
+    use storage::types::{PerformanceConfig, StorageConfig};
+    use tempfile::Builder;
+    use tokio::runtime::Runtime;
+
+    fn upsert_op(ids: std::ops::Range<u64>) -> CollectionUpdateOperations {
+        let points = ids
+            .map(|i| PointStruct {
+                id: i.into(),
+                vector: vec![i as f32, 0.0, 0.0, 1.0].into(),
+                payload: None,
+            })
+            .collect();
+        CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+            PointInsertOperations::PointsList(points),
+        ))
+    }
+
+    fn count_request() -> CountRequest {
+        CountRequest {
+            filter: None,
+            exact: true,
+        }
+    }
+
+    #[test]
+    fn test_mount_snapshot() {
+        let storage_dir = Builder::new().prefix("storage").tempdir().unwrap();
+
+        let config = StorageConfig {
+            storage_path: storage_dir.path().to_str().unwrap().to_string(),
+            snapshots_path: storage_dir
+                .path()
+                .join("snapshots")
+                .to_str()
+                .unwrap()
+                .to_string(),
+            snapshots_temp_path: None,
+            snapshots_config: Default::default(),
+            on_disk_payload: false,
+            optimizers: OptimizersConfig {
+                deleted_threshold: 0.5,
+                vacuum_min_vector_number: 100,
+                default_segment_number: 2,
+                max_segment_size: None,
+                memmap_threshold: Some(100),
+                indexing_threshold: 100,
+                flush_interval_sec: 2,
+                max_optimization_threads: 2,
+                max_segment_disk_size: None,
+                merge_policy: MergePolicy::Auto,
+            },
+            wal: Default::default(),
+            performance: PerformanceConfig {
+                max_search_threads: 1,
+                async_scorer: false,
+                mmap_prefetch: false,
+                max_transfer_threads: 1,
+            },
+            hnsw_index: Default::default(),
+            resource_watchdog: Default::default(),
+        };
+
+        let runtime = Runtime::new().unwrap();
+        let handle = runtime.handle().clone();
+
+        let (propose_sender, _propose_receiver) = std::sync::mpsc::channel();
+        let propose_operation_sender = OperationSender::new(propose_sender);
+
+        let toc = Arc::new(TableOfContent::new(
+            &config,
+            runtime,
+            Default::default(),
+            0,
+            propose_operation_sender,
+        ));
+        let dispatcher = Dispatcher::new(toc.clone());
+
+        handle
+            .block_on(
+                dispatcher.submit_collection_meta_op(
+                    CollectionMetaOperations::CreateCollection(CreateCollectionOperation {
+                        collection_name: "live".to_string(),
+                        create_collection: CreateCollection {
+                            vectors: VectorParams {
+                                size: NonZeroU64::new(4).unwrap(),
+                                distance: Distance::Dot,
+                                quantization_config: None,
+                                on_disk: None,
+                                multivector_config: None,
+                                hnsw_config: None,
+                                normalize: None,
+                            }
+                            .into(),
+                            hnsw_config: None,
+                            wal_config: None,
+                            optimizers_config: None,
+                            sparse_vectors: None,
+                            shard_number: Some(2),
+                            on_disk_payload: None,
+                            max_response_payload_bytes: None,
+                            payload_transformers: None,
+                            ivf_config: None,
+                            placement: None,
+                            read_fan_out_factor: None,
+                            write_consistency: None,
+                            read_consistency: None,
+                            hash_ring: None,
+                        },
+                    }),
+                    None,
+                ),
+            )
+            .unwrap();
+
+        handle
+            .block_on(toc.update("live", upsert_op(0..10), ShardSelectorInternal::All, true))
+            .unwrap();
+        let snapshot = handle.block_on(toc.create_snapshot("live")).unwrap();
+        handle
+            .block_on(toc.update("live", upsert_op(10..30), ShardSelectorInternal::All, true))
+            .unwrap();
+
+        // Mount name can't clash with existing collections
+        assert!(handle
+            .block_on(toc.mount_snapshot("live", &snapshot.name, "live"))
+            .is_err());
+        assert!(handle
+            .block_on(toc.mount_snapshot("live", "missing.snapshot", "live_past"))
+            .is_err());
+
+        assert!(handle
+            .block_on(toc.mount_snapshot("live", &snapshot.name, "live_past"))
+            .unwrap());
+        assert_eq!(
+            handle.block_on(toc.snapshot_mounts()),
+            vec!["live_past".to_string()]
+        );
+
+        let past_count = handle
+            .block_on(toc.count("live_past", count_request(), ShardSelectorInternal::All))
+            .unwrap();
+        assert_eq!(past_count.count, 10);
+        let live_count = handle
+            .block_on(toc.count("live", count_request(), ShardSelectorInternal::All))
+            .unwrap();
+        assert_eq!(live_count.count, 30);
+
+        // Mounted snapshots are read-only
+        assert!(handle
+            .block_on(toc.update(
+                "live_past",
+                upsert_op(30..40),
+                ShardSelectorInternal::All,
+                true
+            ))
+            .is_err());
+
+        // Mounts are not a part of the consensus state
+        let collections_snapshot = handle.block_on(toc.collections_snapshot());
+        assert!(!collections_snapshot.collections.contains_key("live_past"));
+
+        assert!(handle.block_on(toc.unmount_snapshot("live_past")).unwrap());
+        assert!(!handle.block_on(toc.unmount_snapshot("live_past")).unwrap());
+        assert!(handle
+            .block_on(toc.count("live_past", count_request(), ShardSelectorInternal::All))
+            .is_err());
+        assert!(!storage_dir
+            .path()
+            .join("snapshot_mounts")
+            .join("live_past")
+            .exists());
+    }
+}
@@ -1,16 +1,35 @@
 use actix_web::rt::time::Instant;
-use actix_web::{delete, get, web, Responder};
+use actix_web::{delete, get, post, web, Responder};
+use serde::Deserialize;
 use storage::content_manager::consensus_ops::ConsensusOperations;
 use storage::content_manager::errors::StorageError;
+use storage::content_manager::snapshots::do_create_cluster_snapshot;
 use storage::dispatcher::Dispatcher;
 
+use crate::actix::api::collections_api::{ConsistentRead, WaitTimeout};
 use crate::actix::helpers::process_response;
 
+/// Number of audit records, returned if no limit is specified
+const DEFAULT_AUDIT_LIMIT: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct AuditQuery {
+    /// Return records of entries starting from this index
+    from_index: Option<u64>,
+    limit: Option<usize>,
+}
+
 #[get("/cluster")]
-async fn cluster_status(dispatcher: web::Data<Dispatcher>) -> impl Responder {
+async fn cluster_status(
+    dispatcher: web::Data<Dispatcher>,
+    web::Query(query): web::Query<ConsistentRead>,
+) -> impl Responder {
     let timing = Instant::now();
-    let response = dispatcher.cluster_status();
-    process_response(Ok(response), timing)
+    let response = query
+        .await_consistency(dispatcher.get_ref())
+        .await
+        .map(|()| dispatcher.cluster_status());
+    process_response(response, timing)
 }
 
 #[delete("/cluster/peer/{peer_id}")]
@@ -41,7 +60,122 @@ async fn remove_peer(dispatcher: web::Data<Dispatcher>, peer_id: web::Path<u64>)
     process_response(response, timing)
 }
 
+#[post("/cluster/peer/{peer_id}/drain")]
+async fn drain_peer(dispatcher: web::Data<Dispatcher>, peer_id: web::Path<u64>) -> impl Responder {
+    let timing = Instant::now();
+    let dispatcher = dispatcher.into_inner();
+    let peer_id = peer_id.into_inner();
+
+    let response = match dispatcher.consensus_state() {
+        Some(consensus_state) => {
+            if consensus_state
+                .persistent
+                .read()
+                .peer_address_by_id()
+                .contains_key(&peer_id)
+            {
+                consensus_state
+                    .propose_consensus_op_with_await(ConsensusOperations::DrainPeer(peer_id), None)
+                    .await
+            } else {
+                Err(StorageError::NotFound {
+                    description: format!("Peer {peer_id} is not found in the cluster"),
+                })
+            }
+        }
+        None => Err(StorageError::BadRequest {
+            description: "Distributed deployment is disabled.".to_string(),
+        }),
+    };
+    process_response(response, timing)
+}
+
+#[post("/cluster/peer/{peer_id}/transfer_leadership")]
+async fn transfer_leadership(
+    dispatcher: web::Data<Dispatcher>,
+    peer_id: web::Path<u64>,
+    web::Query(query): web::Query<WaitTimeout>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let peer_id = peer_id.into_inner();
+
+    let response = match dispatcher.consensus_state() {
+        Some(consensus_state) => {
+            consensus_state
+                .transfer_leadership(peer_id, query.timeout())
+                .await
+        }
+        None => Err(StorageError::BadRequest {
+            description: "Distributed deployment is disabled.".to_string(),
+        }),
+    };
+    process_response(response, timing)
+}
+
+#[get("/cluster/debug")]
+async fn consensus_debug_info(dispatcher: web::Data<Dispatcher>) -> impl Responder {
+    let timing = Instant::now();
+    let response = match dispatcher.consensus_state() {
+        Some(consensus_state) => consensus_state.debug_info(),
+        None => Err(StorageError::BadRequest {
+            description: "Distributed deployment is disabled.".to_string(),
+        }),
+    };
+    process_response(response, timing)
+}
+
+#[get("/cluster/audit")]
+async fn consensus_audit_log(
+    dispatcher: web::Data<Dispatcher>,
+    web::Query(query): web::Query<AuditQuery>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let response = match dispatcher.consensus_state() {
+        Some(consensus_state) => consensus_state.audit_records(
+            query.from_index.unwrap_or(0),
+            query.limit.unwrap_or(DEFAULT_AUDIT_LIMIT),
+        ),
+        None => Err(StorageError::BadRequest {
+            description: "Distributed deployment is disabled.".to_string(),
+        }),
+    };
+    process_response(response, timing)
+}
+
+#[post("/cluster/wal/compact")]
+async fn compact_consensus_wal(dispatcher: web::Data<Dispatcher>) -> impl Responder {
+    let timing = Instant::now();
+    let response = match dispatcher.consensus_state() {
+        Some(consensus_state) => consensus_state.compact_wal(true),
+        None => Err(StorageError::BadRequest {
+            description: "Distributed deployment is disabled.".to_string(),
+        }),
+    };
+    process_response(response, timing)
+}
+
+#[post("/cluster/snapshots")]
+async fn create_cluster_snapshot(dispatcher: web::Data<Dispatcher>) -> impl Responder {
+    let timing = Instant::now();
+    let response = match dispatcher.consensus_state() {
+        Some(consensus_state) => {
+            do_create_cluster_snapshot(dispatcher.toc(), consensus_state).await
+        }
+        None => Err(StorageError::BadRequest {
+            description: "Distributed deployment is disabled.".to_string(),
+        }),
+    };
+    process_response(response, timing)
+}
+
 // Configure services
 pub fn config_cluster_api(cfg: &mut web::ServiceConfig) {
-    cfg.service(cluster_status).service(remove_peer);
+    cfg.service(cluster_status)
+        .service(remove_peer)
+        .service(drain_peer)
+        .service(transfer_leadership)
+        .service(compact_consensus_wal)
+        .service(consensus_debug_info)
+        .service(consensus_audit_log)
+        .service(create_cluster_snapshot);
 }
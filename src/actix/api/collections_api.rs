@@ -1,13 +1,20 @@
 use std::time::Duration;
 
 use actix_web::rt::time::Instant;
-use actix_web::{delete, get, patch, post, put, web, Responder};
+use actix_web::{delete, get, patch, post, put, web, Either, HttpResponse, Responder};
+use api::grpc::models::{ApiResponse, ApiStatus};
 use collection::operations::cluster_ops::ClusterOperations;
+use collection::operations::types::{
+    ShardCleanupProgress, DEFAULT_CLEANUP_BATCH_SIZE, DEFAULT_WAL_OPERATIONS_LIMIT,
+};
+use collection::shard::ShardId;
+use segment::types::SeqNumberType;
 use serde::Deserialize;
 use storage::content_manager::collection_meta_ops::{
     ChangeAliasesOperation, CollectionMetaOperations, CreateCollection, CreateCollectionOperation,
     DeleteCollectionOperation, UpdateCollection, UpdateCollectionOperation,
 };
+use storage::content_manager::errors::StorageError;
 use storage::content_manager::toc::TableOfContent;
 use storage::dispatcher::Dispatcher;
 
@@ -15,7 +22,7 @@ use crate::actix::helpers::process_response;
 use crate::common::collections::*;
 
 #[derive(Debug, Deserialize)]
-struct WaitTimeout {
+pub struct WaitTimeout {
     timeout: Option<u64>,
 }
 
@@ -25,10 +32,64 @@ impl WaitTimeout {
     }
 }
 
+/// Parameters of reading the state, which is shared through consensus
+#[derive(Debug, Deserialize)]
+pub struct ConsistentRead {
+    /// If true - wait until all operations committed by consensus are applied locally
+    #[serde(default)]
+    consistent: bool,
+    timeout: Option<u64>,
+}
+
+impl ConsistentRead {
+    pub async fn await_consistency(&self, dispatcher: &Dispatcher) -> Result<(), StorageError> {
+        if self.consistent {
+            dispatcher
+                .await_consistency(self.timeout.map(Duration::from_secs))
+                .await
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Validation-only mode of collection meta operations
+#[derive(Debug, Deserialize)]
+struct DryRun {
+    /// If true - only check that the operation can be applied and describe its changes
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Submit the operation, or only validate it if `dry_run` is requested
+async fn submit_or_validate(
+    dispatcher: &Dispatcher,
+    operation: CollectionMetaOperations,
+    wait_timeout: Option<Duration>,
+    dry_run: &DryRun,
+    timing: Instant,
+) -> Either<impl Responder, impl Responder> {
+    if dry_run.dry_run {
+        let response = dispatcher.validate_collection_meta_op(operation).await;
+        Either::Left(process_response(response, timing))
+    } else {
+        let response = dispatcher
+            .submit_collection_meta_op(operation, wait_timeout)
+            .await;
+        Either::Right(process_response(response, timing))
+    }
+}
+
 #[get("/collections")]
-async fn get_collections(toc: web::Data<TableOfContent>) -> impl Responder {
+async fn get_collections(
+    dispatcher: web::Data<Dispatcher>,
+    web::Query(query): web::Query<ConsistentRead>,
+) -> impl Responder {
     let timing = Instant::now();
-    let response = Ok(do_list_collections(toc.get_ref()).await);
+    let response = match query.await_consistency(dispatcher.get_ref()).await {
+        Ok(()) => Ok(do_list_collections(dispatcher.toc()).await),
+        Err(err) => Err(err),
+    };
     process_response(response, timing)
 }
 
@@ -46,19 +107,21 @@ async fn create_collection(
     path: web::Path<String>,
     operation: web::Json<CreateCollection>,
     web::Query(query): web::Query<WaitTimeout>,
+    web::Query(dry_run): web::Query<DryRun>,
 ) -> impl Responder {
     let timing = Instant::now();
     let name = path.into_inner();
-    let response = dispatcher
-        .submit_collection_meta_op(
-            CollectionMetaOperations::CreateCollection(CreateCollectionOperation {
-                collection_name: name,
-                create_collection: operation.0,
-            }),
-            query.timeout(),
-        )
-        .await;
-    process_response(response, timing)
+    submit_or_validate(
+        dispatcher.get_ref(),
+        CollectionMetaOperations::CreateCollection(CreateCollectionOperation {
+            collection_name: name,
+            create_collection: operation.0,
+        }),
+        query.timeout(),
+        &dry_run,
+        timing,
+    )
+    .await
 }
 
 #[patch("/collections/{name}")]
@@ -67,19 +130,21 @@ async fn update_collection(
     path: web::Path<String>,
     operation: web::Json<UpdateCollection>,
     web::Query(query): web::Query<WaitTimeout>,
+    web::Query(dry_run): web::Query<DryRun>,
 ) -> impl Responder {
     let timing = Instant::now();
     let name = path.into_inner();
-    let response = dispatcher
-        .submit_collection_meta_op(
-            CollectionMetaOperations::UpdateCollection(UpdateCollectionOperation {
-                collection_name: name,
-                update_collection: operation.0,
-            }),
-            query.timeout(),
-        )
-        .await;
-    process_response(response, timing)
+    submit_or_validate(
+        dispatcher.get_ref(),
+        CollectionMetaOperations::UpdateCollection(UpdateCollectionOperation {
+            collection_name: name,
+            update_collection: operation.0,
+        }),
+        query.timeout(),
+        &dry_run,
+        timing,
+    )
+    .await
 }
 
 #[delete("/collections/{name}")]
@@ -87,16 +152,18 @@ async fn delete_collection(
     dispatcher: web::Data<Dispatcher>,
     path: web::Path<String>,
     web::Query(query): web::Query<WaitTimeout>,
+    web::Query(dry_run): web::Query<DryRun>,
 ) -> impl Responder {
     let timing = Instant::now();
     let name = path.into_inner();
-    let response = dispatcher
-        .submit_collection_meta_op(
-            CollectionMetaOperations::DeleteCollection(DeleteCollectionOperation(name)),
-            query.timeout(),
-        )
-        .await;
-    process_response(response, timing)
+    submit_or_validate(
+        dispatcher.get_ref(),
+        CollectionMetaOperations::DeleteCollection(DeleteCollectionOperation(name)),
+        query.timeout(),
+        &dry_run,
+        timing,
+    )
+    .await
 }
 
 #[post("/collections/aliases")]
@@ -104,15 +171,17 @@ async fn update_aliases(
     dispatcher: web::Data<Dispatcher>,
     operation: web::Json<ChangeAliasesOperation>,
     web::Query(query): web::Query<WaitTimeout>,
+    web::Query(dry_run): web::Query<DryRun>,
 ) -> impl Responder {
     let timing = Instant::now();
-    let response = dispatcher
-        .submit_collection_meta_op(
-            CollectionMetaOperations::ChangeAliases(operation.0),
-            query.timeout(),
-        )
-        .await;
-    process_response(response, timing)
+    submit_or_validate(
+        dispatcher.get_ref(),
+        CollectionMetaOperations::ChangeAliases(operation.0),
+        query.timeout(),
+        &dry_run,
+        timing,
+    )
+    .await
 }
 
 #[get("/collections/{name}/cluster")]
@@ -126,6 +195,23 @@ async fn get_cluster_info(
     process_response(response, timing)
 }
 
+#[derive(Debug, Deserialize)]
+struct ShardMovementParams {
+    shard_number: usize,
+}
+
+#[get("/collections/{name}/cluster/shard_movement")]
+async fn estimate_shard_movement(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+    web::Query(params): web::Query<ShardMovementParams>,
+) -> impl Responder {
+    let name = path.into_inner();
+    let timing = Instant::now();
+    let response = do_estimate_shard_movement(toc.get_ref(), &name, params.shard_number).await;
+    process_response(response, timing)
+}
+
 #[post("/collections/{name}/cluster")]
 async fn update_collection_cluster(
     toc: web::Data<TableOfContent>,
@@ -148,6 +234,133 @@ async fn update_collection_cluster(
     process_response(response, timing)
 }
 
+#[derive(Debug, Deserialize)]
+struct CleanupParams {
+    batch_size: Option<usize>,
+}
+
+fn cleanup_progress_line(
+    response: Result<ShardCleanupProgress, StorageError>,
+    timing: Instant,
+) -> web::Bytes {
+    let time = timing.elapsed().as_secs_f64();
+    let api_response = match response {
+        Ok(progress) => ApiResponse {
+            result: Some(progress),
+            status: ApiStatus::Ok,
+            time,
+        },
+        Err(err) => ApiResponse {
+            result: None,
+            status: ApiStatus::Error(err.to_string()),
+            time,
+        },
+    };
+    let mut line = serde_json::to_vec(&api_response).unwrap_or_default();
+    line.push(b'\n');
+    web::Bytes::from(line)
+}
+
+/// Delete points of the local shard, which belong to other shards.
+/// Responds with newline-delimited JSON: a line per processed batch, the last one has `finished` set
+/// or describes the error.
+#[post("/collections/{name}/shards/{shard_id}/cleanup")]
+async fn cleanup_shard(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<(String, ShardId)>,
+    web::Query(params): web::Query<CleanupParams>,
+) -> HttpResponse {
+    let timing = Instant::now();
+    let (collection_name, shard_id) = path.into_inner();
+    let batch_size = params.batch_size.unwrap_or(DEFAULT_CLEANUP_BATCH_SIZE);
+
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    actix_web::rt::spawn(async move {
+        let progress_sender = sender.clone();
+        let response = toc
+            .cleanup_shard(&collection_name, shard_id, batch_size, |progress| {
+                // The final progress is sent as the response
+                if !progress.finished {
+                    // Client might have disconnected, cleanup is finished anyway
+                    let _ =
+                        progress_sender.send(cleanup_progress_line(Ok(progress.clone()), timing));
+                }
+            })
+            .await;
+        let _ = sender.send(cleanup_progress_line(response, timing));
+    });
+
+    let lines = futures::stream::unfold(receiver, |mut receiver| async move {
+        let line = receiver.recv().await?;
+        Some((Ok::<_, actix_web::Error>(line), receiver))
+    });
+    HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(lines)
+}
+
+#[derive(Debug, Deserialize)]
+struct WalListParams {
+    offset: Option<SeqNumberType>,
+    limit: Option<usize>,
+}
+
+#[get("/collections/{name}/shards/{shard_id}/wal")]
+async fn get_shard_wal(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<(String, ShardId)>,
+    web::Query(params): web::Query<WalListParams>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let (collection_name, shard_id) = path.into_inner();
+    let limit = params.limit.unwrap_or(DEFAULT_WAL_OPERATIONS_LIMIT);
+    let response = toc
+        .get_ref()
+        .wal_operations(&collection_name, shard_id, params.offset, limit)
+        .await;
+    process_response(response, timing)
+}
+
+#[derive(Debug, Deserialize)]
+struct WalReplayParams {
+    from_op_num: Option<SeqNumberType>,
+}
+
+#[post("/collections/{name}/shards/{shard_id}/wal/replay")]
+async fn replay_shard_wal(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<(String, ShardId)>,
+    web::Query(params): web::Query<WalReplayParams>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let (collection_name, shard_id) = path.into_inner();
+    let response = toc
+        .get_ref()
+        .replay_wal(&collection_name, shard_id, params.from_op_num.unwrap_or(0))
+        .await;
+    process_response(response, timing)
+}
+
+#[derive(Debug, Deserialize)]
+struct WalTruncateParams {
+    from_op_num: SeqNumberType,
+}
+
+#[post("/collections/{name}/shards/{shard_id}/wal/truncate")]
+async fn truncate_shard_wal(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<(String, ShardId)>,
+    web::Query(params): web::Query<WalTruncateParams>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let (collection_name, shard_id) = path.into_inner();
+    let response = toc
+        .get_ref()
+        .truncate_wal(&collection_name, shard_id, params.from_op_num)
+        .await;
+    process_response(response, timing)
+}
+
 // Configure services
 pub fn config_collections_api(cfg: &mut web::ServiceConfig) {
     cfg.service(get_collections)
@@ -157,14 +370,19 @@ pub fn config_collections_api(cfg: &mut web::ServiceConfig) {
         .service(delete_collection)
         .service(update_aliases)
         .service(get_cluster_info)
-        .service(update_collection_cluster);
+        .service(estimate_shard_movement)
+        .service(update_collection_cluster)
+        .service(cleanup_shard)
+        .service(get_shard_wal)
+        .service(replay_shard_wal)
+        .service(truncate_shard_wal);
 }
 
 #[cfg(test)]
 mod tests {
     use actix_web::web::Query;
 
-    use super::WaitTimeout;
+    use super::{ConsistentRead, DryRun, WaitTimeout};
 
     #[test]
     fn timeout_is_deserialized() {
@@ -173,4 +391,21 @@ mod tests {
         let timeout: WaitTimeout = Query::from_query("timeout=10").unwrap().0;
         assert_eq!(timeout.timeout, Some(10))
     }
+
+    #[test]
+    fn dry_run_is_deserialized() {
+        let dry_run: DryRun = Query::from_query("timeout=10").unwrap().0;
+        assert!(!dry_run.dry_run);
+        let dry_run: DryRun = Query::from_query("dry_run=true&timeout=10").unwrap().0;
+        assert!(dry_run.dry_run);
+    }
+
+    #[test]
+    fn consistent_read_is_deserialized() {
+        let read: ConsistentRead = Query::from_query("").unwrap().0;
+        assert!(!read.consistent);
+        let read: ConsistentRead = Query::from_query("consistent=true&timeout=5").unwrap().0;
+        assert!(read.consistent);
+        assert_eq!(read.timeout, Some(5))
+    }
 }
@@ -1,5 +1,6 @@
 use actix_web::rt::time::Instant;
 use actix_web::{post, web, Responder};
+use collection::operations::shard_selector::{ShardSelectorInternal, WithShardSelector};
 use collection::operations::types::CountRequest;
 use storage::content_manager::toc::TableOfContent;
 
@@ -10,13 +11,23 @@ use crate::common::points::do_count_points;
 pub async fn count_points(
     toc: web::Data<TableOfContent>,
     path: web::Path<String>,
-    request: web::Json<CountRequest>,
+    request: web::Json<WithShardSelector<CountRequest>>,
 ) -> impl Responder {
     let collection_name = path.into_inner();
+    let WithShardSelector {
+        request,
+        shard_selector,
+        session_token,
+    } = request.into_inner();
     let timing = Instant::now();
 
-    let response =
-        do_count_points(toc.get_ref(), &collection_name, request.into_inner(), None).await;
+    let response = do_count_points(
+        toc.get_ref(),
+        &collection_name,
+        request,
+        ShardSelectorInternal::with_session(shard_selector, session_token),
+    )
+    .await;
 
     process_response(response, timing)
 }
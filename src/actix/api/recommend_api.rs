@@ -1,5 +1,6 @@
 use actix_web::rt::time::Instant;
 use actix_web::{post, web, Responder};
+use collection::operations::shard_selector::ShardSelectorInternal;
 use collection::operations::types::{RecommendRequest, RecommendRequestBatch};
 use segment::types::ScoredPoint;
 use storage::content_manager::errors::StorageError;
@@ -12,7 +13,8 @@ async fn do_recommend_points(
     collection_name: &str,
     request: RecommendRequest,
 ) -> Result<Vec<ScoredPoint>, StorageError> {
-    toc.recommend(collection_name, request, None).await
+    toc.recommend(collection_name, request, ShardSelectorInternal::All)
+        .await
 }
 
 #[post("/collections/{name}/points/recommend")]
@@ -34,7 +36,8 @@ async fn do_recommend_batch_points(
     collection_name: &str,
     request: RecommendRequestBatch,
 ) -> Result<Vec<Vec<ScoredPoint>>, StorageError> {
-    toc.recommend_batch(collection_name, request, None).await
+    toc.recommend_batch(collection_name, request, ShardSelectorInternal::All)
+        .await
 }
 
 #[post("/collections/{name}/points/recommend/batch")]
@@ -1,6 +1,13 @@
+use std::sync::Arc;
+
 use actix_web::rt::time::Instant;
-use actix_web::{get, post, web, Responder};
-use collection::operations::types::{PointRequest, Record, ScrollRequest, ScrollResult};
+use actix_web::web::Bytes;
+use actix_web::{get, post, web, Either, HttpResponse, Responder};
+use collection::operations::shard_selector::{ShardSelectorInternal, WithShardSelector};
+use collection::operations::types::{
+    ExportRequest, PointRequest, Record, ScrollRequest, ScrollResult,
+};
+use futures::Stream;
 use segment::types::{PointIdType, WithPayloadInterface};
 use storage::content_manager::errors::StorageError;
 use storage::content_manager::toc::TableOfContent;
@@ -17,8 +24,9 @@ async fn do_get_point(
         ids: vec![point_id],
         with_payload: Some(WithPayloadInterface::Bool(true)),
         with_vector: true.into(),
+        max_payload_bytes: None,
     };
-    toc.retrieve(collection_name, request, None)
+    toc.retrieve(collection_name, request, ShardSelectorInternal::All)
         .await
         .map(|points| points.into_iter().next())
 }
@@ -27,8 +35,55 @@ async fn scroll_get_points(
     toc: &TableOfContent,
     collection_name: &str,
     request: ScrollRequest,
+    shard_selection: ShardSelectorInternal,
 ) -> Result<ScrollResult, StorageError> {
-    toc.scroll(collection_name, request, None).await
+    toc.scroll(collection_name, request, shard_selection).await
+}
+
+/// Number of points, read from the collection at once during export
+const EXPORT_BATCH_SIZE: usize = 1000;
+
+/// Read matching points page by page in the order of IDs and serialize them as JSON lines
+fn export_points_stream(
+    toc: Arc<TableOfContent>,
+    collection_name: String,
+    request: ExportRequest,
+) -> impl Stream<Item = Result<Bytes, StorageError>> {
+    // Offset of the next batch, `None` once all points are read
+    let start: Option<Option<PointIdType>> = Some(None);
+    futures::stream::try_unfold(start, move |next_offset| {
+        let toc = toc.clone();
+        let collection_name = collection_name.clone();
+        let request = request.clone();
+        async move {
+            let offset = match next_offset {
+                Some(offset) => offset,
+                None => return Ok(None),
+            };
+            let scroll_request = ScrollRequest {
+                offset,
+                limit: Some(EXPORT_BATCH_SIZE),
+                filter: request.filter,
+                with_payload: request.with_payload,
+                with_vector: request.with_vector,
+                max_payload_bytes: None,
+            };
+            let result = toc
+                .scroll(&collection_name, scroll_request, ShardSelectorInternal::All)
+                .await?;
+            let mut lines = Vec::new();
+            for point in &result.points {
+                serde_json::to_writer(&mut lines, point).map_err(|err| {
+                    StorageError::service_error(&format!("Can't serialize point: {err}"))
+                })?;
+                lines.push(b'\n');
+            }
+            Ok(Some((
+                Bytes::from(lines),
+                result.next_page_offset.map(Some),
+            )))
+        }
+    })
 }
 
 #[get("/collections/{name}/points/{id}")]
@@ -75,19 +130,58 @@ pub async fn get_points(
     let collection_name = path.into_inner();
     let timing = Instant::now();
 
-    let response = do_get_points(toc.get_ref(), &collection_name, request.into_inner(), None).await;
+    let response = do_get_points(
+        toc.get_ref(),
+        &collection_name,
+        request.into_inner(),
+        ShardSelectorInternal::All,
+    )
+    .await;
     process_response(response, timing)
 }
 
+#[post("/collections/{name}/points/export")]
+pub async fn export_points(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+    request: web::Json<ExportRequest>,
+) -> impl Responder {
+    let collection_name = path.into_inner();
+    let timing = Instant::now();
+
+    // Check the collection before the response starts, errors of the stream can't change the status
+    if let Err(err) = toc.get_collection(&collection_name).await {
+        return Either::Left(process_response::<()>(Err(err), timing));
+    }
+
+    let stream = export_points_stream(toc.into_inner(), collection_name, request.into_inner());
+    Either::Right(
+        HttpResponse::Ok()
+            .content_type("application/x-ndjson")
+            .streaming(stream),
+    )
+}
+
 #[post("/collections/{name}/points/scroll")]
 pub async fn scroll_points(
     toc: web::Data<TableOfContent>,
     path: web::Path<String>,
-    request: web::Json<ScrollRequest>,
+    request: web::Json<WithShardSelector<ScrollRequest>>,
 ) -> impl Responder {
     let collection_name = path.into_inner();
+    let WithShardSelector {
+        request,
+        shard_selector,
+        session_token,
+    } = request.into_inner();
     let timing = Instant::now();
 
-    let response = scroll_get_points(toc.get_ref(), &collection_name, request.into_inner()).await;
+    let response = scroll_get_points(
+        toc.get_ref(),
+        &collection_name,
+        request,
+        ShardSelectorInternal::with_session(shard_selector, session_token),
+    )
+    .await;
     process_response(response, timing)
 }
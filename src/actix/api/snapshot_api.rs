@@ -1,11 +1,19 @@
 use actix_files::NamedFile;
+use actix_web::http::header::{self, Header};
 use actix_web::rt::time::Instant;
-use actix_web::{get, post, web, Responder, Result};
+use actix_web::{
+    delete, get, post, put, web, Either, HttpRequest, HttpResponse, Responder, Result,
+};
+use collection::operations::snapshot_storage_ops::{SnapshotData, SnapshotObject};
+use collection::shard::ShardId;
 use storage::content_manager::snapshots::{
-    do_create_full_snapshot, do_list_full_snapshots, get_full_snapshot_path,
+    do_create_full_snapshot, do_list_full_snapshots, do_restore_snapshot_as,
+    get_full_snapshot_path, MountSnapshotRequest, RestoreSnapshotRequest, SnapshotRecover,
 };
 use storage::content_manager::toc::TableOfContent;
+use storage::dispatcher::Dispatcher;
 
+use crate::actix::api::collections_api::WaitTimeout;
 use crate::actix::helpers::{
     collection_into_actix_error, process_response, storage_into_actix_error,
 };
@@ -20,21 +28,80 @@ pub async fn do_get_full_snapshot(toc: &TableOfContent, snapshot_name: &str) ->
 }
 
 pub async fn do_get_snapshot(
+    req: &HttpRequest,
     toc: &TableOfContent,
     collection_name: &str,
     snapshot_name: &str,
-) -> Result<NamedFile> {
+) -> Result<Either<NamedFile, HttpResponse>> {
     let collection = toc
         .get_collection(collection_name)
         .await
         .map_err(storage_into_actix_error)?;
 
-    let file_name = collection
-        .get_snapshot_path(snapshot_name)
+    let snapshot = collection
+        .get_snapshot(snapshot_name)
         .await
         .map_err(collection_into_actix_error)?;
 
-    Ok(NamedFile::open(file_name)?)
+    match snapshot {
+        // Local files are served by `NamedFile`, which handles range requests itself
+        SnapshotData::File(file_name) => Ok(Either::Left(NamedFile::open(file_name)?)),
+        SnapshotData::Object(object) => Ok(Either::Right(object_snapshot_response(
+            req,
+            snapshot_name,
+            object,
+        ))),
+    }
+}
+
+/// Serve snapshot from the object store, respecting a single byte range of the `Range` header,
+/// so that an interrupted download can be resumed.
+fn object_snapshot_response(
+    req: &HttpRequest,
+    snapshot_name: &str,
+    object: SnapshotObject,
+) -> HttpResponse {
+    let size = object.size;
+    let range = match header::Range::parse(req) {
+        Ok(header::Range::Bytes(ranges)) if ranges.len() == 1 => {
+            match ranges[0].to_satisfiable_range(size) {
+                Some(range) => Some(range),
+                None => {
+                    return HttpResponse::RangeNotSatisfiable()
+                        .insert_header(header::ContentRange(header::ContentRangeSpec::Bytes {
+                            range: None,
+                            instance_length: Some(size),
+                        }))
+                        .finish();
+                }
+            }
+        }
+        // Missing, malformed or multi-range headers are answered with the whole snapshot
+        _ => None,
+    };
+
+    let mut response = match range {
+        Some(_) => HttpResponse::PartialContent(),
+        None => HttpResponse::Ok(),
+    };
+    response
+        .content_type("application/octet-stream")
+        .insert_header(header::AcceptRanges(vec![header::RangeUnit::Bytes]))
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{snapshot_name}\""),
+        ));
+
+    match range {
+        Some((start, end)) => response
+            .insert_header(header::ContentRange(header::ContentRangeSpec::Bytes {
+                range: Some((start, end)),
+                instance_length: Some(size),
+            }))
+            .no_chunking(end + 1 - start)
+            .streaming(object.stream(start..end + 1)),
+        None => response.no_chunking(size).streaming(object.stream(0..size)),
+    }
 }
 
 #[get("/collections/{name}/snapshots")]
@@ -60,11 +127,214 @@ async fn create_snapshot(
 
 #[get("/collections/{name}/snapshots/{snapshot_name}")]
 async fn get_snapshot(
+    req: HttpRequest,
+    toc: web::Data<TableOfContent>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (collection_name, snapshot_name) = path.into_inner();
+    do_get_snapshot(&req, toc.get_ref(), &collection_name, &snapshot_name).await
+}
+
+#[post("/collections/{name}/snapshots/{snapshot_name}/mount")]
+async fn mount_snapshot(
     toc: web::Data<TableOfContent>,
     path: web::Path<(String, String)>,
+    request: web::Json<MountSnapshotRequest>,
 ) -> impl Responder {
     let (collection_name, snapshot_name) = path.into_inner();
-    do_get_snapshot(toc.get_ref(), &collection_name, &snapshot_name).await
+    let request = request.into_inner();
+
+    let timing = Instant::now();
+    let response = toc
+        .get_ref()
+        .mount_snapshot(&collection_name, &snapshot_name, &request.mount_name)
+        .await;
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/snapshots/{snapshot_name}/restore")]
+async fn restore_snapshot(
+    dispatcher: web::Data<Dispatcher>,
+    path: web::Path<(String, String)>,
+    request: web::Json<RestoreSnapshotRequest>,
+    web::Query(query): web::Query<WaitTimeout>,
+) -> impl Responder {
+    let (collection_name, snapshot_name) = path.into_inner();
+    let request = request.into_inner();
+
+    let timing = Instant::now();
+    let response = do_restore_snapshot_as(
+        dispatcher.get_ref(),
+        &collection_name,
+        &snapshot_name,
+        &request.collection_name,
+        query.timeout(),
+    )
+    .await;
+    process_response(response, timing)
+}
+
+#[put("/collections/{name}/snapshots/recover")]
+async fn recover_snapshot(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+    request: web::Json<SnapshotRecover>,
+) -> impl Responder {
+    let collection_name = path.into_inner();
+    let request = request.into_inner();
+
+    let timing = Instant::now();
+    let response = toc
+        .get_ref()
+        .recover_snapshot(&collection_name, &request)
+        .await;
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/shards/{shard_id}/segments/export")]
+async fn export_segments(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<(String, ShardId)>,
+) -> impl Responder {
+    let (collection_name, shard_id) = path.into_inner();
+
+    let timing = Instant::now();
+    let response = toc
+        .get_ref()
+        .export_segments(&collection_name, shard_id)
+        .await;
+    process_response(response, timing)
+}
+
+#[get("/collections/{name}/segments/{export_name}")]
+async fn get_segment_export(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<(String, String)>,
+) -> Result<NamedFile> {
+    let (collection_name, export_name) = path.into_inner();
+    let collection = toc
+        .get_collection(&collection_name)
+        .await
+        .map_err(storage_into_actix_error)?;
+
+    let file_name = collection
+        .get_segment_export_path(&export_name)
+        .await
+        .map_err(collection_into_actix_error)?;
+
+    Ok(NamedFile::open(file_name)?)
+}
+
+#[post("/collections/{name}/shards/{shard_id}/segments/{export_name}/import")]
+async fn import_segment(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<(String, ShardId, String)>,
+) -> impl Responder {
+    let (collection_name, shard_id, export_name) = path.into_inner();
+
+    let timing = Instant::now();
+    let response = toc
+        .get_ref()
+        .import_segment(&collection_name, shard_id, &export_name)
+        .await;
+    process_response(response, timing)
+}
+
+#[get("/collections/{name}/shards/{shard_id}/snapshots")]
+async fn list_shard_snapshots(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<(String, ShardId)>,
+) -> impl Responder {
+    let (collection_name, shard_id) = path.into_inner();
+
+    let timing = Instant::now();
+    let response = do_list_shard_snapshots(toc.get_ref(), &collection_name, shard_id).await;
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/shards/{shard_id}/snapshots")]
+async fn create_shard_snapshot(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<(String, ShardId)>,
+) -> impl Responder {
+    let (collection_name, shard_id) = path.into_inner();
+
+    let timing = Instant::now();
+    let response = toc
+        .get_ref()
+        .create_shard_snapshot(&collection_name, shard_id)
+        .await;
+    process_response(response, timing)
+}
+
+#[get("/collections/{name}/shards/{shard_id}/snapshots/{snapshot_name}")]
+async fn get_shard_snapshot(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<(String, ShardId, String)>,
+) -> Result<NamedFile> {
+    let (collection_name, shard_id, snapshot_name) = path.into_inner();
+    let collection = toc
+        .get_collection(&collection_name)
+        .await
+        .map_err(storage_into_actix_error)?;
+
+    let file_name = collection
+        .get_shard_snapshot_path(shard_id, &snapshot_name)
+        .await
+        .map_err(collection_into_actix_error)?;
+
+    Ok(NamedFile::open(file_name)?)
+}
+
+#[post("/collections/{name}/shards/{shard_id}/snapshots/{snapshot_name}/restore")]
+async fn restore_shard_snapshot(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<(String, ShardId, String)>,
+) -> impl Responder {
+    let (collection_name, shard_id, snapshot_name) = path.into_inner();
+
+    let timing = Instant::now();
+    let response = toc
+        .get_ref()
+        .restore_shard_snapshot(&collection_name, shard_id, &snapshot_name)
+        .await;
+    process_response(response, timing)
+}
+
+#[put("/collections/{name}/shards/{shard_id}/snapshots/recover")]
+async fn recover_shard_snapshot(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<(String, ShardId)>,
+    request: web::Json<SnapshotRecover>,
+) -> impl Responder {
+    let (collection_name, shard_id) = path.into_inner();
+    let request = request.into_inner();
+
+    let timing = Instant::now();
+    let response = toc
+        .get_ref()
+        .recover_shard_snapshot(&collection_name, shard_id, &request)
+        .await;
+    process_response(response, timing)
+}
+
+#[get("/snapshot_mounts")]
+async fn list_snapshot_mounts(toc: web::Data<TableOfContent>) -> impl Responder {
+    let timing = Instant::now();
+    let response = Ok(toc.get_ref().snapshot_mounts().await);
+    process_response(response, timing)
+}
+
+#[delete("/snapshot_mounts/{mount_name}")]
+async fn unmount_snapshot(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let mount_name = path.into_inner();
+
+    let timing = Instant::now();
+    let response = toc.get_ref().unmount_snapshot(&mount_name).await;
+    process_response(response, timing)
 }
 
 #[get("/snapshots")]
@@ -95,6 +365,19 @@ pub fn config_snapshots_api(cfg: &mut web::ServiceConfig) {
     cfg.service(list_snapshots)
         .service(create_snapshot)
         .service(get_snapshot)
+        .service(mount_snapshot)
+        .service(restore_snapshot)
+        .service(recover_snapshot)
+        .service(export_segments)
+        .service(get_segment_export)
+        .service(import_segment)
+        .service(list_shard_snapshots)
+        .service(create_shard_snapshot)
+        .service(get_shard_snapshot)
+        .service(restore_shard_snapshot)
+        .service(recover_shard_snapshot)
+        .service(list_snapshot_mounts)
+        .service(unmount_snapshot)
         .service(list_full_snapshots)
         .service(create_full_snapshot)
         .service(get_full_snapshot);
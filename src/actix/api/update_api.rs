@@ -3,6 +3,8 @@ use actix_web::web::Query;
 use actix_web::{delete, post, put, web, Responder};
 use collection::operations::payload_ops::{DeletePayload, SetPayload};
 use collection::operations::point_ops::{PointInsertOperations, PointsSelector};
+use collection::operations::shard_selector::{ShardSelectorInternal, WithShardSelector};
+use collection::operations::types::{FieldIndexEstimationRequest, MovePointsRequest};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use storage::content_manager::toc::TableOfContent;
@@ -10,7 +12,7 @@ use storage::content_manager::toc::TableOfContent;
 use crate::actix::helpers::process_response;
 use crate::common::points::{
     do_clear_payload, do_create_index, do_delete_index, do_delete_payload, do_delete_points,
-    do_set_payload, do_upsert_points, CreateFieldIndex,
+    do_rebuild_index, do_set_payload, do_upsert_points, CreateFieldIndex,
 };
 
 #[derive(Deserialize, Serialize, JsonSchema)]
@@ -22,15 +24,26 @@ pub struct UpdateParam {
 pub async fn upsert_points(
     toc: web::Data<TableOfContent>,
     path: web::Path<String>,
-    operation: web::Json<PointInsertOperations>,
+    operation: web::Json<WithShardSelector<PointInsertOperations>>,
     params: Query<UpdateParam>,
 ) -> impl Responder {
     let collection_name = path.into_inner();
-    let operation = operation.into_inner();
+    let WithShardSelector {
+        request: operation,
+        shard_selector,
+        ..
+    } = operation.into_inner();
     let wait = params.wait.unwrap_or(false);
     let timing = Instant::now();
 
-    let response = do_upsert_points(toc.get_ref(), &collection_name, operation, None, wait).await;
+    let response = do_upsert_points(
+        toc.get_ref(),
+        &collection_name,
+        operation,
+        shard_selector.into(),
+        wait,
+    )
+    .await;
     process_response(response, timing)
 }
 
@@ -38,15 +51,26 @@ pub async fn upsert_points(
 pub async fn delete_points(
     toc: web::Data<TableOfContent>,
     path: web::Path<String>,
-    operation: web::Json<PointsSelector>,
+    operation: web::Json<WithShardSelector<PointsSelector>>,
     params: Query<UpdateParam>,
 ) -> impl Responder {
     let collection_name = path.into_inner();
-    let operation = operation.into_inner();
+    let WithShardSelector {
+        request: operation,
+        shard_selector,
+        ..
+    } = operation.into_inner();
     let wait = params.wait.unwrap_or(false);
     let timing = Instant::now();
 
-    let response = do_delete_points(toc.get_ref(), &collection_name, operation, None, wait).await;
+    let response = do_delete_points(
+        toc.get_ref(),
+        &collection_name,
+        operation,
+        shard_selector.into(),
+        wait,
+    )
+    .await;
     process_response(response, timing)
 }
 
@@ -54,15 +78,26 @@ pub async fn delete_points(
 pub async fn set_payload(
     toc: web::Data<TableOfContent>,
     path: web::Path<String>,
-    operation: web::Json<SetPayload>,
+    operation: web::Json<WithShardSelector<SetPayload>>,
     params: Query<UpdateParam>,
 ) -> impl Responder {
     let collection_name = path.into_inner();
-    let operation = operation.into_inner();
+    let WithShardSelector {
+        request: operation,
+        shard_selector,
+        ..
+    } = operation.into_inner();
     let wait = params.wait.unwrap_or(false);
     let timing = Instant::now();
 
-    let response = do_set_payload(toc.get_ref(), &collection_name, operation, None, wait).await;
+    let response = do_set_payload(
+        toc.get_ref(),
+        &collection_name,
+        operation,
+        shard_selector.into(),
+        wait,
+    )
+    .await;
     process_response(response, timing)
 }
 
@@ -70,15 +105,26 @@ pub async fn set_payload(
 pub async fn delete_payload(
     toc: web::Data<TableOfContent>,
     path: web::Path<String>,
-    operation: web::Json<DeletePayload>,
+    operation: web::Json<WithShardSelector<DeletePayload>>,
     params: Query<UpdateParam>,
 ) -> impl Responder {
     let collection_name = path.into_inner();
-    let operation = operation.into_inner();
+    let WithShardSelector {
+        request: operation,
+        shard_selector,
+        ..
+    } = operation.into_inner();
     let wait = params.wait.unwrap_or(false);
     let timing = Instant::now();
 
-    let response = do_delete_payload(toc.get_ref(), &collection_name, operation, None, wait).await;
+    let response = do_delete_payload(
+        toc.get_ref(),
+        &collection_name,
+        operation,
+        shard_selector.into(),
+        wait,
+    )
+    .await;
     process_response(response, timing)
 }
 
@@ -86,15 +132,40 @@ pub async fn delete_payload(
 pub async fn clear_payload(
     toc: web::Data<TableOfContent>,
     path: web::Path<String>,
-    operation: web::Json<PointsSelector>,
+    operation: web::Json<WithShardSelector<PointsSelector>>,
     params: Query<UpdateParam>,
 ) -> impl Responder {
     let collection_name = path.into_inner();
-    let operation = operation.into_inner();
+    let WithShardSelector {
+        request: operation,
+        shard_selector,
+        ..
+    } = operation.into_inner();
     let wait = params.wait.unwrap_or(false);
     let timing = Instant::now();
 
-    let response = do_clear_payload(toc.get_ref(), &collection_name, operation, None, wait).await;
+    let response = do_clear_payload(
+        toc.get_ref(),
+        &collection_name,
+        operation,
+        shard_selector.into(),
+        wait,
+    )
+    .await;
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/points/move")]
+pub async fn move_points(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+    request: web::Json<MovePointsRequest>,
+) -> impl Responder {
+    let collection_name = path.into_inner();
+    let request = request.into_inner();
+    let timing = Instant::now();
+
+    let response = toc.get_ref().move_points(&collection_name, request).await;
     process_response(response, timing)
 }
 
@@ -110,7 +181,54 @@ pub async fn create_field_index(
     let wait = params.wait.unwrap_or(false);
     let timing = Instant::now();
 
-    let response = do_create_index(toc.get_ref(), &collection_name, operation, None, wait).await;
+    let response = do_create_index(
+        toc.get_ref(),
+        &collection_name,
+        operation,
+        ShardSelectorInternal::All,
+        wait,
+    )
+    .await;
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/index/rebuild")]
+pub async fn rebuild_field_index(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+    operation: web::Json<CreateFieldIndex>,
+    params: Query<UpdateParam>,
+) -> impl Responder {
+    let collection_name = path.into_inner();
+    let operation = operation.into_inner();
+    let wait = params.wait.unwrap_or(false);
+    let timing = Instant::now();
+
+    let response = do_rebuild_index(
+        toc.get_ref(),
+        &collection_name,
+        operation,
+        ShardSelectorInternal::All,
+        wait,
+    )
+    .await;
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/index/estimate")]
+pub async fn estimate_field_index(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+    request: web::Json<FieldIndexEstimationRequest>,
+) -> impl Responder {
+    let collection_name = path.into_inner();
+    let request = request.into_inner();
+    let timing = Instant::now();
+
+    let response = toc
+        .get_ref()
+        .estimate_field_index(&collection_name, request, ShardSelectorInternal::All)
+        .await;
     process_response(response, timing)
 }
 
@@ -124,7 +242,14 @@ pub async fn delete_field_index(
     let wait = params.wait.unwrap_or(false);
     let timing = Instant::now();
 
-    let response = do_delete_index(toc.get_ref(), &collection_name, field_name, None, wait).await;
+    let response = do_delete_index(
+        toc.get_ref(),
+        &collection_name,
+        field_name,
+        ShardSelectorInternal::All,
+        wait,
+    )
+    .await;
     process_response(response, timing)
 }
 
@@ -135,6 +260,9 @@ pub fn config_update_api(cfg: &mut web::ServiceConfig) {
         .service(set_payload)
         .service(delete_payload)
         .service(clear_payload)
+        .service(move_points)
         .service(create_field_index)
+        .service(estimate_field_index)
+        .service(rebuild_field_index)
         .service(delete_field_index);
 }
@@ -1,7 +1,7 @@
 use std::fmt::Debug;
 
 use actix_web::rt::time::Instant;
-use actix_web::{error, Error, HttpResponse, Responder};
+use actix_web::{error, Error, HttpResponse, HttpResponseBuilder, Responder};
 use api::grpc::models::{ApiResponse, ApiStatus};
 use collection::operations::types::CollectionError;
 use serde::Serialize;
@@ -18,6 +18,20 @@ pub fn storage_into_actix_error(err: StorageError) -> Error {
         StorageError::NotFound { .. } => error::ErrorNotFound(format!("{}", err)),
         StorageError::ServiceError { .. } => error::ErrorInternalServerError(format!("{}", err)),
         StorageError::BadRequest { .. } => error::ErrorBadRequest(format!("{}", err)),
+        StorageError::PartialUpdate { error, .. } => storage_into_actix_error(*error),
+    }
+}
+
+fn error_response(err: &StorageError) -> HttpResponseBuilder {
+    match err {
+        StorageError::BadInput { .. } => HttpResponse::BadRequest(),
+        StorageError::NotFound { .. } => HttpResponse::NotFound(),
+        StorageError::ServiceError { .. } => {
+            log::warn!("error processing request: {:?}", err);
+            HttpResponse::InternalServerError()
+        }
+        StorageError::BadRequest { .. } => HttpResponse::BadRequest(),
+        StorageError::PartialUpdate { error, .. } => error_response(error),
     }
 }
 
@@ -34,18 +48,16 @@ where
         Err(err) => {
             let error_description = format!("{}", err);
 
-            let mut resp = match err {
-                StorageError::BadInput { .. } => HttpResponse::BadRequest(),
-                StorageError::NotFound { .. } => HttpResponse::NotFound(),
-                StorageError::ServiceError { .. } => {
-                    log::warn!("error processing request: {:?}", err);
-                    HttpResponse::InternalServerError()
-                }
-                StorageError::BadRequest { .. } => HttpResponse::BadRequest(),
+            let mut resp = error_response(&err);
+
+            // Outcome of a partially failed update in each shard, for the client to retry the failed ones
+            let shard_results = match err {
+                StorageError::PartialUpdate { shard_results, .. } => Some(shard_results),
+                _ => None,
             };
 
-            resp.json(ApiResponse::<()> {
-                result: None,
+            resp.json(ApiResponse {
+                result: shard_results,
                 status: ApiStatus::Error(error_description),
                 time: timing.elapsed().as_secs_f64(),
             })
@@ -17,7 +17,7 @@ use crate::actix::api::cluster_api::config_cluster_api;
 use crate::actix::api::collections_api::config_collections_api;
 use crate::actix::api::count_api::count_points;
 use crate::actix::api::recommend_api::config_recommend_api;
-use crate::actix::api::retrieve_api::{get_point, get_points, scroll_points};
+use crate::actix::api::retrieve_api::{export_points, get_point, get_points, scroll_points};
 use crate::actix::api::search_api::config_search_api;
 use crate::actix::api::snapshot_api::config_snapshots_api;
 use crate::actix::api::telemetry_api::config_telemetry_api;
@@ -95,6 +95,7 @@ pub fn init(
                 .service(get_point)
                 .service(get_points)
                 .service(scroll_points)
+                .service(export_points)
                 .service(count_points)
         })
         .workers(max_web_workers(&settings))
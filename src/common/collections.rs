@@ -2,14 +2,17 @@ use std::time::Duration;
 
 use api::grpc::models::{CollectionDescription, CollectionsResponse};
 use collection::operations::cluster_ops::{
-    AbortTransferOperation, ClusterOperations, MoveShardOperation,
+    AbortTransferOperation, ClusterOperations, MoveShardOperation, SetReplicaStateOperation,
 };
+use collection::operations::shard_selector::ShardSelectorInternal;
 use collection::operations::snapshot_ops::SnapshotDescription;
-use collection::operations::types::{CollectionClusterInfo, CollectionInfo};
+use collection::operations::types::{CollectionClusterInfo, CollectionInfo, ShardMovementEstimate};
 use collection::shard::{ShardId, ShardTransfer};
 use itertools::Itertools;
-use storage::content_manager::collection_meta_ops::CollectionMetaOperations;
 use storage::content_manager::collection_meta_ops::ShardTransferOperations::{Abort, Start};
+use storage::content_manager::collection_meta_ops::{
+    CollectionMetaOperations, SetShardReplicaState,
+};
 use storage::content_manager::errors::StorageError;
 use storage::content_manager::toc::TableOfContent;
 use storage::dispatcher::Dispatcher;
@@ -20,7 +23,11 @@ pub async fn do_get_collection(
     shard_selection: Option<ShardId>,
 ) -> Result<CollectionInfo, StorageError> {
     let collection = toc.get_collection(name).await?;
-    Ok(collection.info(shard_selection).await?)
+    let shard_selection = match shard_selection {
+        None => ShardSelectorInternal::All,
+        Some(shard_id) => ShardSelectorInternal::ShardId(shard_id),
+    };
+    Ok(collection.info(&shard_selection).await?)
 }
 
 pub async fn do_list_collections(toc: &TableOfContent) -> CollectionsResponse {
@@ -52,6 +59,18 @@ pub async fn do_create_snapshot(
     toc.create_snapshot(collection_name).await
 }
 
+pub async fn do_list_shard_snapshots(
+    toc: &TableOfContent,
+    collection_name: &str,
+    shard_id: ShardId,
+) -> Result<Vec<SnapshotDescription>, StorageError> {
+    Ok(toc
+        .get_collection(collection_name)
+        .await?
+        .list_shard_snapshots(shard_id)
+        .await?)
+}
+
 pub async fn do_get_collection_cluster(
     toc: &TableOfContent,
     name: &str,
@@ -60,6 +79,15 @@ pub async fn do_get_collection_cluster(
     Ok(collection.cluster_info(toc.this_peer_id).await?)
 }
 
+pub async fn do_estimate_shard_movement(
+    toc: &TableOfContent,
+    name: &str,
+    shard_number: usize,
+) -> Result<ShardMovementEstimate, StorageError> {
+    let collection = toc.get_collection(name).await?;
+    Ok(collection.estimate_shard_movement(shard_number).await?)
+}
+
 pub async fn do_update_collection_cluster(
     toc: &TableOfContent,
     collection_name: String,
@@ -101,6 +129,15 @@ pub async fn do_update_collection_cluster(
                 });
             }
 
+            if toc.is_witness_peer(move_shard.to_peer_id) {
+                return Err(StorageError::BadRequest {
+                    description: format!(
+                        "Target peer {} is a witness and can't host shards",
+                        move_shard.to_peer_id
+                    ),
+                });
+            }
+
             // validate source peer exists
             let target_peer_exist = consensus_state
                 .persistent
@@ -114,6 +151,16 @@ pub async fn do_update_collection_cluster(
                 });
             }
 
+            // validate target peer satisfies placement constraints
+            collection
+                .validate_replica_placement(
+                    move_shard.shard_id,
+                    move_shard.to_peer_id,
+                    Some(move_shard.from_peer_id),
+                    toc.this_peer_id,
+                )
+                .await?;
+
             // submit operation to consensus
             dispatcher
                 .submit_collection_meta_op(
@@ -158,5 +205,26 @@ pub async fn do_update_collection_cluster(
                 )
                 .await
         }
+        ClusterOperations::SetReplicaState(SetReplicaStateOperation { set_replica_state }) => {
+            collection
+                .validate_replica_state_change(
+                    set_replica_state.shard_id,
+                    set_replica_state.peer_id,
+                    set_replica_state.state,
+                )
+                .await?;
+
+            dispatcher
+                .submit_collection_meta_op(
+                    CollectionMetaOperations::SetShardReplicaState(SetShardReplicaState {
+                        collection_name,
+                        shard_id: set_replica_state.shard_id,
+                        peer_id: set_replica_state.peer_id,
+                        state: set_replica_state.state,
+                    }),
+                    wait_timeout,
+                )
+                .await
+        }
     }
 }
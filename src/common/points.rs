@@ -1,11 +1,11 @@
 use collection::operations::payload_ops::{DeletePayload, PayloadOps, SetPayload};
 use collection::operations::point_ops::{PointInsertOperations, PointOperations, PointsSelector};
+use collection::operations::shard_selector::ShardSelectorInternal;
 use collection::operations::types::{
     CountRequest, CountResult, PointRequest, Record, ScrollRequest, ScrollResult, SearchRequest,
     SearchRequestBatch, UpdateResult,
 };
 use collection::operations::{CollectionUpdateOperations, CreateIndex, FieldIndexOperations};
-use collection::shard::ShardId;
 use schemars::JsonSchema;
 use segment::types::{PayloadFieldSchema, ScoredPoint};
 use serde::{Deserialize, Serialize};
@@ -23,7 +23,7 @@ pub async fn do_upsert_points(
     toc: &TableOfContent,
     collection_name: &str,
     operation: PointInsertOperations,
-    shard_selection: Option<ShardId>,
+    shard_selection: ShardSelectorInternal,
     wait: bool,
 ) -> Result<UpdateResult, StorageError> {
     let collection_operation =
@@ -36,7 +36,7 @@ pub async fn do_delete_points(
     toc: &TableOfContent,
     collection_name: &str,
     points: PointsSelector,
-    shard_selection: Option<ShardId>,
+    shard_selection: ShardSelectorInternal,
     wait: bool,
 ) -> Result<UpdateResult, StorageError> {
     let point_operation = match points {
@@ -56,7 +56,7 @@ pub async fn do_set_payload(
     toc: &TableOfContent,
     collection_name: &str,
     operation: SetPayload,
-    shard_selection: Option<ShardId>,
+    shard_selection: ShardSelectorInternal,
     wait: bool,
 ) -> Result<UpdateResult, StorageError> {
     let collection_operation =
@@ -69,7 +69,7 @@ pub async fn do_delete_payload(
     toc: &TableOfContent,
     collection_name: &str,
     operation: DeletePayload,
-    shard_selection: Option<ShardId>,
+    shard_selection: ShardSelectorInternal,
     wait: bool,
 ) -> Result<UpdateResult, StorageError> {
     let collection_operation =
@@ -82,7 +82,7 @@ pub async fn do_clear_payload(
     toc: &TableOfContent,
     collection_name: &str,
     points: PointsSelector,
-    shard_selection: Option<ShardId>,
+    shard_selection: ShardSelectorInternal,
     wait: bool,
 ) -> Result<UpdateResult, StorageError> {
     let points_operation = match points {
@@ -103,24 +103,77 @@ pub async fn do_create_index(
     toc: &TableOfContent,
     collection_name: &str,
     operation: CreateFieldIndex,
-    shard_selection: Option<ShardId>,
+    shard_selection: ShardSelectorInternal,
     wait: bool,
 ) -> Result<UpdateResult, StorageError> {
+    if let Some(field_schema) = &operation.field_schema {
+        field_schema
+            .validate()
+            .map_err(|description| StorageError::BadInput { description })?;
+    }
+    let field_name = operation.field_name.clone();
     let collection_operation = CollectionUpdateOperations::FieldIndexOperation(
         FieldIndexOperations::CreateIndex(CreateIndex {
             field_name: operation.field_name,
             field_schema: operation.field_schema,
         }),
     );
-    toc.update(collection_name, collection_operation, shard_selection, wait)
-        .await
+    let result = toc
+        .update(
+            collection_name,
+            collection_operation,
+            shard_selection.clone(),
+            wait,
+        )
+        .await?;
+    if wait {
+        // Segments under optimization get the index only when optimization is finished,
+        // wait for it so that filters are guaranteed to use the index after response
+        toc.wait_field_index_ready(collection_name, &field_name, shard_selection)
+            .await?;
+    }
+    Ok(result)
+}
+
+pub async fn do_rebuild_index(
+    toc: &TableOfContent,
+    collection_name: &str,
+    operation: CreateFieldIndex,
+    shard_selection: ShardSelectorInternal,
+    wait: bool,
+) -> Result<UpdateResult, StorageError> {
+    if let Some(field_schema) = &operation.field_schema {
+        field_schema
+            .validate()
+            .map_err(|description| StorageError::BadInput { description })?;
+    }
+    let field_name = operation.field_name.clone();
+    let collection_operation = CollectionUpdateOperations::FieldIndexOperation(
+        FieldIndexOperations::RebuildIndex(CreateIndex {
+            field_name: operation.field_name,
+            field_schema: operation.field_schema,
+        }),
+    );
+    let result = toc
+        .update(
+            collection_name,
+            collection_operation,
+            shard_selection.clone(),
+            wait,
+        )
+        .await?;
+    if wait {
+        toc.wait_field_index_ready(collection_name, &field_name, shard_selection)
+            .await?;
+    }
+    Ok(result)
 }
 
 pub async fn do_delete_index(
     toc: &TableOfContent,
     collection_name: &str,
     index_name: String,
-    shard_selection: Option<ShardId>,
+    shard_selection: ShardSelectorInternal,
     wait: bool,
 ) -> Result<UpdateResult, StorageError> {
     let collection_operation = CollectionUpdateOperations::FieldIndexOperation(
@@ -134,7 +187,7 @@ pub async fn do_search_points(
     toc: &TableOfContent,
     collection_name: &str,
     request: SearchRequest,
-    shard_selection: Option<ShardId>,
+    shard_selection: ShardSelectorInternal,
 ) -> Result<Vec<ScoredPoint>, StorageError> {
     toc.search(collection_name, request, shard_selection).await
 }
@@ -143,7 +196,7 @@ pub async fn do_search_batch_points(
     toc: &TableOfContent,
     collection_name: &str,
     request: SearchRequestBatch,
-    shard_selection: Option<ShardId>,
+    shard_selection: ShardSelectorInternal,
 ) -> Result<Vec<Vec<ScoredPoint>>, StorageError> {
     toc.search_batch(collection_name, request, shard_selection)
         .await
@@ -153,7 +206,7 @@ pub async fn do_count_points(
     toc: &TableOfContent,
     collection_name: &str,
     request: CountRequest,
-    shard_selection: Option<ShardId>,
+    shard_selection: ShardSelectorInternal,
 ) -> Result<CountResult, StorageError> {
     toc.count(collection_name, request, shard_selection).await
 }
@@ -162,7 +215,7 @@ pub async fn do_get_points(
     toc: &TableOfContent,
     collection_name: &str,
     request: PointRequest,
-    shard_selection: Option<ShardId>,
+    shard_selection: ShardSelectorInternal,
 ) -> Result<Vec<Record>, StorageError> {
     toc.retrieve(collection_name, request, shard_selection)
         .await
@@ -172,7 +225,7 @@ pub async fn do_scroll_points(
     toc: &TableOfContent,
     collection_name: &str,
     request: ScrollRequest,
-    shard_selection: Option<ShardId>,
+    shard_selection: ShardSelectorInternal,
 ) -> Result<ScrollResult, StorageError> {
     toc.scroll(collection_name, request, shard_selection).await
 }
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 
+use collection::resource_watchdog::ResourceWatchdogTelemetry;
 use collection::telemetry::CollectionTelemetry;
 use parking_lot::Mutex;
 use schemars::JsonSchema;
@@ -19,6 +20,7 @@ pub struct TelemetryCollector {
     process_id: Uuid,
     settings: Settings,
     dispatcher: Arc<Dispatcher>,
+    resource_watchdog_telemetry: Arc<Mutex<ResourceWatchdogTelemetry>>,
     pub actix_telemetry_collector: Arc<Mutex<ActixTelemetryCollector>>,
     pub tonic_telemetry_collector: Arc<Mutex<TonicTelemetryCollector>>,
 }
@@ -60,6 +62,7 @@ pub struct TelemetryData {
     web: WebApiTelemetry,
     grpc_calls_statistics: TelemetryOperationStatistics,
     cluster_status: ClusterStatus,
+    resource_watchdog: ResourceWatchdogTelemetry,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
@@ -97,6 +100,8 @@ pub struct ServiceConfigTelemetry {
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 pub struct P2pConfigTelemetry {
     connection_pool_size: usize,
+    connection_idle_timeout_sec: u64,
+    max_concurrent_streams: usize,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
@@ -104,6 +109,10 @@ pub struct ConsensusConfigTelemetry {
     max_message_queue_size: usize,
     tick_period_ms: u64,
     bootstrap_timeout_sec: u64,
+    heartbeat_tick: usize,
+    election_tick: usize,
+    pre_vote: bool,
+    max_size_per_msg: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
@@ -140,6 +149,7 @@ impl Anonymize for TelemetryData {
             web: self.web.anonymize(),
             grpc_calls_statistics: self.grpc_calls_statistics.anonymize(),
             cluster_status: self.cluster_status.anonymize(),
+            resource_watchdog: self.resource_watchdog.anonymize(),
         }
     }
 }
@@ -204,6 +214,8 @@ impl Anonymize for P2pConfigTelemetry {
     fn anonymize(&self) -> Self {
         P2pConfigTelemetry {
             connection_pool_size: self.connection_pool_size,
+            connection_idle_timeout_sec: self.connection_idle_timeout_sec,
+            max_concurrent_streams: self.max_concurrent_streams,
         }
     }
 }
@@ -214,6 +226,10 @@ impl Anonymize for ConsensusConfigTelemetry {
             max_message_queue_size: self.max_message_queue_size,
             tick_period_ms: self.tick_period_ms,
             bootstrap_timeout_sec: self.bootstrap_timeout_sec,
+            heartbeat_tick: self.heartbeat_tick,
+            election_tick: self.election_tick,
+            pre_vote: self.pre_vote,
+            max_size_per_msg: self.max_size_per_msg,
         }
     }
 }
@@ -239,11 +255,16 @@ impl WebApiTelemetry {
 }
 
 impl TelemetryCollector {
-    pub fn new(settings: Settings, dispatcher: Arc<Dispatcher>) -> Self {
+    pub fn new(
+        settings: Settings,
+        dispatcher: Arc<Dispatcher>,
+        resource_watchdog_telemetry: Arc<Mutex<ResourceWatchdogTelemetry>>,
+    ) -> Self {
         Self {
             process_id: Uuid::new_v4(),
             settings,
             dispatcher,
+            resource_watchdog_telemetry,
             actix_telemetry_collector: Arc::new(Mutex::new(ActixTelemetryCollector {
                 web_workers_telemetry: Vec::new(),
             })),
@@ -266,6 +287,7 @@ impl TelemetryCollector {
             web: self.get_web_data(),
             grpc_calls_statistics,
             cluster_status,
+            resource_watchdog: self.resource_watchdog_telemetry.lock().clone(),
         }
     }
 
@@ -339,11 +361,17 @@ impl TelemetryCollector {
                 grpc_timeout_ms: settings.cluster.grpc_timeout_ms,
                 p2p: P2pConfigTelemetry {
                     connection_pool_size: settings.cluster.p2p.connection_pool_size,
+                    connection_idle_timeout_sec: settings.cluster.p2p.connection_idle_timeout_sec,
+                    max_concurrent_streams: settings.cluster.p2p.max_concurrent_streams,
                 },
                 consensus: ConsensusConfigTelemetry {
                     max_message_queue_size: settings.cluster.consensus.max_message_queue_size,
                     tick_period_ms: settings.cluster.consensus.tick_period_ms,
                     bootstrap_timeout_sec: settings.cluster.consensus.bootstrap_timeout_sec,
+                    heartbeat_tick: settings.cluster.consensus.heartbeat_tick,
+                    election_tick: settings.cluster.consensus.election_tick,
+                    pre_vote: settings.cluster.consensus.pre_vote,
+                    max_size_per_msg: settings.cluster.consensus.max_size_per_msg,
                 },
             },
         }
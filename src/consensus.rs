@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::{Receiver, RecvTimeoutError, SyncSender};
 use std::sync::{mpsc, Arc};
@@ -14,11 +14,13 @@ use collection::shard::ChannelService;
 use raft::eraftpb::Message as RaftMessage;
 use raft::prelude::*;
 use raft::{SoftState, StateRole};
-use storage::content_manager::consensus_ops::ConsensusOperations;
+use storage::content_manager::consensus_ops::{
+    operation_id_to_context, ConsensusOperations, MAX_PROPOSAL_BATCH,
+};
 use storage::content_manager::consensus_state::ConsensusStateRef;
 use storage::content_manager::errors::StorageError;
 use storage::content_manager::toc::TableOfContent;
-use storage::types::PeerAddressById;
+use storage::types::{PeerAddressById, PeerProgress};
 use tokio::runtime::Runtime;
 use tonic::transport::Uri;
 
@@ -28,6 +30,9 @@ use crate::tonic::init_internal;
 
 type Node = RawNode<ConsensusStateRef>;
 
+/// Delay between attempts to bootstrap from seed peers
+const BOOTSTRAP_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
 pub enum Message {
     FromClient(ConsensusOperations),
     FromPeer(Box<RaftMessage>),
@@ -48,7 +53,7 @@ impl Consensus {
     pub fn run(
         logger: &slog::Logger,
         state_ref: ConsensusStateRef,
-        bootstrap_peer: Option<Uri>,
+        bootstrap_peers: Vec<Uri>,
         uri: Option<String>,
         p2p_host: String,
         p2p_port: u16,
@@ -58,10 +63,11 @@ impl Consensus {
         telemetry_collector: Arc<parking_lot::Mutex<TonicTelemetryCollector>>,
         toc: Arc<TableOfContent>,
     ) -> anyhow::Result<JoinHandle<std::io::Result<()>>> {
+        let join_token = config.join_token.clone();
         let (mut consensus, message_sender) = Self::new(
             logger,
             state_ref.clone(),
-            bootstrap_peer,
+            bootstrap_peers,
             uri,
             p2p_port,
             config,
@@ -86,12 +92,19 @@ impl Consensus {
             .name("forward-proposals".to_string())
             .spawn(move || {
                 while let Ok(entry) = propose_receiver.recv() {
-                    if message_sender_moved
-                        .send(Message::FromClient(entry))
-                        .is_err()
-                    {
-                        log::error!("Can not forward new entry to consensus as it was stopped.");
-                        break;
+                    // Operations, which are already waiting, are proposed in batches
+                    let mut entries = vec![entry];
+                    entries.extend(propose_receiver.try_iter().take(MAX_PROPOSAL_BATCH));
+                    for entry in ConsensusOperations::batch(entries) {
+                        if message_sender_moved
+                            .send(Message::FromClient(entry))
+                            .is_err()
+                        {
+                            log::error!(
+                                "Can not forward new entry to consensus as it was stopped."
+                            );
+                            return;
+                        }
                     }
                 }
             })?;
@@ -106,6 +119,7 @@ impl Consensus {
                     p2p_host,
                     p2p_port,
                     message_sender,
+                    join_token,
                 )
             })
             .unwrap();
@@ -113,12 +127,12 @@ impl Consensus {
         Ok(handle)
     }
 
-    /// If `bootstrap_peer` peer is supplied, then either `uri` or `p2p_port` should be also supplied
+    /// If `bootstrap_peers` are supplied, then either `uri` or `p2p_port` should be also supplied
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         logger: &slog::Logger,
         state_ref: ConsensusStateRef,
-        bootstrap_peer: Option<Uri>,
+        bootstrap_peers: Vec<Uri>,
         uri: Option<String>,
         p2p_port: u16,
         config: ConsensusConfig,
@@ -129,6 +143,10 @@ impl Consensus {
         let raft_config = Config {
             id: state_ref.this_peer_id(),
             applied: last_applied,
+            heartbeat_tick: config.heartbeat_tick,
+            election_tick: config.election_tick,
+            pre_vote: config.pre_vote,
+            max_size_per_msg: config.max_size_per_msg,
             ..Default::default()
         };
         raft_config.validate()?;
@@ -153,12 +171,13 @@ impl Consensus {
         let (sender, receiver) = mpsc::sync_channel(config.max_message_queue_size);
         // State might be initialized but the node might be shutdown without actually syncing or committing anything.
         let is_new_deployment = state_ref.hard_state().term == 0;
+        let mut bootstrap_uri = bootstrap_peers.first().cloned();
         if is_new_deployment {
             let leader_established_in_ms =
                 config.tick_period_ms * raft_config.max_election_tick() as u64;
-            Self::init(
+            let bootstrapped_from = Self::init(
                 &state_ref,
-                bootstrap_peer.clone(),
+                &bootstrap_peers,
                 uri,
                 p2p_port,
                 &config,
@@ -166,8 +185,9 @@ impl Consensus {
                 leader_established_in_ms,
             )
             .context("Failed to initialize Consensus for new Raft state")?;
+            bootstrap_uri = bootstrapped_from.or(bootstrap_uri);
         } else {
-            if bootstrap_peer.is_some() || uri.is_some() {
+            if !bootstrap_peers.is_empty() || uri.is_some() {
                 log::debug!("Local raft state found - bootstrap and uri cli arguments were ignored")
             }
             log::debug!("Local raft state found - skipping initialization");
@@ -181,7 +201,7 @@ impl Consensus {
             node,
             receiver,
             runtime,
-            bootstrap_uri: bootstrap_peer,
+            bootstrap_uri,
             config,
             channel_service,
         };
@@ -189,26 +209,26 @@ impl Consensus {
         Ok((consensus, sender))
     }
 
+    /// Returns address of the peer, which this peer bootstrapped from
     #[allow(clippy::too_many_arguments)]
     fn init(
         state_ref: &ConsensusStateRef,
-        bootstrap_peer: Option<Uri>,
+        bootstrap_peers: &[Uri],
         uri: Option<String>,
         p2p_port: u16,
         config: &ConsensusConfig,
         runtime: &Runtime,
         leader_established_in_ms: u64,
-    ) -> anyhow::Result<()> {
-        if let Some(bootstrap_peer) = bootstrap_peer {
-            log::debug!("Bootstrapping from peer with address: {bootstrap_peer}");
-            runtime.block_on(Self::bootstrap(
+    ) -> anyhow::Result<Option<Uri>> {
+        if !bootstrap_peers.is_empty() {
+            let bootstrapped_from = runtime.block_on(Self::bootstrap_from_seeds(
                 state_ref,
-                bootstrap_peer,
+                bootstrap_peers,
                 uri,
                 p2p_port,
                 config,
             ))?;
-            Ok(())
+            Ok(Some(bootstrapped_from))
         } else {
             log::debug!(
                 "Bootstrapping is disabled. Assuming this peer is the first in the network"
@@ -221,7 +241,46 @@ impl Consensus {
                 uri.ok_or_else(|| anyhow::anyhow!("First peer should specify its uri."))?
                     .parse()?,
             )?;
-            Ok(())
+            Ok(None)
+        }
+    }
+
+    /// Bootstrap from the first of seed peers, which adds this peer to the cluster.
+    /// Seeds are resolved and tried again for `bootstrap_retry_sec`, as they might be not started yet
+    /// or have no leader. Returns address of the peer, which this peer bootstrapped from.
+    async fn bootstrap_from_seeds(
+        state_ref: &ConsensusStateRef,
+        seeds: &[Uri],
+        uri: Option<String>,
+        p2p_port: u16,
+        config: &ConsensusConfig,
+    ) -> anyhow::Result<Uri> {
+        let deadline = Instant::now() + Duration::from_secs(config.bootstrap_retry_sec);
+        loop {
+            let mut last_error = None;
+            for bootstrap_peer in resolve_seeds(seeds).await {
+                log::debug!("Bootstrapping from peer with address: {bootstrap_peer}");
+                match Self::bootstrap(
+                    state_ref,
+                    bootstrap_peer.clone(),
+                    uri.clone(),
+                    p2p_port,
+                    config,
+                )
+                .await
+                {
+                    Ok(()) => return Ok(bootstrap_peer),
+                    Err(err) => {
+                        log::warn!("Failed to bootstrap from peer {bootstrap_peer}: {err:#}");
+                        last_error = Some(err);
+                    }
+                }
+            }
+            if Instant::now() >= deadline {
+                return Err(last_error
+                    .unwrap_or_else(|| anyhow::anyhow!("None of seed peers could be resolved")));
+            }
+            tokio::time::sleep(BOOTSTRAP_RETRY_INTERVAL).await;
         }
     }
 
@@ -248,6 +307,7 @@ impl Consensus {
                     uri,
                     port: Some(p2p_port as u32),
                     id,
+                    join_token: config.join_token.clone(),
                 },
             ))
             .await
@@ -282,10 +342,7 @@ impl Consensus {
         let mut timeout = Duration::from_millis(self.config.tick_period_ms);
 
         loop {
-            if !self
-                .try_promote_learner()
-                .context("Failed to promote learner")?
-            {
+            if !self.try_promote_learner() {
                 // If learner promotion was proposed - do not add other proposals.
                 self.propose_updates(timeout)?;
             }
@@ -297,10 +354,14 @@ impl Consensus {
                 self.node.tick();
                 // Try to reapply entries if some were not applied due to errors.
                 let store = self.node.store().clone();
+                store.set_peers_progress(self.peers_progress());
                 let stop_consensus = store.apply_entries(&mut self.node);
                 if stop_consensus {
                     return Ok(());
                 }
+                if let Err(err) = store.compact_wal(false) {
+                    log::error!("Failed to compact consensus WAL: {err}");
+                }
             } else {
                 timeout -= d;
             }
@@ -344,6 +405,16 @@ impl Consensus {
                         log::debug!("Proposing network configuration change: {:?}", change);
                         self.node.propose_conf_change(vec![], change)
                     }
+                    ConsensusOperations::ReadIndex(request_id) => {
+                        log::trace!("Requesting read index {request_id}");
+                        self.node.read_index(operation_id_to_context(request_id));
+                        Ok(())
+                    }
+                    ConsensusOperations::TransferLeadership(peer_id) => {
+                        log::info!("Transferring leadership to peer {peer_id}");
+                        self.node.transfer_leader(peer_id);
+                        Ok(())
+                    }
                     ConsensusOperations::AddPeer(peer_id, uri) => {
                         let mut change = ConfChangeV2::default();
                         change.set_changes(vec![raft_proto::new_conf_change_single(
@@ -361,8 +432,10 @@ impl Consensus {
                                 return Ok(());
                             }
                         };
+                        // Operation ids allow to skip duplicate proposals of the same operation
+                        let context = self.node.store().proposal_context(&operation);
                         log::debug!("Proposing entry from client with length: {}", message.len());
-                        self.node.propose(vec![], message)
+                        self.node.propose(context, message)
                     }
                 };
 
@@ -384,35 +457,43 @@ impl Consensus {
         Ok(())
     }
 
+    /// Proposes promotion of a learner to voter, once it has caught up with the consensus log.
+    ///
     /// Returns `true` if learner promotion was proposed, `false` otherwise.
-    fn try_promote_learner(&mut self) -> anyhow::Result<bool> {
-        let learner = if let Some(learner) = self.find_learner_to_promote() {
-            learner
-        } else {
-            return Ok(false);
-        };
-        let store = self.node.store();
-        let commit = store.hard_state().commit;
-        let last_log_entry = store.last_index()?;
-        // Promote only when there are no uncommitted changes.
-        if commit != last_log_entry {
-            return Ok(false);
-        }
+    fn try_promote_learner(&mut self) -> bool {
         let status = self.node.status();
         // Promote only if leader
         if status.ss.raft_state != StateRole::Leader {
-            return Ok(false);
+            return false;
         }
+        // Only one configuration change can be in progress at a time.
+        // Wait until the previous one is applied, otherwise Raft would drop the proposal.
+        if self.node.raft.has_pending_conf() {
+            return false;
+        }
+        let learner = if let Some(learner) = self.find_learner_to_promote() {
+            learner
+        } else {
+            return false;
+        };
         let mut change = ConfChangeV2::default();
         change.set_changes(vec![raft_proto::new_conf_change_single(
             learner,
             ConfChangeType::AddNode,
         )]);
         log::debug!("Proposing promotion for learner {learner} to voter");
-        self.node.propose_conf_change(vec![], change)?;
-        Ok(true)
+        if let Err(err) = self.node.propose_conf_change(vec![], change) {
+            // Do not stop consensus, promotion will be retried on the next iteration.
+            log::warn!("Failed to propose promotion for learner {learner}: {err:?}");
+            return false;
+        }
+        true
     }
 
+    /// Finds a learner, which has replicated all committed entries of the log.
+    ///
+    /// Learners are not required to match uncommitted entries, so that they are promoted
+    /// even if the leader keeps receiving new proposals.
     fn find_learner_to_promote(&self) -> Option<u64> {
         let commit = self.node.store().hard_state().commit;
         let learners: HashSet<_> = self
@@ -426,8 +507,20 @@ impl Consensus {
         status
             .progress?
             .iter()
-            .find(|(id, progress)| learners.contains(id) && progress.matched == commit)
+            .filter(|(id, progress)| learners.contains(id) && progress.matched >= commit)
             .map(|(id, _)| *id)
+            .min()
+    }
+
+    /// Replication progress of peers, which is only tracked if this peer is the leader.
+    fn peers_progress(&self) -> Option<HashMap<u64, PeerProgress>> {
+        let status = self.node.status();
+        let progress = status
+            .progress?
+            .iter()
+            .map(|(id, progress)| (*id, progress.into()))
+            .collect();
+        Some(progress)
     }
 
     /// Returns `true` if consensus should be stopped, `false` otherwise.
@@ -479,6 +572,10 @@ impl Consensus {
             return Ok(None);
         }
 
+        for read_state in ready.take_read_states() {
+            store.on_read_index(&read_state.request_ctx, read_state.index);
+        }
+
         if !ready.entries().is_empty() {
             // Append entries to the Raft log.
             log::debug!("Appending {} entries to raft log", ready.entries().len());
@@ -617,6 +714,38 @@ fn handle_committed_entries(
     Ok(stop_consensus)
 }
 
+/// Resolve host of each seed to all of its addresses,
+/// so that a single DNS name could stand for multiple peers, e.g. a headless service in Kubernetes.
+/// Seeds, which can't be resolved, are skipped.
+async fn resolve_seeds(seeds: &[Uri]) -> Vec<Uri> {
+    let mut resolved = Vec::new();
+    for seed in seeds {
+        let (host, port) = match (seed.host(), seed.port_u16()) {
+            (Some(host), Some(port)) => (host, port),
+            _ => {
+                resolved.push(seed.clone());
+                continue;
+            }
+        };
+        let addresses = match tokio::net::lookup_host((host, port)).await {
+            Ok(addresses) => addresses,
+            Err(err) => {
+                log::warn!("Failed to resolve seed peer {seed}: {err}");
+                continue;
+            }
+        };
+        let scheme = seed.scheme_str().unwrap_or("http");
+        for address in addresses {
+            match format!("{scheme}://{address}").parse() {
+                Ok(uri) if !resolved.contains(&uri) => resolved.push(uri),
+                Ok(_) => {}
+                Err(err) => log::warn!("Failed to parse resolved address {address}: {err}"),
+            }
+        }
+    }
+    resolved
+}
+
 async fn who_is(
     peer_id: collection::shard::PeerId,
     bootstrap_uri: Option<Uri>,
@@ -681,9 +810,29 @@ mod tests {
     use storage::dispatcher::Dispatcher;
     use tempfile::Builder;
 
-    use super::Consensus;
+    use super::{resolve_seeds, Consensus};
     use crate::settings::ConsensusConfig;
 
+    #[tokio::test]
+    async fn seeds_are_resolved() {
+        let seeds = vec![
+            "http://localhost:6335".parse().unwrap(),
+            "http://127.0.0.1:6335".parse().unwrap(),
+            "http://127.0.0.1:6336".parse().unwrap(),
+        ];
+        let resolved: Vec<_> = resolve_seeds(&seeds)
+            .await
+            .into_iter()
+            .map(|uri| uri.to_string())
+            .filter(|uri| !uri.contains("[::1]"))
+            .collect();
+        // Repeated addresses are tried once
+        assert_eq!(
+            resolved,
+            vec!["http://127.0.0.1:6335/", "http://127.0.0.1:6336/"]
+        );
+    }
+
     #[test]
     fn collection_creation_passes_consensus() {
         // Given
@@ -712,6 +861,7 @@ mod tests {
             toc_arc.clone(),
             operation_sender,
             storage_path,
+            settings.cluster.consensus.compact_wal_entries,
         )
         .into();
         let dispatcher = Dispatcher::new(toc_arc.clone()).with_consensus(consensus_state.clone());
@@ -719,7 +869,7 @@ mod tests {
         let (mut consensus, message_sender) = Consensus::new(
             &slog_logger,
             consensus_state.clone(),
-            None,
+            vec![],
             Some("http://127.0.0.1:6335".parse().unwrap()),
             6335,
             ConsensusConfig::default(),
@@ -760,13 +910,27 @@ mod tests {
                             vectors: VectorParams {
                                 size: NonZeroU64::new(10).unwrap(),
                                 distance: Distance::Cosine,
+                                quantization_config: None,
+                                on_disk: None,
+                                multivector_config: None,
+                                hnsw_config: None,
+                                normalize: None,
                             }
                             .into(),
                             hnsw_config: None,
                             wal_config: None,
                             optimizers_config: None,
+                            sparse_vectors: None,
                             shard_number: Some(2),
                             on_disk_payload: None,
+                            max_response_payload_bytes: None,
+                            payload_transformers: None,
+                            ivf_config: None,
+                            placement: None,
+                            read_fan_out_factor: None,
+                            write_consistency: None,
+                            read_consistency: None,
+                            hash_ring: None,
                         },
                     }),
                     None,
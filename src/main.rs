@@ -11,20 +11,26 @@ mod startup;
 mod tonic;
 
 use std::io::Error;
+use std::path::Path;
 use std::sync::Arc;
 use std::thread;
 use std::thread::JoinHandle;
 use std::time::Duration;
 
 use ::tonic::transport::Uri;
+use api::grpc::api_crate_version;
 use api::grpc::transport_channel_pool::TransportChannelPool;
 use clap::Parser;
+use collection::config::PeerMetadata;
+use collection::resource_watchdog::ResourceWatchdog;
 use collection::shard::ChannelService;
 use consensus::Consensus;
 use slog::Drain;
 use storage::content_manager::consensus::operation_sender::OperationSender;
 use storage::content_manager::consensus::persistent::Persistent;
+use storage::content_manager::consensus_ops::ConsensusOperations;
 use storage::content_manager::consensus_state::{ConsensusState, ConsensusStateRef};
+use storage::content_manager::replica_verification::ReplicaVerifier;
 use storage::content_manager::toc::TableOfContent;
 use storage::dispatcher::Dispatcher;
 #[cfg(not(target_env = "msvc"))]
@@ -49,9 +55,12 @@ static GLOBAL: Jemalloc = Jemalloc;
 #[command(version, about)]
 struct Args {
     /// Uri of the peer to bootstrap from in case of multi-peer deployment.
+    /// Multiple seed peers could be specified, separated by comma.
+    /// Host of a seed might be a DNS name, which resolves to multiple peers, e.g. a headless service of a StatefulSet.
+    /// Seeds are tried until one of them adds this peer to the cluster.
     /// If not specified - this peer will be considered as a first in a new deployment.
-    #[arg(long, value_parser, value_name = "URI")]
-    bootstrap: Option<Uri>,
+    #[arg(long, value_parser, value_name = "URI", value_delimiter = ',')]
+    bootstrap: Option<Vec<Uri>>,
     /// Uri of this peer.
     /// Other peers should be able to reach it by this uri.
     ///
@@ -74,9 +83,22 @@ struct Args {
     snapshot: Option<Vec<String>>,
 
     /// Path to snapshot of multiple collections.
+    /// Cluster snapshot also restores raft state and consensus WAL of this peer.
     /// Format: <snapshot_file_path>
     #[arg(long, value_name = "PATH")]
     storage_snapshot: Option<String>,
+
+    /// Truncate the consensus WAL at the first corrupted entry, even if the entry is already committed.
+    /// Truncated entries are received from the leader again.
+    /// Without this flag, the peer refuses to start with a corrupted committed entry.
+    #[arg(long, action, default_value_t = false)]
+    repair_consensus_wal: bool,
+
+    /// UNSAFE: recover a cluster, which permanently lost the quorum, from the data of this peer.
+    /// This peer becomes the only voter of the cluster, all other peers are removed from it.
+    /// Operations, which are not committed, are lost. Use only once, when other peers can't be restored.
+    #[arg(long, action, default_value_t = false)]
+    force_single_node_recovery: bool,
 }
 
 fn main() -> anyhow::Result<()> {
@@ -102,6 +124,18 @@ fn main() -> anyhow::Result<()> {
 
     welcome();
 
+    if settings.storage.performance.async_scorer {
+        if cfg!(all(target_os = "linux", feature = "async_scorer")) {
+            segment::vector_storage::common::set_async_scorer(true);
+        } else {
+            log::warn!("Async scorer is enabled in config, but not supported by this build");
+        }
+    }
+
+    segment::vector_storage::common::set_mmap_prefetch(settings.storage.performance.mmap_prefetch);
+
+    collection::shard::forward_proxy_shard::set_forward_window(settings.cluster.p2p.forward_window);
+
     // Create and own search runtime out of the scope of async context to ensure correct
     // destruction of it
     let runtime = create_search_runtime(settings.storage.performance.max_search_threads)
@@ -127,12 +161,20 @@ fn main() -> anyhow::Result<()> {
         // So we initialize it with real values here
         let p2p_grpc_timeout = Duration::from_millis(settings.cluster.grpc_timeout_ms);
         let connection_timeout = Duration::from_millis(settings.cluster.connection_timeout_ms);
-        channel_service.channel_pool = Arc::new(TransportChannelPool::new(
-            p2p_grpc_timeout,
-            connection_timeout,
-            settings.cluster.p2p.connection_pool_size,
-        ));
+        let p2p = &settings.cluster.p2p;
+        let idle_timeout = (p2p.connection_idle_timeout_sec > 0)
+            .then(|| Duration::from_secs(p2p.connection_idle_timeout_sec));
+        channel_service.channel_pool = Arc::new(
+            TransportChannelPool::new(
+                p2p_grpc_timeout,
+                connection_timeout,
+                p2p.connection_pool_size,
+            )
+            .with_idle_timeout(idle_timeout)
+            .with_max_concurrent_streams(Some(p2p.max_concurrent_streams)),
+        );
         channel_service.id_to_address = persistent_consensus_state.peer_address_by_id.clone();
+        channel_service.retry_policy = settings.cluster.p2p.retry_policy;
     }
 
     // Table of content manages the list of collections.
@@ -143,7 +185,9 @@ fn main() -> anyhow::Result<()> {
         channel_service.clone(),
         persistent_consensus_state.this_peer_id(),
         propose_operation_sender.clone(),
-    );
+    )
+    .with_peer_metadata(persistent_consensus_state.peer_metadata_by_id.clone())
+    .with_draining_peers(persistent_consensus_state.draining_peers.clone());
 
     // Here we load all stored collections.
     runtime_handle.block_on(async {
@@ -166,16 +210,37 @@ fn main() -> anyhow::Result<()> {
         toc_arc.clone(),
         propose_operation_sender,
         storage_path,
+        settings.cluster.consensus.compact_wal_entries,
     )
     .into();
 
+    // Torn writes might corrupt the tail of the consensus WAL
+    consensus_state.check_wal_integrity(args.repair_consensus_wal)?;
+
+    if args.force_single_node_recovery {
+        consensus_state.force_single_node_recovery()?;
+    }
+
     if settings.cluster.enabled {
         dispatcher = dispatcher.with_consensus(consensus_state.clone());
     }
     let dispatcher_arc = Arc::new(dispatcher);
 
+    // Watchdog pauses optimizations and shard transfers if the node runs out of resources.
+    let resource_watchdog = ResourceWatchdog::new(
+        settings.storage.resource_watchdog.clone(),
+        Path::new(&settings.storage.storage_path),
+        toc_arc.resource_throttle(),
+    );
+    let resource_watchdog_telemetry = resource_watchdog.telemetry();
+    let _watchdog_handle = resource_watchdog.run();
+
     // Monitoring and telemetry.
-    let telemetry_collector = TelemetryCollector::new(settings.clone(), dispatcher_arc.clone());
+    let telemetry_collector = TelemetryCollector::new(
+        settings.clone(),
+        dispatcher_arc.clone(),
+        resource_watchdog_telemetry,
+    );
     let tonic_telemetry_collector = telemetry_collector.tonic_telemetry_collector.clone();
 
     if settings.cluster.enabled {
@@ -190,7 +255,7 @@ fn main() -> anyhow::Result<()> {
         let handle = Consensus::run(
             &slog_logger,
             consensus_state.clone(),
-            args.bootstrap,
+            args.bootstrap.unwrap_or_default(),
             args.uri.map(|uri| uri.to_string()),
             settings.service.host.clone(),
             p2p_port,
@@ -204,6 +269,36 @@ fn main() -> anyhow::Result<()> {
 
         handles.push(handle);
 
+        let peer_metadata = PeerMetadata {
+            version: Some(api_crate_version().to_string()),
+            ..settings.cluster.peer_metadata.clone()
+        };
+        let consensus_state_clone = consensus_state.clone();
+        let _peer_metadata_handle = runtime_handle.spawn(async move {
+            // Metadata of the peer, including its version, is compared with the consensus state once it is caught up
+            consensus_state_clone.is_leader_established.await_ready();
+            let this_peer_id = consensus_state_clone.this_peer_id();
+            let known_metadata = consensus_state_clone
+                .persistent
+                .read()
+                .peer_metadata_by_id()
+                .remove(&this_peer_id)
+                .unwrap_or_default();
+            if known_metadata == peer_metadata {
+                return;
+            }
+            let operation = ConsensusOperations::UpdatePeerMetadata(this_peer_id, peer_metadata);
+            if let Err(err) = consensus_state_clone.propose_consensus_op(operation).await {
+                log::error!("Can't update metadata of this peer: {}", err);
+            }
+        });
+
+        let _replica_verifier_handle = ReplicaVerifier::new(
+            toc_arc.clone(),
+            Duration::from_secs(settings.cluster.replica_verification_interval_sec),
+        )
+        .run(toc_arc.transfer_runtime_handle());
+
         let toc_arc_clone = toc_arc.clone();
         let _cancel_transfer_handle = runtime_handle.spawn(async move {
             consensus_state.is_leader_established.await_ready();
@@ -218,6 +313,12 @@ fn main() -> anyhow::Result<()> {
                     log::error!("Can't cancel outgoing transfers: {}", err);
                 }
             }
+            if let Err(err) = toc_arc_clone.recover_local_replicas().await {
+                log::error!("Can't recover local replicas: {}", err);
+            }
+            if let Err(err) = toc_arc_clone.drain_local_shards().await {
+                log::error!("Can't continue draining of this peer: {}", err);
+            }
         });
     } else {
         log::info!("Distributed mode disabled");
@@ -4,9 +4,10 @@ use collection::operations::payload_ops::{DeletePayload, SetPayload};
 use collection::operations::point_ops::{PointInsertOperations, PointsSelector};
 use collection::operations::snapshot_ops::SnapshotDescription;
 use collection::operations::types::{
-    CollectionClusterInfo, CollectionInfo, CountRequest, CountResult, PointRequest,
-    RecommendRequest, RecommendRequestBatch, Record, ScrollRequest, ScrollResult, SearchRequest,
-    SearchRequestBatch, UpdateResult,
+    CollectionClusterInfo, CollectionInfo, CountRequest, CountResult, ExportRequest,
+    FieldIndexEstimation, FieldIndexEstimationRequest, PointRequest, RecommendRequest,
+    RecommendRequestBatch, Record, ScrollRequest, ScrollResult, SearchRequest, SearchRequestBatch,
+    ShardMovementEstimate, ShardUpdateResult, UpdateResult,
 };
 use schemars::{schema_for, JsonSchema};
 use segment::types::ScoredPoint;
@@ -14,7 +15,10 @@ use serde::{Deserialize, Serialize};
 use storage::content_manager::collection_meta_ops::{
     ChangeAliasesOperation, CreateCollection, UpdateCollection,
 };
-use storage::types::ClusterStatus;
+use storage::content_manager::snapshots::{
+    MountSnapshotRequest, RestoreSnapshotRequest, SnapshotRecover,
+};
+use storage::types::{AuditRecord, ClusterStatus, ConsensusDebugInfo};
 
 use crate::common::points::CreateFieldIndex;
 use crate::common::telemetry::TelemetryData;
@@ -54,6 +58,16 @@ struct AllDefinitions {
     ar: ClusterOperations,
     at: SearchRequestBatch,
     au: RecommendRequestBatch,
+    av: MountSnapshotRequest,
+    aw: FieldIndexEstimationRequest,
+    ax: FieldIndexEstimation,
+    ay: ShardUpdateResult,
+    az: ShardMovementEstimate,
+    ba: ConsensusDebugInfo,
+    bb: AuditRecord,
+    bc: SnapshotRecover,
+    bd: RestoreSnapshotRequest,
+    be: ExportRequest,
 }
 
 fn save_schema<T: JsonSchema>() {
@@ -1,5 +1,7 @@
 use std::env;
 
+use collection::config::PeerMetadata;
+use collection::shard::retry_policy::RetryPolicy;
 use config::{Config, ConfigError, Environment, File};
 use serde::Deserialize;
 use storage::types::StorageConfig;
@@ -26,6 +28,11 @@ pub struct ClusterConfig {
     pub p2p: P2pConfig,
     #[serde(default)]
     pub consensus: ConsensusConfig,
+    /// Location labels of this peer, used to spread shard replicas across failure domains
+    #[serde(default)]
+    pub peer_metadata: PeerMetadata,
+    #[serde(default = "default_replica_verification_interval_sec")]
+    pub replica_verification_interval_sec: u64, // 0 disables verification of replicas
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -34,6 +41,14 @@ pub struct P2pConfig {
     pub port: Option<u16>,
     #[serde(default = "default_connection_pool_size")]
     pub connection_pool_size: usize,
+    #[serde(default)]
+    pub connection_idle_timeout_sec: u64, // re-establish connections idle for longer, 0 - never
+    #[serde(default)]
+    pub max_concurrent_streams: usize, // max concurrent requests per connection, 0 - unlimited
+    #[serde(default = "default_forward_window")]
+    pub forward_window: usize, // max number of updates queued for forwarding during shard transfers
+    #[serde(default)]
+    pub retry_policy: RetryPolicy, // retries of requests to remote shards on transient errors
 }
 
 impl Default for P2pConfig {
@@ -41,6 +56,10 @@ impl Default for P2pConfig {
         P2pConfig {
             port: None,
             connection_pool_size: default_connection_pool_size(),
+            connection_idle_timeout_sec: 0,
+            max_concurrent_streams: 0,
+            forward_window: default_forward_window(),
+            retry_policy: Default::default(),
         }
     }
 }
@@ -53,6 +72,31 @@ pub struct ConsensusConfig {
     pub tick_period_ms: u64,
     #[serde(default = "default_bootstrap_timeout_sec")]
     pub bootstrap_timeout_sec: u64,
+    /// Time to retry bootstrapping from seed peers, until one of them adds this peer to the cluster.
+    /// Seeds are resolved again on each retry. If 0 - each seed is tried once.
+    #[serde(default = "default_bootstrap_retry_sec")]
+    pub bootstrap_retry_sec: u64,
+    /// Number of latest applied entries to keep in the consensus WAL on compaction
+    #[serde(default = "default_compact_wal_entries")]
+    pub compact_wal_entries: u64,
+    /// Number of ticks between heartbeats of the leader
+    #[serde(default = "default_heartbeat_tick")]
+    pub heartbeat_tick: usize,
+    /// Number of ticks without heartbeats, after which a follower starts an election.
+    /// Should be greater than `heartbeat_tick`.
+    #[serde(default = "default_election_tick")]
+    pub election_tick: usize,
+    /// Check that a candidate could win before starting an election,
+    /// so that a partitioned peer does not disrupt the cluster on reconnect
+    #[serde(default)]
+    pub pre_vote: bool,
+    /// Max size of entries in a single append message, 0 - one entry per message
+    #[serde(default)]
+    pub max_size_per_msg: u64,
+    /// Shared secret, which new peers should present to join the cluster.
+    /// If not set - any peer can join.
+    #[serde(default)]
+    pub join_token: Option<String>,
 }
 
 impl Default for ConsensusConfig {
@@ -61,6 +105,13 @@ impl Default for ConsensusConfig {
             max_message_queue_size: default_max_message_queue_size(),
             tick_period_ms: default_tick_period_ms(),
             bootstrap_timeout_sec: default_bootstrap_timeout_sec(),
+            bootstrap_retry_sec: default_bootstrap_retry_sec(),
+            compact_wal_entries: default_compact_wal_entries(),
+            heartbeat_tick: default_heartbeat_tick(),
+            election_tick: default_election_tick(),
+            pre_vote: false,
+            max_size_per_msg: 0,
+            join_token: None,
         }
     }
 }
@@ -106,6 +157,22 @@ fn default_bootstrap_timeout_sec() -> u64 {
     15
 }
 
+fn default_bootstrap_retry_sec() -> u64 {
+    60
+}
+
+fn default_heartbeat_tick() -> usize {
+    2
+}
+
+fn default_election_tick() -> usize {
+    10
+}
+
+fn default_compact_wal_entries() -> u64 {
+    128
+}
+
 fn default_max_message_queue_size() -> usize {
     100
 }
@@ -114,6 +181,14 @@ fn default_connection_pool_size() -> usize {
     2
 }
 
+fn default_replica_verification_interval_sec() -> u64 {
+    3600
+}
+
+fn default_forward_window() -> usize {
+    64
+}
+
 impl Settings {
     #[allow(dead_code)]
     pub fn new() -> Result<Self, ConfigError> {
@@ -1,10 +1,10 @@
-use std::fs::{remove_dir_all, rename};
+use std::fs::{remove_dir_all, remove_file, rename};
 use std::path::Path;
 
 use collection::collection::Collection;
 use log::info;
 use storage::content_manager::alias_mapping::AliasPersistence;
-use storage::content_manager::snapshots::SnapshotConfig;
+use storage::content_manager::snapshots::{SnapshotConfig, CONSENSUS_SNAPSHOT_DIR};
 use storage::content_manager::toc::{ALIASES_PATH, COLLECTIONS_DIR};
 
 /// Recover snapshots from the given arguments
@@ -103,6 +103,40 @@ pub fn recover_full_snapshot(snapshot_path: &str, storage_dir: &str, force: bool
         alias_persistence.insert(alias, collection_name).unwrap();
     }
 
+    // Cluster snapshot also contains the consensus state of the peer
+    let consensus_path = temporary_dir.join(CONSENSUS_SNAPSHOT_DIR);
+    if consensus_path.exists() {
+        recover_consensus_state(&consensus_path, storage_dir, force);
+    }
+
     // Remove temporary directory
     remove_dir_all(&temporary_dir).unwrap();
 }
+
+/// Replace raft state and consensus WAL of the peer with the ones from the cluster snapshot
+fn recover_consensus_state(consensus_path: &Path, storage_dir: &str, force: bool) {
+    let entries: Vec<_> = std::fs::read_dir(consensus_path)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    // Check everything before replacing anything, so that the consensus state is not mixed up
+    for entry in &entries {
+        let target_path = Path::new(storage_dir).join(entry.file_name().unwrap());
+        if target_path.exists() && !force {
+            panic!(
+                "Consensus state {} already exists. Use --force-snapshot to overwrite it.",
+                target_path.display()
+            );
+        }
+    }
+    for entry in entries {
+        let target_path = Path::new(storage_dir).join(entry.file_name().unwrap());
+        if target_path.is_dir() {
+            remove_dir_all(&target_path).unwrap();
+        } else if target_path.exists() {
+            remove_file(&target_path).unwrap();
+        }
+        info!("Recovering consensus state {}", target_path.display());
+        rename(&entry, &target_path).unwrap();
+    }
+}
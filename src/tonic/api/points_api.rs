@@ -4,16 +4,17 @@ use api::grpc::qdrant::points_server::Points;
 use api::grpc::qdrant::{
     ClearPayloadPoints, CountPoints, CountResponse, CreateFieldIndexCollection,
     DeleteFieldIndexCollection, DeletePayloadPoints, DeletePoints, GetPoints, GetResponse,
-    PointsOperationResponse, RecommendBatchPoints, RecommendBatchResponse, RecommendPoints,
-    RecommendResponse, ScrollPoints, ScrollResponse, SearchBatchPoints, SearchBatchResponse,
-    SearchPoints, SearchResponse, SetPayloadPoints, UpsertPoints,
+    MovePoints, MovePointsResponse, PointsOperationResponse, RecommendBatchPoints,
+    RecommendBatchResponse, RecommendPoints, RecommendResponse, ScrollPoints, ScrollResponse,
+    SearchBatchPoints, SearchBatchResponse, SearchPoints, SearchResponse, SetPayloadPoints,
+    UpsertPoints,
 };
 use storage::content_manager::toc::TableOfContent;
 use tonic::{Request, Response, Status};
 
 use crate::tonic::api::points_common::{
     clear_payload, count, create_field_index, delete, delete_field_index, delete_payload, get,
-    recommend, recommend_batch, scroll, search, search_batch, set_payload, upsert,
+    move_points, recommend, recommend_batch, scroll, search, search_batch, set_payload, upsert,
 };
 
 pub struct PointsService {
@@ -95,8 +96,16 @@ impl Points for PointsService {
         let SearchBatchPoints {
             collection_name,
             search_points,
+            shard_selector,
         } = request.into_inner();
-        search_batch(self.toc.as_ref(), collection_name, search_points, None).await
+        search_batch(
+            self.toc.as_ref(),
+            collection_name,
+            search_points,
+            shard_selector,
+            None,
+        )
+        .await
     }
 
     async fn scroll(
@@ -130,6 +139,13 @@ impl Points for PointsService {
     ) -> Result<Response<CountResponse>, Status> {
         count(self.toc.as_ref(), request.into_inner(), None).await
     }
+
+    async fn move_points(
+        &self,
+        request: Request<MovePoints>,
+    ) -> Result<Response<MovePointsResponse>, Status> {
+        move_points(self.toc.as_ref(), request.into_inner()).await
+    }
 }
 
 #[cfg(test)]
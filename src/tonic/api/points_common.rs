@@ -5,21 +5,24 @@ use api::grpc::qdrant::payload_index_params::IndexParams;
 use api::grpc::qdrant::{
     BatchResult, ClearPayloadPoints, CountPoints, CountResponse, CreateFieldIndexCollection,
     DeleteFieldIndexCollection, DeletePayloadPoints, DeletePoints, FieldType, GetPoints,
-    GetResponse, PayloadIndexParams, PointsOperationResponse, RecommendBatchResponse,
-    RecommendPoints, RecommendResponse, ScrollPoints, ScrollResponse, SearchBatchResponse,
-    SearchPoints, SearchResponse, SetPayloadPoints, SyncPoints, UpsertPoints,
+    GetResponse, MovePoints, MovePointsMode, MovePointsResponse, PayloadIndexParams,
+    PointsOperationResponse, RecommendBatchResponse, RecommendPoints, RecommendResponse,
+    ScrollPoints, ScrollResponse, SearchBatchResponse, SearchPoints, SearchResponse,
+    SetPayloadPoints, ShardSelector as ShardSelectorGrpc, SyncPoints, UpsertPoints,
 };
 use collection::operations::payload_ops::DeletePayload;
 use collection::operations::point_ops::{
     PointInsertOperations, PointOperations, PointSyncOperation,
 };
+use collection::operations::shard_selector::{ShardSelector, ShardSelectorInternal};
 use collection::operations::types::{
-    default_exact_count, PointRequest, RecommendRequestBatch, ScrollRequest, SearchRequest,
-    SearchRequestBatch,
+    default_exact_count, MovePointsRequest, PointRequest, RecommendRequestBatch, ScrollRequest,
+    SearchRequest, SearchRequestBatch,
 };
 use collection::operations::CollectionUpdateOperations;
 use collection::shard::ShardId;
-use segment::data_types::vectors::NamedVector;
+use segment::data_types::sparse_vector::SparseVector;
+use segment::data_types::vectors::{NamedSparseVector, NamedVector, DEFAULT_VECTOR_NAME};
 use segment::types::{PayloadFieldSchema, PayloadSchemaParams, PayloadSchemaType};
 use storage::content_manager::conversions::error_to_status;
 use storage::content_manager::toc::TableOfContent;
@@ -27,8 +30,8 @@ use tonic::{Response, Status};
 
 use crate::common::points::{
     do_clear_payload, do_count_points, do_create_index, do_delete_index, do_delete_payload,
-    do_delete_points, do_get_points, do_scroll_points, do_search_batch_points, do_search_points,
-    do_set_payload, do_upsert_points, CreateFieldIndex,
+    do_delete_points, do_get_points, do_rebuild_index, do_scroll_points, do_search_batch_points,
+    do_search_points, do_set_payload, do_upsert_points, CreateFieldIndex,
 };
 
 pub fn points_operation_response(
@@ -41,6 +44,21 @@ pub fn points_operation_response(
     }
 }
 
+/// Shards to execute the request on.
+/// Shard id is set by another peer and takes precedence over the shards selected by the client.
+fn shard_selection_internal(
+    shard_selection: Option<ShardId>,
+    shard_selector: Option<ShardSelectorGrpc>,
+) -> Result<ShardSelectorInternal, Status> {
+    match shard_selection {
+        Some(shard_id) => Ok(ShardSelectorInternal::ShardId(shard_id)),
+        None => Ok(shard_selector
+            .map(ShardSelector::try_from)
+            .transpose()?
+            .into()),
+    }
+}
+
 pub async fn upsert(
     toc: &TableOfContent,
     upsert_points: UpsertPoints,
@@ -50,7 +68,9 @@ pub async fn upsert(
         collection_name,
         wait,
         points,
+        shard_selector,
     } = upsert_points;
+    let shard_selection = shard_selection_internal(shard_selection, shard_selector)?;
     let points = points
         .into_iter()
         .map(|point| point.try_into())
@@ -102,7 +122,7 @@ pub async fn sync(
         .update(
             &collection_name,
             collection_operation,
-            Some(shard_selection),
+            ShardSelectorInternal::ShardId(shard_selection),
             wait.unwrap_or(false),
         )
         .await
@@ -121,7 +141,9 @@ pub async fn delete(
         collection_name,
         wait,
         points,
+        shard_selector,
     } = delete_points;
+    let shard_selection = shard_selection_internal(shard_selection, shard_selector)?;
 
     let points_selector = match points {
         None => return Err(Status::invalid_argument("PointSelector is missing")),
@@ -153,7 +175,9 @@ pub async fn set_payload(
         wait,
         payload,
         points,
+        shard_selector,
     } = set_payload_points;
+    let shard_selection = shard_selection_internal(shard_selection, shard_selector)?;
 
     let operation = collection::operations::payload_ops::SetPayload {
         payload: proto_to_payloads(payload)?,
@@ -188,7 +212,9 @@ pub async fn delete_payload(
         wait,
         keys,
         points,
+        shard_selector,
     } = delete_payload_points;
+    let shard_selection = shard_selection_internal(shard_selection, shard_selector)?;
 
     let operation = DeletePayload {
         keys,
@@ -222,7 +248,9 @@ pub async fn clear_payload(
         collection_name,
         wait,
         points,
+        shard_selector,
     } = clear_payload_points;
+    let shard_selection = shard_selection_internal(shard_selection, shard_selector)?;
 
     let points_selector = match points {
         None => return Err(Status::invalid_argument("PointSelector is missing")),
@@ -255,7 +283,9 @@ pub async fn create_field_index(
         field_name,
         field_type,
         field_index_params,
+        rebuild,
     } = create_field_index_collection;
+    let shard_selection = shard_selection_internal(shard_selection, None)?;
 
     let filed_type_parsed = field_type
         .map(FieldType::from_i32)
@@ -277,12 +307,34 @@ pub async fn create_field_index(
                 ))
             }
         },
+        (
+            Some(v),
+            Some(PayloadIndexParams {
+                index_params: Some(index_params),
+            }),
+        ) => match (v, &index_params) {
+            (FieldType::Integer, IndexParams::IntegerIndexParams(_))
+            | (FieldType::Float, IndexParams::FloatIndexParams(_))
+            | (FieldType::Composite, IndexParams::CompositeIndexParams(_)) => Some(
+                PayloadFieldSchema::FieldParams(PayloadSchemaParams::try_from(index_params)?),
+            ),
+            _ => {
+                return Err(Status::invalid_argument(
+                    "field_type and field_index_params do not match",
+                ))
+            }
+        },
         (Some(v), None | Some(PayloadIndexParams { index_params: None })) => match v {
             FieldType::Keyword => Some(PayloadSchemaType::Keyword.into()),
             FieldType::Integer => Some(PayloadSchemaType::Integer.into()),
             FieldType::Float => Some(PayloadSchemaType::Float.into()),
             FieldType::Geo => Some(PayloadSchemaType::Geo.into()),
             FieldType::Text => Some(PayloadSchemaType::Text.into()),
+            FieldType::Composite => {
+                return Err(Status::invalid_argument(
+                    "composite index requires field_index_params",
+                ))
+            }
         },
         (None, Some(_)) => return Err(Status::invalid_argument("field type is missing")),
         (None, None) => None,
@@ -294,14 +346,25 @@ pub async fn create_field_index(
     };
 
     let timing = Instant::now();
-    let result = do_create_index(
-        toc,
-        &collection_name,
-        operation,
-        shard_selection,
-        wait.unwrap_or(false),
-    )
-    .await
+    let result = if rebuild.unwrap_or(false) {
+        do_rebuild_index(
+            toc,
+            &collection_name,
+            operation,
+            shard_selection,
+            wait.unwrap_or(false),
+        )
+        .await
+    } else {
+        do_create_index(
+            toc,
+            &collection_name,
+            operation,
+            shard_selection,
+            wait.unwrap_or(false),
+        )
+        .await
+    }
     .map_err(error_to_status)?;
 
     let response = points_operation_response(timing, result);
@@ -318,6 +381,7 @@ pub async fn delete_field_index(
         wait,
         field_name,
     } = delete_field_index_collection;
+    let shard_selection = shard_selection_internal(shard_selection, None)?;
 
     let timing = Instant::now();
     let result = do_delete_index(
@@ -350,12 +414,22 @@ pub async fn search(
         score_threshold,
         vector_name,
         with_vectors,
+        sparse_indices,
+        shard_selector,
+        max_payload_bytes,
     } = search_points;
+    let shard_selection = shard_selection_internal(shard_selection, shard_selector)?;
 
     let search_request = SearchRequest {
-        vector: match vector_name {
-            None => vector.into(),
-            Some(name) => NamedVector { name, vector }.into(),
+        vector: match (sparse_indices, vector_name) {
+            (Some(indices), name) => NamedSparseVector {
+                name: name.unwrap_or_else(|| DEFAULT_VECTOR_NAME.to_owned()),
+                vector: SparseVector::new(indices.data, vector)
+                    .map_err(Status::invalid_argument)?,
+            }
+            .into(),
+            (None, None) => vector.into(),
+            (None, Some(name)) => NamedVector { name, vector }.into(),
         },
         filter: filter.map(|f| f.try_into()).transpose()?,
         params: params.map(|p| p.into()),
@@ -368,6 +442,7 @@ pub async fn search(
                 .unwrap_or_default(),
         ),
         score_threshold,
+        max_payload_bytes: max_payload_bytes.map(|bytes| bytes as usize),
     };
 
     let timing = Instant::now();
@@ -390,8 +465,10 @@ pub async fn search_batch(
     toc: &TableOfContent,
     collection_name: String,
     search_points: Vec<SearchPoints>,
+    shard_selector: Option<ShardSelectorGrpc>,
     shard_selection: Option<ShardId>,
 ) -> Result<Response<SearchBatchResponse>, Status> {
+    let shard_selection = shard_selection_internal(shard_selection, shard_selector)?;
     let searches: Result<Vec<_>, Status> = search_points
         .into_iter()
         .map(|search_point| search_point.try_into())
@@ -436,7 +513,9 @@ pub async fn recommend(
         score_threshold,
         using,
         with_vectors,
+        max_payload_bytes,
     } = recommend_points;
+    let shard_selection = shard_selection_internal(shard_selection, None)?;
 
     let request = collection::operations::types::RecommendRequest {
         positive: positive
@@ -459,6 +538,7 @@ pub async fn recommend(
         ),
         score_threshold,
         using: using.map(|u| u.into()),
+        max_payload_bytes: max_payload_bytes.map(|bytes| bytes as usize),
     };
 
     let timing = Instant::now();
@@ -484,6 +564,7 @@ pub async fn recommend_batch(
     recommend_points: Vec<RecommendPoints>,
     shard_selection: Option<ShardId>,
 ) -> Result<Response<RecommendBatchResponse>, Status> {
+    let shard_selection = shard_selection_internal(shard_selection, None)?;
     let searches: Result<Vec<_>, Status> = recommend_points
         .into_iter()
         .map(|recommend_point| recommend_point.try_into())
@@ -523,7 +604,10 @@ pub async fn scroll(
         limit,
         with_payload,
         with_vectors,
+        max_payload_bytes,
+        shard_selector,
     } = scroll_points;
+    let shard_selection = shard_selection_internal(shard_selection, shard_selector)?;
 
     let scroll_request = ScrollRequest {
         offset: offset.map(|o| o.try_into()).transpose()?,
@@ -533,6 +617,7 @@ pub async fn scroll(
         with_vector: with_vectors
             .map(|selector| selector.into())
             .unwrap_or_default(),
+        max_payload_bytes: max_payload_bytes.map(|bytes| bytes as usize),
     };
 
     let timing = Instant::now();
@@ -562,7 +647,9 @@ pub async fn count(
         collection_name,
         filter,
         exact,
+        shard_selector,
     } = count_points;
+    let shard_selection = shard_selection_internal(shard_selection, shard_selector)?;
 
     let count_request = collection::operations::types::CountRequest {
         filter: filter.map(|f| f.try_into()).transpose()?,
@@ -582,6 +669,45 @@ pub async fn count(
     Ok(Response::new(response))
 }
 
+pub async fn move_points(
+    toc: &TableOfContent,
+    move_points: MovePoints,
+) -> Result<Response<MovePointsResponse>, Status> {
+    let MovePoints {
+        collection_name,
+        target_collection,
+        filter,
+        mode,
+        batch_size,
+    } = move_points;
+
+    let mode = match mode {
+        None => Default::default(),
+        Some(mode) => MovePointsMode::from_i32(mode)
+            .ok_or_else(|| Status::invalid_argument(format!("Unknown move mode: {mode}")))?
+            .into(),
+    };
+    let request = MovePointsRequest {
+        target_collection,
+        filter: filter.map(|f| f.try_into()).transpose()?,
+        mode,
+        batch_size: batch_size.map(|size| size as usize),
+    };
+
+    let timing = Instant::now();
+    let move_result = toc
+        .move_points(&collection_name, request)
+        .await
+        .map_err(error_to_status)?;
+
+    let response = MovePointsResponse {
+        result: Some(move_result.into()),
+        time: timing.elapsed().as_secs_f64(),
+    };
+
+    Ok(Response::new(response))
+}
+
 pub async fn get(
     toc: &TableOfContent,
     get_points: GetPoints,
@@ -592,7 +718,9 @@ pub async fn get(
         ids,
         with_payload,
         with_vectors,
+        max_payload_bytes,
     } = get_points;
+    let shard_selection = shard_selection_internal(shard_selection, None)?;
 
     let point_request = PointRequest {
         ids: ids
@@ -603,6 +731,7 @@ pub async fn get(
         with_vector: with_vectors
             .map(|selector| selector.into())
             .unwrap_or_default(),
+        max_payload_bytes: max_payload_bytes.map(|bytes| bytes as usize),
     };
 
     let timing = Instant::now();
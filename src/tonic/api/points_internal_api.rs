@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Instant;
 
 use api::grpc::qdrant::points_internal_server::PointsInternal;
 use api::grpc::qdrant::{
@@ -7,8 +8,10 @@ use api::grpc::qdrant::{
     DeletePayloadPointsInternal, DeletePointsInternal, GetPointsInternal, GetResponse,
     PointsOperationResponse, RecommendPointsInternal, RecommendResponse, ScrollPointsInternal,
     ScrollResponse, SearchBatchPointsInternal, SearchBatchResponse, SearchPointsInternal,
-    SearchResponse, SetPayloadPointsInternal, SyncPointsInternal, UpsertPointsInternal,
+    SearchResponse, SetPayloadPointsInternal, ShardChecksumInternal, ShardChecksumResponse,
+    SyncPointsInternal, UpsertPointsInternal,
 };
+use storage::content_manager::conversions::error_to_status;
 use storage::content_manager::toc::TableOfContent;
 use tonic::{Request, Response, Status};
 
@@ -174,6 +177,7 @@ impl PointsInternal for PointsInternalService {
             self.toc.as_ref(),
             collection_name,
             search_points,
+            None,
             Some(shard_id),
         )
         .await
@@ -259,4 +263,28 @@ mod tests {
         // For running build from IDE
         eprintln!("hello");
     }
+
+    async fn checksum(
+        &self,
+        request: Request<ShardChecksumInternal>,
+    ) -> Result<Response<ShardChecksumResponse>, Status> {
+        let ShardChecksumInternal {
+            collection_name,
+            shard_id,
+        } = request.into_inner();
+
+        let timing = Instant::now();
+        let checksum = self
+            .toc
+            .shard_checksum(&collection_name, shard_id)
+            .await
+            .map_err(error_to_status)?;
+
+        let response = ShardChecksumResponse {
+            points_count: checksum.points_count as u64,
+            checksum: checksum.checksum,
+            time: timing.elapsed().as_secs_f64(),
+        };
+        Ok(Response::new(response))
+    }
 }
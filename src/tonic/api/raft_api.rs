@@ -17,15 +17,43 @@ use crate::consensus;
 pub struct RaftService {
     message_sender: Mutex<SyncSender<consensus::Message>>,
     consensus_state: ConsensusStateRef,
+    /// Shared secret, which new peers should present to join the cluster
+    join_token: Option<String>,
 }
 
 impl RaftService {
-    pub fn new(sender: SyncSender<consensus::Message>, consensus_state: ConsensusStateRef) -> Self {
+    pub fn new(
+        sender: SyncSender<consensus::Message>,
+        consensus_state: ConsensusStateRef,
+        join_token: Option<String>,
+    ) -> Self {
         Self {
             message_sender: Mutex::new(sender),
             consensus_state,
+            join_token,
         }
     }
+
+    fn check_join_token(&self, join_token: Option<&str>) -> Result<(), Status> {
+        let expected = match &self.join_token {
+            Some(expected) => expected,
+            None => return Ok(()),
+        };
+        match join_token {
+            Some(join_token) if constant_time_eq(join_token.as_bytes(), expected.as_bytes()) => {
+                Ok(())
+            }
+            Some(_) => Err(Status::permission_denied("Invalid cluster join token")),
+            None => Err(Status::unauthenticated(
+                "Cluster join token is required to join the cluster",
+            )),
+        }
+    }
+}
+
+/// Compares secrets in time, which does not depend on the position of the first mismatch
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
 }
 
 #[async_trait]
@@ -61,6 +89,15 @@ impl Raft for RaftService {
         request: tonic::Request<AddPeerToKnownMessage>,
     ) -> Result<tonic::Response<AllPeers>, tonic::Status> {
         let peer = request.get_ref();
+        // Peer should prove it belongs to the cluster before it receives any cluster data
+        if let Err(status) = self.check_join_token(peer.join_token.as_deref()) {
+            log::warn!(
+                "Rejected peer {} joining the cluster: {}",
+                peer.id,
+                status.message()
+            );
+            return Err(status);
+        }
         let uri_string = if let Some(uri) = &peer.uri {
             uri.clone()
         } else {
@@ -116,3 +153,16 @@ impl Raft for RaftService {
         Ok(Response::new(()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::constant_time_eq;
+
+    #[test]
+    fn compare_join_tokens() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(constant_time_eq(b"", b""));
+    }
+}
@@ -91,6 +91,7 @@ pub fn init_internal(
     host: String,
     internal_grpc_port: u16,
     to_consensus: std::sync::mpsc::SyncSender<crate::consensus::Message>,
+    join_token: Option<String>,
 ) -> std::io::Result<()> {
     use ::api::grpc::qdrant::raft_server::RaftServer;
 
@@ -112,7 +113,7 @@ pub fn init_internal(
             let service = QdrantService::default();
             let collections_internal_service = CollectionsInternalService::new(toc.clone());
             let points_internal_service = PointsInternalService::new(toc.clone());
-            let raft_service = RaftService::new(to_consensus, consensus_state);
+            let raft_service = RaftService::new(to_consensus, consensus_state, join_token);
 
             log::debug!("Qdrant internal gRPC listening on {}", internal_grpc_port);
 
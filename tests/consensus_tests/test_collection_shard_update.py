@@ -98,3 +98,10 @@ def test_collection_shard_update(tmp_path: pathlib.Path):
     assert error.__contains__("Wrong input: 1 out of 3 shards failed to apply operation")
     assert error.__contains__("Wrong input: Missed vector name error: text")
 
+    # Outcome of the update in each shard, to retry the failed ones
+    shard_results = r.json()["result"]
+    assert len(shard_results) == 3
+    failed = [res for res in shard_results if "error" in res]
+    assert len(failed) == 1
+    assert all(res["status"] == "completed" for res in shard_results if "error" not in res)
+